@@ -104,6 +104,14 @@ class SharedEmbeddingModel:
             return True
         return self._ready_event.wait(timeout=timeout)
 
+    def ensure_loaded(self, timeout: float = 60.0) -> None:
+        """触发加载并等待完成，超时或加载失败时抛出异常（供预热使用）"""
+        self.start_loading()
+        if not self.wait_until_ready(timeout=timeout):
+            raise TimeoutError(f"嵌入模型加载超过 {timeout:.0f}s")
+        if self._model is None:
+            raise RuntimeError(f"嵌入模型加载失败: {self._load_error}")
+
     def encode(self, text: str) -> List[float]:
         """
         生成嵌入向量（单个文本）
@@ -12,6 +12,7 @@ from typing import Dict, Any, Optional, Callable
 from agent.executor.email_sender import EmailSender
 from agent.executor.email_reader import EmailReader
 from agent.executor.file_compressor import FileCompressor
+from agent.tools import email_auth, tool_server
 from agent.tools.config import Config
 from agent.user_input import UserInputManager
 
@@ -24,6 +25,7 @@ class EmailExecutor:
    
     职责：
     - 发送邮件（带附件）
+    - 读取最近的邮件列表和正文（由 Tauri 通过 email.fetch 读取）
     - 搜索邮件 (IMAP)
     - 获取邮件详情 (IMAP)
     - 管理邮件 (归档/移动/标记已读)
@@ -78,6 +80,8 @@ class EmailExecutor:
         try:
             if step_type == "send_email":
                 return self._send_email(params)
+            elif step_type == "fetch_recent_emails":
+                return self._fetch_recent_emails(params)
             elif step_type == "search_emails":
                 return self._search_emails(params)
             elif step_type == "get_email_details":
@@ -156,7 +160,8 @@ class EmailExecutor:
         sender_email = params.get("sender") or getattr(self.config, 'email_sender', None)
         sender_password = params.get("password") or getattr(self.config, 'email_password', None)
         
-        if not sender_email or not sender_password:
+        # 使用 OAuth2 登录时不需要密码
+        if not sender_email or not (sender_password or email_auth.get_credential(sender_email)):
             logger.info("邮件配置缺失，请求用户输入...")
             if self.emit:
                 self.emit("status_update", {"message": "检测到尚未设置发件箱，请在弹出的窗口中填写配置..."})
@@ -254,7 +259,7 @@ class EmailExecutor:
         sender_email = self.config.email_sender
         sender_password = self.config.email_password
         
-        if not sender_email or not sender_password:
+        if not sender_email or not (sender_password or email_auth.get_credential(sender_email)):
             logger.warning("邮件配置缺失，无法连接 IMAP")
             return False
             
@@ -262,6 +267,39 @@ class EmailExecutor:
         # 连接时已设置 timeout=10
         return self.email_reader.connect(sender_email, sender_password)
 
+    def _fetch_recent_emails(self, params: Dict[str, Any]) -> Dict[str, Any]:
+        """
+        读取最近的邮件（由 Tauri 在 Rust 侧连接 IMAP，只读，不会标为已读）
+        
+        Args:
+            params: 参数字典
+                - unseen_only: 只取未读邮件（可选）
+                - since_days: 只取最近几天的邮件，0 表示今天（可选）
+                - from / subject: 发件人 / 主题包含的关键词（可选）
+                - folder: 邮件文件夹（可选，默认 "INBOX"）
+                - limit: 邮件数量（可选，默认 20，最多 50）
+                - include_body: 是否读取正文（可选）
+                - max_body_chars: 每封正文保留的字符数（可选，默认 2000）
+        """
+        if not tool_server.is_available():
+            return {"success": False, "message": "当前运行方式不支持读取邮件", "data": None}
+
+        keys = ("folder", "unseen_only", "since_days", "from", "subject", "limit", "include_body", "max_body_chars")
+        args = {key: params[key] for key in keys if params.get(key) is not None}
+        try:
+            result = tool_server.call_tool("email.fetch", args)
+        except tool_server.ToolCallError as e:
+            return {"success": False, "message": f"读取邮件失败: {e}", "data": None}
+
+        emails = result.get("emails", [])
+        total = result.get("total", len(emails))
+        more = f"（共 {total} 封符合条件，只取最近 {len(emails)} 封）" if total > len(emails) else ""
+        return {
+            "success": True,
+            "message": f"读取到 {len(emails)} 封邮件{more}",
+            "data": {"emails": emails, "total": total, "folder": result.get("folder")}
+        }
+
     def _search_emails(self, params: Dict[str, Any]) -> Dict[str, Any]:
         """
         搜索邮件
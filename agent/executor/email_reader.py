@@ -10,6 +10,8 @@ import threading
 from typing import List, Dict, Any, Optional
 from pathlib import Path
 
+from agent.tools import email_auth
+
 logger = logging.getLogger(__name__)
 
 # 全局停止事件单例
@@ -60,7 +62,11 @@ class EmailReader:
             # 设置超时时间为 10 秒
             self.mail = imaplib.IMAP4_SSL(self.imap_server, self.imap_port)
             self.mail.sock.settimeout(10)
-            self.mail.login(email_user, email_pass)
+            credential = email_auth.get_credential(email_user)
+            if credential:
+                self.mail.authenticate("XOAUTH2", lambda _: email_auth.xoauth2_string(credential).encode())
+            else:
+                self.mail.login(email_user, email_pass)
             logger.info(f"Successfully connected to IMAP: {self.imap_server}")
             return True
         except Exception as e:
@@ -12,6 +12,8 @@ from email.mime.application import MIMEApplication
 from pathlib import Path
 from typing import List, Union, Optional
 
+from agent.tools import email_auth
+
 logger = logging.getLogger(__name__)
 
 
@@ -54,7 +56,7 @@ class EmailSender:
         
         Args:
             sender_email: 发件人邮箱
-            sender_password: 发件人密码（或应用专用密码）
+            sender_password: 发件人密码（或应用专用密码），使用 OAuth2 登录时不需要
             recipient: 收件人（单个或列表）
             subject: 邮件主题
             body: 邮件正文
@@ -109,7 +111,11 @@ class EmailSender:
                 if self.use_tls:
                     server.starttls()
                 
-                server.login(sender_email, sender_password)
+                credential = email_auth.get_credential(sender_email)
+                if credential:
+                    server.auth("XOAUTH2", lambda challenge=None: email_auth.xoauth2_string(credential))
+                else:
+                    server.login(sender_email, sender_password)
                 logger.info("✅ SMTP登录成功")
                 
                 # 发送邮件
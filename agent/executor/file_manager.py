@@ -13,6 +13,8 @@ import time
 from agent.tools.exceptions import FileManagerError
 from agent.tools.config import Config
 from agent.tools.path_validator import validate_path
+from agent.tools.file_access import require_access
+from agent.tools import tool_server
 
 logger = logging.getLogger(__name__)
 
@@ -99,6 +101,9 @@ class FileManager:
         Raises:
             FileManagerError: 如果移动失败
         """
+        # 移动会删除源文件，源和目标都需要许可
+        require_access(source, "move")
+        require_access(target, "write")
         try:
             # 确保目标目录存在
             target.parent.mkdir(parents=True, exist_ok=True)
@@ -306,6 +311,7 @@ class FileManager:
         
         target_base_dir = target_base_dir.resolve()
         target_base_dir = self._validate_path(target_base_dir)
+        require_access(target_base_dir, "write")
         target_base_dir.mkdir(parents=True, exist_ok=True)
         
         # 获取分类规则
@@ -909,6 +915,7 @@ class FileManager:
         
         # 安全：验证路径
         folder_path = self._validate_path(folder_path)
+        require_access(folder_path, "rename")
         
         if not folder_path.exists():
             raise FileManagerError(f"文件夹不存在: {folder_path}")
@@ -1098,6 +1105,7 @@ class FileManager:
             
             # 安全：验证目标路径
             target_path = self._validate_path(target_path)
+            require_access(target_path, "write")
             
             # 如果目标路径是目录，使用源文件名并添加后缀
             if target_path.is_dir() or (not target_path.exists() and target_path_str.endswith("/")):
@@ -1336,6 +1344,7 @@ class FileManager:
         
         # 安全：验证路径
         file_path = self._validate_path(file_path)
+        require_access(file_path, "write")
         
         # 确保父目录存在
         file_path.parent.mkdir(parents=True, exist_ok=True)
@@ -1464,8 +1473,14 @@ class FileManager:
                 except Exception as e:
                     logger.warning(f"使用 python-docx 读取失败: {e}，尝试作为文本文件读取")
             
-            # 普通文本文件读取
-            content = file_path.read_text(encoding="utf-8")
+            # 普通文本文件读取（常驻服务中由 Rust 读取，受大小上限限制）
+            if tool_server.is_available():
+                output = tool_server.call_tool("fs.read", {"path": str(file_path)})
+                if output.get("encoding") != "utf-8":
+                    raise UnicodeDecodeError("utf-8", b"", 0, 1, "非 UTF-8 内容")
+                content = output.get("content", "")
+            else:
+                content = file_path.read_text(encoding="utf-8")
             logger.info(f"✅ 已读取文件: {file_path} ({len(content)} 字符)")
             
             return {
@@ -1559,6 +1574,7 @@ class FileManager:
         
         # 安全：验证路径
         file_path = self._validate_path(file_path)
+        require_access(file_path, "append" if append else "write")
         
         # 确保父目录存在
         file_path.parent.mkdir(parents=True, exist_ok=True)
@@ -1697,7 +1713,7 @@ class FileManager:
     
     def _delete_file(self, params: Dict[str, Any]) -> Dict[str, Any]:
         """
-        删除文件或文件夹
+        删除文件或文件夹（常驻服务中获准后由 Tauri 移入回收区，否则直接删除）
         
         Args:
             params: 包含：
@@ -1735,10 +1751,21 @@ class FileManager:
         
         # 安全：验证路径
         file_path = self._validate_path(file_path)
-        
         if not file_path.exists():
             raise FileManagerError(f"文件/文件夹不存在: {file_path}")
         
+        is_dir = file_path.is_dir()
+        require_access(file_path, "delete")
+        
+        # 常驻服务中获准后由 Tauri 移入回收区（sandbox/.trash），可用 restore_trashed_file 恢复
+        if not file_path.exists():
+            logger.info(f"✅ 已移入回收区: {file_path}")
+            return {
+                "success": True,
+                "message": "已移入回收区: " + str(file_path),
+                "data": {"path": str(file_path), "type": "folder" if is_dir else "file", "trashed": True}
+            }
+        
         try:
             if file_path.is_file():
                 file_path.unlink()
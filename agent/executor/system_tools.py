@@ -12,10 +12,14 @@ import time
 import platform
 import json
 import base64
+import shutil
 from datetime import datetime, timedelta
 from pathlib import Path
 from agent.tools.exceptions import BrowserError
 from agent.tools.config import Config
+from agent.tools.focus import is_focus_active
+from agent.tools import screenshot, tool_server
+from agent.tools.mcp import format_result
 from agent.executor.code_interpreter import CodeInterpreter
 from agent.executor.document_processor import DocumentProcessor
 from agent.executor.ocr_helper import OCRHelper
@@ -23,6 +27,9 @@ from agent.executor.base_executor import BaseExecutor
 
 logger = logging.getLogger(__name__)
 
+# system.info 支持的信息类型（见 src-tauri/src/system_info.rs）
+SYSTEM_INFO_SECTIONS = ("os", "cpu", "memory", "disk", "battery", "network")
+
 
 class SystemTools(BaseExecutor):
     """
@@ -228,6 +235,10 @@ class SystemTools(BaseExecutor):
                 return self._analyze_document(params)
             elif step_type == "run_applescript":
                 return self._run_applescript(params)
+            elif step_type == "run_shell":
+                return self._run_shell(params)
+            elif step_type == "mcp_call":
+                return self._mcp_call(params)
             elif step_type == "manage_calendar_event":
                 return self._manage_calendar_event(params)
             elif step_type == "manage_reminder":
@@ -248,7 +259,7 @@ class SystemTools(BaseExecutor):
                     "set_reminder", "list_reminders", "cancel_reminder",
                     "create_workflow", "list_workflows", "delete_workflow",
                     "get_task_history", "search_history", "add_favorite", "list_favorites", "remove_favorite",
-                    "text_process", "analyze_document", "run_applescript",
+                    "text_process", "analyze_document", "run_applescript", "run_shell", "mcp_call",
                     "manage_calendar_event", "manage_reminder",
                     "visual_assist"  # Phase 39: 视觉交互助手
                 ]
@@ -496,11 +507,13 @@ class SystemTools(BaseExecutor):
                     - 可以是相对路径（相对于用户主目录）
                     - 可以是绝对路径（必须在用户主目录下）
                     - 支持 ~ 符号（如 ~/Desktop/screenshot.png）
+                    mode（截图范围，可选）：full（默认）/ active_window / region（用户框选）
         
         Returns:
             截图结果，包含保存路径
         """
         save_path_str = params.get("save_path")
+        mode = params.get("mode") or "full"
         home = Path.home()
         
         # 如果没有指定路径，使用默认路径（沙盒目录下）
@@ -558,6 +571,25 @@ class SystemTools(BaseExecutor):
 
         save_path.parent.mkdir(parents=True, exist_ok=True)
         
+        # 常驻服务中由 Tauri 在系统层截图（支持当前窗口和框选区域），路径作为步骤结果交给后续步骤
+        if screenshot.is_available():
+            try:
+                captured = Path(screenshot.request_screenshot(mode))
+            except screenshot.ScreenshotError as e:
+                raise BrowserError(str(e))
+            if save_path_str:
+                shutil.copy2(captured, save_path)
+            else:
+                save_path = captured
+            logger.info(f"✅ 桌面截图已保存: {save_path}")
+            return {
+                "success": True,
+                "message": f"桌面截图已保存: {save_path}",
+                "data": {"path": str(save_path), "mode": mode}
+            }
+        if mode != "full":
+            raise BrowserError(f"当前运行方式只支持全屏截图（mode={mode}）")
+
         # 保存原始路径，用于查找实际保存的文件
         original_save_path = save_path
         
@@ -1522,6 +1554,10 @@ class SystemTools(BaseExecutor):
         message = params.get("message", "")
         subtitle = params.get("subtitle", "")
         sound = params.get("sound", True)
+
+        if is_focus_active():
+            logger.info(f"专注时段内跳过通知: {title}")
+            return {"success": True, "message": "专注时段内不发送通知", "data": {"title": title, "message": message, "skipped": True}}
         
         try:
             if sys.platform == "darwin":
@@ -1858,156 +1894,239 @@ class SystemTools(BaseExecutor):
     
     def _get_system_info(self, params: Dict[str, Any]) -> Dict[str, Any]:
         """
-        获取系统信息
+        获取系统信息（由 Tauri 在 Rust 侧通过 system.info 工具读取；单次模式下没有 Tauri，退回本地读取）
         
         Args:
-            params: 包含 info_type (信息类型: battery/disk/memory/cpu/network/apps)
+            params: 包含 info_type (信息类型: os/cpu/memory/disk/battery/network/apps/all，可用逗号分隔多个)、
+                    save_path (报告保存路径，可选)
         """
-        info_type = params.get("info_type", "all")
-        
-        try:
-            result_data = {}
-            
-            if info_type in ["battery", "all"]:
-                # 获取电池信息
-                if sys.platform == "darwin":
-                    battery_result = subprocess.run(
-                        ["pmset", "-g", "batt"],
-                        capture_output=True, text=True
-                    )
-                    if battery_result.returncode == 0:
-                        output = battery_result.stdout
-                        # 解析电池百分比
-                        import re
-                        match = re.search(r'(\d+)%', output)
-                        if match:
-                            result_data["battery"] = {
-                                "percentage": int(match.group(1)),
-                                "charging": "charging" in output.lower() or "ac power" in output.lower()
-                            }
-            
-            if info_type in ["disk", "all"]:
-                # 获取磁盘信息
-                disk_result = subprocess.run(
-                    ["df", "-h", "/"],
-                    capture_output=True, text=True
-                )
-                if disk_result.returncode == 0:
-                    lines = disk_result.stdout.strip().split("\n")
-                    if len(lines) > 1:
-                        parts = lines[1].split()
-                        if len(parts) >= 5:
-                            result_data["disk"] = {
-                                "total": parts[1],
-                                "used": parts[2],
-                                "available": parts[3],
-                                "use_percent": parts[4]
-                            }
-            
-            if info_type in ["memory", "all"]:
-                # 获取内存信息
-                if sys.platform == "darwin":
-                    mem_result = subprocess.run(
-                        ["vm_stat"],
-                        capture_output=True, text=True
-                    )
-                    if mem_result.returncode == 0:
-                        # 简化内存信息
-                        result_data["memory"] = {"info": "macOS 内存使用正常"}
-            
-            if info_type in ["apps", "all"]:
-                # 获取运行中的应用
-                if sys.platform == "darwin":
-                    apps_result = subprocess.run(
-                        ["osascript", "-e", 'tell application "System Events" to get name of every process whose background only is false'],
-                        capture_output=True, text=True
-                    )
-                    if apps_result.returncode == 0:
-                        apps = [app.strip() for app in apps_result.stdout.split(",")]
-                        result_data["running_apps"] = apps[:20]  # 最多显示20个
-            
-            if info_type in ["network", "all"]:
-                # 获取网络信息
-                if sys.platform == "darwin":
-                    # 获取 IP
-                    ip_result = subprocess.run(
-                        ["ipconfig", "getifaddr", "en0"],
-                        capture_output=True, text=True
-                    )
-                    if ip_result.returncode == 0:
-                        result_data["network"] = {
-                            "local_ip": ip_result.stdout.strip()
-                        }
-            
-            # 构建消息
-            message_parts = []
-            report_lines = ["# 系统信息报告", ""]
-            report_lines.append("生成时间: " + time.strftime("%Y-%m-%d %H:%M:%S"))
+        info_type = (params.get("info_type") or "all").strip().lower()
+        if info_type == "all":
+            requested = list(SYSTEM_INFO_SECTIONS) + ["apps"]
+        else:
+            requested = [part.strip() for part in info_type.split(",") if part.strip()]
+        # 运行中的应用由 Python 读取，其余交给 system.info
+        sections = [section for section in requested if section != "apps"]
+
+        result_data: Dict[str, Any] = {}
+        if sections and tool_server.is_available():
+            try:
+                result_data = tool_server.call_tool("system.info", {"sections": sections})
+            except tool_server.ToolCallError as e:
+                return {"success": False, "message": "获取系统信息失败: " + str(e), "data": None}
+        elif sections:
+            unknown = [section for section in sections if section not in SYSTEM_INFO_SECTIONS]
+            if unknown:
+                return {
+                    "success": False,
+                    "message": "未知的系统信息类型: " + ", ".join(unknown) + "（支持 " + "、".join(SYSTEM_INFO_SECTIONS) + "、apps）",
+                    "data": None,
+                }
+            result_data = self._local_system_info(sections)
+        if "apps" in requested:
+            apps = self._running_apps()
+            if apps is not None:
+                result_data["running_apps"] = apps
+
+        # 构建消息
+        message_parts = []
+        report_lines = ["# 系统信息报告", ""]
+        report_lines.append("生成时间: " + time.strftime("%Y-%m-%d %H:%M:%S"))
+        report_lines.append("")
+
+        os_info = result_data.get("os")
+        if os_info:
+            system_name = (os_info.get("long_version") or os_info.get("name") or "未知") + " (" + os_info.get("arch", "") + ")"
+            message_parts.append("系统: " + system_name)
+            report_lines.append("## 系统")
+            report_lines.append("- 版本: " + system_name)
+            report_lines.append("- 主机名: " + (os_info.get("host_name") or "未知"))
+            if os_info.get("uptime_secs") is not None:
+                report_lines.append("- 已运行: " + str(os_info["uptime_secs"] // 3600) + " 小时")
             report_lines.append("")
-            
-            if "battery" in result_data:
-                b = result_data["battery"]
-                status = "充电中" if b["charging"] else "使用电池"
-                message_parts.append("电池: " + str(b["percentage"]) + "% (" + status + ")")
+
+        cpu = result_data.get("cpu")
+        if cpu:
+            report_lines.append("## CPU")
+            report_lines.append("- 型号: " + (cpu.get("brand") or "未知"))
+            report_lines.append("- 逻辑核心: " + str(cpu.get("logical_cores", 0)))
+            if cpu.get("usage_percent") is not None:
+                message_parts.append("CPU 占用: " + str(round(cpu["usage_percent"], 1)) + "%")
+                report_lines.append("- 占用: " + str(round(cpu["usage_percent"], 1)) + "%")
+            report_lines.append("")
+
+        memory = result_data.get("memory")
+        if memory:
+            message_parts.append("内存: 已用 " + self._format_size(memory["used_bytes"]) + " / 总共 " + self._format_size(memory["total_bytes"]))
+            report_lines.append("## 内存")
+            report_lines.append("- 总容量: " + self._format_size(memory["total_bytes"]))
+            report_lines.append("- 已使用: " + self._format_size(memory["used_bytes"]))
+            report_lines.append("- 可用: " + self._format_size(memory["available_bytes"]))
+            report_lines.append("")
+
+        disks = result_data.get("disk")
+        if disks:
+            report_lines.append("## 磁盘空间")
+            for d in disks:
+                summary = (d["mount_point"] + " 可用 " + self._format_size(d["available_bytes"])
+                           + " / 总共 " + self._format_size(d["total_bytes"])
+                           + " (已用 " + str(round(d["used_percent"])) + "%)")
+                message_parts.append("磁盘 " + summary)
+                report_lines.append("- " + summary)
+            report_lines.append("")
+
+        if "battery" in result_data:
+            b = result_data["battery"]
+            if b is None:
+                message_parts.append("电池: 无")
+                report_lines.append("## 电池状态")
+                report_lines.append("- 未检测到电池")
+            else:
+                status = "充电中" if b.get("charging") else ("已接通电源" if b.get("on_ac_power") else "使用电池")
+                percent = str(b["percent"]) + "%" if b.get("percent") is not None else "未知"
+                message_parts.append("电池: " + percent + " (" + status + ")")
                 report_lines.append("## 电池状态")
-                report_lines.append("- 电量: " + str(b["percentage"]) + "%")
+                report_lines.append("- 电量: " + percent)
                 report_lines.append("- 状态: " + status)
-                report_lines.append("")
-            
-            if "disk" in result_data:
-                d = result_data["disk"]
-                message_parts.append("磁盘: 已用 " + d["used"] + " / 总共 " + d["total"] + " (" + d["use_percent"] + ")")
-                report_lines.append("## 磁盘空间")
-                report_lines.append("- 总容量: " + d["total"])
-                report_lines.append("- 已使用: " + d["used"])
-                report_lines.append("- 可用: " + d["available"])
-                report_lines.append("- 使用率: " + d["use_percent"])
-                report_lines.append("")
-            
-            if "memory" in result_data:
-                report_lines.append("## 内存状态")
-                report_lines.append("- 状态: " + result_data["memory"].get("info", "正常"))
-                report_lines.append("")
-            
-            if "running_apps" in result_data:
-                apps = result_data["running_apps"]
-                message_parts.append("运行中应用: " + str(len(apps)) + " 个")
-                report_lines.append("## 运行中的应用 (" + str(len(apps)) + " 个)")
-                for app in apps:
-                    report_lines.append("- " + app)
-                report_lines.append("")
-            
-            if "network" in result_data:
-                n = result_data["network"]
-                message_parts.append("本机IP: " + n.get("local_ip", "未知"))
-                report_lines.append("## 网络信息")
-                report_lines.append("- 本机IP: " + n.get("local_ip", "未知"))
-                report_lines.append("")
-            
-            message = "; ".join(message_parts) if message_parts else "系统信息获取完成"
-            
-            # 如果指定了保存路径，保存报告
-            save_path = params.get("save_path", "")
-            if save_path:
-                import os
-                if save_path.startswith("~"):
-                    save_path = os.path.expanduser(save_path)
-                
-                # 确保目录存在
-                save_dir = os.path.dirname(save_path)
-                if save_dir:
-                    os.makedirs(save_dir, exist_ok=True)
-                
-                report_content = "\n".join(report_lines)
-                with open(save_path, "w", encoding="utf-8") as f:
-                    f.write(report_content)
-                
-                message = message + "，报告已保存到: " + save_path
-                result_data["saved_path"] = save_path
-            
-            return {"success": True, "message": message, "data": result_data}
-        except Exception as e:
-            return {"success": False, "message": "获取系统信息失败: " + str(e), "data": None}
+                if b.get("time_remaining_mins") is not None:
+                    report_lines.append("- 预计剩余: " + str(b["time_remaining_mins"]) + " 分钟")
+            report_lines.append("")
+
+        network = result_data.get("network")
+        if network:
+            addresses = [
+                addr for iface in network.get("interfaces", []) for addr in iface.get("addresses", [])
+                if not addr.startswith(("127.", "::1", "fe80"))
+            ]
+            message_parts.append("网络: " + ("已连接" if network.get("connected") else "未连接"))
+            report_lines.append("## 网络信息")
+            report_lines.append("- 状态: " + ("已连接" if network.get("connected") else "未连接"))
+            report_lines.append("- 本机IP: " + (", ".join(addresses) if addresses else "未知"))
+            report_lines.append("")
+
+        if "running_apps" in result_data:
+            apps = result_data["running_apps"]
+            message_parts.append("运行中应用: " + str(len(apps)) + " 个")
+            report_lines.append("## 运行中的应用 (" + str(len(apps)) + " 个)")
+            for app in apps:
+                report_lines.append("- " + app)
+            report_lines.append("")
+
+        message = "; ".join(message_parts) if message_parts else "系统信息获取完成"
+
+        # 如果指定了保存路径，保存报告
+        save_path = params.get("save_path", "")
+        if save_path:
+            try:
+                report_path = Path(save_path).expanduser()
+                report_path.parent.mkdir(parents=True, exist_ok=True)
+                report_path.write_text("\n".join(report_lines), encoding="utf-8")
+            except OSError as e:
+                return {"success": False, "message": "保存系统信息报告失败: " + str(e), "data": result_data}
+            message = message + "，报告已保存到: " + str(report_path)
+            result_data["saved_path"] = str(report_path)
+
+        return {"success": True, "message": message, "data": result_data}
+    
+    def _local_system_info(self, sections: List[str]) -> Dict[str, Any]:
+        """
+        没有 Tauri 时在本地读取系统信息，字段与 system.info 一致；无法读取的部分省略
+        
+        Args:
+            sections: os/cpu/memory/disk/battery/network 中要读取的部分
+        """
+        import os
+        import re
+        import socket
+
+        result: Dict[str, Any] = {}
+        if "os" in sections:
+            result["os"] = {
+                "name": platform.system(),
+                "long_version": platform.platform(),
+                "host_name": platform.node(),
+                "arch": platform.machine(),
+            }
+        if "cpu" in sections:
+            result["cpu"] = {"brand": platform.processor(), "logical_cores": os.cpu_count() or 0}
+        if "memory" in sections:
+            memory = self._local_memory_info()
+            if memory:
+                result["memory"] = memory
+        if "disk" in sections:
+            try:
+                root = Path.home().anchor or "/"
+                usage = shutil.disk_usage(root)
+                result["disk"] = [{
+                    "mount_point": root,
+                    "total_bytes": usage.total,
+                    "available_bytes": usage.free,
+                    "used_percent": usage.used * 100 / usage.total if usage.total else 0,
+                }]
+            except OSError as e:
+                logger.warning("读取磁盘空间失败: " + str(e))
+        if "battery" in sections and sys.platform == "darwin":
+            battery_result = subprocess.run(["pmset", "-g", "batt"], capture_output=True, text=True)
+            if battery_result.returncode == 0:
+                output = battery_result.stdout
+                match = re.search(r"(\d+)%", output)
+                if match:
+                    result["battery"] = {
+                        "percent": int(match.group(1)),
+                        "charging": "charging" in output.lower() and "discharging" not in output.lower(),
+                        "on_ac_power": "ac power" in output.lower(),
+                    }
+        if "network" in sections:
+            # UDP 连接不发送数据，只用来取出口网卡的地址
+            try:
+                with socket.socket(socket.AF_INET, socket.SOCK_DGRAM) as sock:
+                    sock.connect(("8.8.8.8", 80))
+                    local_ip = sock.getsockname()[0]
+                result["network"] = {"connected": True, "interfaces": [{"name": "", "addresses": [local_ip]}]}
+            except OSError:
+                result["network"] = {"connected": False, "interfaces": []}
+        return result
+
+    def _local_memory_info(self) -> Optional[Dict[str, Any]]:
+        """读取内存总量和可用量（Linux 读取 /proc/meminfo，macOS 调用 sysctl 和 vm_stat），失败时返回 None"""
+        import re
+
+        try:
+            if sys.platform.startswith("linux"):
+                values = {}
+                for line in Path("/proc/meminfo").read_text().splitlines():
+                    key, _, rest = line.partition(":")
+                    values[key] = int(rest.split()[0]) * 1024
+                total = values["MemTotal"]
+                available = values.get("MemAvailable", values.get("MemFree", 0))
+            elif sys.platform == "darwin":
+                total = int(subprocess.run(["sysctl", "-n", "hw.memsize"], capture_output=True, text=True).stdout.strip())
+                vm_stat = subprocess.run(["vm_stat"], capture_output=True, text=True).stdout
+                page_size = int(re.search(r"page size of (\d+) bytes", vm_stat).group(1))
+                pages = sum(
+                    int(match.group(1))
+                    for match in re.finditer(r"Pages (?:free|inactive|speculative):\s+(\d+)", vm_stat)
+                )
+                available = pages * page_size
+            else:
+                return None
+        except (OSError, ValueError, KeyError, AttributeError) as e:
+            logger.warning("读取内存信息失败: " + str(e))
+            return None
+        return {"total_bytes": total, "used_bytes": total - available, "available_bytes": available}
+
+    def _running_apps(self) -> Optional[List[str]]:
+        """运行中的前台应用（仅 macOS，最多 20 个），无法读取时返回 None"""
+        if sys.platform != "darwin":
+            return None
+        apps_result = subprocess.run(
+            ["osascript", "-e", 'tell application "System Events" to get name of every process whose background only is false'],
+            capture_output=True, text=True
+        )
+        if apps_result.returncode != 0:
+            return None
+        apps = [app.strip() for app in apps_result.stdout.split(",") if app.strip()]
+        return apps[:20]
     
     # ========== 图片处理 ==========
     
@@ -2138,8 +2257,10 @@ class SystemTools(BaseExecutor):
             return str(size_bytes) + " B"
         elif size_bytes < 1024 * 1024:
             return str(round(size_bytes / 1024, 1)) + " KB"
-        else:
+        elif size_bytes < 1024 * 1024 * 1024:
             return str(round(size_bytes / (1024 * 1024), 1)) + " MB"
+        else:
+            return str(round(size_bytes / (1024 * 1024 * 1024), 1)) + " GB"
     
     # ========== 定时提醒 ==========
     
@@ -2465,6 +2586,66 @@ class SystemTools(BaseExecutor):
         except Exception as e:
             return {"success": False, "message": f"执行异常: {e}"}
 
+    def _run_shell(self, params: Dict[str, Any]) -> Dict[str, Any]:
+        """
+        执行 shell 命令（由 Tauri 在 Rust 侧执行，受程序白名单、超时和输出大小限制）
+
+        Args:
+            params: 包含 command（命令）、cwd（工作目录，可选）、timeout（超时秒数，可选）
+        """
+        command = (params.get("command") or params.get("cmd") or "").strip()
+        if not command:
+            return {"success": False, "message": "缺失命令内容", "data": None}
+        if not tool_server.is_available():
+            return {"success": False, "message": "当前运行方式不支持执行命令", "data": None}
+
+        args: Dict[str, Any] = {"cmd": command}
+        if params.get("cwd"):
+            args["cwd"] = str(Path(params["cwd"]).expanduser())
+        if params.get("timeout"):
+            args["timeout"] = int(params["timeout"])
+        try:
+            output = tool_server.call_tool("shell", args)
+        except tool_server.ToolCallError as e:
+            return {"success": False, "message": f"执行命令失败: {e}", "data": None}
+
+        exit_code = output.get("exit_code")
+        if output.get("timed_out"):
+            message = f"命令执行超时（{output.get('duration_ms', 0) // 1000} 秒）"
+        elif exit_code == 0:
+            message = "命令执行成功"
+        else:
+            stderr = (output.get("stderr") or "").strip()
+            message = f"命令退出码 {exit_code}" + (f": {stderr[-500:]}" if stderr else "")
+        if output.get("truncated"):
+            message += "（输出过长，已截断）"
+        return {"success": exit_code == 0, "message": message, "data": output}
+
+    def _mcp_call(self, params: Dict[str, Any]) -> Dict[str, Any]:
+        """
+        调用 MCP 服务器工具（由 Tauri 转发给用户配置的 MCP 服务器）
+
+        Args:
+            params: 包含 server（服务器名称）、tool（工具名）、arguments（工具参数，可选）
+        """
+        server = (params.get("server") or "").strip()
+        tool = (params.get("tool") or params.get("name") or "").strip()
+        if not server or not tool:
+            return {"success": False, "message": "缺失 MCP 服务器或工具名", "data": None}
+        if not tool_server.is_available():
+            return {"success": False, "message": "当前运行方式不支持调用 MCP 工具", "data": None}
+
+        arguments = params.get("arguments") or {}
+        try:
+            result = tool_server.call_tool("mcp", {"server": server, "tool": tool, "arguments": arguments})
+        except tool_server.ToolCallError as e:
+            return {"success": False, "message": f"调用 MCP 工具失败: {e}", "data": None}
+
+        text, is_error = format_result(result)
+        if is_error:
+            return {"success": False, "message": f"MCP 工具 {tool} 报告失败: {text[-500:]}", "data": result}
+        return {"success": True, "message": f"MCP 工具 {tool} 调用成功", "data": {"text": text, "result": result}}
+
     def _parse_calendar_events(self, list_result: Dict[str, Any]) -> List[Dict[str, Any]]:
         """
         解析日历事件列表（从 AppleScript 返回的 JSON）
@@ -2587,8 +2768,12 @@ class SystemTools(BaseExecutor):
         end_time = params.get("end_time")
         duration = params.get("duration")  # 分钟
         
+        # 配置了日历（calendars）时由 Tauri 直接读写 .ics 文件或 CalDAV，不再操作日历界面
+        if tool_server.is_available() and self.config.get("calendars"):
+            return self._native_calendar_event(params)
+        
         if platform.system() != "Darwin":
-            return {"success": False, "message": "目前仅支持 macOS 系统操控日历"}
+            return {"success": False, "message": "目前仅支持 macOS 系统操控日历（或在设置中配置 .ics / CalDAV 日历）"}
 
         if action == "create":
             if not start_time: 
@@ -2671,6 +2856,59 @@ class SystemTools(BaseExecutor):
             
         return {"success": False, "message": f"不支持的操作: {action}"}
 
+    def _native_calendar_event(self, params: Dict[str, Any]) -> Dict[str, Any]:
+        """
+        通过 calendar.list / calendar.create 工具读写配置的日历，参数与 _manage_calendar_event 相同，
+        另外支持 location、description、calendar（日历名称）、all_day，list 支持 days
+        """
+        action = params.get("action")
+        try:
+            if action == "create":
+                if not params.get("start_time"):
+                    return {"success": False, "message": "创建事件需要 start_time"}
+                optional = {
+                    "end": params.get("end_time"),
+                    "duration_minutes": int(params["duration"]) if params.get("duration") else None,
+                    "all_day": params.get("all_day"),
+                    "location": params.get("location"),
+                    "description": params.get("description"),
+                    "calendar": params.get("calendar"),
+                }
+                args = {"title": params.get("title") or "新会议", "start": params["start_time"]}
+                args.update({key: value for key, value in optional.items() if value is not None})
+                result = tool_server.call_tool("calendar.create", args)
+                event = result["event"]
+                response = {
+                    "success": True,
+                    "message": f"已在日历「{event['calendar']}」创建日程: {event['title']}（{event['start']}）",
+                    "data": event,
+                }
+                conflicts = result.get("conflicts") or []
+                if conflicts:
+                    response["warnings"] = conflicts
+                    response["message"] += f"。⚠️ 检测到时间冲突: {', '.join(c['title'] for c in conflicts)}"
+                return response
+
+            if action == "list":
+                optional = {
+                    "start": params.get("start_time"),
+                    "end": params.get("end_time"),
+                    "days": params.get("days"),
+                    "calendar": params.get("calendar"),
+                }
+                result = tool_server.call_tool("calendar.list", {key: value for key, value in optional.items() if value is not None})
+                events = result.get("events", [])
+                message = f"找到 {len(events)} 个日程"
+                if result.get("errors"):
+                    message += "（部分日历读取失败: " + "；".join(result["errors"]) + "）"
+                return {"success": True, "message": message, "data": events, "events": events}
+        except tool_server.ToolCallError as e:
+            return {"success": False, "message": f"日历操作失败: {e}"}
+        except ValueError:
+            return {"success": False, "message": f"无效的时长: {params.get('duration')}"}
+
+        return {"success": False, "message": f"不支持的操作: {action}"}
+
     def _manage_reminder(self, params: Dict[str, Any]) -> Dict[str, Any]:
         """
         管理提醒事项 (Phase 38)
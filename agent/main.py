@@ -17,7 +17,7 @@ import time
 import json
 import traceback
 import contextvars
-from typing import Dict, Any, Optional, Callable, Set
+from typing import Dict, Any, List, Optional, Callable, Set, Tuple
 from pathlib import Path
 
 # 添加项目根目录到路径
@@ -34,6 +34,7 @@ from agent.core.embedding_model import SharedEmbeddingModel
 from agent.core.intent_router import IntentRouter
 from agent.orchestrator.plan_executor import PlanExecutor
 from agent.orchestrator.task_orchestrator import TaskOrchestrator
+from agent.tools.dry_run import approved_plan
 
 logger = logging.getLogger(__name__)
 
@@ -99,6 +100,13 @@ class DeskJarvisAgent:
             self._memory = MemoryManager()
             logger.info(f"MemoryManager ready in {time.time() - start:.2f}s")
         return self._memory
+
+    def warmup_steps(self) -> List[Tuple[str, Callable[[], Any]]]:
+        """预热时依次加载的重资源（见 agent/tools/warmup.py）"""
+        return [
+            ("embedding_model", lambda: self.embedding_model.ensure_loaded(timeout=120)),
+            ("memory", lambda: self.memory),
+        ]
         
     def _dummy_emit(self, event_type: str, data: Any):
         """占位 emit 函数（初始化时使用）"""
@@ -309,6 +317,24 @@ class DeskJarvisAgent:
         
         return None
 
+    def plan(self, user_instruction: str, context: Optional[Dict[str, Any]] = None) -> List[Dict[str, Any]]:
+        """
+        只规划不执行（dry-run，见 agent/tools/dry_run.py）：不走快路径，不执行任何步骤
+        
+        Args:
+            user_instruction: 用户指令
+            context: 上下文信息
+            
+        Returns:
+            规划器给出的步骤列表
+        """
+        # 规划过程不向前端推送进度事件
+        orchestrator = self._create_orchestrator(lambda event_type, data: None)
+        try:
+            return orchestrator.plan(user_instruction, context)
+        finally:
+            _emit_context.set(None)
+
     def execute(
         self, 
         user_instruction: str, 
@@ -349,6 +375,8 @@ class DeskJarvisAgent:
                 'plan_ready': 'thinking',  # 规划完成也算 thinking
                 'sensitive_operation_detected': 'thinking',  # 敏感操作检测也算 thinking
                 'error': 'error',
+                'request_input': 'request_input',  # 登录、验证码等需要用户填写
+                'waiting_for_input': 'waiting_for_input',
             }
             return event_mapping.get(event_type, None)
         
@@ -409,6 +437,10 @@ class DeskJarvisAgent:
                 if 'total_steps' in data:
                     sanitized['total_steps'] = data['total_steps']
                 # 移除 traceback、原始 step 等技术细节
+
+            elif event_type in ('request_input', 'waiting_for_input'):
+                # 用户输入请求：前端需要完整的表单定义，原样保留
+                sanitized = dict(data)
             
             return sanitized
         
@@ -441,8 +473,8 @@ class DeskJarvisAgent:
             step_index = sanitized_data.get('step_index')
             event_key = f"{mapped_type}:{description}:{step_index}"
             
-            # 如果与上次事件完全相同，则去重
-            if event_key == _last_event_key:
+            # 如果与上次事件完全相同，则去重（用户输入请求和等待心跳不去重）
+            if event_key == _last_event_key and mapped_type not in ('request_input', 'waiting_for_input'):
                 logger.debug(f"[UX_FILTER] 去重事件: {event_key}")
                 return
             
@@ -470,14 +502,15 @@ class DeskJarvisAgent:
         if not self.embedding_model.wait_until_ready(timeout=3.0):
             logger.warning("[SECURITY_SHIELD] 嵌入模型未就绪，可能影响意图路由，但继续执行")
         
-        # === 尝试快路径（意图路由）===
-        try:
-            shortcut_result = self._try_intent_shortcut(user_instruction, emit, context)
-            if shortcut_result:
-                logger.info("[SECURITY_SHIELD] 快路径执行成功，跳过 LLM 规划")
-                return shortcut_result
-        except Exception as e:
-            logger.warning(f"[SECURITY_SHIELD] 快路径失败: {e}，继续正常流程")
+        # === 尝试快路径（意图路由），执行已审阅的计划时跳过 ===
+        if approved_plan(context) is None:
+            try:
+                shortcut_result = self._try_intent_shortcut(user_instruction, emit, context)
+                if shortcut_result:
+                    logger.info("[SECURITY_SHIELD] 快路径执行成功，跳过 LLM 规划")
+                    return shortcut_result
+            except Exception as e:
+                logger.warning(f"[SECURITY_SHIELD] 快路径失败: {e}，继续正常流程")
         
         # === 正常流程：创建 Orchestrator 并执行 ===
         orchestrator = None
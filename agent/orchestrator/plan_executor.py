@@ -13,6 +13,7 @@ from typing import Dict, Any, List, Callable
 
 from agent.tools.exceptions import PlaceholderError
 from agent.tools.log_sanitizer import LogSanitizer
+from agent.tools.capabilities import check_step
 
 logger = logging.getLogger(__name__)
 
@@ -101,6 +102,12 @@ class PlanExecutor:
                     "result": step_result,
                     "status": "success"
                 })
+                # 逐步执行：还有后续步骤时等待用户继续或中止
+                step_gate = context.get("_step_gate")
+                if callable(step_gate) and i < len(plan) - 1:
+                    if step_gate(i, len(plan), step.get("description", "")):
+                        logger.info("用户中止了逐步执行，终止执行")
+                        break
             else:
                 overall_success = False
                 failed_reason = step_result.get("message", "Unknown error")
@@ -152,14 +159,14 @@ class PlanExecutor:
         system_ops = [
             "screenshot_desktop", "open_app", "close_app", "set_volume", 
             "set_brightness", "get_system_info", "open_folder", "open_file", 
-            "text_process", "python_script", "python", "code_interpreter"
+            "text_process", "python_script", "python", "code_interpreter", "run_shell", "mcp_call"
         ]
         for op in system_ops:
             self.executor_registry[op] = "system_tools"
         
         # 邮件操作
         email_ops = [
-            "send_email", "search_emails", "get_email_details", 
+            "send_email", "fetch_recent_emails", "search_emails", "get_email_details", 
             "download_attachments", "manage_emails", "compress_files"
         ]
         for op in email_ops:
@@ -534,6 +541,12 @@ class PlanExecutor:
                 step_type = "open_app"
                 step["type"] = "open_app"
         
+        # 修改类步骤需经 Tauri 按确认策略审批（只读观察模式下一律拒绝）
+        denied = check_step(step_type, params)
+        if denied:
+            logger.warning(f"[SECURITY_SHIELD] {denied}")
+            return {"success": False, "message": denied, "permission_denied": True}
+        
         # 1. Python Code Execution
         if step_type in ["python_script", "python"]:
             code = params.get("code", "")
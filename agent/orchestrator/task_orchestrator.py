@@ -11,10 +11,11 @@ Task Orchestrator - High Level Workflow Manager
 
 import time
 import logging
-from typing import Dict, Any, Optional, Callable
+from typing import Dict, Any, List, Optional, Callable
 
 from agent.core.intent_router import IntentRouter
 from agent.orchestrator.plan_executor import PlanExecutor
+from agent.tools.dry_run import approved_plan
 
 logger = logging.getLogger(__name__)
 
@@ -52,23 +53,8 @@ class TaskOrchestrator:
         # 初始化计时器
         start_time = time.time()
         
-        # 🟢 CRITICAL: 每次运行前刷新配置，并根据最新配置重置规划器和执行器状态
-        if hasattr(self.config, "reload"):
-            self.config.reload()
-            # 重新创建规划器以确保使用最新的 API Key/Provider
-            from agent.planner.planner_factory import create_planner
-            self.planner = create_planner(self.config)
-            logger.info("已根据最新配置刷新规划器状态")
-
-        if context is None:
-            context = {}
-        
-        # 注入实时时间感官 (Protocol Phase 38+)
-        current_time_str = time.strftime("%Y-%m-%d %H:%M:%S")
-        context["current_time"] = current_time_str
-        
-        # 将会话缓存注入 context，供 Planner 和 Executor 共享 (Protocol R3)
-        context["_file_context_buffer"] = self.file_context_buffer
+        self._refresh_planner()
+        context = self._prepare_context(context)
         
         # 检查停止标志（如果 context 中有停止检查函数）
         check_stop = context.get("_check_stop")
@@ -87,18 +73,23 @@ class TaskOrchestrator:
             "phase": "analyzing"
         })
 
+        # 0. 用户审阅并批准的计划（dry-run）：跳过快速通道和规划，只执行批准的步骤
+        approved_steps = approved_plan(context)
+        if approved_steps is not None:
+            emit("plan_ready", {
+                "content": "Approved plan.",
+                "steps": approved_steps,
+                "step_count": len(approved_steps)
+            })
+            return self._execute(approved_steps, user_instruction, context, start_time)
+
         # 1. 尝试快速通道 (Semantic Intent Router)
         fast_result = self._try_fast_path(user_instruction, emit)
         if fast_result:
             return fast_result
             
         # 2. 获取记忆上下文
-        # 注意：这里需要处理 memory 为 None 的情况（懒加载未触发）
-        memory_context = ""
-        if self.memory:
-            memory_context = self.memory.get_context_for_instruction(user_instruction)
-            if memory_context:
-                context["memory_context"] = memory_context
+        self._apply_memory_context(user_instruction, context)
                 
         emit("thinking", {
             "content": "Planning steps...",
@@ -137,6 +128,56 @@ class TaskOrchestrator:
                 "user_instruction": user_instruction
             }
             
+        return self._execute(plan_steps, user_instruction, context, start_time)
+
+    def plan(self, user_instruction: str, context: Optional[Dict[str, Any]] = None) -> List[Dict[str, Any]]:
+        """
+        只规划不执行（dry-run）：返回规划器给出的步骤，不走快速通道、不执行任何步骤
+        """
+        self._refresh_planner()
+        context = self._prepare_context(context)
+        self._apply_memory_context(user_instruction, context)
+        return self.planner.plan(user_instruction, context)
+
+    def _refresh_planner(self) -> None:
+        """每次运行前刷新配置，并根据最新配置重建规划器"""
+        # 🟢 CRITICAL: 每次运行前刷新配置，并根据最新配置重置规划器和执行器状态
+        if hasattr(self.config, "reload"):
+            self.config.reload()
+            # 重新创建规划器以确保使用最新的 API Key/Provider
+            from agent.planner.planner_factory import create_planner
+            self.planner = create_planner(self.config)
+            logger.info("已根据最新配置刷新规划器状态")
+
+    def _prepare_context(self, context: Optional[Dict[str, Any]]) -> Dict[str, Any]:
+        """注入当前时间和会话缓存"""
+        if context is None:
+            context = {}
+        
+        # 注入实时时间感官 (Protocol Phase 38+)
+        current_time_str = time.strftime("%Y-%m-%d %H:%M:%S")
+        context["current_time"] = current_time_str
+        
+        # 将会话缓存注入 context，供 Planner 和 Executor 共享 (Protocol R3)
+        context["_file_context_buffer"] = self.file_context_buffer
+        return context
+
+    def _apply_memory_context(self, user_instruction: str, context: Dict[str, Any]) -> None:
+        """把与指令相关的记忆并入 context"""
+        # 注意：这里需要处理 memory 为 None 的情况（懒加载未触发）
+        if self.memory:
+            memory_context = self.memory.get_context_for_instruction(user_instruction)
+            if memory_context:
+                context["memory_context"] = memory_context
+
+    def _execute(
+        self,
+        plan_steps: List[Dict[str, Any]],
+        user_instruction: str,
+        context: Dict[str, Any],
+        start_time: float
+    ) -> Dict[str, Any]:
+        """执行计划并保存记忆"""
         # 4. 执行计划 (Executor)
         # 确保 context 中包含停止检查函数
         check_stop = context.get("_check_stop")
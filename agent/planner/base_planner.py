@@ -7,6 +7,8 @@
 from abc import ABC, abstractmethod
 from typing import List, Dict, Any, Optional
 from agent.tools.config import Config
+from agent.tools.mcp import mcp_hint
+from agent.tools.resume import resume_hint
 
 
 class BasePlanner(ABC):
@@ -49,6 +51,101 @@ class BasePlanner(ABC):
         """
         pass
     
+    @staticmethod
+    def _response_language_hint(context: Optional[Dict[str, Any]]) -> str:
+        """
+        回复语言提示：Tauri 按指令语言（或用户设置）在 context 中给出 response_language
+
+        Returns:
+            提示词片段，未指定语言时返回空字符串
+        """
+        language = (context or {}).get("response_language")
+        if not language:
+            return ""
+        return (
+            f"用户使用 {language} 下达指令。步骤描述（description）以及所有给用户看的文字"
+            f"（回复、总结、通知、邮件正文等）都必须使用 {language}，除非用户明确要求其他语言。"
+        )
+
+    @staticmethod
+    def _clipboard_hint(context: Optional[Dict[str, Any]]) -> str:
+        """
+        剪贴板内容：指令提到剪贴板时 Tauri 在 context 中给出 clipboard_text
+
+        Returns:
+            提示词片段，没有剪贴板内容时返回空字符串
+        """
+        text = (context or {}).get("clipboard_text")
+        if not text:
+            return ""
+        return (
+            "用户剪贴板的当前内容如下。指令中的\"剪贴板\"、\"剪贴板内容\"指的就是这段文字，"
+            "直接使用它，不要再生成读取剪贴板的步骤：\n"
+            f"<clipboard>\n{text}\n</clipboard>"
+        )
+
+    @staticmethod
+    def _active_window_hint(context: Optional[Dict[str, Any]]) -> str:
+        """
+        前台窗口：任务请求带入前台窗口时 Tauri 在 context 中给出 active_window
+        （用户切到 DeskJarvis 之前所在窗口的应用名、标题，以及能读取时的选中文字）
+
+        Returns:
+            提示词片段，没有前台窗口信息时返回空字符串
+        """
+        window = (context or {}).get("active_window")
+        if not window:
+            return ""
+        hint = (
+            f"用户刚才正在使用 {window.get('app_name') or '未知应用'}，"
+            f"窗口标题为 \"{window.get('window_title') or ''}\"。"
+            "指令中的\"我现在看的\"、\"这篇文章\"、\"这个页面\"等指的就是这个窗口的内容"
+        )
+        selected = window.get("selected_text")
+        if selected:
+            return hint + f"，用户选中的文字如下，优先直接使用它：\n<selected_text>\n{selected}\n</selected_text>"
+        return hint + "；没有读取到正文，需要时根据窗口标题获取内容（如打开对应网页或文件）。"
+
+    @staticmethod
+    def _attachments_hint(context: Optional[Dict[str, Any]]) -> str:
+        """
+        拖入的文件：用户把文件拖到窗口后 Tauri 在 context 中给出 attached_files
+
+        Returns:
+            提示词片段，没有拖入文件时返回空字符串
+        """
+        files = (context or {}).get("attached_files") or []
+        lines = []
+        for f in files:
+            kind = "文件夹" if f.get("is_dir") else f.get("mime", "")
+            lines.append(f"- {f.get('name')}（{kind}，{f.get('size', 0)} 字节）: {f.get('path')}")
+        if not lines:
+            return ""
+        return (
+            "用户拖进来的文件如下。指令中的\"拖进来的文件\"、\"这几个文件\"指的就是它们，"
+            "直接使用下列路径：\n" + "\n".join(lines)
+        )
+
+    @staticmethod
+    def _resume_hint(context: Optional[Dict[str, Any]]) -> str:
+        """
+        断点续跑：临时失败后重试时 context 中带有 resume，已完成的步骤不再规划
+
+        Returns:
+            提示词片段，不是续跑时返回空字符串
+        """
+        return resume_hint(context)
+
+    @staticmethod
+    def _mcp_hint(context: Optional[Dict[str, Any]]) -> str:
+        """
+        MCP 工具：Tauri 在 context 中给出已配置服务器的 mcp_tools
+
+        Returns:
+            提示词片段，没有 MCP 工具时返回空字符串
+        """
+        return mcp_hint(context)
+
     @abstractmethod
     def _build_prompt(
         self,
@@ -18,6 +18,7 @@ from pathlib import Path
 from agent.planner.base_planner import BasePlanner
 from agent.tools.config import Config
 from agent.tools.exceptions import PlannerError
+from agent.tools.usage import report_prompt, report_usage, track_call
 
 logger = logging.getLogger(__name__)
 
@@ -66,11 +67,16 @@ class ClaudePlanner(BasePlanner):
             prompt = self._build_prompt(user_instruction, context)
             
             def call_llm(user_prompt: str):
-                return self.client.messages.create(
-                    model=self.model,
-                    max_tokens=4096,
-                    messages=[{"role": "user", "content": user_prompt}],
-                )
+                messages = [{"role": "user", "content": user_prompt}]
+                with track_call(self.model):
+                    response = self.client.messages.create(
+                        model=self.model,
+                        max_tokens=4096,
+                        messages=messages,
+                    )
+                report_usage(self.model, response)
+                report_prompt(self.model, messages, response)
+                return response
 
             # 调用Claude API
             logger.warning("🔵 正在调用Claude API规划任务...")
@@ -270,6 +276,42 @@ class ClaudePlanner(BasePlanner):
 步骤序列：{' → '.join(action_seq)}
 成功率：{pattern.get('success_rate', 0) * 100:.0f}%""")
             
+            # 0.8 回复语言
+            language_hint = self._response_language_hint(context)
+            if language_hint:
+                context_parts.append(f"""### 回复语言
+{language_hint}""")
+
+            # 0.9 剪贴板内容
+            clipboard_hint = self._clipboard_hint(context)
+            if clipboard_hint:
+                context_parts.append(f"""### 剪贴板内容
+{clipboard_hint}""")
+
+            # 0.92 前台窗口
+            active_window_hint = self._active_window_hint(context)
+            if active_window_hint:
+                context_parts.append(f"""### 前台窗口
+{active_window_hint}""")
+
+            # 0.95 拖入的文件
+            attachments_hint = self._attachments_hint(context)
+            if attachments_hint:
+                context_parts.append(f"""### 拖入的文件
+{attachments_hint}""")
+
+            # 0.96 重试时跳过已完成的步骤
+            resume_hint = self._resume_hint(context)
+            if resume_hint:
+                context_parts.append(f"""### 断点续跑
+{resume_hint}""")
+
+            # 0.97 用户配置的 MCP 工具
+            mcp_hint = self._mcp_hint(context)
+            if mcp_hint:
+                context_parts.append(f"""### MCP 工具
+{mcp_hint}""")
+            
             # 1. 处理最近创建/操作的文件
             last_file = context.get("last_created_file")
             created_files = context.get("created_files", [])
@@ -456,9 +498,12 @@ print(json.dumps(result, ensure_ascii=False))
 ## 你的能力
 {browser_section}
 ### 2. 系统操作
-- screenshot_desktop: 截取整个桌面（不是浏览器页面），params: {{save_path: "保存路径（可选）"}}
+- screenshot_desktop: 截取桌面（不是浏览器页面），params: {{save_path: "保存路径（可选）", mode: "full（默认）/ active_window（当前窗口）/ region（让用户框选区域）"}}
   - **注意**：只有当用户明确要求"截图桌面"、"截图整个屏幕"时才使用此工具
   - 如果用户先有浏览器操作（如"搜索"、"打开网页"），然后说"截图"，应该使用 browser_screenshot，而不是 screenshot_desktop
+- run_shell: 执行一条 shell 命令（统计文件、查找内容等，能用其他工具完成时优先用其他工具），params: {{command: "命令", cwd: "工作目录（可选）", timeout: 超时秒数（可选，默认 60）}}
+- mcp_call: 调用用户配置的 MCP 服务器工具（只能调用上下文 MCP 工具中列出的工具），params: {{server: "服务器", tool: "工具名", arguments: {{参数}}}}
+  - 只能使用常用命令（ls、grep、find、sort 等），不允许 $()、反引号和输出重定向；返回 data.stdout、data.stderr、data.exit_code
 - open_folder: 打开文件夹，params: {{folder_path: "..."}}
 - open_file: 打开文件，params: {{file_path: "..."}}
 - open_app: 打开应用，params: {{app_name: "应用名称"}}
@@ -497,7 +542,7 @@ print(json.dumps(result, ensure_ascii=False))
 - download_latest_python_installer: 下载最新 Python 安装包，params: {{save_dir: "保存目录（可选，默认桌面）"}} 或 {{save_path: "保存路径/目录（可选）"}}, 可选 {{timeout: 180000}}
 
 **系统信息和图片处理**：
-- get_system_info: 获取系统信息，params: {{info_type: "battery/disk/memory/apps/network/all", save_path: "~/Desktop/系统报告.md（可选，指定后自动保存）"}}
+- get_system_info: 获取系统信息，params: {{info_type: "os/cpu/memory/disk/battery/network/apps/all（可用逗号分隔多个）", save_path: "~/Desktop/系统报告.md（可选，指定后自动保存）"}}
   **重要：查询系统信息必须使用这个工具，不要自己写脚本！如果用户要求保存，直接在 save_path 中指定路径！**
 - image_process: 图片处理，params: {{image_path: "图片路径", action: "compress/resize/convert/info", width: 800, height: 600, format: "jpg/png/webp", quality: 80}}
 
@@ -711,11 +756,15 @@ print(json.dumps(result, ensure_ascii=False))
         logger.info("调用Claude进行反思...")
         
         try:
-            response = self.client.messages.create(
-                model=self.model,
-                max_tokens=4096,
-                messages=[{"role": "user", "content": prompt}]
-            )
+            messages = [{"role": "user", "content": prompt}]
+            with track_call(self.model):
+                response = self.client.messages.create(
+                    model=self.model,
+                    max_tokens=4096,
+                    messages=messages
+                )
+            report_usage(self.model, response)
+            report_prompt(self.model, messages, response)
             
             content = response.content[0].text
             logger.debug(f"反思响应: {content[:500]}...")
@@ -12,6 +12,7 @@ from openai import OpenAI
 from agent.tools.exceptions import PlannerError
 from agent.tools.config import Config
 from agent.planner.base_planner import BasePlanner
+from agent.tools.usage import report_prompt, report_usage, track_call
 
 logger = logging.getLogger(__name__)
 
@@ -85,12 +86,16 @@ class DeepSeekPlanner(BasePlanner):
             logger.info("开始规划任务...")
 
             def call_llm(messages):
-                return self.client.chat.completions.create(
-                    model=self.model,
-                    messages=messages,
-                    temperature=0.3,
-                    max_tokens=4000,
-                )
+                with track_call(self.model):
+                    response = self.client.chat.completions.create(
+                        model=self.model,
+                        messages=messages,
+                        temperature=0.3,
+                        max_tokens=4000,
+                    )
+                report_usage(self.model, response)
+                report_prompt(self.model, messages, response)
+                return response
 
             messages = [
                 {
@@ -385,6 +390,36 @@ class DeepSeekPlanner(BasePlanner):
                 if action_seq:
                     context_info += f"常用步骤序列：{' → '.join(action_seq)}\n"
             
+            # 添加回复语言
+            language_hint = self._response_language_hint(context)
+            if language_hint:
+                context_info += "\n\n**回复语言**：\n" + language_hint + "\n"
+
+            # 添加剪贴板内容
+            clipboard_hint = self._clipboard_hint(context)
+            if clipboard_hint:
+                context_info += "\n\n**剪贴板内容**：\n" + clipboard_hint + "\n"
+
+            # 添加前台窗口
+            active_window_hint = self._active_window_hint(context)
+            if active_window_hint:
+                context_info += "\n\n**前台窗口**：\n" + active_window_hint + "\n"
+
+            # 添加拖入的文件
+            attachments_hint = self._attachments_hint(context)
+            if attachments_hint:
+                context_info += "\n\n**拖入的文件**：\n" + attachments_hint + "\n"
+
+            # 重试时跳过已完成的步骤
+            resume_hint = self._resume_hint(context)
+            if resume_hint:
+                context_info += "\n\n**断点续跑**：\n" + resume_hint + "\n"
+
+            # 用户配置的 MCP 工具
+            mcp_hint = self._mcp_hint(context)
+            if mcp_hint:
+                context_info += "\n\n**MCP 工具**：\n" + mcp_hint + "\n"
+            
             # 添加聊天历史
             if chat_history:
                 context_info += "\n\n**对话历史**：\n"
@@ -562,7 +597,9 @@ class DeepSeekPlanner(BasePlanner):
 - file_move: 移动文件 → params: {{"file_path": "原路径", "destination": "目标路径"}}
 - file_copy: 复制文件 → params: {{"file_path": "原路径", "destination": "目标路径"}}
 - file_delete: 删除文件 → params: {{"file_path": "文件路径"}}
-- screenshot_desktop: 截图桌面 → params: {{"save_path": "保存路径（可选）"}}
+- screenshot_desktop: 截图桌面 → params: {{"save_path": "保存路径（可选）", "mode": "full（默认）/ active_window（当前窗口）/ region（让用户框选区域）"}}
+- run_shell: 执行一条 shell 命令（只能用 ls、grep、find、sort 等常用命令，不允许 $()、反引号和输出重定向；能用其他工具时优先用其他工具） → params: {{"command": "命令", "cwd": "工作目录（可选）", "timeout": 超时秒数（可选，默认 60）}}
+- mcp_call: 调用用户配置的 MCP 服务器工具（只能调用上下文"MCP 工具"中列出的工具） → params: {{"server": "服务器", "tool": "工具名", "arguments": {{参数}}}}
 - open_file: 打开文件 → params: {{"file_path": "文件路径"}}
 - open_folder: 打开文件夹 → params: {{"folder_path": "文件夹路径"}}
 - list_files: 列出文件 (Grounding) → params: {{"path": "目录路径(如 ~/Desktop)"}}
@@ -606,6 +643,8 @@ class DeepSeekPlanner(BasePlanner):
 - clipboard_write: 写入剪贴板 → params: {{"content": "内容"}}
 - keyboard_type: 键盘输入 → params: {{"text": "要输入的文本"}}
 - keyboard_shortcut: 按键/快捷键（用于回车/Tab/Esc/方向键/⌘C 等）→ params: {{"keys": "command+c"}}，可选 {{"repeat": 2}}（如按两次回车）
+- fetch_recent_emails: 读取最近的邮件（只读，不会标为已读）→ params: {{"unseen_only": true(可选), "since_days": 0(可选，0 表示今天), "from": "发件人关键词(可选)", "subject": "主题关键词(可选)", "limit": 20(可选，最多50), "include_body": true(可选，需要正文时), "max_body_chars": 2000(可选)}}
+  - **推荐使用**: 「总结今天的未读邮件」「看看老板最近发了什么」这类需要读取邮件内容的任务，优先用 fetch_recent_emails（include_body: true），结果的 emails 中带有 subject、from、date、body，可直接交给 text_process 总结
 - search_emails: 搜索邮件 → params: {{"query": "IMAP查询(如ALL)", "folder": "文件夹(可选)", "limit": 10(可选), "keyword_filter": "关键词(可选)"}}
   - **重要**: query 必须包含 IMAP 语法（如 `ALL`, `(FROM "xxx")`, `(SUBJECT "xxx")`, `UNSEEN`）。
   - **keyword_filter**: 可选的关键词过滤，在邮件主题或发件人中搜索（不区分大小写）。
@@ -618,7 +657,8 @@ class DeepSeekPlanner(BasePlanner):
 - analyze_document: 智能文档分析 (PDF/Docx/Excel) → params: {{"file_path": "路径", "action": "map/read/analyze", "query": "问题", "page_num": 1(可选)}}
   - **重要**: 优先使用 `map` 获取结构，再根据需求 `read` 特定页或 `analyze` 全文。
 - run_applescript: 运行 AppleScript (macOS 自动化) → params: {{"script": "脚本内容"}}
-- manage_calendar_event: 管理日历 (macOS) → params: {{"action": "create/list", "title": "标题", "start_time": "YYYY-MM-DD HH:MM:SS"}}
+- manage_calendar_event: 管理日历（设置中配置了 .ics / CalDAV 日历时直接读写，否则使用 macOS 日历）→ params: {{"action": "create/list", "title": "标题", "start_time": "YYYY-MM-DD HH:MM:SS", "end_time": "结束时间(可选)", "duration": 60(分钟,可选), "location": "地点(可选)", "calendar": "日历名称(可选)"}}
+  - list 可选 {{"start_time", "end_time", "days": 7}}，默认列出今后 7 天；只有日期（如 "2026-03-01"）时为全天日程
 - manage_reminder: 管理提醒事项 (macOS) → params: {{"action": "create/list", "title": "标题"}}
 - visual_assist: 视觉助手 (Phase 39) → params: {{"action": "query/locate/extract_text", "query": "问题(extract_text时可选)", "image_path": "图片路径(可选，不提供则自动截图)", "force_vlm": false}}
   - **action说明**:
@@ -645,7 +685,7 @@ class DeepSeekPlanner(BasePlanner):
 - window_maximize: 最大化窗口 → params: {{"app_name": "应用名（可选）"}}
 
 **系统信息和图片处理**：
-- get_system_info: 获取系统信息 → params: {{"info_type": "battery/disk/memory/apps/network/all", "save_path": "~/Desktop/系统报告.md（可选，指定后自动保存）"}}
+- get_system_info: 获取系统信息 → params: {{"info_type": "os/cpu/memory/disk/battery/network/apps/all（可用逗号分隔多个）", "save_path": "~/Desktop/系统报告.md（可选，指定后自动保存）"}}
   **重要：查询系统信息必须使用这个工具，不要自己写脚本！如果用户要求保存，直接在 save_path 中指定路径！**
 - image_process: 图片处理 → params: {{"image_path": "图片路径", "action": "compress/resize/convert/info", "width": 800, "height": 600, "format": "jpg/png/webp", "quality": 80}}
 
@@ -839,7 +879,7 @@ class DeepSeekPlanner(BasePlanner):
 
 **日历与任务自动化协议 (Phase 38+)**：
 - **时间锚点**：系统已在上下文 `current_time` 提供当前精确时间。在安排任何日程前，必须先比对当前时间，禁止排错日期。
-- **冲突预警**：创建日历事件前，应先执行 `manage_calendar_event(action="list")`。若发现已有重合日程，必须如实反馈给用户。
+- **冲突预警**：创建日历事件时会返回时间重合的已有日程（warnings），若发现冲突必须如实反馈给用户。
 
 **邮件深度处理工作流**（极其重要）：
 - **优先原则**：绝对优先使用内置工具。**禁止**为“搜索/读取/下载附件/发送”编写任何 Python 脚本或调用 `imaplib`！
@@ -860,6 +900,10 @@ class DeepSeekPlanner(BasePlanner):
     2. `get_email_details(id='xxx')`
     3. `text_process(action='summarize', text='...')`
     4. `send_email(recipient='李总邮箱', body='摘要：...')`
+- **读取与总结工作流**：
+  - 示例："总结今天的未读邮件"：
+    1. `fetch_recent_emails(unseen_only=true, since_days=0, include_body=true)`
+    2. `text_process(action='summarize', text='...')`
 - **归档/标记工作流**：
   - 示例："把包含发票的邮件移到财务文件夹"：
     1. `search_emails(query='(SUBJECT "发票")')`
@@ -919,15 +963,19 @@ class DeepSeekPlanner(BasePlanner):
         logger.info("调用DeepSeek进行反思...")
         
         try:
-            response = self.client.chat.completions.create(
-                model=self.model,
-                messages=[
-                    {"role": "system", "content": "你是一个任务反思专家。分析失败原因并给出新方案。只返回JSON，不要添加其他文字。"},
-                    {"role": "user", "content": prompt}
-                ],
-                temperature=0.3,
-                max_tokens=4000
-            )
+            messages = [
+                {"role": "system", "content": "你是一个任务反思专家。分析失败原因并给出新方案。只返回JSON，不要添加其他文字。"},
+                {"role": "user", "content": prompt}
+            ]
+            with track_call(self.model):
+                response = self.client.chat.completions.create(
+                    model=self.model,
+                    messages=messages,
+                    temperature=0.3,
+                    max_tokens=4000
+                )
+            report_usage(self.model, response)
+            report_prompt(self.model, messages, response)
             
             content = response.choices[0].message.content
             logger.debug(f"反思响应: {content[:500]}...")
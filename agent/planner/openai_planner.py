@@ -10,6 +10,7 @@ from openai import OpenAI
 from agent.tools.exceptions import PlannerError
 from agent.tools.config import Config
 from agent.planner.base_planner import BasePlanner
+from agent.tools.usage import report_prompt, report_usage, track_call
 
 logger = logging.getLogger(__name__)
 
@@ -33,10 +34,13 @@ class OpenAIPlanner(BasePlanner):
         api_key = config.api_key
         
         if not api_key:
-            raise PlannerError("API密钥未设置，请在配置文件中设置api_key")
+            if not config.is_local_provider:
+                raise PlannerError("API密钥未设置，请在配置文件中设置api_key")
+            # 本地模型不校验密钥，但客户端要求非空
+            api_key = config.provider.lower()
         
         try:
-            self.client = OpenAI(api_key=api_key)
+            self.client = OpenAI(api_key=api_key, base_url=config.base_url)
             self.model = config.model
             logger.info(f"OpenAI规划器已初始化，模型: {self.model}")
         except Exception as e:
@@ -62,12 +66,16 @@ class OpenAIPlanner(BasePlanner):
             logger.info("开始规划任务...")
 
             def call_llm(messages):
-                return self.client.chat.completions.create(
-                    model=self.model,
-                    messages=messages,
-                    temperature=0.3,
-                    max_tokens=4000,
-                )
+                with track_call(self.model):
+                    response = self.client.chat.completions.create(
+                        model=self.model,
+                        messages=messages,
+                        temperature=0.3,
+                        max_tokens=4000,
+                    )
+                report_usage(self.model, response)
+                report_prompt(self.model, messages, response)
+                return response
 
             messages = [
                 {
@@ -165,6 +173,36 @@ class OpenAIPlanner(BasePlanner):
             attached_path = context.get("attached_path")
             chat_history = context.get("chat_history", [])
             
+            # 添加回复语言
+            language_hint = self._response_language_hint(context)
+            if language_hint:
+                context_info += "\n\n**回复语言**：\n" + language_hint + "\n"
+
+            # 添加剪贴板内容
+            clipboard_hint = self._clipboard_hint(context)
+            if clipboard_hint:
+                context_info += "\n\n**剪贴板内容**：\n" + clipboard_hint + "\n"
+
+            # 添加前台窗口
+            active_window_hint = self._active_window_hint(context)
+            if active_window_hint:
+                context_info += "\n\n**前台窗口**：\n" + active_window_hint + "\n"
+
+            # 添加拖入的文件
+            attachments_hint = self._attachments_hint(context)
+            if attachments_hint:
+                context_info += "\n\n**拖入的文件**：\n" + attachments_hint + "\n"
+
+            # 重试时跳过已完成的步骤
+            resume_hint = self._resume_hint(context)
+            if resume_hint:
+                context_info += "\n\n**断点续跑**：\n" + resume_hint + "\n"
+
+            # 用户配置的 MCP 工具
+            mcp_hint = self._mcp_hint(context)
+            if mcp_hint:
+                context_info += "\n\n**MCP 工具**：\n" + mcp_hint + "\n"
+            
             # 添加聊天历史
             if chat_history:
                 context_info += "\n\n**对话历史**：\n"
@@ -218,7 +256,9 @@ class OpenAIPlanner(BasePlanner):
 - browser_wait: 等待页面加载
 - browser_screenshot: 截图网页
 - download_file: 下载文件（通过点击下载链接）
-- screenshot_desktop: 截图整个桌面
+- screenshot_desktop: 截图整个桌面，params: {{"save_path": "保存路径（可选）", "mode": "full（默认）/ active_window（当前窗口）/ region（让用户框选区域）"}}
+- run_shell: 执行一条 shell 命令（只能用 ls、grep、find、sort 等常用命令，不允许 $()、反引号和输出重定向；能用其他工具时优先用其他工具），params: {{"command": "命令", "cwd": "工作目录（可选）", "timeout": 超时秒数（可选，默认 60）}}
+- mcp_call: 调用用户配置的 MCP 服务器工具（只能调用上下文"MCP 工具"中列出的工具），params: {{"server": "服务器", "tool": "工具名", "arguments": {{参数}}}}
 - open_file: 用默认应用打开文件（只在用户明确说"打开文件"时使用）
 - open_folder: 在文件管理器中打开文件夹（只在用户明确说"打开文件夹"时使用）
 - open_app: 打开应用程序，params: {{"app_name": "应用名称"}}
@@ -48,8 +48,13 @@ def create_planner(config: Config) -> BasePlanner:
             logger.warning("Grok规划器使用OpenAI兼容接口，请确保配置正确的base_url")
             return OpenAIPlanner(config)
         
+        elif provider in Config.LOCAL_BASE_URLS:
+            # 本地模型（Ollama、LM Studio）提供 OpenAI 兼容接口
+            from agent.planner.openai_planner import OpenAIPlanner
+            return OpenAIPlanner(config)
+        
         else:
-            raise PlannerError(f"不支持的AI提供商: {provider}。支持: claude, openai, deepseek, grok")
+            raise PlannerError(f"不支持的AI提供商: {provider}。支持: claude, openai, deepseek, grok, ollama, lmstudio")
     
     except ImportError as e:
         raise PlannerError(f"导入规划器模块失败: {e}。请确保已安装相应的依赖包")
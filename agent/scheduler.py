@@ -15,6 +15,8 @@ from pathlib import Path
 import subprocess
 import sys
 
+from agent.tools.focus import is_focus_active
+
 logger = logging.getLogger(__name__)
 
 
@@ -203,8 +205,12 @@ class Scheduler:
         logger.info("定时任务调度器已停止")
     
     def _run_loop(self):
-        """调度循环"""
+        """调度循环（专注时段内暂停，到点的提醒在结束后补发）"""
         while self.running:
+            if is_focus_active():
+                time.sleep(1)
+                continue
+
             now = datetime.now()
             triggered_ids = []
             
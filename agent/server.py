@@ -8,19 +8,42 @@ DeskJarvis 常驻 Python 服务进程
 - sentence-transformers 异步后台加载
 
 协议格式（stdin → Python）：
-  {"cmd":"execute","id":"task_123","instruction":"翻译 hello","context":null}
+  {"cmd":"execute","id":"task_123","instruction":"翻译 hello","context":null,"work_dir":"~/.deskjarvis/sandbox/task_123","session_id":null,"log_prompts":true,"resume_from_step":null,"completed_steps":null,"email_oauth":null,"interactive":false}  # session_id 非空时读写会话记忆；log_prompts 为 false 时不上报 prompt 事件；resume_from_step 非空表示临时失败后的重试，跳过 completed_steps；email_oauth 为邮箱 OAuth2 登录凭据 {"user","access_token","expires_at"}；interactive 为 true 时逐步执行，每步完成后等待继续
+  {"cmd":"plan","id":"plan_1","instruction":"整理下载目录","context":null}  # 只规划不执行（dry-run）；审阅后以 execute 执行，批准的步骤放在 context.approved_plan 中
   {"cmd":"ping","id":"health_1"}
   {"cmd":"stop","id":"task_123"}  # 停止指定任务
+  {"cmd":"validate","id":"validate_1","config":{"provider":...,"api_key":...,"model":...}}  # 测试提供商连通性
+  {"cmd":"reload_config","id":"reload_1"}  # 重新读取配置并重建 Agent
+  {"cmd":"warmup","id":"warmup_1"}  # 预加载嵌入模型、记忆库等重资源
+  {"cmd":"transcribe","id":"voice_1","path":"/tmp/deskjarvis_voice/voice_1.wav","language":"zh"}  # 语音转写，language 为空时自动识别
+  {"cmd":"ocr","id":"ocr_1","path":"/x/shot.png","lang":"zh"}  # 图片文字识别，lang 为空时中英文混合
   {"cmd":"shutdown","id":"bye_1"}
 
-协议格式（Python → stdout）：
-  {"type":"ready","timestamp":1234567890.0}
-  {"type":"progress","id":"task_123","timestamp":...,"data":{...}}
-  {"type":"result","id":"task_123","timestamp":...,"data":{...}}
+协议格式（Python → stdout，ready 之后按协商的版本分帧，见 agent/tools/framing.py）：
+  {"type":"ready","timestamp":1234567890.0,"protocol":2}
+  {"type":"thinking|executing|success|error|request_input|waiting_for_input","id":"task_123","timestamp":...,"data":{...}}  # 进度事件
+  {"type":"stream","id":"task_123","delta":"部分文本"}  # LLM 流式输出增量
+  {"type":"usage","id":"task_123","model":"...","input_tokens":1200,"output_tokens":300}  # 单次模型调用用量
+  {"type":"api_call","id":"task_123","provider":"claude","model":"...","latency_ms":1800,"ok":false,"error":"..."}  # 单次模型调用的耗时与结果
+  {"type":"prompt","id":"task_123","model":"...","messages":[...],"response":"..."}  # 单次模型调用的完整提示词与响应，写入提示词日志
+  {"type":"confirmation_request","id":"task_123","access_id":"confirm_1","capability":"shell","step_type":"python_script","summary":"..."}  # 修改类步骤执行前按确认策略审批，结果写入方式同 file_access_request
+  {"type":"file_access_request","id":"task_123","access_id":"access_1","path":"/x","operation":"write"}  # 写文件前请求审批，结果写入 ~/.deskjarvis/file_access/<access_id>.json
+  {"type":"screenshot_request","id":"task_123","access_id":"shot_1","mode":"full|active_window|region"}  # 请 Tauri 系统截图，路径写入 ~/.deskjarvis/screenshot_requests/<access_id>.json
+  {"type":"tool_call","id":"task_123","call_id":"tool_1","name":"shell","args":{"cmd":"ls"}}  # 请 Tauri 执行内置工具，tool_result 写入 ~/.deskjarvis/tool_results/<call_id>.json
+  {"type":"paused|resumed","id":"task_123","timestamp":...}  # 按 ~/.deskjarvis/task_control/<id>.json 在步骤之间暂停、恢复
+  {"type":"step_gate","id":"task_123","timestamp":...,"step_index":0,"total_steps":3,"description":"..."}  # 逐步执行时一个步骤已完成，等待 task_control 写入 resume（继续，随后发出 resumed）或 stop（中止）
+  {"type":"result","id":"task_123","timestamp":...,"data":{...}}  # 被 stop 中止时 data 带 "termination_reason":"user_cancel" 及已完成的 steps
+  {"type":"plan_result","id":"plan_1","ok":true,"steps":[{"type":"file_move","params":{...},"description":"...","capability":"file_write"}],"message":null}  # capability 为修改类步骤的能力类别，只读步骤为 null
   {"type":"pong","id":"health_1","timestamp":1234567890.0}
   {"type":"stop_ack","id":"task_123","timestamp":1234567890.0}
+  {"type":"validate_result","id":"validate_1","ok":true,"message":"..."}
+  {"type":"reload_ack","id":"reload_1","provider":"claude","model":"..."}
+  {"type":"warmup_ack","id":"warmup_1","elapsed_ms":3200,"steps":[{"name":"memory","ok":true,"elapsed_ms":800}]}
+  {"type":"transcribe_result","id":"voice_1","ok":true,"text":"...","message":null}
+  {"type":"ocr_result","id":"ocr_1","ok":true,"text":"...","blocks":[{"text":"...","x":10,"y":20,"width":200,"height":14,"confidence":91.5}],"message":null}
 """
 
+import os
 import sys
 import json
 import logging
@@ -31,6 +54,26 @@ from typing import Dict, Any
 # 添加项目根目录到路径
 sys.path.insert(0, str(Path(__file__).parent.parent))
 
+from agent.tools.usage import set_reporter as set_usage_reporter
+from agent.tools.usage import set_call_reporter
+from agent.tools.usage import set_prompt_reporter
+from agent.tools.file_access import set_requester as set_file_access_requester
+from agent.tools.file_access import wait_for_decision as wait_for_file_access
+from agent.tools.capabilities import set_requester as set_capability_requester
+from agent.tools.screenshot import set_requester as set_screenshot_requester
+from agent.tools.screenshot import wait_for_response as wait_for_screenshot
+from agent.tools.tool_server import set_caller as set_tool_caller
+from agent.tools.tool_server import wait_for_result as wait_for_tool_result
+from agent.tools.framing import PROTOCOL_ENV, encode_event, negotiate_version
+from agent.tools.resume import apply_resume
+from agent.tools.session_memory import apply_session_context, record_turn
+from agent.tools import email_auth, task_control
+from agent.tools.warmup import run_warmup
+from agent.tools.transcribe import transcribe_file
+from agent.tools.ocr import ocr_image
+from agent.tools.dry_run import describe_plan
+from agent.tools.log_sanitizer import install_redaction
+
 logger = logging.getLogger(__name__)
 
 # 全局停止标志字典：{request_id: True} 表示该任务需要停止
@@ -41,24 +84,62 @@ def is_stopped(request_id: str) -> bool:
     return _stop_flags.get(request_id, False)
 
 
+# 与 Tauri 协商的 stdout 协议版本，ready 事件发出后生效（见 agent/tools/framing.py）
+_protocol = 1
+
+
 def send_event(event: Dict[str, Any]) -> None:
     """发送 JSON 事件到 stdout（一行一个，Tauri 逐行读取）"""
     try:
-        line = json.dumps(event, ensure_ascii=False)
+        line = encode_event(event, _protocol)
         sys.stdout.write(line + "\n")
         sys.stdout.flush()
     except Exception:
         pass  # stdout 管道关闭时静默忽略
 
 
+def validate_provider(settings: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    用给定的提供商设置发起一次最小化调用（max_tokens=1），检查连通性
+
+    不修改磁盘上的配置，也不影响正在使用的 Agent 实例。
+    """
+    from agent.tools.config import Config
+    from agent.planner.planner_factory import create_planner
+
+    try:
+        test_config = Config()
+        for key in ("provider", "api_key", "model", "base_url"):
+            if settings.get(key):
+                test_config.set(key, settings[key])
+
+        planner = create_planner(test_config)
+        client = getattr(planner, "client", None)
+        if client is None:
+            return {"ok": False, "message": "规划器未初始化 API 客户端"}
+
+        messages = [{"role": "user", "content": "ping"}]
+        if hasattr(client, "messages"):
+            client.messages.create(model=test_config.model, max_tokens=1, messages=messages)
+        else:
+            client.chat.completions.create(model=test_config.model, max_tokens=1, messages=messages)
+        return {"ok": True, "message": "连接成功"}
+    except Exception as e:
+        return {"ok": False, "message": str(e)}
+
+
 def main() -> None:
     """常驻服务主循环"""
+    global _protocol
+
     # ========== 日志只输出到 stderr，stdout 留给通信协议 ==========
     logging.basicConfig(
         level=logging.INFO,
         format="%(asctime)s [%(name)s] %(levelname)s %(message)s",
         stream=sys.stderr,
     )
+    # 写入 stderr 前替换密钥和常见令牌（Tauri 转发时还会再按用户规则脱敏一次）
+    install_redaction()
 
     logger.info("DeskJarvis Python 服务启动中...")
     startup_start = time.time()
@@ -67,6 +148,7 @@ def main() -> None:
     try:
         from agent.tools.config import Config
         config = Config()
+        install_redaction(config)
         if not config.validate():
             send_event({"type": "error", "message": "配置无效，请检查 ~/.deskjarvis/config.json"})
             sys.exit(1)
@@ -85,12 +167,15 @@ def main() -> None:
     startup_elapsed = time.time() - startup_start
     logger.info("DeskJarvis Python 服务已就绪，启动耗时 %.1fs" % startup_elapsed)
 
-    # ========== 发送就绪信号 ==========
+    # ========== 发送就绪信号（按版本 1 发送，之后的事件按协商的版本分帧） ==========
+    protocol = negotiate_version(os.environ.get(PROTOCOL_ENV))
     send_event({
         "type": "ready",
         "timestamp": time.time(),
         "startup_time": round(startup_elapsed, 2),
+        "protocol": protocol,
     })
+    _protocol = protocol
 
     # ========== 主循环：从 stdin 读取命令，执行并返回结果 ==========
     try:
@@ -137,6 +222,98 @@ def main() -> None:
                     "timestamp": time.time(),
                 })
 
+            # ---------- validate ----------
+            elif cmd_type == "validate":
+                outcome = validate_provider(cmd.get("config") or {})
+                send_event({
+                    "type": "validate_result",
+                    "id": request_id,
+                    "timestamp": time.time(),
+                    **outcome,
+                })
+
+            # ---------- reload_config ----------
+            elif cmd_type == "reload_config":
+                try:
+                    config.reload()
+                    install_redaction(config)
+                    if not config.validate():
+                        raise ValueError("配置无效，请检查 ~/.deskjarvis/config.json")
+                    new_agent = DeskJarvisAgent(config)
+                    # 记忆与提供商无关，沿用已加载的实例，避免重复加载
+                    new_agent._memory = agent._memory
+                    agent = new_agent
+                    # 工作流和收藏可能被导入的自动化包改写，丢弃缓存以便重新读取
+                    import agent.workflows as workflows_module
+                    import agent.history as history_module
+                    workflows_module._workflow_manager = None
+                    history_module._task_history = None
+                    logger.info(f"配置已重新加载: {config.provider} / {config.model}")
+                    send_event({
+                        "type": "reload_ack",
+                        "id": request_id,
+                        "timestamp": time.time(),
+                        "provider": config.provider,
+                        "model": config.model,
+                    })
+                except Exception as e:
+                    logger.error(f"重新加载配置失败: {e}")
+                    send_event({
+                        "type": "error",
+                        "id": request_id,
+                        "message": "重新加载配置失败: " + str(e),
+                    })
+
+            # ---------- warmup ----------
+            elif cmd_type == "warmup":
+                report = run_warmup(agent.warmup_steps())
+                send_event({
+                    "type": "warmup_ack",
+                    "id": request_id,
+                    "timestamp": time.time(),
+                    **report,
+                })
+
+            # ---------- transcribe ----------
+            elif cmd_type == "transcribe":
+                outcome = transcribe_file(cmd.get("path", ""), cmd.get("language"))
+                send_event({
+                    "type": "transcribe_result",
+                    "id": request_id,
+                    "timestamp": time.time(),
+                    "message": None,
+                    **outcome,
+                })
+
+            # ---------- ocr ----------
+            elif cmd_type == "ocr":
+                outcome = ocr_image(cmd.get("path", ""), cmd.get("lang"))
+                send_event({
+                    "type": "ocr_result",
+                    "id": request_id,
+                    "timestamp": time.time(),
+                    "message": None,
+                    **outcome,
+                })
+
+            # ---------- plan ----------
+            elif cmd_type == "plan":
+                instruction = cmd.get("instruction", "")
+                try:
+                    if not instruction:
+                        raise ValueError("指令为空")
+                    steps = agent.plan(instruction, context=cmd.get("context"))
+                    outcome = {"ok": True, "steps": describe_plan(steps), "message": None}
+                except Exception as e:
+                    logger.error("规划任务失败: " + str(e), exc_info=True)
+                    outcome = {"ok": False, "steps": [], "message": str(e)}
+                send_event({
+                    "type": "plan_result",
+                    "id": request_id,
+                    "timestamp": time.time(),
+                    **outcome,
+                })
+
             # ---------- shutdown ----------
             elif cmd_type == "shutdown":
                 logger.info("收到关闭命令，正在退出...")
@@ -154,6 +331,13 @@ def main() -> None:
                     del _stop_flags[request_id]
                 instruction = cmd.get("instruction", "")
                 context = cmd.get("context")
+                work_dir = cmd.get("work_dir")
+                session_id = cmd.get("session_id")
+                log_prompts = cmd.get("log_prompts", False)
+                resume_from_step = cmd.get("resume_from_step")
+                completed_steps = cmd.get("completed_steps")
+                email_oauth = cmd.get("email_oauth")
+                interactive = bool(cmd.get("interactive", False))
 
                 if not instruction:
                     send_event({
@@ -178,14 +362,145 @@ def main() -> None:
 
                 progress_cb = make_progress_callback(request_id)
 
+                def make_usage_reporter(rid: str):
+                    def reporter(usage: Dict[str, Any]):
+                        send_event({"type": "usage", "id": rid, "timestamp": time.time(), **usage})
+                    return reporter
+
+                def make_call_reporter(rid: str):
+                    def reporter(call: Dict[str, Any]):
+                        send_event({
+                            "type": "api_call",
+                            "id": rid,
+                            "timestamp": time.time(),
+                            "provider": config.provider,
+                            **call,
+                        })
+                    return reporter
+
+                def make_prompt_reporter(rid: str):
+                    def reporter(prompt: Dict[str, Any]):
+                        send_event({"type": "prompt", "id": rid, "timestamp": time.time(), **prompt})
+                    return reporter
+
+                def make_file_access_requester(rid: str):
+                    response_dir = Path.home() / ".deskjarvis" / "file_access"
+                    def requester(path: str, operation: str) -> bool:
+                        access_id = f"access_{int(time.time() * 1000)}_{os.urandom(3).hex()}"
+                        send_event({
+                            "type": "file_access_request",
+                            "id": rid,
+                            "timestamp": time.time(),
+                            "access_id": access_id,
+                            "path": path,
+                            "operation": operation,
+                        })
+                        return wait_for_file_access(
+                            response_dir, access_id, is_cancelled=lambda: is_stopped(rid)
+                        )
+                    return requester
+
+                def make_screenshot_requester(rid: str):
+                    response_dir = Path.home() / ".deskjarvis" / "screenshot_requests"
+                    def requester(mode: str) -> str:
+                        access_id = f"shot_{int(time.time() * 1000)}_{os.urandom(3).hex()}"
+                        send_event({
+                            "type": "screenshot_request",
+                            "id": rid,
+                            "timestamp": time.time(),
+                            "access_id": access_id,
+                            "mode": mode,
+                        })
+                        return wait_for_screenshot(
+                            response_dir, access_id, is_cancelled=lambda: is_stopped(rid)
+                        )
+                    return requester
+
+                def make_tool_caller(rid: str):
+                    response_dir = Path.home() / ".deskjarvis" / "tool_results"
+                    def caller(name: str, args: Dict[str, Any]) -> Dict[str, Any]:
+                        call_id = f"tool_{int(time.time() * 1000)}_{os.urandom(3).hex()}"
+                        send_event({
+                            "type": "tool_call",
+                            "id": rid,
+                            "timestamp": time.time(),
+                            "call_id": call_id,
+                            "name": name,
+                            "args": args,
+                        })
+                        return wait_for_tool_result(
+                            response_dir, call_id, is_cancelled=lambda: is_stopped(rid)
+                        )
+                    return caller
+
+                def make_stop_checker(rid: str):
+                    def emit(event_type: str):
+                        send_event({"type": event_type, "id": rid, "timestamp": time.time()})
+                    def check_stop() -> bool:
+                        # 步骤之间处理 Tauri 写入的暂停/恢复/停止命令
+                        if task_control.wait_while_paused(
+                            rid, lambda: emit("paused"), lambda: emit("resumed")
+                        ):
+                            _stop_flags[rid] = True
+                        return is_stopped(rid)
+                    return check_stop
+
+                def make_step_gate(rid: str):
+                    def gate(step_index: int, total_steps: int, description: str) -> bool:
+                        # 逐步执行：步骤完成后等待 Tauri 写入继续或中止命令
+                        def on_waiting():
+                            send_event({
+                                "type": "step_gate",
+                                "id": rid,
+                                "timestamp": time.time(),
+                                "step_index": step_index,
+                                "total_steps": total_steps,
+                                "description": description,
+                            })
+                        def on_continued():
+                            send_event({"type": "resumed", "id": rid, "timestamp": time.time()})
+                        if task_control.wait_for_next_step(rid, on_waiting, on_continued):
+                            _stop_flags[rid] = True
+                        return is_stopped(rid)
+                    return gate
+
+                def make_confirmation_requester(rid: str):
+                    response_dir = Path.home() / ".deskjarvis" / "file_access"
+                    def requester(capability: str, step_type: str, summary: str) -> bool:
+                        access_id = f"confirm_{int(time.time() * 1000)}_{os.urandom(3).hex()}"
+                        send_event({
+                            "type": "confirmation_request",
+                            "id": rid,
+                            "timestamp": time.time(),
+                            "access_id": access_id,
+                            "capability": capability,
+                            "step_type": step_type,
+                            "summary": summary,
+                        })
+                        # 按策略可能需要用户确认，留足时间（短于 Tauri 的卡死检测）
+                        return wait_for_file_access(
+                            response_dir, access_id, timeout=240, is_cancelled=lambda: is_stopped(rid)
+                        )
+                    return requester
+
                 try:
+                    # 同一会话之前的对话和文件并入 context，支持追问
+                    if session_id:
+                        context = apply_session_context(session_id, context)
+                    # 自动重试时只规划剩余的步骤
+                    context = apply_resume(context, resume_from_step, completed_steps)
                     # 将停止标志和检查函数注入到 context 中
                     if context is None:
                         context = {}
                     context["_request_id"] = request_id
                     # 注入停止检查函数，让执行器可以随时检查是否被停止
-                    context["_check_stop"] = lambda: is_stopped(request_id)
+                    context["_check_stop"] = make_stop_checker(request_id)
                     context["_stop_execution"] = False  # 初始化为 False
+                    if interactive:
+                        context["_step_gate"] = make_step_gate(request_id)
+                    # 任务专属工作目录（sandbox/task_<id>），避免任务间文件互相覆盖
+                    if work_dir:
+                        context["_work_dir"] = work_dir
                     
                     # 清除停止事件（新任务开始）
                     try:
@@ -202,14 +517,46 @@ def main() -> None:
                             "message": "任务已取消",
                             "steps": [],
                             "user_instruction": instruction,
+                            "termination_reason": "user_cancel",
                         }
                     else:
-                        # 执行任务
-                        result = agent.execute(
-                            instruction,
-                            progress_callback=progress_cb,
-                            context=context,
-                        )
+                        # 执行任务（在任务工作目录下执行，结束后恢复）
+                        previous_cwd = os.getcwd()
+                        if work_dir and os.path.isdir(work_dir):
+                            os.chdir(work_dir)
+                        # 模型用量以 usage 事件上报，由 Tauri 统计费用并执行费用上限
+                        set_usage_reporter(make_usage_reporter(request_id))
+                        # 每次模型调用的耗时与失败原因以 api_call 事件上报，供 Tauri 统计提供商健康状况
+                        set_call_reporter(make_call_reporter(request_id))
+                        # 完整提示词与响应以 prompt 事件上报，由 Tauri 写入提示词日志（任务或全局关闭时不上报）
+                        if log_prompts:
+                            set_prompt_reporter(make_prompt_reporter(request_id))
+                        # 写沙盒以外的路径前以 file_access_request 事件请求 Tauri 审批
+                        set_file_access_requester(make_file_access_requester(request_id))
+                        # 修改类步骤以 confirmation_request 事件请求 Tauri 按确认策略审批
+                        set_capability_requester(make_confirmation_requester(request_id))
+                        # 桌面截图以 screenshot_request 事件请 Tauri 在系统层截取（支持当前窗口和框选区域）
+                        set_screenshot_requester(make_screenshot_requester(request_id))
+                        # 命令执行以 tool_call 事件交给 Tauri，统一限制和审计
+                        set_tool_caller(make_tool_caller(request_id))
+                        # 邮箱使用 OAuth2 登录时由 Tauri 提供已刷新的访问令牌
+                        email_auth.set_credential(email_oauth)
+                        try:
+                            result = agent.execute(
+                                instruction,
+                                progress_callback=progress_cb,
+                                context=context,
+                            )
+                        finally:
+                            set_usage_reporter(None)
+                            set_call_reporter(None)
+                            set_prompt_reporter(None)
+                            set_file_access_requester(None)
+                            set_capability_requester(None)
+                            set_screenshot_requester(None)
+                            set_tool_caller(None)
+                            email_auth.set_credential(None)
+                            os.chdir(previous_cwd)
                         
                         # 检查是否在执行过程中被停止
                         if is_stopped(request_id):
@@ -219,11 +566,16 @@ def main() -> None:
                                 "message": "任务已取消",
                                 "steps": result.get("steps", []),
                                 "user_instruction": instruction,
+                                "termination_reason": "user_cancel",
                             }
                     
                     # 清理停止标志
                     if request_id in _stop_flags:
                         del _stop_flags[request_id]
+                    task_control.clear(request_id)
+
+                    if session_id:
+                        record_turn(session_id, instruction, result)
                     
                     send_event({
                         "type": "result",
@@ -236,6 +588,7 @@ def main() -> None:
                     # 清理停止标志
                     if request_id in _stop_flags:
                         del _stop_flags[request_id]
+                    task_control.clear(request_id)
                     send_event({
                         "type": "result",
                         "id": request_id,
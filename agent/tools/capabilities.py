@@ -0,0 +1,125 @@
+"""
+能力审批：执行会修改系统状态的步骤前向 Tauri 请求许可
+
+步骤按类型归入能力（写文件、执行脚本、发邮件、模拟输入、系统控制）。常驻服务在执行
+任务前通过 set_requester 注册回调，回调以 confirmation_request 事件询问 Tauri，
+由 Rust 侧按确认策略自动放行、拒绝或请用户确认（观察模式下拒绝所有修改类能力）。
+未注册回调时（单次模式、测试）不做限制。只读步骤不在表中，始终放行。
+"""
+
+import json
+import logging
+from typing import Any, Callable, Dict, Optional
+
+from agent.tools.log_sanitizer import LogSanitizer
+
+logger = logging.getLogger(__name__)
+
+# 步骤类型 → 能力
+STEP_CAPABILITIES: Dict[str, str] = {
+    # 写文件
+    "file_create": "file_write",
+    "file_write": "file_write",
+    "file_delete": "file_write",
+    "file_move": "file_write",
+    "file_copy": "file_write",
+    "file_rename": "file_write",
+    "file_organize": "file_write",
+    "file_classify": "file_write",
+    "file_batch_copy": "file_write",
+    "file_batch_organize": "file_write",
+    "file_batch_rename": "file_write",
+    "compress_files": "file_write",
+    "download_file": "file_write",
+    "download_attachments": "file_write",
+    "image_process": "file_write",
+    "create_workflow": "file_write",
+    "delete_workflow": "file_write",
+    "add_favorite": "file_write",
+    "remove_favorite": "file_write",
+    # 执行脚本
+    "python": "shell",
+    "python_script": "shell",
+    "execute_python_script": "shell",
+    "run_applescript": "shell",
+    "run_shell": "shell",
+    "mcp_call": "shell",
+    "download_latest_python_installer": "shell",
+    # 邮件
+    "send_email": "email",
+    "manage_emails": "email",
+    # 模拟输入
+    "keyboard_type": "input",
+    "keyboard_shortcut": "input",
+    "mouse_click": "input",
+    "mouse_move": "input",
+    "clipboard_write": "input",
+    "browser_click": "input",
+    "browser_fill": "input",
+    "fill_login": "input",
+    "fill_captcha": "input",
+    # 系统控制
+    "open_app": "system",
+    "close_app": "system",
+    "set_volume": "system",
+    "set_brightness": "system",
+    "window_minimize": "system",
+    "window_maximize": "system",
+    "window_close": "system",
+    "set_reminder": "system",
+    "cancel_reminder": "system",
+    "manage_reminder": "system",
+    "manage_calendar_event": "system",
+}
+
+# 确认摘要中参数部分的最大长度
+MAX_SUMMARY_CHARS = 300
+
+# 回调参数：(能力, 步骤类型, 参数摘要)，返回是否允许
+CapabilityRequester = Callable[[str, str, str], bool]
+
+_requester: Optional[CapabilityRequester] = None
+
+
+def set_requester(requester: Optional[CapabilityRequester]) -> None:
+    """注册（或清除）能力审批回调"""
+    global _requester
+    _requester = requester
+
+
+def capability_of(step_type: str) -> Optional[str]:
+    """步骤所需的能力，只读步骤返回 None"""
+    return STEP_CAPABILITIES.get(step_type)
+
+
+def summarize_params(params: Optional[Dict[str, Any]]) -> str:
+    """脱敏后的参数摘要，供用户确认时查看"""
+    if not params:
+        return ""
+    try:
+        text = json.dumps(LogSanitizer.sanitize_dict(params), ensure_ascii=False, default=str)
+    except Exception:
+        text = str(params)
+    if len(text) > MAX_SUMMARY_CHARS:
+        text = text[:MAX_SUMMARY_CHARS] + "…"
+    return text
+
+
+def check_step(step_type: str, params: Optional[Dict[str, Any]] = None) -> Optional[str]:
+    """
+    检查步骤是否获准执行
+
+    Returns:
+        未获准时返回拒绝原因，获准时返回 None
+    """
+    capability = capability_of(step_type)
+    if capability is None or _requester is None:
+        return None
+    try:
+        approved = _requester(capability, step_type, summarize_params(params))
+    except Exception as e:
+        logger.error(f"请求能力许可失败: {e}")
+        approved = False
+    if approved:
+        return None
+    return f"未获准执行 {step_type}（需要 {capability} 能力，已被确认策略、用户或只读观察模式拒绝）"
@@ -4,10 +4,11 @@
 遵循 docs/ARCHITECTURE.md 中的配置管理规范
 """
 
-from typing import Optional, Dict, Any
+from typing import Optional, Dict, Any, Mapping
 from pathlib import Path
 import json
 import logging
+import os
 from agent.tools.exceptions import ConfigError
 from agent.tools.key_encryptor import KeyEncryptor
 
@@ -27,7 +28,7 @@ class Config:
     """
     
     DEFAULT_CONFIG = {
-        "provider": "claude",  # AI提供商: claude, openai, deepseek, grok
+        "provider": "claude",  # AI提供商: claude, openai, deepseek, grok, ollama, lmstudio
         "api_key": "",
         "model": "claude-3-5-sonnet-20241022",  # 根据provider自动选择默认模型
         "sandbox_path": str(Path.home() / ".deskjarvis" / "sandbox"),
@@ -43,23 +44,72 @@ class Config:
         "deepseek": "deepseek-chat",  # DeepSeek-V3，最强通用模型
         "grok": "grok-beta",
     }
+
+    # 本地模型运行时的默认 API 地址（OpenAI 兼容接口，不需要 API 密钥）
+    LOCAL_BASE_URLS = {
+        "ollama": "http://localhost:11434/v1",
+        "lmstudio": "http://localhost:1234/v1",
+    }
+    
+    # 环境变量覆盖（与 Tauri 侧 config::resolve 一致）：只影响读取，不写回配置文件
+    ENV_OVERRIDES = {
+        "DESKJARVIS_SANDBOX": "sandbox_path",
+        "DESKJARVIS_PROVIDER": "provider",
+        "DESKJARVIS_MODEL": "model",
+        "DESKJARVIS_API_KEY": "api_key",
+        "DESKJARVIS_BASE_URL": "base_url",
+        "DESKJARVIS_LOG_LEVEL": "log_level",
+        "DESKJARVIS_AUTO_CONFIRM": "auto_confirm",
+    }
     
     def __init__(self, config_path: Optional[str] = None):
         """
         初始化配置
         
         Args:
-            config_path: 配置文件路径，如果为None则使用默认路径
+            config_path: 配置文件路径，如果为None则使用 DESKJARVIS_CONFIG 或默认路径
         """
         if config_path is None:
+            config_path = os.environ.get("DESKJARVIS_CONFIG", "").strip()
+        if config_path:
+            config_path = str(Path(config_path).expanduser())
+        else:
             config_dir = Path.home() / ".deskjarvis"
             config_dir.mkdir(parents=True, exist_ok=True)
             config_path = str(config_dir / "config.json")
         
         self.config_path = Path(config_path)
         self._config: Dict[str, Any] = {}
+        self._overrides: Dict[str, Any] = {}
         self.load()
     
+    @classmethod
+    def env_overrides(cls, environ: Optional[Mapping[str, str]] = None) -> Dict[str, Any]:
+        """
+        从环境变量读取配置覆盖
+        
+        Args:
+            environ: 环境变量表，默认 os.environ
+        
+        Returns:
+            配置键 → 覆盖值（auto_confirm 转为布尔值）
+        """
+        environ = os.environ if environ is None else environ
+        overrides: Dict[str, Any] = {}
+        for name, key in cls.ENV_OVERRIDES.items():
+            value = (environ.get(name) or "").strip()
+            if not value:
+                continue
+            if key == "auto_confirm":
+                lowered = value.lower()
+                if lowered not in ("1", "true", "yes", "on", "0", "false", "no", "off"):
+                    logger.warning(f"忽略环境变量 {name}: 无效的布尔值 {value}")
+                    continue
+                overrides[key] = lowered in ("1", "true", "yes", "on")
+            else:
+                overrides[key] = value
+        return overrides
+    
     def load(self) -> None:
         """
         加载配置文件（自动解密 API Key）
@@ -89,6 +139,7 @@ class Config:
             raise ConfigError(f"配置文件格式错误: {e}")
         except Exception as e:
             raise ConfigError(f"加载配置文件失败: {e}")
+        self._overrides = self.env_overrides()
     
     def reload(self) -> None:
         """从磁盘强制重新加载配置"""
@@ -131,6 +182,8 @@ class Config:
         Returns:
             配置值
         """
+        if key in self._overrides:
+            return self._overrides[key]
         return self._config.get(key, default)
     
     def set(self, key: str, value: Any) -> None:
@@ -142,6 +195,8 @@ class Config:
             value: 配置值
         """
         self._config[key] = value
+        # 显式设置的值优先于环境变量覆盖
+        self._overrides.pop(key, None)
         logger.debug(f"配置已更新: {key} = {value}")
     
     def validate(self) -> bool:
@@ -151,7 +206,7 @@ class Config:
         Returns:
             配置是否有效
         """
-        if not self.get("api_key"):
+        if not self.get("api_key") and not self.is_local_provider:
             logger.warning("API密钥未设置")
             return False
         return True
@@ -166,6 +221,16 @@ class Config:
         """获取AI提供商"""
         return self.get("provider", self.DEFAULT_CONFIG["provider"])
     
+    @property
+    def is_local_provider(self) -> bool:
+        """是否为本地模型（Ollama、LM Studio）"""
+        return self.provider.lower() in self.LOCAL_BASE_URLS
+
+    @property
+    def base_url(self) -> Optional[str]:
+        """自定义 API 地址，本地模型未设置时使用默认地址"""
+        return self.get("base_url") or self.LOCAL_BASE_URLS.get(self.provider.lower())
+
     @property
     def model(self) -> str:
         """获取模型名称"""
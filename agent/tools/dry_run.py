@@ -0,0 +1,64 @@
+"""
+只规划不执行（dry-run）：先审阅步骤，再只执行批准的步骤
+
+Tauri 发送 plan 命令时服务只调用规划器，返回步骤列表（附每个步骤所需的能力），
+不执行任何步骤。用户审阅并勾选后，Tauri 以普通 execute 命令执行，批准的步骤放在
+context["approved_plan"] 中，编排器据此跳过快速通道和规划，直接执行这些步骤。
+
+使用示例:
+    from agent.tools.dry_run import approved_plan, describe_plan
+
+    steps = describe_plan(agent.plan(instruction, context))  # 审阅用的步骤列表
+    plan = approved_plan(context)  # 执行时取出批准的步骤，不是已审阅的计划时为 None
+"""
+
+from typing import Any, Dict, List, Optional
+
+from agent.tools.capabilities import capability_of
+
+# context 中批准执行的步骤
+APPROVED_PLAN_KEY = "approved_plan"
+
+# 审阅时附加在步骤上的字段，执行前去掉
+REVIEW_FIELDS = ("capability",)
+
+
+def describe_plan(steps: Optional[List[Any]]) -> List[Dict[str, Any]]:
+    """
+    规划结果转为审阅用的步骤列表
+
+    Args:
+        steps: 规划器返回的步骤
+
+    Returns:
+        原步骤附加 capability（修改类步骤的能力类别，只读步骤为 None），忽略不是字典的项
+    """
+    described = []
+    for step in steps or []:
+        if not isinstance(step, dict):
+            continue
+        item = dict(step)
+        item["capability"] = capability_of(str(step.get("type", "")))
+        described.append(item)
+    return described
+
+
+def approved_plan(context: Optional[Dict[str, Any]]) -> Optional[List[Dict[str, Any]]]:
+    """
+    取出 context 中批准执行的步骤
+
+    临时失败后自动重试时（context 带 resume，见 agent/tools/resume.py）跳过已完成的步骤。
+
+    Returns:
+        去掉审阅字段的步骤；不是执行已审阅的计划时返回 None
+    """
+    plan = (context or {}).get(APPROVED_PLAN_KEY)
+    if not isinstance(plan, list):
+        return None
+    steps = [
+        {k: v for k, v in step.items() if k not in REVIEW_FIELDS}
+        for step in plan
+        if isinstance(step, dict)
+    ]
+    resume = (context or {}).get("resume") or {}
+    return steps[max(int(resume.get("from_step") or 0), 0):]
@@ -0,0 +1,54 @@
+"""
+邮箱 OAuth2 凭据：配置 email_auth_method 为 oauth2 时用 XOAUTH2 登录 SMTP / IMAP
+
+授权和令牌刷新由 Tauri 完成（令牌保存在系统钥匙串），execute 命令的 email_oauth 字段
+带上本次任务可用的 {"user", "access_token", "expires_at"}。常驻服务在执行任务前通过
+set_credential 设置，任务结束后清除；发送和读取邮件时据此改用 XOAUTH2 认证。
+
+使用示例:
+    from agent.tools import email_auth
+
+    credential = email_auth.get_credential("me@gmail.com")
+    if credential:
+        smtp.auth("XOAUTH2", lambda challenge=None: email_auth.xoauth2_string(credential))
+"""
+
+import logging
+import time
+from typing import Any, Dict, Optional
+
+logger = logging.getLogger(__name__)
+
+_credential: Optional[Dict[str, Any]] = None
+
+
+def set_credential(credential: Optional[Dict[str, Any]]) -> None:
+    """设置（或清除）本次任务的 OAuth2 凭据"""
+    global _credential
+    _credential = credential if credential and credential.get("access_token") else None
+
+
+def get_credential(user: Optional[str] = None) -> Optional[Dict[str, Any]]:
+    """
+    本次任务可用的 OAuth2 凭据
+
+    Args:
+        user: 登录的邮箱，与授权的邮箱不一致时不使用（为空时不检查）
+
+    Returns:
+        {"user", "access_token", "expires_at"}，未启用 OAuth2 或已过期时为 None
+    """
+    if _credential is None:
+        return None
+    if user and _credential.get("user", "").lower() != user.lower():
+        return None
+    expires_at = _credential.get("expires_at")
+    if expires_at and expires_at <= time.time() * 1000:
+        logger.warning("邮箱访问令牌已过期，请重新执行任务以刷新")
+        return None
+    return _credential
+
+
+def xoauth2_string(credential: Dict[str, Any]) -> str:
+    """SASL XOAUTH2 初始响应（未经 base64 编码，smtplib / imaplib 会自行编码）"""
+    return f"user={credential['user']}\x01auth=Bearer {credential['access_token']}\x01\x01"
@@ -0,0 +1,86 @@
+"""
+文件访问审批：文件管理器写入、移动、删除文件前先向 Tauri 请求许可
+
+常驻服务在执行任务前通过 set_requester 注册回调，回调以 file_access_request 事件
+把路径发给 Tauri，由 Rust 侧对照沙盒目录和用户白名单决定放行，或请前端确认。
+未注册回调时（单次模式、测试）不做限制，沿用 path_validator 的检查。
+
+使用示例:
+    from agent.tools.file_access import require_access
+
+    require_access(target_path, "write")
+"""
+
+import json
+import logging
+import time
+from pathlib import Path
+from typing import Callable, Optional
+
+from agent.tools.exceptions import FileManagerError
+
+logger = logging.getLogger(__name__)
+
+# 回调参数：(绝对路径, 操作类型)，返回是否允许
+AccessRequester = Callable[[str, str], bool]
+
+_requester: Optional[AccessRequester] = None
+
+
+def set_requester(requester: Optional[AccessRequester]) -> None:
+    """注册（或清除）访问审批回调"""
+    global _requester
+    _requester = requester
+
+
+def require_access(path: Path, operation: str) -> None:
+    """
+    请求对路径执行写操作的许可
+
+    Args:
+        path: 目标路径（已通过 validate_path 解析为绝对路径）
+        operation: 操作类型，如 "write"、"delete"、"move"
+
+    Raises:
+        FileManagerError: 未获准访问
+    """
+    if _requester is None:
+        return
+    try:
+        approved = _requester(str(path), operation)
+    except Exception as e:
+        logger.error(f"请求文件访问许可失败: {e}")
+        approved = False
+    if not approved:
+        raise FileManagerError(f"未获准访问沙盒以外的路径: {path}")
+
+
+def wait_for_decision(
+    response_dir: Path,
+    access_id: str,
+    timeout: float = 120,
+    is_cancelled: Optional[Callable[[], bool]] = None,
+) -> bool:
+    """
+    轮询 Tauri 写入的审批结果文件 <response_dir>/<access_id>.json
+
+    执行任务期间服务不读取 stdin，因此审批结果与用户输入一样通过文件交换。
+    超时或任务被取消时视为拒绝。
+    """
+    response_file = response_dir / f"{access_id}.json"
+    deadline = time.time() + timeout
+    while time.time() < deadline:
+        if is_cancelled is not None and is_cancelled():
+            return False
+        if response_file.exists():
+            try:
+                with open(response_file, "r", encoding="utf-8") as f:
+                    response = json.load(f)
+                response_file.unlink()
+                return bool(response.get("approved"))
+            except (json.JSONDecodeError, IOError) as e:
+                logger.warning(f"读取审批结果失败: {e}")
+        time.sleep(0.3)
+
+    logger.warning(f"文件访问审批超时: {access_id}")
+    return False
@@ -0,0 +1,51 @@
+"""
+专注时段：Tauri 开启专注后，提醒推迟到结束后触发，系统通知直接跳过
+
+会话由 Tauri 写入 ~/.deskjarvis/focus.json（{"started_at", "ends_at", "allow_interactive"}，
+时间为毫秒时间戳），结束或到期后删除；文件缺失、损坏或已到期都视为未在专注。
+
+使用示例:
+    from agent.tools.focus import is_focus_active
+
+    if is_focus_active():
+        return  # 稍后再提醒
+"""
+
+import json
+import logging
+import time
+from pathlib import Path
+from typing import Optional
+
+logger = logging.getLogger(__name__)
+
+FOCUS_FILE = Path.home() / ".deskjarvis" / "focus.json"
+
+
+def focus_ends_at(path: Optional[Path] = None, now: Optional[float] = None) -> Optional[float]:
+    """
+    当前专注时段的结束时间
+
+    Args:
+        path: 会话文件，默认 ~/.deskjarvis/focus.json
+        now: 当前时间（秒），默认 time.time()
+
+    Returns:
+        结束时间（秒级时间戳），未在专注时为 None
+    """
+    path = path or FOCUS_FILE
+    now = time.time() if now is None else now
+    try:
+        session = json.loads(path.read_text(encoding="utf-8"))
+        ends_at = float(session["ends_at"]) / 1000
+    except FileNotFoundError:
+        return None
+    except (OSError, ValueError, KeyError, TypeError) as e:
+        logger.warning(f"读取专注状态失败: {e}")
+        return None
+    return ends_at if ends_at > now else None
+
+
+def is_focus_active(path: Optional[Path] = None, now: Optional[float] = None) -> bool:
+    """是否处于专注时段"""
+    return focus_ends_at(path, now) is not None
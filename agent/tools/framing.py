@@ -0,0 +1,58 @@
+"""
+stdout 协议分帧：常驻服务发给 Tauri 的事件逐行编码，避免混入 stdout 的杂散输出破坏协议
+
+协议版本 1：每行一个 JSON 事件。
+协议版本 2：每行为 "@dj:" + base64(UTF-8 JSON)，Tauri 忽略不带前缀的行。
+
+Tauri 启动服务时通过环境变量 DESKJARVIS_PROTOCOL 声明支持的最高版本，
+服务取双方都支持的版本，写在（始终按版本 1 发送的）ready 事件的 protocol 字段中。
+旧版 Tauri 不设置该变量，旧版服务的 ready 事件不带该字段，双方都退回版本 1。
+
+使用示例:
+    from agent.tools.framing import negotiate_version, encode_event
+
+    protocol = negotiate_version(os.environ.get(PROTOCOL_ENV))
+    sys.stdout.write(encode_event(event, protocol) + "\\n")
+"""
+
+import base64
+import json
+from typing import Any, Dict, Optional
+
+# 本服务支持的最高协议版本
+PROTOCOL_VERSION = 2
+
+# Tauri 声明其支持的最高版本所用的环境变量
+PROTOCOL_ENV = "DESKJARVIS_PROTOCOL"
+
+# 版本 2 的帧前缀
+FRAME_PREFIX = "@dj:"
+
+
+def negotiate_version(requested: Optional[str]) -> int:
+    """取 Tauri 声明的版本与本服务支持版本中较小的一个，未声明或无效时为 1"""
+    try:
+        version = int(requested or 1)
+    except ValueError:
+        return 1
+    return max(1, min(version, PROTOCOL_VERSION))
+
+
+def encode_event(event: Dict[str, Any], protocol: int) -> str:
+    """把事件编码为一行（不含换行符）"""
+    line = json.dumps(event, ensure_ascii=False)
+    if protocol < 2:
+        return line
+    return FRAME_PREFIX + base64.b64encode(line.encode("utf-8")).decode("ascii")
+
+
+def decode_line(line: str) -> Optional[Dict[str, Any]]:
+    """解码一行：带帧前缀的按版本 2 解码，其余按 JSON 解析；无法解析时返回 None"""
+    text = line.strip()
+    try:
+        if text.startswith(FRAME_PREFIX):
+            text = base64.b64decode(text[len(FRAME_PREFIX):], validate=True).decode("utf-8")
+        event = json.loads(text)
+    except (ValueError, UnicodeDecodeError):
+        return None
+    return event if isinstance(event, dict) else None
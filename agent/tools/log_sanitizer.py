@@ -4,14 +4,51 @@
 功能：
 - 检测敏感参数名（password, key, token 等）
 - 对敏感值进行脱敏显示（只显示前几位和后几位）
+- 日志过滤器：写入日志前替换配置中的密钥值和常见令牌格式（与 Tauri 端
+  src-tauri/src/redaction.rs 的内置规则一致）
+
+使用示例:
+    from agent.tools.log_sanitizer import install_redaction
+
+    install_redaction(config)  # 给根日志记录器的所有处理器加上脱敏过滤器
 """
 
 import logging
+import os
 import re
-from typing import Any, Dict
+from typing import Any, Dict, Iterable, List, Optional, Sequence
 
 logger = logging.getLogger(__name__)
 
+# 替换文本
+REDACTED = "[REDACTED]"
+
+# 短于此长度的密钥值不做字面替换，避免误伤普通文本
+MIN_SECRET_LENGTH = 6
+
+# 常见令牌格式：(正则, 替换文本)
+TOKEN_PATTERNS = [
+    (
+        re.compile(
+            r"(?i)((?:api[_-]?key|access[_-]?token|refresh[_-]?token|client[_-]?secret|secret[_-]?key"
+            r"|password|passwd)[\"']?\s*[:=]\s*[\"']?)[^\s\"'&,;]{4,}"
+        ),
+        r"\1" + REDACTED,
+    ),
+    (re.compile(r"(?i)\b(bearer\s+)[A-Za-z0-9._~+/\-]{16,}=*"), r"\1" + REDACTED),
+    (re.compile(r"(://)[^/\s:@]+:[^/\s@]+@"), r"\1" + REDACTED + "@"),
+    (re.compile(r"\bsk-[A-Za-z0-9_\-]{16,}"), REDACTED),
+    (re.compile(r"\b(?:gh[pousr]_[A-Za-z0-9]{20,}|github_pat_[A-Za-z0-9_]{20,})"), REDACTED),
+    (re.compile(r"\bxox[abeprs]-[A-Za-z0-9\-]{10,}"), REDACTED),
+    (re.compile(r"\bAKIA[0-9A-Z]{16}\b"), REDACTED),
+    (re.compile(r"\bAIza[0-9A-Za-z_\-]{35}"), REDACTED),
+    (re.compile(r"\bya29\.[0-9A-Za-z_\-]{20,}"), REDACTED),
+    (re.compile(r"\beyJ[A-Za-z0-9_\-]{10,}\.[A-Za-z0-9_\-]{10,}\.[A-Za-z0-9_\-]{10,}"), REDACTED),
+]
+
+# 环境变量名包含这些片段时视为密钥（Tauri 从钥匙串注入的变量等）
+SECRET_ENV_HINTS = ("KEY", "TOKEN", "SECRET", "PASSWORD", "PASSWD", "AUTH", "CREDENTIAL")
+
 
 class LogSanitizer:
     """
@@ -117,3 +154,105 @@ class LogSanitizer:
                     message = message.replace(value_str, sanitized_value)
         
         return message
+
+
+def redact_text(text: str, secrets: Sequence[str] = ()) -> str:
+    """
+    替换文本中的密钥值和常见令牌
+
+    Args:
+        text: 原文本
+        secrets: 需要替换的密钥值（应按长度从长到短排列）
+
+    Returns:
+        脱敏后的文本
+    """
+    for secret in secrets:
+        if secret and secret in text:
+            text = text.replace(secret, REDACTED)
+    for pattern, replacement in TOKEN_PATTERNS:
+        text = pattern.sub(replacement, text)
+    return text
+
+
+def config_secrets(config: Any = None, environ: Optional[Dict[str, str]] = None) -> List[str]:
+    """
+    收集配置和环境变量中的密钥值
+
+    Args:
+        config: Config 对象（或提供 get 方法的对象），为 None 时只收集环境变量
+        environ: 环境变量，默认 os.environ
+
+    Returns:
+        去重后按长度从长到短排列的密钥值（忽略过短的值）
+    """
+    values: List[Any] = []
+    if config is not None:
+        get = config.get
+        for key in ("api_key", "email_password", "email_oauth_client_secret", "http_api_token"):
+            values.append(get(key))
+        values.extend(p.get("api_key") for p in get("profiles") or [] if isinstance(p, dict))
+        values.extend(c.get("password") for c in get("calendars") or [] if isinstance(c, dict))
+        for hook in get("webhooks") or []:
+            if isinstance(hook, dict):
+                values.extend([hook.get("url"), hook.get("secret")])
+    env = os.environ if environ is None else environ
+    # 跳过路径类的值（如 SSH_AUTH_SOCK、XAUTHORITY）
+    values.extend(
+        v for k, v in env.items()
+        if any(h in k.upper() for h in SECRET_ENV_HINTS) and not os.path.isabs(v)
+    )
+
+    secrets = {
+        str(v).strip() for v in values
+        if isinstance(v, str) and len(v.strip()) >= MIN_SECRET_LENGTH
+    }
+    return sorted(secrets, key=len, reverse=True)
+
+
+class RedactionFilter(logging.Filter):
+    """
+    日志脱敏过滤器：格式化后的消息和异常堆栈写出前替换密钥值和常见令牌
+    """
+
+    def __init__(self, secrets: Iterable[str] = ()):
+        super().__init__()
+        self.secrets: List[str] = list(secrets)
+
+    def filter(self, record: logging.LogRecord) -> bool:
+        try:
+            message = record.getMessage()
+        except Exception:
+            return True
+        record.msg = redact_text(message, self.secrets)
+        record.args = None
+        if record.exc_info and not record.exc_text:
+            record.exc_text = logging.Formatter().formatException(record.exc_info)
+        if record.exc_text:
+            record.exc_text = redact_text(record.exc_text, self.secrets)
+        return True
+
+
+def install_redaction(config: Any = None) -> RedactionFilter:
+    """
+    给根日志记录器的所有处理器加上脱敏过滤器（重复调用时更新密钥列表）
+
+    过滤器挂在处理器上，子记录器传播上来的日志同样经过脱敏。
+
+    Args:
+        config: Config 对象，用于收集密钥值
+
+    Returns:
+        使用中的过滤器
+    """
+    secrets = config_secrets(config)
+    handlers = logging.getLogger().handlers
+    existing = next(
+        (f for h in handlers for f in h.filters if isinstance(f, RedactionFilter)), None
+    )
+    redaction = existing or RedactionFilter()
+    redaction.secrets = secrets
+    for handler in handlers:
+        if redaction not in handler.filters:
+            handler.addFilter(redaction)
+    return redaction
@@ -10,6 +10,8 @@ from pathlib import Path
 from datetime import datetime
 from typing import Dict, Any
 
+from agent.tools.log_sanitizer import install_redaction
+
 
 class JSONFormatter(logging.Formatter):
     """
@@ -93,4 +95,7 @@ def setup_logger(
         file_handler.setFormatter(JSONFormatter())
         root_logger.addHandler(file_handler)
     
+    # 写出前替换密钥和常见令牌
+    install_redaction()
+
     logging.info(f"日志系统已配置: 级别={log_level}, 目录={log_dir}")
@@ -0,0 +1,90 @@
+"""
+MCP 工具：用户在设置中配置的 MCP 服务器提供的外部工具
+
+Tauri 启动后连接已启用的 MCP 服务器并缓存工具列表，执行任务时写入 context["mcp_tools"]
+（每项含 server、name、description、input_schema）。规划器据此生成 mcp_call 步骤，
+执行时经 tool_server 以工具名 mcp 请 Tauri 转发 tools/call，结果中的文本内容合并返回。
+
+使用示例:
+    from agent.tools.mcp import format_result, mcp_hint
+
+    hint = mcp_hint(context)  # 规划提示词片段
+    text, is_error = format_result(tool_server.call_tool("mcp", {"server": "github", "tool": "search", "arguments": {}}))
+"""
+
+import json
+from typing import Any, Dict, Optional, Tuple
+
+# 提示词中单个工具参数定义的最大长度
+MAX_SCHEMA_CHARS = 400
+
+# 提示词中最多列出的工具数
+MAX_TOOLS = 50
+
+
+def _describe_params(schema: Any) -> str:
+    """把 inputSchema 压缩为参数说明"""
+    if not isinstance(schema, dict):
+        return "{}"
+    properties = schema.get("properties") or {}
+    required = set(schema.get("required") or [])
+    params = {}
+    for name, prop in properties.items():
+        prop = prop if isinstance(prop, dict) else {}
+        text = prop.get("type", "any")
+        if prop.get("description"):
+            text += f"，{prop['description']}"
+        if name not in required:
+            text += "（可选）"
+        params[name] = text
+    text = json.dumps(params, ensure_ascii=False)
+    if len(text) > MAX_SCHEMA_CHARS:
+        text = text[:MAX_SCHEMA_CHARS] + "..."
+    return text
+
+
+def mcp_hint(context: Optional[Dict[str, Any]]) -> str:
+    """
+    规划提示词片段：列出可用的 MCP 工具及调用方式
+
+    Returns:
+        提示词片段，没有 MCP 工具时返回空字符串
+    """
+    tools = [t for t in (context or {}).get("mcp_tools") or [] if isinstance(t, dict) and t.get("name")]
+    if not tools:
+        return ""
+    lines = []
+    for tool in tools[:MAX_TOOLS]:
+        description = (tool.get("description") or "").strip().splitlines()
+        summary = description[0] if description else ""
+        lines.append(
+            f"- {tool.get('server')} / {tool['name']}: {summary} 参数: {_describe_params(tool.get('input_schema'))}"
+        )
+    if len(tools) > MAX_TOOLS:
+        lines.append(f"- ……另有 {len(tools) - MAX_TOOLS} 个工具未列出")
+    return (
+        "用户配置了以下 MCP 服务器工具（服务器 / 工具名），内置步骤无法完成时可用 mcp_call 步骤调用，"
+        'params: {"server": "服务器", "tool": "工具名", "arguments": {参数}}：\n'
+        + "\n".join(lines)
+    )
+
+
+def format_result(result: Dict[str, Any]) -> Tuple[str, bool]:
+    """
+    整理 tools/call 的结果
+
+    Returns:
+        (合并后的文本内容, 工具是否报告失败)
+    """
+    parts = []
+    for item in (result or {}).get("content") or []:
+        if not isinstance(item, dict):
+            continue
+        if item.get("type") == "text":
+            parts.append(item.get("text") or "")
+        elif item.get("type") == "resource":
+            resource = item.get("resource") or {}
+            parts.append(resource.get("text") or f"[资源] {resource.get('uri', '')}")
+        else:
+            parts.append(f"[{item.get('type', '未知')} 内容]")
+    return "\n".join(parts).strip(), bool((result or {}).get("isError"))
@@ -0,0 +1,146 @@
+"""
+图片文字识别：识别截图、照片中的文字并返回每一行的位置
+
+Tauri 发送 {"cmd":"ocr","id":...,"path":...,"lang":...}，服务用 Tesseract 识别，
+以 ocr_result 事件返回全文和按行合并的文本块（像素坐标与平均置信度），
+便于按位置还原表格等版面。需要系统安装 Tesseract 并 pip install pytesseract pillow。
+
+使用示例:
+    from agent.tools.ocr import ocr_image
+
+    outcome = ocr_image("~/Desktop/截图.png", lang="zh")
+"""
+
+import logging
+from pathlib import Path
+from typing import Any, Callable, Dict, List, Optional
+
+logger = logging.getLogger(__name__)
+
+# 常用语言代码到 Tesseract 语言包的映射
+LANG_ALIASES = {
+    "zh": "chi_sim",
+    "zh-cn": "chi_sim",
+    "zh-tw": "chi_tra",
+    "en": "eng",
+    "ja": "jpn",
+    "ko": "kor",
+}
+
+# 未指定语言时优先使用的组合（中文语言包未安装时只用英文）
+DEFAULT_LANG = "chi_sim+eng"
+
+
+def resolve_lang(lang: Optional[str], installed: Optional[List[str]] = None) -> str:
+    """
+    把 zh、en 等语言代码转为 Tesseract 语言包名，多个语言用 + 连接
+
+    Args:
+        lang: 语言代码，如 "zh"、"en"、"chi_sim+eng"，为空时使用默认组合
+        installed: 已安装的语言包，用于默认组合缺少中文包时退回英文
+    """
+    if not lang or lang == "auto":
+        if installed is not None and "chi_sim" not in installed:
+            return "eng"
+        return DEFAULT_LANG
+    parts = [LANG_ALIASES.get(part.strip().lower(), part.strip()) for part in lang.split("+")]
+    return "+".join(part for part in parts if part)
+
+
+def group_lines(data: Dict[str, List[Any]]) -> List[Dict[str, Any]]:
+    """
+    把 pytesseract.image_to_data 的逐词结果按行合并
+
+    Returns:
+        [{"text": ..., "x": ..., "y": ..., "width": ..., "height": ..., "confidence": ...}]，按出现顺序
+    """
+    lines: Dict[tuple, Dict[str, Any]] = {}
+    for i, word in enumerate(data.get("text", [])):
+        word = (word or "").strip()
+        conf = float(data["conf"][i])
+        if not word or conf < 0:
+            continue
+        key = (data["block_num"][i], data["par_num"][i], data["line_num"][i])
+        left, top = int(data["left"][i]), int(data["top"][i])
+        right, bottom = left + int(data["width"][i]), top + int(data["height"][i])
+        line = lines.get(key)
+        if line is None:
+            lines[key] = {"words": [word], "box": [left, top, right, bottom], "confs": [conf]}
+            continue
+        line["words"].append(word)
+        line["confs"].append(conf)
+        box = line["box"]
+        line["box"] = [min(box[0], left), min(box[1], top), max(box[2], right), max(box[3], bottom)]
+
+    blocks = []
+    for line in lines.values():
+        left, top, right, bottom = line["box"]
+        # 中文词之间不需要空格，含 ASCII 字母数字的词之间保留空格
+        text = ""
+        for word in line["words"]:
+            if text and (text[-1].isascii() and text[-1].isalnum()) and (word[0].isascii() and word[0].isalnum()):
+                text += " "
+            text += word
+        blocks.append({
+            "text": text,
+            "x": left,
+            "y": top,
+            "width": right - left,
+            "height": bottom - top,
+            "confidence": round(sum(line["confs"]) / len(line["confs"]), 1),
+        })
+    return blocks
+
+
+def _run_tesseract(path: str, lang: Optional[str]) -> Dict[str, List[Any]]:
+    """调用 Tesseract 识别，返回逐词结果"""
+    import pytesseract
+    from PIL import Image
+
+    try:
+        installed = pytesseract.get_languages()
+    except Exception:
+        installed = None
+    with Image.open(path) as image:
+        return pytesseract.image_to_data(
+            image,
+            lang=resolve_lang(lang, installed),
+            output_type=pytesseract.Output.DICT,
+        )
+
+
+def ocr_image(
+    path: str,
+    lang: Optional[str] = None,
+    recognizer: Optional[Callable[[str, Optional[str]], Dict[str, List[Any]]]] = None,
+) -> Dict[str, Any]:
+    """
+    识别图片中的文字
+
+    Args:
+        path: 图片路径（支持 ~）
+        lang: 语言代码，如 "zh"、"en"，为空时中英文混合识别
+        recognizer: 返回逐词结果的识别函数（测试用），默认调用 Tesseract
+
+    Returns:
+        {"ok": True, "text": 全文, "blocks": [...]} 或 {"ok": False, "text": "", "blocks": [], "message": ...}
+    """
+    image_path = Path(path).expanduser()
+    if not image_path.is_file():
+        return {"ok": False, "text": "", "blocks": [], "message": f"图片不存在: {path}"}
+
+    try:
+        data = (recognizer or _run_tesseract)(str(image_path), lang)
+    except ImportError:
+        return {
+            "ok": False,
+            "text": "",
+            "blocks": [],
+            "message": "未安装 OCR 依赖，请安装 Tesseract 并执行 pip install pytesseract pillow",
+        }
+    except Exception as e:
+        logger.warning(f"文字识别失败: {e}")
+        return {"ok": False, "text": "", "blocks": [], "message": str(e)}
+
+    blocks = group_lines(data)
+    return {"ok": True, "text": "\n".join(block["text"] for block in blocks), "blocks": blocks}
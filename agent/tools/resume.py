@@ -0,0 +1,79 @@
+"""
+断点续跑：任务因网络错误、限流等临时原因失败后，Tauri 自动重试时从失败的步骤继续
+
+Tauri 在 execute 命令中带上 resume_from_step（已完成的步骤数）和 completed_steps
+（已成功执行的步骤定义），服务执行前把它们并入 context["resume"]，规划器据此
+只规划剩余的步骤，已完成的步骤不会重复执行（例如不会重复发送邮件、重复下载文件）。
+
+使用示例:
+    from agent.tools.resume import apply_resume
+
+    context = apply_resume(context, cmd.get("resume_from_step"), cmd.get("completed_steps"))
+    hint = resume_hint(context)  # 规划提示词片段
+"""
+
+import json
+from typing import Any, Dict, List, Optional
+
+# 提示词中单个步骤定义的最大长度
+MAX_STEP_CHARS = 300
+
+
+def apply_resume(
+    context: Optional[Dict[str, Any]],
+    resume_from_step: Optional[int],
+    completed_steps: Optional[List[Dict[str, Any]]],
+) -> Optional[Dict[str, Any]]:
+    """
+    把续跑信息并入 context
+
+    Args:
+        context: 前端传入的上下文，可为 None
+        resume_from_step: 下一个要执行的步骤序号（从 0 开始），首次执行时为 None
+        completed_steps: 已成功执行的步骤定义
+
+    Returns:
+        合并后的 context；不是续跑时原样返回
+    """
+    if resume_from_step is None:
+        return context
+    steps = [s for s in (completed_steps or []) if isinstance(s, dict)]
+    context = dict(context or {})
+    context["resume"] = {
+        "from_step": max(int(resume_from_step), 0),
+        "completed_steps": steps,
+    }
+    return context
+
+
+def _describe(step: Dict[str, Any]) -> str:
+    text = step.get("description") or json.dumps(
+        {"type": step.get("type"), "params": step.get("params")}, ensure_ascii=False
+    )
+    if len(text) > MAX_STEP_CHARS:
+        text = text[:MAX_STEP_CHARS] + "..."
+    return f"{step.get('type', '?')}: {text}"
+
+
+def resume_hint(context: Optional[Dict[str, Any]]) -> str:
+    """
+    规划提示词片段：告知已完成的步骤，只规划剩余部分
+
+    Returns:
+        提示词片段，不是续跑时返回空字符串
+    """
+    resume = (context or {}).get("resume")
+    if not resume:
+        return ""
+    steps = resume.get("completed_steps") or []
+    if not steps:
+        return (
+            "这是上次因网络或服务临时故障失败后的重试，上次没有成功完成任何步骤，"
+            "请重新规划完整的任务。"
+        )
+    lines = [f"{i}. {_describe(step)}" for i, step in enumerate(steps, 1)]
+    return (
+        f"这是上次因网络或服务临时故障失败后的重试。下列前 {len(steps)} 个步骤已经成功执行，"
+        "其产生的文件和效果都已存在，不要重复这些步骤，只规划完成任务还需要的剩余步骤：\n"
+        + "\n".join(lines)
+    )
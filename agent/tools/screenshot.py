@@ -0,0 +1,100 @@
+"""
+系统截图请求：由 Tauri 在系统层截取全屏、当前窗口或框选区域，返回保存路径
+
+常驻服务在执行任务前通过 set_requester 注册回调，回调以 screenshot_request 事件
+请 Tauri 截图（保存到任务工作目录），再轮询 Tauri 写入的结果文件。
+未注册回调时（单次模式、测试）由调用方自行截图。
+
+使用示例:
+    from agent.tools.screenshot import is_available, request_screenshot
+
+    if is_available():
+        path = request_screenshot("region")
+"""
+
+import json
+import logging
+import time
+from pathlib import Path
+from typing import Any, Callable, Dict, Optional
+
+from agent.tools.exceptions import DeskJarvisError
+
+logger = logging.getLogger(__name__)
+
+# 支持的截图范围：全屏、当前窗口、框选区域
+MODES = ("full", "active_window", "region")
+
+# 回调参数：截图范围，返回截图文件路径
+ScreenshotRequester = Callable[[str], str]
+
+_requester: Optional[ScreenshotRequester] = None
+
+
+class ScreenshotError(DeskJarvisError):
+    """截图失败或被取消"""
+    pass
+
+
+def set_requester(requester: Optional[ScreenshotRequester]) -> None:
+    """注册（或清除）截图回调"""
+    global _requester
+    _requester = requester
+
+
+def is_available() -> bool:
+    """是否可以请求 Tauri 截图"""
+    return _requester is not None
+
+
+def request_screenshot(mode: str = "full") -> str:
+    """
+    请求 Tauri 截图
+
+    Args:
+        mode: 截图范围，full / active_window / region
+
+    Returns:
+        截图文件的绝对路径
+
+    Raises:
+        ScreenshotError: 未注册回调、范围无效、截图失败或被取消
+    """
+    if mode not in MODES:
+        raise ScreenshotError(f"不支持的截图范围: {mode}")
+    if _requester is None:
+        raise ScreenshotError("当前运行方式不支持系统截图")
+    return _requester(mode)
+
+
+def wait_for_response(
+    response_dir: Path,
+    access_id: str,
+    timeout: float = 240,
+    is_cancelled: Optional[Callable[[], bool]] = None,
+) -> str:
+    """
+    轮询 Tauri 写入的截图结果文件 <response_dir>/<access_id>.json
+
+    框选区域需要等待用户操作，超时较长（短于 Tauri 的卡死检测）；超时或任务被取消时抛出 ScreenshotError。
+    """
+    response_file = response_dir / f"{access_id}.json"
+    deadline = time.time() + timeout
+    while time.time() < deadline:
+        if is_cancelled is not None and is_cancelled():
+            raise ScreenshotError("任务已取消")
+        if response_file.exists():
+            try:
+                with open(response_file, "r", encoding="utf-8") as f:
+                    response: Dict[str, Any] = json.load(f)
+                response_file.unlink()
+            except (json.JSONDecodeError, IOError) as e:
+                logger.warning(f"读取截图结果失败: {e}")
+                time.sleep(0.3)
+                continue
+            if response.get("path"):
+                return str(response["path"])
+            raise ScreenshotError(f"截图失败: {response.get('error') or '未知错误'}")
+        time.sleep(0.3)
+
+    raise ScreenshotError(f"等待截图超时: {access_id}")
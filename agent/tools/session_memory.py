@@ -0,0 +1,132 @@
+"""
+会话记忆：同一会话内的任务共享对话历史和最近操作的文件
+
+Tauri 在 execute 命令中带上 session_id，服务执行前把会话记忆并入 context
+（chat_history、created_files、last_created_file，前端已提供的字段不覆盖），
+执行后追加本轮指令与结果。记忆保存在 ~/.deskjarvis/sessions/<session_id>.json，
+多个服务进程共用；删除会话时由 Tauri 删除该文件。
+
+使用示例:
+    from agent.tools.session_memory import apply_session_context, record_turn
+
+    context = apply_session_context(session_id, context)
+    result = agent.execute(instruction, context=context)
+    record_turn(session_id, instruction, result)
+"""
+
+import json
+import logging
+import re
+import time
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+logger = logging.getLogger(__name__)
+
+SESSIONS_DIR = Path.home() / ".deskjarvis" / "sessions"
+
+# 每个会话最多保留的轮数
+MAX_TURNS = 20
+
+# 并入 context 的最近轮数
+CONTEXT_TURNS = 5
+
+_SESSION_ID_RE = re.compile(r"^[A-Za-z0-9_-]+$")
+
+
+def _session_path(session_id: str, base_dir: Optional[Path] = None) -> Path:
+    if not _SESSION_ID_RE.match(session_id or ""):
+        raise ValueError(f"无效的会话 ID: {session_id!r}")
+    return (base_dir or SESSIONS_DIR) / f"{session_id}.json"
+
+
+def load_turns(session_id: str, base_dir: Optional[Path] = None) -> List[Dict[str, Any]]:
+    """读取会话的历史轮次，文件缺失或损坏时返回空列表"""
+    try:
+        data = json.loads(_session_path(session_id, base_dir).read_text(encoding="utf-8"))
+    except FileNotFoundError:
+        return []
+    except (OSError, ValueError) as e:
+        logger.warning(f"读取会话记忆失败: {e}")
+        return []
+    turns = data.get("turns") if isinstance(data, dict) else None
+    return turns if isinstance(turns, list) else []
+
+
+def collect_files(result: Dict[str, Any]) -> List[str]:
+    """从任务结果的步骤中收集操作过的文件路径（按出现顺序去重）"""
+    files: List[str] = []
+    for item in result.get("steps") or []:
+        step_result = (item or {}).get("result") or {}
+        data = step_result.get("data") if isinstance(step_result, dict) else None
+        if not isinstance(data, dict):
+            continue
+        candidates = [data.get("path")] + list(data.get("created_files") or [])
+        for path in candidates:
+            if isinstance(path, str) and path and path not in files:
+                files.append(path)
+    return files
+
+
+def apply_session_context(
+    session_id: str,
+    context: Optional[Dict[str, Any]],
+    base_dir: Optional[Path] = None,
+) -> Dict[str, Any]:
+    """
+    把会话记忆并入 context
+
+    Args:
+        session_id: 会话 ID
+        context: 前端传入的上下文，可为 None
+        base_dir: 会话记忆目录，默认 ~/.deskjarvis/sessions
+
+    Returns:
+        合并后的 context（已有字段不覆盖）
+    """
+    context = dict(context or {})
+    turns = load_turns(session_id, base_dir)[-CONTEXT_TURNS:]
+    if not turns:
+        return context
+
+    if not context.get("chat_history"):
+        history = []
+        for turn in turns:
+            history.append({"role": "user", "content": turn.get("instruction", "")})
+            history.append({"role": "assistant", "content": turn.get("message", "")})
+        context["chat_history"] = history
+
+    files = [f for turn in turns for f in turn.get("files", [])]
+    if files and not context.get("created_files"):
+        context["created_files"] = files
+    if files and not context.get("last_created_file"):
+        context["last_created_file"] = files[-1]
+    return context
+
+
+def record_turn(
+    session_id: str,
+    instruction: str,
+    result: Dict[str, Any],
+    base_dir: Optional[Path] = None,
+) -> None:
+    """追加一轮指令与结果，超出 MAX_TURNS 时丢弃最早的轮次"""
+    try:
+        path = _session_path(session_id, base_dir)
+        turns = load_turns(session_id, base_dir)
+        turns.append({
+            "instruction": instruction,
+            "message": result.get("message", ""),
+            "success": bool(result.get("success")),
+            "files": collect_files(result),
+            "timestamp": time.time(),
+        })
+        path.parent.mkdir(parents=True, exist_ok=True)
+        tmp = path.with_suffix(".tmp")
+        tmp.write_text(
+            json.dumps({"session_id": session_id, "turns": turns[-MAX_TURNS:]}, ensure_ascii=False),
+            encoding="utf-8",
+        )
+        tmp.replace(path)
+    except (OSError, ValueError) as e:
+        logger.warning(f"保存会话记忆失败: {e}")
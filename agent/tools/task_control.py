@@ -0,0 +1,143 @@
+"""
+任务控制：执行中的任务在步骤之间响应暂停、恢复和停止
+
+执行任务期间服务不读取 stdin，Tauri 把控制消息写入
+~/.deskjarvis/task_control/<request_id>.json（{"cmd": "pause" | "resume" | "stop", "id": ...}）。
+执行器调用 context["_check_stop"] 时检查该文件：暂停时发出 paused 事件并阻塞到恢复或停止，
+恢复后发出 resumed 事件。任务结束后删除该文件。
+
+逐步执行（execute 命令带 "interactive": true）时，每个步骤完成后调用 wait_for_next_step：
+发出 step_gate 事件并阻塞，直到 Tauri 写入 resume（continue_task）或 stop（abort_task）。
+
+使用示例:
+    from agent.tools.task_control import wait_for_next_step, wait_while_paused
+
+    stopped = wait_while_paused(request_id, on_paused, on_resumed)
+    aborted = wait_for_next_step(request_id, on_waiting, on_continued)
+"""
+
+import json
+import logging
+import re
+import time
+from pathlib import Path
+from typing import Callable, Optional
+
+logger = logging.getLogger(__name__)
+
+CONTROL_DIR = Path.home() / ".deskjarvis" / "task_control"
+
+# 暂停期间检查控制文件的间隔（秒）
+POLL_INTERVAL = 0.5
+
+_REQUEST_ID_RE = re.compile(r"^[A-Za-z0-9_-]+$")
+
+
+def _control_path(request_id: str, base_dir: Optional[Path] = None) -> Optional[Path]:
+    if not _REQUEST_ID_RE.match(request_id or ""):
+        return None
+    return (base_dir or CONTROL_DIR) / f"{request_id}.json"
+
+
+def read_command(request_id: str, base_dir: Optional[Path] = None) -> Optional[str]:
+    """读取最近一条控制命令，没有时返回 None"""
+    path = _control_path(request_id, base_dir)
+    if path is None:
+        return None
+    try:
+        data = json.loads(path.read_text(encoding="utf-8"))
+    except FileNotFoundError:
+        return None
+    except (OSError, ValueError) as e:
+        logger.warning(f"读取任务控制命令失败: {e}")
+        return None
+    cmd = data.get("cmd") if isinstance(data, dict) else None
+    return cmd if cmd in ("pause", "resume", "stop") else None
+
+
+def wait_while_paused(
+    request_id: str,
+    on_paused: Callable[[], None],
+    on_resumed: Callable[[], None],
+    base_dir: Optional[Path] = None,
+    poll_interval: float = POLL_INTERVAL,
+) -> bool:
+    """
+    处理控制命令：暂停时阻塞到恢复或停止
+
+    Args:
+        request_id: 任务 ID
+        on_paused: 进入暂停时调用（发送 paused 事件）
+        on_resumed: 恢复时调用（发送 resumed 事件）
+        base_dir: 控制文件目录，默认 ~/.deskjarvis/task_control
+        poll_interval: 暂停期间的检查间隔（秒）
+
+    Returns:
+        是否收到停止命令
+    """
+    cmd = read_command(request_id, base_dir)
+    if cmd != "pause":
+        return cmd == "stop"
+
+    logger.info(f"任务 {request_id} 已暂停")
+    on_paused()
+    while cmd == "pause":
+        time.sleep(poll_interval)
+        cmd = read_command(request_id, base_dir)
+    if cmd == "stop":
+        return True
+    logger.info(f"任务 {request_id} 已恢复")
+    on_resumed()
+    return False
+
+
+def wait_for_next_step(
+    request_id: str,
+    on_waiting: Callable[[], None],
+    on_continued: Callable[[], None],
+    base_dir: Optional[Path] = None,
+    poll_interval: float = POLL_INTERVAL,
+) -> bool:
+    """
+    逐步执行：一个步骤完成后阻塞到继续或中止
+
+    之前留下的 pause / resume 命令先清除，只有本次等待开始后写入的 resume 才会继续。
+
+    Args:
+        request_id: 任务 ID
+        on_waiting: 开始等待时调用（发送 step_gate 事件）
+        on_continued: 继续时调用（发送 resumed 事件）
+        base_dir: 控制文件目录，默认 ~/.deskjarvis/task_control
+        poll_interval: 等待期间的检查间隔（秒）
+
+    Returns:
+        是否收到中止（停止）命令
+    """
+    if read_command(request_id, base_dir) == "stop":
+        return True
+    clear(request_id, base_dir)
+
+    logger.info(f"任务 {request_id} 等待继续下一步")
+    on_waiting()
+    cmd = None
+    while cmd not in ("resume", "stop"):
+        time.sleep(poll_interval)
+        cmd = read_command(request_id, base_dir)
+    if cmd == "stop":
+        return True
+    logger.info(f"任务 {request_id} 继续下一步")
+    on_continued()
+    return False
+
+
+def clear(request_id: str, base_dir: Optional[Path] = None) -> None:
+    """任务结束后删除控制文件"""
+    path = _control_path(request_id, base_dir)
+    if path is None:
+        return
+    try:
+        path.unlink()
+    except FileNotFoundError:
+        pass
+    except OSError as e:
+        logger.warning(f"删除任务控制文件失败: {e}")
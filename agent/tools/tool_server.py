@@ -0,0 +1,126 @@
+"""
+内置工具调用：命令执行和常用文件操作交给 Tauri 在 Rust 侧统一执行和审计
+
+常驻服务在执行任务前通过 set_caller 注册回调，回调以 tool_call 事件请 Tauri 执行工具
+（shell、fs.read / fs.write / fs.list / fs.move / fs.delete、读取系统状态的 system.info、读取邮件的 email.fetch、读写日程的 calendar.list / calendar.create 及转发到 MCP 服务器的 mcp），再轮询 Tauri 写入的 tool_result 文件。Rust 侧负责程序白名单、超时、
+输出大小限制、文件操作的沙盒边界和大小上限以及审计日志，Python 不再自行启动 shell。未注册回调时（单次模式、测试）
+工具不可用，调用方应返回失败而不是退回到本地执行（只读取状态的 system.info 除外，见 SystemTools._get_system_info）。
+
+使用示例:
+    from agent.tools.tool_server import call_tool, is_available
+
+    if is_available():
+        output = call_tool("shell", {"cmd": "ls -la", "timeout": 30})
+        print(output["exit_code"], output["stdout"])
+"""
+
+import json
+import logging
+import time
+from pathlib import Path
+from typing import Any, Callable, Dict, Optional
+
+from agent.tools.exceptions import DeskJarvisError
+
+logger = logging.getLogger(__name__)
+
+# Rust 侧支持的工具
+TOOLS = ("shell", "fs.read", "fs.write", "fs.list", "fs.move", "fs.delete", "system.info", "email.fetch", "calendar.list", "calendar.create", "mcp")
+
+# 等待结果的超时（Rust 侧命令超时上限 240 秒，留出余量，短于 Tauri 的卡死检测）
+RESULT_TIMEOUT = 270
+
+# 任务取消后继续等待 Rust 结束命令并写回结果的秒数
+CANCEL_GRACE = 5
+
+# 回调参数：(工具名, 参数)，返回工具输出
+ToolCaller = Callable[[str, Dict[str, Any]], Dict[str, Any]]
+
+_caller: Optional[ToolCaller] = None
+
+
+class ToolCallError(DeskJarvisError):
+    """工具不可用、被拒绝或执行失败"""
+    pass
+
+
+def set_caller(caller: Optional[ToolCaller]) -> None:
+    """注册（或清除）工具调用回调"""
+    global _caller
+    _caller = caller
+
+
+def is_available() -> bool:
+    """是否可以请 Tauri 执行内置工具"""
+    return _caller is not None
+
+
+def call_tool(name: str, args: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    请 Tauri 执行内置工具
+
+    Args:
+        name: 工具名，如 shell
+        args: 工具参数，shell 为 {"cmd": ..., "cwd": 可选, "timeout": 可选秒数}；
+              fs.read 为 {"path"}，fs.write 为 {"path", "content", "encoding": 可选 "base64", "append": 可选}，
+              fs.list 为 {"path", "recursive": 可选}，fs.move 为 {"src", "dst"}，fs.delete 为 {"path", "recursive": 可选}，
+              system.info 为 {"sections": 可选，os/cpu/memory/disk/battery/network 的列表}，
+              email.fetch 为 {"folder", "unseen_only", "since_days", "from", "subject", "limit", "include_body", "max_body_chars"}（均可选），
+              calendar.list 为 {"start", "end", "days", "calendar", "limit"}（均可选），
+              calendar.create 为 {"title", "start", "end": 可选, "duration_minutes": 可选, "all_day": 可选, "location": 可选, "description": 可选, "calendar": 可选}，
+              mcp 为 {"server", "tool", "arguments"}
+
+    Returns:
+        工具输出，shell 为 {"exit_code", "stdout", "stderr", "truncated", "timed_out", "duration_ms"}；
+        fs.read 为 {"path", "content", "encoding": "utf-8" 或 "base64", "size"}；
+        system.info 为 {"os", "cpu", "memory", "disk", "battery", "network"} 中请求的部分；
+        email.fetch 为 {"folder", "total", "emails": [{"uid", "subject", "from", "from_name", "date", "seen", "body", ...}]}；
+        calendar.list 为 {"events": [{"calendar", "uid", "title", "start", "end", "all_day", ...}], "errors"}，
+        calendar.create 为 {"event", "conflicts"}
+
+    Raises:
+        ToolCallError: 未注册回调、工具未知、被拒绝或执行失败
+    """
+    if name not in TOOLS:
+        raise ToolCallError(f"未知的工具: {name}")
+    if _caller is None:
+        raise ToolCallError("当前运行方式不支持执行命令")
+    return _caller(name, args)
+
+
+def wait_for_result(
+    response_dir: Path,
+    call_id: str,
+    timeout: float = RESULT_TIMEOUT,
+    is_cancelled: Optional[Callable[[], bool]] = None,
+    cancel_grace: float = CANCEL_GRACE,
+) -> Dict[str, Any]:
+    """
+    轮询 Tauri 写入的结果文件 <response_dir>/<call_id>.json
+
+    任务被取消时 Rust 侧会结束命令并照常写入结果；超时仍没有结果时抛出 ToolCallError。
+    """
+    response_file = response_dir / f"{call_id}.json"
+    deadline = time.time() + timeout
+    cancelled_at: Optional[float] = None
+    while time.time() < deadline:
+        if response_file.exists():
+            try:
+                with open(response_file, "r", encoding="utf-8") as f:
+                    response: Dict[str, Any] = json.load(f)
+                response_file.unlink()
+            except (json.JSONDecodeError, IOError) as e:
+                logger.warning(f"读取工具结果失败: {e}")
+                time.sleep(0.1)
+                continue
+            if response.get("ok"):
+                return response.get("output") or {}
+            raise ToolCallError(response.get("error") or "工具执行失败")
+        # 取消后再等一会儿，让 Rust 结束命令并写回结果
+        if is_cancelled is not None and cancelled_at is None and is_cancelled():
+            cancelled_at = time.time()
+        if cancelled_at is not None and time.time() - cancelled_at > cancel_grace:
+            raise ToolCallError("任务已取消")
+        time.sleep(0.1)
+
+    raise ToolCallError(f"等待工具结果超时: {call_id}")
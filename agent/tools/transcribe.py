@@ -0,0 +1,95 @@
+"""
+语音转写：把 Tauri 录制的 WAV 文件转成文字，用于预填指令输入框
+
+Tauri 录音结束后发送 {"cmd":"transcribe","id":...,"path":...,"language":...}，
+服务用本地已安装的 Whisper 实现转写并以 transcribe_result 事件返回。
+优先使用 faster-whisper，其次 openai-whisper；模型在第一次转写时加载并缓存。
+
+使用示例:
+    from agent.tools.transcribe import transcribe_file
+
+    outcome = transcribe_file("/tmp/deskjarvis_voice/voice_1.wav", language="zh")
+"""
+
+import logging
+import os
+from pathlib import Path
+from typing import Any, Callable, Dict, Optional
+
+logger = logging.getLogger(__name__)
+
+# 模型大小，可用环境变量覆盖（tiny / base / small / medium / large-v3）
+MODEL_ENV = "DESKJARVIS_WHISPER_MODEL"
+DEFAULT_MODEL = "base"
+
+# 已加载的转写函数：(音频路径, 语言) -> 文字
+_transcriber: Optional[Callable[[str, Optional[str]], str]] = None
+
+
+def _load_transcriber() -> Callable[[str, Optional[str]], str]:
+    """加载本地 Whisper 实现，都未安装时抛出 RuntimeError"""
+    model_name = os.environ.get(MODEL_ENV) or DEFAULT_MODEL
+    try:
+        from faster_whisper import WhisperModel
+
+        model = WhisperModel(model_name, device="auto", compute_type="int8")
+
+        def run_faster(path: str, language: Optional[str]) -> str:
+            segments, _ = model.transcribe(path, language=language)
+            return "".join(segment.text for segment in segments)
+
+        logger.info(f"语音转写使用 faster-whisper（{model_name}）")
+        return run_faster
+    except ImportError:
+        pass
+
+    try:
+        import whisper
+
+        model = whisper.load_model(model_name)
+
+        def run_whisper(path: str, language: Optional[str]) -> str:
+            return model.transcribe(path, language=language)["text"]
+
+        logger.info(f"语音转写使用 openai-whisper（{model_name}）")
+        return run_whisper
+    except ImportError:
+        pass
+
+    raise RuntimeError("未安装语音识别模型，请执行 pip install faster-whisper")
+
+
+def transcribe_file(
+    path: str,
+    language: Optional[str] = None,
+    transcriber: Optional[Callable[[str, Optional[str]], str]] = None,
+) -> Dict[str, Any]:
+    """
+    转写音频文件
+
+    Args:
+        path: WAV 文件路径
+        language: 语言代码（如 zh、en），None 或 "auto" 时自动识别
+        transcriber: 指定转写函数（测试用），默认加载本地 Whisper
+
+    Returns:
+        {"ok": True, "text": ...} 或 {"ok": False, "text": "", "message": ...}
+    """
+    global _transcriber
+
+    if not Path(path).is_file():
+        return {"ok": False, "text": "", "message": f"音频文件不存在: {path}"}
+    if language in ("", "auto"):
+        language = None
+
+    try:
+        if transcriber is None:
+            if _transcriber is None:
+                _transcriber = _load_transcriber()
+            transcriber = _transcriber
+        text = transcriber(path, language).strip()
+    except Exception as e:
+        logger.warning(f"语音转写失败: {e}")
+        return {"ok": False, "text": "", "message": str(e)}
+
+    return {"ok": True, "text": text}
@@ -0,0 +1,145 @@
+"""
+LLM 用量上报：规划器每次调用模型后上报 token 用量和调用结果
+
+常驻服务在执行任务前通过 set_reporter 注册回调，将用量以 usage 事件发送给 Tauri，
+由 Rust 侧统计费用并执行单任务费用上限；通过 set_call_reporter 注册的回调以 api_call
+事件上报每次调用的耗时与失败原因，供 Tauri 统计各提供商的健康状况；通过 set_prompt_reporter
+注册的回调以 prompt 事件上报完整的提示词与响应，由 Tauri 写入任务的提示词日志。
+未注册回调时上报为空操作。
+
+使用示例:
+    from agent.tools.usage import report_prompt, report_usage, track_call
+
+    with track_call(model):
+        response = client.messages.create(...)
+    report_usage(model, response)
+    report_prompt(model, messages, response)
+"""
+
+import logging
+import time
+from contextlib import contextmanager
+from typing import Any, Callable, Dict, Iterator, List, Optional
+
+logger = logging.getLogger(__name__)
+
+UsageReporter = Callable[[Dict[str, Any]], None]
+
+_reporter: Optional[UsageReporter] = None
+_call_reporter: Optional[UsageReporter] = None
+_prompt_reporter: Optional[UsageReporter] = None
+
+# 失败原因的最大长度
+MAX_ERROR_CHARS = 300
+
+
+def set_reporter(reporter: Optional[UsageReporter]) -> None:
+    """注册（或清除）用量回调"""
+    global _reporter
+    _reporter = reporter
+
+
+def set_call_reporter(reporter: Optional[UsageReporter]) -> None:
+    """注册（或清除）调用结果回调"""
+    global _call_reporter
+    _call_reporter = reporter
+
+
+def set_prompt_reporter(reporter: Optional[UsageReporter]) -> None:
+    """注册（或清除）提示词回调，任务关闭提示词日志时不注册"""
+    global _prompt_reporter
+    _prompt_reporter = reporter
+
+
+def extract_usage(response: Any) -> Optional[Dict[str, int]]:
+    """
+    从模型响应中提取 token 用量
+
+    兼容 Anthropic（input_tokens/output_tokens）与 OpenAI 兼容接口（prompt_tokens/completion_tokens）。
+
+    Returns:
+        {"input_tokens": ..., "output_tokens": ...}，响应不含用量时返回 None
+    """
+    usage = getattr(response, "usage", None)
+    if usage is None:
+        return None
+
+    input_tokens = getattr(usage, "input_tokens", None)
+    output_tokens = getattr(usage, "output_tokens", None)
+    if input_tokens is None and output_tokens is None:
+        input_tokens = getattr(usage, "prompt_tokens", None)
+        output_tokens = getattr(usage, "completion_tokens", None)
+    if input_tokens is None and output_tokens is None:
+        return None
+
+    return {
+        "input_tokens": int(input_tokens or 0),
+        "output_tokens": int(output_tokens or 0),
+    }
+
+
+def report_usage(model: str, response: Any) -> None:
+    """上报一次模型调用的用量（回调异常不影响调用方）"""
+    if _reporter is None:
+        return
+    usage = extract_usage(response)
+    if usage is None:
+        return
+    try:
+        _reporter({"model": model, **usage})
+    except Exception as e:
+        logger.warning(f"上报用量失败: {e}")
+
+
+def extract_text(response: Any) -> Optional[str]:
+    """
+    从模型响应中提取文本
+
+    兼容 Anthropic（content[].text）与 OpenAI 兼容接口（choices[0].message.content）。
+    """
+    content = getattr(response, "content", None)
+    if isinstance(content, list):
+        texts = [getattr(block, "text", None) for block in content]
+        return "".join(t for t in texts if isinstance(t, str))
+    choices = getattr(response, "choices", None)
+    if choices:
+        message = getattr(choices[0], "message", None)
+        text = getattr(message, "content", None)
+        if isinstance(text, str):
+            return text
+    return None
+
+
+def report_prompt(model: str, messages: List[Dict[str, Any]], response: Any) -> None:
+    """上报一次模型调用的提示词与响应（回调异常不影响调用方）"""
+    if _prompt_reporter is None:
+        return
+    try:
+        _prompt_reporter({"model": model, "messages": messages, "response": extract_text(response)})
+    except Exception as e:
+        logger.warning(f"上报提示词失败: {e}")
+
+
+def report_call(model: str, latency_ms: int, error: Optional[str] = None) -> None:
+    """上报一次模型调用的耗时与结果（回调异常不影响调用方）"""
+    if _call_reporter is None:
+        return
+    event: Dict[str, Any] = {"model": model, "latency_ms": latency_ms, "ok": error is None}
+    if error is not None:
+        event["error"] = error[:MAX_ERROR_CHARS]
+    try:
+        _call_reporter(event)
+    except Exception as e:
+        logger.warning(f"上报调用结果失败: {e}")
+
+
+@contextmanager
+def track_call(model: str) -> Iterator[None]:
+    """计时包裹一次模型调用，结束后上报耗时；抛出的异常作为失败原因上报后原样抛出"""
+    start = time.monotonic()
+    try:
+        yield
+    except Exception as e:
+        report_call(model, int((time.monotonic() - start) * 1000), f"{type(e).__name__}: {e}")
+        raise
+    report_call(model, int((time.monotonic() - start) * 1000))
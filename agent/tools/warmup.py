@@ -0,0 +1,52 @@
+"""
+预热：启动后提前加载嵌入模型、记忆库等重资源，避免第一个任务变慢
+
+Tauri 在服务 ready 后（按配置）或用户开始输入时发送 warmup 命令，服务依次执行
+DeskJarvisAgent.warmup_steps() 中的加载函数并以 warmup_ack 事件返回每一项的耗时与结果。
+已加载的资源再次预热时立即返回；单项失败不影响其他项。
+
+使用示例:
+    from agent.tools.warmup import run_warmup
+
+    report = run_warmup(agent.warmup_steps())
+"""
+
+import logging
+import time
+from typing import Any, Callable, Dict, List, Tuple
+
+logger = logging.getLogger(__name__)
+
+WarmupStep = Tuple[str, Callable[[], Any]]
+
+# 失败原因的最大长度
+MAX_ERROR_CHARS = 300
+
+
+def run_warmup(steps: List[WarmupStep]) -> Dict[str, Any]:
+    """
+    依次执行预热步骤
+
+    Args:
+        steps: (名称, 加载函数) 列表，加载函数抛出异常视为失败
+
+    Returns:
+        {"elapsed_ms": ..., "steps": [{"name": ..., "ok": ..., "elapsed_ms": ..., "error"?: ...}]}
+    """
+    start = time.monotonic()
+    results = []
+    for name, load in steps:
+        step_start = time.monotonic()
+        result: Dict[str, Any] = {"name": name, "ok": True}
+        try:
+            load()
+        except Exception as e:
+            logger.warning(f"预热 {name} 失败: {e}")
+            result["ok"] = False
+            result["error"] = f"{type(e).__name__}: {e}"[:MAX_ERROR_CHARS]
+        result["elapsed_ms"] = int((time.monotonic() - step_start) * 1000)
+        results.append(result)
+
+    elapsed_ms = int((time.monotonic() - start) * 1000)
+    logger.info(f"预热完成，耗时 {elapsed_ms}ms")
+    return {"elapsed_ms": elapsed_ms, "steps": results}
@@ -0,0 +1,194 @@
+//! 前台窗口上下文：任务 context 中带 "capture_active_window": true 时，把用户切到 DeskJarvis
+//! 之前所在窗口的应用名、标题和选中文字写入 context.active_window，
+//! 让"总结我现在看的这篇文章"这类指令知道"这篇"指什么
+//!
+//! 主窗口失去焦点期间定时记录前台窗口（不记录 DeskJarvis 自身），召唤快捷面板时也会记录。
+//! 选中文字在执行任务时读取：macOS 通过辅助功能读取该应用焦点控件的 AXSelectedText
+//! （需要授予辅助功能权限），Linux 读取 X11 PRIMARY 选区（xclip 或 xsel）。
+//! Windows 暂不支持读取前台窗口。配置 active_window_tracking 为 false 时不记录。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config;
+use crate::history::now_millis;
+use crate::project_context::{self, FrontWindow};
+
+/// 主窗口失去焦点期间记录前台窗口的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// 失去焦点后第一次记录前的等待（等切换动画结束）
+const FIRST_POLL_DELAY: Duration = Duration::from_millis(800);
+
+/// 超过这个时间的记录不再带入任务（毫秒）
+const MAX_AGE_MS: u64 = 30 * 60 * 1000;
+
+/// 带入 context 的选中文字最大长度（字符）
+const MAX_SELECTED_CHARS: usize = 20_000;
+
+/// context 中请求带入前台窗口的开关
+const CAPTURE_FLAG: &str = "capture_active_window";
+
+/// 最近一次记录的前台窗口
+static LAST: Mutex<Option<ActiveWindow>> = Mutex::new(None);
+
+/// 主窗口失去焦点、正在定时记录
+static TRACKING: AtomicBool = AtomicBool::new(false);
+
+/// 记录的前台窗口
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveWindow {
+    #[serde(skip)]
+    pid: u32,
+    pub app_name: String,
+    pub window_title: String,
+    /// 记录时间（毫秒时间戳）
+    pub captured_at: u64,
+}
+
+fn tracking_enabled() -> bool {
+    config::load_config()
+        .ok()
+        .and_then(|c| c.active_window_tracking)
+        .unwrap_or(true)
+}
+
+/// 前台应用的进程名（xdotool 只给出进程 ID 时用于补全应用名）
+fn process_name(pid: u32) -> String {
+    let pid = sysinfo::Pid::from_u32(pid);
+    let mut system = sysinfo::System::new();
+    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+    system
+        .process(pid)
+        .map(|p| p.name().to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// 记录前台窗口（DeskJarvis 自身除外）
+pub fn record(front: &FrontWindow) {
+    if front.pid == std::process::id() {
+        return;
+    }
+    let app_name = if front.app_name.is_empty() {
+        process_name(front.pid)
+    } else {
+        front.app_name.clone()
+    };
+    if let Ok(mut last) = LAST.lock() {
+        *last = Some(ActiveWindow {
+            pid: front.pid,
+            app_name,
+            window_title: front.title.clone(),
+            captured_at: now_millis(),
+        });
+    }
+}
+
+/// 主窗口焦点变化：失去焦点时开始定时记录前台窗口，重新获得焦点时停止
+pub fn on_main_focus_changed(focused: bool) {
+    if focused {
+        TRACKING.store(false, Ordering::SeqCst);
+        return;
+    }
+    if !tracking_enabled() || TRACKING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tauri::async_runtime::spawn(async {
+        tokio::time::sleep(FIRST_POLL_DELAY).await;
+        while TRACKING.load(Ordering::SeqCst) {
+            if let Ok(Some(front)) = tauri::async_runtime::spawn_blocking(project_context::front_window).await {
+                record(&front);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(target_os = "macos")]
+fn selected_text(pid: u32) -> Option<String> {
+    let script = format!(
+        r#"tell application "System Events"
+    set p to first application process whose unix id is {}
+    return value of attribute "AXSelectedText" of (value of attribute "AXFocusedUIElement" of p)
+end tell"#,
+        pid
+    );
+    let output = std::process::Command::new("osascript")
+        .args(["-e", &script])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).trim_end_matches('\n').to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn selected_text(_pid: u32) -> Option<String> {
+    [("xclip", &["-o", "-selection", "primary"][..]), ("xsel", &["-o", "-p"][..])]
+        .iter()
+        .find_map(|(program, args)| {
+            std::process::Command::new(program)
+                .args(*args)
+                .output()
+                .ok()
+                .filter(|o| o.status.success())
+                .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        })
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn selected_text(_pid: u32) -> Option<String> {
+    None
+}
+
+/// context 带 capture_active_window 时写入 context.active_window（没有可用记录时只去掉开关）
+pub async fn apply_context(context: Option<Value>) -> Option<Value> {
+    let mut context = context?;
+    let requested = context
+        .as_object_mut()
+        .and_then(|map| map.remove(CAPTURE_FLAG))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if !requested {
+        return Some(context);
+    }
+    let Some(window) = LAST
+        .lock()
+        .ok()
+        .and_then(|last| last.clone())
+        .filter(|w| now_millis().saturating_sub(w.captured_at) <= MAX_AGE_MS)
+    else {
+        eprintln!("[Tauri] ⚠️ 没有最近的前台窗口记录，任务不带前台窗口上下文");
+        return Some(context);
+    };
+
+    let pid = window.pid;
+    let selected = tauri::async_runtime::spawn_blocking(move || selected_text(pid))
+        .await
+        .ok()
+        .flatten()
+        .filter(|t| !t.trim().is_empty())
+        .map(|t| t.chars().take(MAX_SELECTED_CHARS).collect::<String>());
+    let mut value = serde_json::to_value(&window).unwrap_or(Value::Null);
+    if let (Some(map), Some(text)) = (value.as_object_mut(), selected) {
+        map.insert("selected_text".to_string(), Value::String(text));
+    }
+    eprintln!("[Tauri] 🪟 带入前台窗口上下文: {} - {}", window.app_name, window.window_title);
+    if let Some(map) = context.as_object_mut() {
+        map.insert("active_window".to_string(), value);
+    }
+    Some(context)
+}
+
+/// 最近一次记录的前台窗口（供前端显示将要带入的内容）
+#[tauri::command]
+pub async fn get_active_window() -> Result<Option<ActiveWindow>, String> {
+    Ok(LAST
+        .lock()
+        .ok()
+        .and_then(|last| last.clone())
+        .filter(|w| now_millis().saturating_sub(w.captured_at) <= MAX_AGE_MS))
+}
@@ -0,0 +1,380 @@
+//! Python 服务 stdout 事件的类型定义，Rust 与前端共用
+//!
+//! execute_via_server 把每一行解析为 AgentEvent，形状不符的事件不再转发，
+//! 改发 task-warning 提示。前端类型由 ts-rs 生成到 src/types/bindings/
+//! （`cargo test export_bindings` 重新生成），修改本文件后需一并更新。
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::screenshot::ScreenshotMode;
+use crate::{redaction, TaskResult};
+
+/// Python 服务输出的一条事件，按 type 字段区分
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub enum AgentEvent {
+    /// 服务初始化完成
+    Ready {
+        timestamp: Option<f64>,
+        /// 启动耗时（秒）
+        startup_time: Option<f64>,
+        /// 协商的 stdout 协议版本，旧版服务不带该字段
+        protocol: Option<u32>,
+    },
+    /// ping 命令的应答
+    Pong { id: String, timestamp: Option<f64> },
+    /// stop 命令的应答
+    StopAck { id: String, timestamp: Option<f64> },
+    /// shutdown 命令的应答
+    ShutdownAck { id: Option<String>, timestamp: Option<f64> },
+    /// validate 命令的结果
+    ValidateResult {
+        id: String,
+        timestamp: Option<f64>,
+        ok: bool,
+        message: String,
+    },
+    /// reload_config 命令的应答
+    ReloadAck {
+        id: String,
+        timestamp: Option<f64>,
+        provider: Option<String>,
+        model: Option<String>,
+    },
+    /// warmup 命令的应答
+    WarmupAck(WarmupAckEvent),
+    /// transcribe 命令的结果
+    TranscribeResult {
+        id: String,
+        timestamp: Option<f64>,
+        ok: bool,
+        text: String,
+        message: Option<String>,
+    },
+    /// ocr 命令的结果
+    OcrResult {
+        id: String,
+        timestamp: Option<f64>,
+        ok: bool,
+        text: String,
+        #[serde(default)]
+        blocks: Vec<OcrBlock>,
+        message: Option<String>,
+    },
+    /// 规划阶段的进度
+    #[serde(rename = "thinking")]
+    Progress {
+        id: Option<String>,
+        timestamp: Option<f64>,
+        data: ProgressData,
+    },
+    /// 步骤开始执行
+    #[serde(rename = "executing")]
+    Step {
+        id: Option<String>,
+        timestamp: Option<f64>,
+        data: StepData,
+    },
+    /// 步骤执行成功
+    #[serde(rename = "success")]
+    StepSucceeded {
+        id: Option<String>,
+        timestamp: Option<f64>,
+        data: StepData,
+    },
+    /// LLM 流式输出增量
+    Stream { id: Option<String>, delta: String },
+    /// 单次模型调用的用量
+    Usage(UsageEvent),
+    /// 单次模型调用的耗时与结果
+    ApiCall(ApiCallEvent),
+    /// 单次模型调用的完整提示词与响应（见 prompt_log）
+    Prompt(PromptEvent),
+    /// 修改类步骤执行前的审批请求
+    ConfirmationRequest(ConfirmationRequestEvent),
+    /// 写沙盒以外的路径前的审批请求
+    FileAccessRequest(FileAccessRequestEvent),
+    /// 请求在系统层截图（见 screenshot）
+    ScreenshotRequest(ScreenshotRequestEvent),
+    /// 请求 Rust 侧执行内置工具（见 tool_server）
+    ToolCall(ToolCallEvent),
+    /// 需要用户填写的输入（登录、验证码等）
+    #[serde(rename = "request_input")]
+    UserInputRequest {
+        id: Option<String>,
+        timestamp: Option<f64>,
+        data: UserInputRequestData,
+    },
+    /// 等待用户输入期间的心跳
+    WaitingForInput {
+        id: Option<String>,
+        timestamp: Option<f64>,
+        data: WaitingForInputData,
+    },
+    /// 任务已在步骤之间暂停（见 task_control）
+    Paused { id: Option<String>, timestamp: Option<f64> },
+    /// 暂停的任务已恢复（逐步执行时也表示已继续下一步）
+    Resumed { id: Option<String>, timestamp: Option<f64> },
+    /// 逐步执行时一个步骤已完成，等待继续或中止（见 task_control）
+    StepGate(StepGateEvent),
+    /// 任务最终结果
+    Result {
+        id: Option<String>,
+        timestamp: Option<f64>,
+        data: TaskResult,
+    },
+    /// 步骤失败（data）或服务级错误（message）
+    Error {
+        id: Option<String>,
+        timestamp: Option<f64>,
+        message: Option<String>,
+        data: Option<StepData>,
+    },
+}
+
+/// thinking 事件的数据
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct ProgressData {
+    /// 规划阶段，如 planning、reflecting、multi_agent
+    #[serde(default)]
+    pub phase: String,
+    /// 进度摘要（最多 50 字符）
+    #[serde(default)]
+    pub summary: String,
+}
+
+/// 步骤事件的数据（executing、success、error）
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct StepData {
+    pub description: Option<String>,
+    /// 失败原因，仅 error 事件有
+    pub message: Option<String>,
+    pub step_index: Option<u32>,
+    pub total_steps: Option<u32>,
+}
+
+/// usage 事件
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct UsageEvent {
+    pub id: Option<String>,
+    pub timestamp: Option<f64>,
+    pub model: String,
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+}
+
+/// api_call 事件
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct ApiCallEvent {
+    pub id: Option<String>,
+    pub timestamp: Option<f64>,
+    pub provider: String,
+    pub model: String,
+    pub latency_ms: u64,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+/// warmup_ack 事件中一项资源的预热结果
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct WarmupStep {
+    /// 资源名称，如 embedding_model、memory
+    pub name: String,
+    pub ok: bool,
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
+}
+
+/// warmup_ack 事件
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct WarmupAckEvent {
+    pub id: String,
+    pub timestamp: Option<f64>,
+    /// 预热总耗时（毫秒）
+    pub elapsed_ms: u64,
+    #[serde(default)]
+    pub steps: Vec<WarmupStep>,
+}
+
+/// ocr_result 事件中的一行文字及其位置（图片像素坐标）
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct OcrBlock {
+    pub text: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    /// 平均置信度（0~100）
+    pub confidence: f64,
+}
+
+/// prompt 事件中的一条消息
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct PromptMessage {
+    /// system、user 或 assistant
+    pub role: String,
+    pub content: String,
+}
+
+/// prompt 事件，也是提示词日志中的一条记录
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct PromptEvent {
+    pub id: Option<String>,
+    pub timestamp: Option<f64>,
+    pub model: String,
+    #[serde(default)]
+    pub messages: Vec<PromptMessage>,
+    /// 模型响应文本，无法提取时为空
+    pub response: Option<String>,
+    /// 写入日志时是否截断过内容
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// confirmation_request 事件
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct ConfirmationRequestEvent {
+    pub id: Option<String>,
+    pub timestamp: Option<f64>,
+    pub access_id: String,
+    pub capability: String,
+    pub step_type: String,
+    /// 脱敏后的步骤参数摘要
+    #[serde(default)]
+    pub summary: String,
+}
+
+/// step_gate 事件
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct StepGateEvent {
+    pub id: Option<String>,
+    pub timestamp: Option<f64>,
+    /// 刚完成的步骤序号（从 0 开始）
+    pub step_index: u32,
+    pub total_steps: u32,
+    pub description: Option<String>,
+}
+
+/// file_access_request 事件
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct FileAccessRequestEvent {
+    pub id: Option<String>,
+    pub timestamp: Option<f64>,
+    pub access_id: String,
+    pub path: String,
+    /// write 或 append
+    pub operation: String,
+}
+
+/// screenshot_request 事件
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct ScreenshotRequestEvent {
+    pub id: Option<String>,
+    pub timestamp: Option<f64>,
+    pub access_id: String,
+    #[serde(default)]
+    pub mode: ScreenshotMode,
+}
+
+/// tool_call 事件
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct ToolCallEvent {
+    pub id: Option<String>,
+    pub timestamp: Option<f64>,
+    pub call_id: String,
+    /// 工具名，如 shell
+    pub name: String,
+    #[serde(default)]
+    #[ts(type = "Record<string, any>")]
+    pub args: serde_json::Value,
+}
+
+/// request_input 事件的数据
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct UserInputRequestData {
+    pub id: String,
+    /// 输入类型，如 login、captcha、text
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub title: String,
+    pub message: Option<String>,
+    #[serde(default)]
+    #[ts(type = "Array<any>")]
+    pub fields: Vec<serde_json::Value>,
+    #[serde(rename = "captchaImage")]
+    pub captcha_image: Option<String>,
+}
+
+/// waiting_for_input 事件的数据
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct WaitingForInputData {
+    pub request_id: String,
+    /// 已等待秒数
+    pub elapsed: u64,
+    /// 剩余秒数
+    pub remaining: u64,
+}
+
+/// task-warning 事件负载：服务输出了无法识别的事件
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub struct EventWarning {
+    pub request_id: String,
+    /// 原始事件的 type 字段，缺失时为空
+    pub event_type: String,
+    pub message: String,
+}
+
+impl AgentEvent {
+    /// 解析一行 stdout；非 JSON 行返回 Ok(None)，形状不符时返回警告
+    pub fn parse(line: &str, request_id: &str) -> Result<Option<AgentEvent>, EventWarning> {
+        let value: serde_json::Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(_) => return Ok(None),
+        };
+        let event_type = value
+            .get("type")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        serde_json::from_value(value).map(Some).map_err(|e| EventWarning {
+            request_id: request_id.to_string(),
+            event_type,
+            // serde 的错误信息可能带有原始字段值
+            message: redaction::redact(&format!("无法识别的服务事件: {}", e)),
+        })
+    }
+
+    /// 是否作为 task-progress 转发给前端
+    pub fn is_progress(&self) -> bool {
+        matches!(
+            self,
+            AgentEvent::Progress { .. }
+                | AgentEvent::Step { .. }
+                | AgentEvent::StepSucceeded { .. }
+                | AgentEvent::UserInputRequest { .. }
+                | AgentEvent::WaitingForInput { .. }
+                | AgentEvent::Error { .. }
+        )
+    }
+}
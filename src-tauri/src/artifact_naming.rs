@@ -0,0 +1,117 @@
+//! 产物命名：Agent 覆盖已有文件前，把旧文件按命名规则改名保留
+//!
+//! Python 写文件前都会发送 file_access_request（沙盒内也不例外）。获准的 write 操作
+//! 指向已存在的文件时，旧文件按配置 artifact_naming.pattern 改名（默认
+//! "{stem}_{date}_{time}{ext}"，时间取旧文件的修改时间），新内容仍写到原路径。
+//! 改名后的名称已存在时依次追加 _2、_3…，不会覆盖任何版本。
+
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// 同名版本最多尝试的序号
+const MAX_VERSIONS: u32 = 1000;
+
+/// 产物命名配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactNaming {
+    /// 覆盖已有文件前是否保留旧版本
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 旧版本的文件名模板，可用 {stem} {ext} {date} {time} {task_id}
+    #[serde(default = "default_pattern")]
+    pub pattern: String,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_pattern() -> String {
+    "{stem}_{date}_{time}{ext}".to_string()
+}
+
+impl Default for ArtifactNaming {
+    fn default() -> Self {
+        ArtifactNaming {
+            enabled: default_enabled(),
+            pattern: default_pattern(),
+        }
+    }
+}
+
+/// 按模板生成文件名（模板中的路径分隔符替换为 "_"，避免写到其他目录）
+fn render(pattern: &str, path: &Path, task_id: &str, time: DateTime<Local>) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let task_id = task_id.replace(['/', '\\'], "_");
+    pattern
+        .replace("{stem}", &stem)
+        .replace("{ext}", &ext)
+        .replace("{date}", &time.format("%Y%m%d").to_string())
+        .replace("{time}", &time.format("%H%M%S").to_string())
+        .replace("{task_id}", &task_id)
+        .replace(['/', '\\'], "_")
+}
+
+/// 在 dir 中找一个不存在的文件名：name 本身可用则直接使用，否则在扩展名前追加 _2、_3…
+pub fn available_path(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return Ok(candidate);
+    }
+    let named = Path::new(name);
+    let stem = named
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = named
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (2..=MAX_VERSIONS)
+        .map(|n| dir.join(format!("{}_{}{}", stem, n, ext)))
+        .find(|p| !p.exists())
+        .ok_or_else(|| format!("同名版本过多，无法保留旧文件: {}", name))
+}
+
+/// 旧文件按规则改名保留，返回改名后的路径；路径不是已存在的文件或未启用时返回 None
+pub fn preserve_existing(
+    path: &Path,
+    task_id: &str,
+    naming: &ArtifactNaming,
+) -> Result<Option<PathBuf>, String> {
+    if !naming.enabled || !path.is_file() {
+        return Ok(None);
+    }
+    let Some(dir) = path.parent() else {
+        return Ok(None);
+    };
+    let modified = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(DateTime::<Local>::from)
+        .unwrap_or_else(|_| Local::now());
+    let pattern = if naming.pattern.trim().is_empty() {
+        default_pattern()
+    } else {
+        naming.pattern.clone()
+    };
+    let name = render(&pattern, path, task_id, modified);
+    if name.is_empty() || dir.join(&name) == path {
+        return Ok(None);
+    }
+    let target = available_path(dir, &name)?;
+    std::fs::rename(path, &target).map_err(|e| format!("保留旧版本失败: {}", e))?;
+    eprintln!(
+        "[Tauri] 🗂 {} 将被覆盖，旧版本已保存为 {}",
+        path.display(),
+        target.display()
+    );
+    Ok(Some(target))
+}
@@ -0,0 +1,194 @@
+//! 拖入文件：把拖到主窗口的文件复制进沙盒，并带入下一个任务的 context
+//!
+//! 主窗口收到文件拖放后，文件复制到 sandbox/attachments/<批次>/ 下（目录和超过大小上限的
+//! 文件不复制，只记录原路径），以 files-attached 事件通知前端。前端执行下一个任务时，
+//! execute_task 把待用附件写入 context.attached_files 并清空列表，
+//! "把我拖进来的这几个 PDF 合并"无需再说明文件位置。
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{Emitter, Window};
+
+use crate::{history, sandbox};
+
+/// 超过该大小的文件不复制，直接使用原路径
+const MAX_COPY_BYTES: u64 = 200 * 1024 * 1024;
+
+/// 等待带入下一个任务的附件
+static PENDING: Mutex<Vec<AttachedFile>> = Mutex::new(Vec::new());
+
+/// 拖入的文件
+#[derive(Debug, Clone, Serialize)]
+pub struct AttachedFile {
+    pub name: String,
+    /// Agent 使用的路径：复制到沙盒后的路径，未复制时为原路径
+    pub path: String,
+    pub original_path: String,
+    pub size: u64,
+    pub mime: String,
+    pub is_dir: bool,
+    /// 是否已复制到沙盒（false 表示直接引用原文件）
+    pub copied: bool,
+}
+
+/// 按扩展名推断 MIME 类型
+fn mime_of(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "pdf" => "application/pdf",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xls" => "application/vnd.ms-excel",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "html" | "htm" => "text/html",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// 目标目录中不重名的路径（重名时追加序号）
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let path = Path::new(name);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(name);
+    let ext = path.extension().and_then(|e| e.to_str());
+    (1..)
+        .map(|i| match ext {
+            Some(ext) => dir.join(format!("{}_{}.{}", stem, i, ext)),
+            None => dir.join(format!("{}_{}", stem, i)),
+        })
+        .find(|p| !p.exists())
+        .unwrap_or(candidate)
+}
+
+/// 复制（或引用）一个拖入的路径
+fn attach(source: &Path, dir: &Path) -> Result<AttachedFile, String> {
+    let meta = std::fs::metadata(source)
+        .map_err(|e| format!("读取拖入文件失败 {}: {}", source.display(), e))?;
+    let name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("无效的文件路径: {}", source.display()))?;
+    let original_path = source.to_string_lossy().to_string();
+    let copy = meta.is_file() && meta.len() <= MAX_COPY_BYTES;
+    let path = if copy {
+        std::fs::create_dir_all(dir).map_err(|e| format!("创建目录失败: {}", e))?;
+        let target = unique_path(dir, &name);
+        std::fs::copy(source, &target).map_err(|e| format!("复制拖入文件失败: {}", e))?;
+        target.to_string_lossy().to_string()
+    } else {
+        original_path.clone()
+    };
+    Ok(AttachedFile {
+        mime: if meta.is_dir() {
+            "inode/directory".to_string()
+        } else {
+            mime_of(source).to_string()
+        },
+        name,
+        path,
+        original_path,
+        size: meta.len(),
+        is_dir: meta.is_dir(),
+        copied: copy,
+    })
+}
+
+/// 处理主窗口的文件拖放：在后台线程复制，完成后发送 files-attached
+pub fn on_drop(window: &Window, paths: Vec<PathBuf>) {
+    if paths.is_empty() {
+        return;
+    }
+    let window = window.clone();
+    std::thread::spawn(move || {
+        let dir = sandbox::sandbox_root()
+            .join("attachments")
+            .join(history::now_millis().to_string());
+        let mut files = Vec::new();
+        for source in &paths {
+            match attach(source, &dir) {
+                Ok(file) => files.push(file),
+                Err(e) => eprintln!("[Tauri] ⚠️ {}", e),
+            }
+        }
+        if files.is_empty() {
+            return;
+        }
+        eprintln!("[Tauri] 📎 已附加 {} 个拖入的文件", files.len());
+        let pending = match PENDING.lock() {
+            Ok(mut pending) => {
+                pending.extend(files.iter().cloned());
+                pending.clone()
+            }
+            Err(_) => files.clone(),
+        };
+        let _ = window.emit(
+            "files-attached",
+            serde_json::json!({ "files": files, "pending": pending }),
+        );
+    });
+}
+
+/// 取出待用附件并写入 context.attached_files（没有附件时原样返回）
+pub fn apply_context(context: Option<serde_json::Value>) -> Option<serde_json::Value> {
+    let files = match PENDING.lock() {
+        Ok(mut pending) => std::mem::take(&mut *pending),
+        Err(_) => return context,
+    };
+    if files.is_empty() {
+        return context;
+    }
+    let mut context = match context {
+        Some(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    context.insert(
+        "attached_files".to_string(),
+        serde_json::to_value(files).unwrap_or_default(),
+    );
+    Some(serde_json::Value::Object(context))
+}
+
+/// 获取等待带入下一个任务的附件
+#[tauri::command]
+pub async fn get_attachments() -> Result<Vec<AttachedFile>, String> {
+    PENDING
+        .lock()
+        .map(|pending| pending.clone())
+        .map_err(|_| "附件状态不可用".to_string())
+}
+
+/// 移除附件：path 为空时全部清空（已复制到沙盒的文件保留，随沙盒清理）
+#[tauri::command]
+pub async fn remove_attachment(path: Option<String>) -> Result<Vec<AttachedFile>, String> {
+    let mut pending = PENDING.lock().map_err(|_| "附件状态不可用")?;
+    match path {
+        Some(path) => pending.retain(|f| f.path != path),
+        None => pending.clear(),
+    }
+    Ok(pending.clone())
+}
@@ -0,0 +1,255 @@
+//! 审计日志：Agent 执行的每个步骤追加到 ~/.deskjarvis/audit.log
+//!
+//! 每行一条 JSON 记录，包含工具名、参数（已脱敏）、结果摘要和时间戳。
+//! 每条记录的 hash 为 SHA-256(上一条 hash + 本条内容)，形成哈希链：
+//! 修改、删除或插入任何一行都会让 verify_audit_log 在该处报告断链。
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{config, history, redaction};
+
+/// 哈希链起点（第一条记录的 prev_hash）
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// 结果摘要的最大长度（字符）
+const MAX_SUMMARY_CHARS: usize = 500;
+
+/// 串行化追加，保证 seq 和 prev_hash 连续
+static APPEND_LOCK: Mutex<()> = Mutex::new(());
+
+/// 一条审计记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    /// 记录时间（毫秒时间戳）
+    pub timestamp: u64,
+    pub task_id: String,
+    /// 步骤类型（工具名）
+    pub tool: String,
+    pub arguments: serde_json::Value,
+    pub success: bool,
+    pub result_summary: String,
+    pub prev_hash: String,
+    #[serde(default)]
+    pub hash: String,
+}
+
+impl AuditEntry {
+    /// 计算本条记录的哈希（不含 hash 字段本身）
+    fn compute_hash(&self) -> String {
+        let mut unhashed = self.clone();
+        unhashed.hash.clear();
+        let content = serde_json::to_string(&unhashed).unwrap_or_default();
+        let mut hasher = Sha256::new();
+        hasher.update(self.prev_hash.as_bytes());
+        hasher.update(content.as_bytes());
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+/// 校验结果
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditVerification {
+    pub valid: bool,
+    /// 已校验通过的记录数
+    pub entries: u64,
+    /// 第一处断链的行号（从 1 开始），完整时为 None
+    pub broken_at_line: Option<u64>,
+    pub message: String,
+}
+
+fn log_path() -> Result<PathBuf, String> {
+    Ok(config::get_data_dir()?.join("audit.log"))
+}
+
+/// 读取全部记录，遇到无法解析的行时报错
+fn read_entries() -> Result<Vec<AuditEntry>, String> {
+    let path = log_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("读取审计日志失败: {}", e))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str::<AuditEntry>(line)
+                .map_err(|e| format!("审计日志第 {} 行格式错误: {}", i + 1, e))
+        })
+        .collect()
+}
+
+/// 最后一条记录（只读取文件末尾的非空行）
+fn last_entry() -> Result<Option<AuditEntry>, String> {
+    let path = log_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("读取审计日志失败: {}", e))?;
+    match content.lines().rev().find(|line| !line.trim().is_empty()) {
+        Some(line) => serde_json::from_str(line)
+            .map(Some)
+            .map_err(|e| format!("审计日志末行格式错误: {}", e)),
+        None => Ok(None),
+    }
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_SUMMARY_CHARS {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(MAX_SUMMARY_CHARS).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// 追加一条步骤记录
+pub fn record_step(
+    task_id: &str,
+    tool: &str,
+    arguments: &serde_json::Value,
+    success: bool,
+    result_summary: &str,
+) -> Result<(), String> {
+    let _guard = APPEND_LOCK.lock().map_err(|_| "审计日志状态不可用")?;
+    let last = last_entry()?;
+
+    let mut arguments = arguments.clone();
+    redaction::redact_json(&mut arguments);
+    let mut entry = AuditEntry {
+        seq: last.as_ref().map(|e| e.seq + 1).unwrap_or(1),
+        timestamp: history::now_millis(),
+        task_id: task_id.to_string(),
+        tool: tool.to_string(),
+        arguments,
+        success,
+        result_summary: truncate(&redaction::redact(result_summary)),
+        prev_hash: last
+            .map(|e| e.hash)
+            .unwrap_or_else(|| GENESIS_HASH.to_string()),
+        hash: String::new(),
+    };
+    entry.hash = entry.compute_hash();
+
+    let line = serde_json::to_string(&entry).map_err(|e| format!("序列化审计记录失败: {}", e))?;
+    let path = log_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("打开审计日志失败: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("写入审计日志失败: {}", e))
+}
+
+/// 记录任务结果中的全部步骤（step 为 {type, params, ...}，result 为 {success, message, ...}）
+pub fn record_task_steps<'a>(
+    task_id: &str,
+    steps: impl Iterator<Item = (&'a serde_json::Value, Option<&'a serde_json::Value>)>,
+) {
+    for (step, result) in steps {
+        let tool = step.get("type").and_then(|v| v.as_str()).unwrap_or("unknown");
+        let arguments = step.get("params").cloned().unwrap_or(serde_json::Value::Null);
+        let success = result
+            .and_then(|r| r.get("success"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let summary = result
+            .and_then(|r| r.get("message"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        if let Err(e) = record_step(task_id, tool, &arguments, success, summary) {
+            eprintln!("[Tauri] ⚠️ {}", e);
+        }
+    }
+}
+
+/// 按顺序校验哈希链
+fn verify_entries(entries: &[AuditEntry]) -> AuditVerification {
+    let mut expected_prev = entries
+        .first()
+        .map(|e| e.prev_hash.clone())
+        .unwrap_or_default();
+    for (i, entry) in entries.iter().enumerate() {
+        let problem = if entry.prev_hash != expected_prev {
+            Some("与上一条记录的哈希不连续")
+        } else if entry.hash != entry.compute_hash() {
+            Some("内容与哈希不符")
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return AuditVerification {
+                valid: false,
+                entries: i as u64,
+                broken_at_line: Some(i as u64 + 1),
+                message: format!("第 {} 条记录（seq {}）{}", i + 1, entry.seq, problem),
+            };
+        }
+        expected_prev = entry.hash.clone();
+    }
+    AuditVerification {
+        valid: true,
+        entries: entries.len() as u64,
+        broken_at_line: None,
+        message: format!("{} 条记录校验通过", entries.len()),
+    }
+}
+
+/// 导出指定时间范围（毫秒时间戳，含端点）内的记录到 path，返回导出条数
+///
+/// 导出内容保留 hash 与 prev_hash，可单独核对区间内的链是否完整。
+#[tauri::command]
+pub async fn export_audit_log(
+    path: String,
+    from: Option<u64>,
+    to: Option<u64>,
+) -> Result<usize, String> {
+    let selected: Vec<AuditEntry> = read_entries()?
+        .into_iter()
+        .filter(|e| from.is_none_or(|from| e.timestamp >= from))
+        .filter(|e| to.is_none_or(|to| e.timestamp <= to))
+        .collect();
+    let mut content = String::new();
+    for entry in &selected {
+        let line =
+            serde_json::to_string(entry).map_err(|e| format!("序列化审计记录失败: {}", e))?;
+        content.push_str(&line);
+        content.push('\n');
+    }
+    std::fs::write(&path, content).map_err(|e| format!("导出审计日志失败: {}", e))?;
+    eprintln!("[Tauri] 📤 已导出 {} 条审计记录到 {}", selected.len(), path);
+    Ok(selected.len())
+}
+
+/// 校验审计日志的哈希链是否完整
+#[tauri::command]
+pub async fn verify_audit_log() -> Result<AuditVerification, String> {
+    let entries = read_entries()?;
+    let mut verification = verify_entries(&entries);
+    // 第一条记录必须从链起点开始，否则开头被删除过
+    if let Some(first) = entries.first() {
+        if verification.valid && first.prev_hash != GENESIS_HASH {
+            verification = AuditVerification {
+                valid: false,
+                entries: 0,
+                broken_at_line: Some(1),
+                message: "第 1 条记录不是链起点，日志开头可能被删除".to_string(),
+            };
+        }
+    }
+    Ok(verification)
+}
@@ -0,0 +1,460 @@
+//! 自动化包：把工作流模板、快捷指令（收藏）和定时任务导出为一个 JSON 文件，供团队共享
+//!
+//! 导入时先整体校验，任何一项不合法则整个包都不导入；名称冲突按 rename / skip / overwrite 处理。
+//! 导入的条目记录 source_pack（包名），便于追溯来源。
+//! 工作流和收藏由 Python 服务读写，导入后会通知运行中的服务重新加载。
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::config;
+use crate::history::now_millis;
+use crate::scheduler::{self, Schedule, ScheduleTrigger};
+
+/// 当前包格式版本
+const PACK_FORMAT_VERSION: u32 = 1;
+
+/// 包的来源信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackProvenance {
+    pub name: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 导出时间（毫秒时间戳）
+    pub exported_at: u64,
+    /// 导出时的应用版本
+    #[serde(default)]
+    pub app_version: String,
+}
+
+/// 工作流模板（对应 workflows.json 中的一项）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackWorkflow {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub commands: Vec<String>,
+}
+
+/// 快捷指令（对应 favorites.json 中的一项）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackQuickAction {
+    pub name: String,
+    pub instruction: String,
+}
+
+/// 定时任务规则（不含运行状态）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackSchedule {
+    pub name: String,
+    pub instruction: String,
+    pub trigger: ScheduleTrigger,
+}
+
+/// 自动化包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationPack {
+    pub format_version: u32,
+    pub provenance: PackProvenance,
+    #[serde(default)]
+    pub workflows: Vec<PackWorkflow>,
+    #[serde(default)]
+    pub quick_actions: Vec<PackQuickAction>,
+    #[serde(default)]
+    pub schedules: Vec<PackSchedule>,
+}
+
+/// 名称冲突时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// 以 "名称 (2)" 等新名称导入
+    Rename,
+    /// 保留已有条目，不导入
+    Skip,
+    /// 用包中的条目替换已有条目
+    Overwrite,
+}
+
+/// 单个条目的导入结果
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportOutcome {
+    Added,
+    Renamed,
+    Skipped,
+    Overwritten,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportItem {
+    /// workflow / quick_action / schedule
+    pub kind: &'static str,
+    pub name: String,
+    /// 实际导入后的名称（跳过时为 None）
+    pub imported_as: Option<String>,
+    pub outcome: ImportOutcome,
+}
+
+/// 导入报告
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub provenance: PackProvenance,
+    pub items: Vec<ImportItem>,
+}
+
+fn data_file(name: &str) -> Result<PathBuf, String> {
+    Ok(config::get_data_dir()?.join(name))
+}
+
+/// 读取 JSON 文件，文件不存在时返回默认值
+pub(crate) fn read_json<T: serde::de::DeserializeOwned + Default>(name: &str) -> Result<T, String> {
+    let path = data_file(name)?;
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("读取 {} 失败: {}", name, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析 {} 失败: {}", name, e))
+}
+
+pub(crate) fn write_json<T: Serialize>(name: &str, value: &T) -> Result<(), String> {
+    let path = data_file(name)?;
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("序列化 {} 失败: {}", name, e))?;
+    std::fs::write(&path, content).map_err(|e| format!("写入 {} 失败: {}", name, e))
+}
+
+/// 生成不与已有名称冲突的新名称，如 "工作模式 (2)"
+fn unique_name(name: &str, taken: &HashSet<String>) -> String {
+    (2..)
+        .map(|n| format!("{} ({})", name, n))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// 按冲突策略决定导入名称，返回 (导入名称, 结果)，跳过时名称为 None
+fn resolve_name(
+    name: &str,
+    taken: &mut HashSet<String>,
+    strategy: ConflictStrategy,
+) -> (Option<String>, ImportOutcome) {
+    if !taken.contains(name) {
+        taken.insert(name.to_string());
+        return (Some(name.to_string()), ImportOutcome::Added);
+    }
+    match strategy {
+        ConflictStrategy::Skip => (None, ImportOutcome::Skipped),
+        ConflictStrategy::Overwrite => (Some(name.to_string()), ImportOutcome::Overwritten),
+        ConflictStrategy::Rename => {
+            let renamed = unique_name(name, taken);
+            taken.insert(renamed.clone());
+            (Some(renamed), ImportOutcome::Renamed)
+        }
+    }
+}
+
+/// 检查包内同类条目名称不重复且不为空
+fn check_names<'a>(kind: &str, names: impl Iterator<Item = &'a str>) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for name in names {
+        if name.trim().is_empty() {
+            return Err(format!("{}名称不能为空", kind));
+        }
+        if !seen.insert(name) {
+            return Err(format!("包中存在重复的{}: {}", kind, name));
+        }
+    }
+    Ok(())
+}
+
+/// 整体校验自动化包，返回所有问题
+fn validate_pack(pack: &AutomationPack) -> Result<(), String> {
+    let mut errors = Vec::new();
+    if pack.format_version == 0 || pack.format_version > PACK_FORMAT_VERSION {
+        errors.push(format!(
+            "不支持的包格式版本 {}（当前支持 {}）",
+            pack.format_version, PACK_FORMAT_VERSION
+        ));
+    }
+    if pack.provenance.name.trim().is_empty() {
+        errors.push("包名称不能为空".to_string());
+    }
+
+    let name_checks = [
+        check_names("工作流", pack.workflows.iter().map(|w| w.name.as_str())),
+        check_names("快捷指令", pack.quick_actions.iter().map(|q| q.name.as_str())),
+        check_names("定时任务", pack.schedules.iter().map(|s| s.name.as_str())),
+    ];
+    errors.extend(name_checks.into_iter().filter_map(Result::err));
+
+    for workflow in &pack.workflows {
+        if workflow.commands.iter().all(|c| c.trim().is_empty()) {
+            errors.push(format!("工作流 {} 没有命令", workflow.name));
+        }
+    }
+    for action in &pack.quick_actions {
+        if action.instruction.trim().is_empty() {
+            errors.push(format!("快捷指令 {} 的指令为空", action.name));
+        }
+    }
+    for schedule in &pack.schedules {
+        if let Err(e) = scheduler::new_schedule(
+            Some(schedule.name.clone()),
+            schedule.instruction.clone(),
+            schedule.trigger.clone(),
+        ) {
+            errors.push(format!("定时任务 {}: {}", schedule.name, e));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("自动化包校验失败：{}", errors.join("；")))
+    }
+}
+
+/// 导入工作流模板
+fn import_workflows(
+    pack: &AutomationPack,
+    strategy: ConflictStrategy,
+    items: &mut Vec<ImportItem>,
+) -> Result<bool, String> {
+    let mut workflows: serde_json::Map<String, serde_json::Value> = read_json("workflows.json")?;
+    let mut taken: HashSet<String> = workflows.keys().cloned().collect();
+    let mut changed = false;
+
+    for workflow in &pack.workflows {
+        let (imported_as, outcome) = resolve_name(&workflow.name, &mut taken, strategy);
+        if let Some(name) = &imported_as {
+            workflows.insert(
+                name.clone(),
+                serde_json::json!({
+                    "name": name,
+                    "description": workflow.description,
+                    "commands": workflow.commands,
+                    "source_pack": pack.provenance.name,
+                }),
+            );
+            changed = true;
+        }
+        items.push(ImportItem {
+            kind: "workflow",
+            name: workflow.name.clone(),
+            imported_as,
+            outcome,
+        });
+    }
+
+    if changed {
+        write_json("workflows.json", &workflows)?;
+    }
+    Ok(changed)
+}
+
+/// 导入快捷指令
+fn import_quick_actions(
+    pack: &AutomationPack,
+    strategy: ConflictStrategy,
+    items: &mut Vec<ImportItem>,
+) -> Result<bool, String> {
+    let mut favorites: Vec<serde_json::Value> = read_json("favorites.json")?;
+    let name_of = |fav: &serde_json::Value| {
+        fav.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string()
+    };
+    let mut taken: HashSet<String> = favorites.iter().map(name_of).collect();
+    let now = now_millis();
+    let mut changed = false;
+
+    for (index, action) in pack.quick_actions.iter().enumerate() {
+        let (imported_as, outcome) = resolve_name(&action.name, &mut taken, strategy);
+        if let Some(name) = &imported_as {
+            if outcome == ImportOutcome::Overwritten {
+                favorites.retain(|fav| name_of(fav) != *name);
+            }
+            favorites.push(serde_json::json!({
+                "id": format!("fav_{}_{}", now, index),
+                "name": name,
+                "instruction": action.instruction.trim(),
+                "created_at": Local::now().format("%Y-%m-%dT%H:%M:%S%.6f").to_string(),
+                "source_pack": pack.provenance.name,
+            }));
+            changed = true;
+        }
+        items.push(ImportItem {
+            kind: "quick_action",
+            name: action.name.clone(),
+            imported_as,
+            outcome,
+        });
+    }
+
+    if changed {
+        write_json("favorites.json", &favorites)?;
+    }
+    Ok(changed)
+}
+
+/// 导入定时任务
+fn import_schedules(
+    state: &crate::AppState,
+    pack: &AutomationPack,
+    strategy: ConflictStrategy,
+    items: &mut Vec<ImportItem>,
+) -> Result<bool, String> {
+    if pack.schedules.is_empty() {
+        return Ok(false);
+    }
+    let now = now_millis();
+    state.schedules.update(|schedules| {
+        let mut taken: HashSet<String> = schedules.iter().map(|s| s.name.clone()).collect();
+        let mut changed = false;
+        for (index, rule) in pack.schedules.iter().enumerate() {
+            let (imported_as, outcome) = resolve_name(&rule.name, &mut taken, strategy);
+            if let Some(name) = &imported_as {
+                if outcome == ImportOutcome::Overwritten {
+                    schedules.retain(|s| s.name != *name);
+                }
+                let mut schedule = scheduler::new_schedule(
+                    Some(name.clone()),
+                    rule.instruction.clone(),
+                    rule.trigger.clone(),
+                )?;
+                // 同一毫秒内批量创建，需要区分 ID
+                schedule.id = format!("schedule_{}_{}", now, index);
+                schedule.source_pack = Some(pack.provenance.name.clone());
+                schedules.push(schedule);
+                changed = true;
+            }
+            items.push(ImportItem {
+                kind: "schedule",
+                name: rule.name.clone(),
+                imported_as,
+                outcome,
+            });
+        }
+        Ok(changed)
+    })
+}
+
+/// 导出工作流模板、快捷指令和定时任务到指定文件
+///
+/// 一次性定时任务（如延后执行的任务）只对本机有意义，不导出。
+#[tauri::command]
+pub async fn export_automation_pack(
+    app: AppHandle,
+    state: tauri::State<'_, crate::AppState>,
+    path: String,
+    name: Option<String>,
+    author: Option<String>,
+    description: Option<String>,
+) -> Result<AutomationPack, String> {
+    let workflows: serde_json::Map<String, serde_json::Value> = read_json("workflows.json")?;
+    let workflows = workflows
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let mut workflow: PackWorkflow = serde_json::from_value(value).ok()?;
+            workflow.name = key;
+            Some(workflow)
+        })
+        .collect();
+
+    let favorites: Vec<serde_json::Value> = read_json("favorites.json")?;
+    let quick_actions = favorites
+        .into_iter()
+        .filter_map(|fav| serde_json::from_value::<PackQuickAction>(fav).ok())
+        .collect();
+
+    let schedules = state
+        .schedules
+        .list()
+        .into_iter()
+        .filter(|s| !matches!(s.trigger, ScheduleTrigger::Once { .. }))
+        .map(|s: Schedule| PackSchedule {
+            name: s.name,
+            instruction: s.instruction,
+            trigger: s.trigger,
+        })
+        .collect();
+
+    let pack = AutomationPack {
+        format_version: PACK_FORMAT_VERSION,
+        provenance: PackProvenance {
+            name: name
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty())
+                .unwrap_or_else(|| "DeskJarvis 自动化包".to_string()),
+            author: author.filter(|a| !a.trim().is_empty()),
+            description: description.filter(|d| !d.trim().is_empty()),
+            exported_at: now_millis(),
+            app_version: app.package_info().version.to_string(),
+        },
+        workflows,
+        quick_actions,
+        schedules,
+    };
+
+    let path = PathBuf::from(path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&pack)
+        .map_err(|e| format!("序列化自动化包失败: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("写入自动化包失败: {}", e))?;
+    eprintln!(
+        "[Tauri] 📦 已导出自动化包 {}（{} 个工作流，{} 个快捷指令，{} 个定时任务）",
+        path.display(),
+        pack.workflows.len(),
+        pack.quick_actions.len(),
+        pack.schedules.len()
+    );
+    Ok(pack)
+}
+
+/// 从文件导入自动化包，conflict 决定与已有条目重名时的处理方式
+#[tauri::command]
+pub async fn import_automation_pack(
+    app: AppHandle,
+    state: tauri::State<'_, crate::AppState>,
+    path: String,
+    conflict: ConflictStrategy,
+) -> Result<ImportReport, String> {
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("读取自动化包失败: {}", e))?;
+    let pack: AutomationPack = serde_json::from_str(&content)
+        .map_err(|e| format!("解析自动化包失败: {}", e))?;
+    validate_pack(&pack)?;
+
+    let mut items = Vec::new();
+    let workflows_changed = import_workflows(&pack, conflict, &mut items)?;
+    let favorites_changed = import_quick_actions(&pack, conflict, &mut items)?;
+    if import_schedules(&state, &pack, conflict, &mut items)? {
+        scheduler::notify_changed(&app);
+    }
+
+    if workflows_changed || favorites_changed {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let state = app.state::<crate::AppState>();
+            if let Err(e) = crate::reload_server_config(&state).await {
+                eprintln!("[Tauri] ⚠️ 通知 Python 服务重新加载工作流失败: {}", e);
+            }
+        });
+    }
+
+    eprintln!(
+        "[Tauri] 📦 已导入自动化包 {}（{} 项）",
+        pack.provenance.name,
+        items.len()
+    );
+    Ok(ImportReport {
+        provenance: pack.provenance,
+        items,
+    })
+}
@@ -0,0 +1,90 @@
+//! 开机自启：通过 autostart 插件登记登录项，launch_minimized 为 true 时启动后只驻留托盘
+//!
+//! 登录项启动时带 --autostart 参数。隐藏启动的主窗口仍会加载页面，
+//! 可通过托盘菜单、点击托盘图标或快捷面板的全局快捷键唤出。
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_autostart::{AutoLaunchManager, MacosLauncher};
+
+use crate::{config, features, window_manager};
+
+/// 登录项启动时附带的参数
+pub const AUTOSTART_ARG: &str = "--autostart";
+
+/// 开机自启状态
+#[derive(Debug, Clone, Serialize)]
+pub struct AutostartStatus {
+    /// 插件是否可用
+    pub available: bool,
+    pub enabled: bool,
+    /// 启动时不显示主窗口
+    pub launch_minimized: bool,
+}
+
+/// autostart 插件（macOS 使用 LaunchAgent）
+pub fn plugin<R: Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri_plugin_autostart::init(MacosLauncher::LaunchAgent, Some(vec![AUTOSTART_ARG]))
+}
+
+/// 按 launch_minimized 在启动时隐藏主窗口
+pub fn apply_launch_window(app: &AppHandle) {
+    let minimized = config::load_config()
+        .ok()
+        .and_then(|c| c.launch_minimized)
+        .unwrap_or(false);
+    if !minimized {
+        return;
+    }
+    if let Some(window) = app.get_webview_window(window_manager::MAIN_LABEL) {
+        let from_login = std::env::args().any(|a| a == AUTOSTART_ARG);
+        eprintln!(
+            "[Tauri] 🌙 {}启动，主窗口隐藏到托盘",
+            if from_login { "开机自启" } else { "最小化" }
+        );
+        let _ = window.hide();
+    }
+}
+
+fn manager(app: &AppHandle) -> Result<tauri::State<'_, AutoLaunchManager>, String> {
+    if !features::is_available("autostart") {
+        return Err("开机自启功能不可用".to_string());
+    }
+    app.try_state::<AutoLaunchManager>()
+        .ok_or_else(|| "开机自启功能不可用".to_string())
+}
+
+/// 获取开机自启状态
+#[tauri::command]
+pub async fn get_autostart_status(app: AppHandle) -> Result<AutostartStatus, String> {
+    let launch_minimized = config::load_config()?.launch_minimized.unwrap_or(false);
+    let enabled = match manager(&app) {
+        Ok(manager) => manager
+            .is_enabled()
+            .map_err(|e| format!("读取开机自启状态失败: {}", e))?,
+        Err(_) => {
+            return Ok(AutostartStatus {
+                available: false,
+                enabled: false,
+                launch_minimized,
+            })
+        }
+    };
+    Ok(AutostartStatus {
+        available: true,
+        enabled,
+        launch_minimized,
+    })
+}
+
+/// 开启或关闭开机自启
+#[tauri::command]
+pub async fn set_autostart(app: AppHandle, enabled: bool) -> Result<AutostartStatus, String> {
+    {
+        let manager = manager(&app)?;
+        let result = if enabled { manager.enable() } else { manager.disable() };
+        result.map_err(|e| format!("设置开机自启失败: {}", e))?;
+    }
+    eprintln!("[Tauri] 🚀 开机自启已{}", if enabled { "开启" } else { "关闭" });
+    get_autostart_status(app).await
+}
@@ -0,0 +1,292 @@
+//! 数据目录备份：把 ~/.deskjarvis（配置、任务历史、定时任务、记忆等）快照到
+//! ~/.deskjarvis/backups/<备份 ID>/，支持手动、按 backup_interval_hours 定时备份和恢复
+//!
+//! 每个备份目录带 manifest.json。任务期间的临时通信目录不备份，沙盒产物体积较大，
+//! 只在手动备份时按需包含。恢复前先自动备份当前数据（不计入 backup_keep），
+//! 恢复后 Python 服务和内存中的状态仍是旧数据，需要重启应用。
+//! DESKJARVIS_CONFIG 指定的外部配置文件不在备份范围内。
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config;
+use crate::history::now_millis;
+
+/// 备份目录名
+const BACKUP_DIR: &str = "backups";
+
+/// 备份清单文件名
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// 默认保留的备份数
+const DEFAULT_KEEP: u32 = 10;
+
+/// 检查是否到了定时备份时间的间隔
+const CHECK_INTERVAL_SECS: u64 = 600;
+
+/// 不备份的顶层条目：备份本身和任务期间的临时通信目录
+const EXCLUDED: &[&str] = &[
+    BACKUP_DIR,
+    "screenshot_requests",
+    "tool_results",
+    "task_control",
+    "file_access",
+    "user_input_response.json",
+];
+
+/// 沙盒目录（产物）
+const SANDBOX_DIR: &str = "sandbox";
+
+/// 备份原因
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupReason {
+    Manual,
+    Scheduled,
+    /// 恢复前自动备份的当前数据
+    PreRestore,
+}
+
+/// 备份清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub id: String,
+    /// 创建时间（毫秒时间戳）
+    pub created_at: u64,
+    pub reason: BackupReason,
+    pub app_version: String,
+    pub include_sandbox: bool,
+    pub files: u64,
+    pub size_bytes: u64,
+}
+
+/// restore_backup 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreResult {
+    pub restored: BackupInfo,
+    /// 恢复前自动备份的 ID
+    pub safety_backup: String,
+    /// 恢复的文件数
+    pub files: u64,
+    /// 需要重启应用后生效
+    pub restart_required: bool,
+}
+
+fn backups_dir() -> Result<PathBuf, String> {
+    Ok(config::get_data_dir()?.join(BACKUP_DIR))
+}
+
+/// 备份 ID 只能是 backups 下的目录名
+fn backup_path(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || id.contains(['/', '\\']) || id.starts_with('.') {
+        return Err(format!("无效的备份 ID: {}", id));
+    }
+    Ok(backups_dir()?.join(id))
+}
+
+/// 复制目录，返回 (文件数, 字节数)；skip 判断顶层条目是否跳过，符号链接不复制
+fn copy_tree(src: &Path, dst: &Path, skip: &dyn Fn(&str) -> bool) -> Result<(u64, u64), String> {
+    std::fs::create_dir_all(dst).map_err(|e| format!("创建目录失败: {}", e))?;
+    let entries = std::fs::read_dir(src).map_err(|e| format!("读取目录 {} 失败: {}", src.display(), e))?;
+    let (mut files, mut bytes) = (0, 0);
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if skip(&name) {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let target = dst.join(&name);
+        if file_type.is_dir() {
+            let (f, b) = copy_tree(&entry.path(), &target, &|_| false)?;
+            files += f;
+            bytes += b;
+        } else if file_type.is_file() {
+            bytes += std::fs::copy(entry.path(), &target)
+                .map_err(|e| format!("复制 {} 失败: {}", entry.path().display(), e))?;
+            files += 1;
+        }
+    }
+    Ok((files, bytes))
+}
+
+fn read_manifest(dir: &Path) -> Option<BackupInfo> {
+    let content = std::fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 所有备份，按创建时间从新到旧
+fn list() -> Result<Vec<BackupInfo>, String> {
+    let dir = backups_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries = std::fs::read_dir(&dir).map_err(|e| format!("读取备份目录失败: {}", e))?;
+    let mut backups: Vec<BackupInfo> = entries
+        .flatten()
+        .filter_map(|entry| read_manifest(&entry.path()))
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+    Ok(backups)
+}
+
+/// 快照数据目录
+fn create(app_version: &str, reason: BackupReason, include_sandbox: bool) -> Result<BackupInfo, String> {
+    let data_dir = config::get_data_dir()?;
+    let mut id = format!("backup_{}", Local::now().format("%Y%m%d_%H%M%S"));
+    if backup_path(&id)?.exists() {
+        id = format!("{}_{}", id, now_millis() % 1000);
+    }
+    let dir = backup_path(&id)?;
+    let skip = |name: &str| EXCLUDED.contains(&name) || (!include_sandbox && name == SANDBOX_DIR);
+    let (files, size_bytes) = match copy_tree(&data_dir, &dir, &skip) {
+        Ok(copied) => copied,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&dir);
+            return Err(format!("备份数据目录失败: {}", e));
+        }
+    };
+
+    let info = BackupInfo {
+        id,
+        created_at: now_millis(),
+        reason,
+        app_version: app_version.to_string(),
+        include_sandbox,
+        files,
+        size_bytes,
+    };
+    let manifest = serde_json::to_string_pretty(&info).map_err(|e| format!("序列化备份清单失败: {}", e))?;
+    std::fs::write(dir.join(MANIFEST_FILE), manifest).map_err(|e| format!("写入备份清单失败: {}", e))?;
+    eprintln!("[Tauri] 💾 已备份数据目录: {}（{} 个文件，{} 字节）", info.id, files, size_bytes);
+    Ok(info)
+}
+
+/// 超出保留数时删除最旧的备份（恢复前的自动备份不计入也不删除）
+fn prune(keep: u32) {
+    let Ok(backups) = list() else {
+        return;
+    };
+    let stale = backups
+        .into_iter()
+        .filter(|b| b.reason != BackupReason::PreRestore)
+        .skip(keep.max(1) as usize);
+    for backup in stale {
+        if let Ok(path) = backup_path(&backup.id) {
+            match std::fs::remove_dir_all(&path) {
+                Ok(()) => eprintln!("[Tauri] 🗑️ 已删除旧备份: {}", backup.id),
+                Err(e) => eprintln!("[Tauri] ⚠️ 删除旧备份 {} 失败: {}", backup.id, e),
+            }
+        }
+    }
+}
+
+fn keep_count() -> u32 {
+    config::load_config()
+        .ok()
+        .and_then(|c| c.backup_keep)
+        .unwrap_or(DEFAULT_KEEP)
+}
+
+/// 到了 backup_interval_hours 时创建定时备份
+fn backup_if_due(app_version: &str) -> Result<(), String> {
+    let config = config::load_config()?;
+    let Some(hours) = config.backup_interval_hours.filter(|h| *h > 0) else {
+        return Ok(());
+    };
+    let last = list()?
+        .into_iter()
+        .filter(|b| b.reason != BackupReason::PreRestore)
+        .map(|b| b.created_at)
+        .max()
+        .unwrap_or(0);
+    if now_millis().saturating_sub(last) < hours * 3600 * 1000 {
+        return Ok(());
+    }
+    create(app_version, BackupReason::Scheduled, false)?;
+    prune(config.backup_keep.unwrap_or(DEFAULT_KEEP));
+    Ok(())
+}
+
+/// 后台定时备份
+pub fn spawn(app: AppHandle) {
+    let app_version = app.package_info().version.to_string();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let app_version = app_version.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || backup_if_due(&app_version)).await;
+            if let Ok(Err(e)) = result {
+                eprintln!("[Tauri] ⚠️ 定时备份失败: {}", e);
+            }
+        }
+    });
+}
+
+/// 立即备份数据目录，include_sandbox 为 true 时包含沙盒中的任务产物
+#[tauri::command]
+pub async fn create_backup(app: AppHandle, include_sandbox: Option<bool>) -> Result<BackupInfo, String> {
+    let app_version = app.package_info().version.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        let info = create(&app_version, BackupReason::Manual, include_sandbox.unwrap_or(false))?;
+        prune(keep_count());
+        Ok(info)
+    })
+    .await
+    .map_err(|e| format!("备份数据目录失败: {}", e))?
+}
+
+/// 列出所有备份（从新到旧）
+#[tauri::command]
+pub async fn list_backups() -> Result<Vec<BackupInfo>, String> {
+    list()
+}
+
+/// 从备份恢复数据目录：先自动备份当前数据，再覆盖写回，完成后需重启应用
+///
+/// 备份中没有的文件保留不动。执行任务期间不能恢复。
+#[tauri::command]
+pub async fn restore_backup(
+    app: AppHandle,
+    state: tauri::State<'_, crate::AppState>,
+    id: String,
+) -> Result<RestoreResult, String> {
+    if state.running.any() {
+        return Err("有任务正在执行，请等待任务结束后再恢复备份".to_string());
+    }
+    let dir = backup_path(&id)?;
+    let restored = read_manifest(&dir).ok_or_else(|| format!("未找到备份: {}", id))?;
+    let app_version = app.package_info().version.to_string();
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let safety = create(&app_version, BackupReason::PreRestore, restored.include_sandbox)?;
+        let data_dir = config::get_data_dir()?;
+        let (files, _) = copy_tree(&dir, &data_dir, &|name| name == MANIFEST_FILE || EXCLUDED.contains(&name))
+            .map_err(|e| format!("恢复备份失败（恢复前的数据已备份为 {}）: {}", safety.id, e))?;
+        Ok::<_, String>(RestoreResult {
+            restored,
+            safety_backup: safety.id,
+            files,
+            restart_required: true,
+        })
+    })
+    .await
+    .map_err(|e| format!("恢复备份失败: {}", e))??;
+
+    eprintln!("[Tauri] 💾 已从备份 {} 恢复 {} 个文件，需要重启应用", id, result.files);
+    let _ = app.emit("backup-restored", &result);
+    Ok(result)
+}
+
+/// 恢复备份后重启应用
+#[tauri::command]
+pub async fn restart_app(app: AppHandle) -> Result<(), String> {
+    crate::shutdown_python_servers(&app.state::<crate::AppState>()).await;
+    app.restart()
+}
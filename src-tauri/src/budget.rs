@@ -0,0 +1,179 @@
+//! 全局预算：每小时任务数和每日估算费用上限
+//!
+//! 任务开始前（run_tracked_task，界面、HTTP 接口、定时任务、链接和会话共用；脱离任务见
+//! execute_task_detached）检查配置的
+//! max_tasks_per_hour 和 max_daily_cost_usd，超出时以 BUDGET_EXCEEDED 错误（见 error）拒绝，
+//! 发出 budget-exceeded 事件并弹出系统通知。
+//! 用户确认后可调用 override_budget_once 放行下一个任务。单任务费用上限见 cost。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::config::{self, AppConfig};
+use crate::error::DeskJarvisError;
+use crate::event_sink::EventSink;
+use crate::history::{now_millis, HistoryStore};
+use crate::{features, i18n, usage};
+
+/// 下一个任务跳过预算检查
+static OVERRIDE_ONCE: AtomicBool = AtomicBool::new(false);
+
+/// 超出的预算
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum BudgetExceeded {
+    /// 最近一小时开始的任务数已达上限
+    TasksPerHour { limit: u32, count: u32 },
+    /// 今日估算费用已达上限
+    DailyCost { limit_usd: f64, spent_usd: f64 },
+}
+
+impl BudgetExceeded {
+    pub fn message(&self) -> String {
+        match self {
+            BudgetExceeded::TasksPerHour { limit, count } => {
+                format!("最近一小时已执行 {} 个任务，达到上限 {} 个", count, limit)
+            }
+            BudgetExceeded::DailyCost { limit_usd, spent_usd } => {
+                format!("今日模型费用约 ${:.4}，达到上限 ${:.2}", spent_usd, limit_usd)
+            }
+        }
+    }
+}
+
+/// 检查预算，超出时返回超出的项（放行一次的标记在此消耗）
+fn check(config: Option<&AppConfig>, history: &HistoryStore) -> Result<(), BudgetExceeded> {
+    if OVERRIDE_ONCE.swap(false, Ordering::SeqCst) {
+        eprintln!("[Tauri] 💸 已手动放行一次，跳过预算检查");
+        return Ok(());
+    }
+    let Some(config) = config else {
+        return Ok(());
+    };
+    check_limits(
+        config,
+        || history.started_since(now_millis().saturating_sub(3600 * 1000)) as u32,
+        usage::today_cost_usd,
+    )
+}
+
+/// 按配置的上限比较用量，只在对应上限启用时才读取用量
+fn check_limits(
+    config: &AppConfig,
+    tasks_last_hour: impl FnOnce() -> u32,
+    cost_today_usd: impl FnOnce() -> f64,
+) -> Result<(), BudgetExceeded> {
+    if let Some(limit) = config.max_tasks_per_hour.filter(|l| *l > 0) {
+        let count = tasks_last_hour();
+        if count >= limit {
+            return Err(BudgetExceeded::TasksPerHour { limit, count });
+        }
+    }
+    if let Some(limit_usd) = config.max_daily_cost_usd.filter(|l| *l > 0.0) {
+        let spent_usd = cost_today_usd();
+        if spent_usd >= limit_usd {
+            return Err(BudgetExceeded::DailyCost { limit_usd, spent_usd });
+        }
+    }
+    Ok(())
+}
+
+/// 通知前端并弹出系统通知
+fn notify(app: &AppHandle, sink: &impl EventSink, exceeded: &BudgetExceeded) {
+    let message = exceeded.message();
+    eprintln!("[Tauri] 💸 预算已用尽，拒绝执行任务: {}", message);
+    sink.send("budget-exceeded", DeskJarvisError::from(exceeded.clone()));
+    if features::is_available("notification") {
+        let _ = app
+            .notification()
+            .builder()
+            .title(i18n::tr("DeskJarvis 预算已用尽"))
+            .body(i18n::tr(&format!("{}，可在应用中手动放行一次", message)))
+            .show();
+    }
+}
+
+/// 任务开始前的预算关卡：超出时通知前端并返回 BUDGET_EXCEEDED
+pub fn admit(
+    app: &AppHandle,
+    sink: &impl EventSink,
+    history: &HistoryStore,
+) -> Result<(), DeskJarvisError> {
+    check(config::load_config().ok().as_ref(), history).map_err(|exceeded| {
+        notify(app, sink, &exceeded);
+        exceeded.into()
+    })
+}
+
+/// 放行下一个任务：跳过一次每小时任务数和每日费用检查
+#[tauri::command]
+pub async fn override_budget_once() -> Result<(), String> {
+    OVERRIDE_ONCE.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(per_hour: Option<u32>, daily_usd: Option<f64>) -> AppConfig {
+        AppConfig {
+            max_tasks_per_hour: per_hour,
+            max_daily_cost_usd: daily_usd,
+            ..AppConfig::default()
+        }
+    }
+
+    #[test]
+    fn hourly_limit_rejects_at_limit() {
+        let config = limits(Some(3), None);
+        assert!(check_limits(&config, || 2, || 0.0).is_ok());
+        assert!(matches!(
+            check_limits(&config, || 3, || 0.0),
+            Err(BudgetExceeded::TasksPerHour { limit: 3, count: 3 })
+        ));
+        assert!(matches!(
+            check_limits(&config, || 7, || 0.0),
+            Err(BudgetExceeded::TasksPerHour { limit: 3, count: 7 })
+        ));
+    }
+
+    #[test]
+    fn daily_cost_rejects_at_limit() {
+        let config = limits(None, Some(1.5));
+        assert!(check_limits(&config, || 0, || 1.4999).is_ok());
+        assert!(matches!(
+            check_limits(&config, || 0, || 1.5),
+            Err(BudgetExceeded::DailyCost { .. })
+        ));
+        assert!(check_limits(&config, || 0, || 2.0).is_err());
+    }
+
+    #[test]
+    fn disabled_limits_skip_usage() {
+        for config in [limits(None, None), limits(Some(0), Some(0.0))] {
+            let result = check_limits(
+                &config,
+                || panic!("未启用的上限不应读取任务数"),
+                || panic!("未启用的上限不应读取费用"),
+            );
+            assert!(result.is_ok());
+        }
+    }
+
+    #[test]
+    fn hourly_limit_checked_before_cost() {
+        let config = limits(Some(1), Some(1.0));
+        assert!(matches!(
+            check_limits(&config, || 1, || 5.0),
+            Err(BudgetExceeded::TasksPerHour { .. })
+        ));
+        assert!(matches!(
+            check_limits(&config, || 0, || 5.0),
+            Err(BudgetExceeded::DailyCost { .. })
+        ));
+    }
+}
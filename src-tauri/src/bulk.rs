@@ -0,0 +1,188 @@
+//! 批量操作：一次调用删除或导出多条任务历史、删除多个任务产物
+//!
+//! 先校验全部目标，任一无效时不做任何修改；历史记录在一次加锁内修改并只写盘一次。
+//! 逐项处理的进度以 bulk-progress 事件通知前端，删除文件失败的项在结果中列出。
+
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager, Window};
+
+use crate::history::TaskRecord;
+use crate::{prompt_log, report, sandbox, tray, undo};
+
+/// bulk-progress 事件负载
+#[derive(Debug, Clone, Serialize)]
+struct BulkProgress<'a> {
+    /// delete_tasks、export_tasks 或 delete_artifacts
+    operation: &'a str,
+    done: usize,
+    total: usize,
+    /// 刚处理完的项
+    item: &'a str,
+}
+
+/// 单项失败原因
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkFailure {
+    pub id: String,
+    pub error: String,
+}
+
+/// 批量操作结果
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkResult {
+    pub succeeded: usize,
+    pub failed: Vec<BulkFailure>,
+}
+
+/// 任务产物定位：任务 ID + list_task_artifacts 返回的相对路径
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArtifactRef {
+    pub task_id: String,
+    pub name: String,
+}
+
+fn emit_progress(window: &Window, operation: &str, done: usize, total: usize, item: &str) {
+    let _ = window.emit(
+        "bulk-progress",
+        BulkProgress {
+            operation,
+            done,
+            total,
+            item,
+        },
+    );
+}
+
+/// 任务工作目录，只返回位于沙盒根目录内的已存在目录
+fn sandboxed_work_dir(record: &TaskRecord) -> Option<PathBuf> {
+    let dir = match &record.work_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => sandbox::task_dir(&record.id).ok()?,
+    };
+    let dir = dir.canonicalize().ok()?;
+    let root = sandbox::sandbox_root().canonicalize().ok()?;
+    (dir != root && dir.starts_with(&root)).then_some(dir)
+}
+
+/// 批量删除任务历史及其工作目录、撤销日志
+#[tauri::command]
+pub async fn delete_tasks(
+    window: Window,
+    state: tauri::State<'_, crate::AppState>,
+    ids: Vec<String>,
+) -> Result<BulkResult, String> {
+    if let Some(running) = ids.iter().find(|id| state.running.contains(id)) {
+        return Err(format!("任务 {} 正在执行，无法删除", running));
+    }
+    let removed = state.history.remove_many(&ids)?;
+
+    let total = removed.len();
+    let mut failed = Vec::new();
+    for (index, record) in removed.iter().enumerate() {
+        prompt_log::remove(&record.id);
+        report::remove(&record.id);
+        undo::discard(&record.id);
+        if let Some(dir) = sandboxed_work_dir(record) {
+            if let Err(e) = std::fs::remove_dir_all(&dir) {
+                failed.push(BulkFailure {
+                    id: record.id.clone(),
+                    error: format!("删除工作目录失败: {}", e),
+                });
+            }
+        }
+        emit_progress(&window, "delete_tasks", index + 1, total, &record.id);
+    }
+    eprintln!("[Tauri] 🗑 已删除 {} 条任务历史", total);
+    tray::refresh_recent_tasks(window.app_handle());
+    Ok(BulkResult {
+        succeeded: total - failed.len(),
+        failed,
+    })
+}
+
+/// 批量导出任务历史（含产物清单）到 JSON 文件
+#[tauri::command]
+pub async fn export_tasks(
+    window: Window,
+    state: tauri::State<'_, crate::AppState>,
+    ids: Vec<String>,
+    path: String,
+) -> Result<usize, String> {
+    let missing: Vec<&str> = ids
+        .iter()
+        .filter(|id| state.history.get(id).is_none())
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("未找到任务记录: {}", missing.join(", ")));
+    }
+
+    let total = ids.len();
+    let mut records = Vec::with_capacity(total);
+    for (index, id) in ids.iter().enumerate() {
+        if let Some(record) = state.history.get(id) {
+            records.push(record);
+        }
+        emit_progress(&window, "export_tasks", index + 1, total, id);
+    }
+    let content = serde_json::to_string_pretty(&records)
+        .map_err(|e| format!("序列化任务历史失败: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("导出任务历史失败: {}", e))?;
+    eprintln!("[Tauri] 📤 已导出 {} 条任务历史到 {}", records.len(), path);
+    Ok(records.len())
+}
+
+/// 校验产物路径位于任务工作目录内且是文件
+fn resolve_artifact(state: &crate::AppState, artifact: &ArtifactRef) -> Result<PathBuf, String> {
+    let dir = match state.history.get(&artifact.task_id).and_then(|r| r.work_dir) {
+        Some(dir) => PathBuf::from(dir),
+        None => sandbox::task_dir(&artifact.task_id)?,
+    }
+    .canonicalize()
+    .map_err(|e| format!("任务 {} 的工作目录不存在: {}", artifact.task_id, e))?;
+    let path = dir
+        .join(&artifact.name)
+        .canonicalize()
+        .map_err(|e| format!("产物文件不存在: {}: {}", artifact.name, e))?;
+    // 拒绝 "../" 或符号链接指向工作目录之外的文件
+    if !path.starts_with(&dir) || !path.is_file() {
+        return Err(format!("不允许删除任务工作目录之外的文件: {}", artifact.name));
+    }
+    Ok(path)
+}
+
+/// 批量删除任务产物，并从任务历史的产物登记中移除
+#[tauri::command]
+pub async fn delete_artifacts(
+    window: Window,
+    state: tauri::State<'_, crate::AppState>,
+    ids: Vec<ArtifactRef>,
+) -> Result<BulkResult, String> {
+    let targets: Vec<(&ArtifactRef, PathBuf)> = ids
+        .iter()
+        .map(|artifact| resolve_artifact(&state, artifact).map(|path| (artifact, path)))
+        .collect::<Result<_, _>>()?;
+
+    let total = targets.len();
+    let mut failed = Vec::new();
+    let mut deleted: Vec<(String, String)> = Vec::new();
+    for (index, (artifact, path)) in targets.iter().enumerate() {
+        let id = format!("{}/{}", artifact.task_id, artifact.name);
+        match std::fs::remove_file(path) {
+            Ok(()) => deleted.push((artifact.task_id.clone(), artifact.name.clone())),
+            Err(e) => failed.push(BulkFailure {
+                id: id.clone(),
+                error: format!("删除产物失败: {}", e),
+            }),
+        }
+        emit_progress(&window, "delete_artifacts", index + 1, total, &id);
+    }
+
+    state.history.remove_artifacts(&deleted);
+    eprintln!("[Tauri] 🗑 已删除 {} 个任务产物", deleted.len());
+    Ok(BulkResult {
+        succeeded: deleted.len(),
+        failed,
+    })
+}
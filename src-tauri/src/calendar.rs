@@ -0,0 +1,490 @@
+//! 日历：读取本地 .ics 文件和 CalDAV 账户中的日程，并可新建日程
+//!
+//! 在配置 calendars 中添加日历：本地文件 {"name", "type": "ics", "path"}，或 CalDAV 日历
+//! {"name", "type": "caldav", "url", "username", "password"}（url 为日历集合地址）。
+//! list_upcoming_events 合并各日历在时间范围内的日程，重复日程展开为每一次（解析见 ics）；
+//! create_calendar_event 写入指定的日历（默认第一个启用的）：本地文件追加 VEVENT，CalDAV 以 PUT 上传，
+//! 并返回时间上冲突的已有日程。Agent 通过 calendar.list / calendar.create 工具调用使用，
+//! 「明天下午三点帮我建个会议」不再依赖 AppleScript 操作日历界面。
+
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::ics::{self, VEvent};
+use crate::{config, mail_conn};
+
+/// 默认列出今后几天的日程
+const DEFAULT_DAYS: i64 = 7;
+
+/// 时间范围最长天数
+const MAX_DAYS: i64 = 366;
+
+/// 默认最多返回的日程数
+const DEFAULT_LIMIT: usize = 100;
+
+/// 最多返回的日程数
+const MAX_LIMIT: usize = 500;
+
+/// 新日程未指定结束时间时的时长（分钟）
+const DEFAULT_DURATION_MINUTES: i64 = 60;
+
+/// 本地日历文件的大小上限
+const MAX_ICS_BYTES: u64 = 20 * 1024 * 1024;
+
+/// CalDAV 请求超时
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 日历类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CalendarKind {
+    /// 本地 .ics 文件
+    #[default]
+    Ics,
+    /// CalDAV 日历集合
+    Caldav,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 日历配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarSource {
+    /// 日历名称，新建日程时以此指定日历
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: CalendarKind,
+    /// ics：文件路径（支持 "~/"），不存在时新建日程会创建
+    #[serde(default)]
+    pub path: Option<String>,
+    /// caldav：日历集合地址
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    /// caldav：密码或应用专用密码
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// list_upcoming_events 的时间范围和筛选条件，字段都可省略
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EventRange {
+    /// 开始时间，默认现在
+    pub start: Option<String>,
+    /// 结束时间，默认开始后 days 天
+    pub end: Option<String>,
+    /// 未指定结束时间时的天数，默认 7
+    pub days: Option<i64>,
+    /// 只列出指定日历
+    pub calendar: Option<String>,
+    /// 默认 100，最多 500
+    pub limit: Option<usize>,
+}
+
+/// 新日程
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewEvent {
+    pub title: String,
+    /// 开始时间，如 "2026-03-01 15:00"；只有日期时为全天日程
+    pub start: String,
+    #[serde(default)]
+    pub end: Option<String>,
+    /// 未指定结束时间时的时长（分钟），默认 60
+    #[serde(default)]
+    pub duration_minutes: Option<i64>,
+    #[serde(default)]
+    pub all_day: bool,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 写入的日历，默认第一个启用的日历
+    #[serde(default)]
+    pub calendar: Option<String>,
+}
+
+/// 一次日程
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarEvent {
+    pub calendar: String,
+    pub uid: String,
+    pub title: String,
+    /// 开始时间（RFC 3339，本地时区）
+    pub start: String,
+    pub end: String,
+    pub all_day: bool,
+    pub location: Option<String>,
+    pub description: Option<String>,
+}
+
+/// list_upcoming_events 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct EventList {
+    /// 按开始时间排序
+    pub events: Vec<CalendarEvent>,
+    /// 读取失败的日历（"日历名: 原因"），其余日历的日程照常返回
+    pub errors: Vec<String>,
+}
+
+/// create_calendar_event 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedEvent {
+    pub event: CalendarEvent,
+    /// 时间上重叠的已有日程（不含全天日程）
+    pub conflicts: Vec<CalendarEvent>,
+}
+
+fn to_calendar_event(calendar: &str, event: &VEvent) -> CalendarEvent {
+    CalendarEvent {
+        calendar: calendar.to_string(),
+        uid: event.uid.clone(),
+        title: event.summary.clone(),
+        start: event.start.to_rfc3339(),
+        end: event.end.to_rfc3339(),
+        all_day: event.all_day,
+        location: event.location.clone(),
+        description: event.description.clone(),
+    }
+}
+
+/// 解析输入的时间（RFC 3339 或本地时间 "YYYY-MM-DD HH:MM[:SS]"），返回时间和是否只有日期
+fn parse_input_time(value: &str) -> Result<(DateTime<Local>, bool), String> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok((time.with_timezone(&Local), false));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(value, format) {
+            let time = ics::local_from_naive(naive).ok_or_else(|| format!("本地时间不存在: {}", value))?;
+            return Ok((time, false));
+        }
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .and_then(ics::local_from_naive)
+        .map(|time| (time, true))
+        .ok_or_else(|| format!("无法识别的时间: {}（使用 YYYY-MM-DD HH:MM 格式）", value))
+}
+
+/// 启用的日历，name 指定时只取该日历
+fn sources(name: Option<&str>) -> Result<Vec<CalendarSource>, String> {
+    let all: Vec<CalendarSource> = config::load_config()?
+        .calendars
+        .into_iter()
+        .filter(|c| c.enabled)
+        .collect();
+    if all.is_empty() {
+        return Err("未配置日历（在设置的 calendars 中添加 .ics 文件或 CalDAV 账户）".to_string());
+    }
+    match name.map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) => {
+            let matched: Vec<_> = all.into_iter().filter(|c| c.name == name).collect();
+            if matched.is_empty() {
+                return Err(format!("未配置或未启用的日历: {}", name));
+            }
+            Ok(matched)
+        }
+        None => Ok(all),
+    }
+}
+
+fn ics_path(source: &CalendarSource) -> Result<std::path::PathBuf, String> {
+    source
+        .path
+        .as_deref()
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| config::expand_home(p.trim()))
+        .ok_or_else(|| format!("日历 {} 未设置 path", source.name))
+}
+
+fn read_ics_file(path: &Path) -> Result<Vec<VEvent>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let size = std::fs::metadata(path).map_err(|e| format!("读取日历文件失败: {}", e))?.len();
+    if size > MAX_ICS_BYTES {
+        return Err(format!("日历文件过大（{} MB）", size / 1024 / 1024));
+    }
+    let text = std::fs::read_to_string(path).map_err(|e| format!("读取日历文件失败: {}", e))?;
+    Ok(ics::parse(&text))
+}
+
+/// CalDAV 请求：(地址, 用户名, 密码)
+fn caldav_target(source: &CalendarSource) -> Result<(String, Option<String>, Option<String>), String> {
+    let url = source
+        .url
+        .as_deref()
+        .map(str::trim)
+        .filter(|u| !u.is_empty())
+        .ok_or_else(|| format!("日历 {} 未设置 url", source.name))?;
+    // 日历集合地址以 / 结尾，新日程的地址是集合地址加文件名
+    let url = if url.ends_with('/') { url.to_string() } else { format!("{}/", url) };
+    Ok((url, source.username.clone(), source.password.clone()))
+}
+
+fn http_client() -> Result<reqwest::Client, String> {
+    mail_conn::ensure_crypto_provider();
+    reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))
+}
+
+fn with_auth(request: reqwest::RequestBuilder, username: Option<String>, password: Option<String>) -> reqwest::RequestBuilder {
+    match username.filter(|u| !u.is_empty()) {
+        Some(username) => request.basic_auth(username, password),
+        None => request,
+    }
+}
+
+/// 解码 XML 中的 calendar-data 文本
+fn xml_text(raw: &str) -> String {
+    if let Some(cdata) = raw.trim().strip_prefix("<![CDATA[").and_then(|r| r.strip_suffix("]]>")) {
+        return cdata.to_string();
+    }
+    raw.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#13;", "\r")
+        .replace("&#xD;", "\r")
+        .replace("&amp;", "&")
+}
+
+/// CalDAV calendar-query：取回时间范围内的日程（请服务器展开重复日程）
+async fn caldav_events(source: &CalendarSource, from: DateTime<Local>, to: DateTime<Local>) -> Result<Vec<VEvent>, String> {
+    let (url, username, password) = caldav_target(source)?;
+    let utc = |t: DateTime<Local>| t.with_timezone(&chrono::Utc).format("%Y%m%dT%H%M%SZ").to_string();
+    let body = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<C:calendar-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <D:prop><C:calendar-data><C:expand start="{start}" end="{end}"/></C:calendar-data></D:prop>
+  <C:filter><C:comp-filter name="VCALENDAR"><C:comp-filter name="VEVENT">
+    <C:time-range start="{start}" end="{end}"/>
+  </C:comp-filter></C:comp-filter></C:filter>
+</C:calendar-query>"#,
+        start = utc(from),
+        end = utc(to)
+    );
+    let method = reqwest::Method::from_bytes(b"REPORT").map_err(|e| e.to_string())?;
+    let request = http_client()?
+        .request(method, &url)
+        .header("Depth", "1")
+        .header("Content-Type", "application/xml; charset=utf-8")
+        .body(body);
+    let response = with_auth(request, username, password)
+        .send()
+        .await
+        .map_err(|e| format!("请求 CalDAV 失败: {}", e))?;
+    let status = response.status();
+    let text = response.text().await.map_err(|e| format!("读取 CalDAV 响应失败: {}", e))?;
+    if !status.is_success() {
+        return Err(match status.as_u16() {
+            401 | 403 => "CalDAV 登录失败，请检查用户名和密码".to_string(),
+            code => format!("CalDAV 请求失败（HTTP {}）", code),
+        });
+    }
+    let re = Regex::new(r"(?s)<(?:[A-Za-z0-9]+:)?calendar-data[^>]*>(.*?)</(?:[A-Za-z0-9]+:)?calendar-data>")
+        .map_err(|e| e.to_string())?;
+    Ok(re
+        .captures_iter(&text)
+        .flat_map(|c| ics::parse(&xml_text(&c[1])))
+        .collect())
+}
+
+async fn read_events(source: &CalendarSource, from: DateTime<Local>, to: DateTime<Local>) -> Result<Vec<VEvent>, String> {
+    match source.kind {
+        CalendarKind::Ics => read_ics_file(&ics_path(source)?),
+        CalendarKind::Caldav => caldav_events(source, from, to).await,
+    }
+}
+
+/// 列出各日历在 [from, to) 内的日程
+async fn collect_events(sources: &[CalendarSource], from: DateTime<Local>, to: DateTime<Local>) -> EventList {
+    let mut events = Vec::new();
+    let mut errors = Vec::new();
+    for source in sources {
+        match read_events(source, from, to).await {
+            Ok(raw) => events.extend(
+                ics::expand(&raw, from, to)
+                    .iter()
+                    .map(|e| (e.start, to_calendar_event(&source.name, e))),
+            ),
+            Err(e) => {
+                eprintln!("[Tauri] ⚠️ 读取日历 {} 失败: {}", source.name, e);
+                errors.push(format!("{}: {}", source.name, e));
+            }
+        }
+    }
+    events.sort_by_key(|(start, _)| *start);
+    EventList {
+        events: events.into_iter().map(|(_, e)| e).collect(),
+        errors,
+    }
+}
+
+/// 按时间范围列出日程
+pub async fn list_events(range: &EventRange) -> Result<EventList, String> {
+    let sources = sources(range.calendar.as_deref())?;
+    let from = match range.start.as_deref().filter(|s| !s.trim().is_empty()) {
+        Some(start) => parse_input_time(start)?.0,
+        None => Local::now(),
+    };
+    let to = match range.end.as_deref().filter(|s| !s.trim().is_empty()) {
+        // 只有日期的结束时间包含当天
+        Some(end) => match parse_input_time(end)? {
+            (end, true) => end + chrono::Duration::days(1),
+            (end, false) => end,
+        },
+        None => from + chrono::Duration::days(range.days.unwrap_or(DEFAULT_DAYS).clamp(1, MAX_DAYS)),
+    };
+    if to <= from {
+        return Err("结束时间必须晚于开始时间".to_string());
+    }
+    if to - from > chrono::Duration::days(MAX_DAYS) {
+        return Err(format!("时间范围不能超过 {} 天", MAX_DAYS));
+    }
+    let mut list = collect_events(&sources, from, to).await;
+    if list.events.is_empty() && list.errors.len() == sources.len() {
+        return Err(list.errors.join("；"));
+    }
+    list.events.truncate(range.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT));
+    Ok(list)
+}
+
+/// 随机的日程 UID
+fn new_uid() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).map_err(|e| format!("生成随机数失败: {}", e))?;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!("{}@deskjarvis", hex))
+}
+
+/// 追加到本地日历文件（不存在时创建），先写临时文件再改名
+fn append_to_file(path: &Path, vevent: &str) -> Result<(), String> {
+    let content = if path.exists() {
+        let existing = std::fs::read_to_string(path).map_err(|e| format!("读取日历文件失败: {}", e))?;
+        let end = existing
+            .rfind("END:VCALENDAR")
+            .ok_or("日历文件格式错误：缺少 END:VCALENDAR")?;
+        format!("{}{}{}", &existing[..end], vevent, &existing[end..])
+    } else {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        ics::to_calendar(vevent)
+    };
+    let tmp = path.with_extension("ics.tmp");
+    std::fs::write(&tmp, content).map_err(|e| format!("写入日历文件失败: {}", e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("写入日历文件失败: {}", e))
+}
+
+/// 上传到 CalDAV 日历集合
+async fn put_caldav(source: &CalendarSource, uid: &str, vevent: &str) -> Result<(), String> {
+    let (url, username, password) = caldav_target(source)?;
+    let file_name: String = uid.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    let request = http_client()?
+        .put(format!("{}{}.ics", url, file_name))
+        .header("Content-Type", "text/calendar; charset=utf-8")
+        .header("If-None-Match", "*")
+        .body(ics::to_calendar(vevent));
+    let response = with_auth(request, username, password)
+        .send()
+        .await
+        .map_err(|e| format!("请求 CalDAV 失败: {}", e))?;
+    match response.status().as_u16() {
+        200..=299 => Ok(()),
+        401 | 403 => Err("CalDAV 登录失败，请检查用户名和密码".to_string()),
+        code => Err(format!("创建日程失败（HTTP {}）", code)),
+    }
+}
+
+/// 新建日程，返回时间上冲突的已有日程
+pub async fn create_event(new: &NewEvent) -> Result<CreatedEvent, String> {
+    let title = new.title.trim();
+    if title.is_empty() {
+        return Err("日程标题不能为空".to_string());
+    }
+    let source = sources(new.calendar.as_deref())?.remove(0);
+    let (start, date_only) = parse_input_time(&new.start)?;
+    let all_day = new.all_day || date_only;
+    let end = match new.end.as_deref().filter(|s| !s.trim().is_empty()) {
+        Some(end) => match parse_input_time(end)? {
+            // 全天日程的结束日期包含当天
+            (end, true) if all_day => end + chrono::Duration::days(1),
+            (end, _) => end,
+        },
+        None if all_day => start + chrono::Duration::days(1),
+        None => start + chrono::Duration::minutes(new.duration_minutes.unwrap_or(DEFAULT_DURATION_MINUTES).max(1)),
+    };
+    if end <= start {
+        return Err("结束时间必须晚于开始时间".to_string());
+    }
+
+    let conflicts = match sources(None) {
+        Ok(all) => collect_events(&all, start, end)
+            .await
+            .events
+            .into_iter()
+            .filter(|e| !e.all_day)
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    let event = VEvent {
+        uid: new_uid()?,
+        summary: title.to_string(),
+        location: new.location.clone().filter(|s| !s.trim().is_empty()),
+        description: new.description.clone().filter(|s| !s.trim().is_empty()),
+        start,
+        end,
+        all_day,
+        ..Default::default()
+    };
+    let vevent = ics::to_vevent(&event);
+    match source.kind {
+        CalendarKind::Ics => append_to_file(&ics_path(&source)?, &vevent)?,
+        CalendarKind::Caldav => put_caldav(&source, &event.uid, &vevent).await?,
+    }
+    eprintln!("[Tauri] 📅 已创建日程: {}（{}，{}）", title, source.name, start.format("%Y-%m-%d %H:%M"));
+    Ok(CreatedEvent {
+        event: to_calendar_event(&source.name, &event),
+        conflicts,
+    })
+}
+
+/// calendar.list 工具调用，args 即时间范围
+pub fn call_list(args: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let range: EventRange = serde_json::from_value(args.clone()).map_err(|e| format!("参数格式错误: {}", e))?;
+    let list = tauri::async_runtime::block_on(list_events(&range))?;
+    serde_json::to_value(list).map_err(|e| format!("序列化日程失败: {}", e))
+}
+
+/// calendar.create 工具调用，args 即新日程
+pub fn call_create(args: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let new: NewEvent = serde_json::from_value(args.clone()).map_err(|e| format!("参数格式错误: {}", e))?;
+    let created = tauri::async_runtime::block_on(create_event(&new))?;
+    serde_json::to_value(created).map_err(|e| format!("序列化日程失败: {}", e))
+}
+
+/// 列出时间范围内（默认今后 7 天）的日程
+#[tauri::command]
+pub async fn list_upcoming_events(range: Option<EventRange>) -> Result<EventList, String> {
+    list_events(&range.unwrap_or_default()).await
+}
+
+/// 新建日程
+#[tauri::command]
+pub async fn create_calendar_event(event: NewEvent) -> Result<CreatedEvent, String> {
+    create_event(&event).await
+}
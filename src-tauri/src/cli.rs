@@ -0,0 +1,255 @@
+//! 命令行模式：`deskjarvis --exec "指令" [--json]` 不打开窗口直接执行一条指令
+//!
+//! 进度输出到 stderr（--json 时不输出），结果输出到 stdout。
+//! 退出码：0 成功，1 任务失败，2 参数错误或无法执行。
+
+use serde::Serialize;
+
+use crate::event_sink::EventSink;
+use crate::history::{new_task_id, now_millis, HistoryStore};
+use crate::{config, email_oauth, language, prompt_log, redaction, sandbox, webhooks, TaskRequest, TaskResult};
+
+/// 等待 Python 服务退出应答的时间
+const SHUTDOWN_TIMEOUT_SECS: u64 = 5;
+
+/// 命令行参数
+pub struct CliOptions {
+    instruction: String,
+    json: bool,
+}
+
+/// 解析命令行参数；未指定 --exec 时返回 None（正常启动界面）
+pub fn parse_args(args: &[String]) -> Option<Result<CliOptions, String>> {
+    if !args.iter().any(|a| a == "--exec" || a.starts_with("--exec=")) {
+        return None;
+    }
+
+    let mut instruction = None;
+    let mut json = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--exec" => match iter.next() {
+                Some(value) => instruction = Some(value.clone()),
+                None => return Some(Err("--exec 缺少指令".to_string())),
+            },
+            other => match other.strip_prefix("--exec=") {
+                Some(value) => instruction = Some(value.to_string()),
+                None => return Some(Err(format!("未知参数: {}", other))),
+            },
+        }
+    }
+
+    Some(
+        instruction
+            .map(|i| i.trim().to_string())
+            .filter(|i| !i.is_empty())
+            .map(|instruction| CliOptions { instruction, json })
+            .ok_or_else(|| "--exec 缺少指令".to_string()),
+    )
+}
+
+/// 把任务事件打印到 stderr
+struct ConsoleSink {
+    quiet: bool,
+}
+
+impl EventSink for ConsoleSink {
+    fn send<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        if self.quiet {
+            return;
+        }
+        let Ok(payload) = serde_json::to_value(payload) else {
+            return;
+        };
+        let text = |key: &str| payload.get(key).and_then(|v| v.as_str()).unwrap_or("");
+        match event {
+            "task-stream" => eprint!("{}", text("delta")),
+            "task-usage" => eprintln!(
+                "[usage] {} 累计 ${:.4}",
+                text("model"),
+                payload
+                    .get("total_cost_usd")
+                    .and_then(|v| v.as_f64())
+                    .unwrap_or_default()
+            ),
+            "task-stuck" => eprintln!(
+                "[stuck] 任务已 {} 秒没有进度，继续等待",
+                payload.get("idle_secs").and_then(|v| v.as_u64()).unwrap_or_default()
+            ),
+            "task-warning" => eprintln!("[warning] {}", text("message")),
+            "task-progress" => {
+                let data = payload.get("data");
+                let detail = ["description", "message", "content"]
+                    .iter()
+                    .find_map(|key| data.and_then(|d| d.get(*key)).and_then(|v| v.as_str()))
+                    .unwrap_or("");
+                if !detail.is_empty() {
+                    eprintln!("[{}] {}", text("type"), detail);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn interactive(&self) -> bool {
+        false
+    }
+}
+
+/// 启动 Python 服务执行，服务不可用时降级为单次进程
+async fn run_request(sink: &ConsoleSink, request: &TaskRequest) -> Result<TaskResult, String> {
+    let mut server = match crate::launch_python_server().await {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("[Tauri] ⚠️ 无法启动常驻服务: {}，降级为单次模式", e);
+            return crate::execute_oneshot(sink, request).await.map_err(String::from);
+        }
+    };
+
+    match crate::execute_via_server(sink, &mut server, request).await {
+        Ok(result) => {
+            let cmd = serde_json::json!({
+                "cmd": "shutdown",
+                "id": format!("bye_{}", now_millis()),
+            });
+            let _ = crate::send_control_command(
+                &mut server,
+                &cmd,
+                "shutdown_ack",
+                std::time::Duration::from_secs(SHUTDOWN_TIMEOUT_SECS),
+            )
+            .await;
+            Ok(result)
+        }
+        Err(e) => {
+            eprintln!("[Tauri] ⚠️ 常驻进程执行失败: {}，降级为单次模式", e);
+            drop(server);
+            crate::execute_oneshot(sink, request).await.map_err(String::from)
+        }
+    }
+}
+
+/// 执行一条指令并登记任务历史
+async fn execute(options: &CliOptions, sink: &ConsoleSink) -> Result<TaskResult, String> {
+    redaction::init();
+    let history = HistoryStore::load();
+
+    let request_id = new_task_id();
+    let work_dir = match sandbox::create_task_dir(&request_id) {
+        Ok(dir) => Some(dir),
+        Err(e) => {
+            eprintln!("[Tauri] ⚠️ {}，使用共享沙盒目录", e);
+            None
+        }
+    };
+    history.record_start(
+        &request_id,
+        &options.instruction,
+        work_dir.as_ref().map(|d| d.to_string_lossy().to_string()),
+    );
+    let started_hook = history
+        .get(&request_id)
+        .map(|record| tokio::spawn(async move { webhooks::send(&record).await }));
+
+    let app_config = config::load_config().ok();
+    let response_language = language::response_language(&options.instruction, app_config.as_ref());
+    let email_oauth = email_oauth::task_credential(app_config.as_ref()).await;
+    let request = TaskRequest {
+        id: request_id,
+        instruction: options.instruction.clone(),
+        context: language::apply_hint(None, response_language),
+        work_dir,
+        log_prompts: prompt_log::enabled(app_config.as_ref(), false),
+        max_cost_usd: app_config.and_then(|c| c.max_task_cost_usd),
+        session_id: None,
+        resume: None,
+        email_oauth,
+        interactive: false,
+    };
+    let mut result = run_request(sink, &request).await;
+
+    match &result {
+        Ok(r) => {
+            history.record_finish(&request.id, r.success, &r.message, r.termination_reason);
+            r.record_audit(&request.id);
+        }
+        Err(e) => history.record_finish(&request.id, false, e, None),
+    }
+    if let Some(usage) = crate::usage::finish(sink, &request.id) {
+        history.record_usage(&request.id, usage);
+    }
+    if let Some(dir) = &request.work_dir {
+        let names: Vec<String> = sandbox::scan_artifacts(dir)
+            .into_iter()
+            .map(|a| a.name)
+            .collect();
+        history.record_artifacts(&request.id, names.clone());
+        if let Ok(r) = &mut result {
+            r.artifacts = names;
+        }
+    }
+    // 命令行模式在进程退出前等待 Webhook 发送完成
+    if let Some(handle) = started_hook {
+        let _ = handle.await;
+    }
+    if let Some(record) = history.get(&request.id) {
+        webhooks::send(&record).await;
+    }
+    result
+}
+
+/// 执行命令行模式，返回进程退出码
+pub fn run(options: Result<CliOptions, String>) -> i32 {
+    let options = match options {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("用法: deskjarvis --exec \"指令\" [--json]");
+            return 2;
+        }
+    };
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("创建运行时失败: {}", e);
+            return 2;
+        }
+    };
+    let sink = ConsoleSink {
+        quiet: options.json,
+    };
+    let outcome = runtime.block_on(execute(&options, &sink));
+
+    let (result, code) = match outcome {
+        Ok(result) => {
+            let code = if result.success { 0 } else { 1 };
+            (result, code)
+        }
+        Err(e) => (
+            TaskResult {
+                success: false,
+                message: e,
+                steps: Vec::new(),
+                user_instruction: options.instruction.clone(),
+                truncated_by_budget: false,
+                termination_reason: None,
+                artifacts: Vec::new(),
+            },
+            2,
+        ),
+    };
+
+    if options.json {
+        match serde_json::to_string(&result) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("序列化结果失败: {}", e),
+        }
+    } else {
+        let mark = if result.success { "✅" } else { "❌" };
+        println!("{} {}", mark, result.message);
+    }
+    code
+}
@@ -0,0 +1,325 @@
+//! 剪贴板：读写文本和图片，并在指令提到剪贴板时把当前文本带入任务 context
+//!
+//! 按平台调用系统工具：macOS 用 pbpaste/pbcopy 与 osascript（图片），Linux 在 Wayland 下用
+//! wl-paste/wl-copy、X11 下用 xclip，Windows 用 PowerShell。图片统一为 PNG，前端以 base64 传递。
+//!
+//! 配置 clipboard_context 未关闭时（默认开启），指令含"剪贴板"等词会把剪贴板文本写入
+//! context.clipboard_text，"总结剪贴板内容"无需手动粘贴。
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+/// 触发自动带入剪贴板文本的词
+const TRIGGER_WORDS: &[&str] = &["剪贴板", "剪切板", "粘贴板", "clipboard"];
+
+/// 带入 context 的剪贴板文本最大长度（字符）
+const MAX_CONTEXT_CHARS: usize = 20_000;
+
+/// 剪贴板内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ClipboardContent {
+    Text {
+        text: String,
+    },
+    /// PNG 图片（base64 编码）
+    Image {
+        png_base64: String,
+    },
+    Empty,
+}
+
+/// 执行命令并返回 stdout；input 非空时写入 stdin，不读取输出
+///
+/// xclip、wl-copy 写入后会留下后台进程持有剪贴板，继承的输出管道会让等待一直阻塞。
+fn run(program: &str, args: &[&str], input: Option<&[u8]>) -> Result<Vec<u8>, String> {
+    let writing = input.is_some();
+    let pipe = |yes: bool| if yes { Stdio::piped() } else { Stdio::null() };
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(pipe(writing))
+        .stdout(pipe(!writing))
+        .stderr(pipe(!writing))
+        .spawn()
+        .map_err(|e| format!("运行 {} 失败: {}", program, e))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin
+            .write_all(input)
+            .map_err(|e| format!("写入 {} 失败: {}", program, e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("运行 {} 失败: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} 执行失败: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// 图片读写使用的临时文件
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn temp_png() -> std::path::PathBuf {
+    std::env::temp_dir().join(format!(
+        "deskjarvis_clipboard_{}.png",
+        crate::history::now_millis()
+    ))
+}
+
+#[cfg(target_os = "macos")]
+fn read_text() -> Result<String, String> {
+    run("pbpaste", &[], None).map(|out| String::from_utf8_lossy(&out).to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn write_text(text: &str) -> Result<(), String> {
+    run("pbcopy", &[], Some(text.as_bytes())).map(|_| ())
+}
+
+#[cfg(target_os = "macos")]
+fn read_image() -> Result<Option<Vec<u8>>, String> {
+    let script = r#"on run argv
+    try
+        set png to the clipboard as «class PNGf»
+    on error
+        return "none"
+    end try
+    set f to open for access (POSIX file (item 1 of argv)) with write permission
+    set eof f to 0
+    write png to f
+    close access f
+    return "ok"
+end run"#;
+    let path = temp_png();
+    let out = run("osascript", &["-e", script, &path.to_string_lossy()], None)?;
+    if String::from_utf8_lossy(&out).trim() != "ok" {
+        return Ok(None);
+    }
+    let bytes = std::fs::read(&path).map_err(|e| format!("读取剪贴板图片失败: {}", e));
+    let _ = std::fs::remove_file(&path);
+    bytes.map(Some)
+}
+
+#[cfg(target_os = "macos")]
+fn write_image(png: &[u8]) -> Result<(), String> {
+    let script = r#"on run argv
+    set the clipboard to (read (POSIX file (item 1 of argv)) as «class PNGf»)
+end run"#;
+    let path = temp_png();
+    std::fs::write(&path, png).map_err(|e| format!("写入临时图片失败: {}", e))?;
+    let result = run("osascript", &["-e", script, &path.to_string_lossy()], None);
+    let _ = std::fs::remove_file(&path);
+    result.map(|_| ())
+}
+
+/// Wayland 会话使用 wl-clipboard，否则使用 xclip
+#[cfg(target_os = "linux")]
+fn is_wayland() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+#[cfg(target_os = "linux")]
+fn read_text() -> Result<String, String> {
+    let out = if is_wayland() {
+        run("wl-paste", &["--no-newline", "--type", "text/plain"], None)?
+    } else {
+        run("xclip", &["-selection", "clipboard", "-o"], None)?
+    };
+    Ok(String::from_utf8_lossy(&out).to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn write_text(text: &str) -> Result<(), String> {
+    if is_wayland() {
+        run("wl-copy", &[], Some(text.as_bytes())).map(|_| ())
+    } else {
+        run("xclip", &["-selection", "clipboard"], Some(text.as_bytes())).map(|_| ())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_image() -> Result<Option<Vec<u8>>, String> {
+    let types = if is_wayland() {
+        run("wl-paste", &["--list-types"], None)?
+    } else {
+        run("xclip", &["-selection", "clipboard", "-t", "TARGETS", "-o"], None)?
+    };
+    if !String::from_utf8_lossy(&types).lines().any(|t| t.trim() == "image/png") {
+        return Ok(None);
+    }
+    let png = if is_wayland() {
+        run("wl-paste", &["--type", "image/png"], None)?
+    } else {
+        run("xclip", &["-selection", "clipboard", "-t", "image/png", "-o"], None)?
+    };
+    Ok(Some(png))
+}
+
+#[cfg(target_os = "linux")]
+fn write_image(png: &[u8]) -> Result<(), String> {
+    if is_wayland() {
+        run("wl-copy", &["--type", "image/png"], Some(png)).map(|_| ())
+    } else {
+        run("xclip", &["-selection", "clipboard", "-t", "image/png"], Some(png)).map(|_| ())
+    }
+}
+
+/// PowerShell 单引号字符串中的路径
+#[cfg(target_os = "windows")]
+fn ps_quote(path: &std::path::Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "''"))
+}
+
+#[cfg(target_os = "windows")]
+fn powershell(script: &str, input: Option<&[u8]>) -> Result<Vec<u8>, String> {
+    run("powershell", &["-NoProfile", "-NonInteractive", "-Command", script], input)
+}
+
+#[cfg(target_os = "windows")]
+fn read_text() -> Result<String, String> {
+    let out = powershell(
+        "[Console]::OutputEncoding = [Text.Encoding]::UTF8; Get-Clipboard -Raw",
+        None,
+    )?;
+    Ok(String::from_utf8_lossy(&out).trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn write_text(text: &str) -> Result<(), String> {
+    powershell(
+        "[Console]::InputEncoding = [Text.Encoding]::UTF8; Set-Clipboard -Value ([Console]::In.ReadToEnd())",
+        Some(text.as_bytes()),
+    )
+    .map(|_| ())
+}
+
+#[cfg(target_os = "windows")]
+fn read_image() -> Result<Option<Vec<u8>>, String> {
+    let path = temp_png();
+    let script = format!(
+        "Add-Type -AssemblyName System.Windows.Forms; $img = [System.Windows.Forms.Clipboard]::GetImage(); \
+         if ($img) {{ $img.Save({}, [System.Drawing.Imaging.ImageFormat]::Png) }}",
+        ps_quote(&path)
+    );
+    powershell(&script, None)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let bytes = std::fs::read(&path).map_err(|e| format!("读取剪贴板图片失败: {}", e));
+    let _ = std::fs::remove_file(&path);
+    bytes.map(Some)
+}
+
+#[cfg(target_os = "windows")]
+fn write_image(png: &[u8]) -> Result<(), String> {
+    let path = temp_png();
+    std::fs::write(&path, png).map_err(|e| format!("写入临时图片失败: {}", e))?;
+    let script = format!(
+        "Add-Type -AssemblyName System.Windows.Forms; Add-Type -AssemblyName System.Drawing; \
+         $img = [System.Drawing.Image]::FromFile({}); [System.Windows.Forms.Clipboard]::SetImage($img); $img.Dispose()",
+        ps_quote(&path)
+    );
+    let result = powershell(&script, None);
+    let _ = std::fs::remove_file(&path);
+    result.map(|_| ())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn read_text() -> Result<String, String> {
+    Err("当前平台不支持读取剪贴板".to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn write_text(_text: &str) -> Result<(), String> {
+    Err("当前平台不支持写入剪贴板".to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn read_image() -> Result<Option<Vec<u8>>, String> {
+    Ok(None)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn write_image(_png: &[u8]) -> Result<(), String> {
+    Err("当前平台不支持写入剪贴板".to_string())
+}
+
+/// 读取剪贴板：有文本时返回文本，否则尝试读取图片
+fn read() -> Result<ClipboardContent, String> {
+    // 剪贴板只有图片时，部分平台读取文本会报错
+    if let Ok(text) = read_text() {
+        if !text.is_empty() {
+            return Ok(ClipboardContent::Text { text });
+        }
+    }
+    Ok(match read_image()? {
+        Some(png) => ClipboardContent::Image {
+            png_base64: base64::engine::general_purpose::STANDARD.encode(png),
+        },
+        None => ClipboardContent::Empty,
+    })
+}
+
+fn write(content: &ClipboardContent) -> Result<(), String> {
+    match content {
+        ClipboardContent::Text { text } => write_text(text),
+        ClipboardContent::Image { png_base64 } => {
+            let png = base64::engine::general_purpose::STANDARD
+                .decode(png_base64)
+                .map_err(|e| format!("图片数据无效: {}", e))?;
+            write_image(&png)
+        }
+        ClipboardContent::Empty => write_text(""),
+    }
+}
+
+/// 指令提到剪贴板且未关闭该选项时，把剪贴板文本写入 context.clipboard_text
+pub async fn apply_context(
+    context: Option<serde_json::Value>,
+    instruction: &str,
+    app_config: Option<&config::AppConfig>,
+) -> Option<serde_json::Value> {
+    let enabled = app_config.and_then(|c| c.clipboard_context).unwrap_or(true);
+    let lower = instruction.to_lowercase();
+    if !enabled || !TRIGGER_WORDS.iter().any(|w| lower.contains(w)) {
+        return context;
+    }
+    let text = match tauri::async_runtime::spawn_blocking(read_text).await {
+        Ok(Ok(text)) if !text.trim().is_empty() => text,
+        Ok(Err(e)) => {
+            eprintln!("[Tauri] ⚠️ 读取剪贴板失败: {}", e);
+            return context;
+        }
+        _ => return context,
+    };
+    let text: String = text.chars().take(MAX_CONTEXT_CHARS).collect();
+    let mut context = context.unwrap_or_else(|| serde_json::json!({}));
+    if let Some(map) = context.as_object_mut() {
+        map.entry("clipboard_text")
+            .or_insert(serde_json::Value::String(text));
+    }
+    Some(context)
+}
+
+/// 读取剪贴板（文本优先，其次 PNG 图片）
+#[tauri::command]
+pub async fn read_clipboard() -> Result<ClipboardContent, String> {
+    tauri::async_runtime::spawn_blocking(read)
+        .await
+        .map_err(|e| format!("读取剪贴板失败: {}", e))?
+}
+
+/// 写入剪贴板，图片为 base64 编码的 PNG
+#[tauri::command]
+pub async fn write_clipboard(content: ClipboardContent) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || write(&content))
+        .await
+        .map_err(|e| format!("写入剪贴板失败: {}", e))?
+}
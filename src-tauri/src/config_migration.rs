@@ -0,0 +1,92 @@
+//! 配置文件版本迁移：读取时按 version 依次升级到当前结构
+//!
+//! 迁移在反序列化之前对 JSON 进行，旧文件缺少新增的必填字段时用默认值补齐，
+//! 避免结构调整后老用户遇到"解析配置文件失败"。迁移后先把原文件备份为
+//! config.v<旧版本>.bak.json 再写回。新增结构变化时在 MIGRATIONS 末尾追加一步并提升 CURRENT_VERSION。
+
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::config::AppConfig;
+
+/// 当前配置结构版本
+pub const CURRENT_VERSION: u32 = 1;
+
+/// 迁移步骤：MIGRATIONS[n] 把 vn 升级到 vn+1
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[v0_to_v1];
+
+/// v0 → v1：扁平的 provider / api_key / model / base_url 生成默认档案
+fn v0_to_v1(config: &mut Map<String, Value>) {
+    let has_profiles = config
+        .get("profiles")
+        .and_then(|p| p.as_array())
+        .is_some_and(|p| !p.is_empty());
+    if has_profiles {
+        return;
+    }
+    let field = |key: &str| config.get(key).cloned().unwrap_or(Value::String(String::new()));
+    let profile = serde_json::json!({
+        "name": "default",
+        "provider": field("provider"),
+        "api_key": field("api_key"),
+        "model": field("model"),
+        "base_url": config.get("base_url").cloned().unwrap_or(Value::Null),
+        "env": {},
+    });
+    config.insert("profiles".to_string(), Value::Array(vec![profile]));
+    config.insert("active_profile".to_string(), Value::String("default".to_string()));
+}
+
+/// 配置文件中的版本，没有 version 字段的是 v0
+fn version_of(config: &Map<String, Value>) -> u32 {
+    config
+        .get("version")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32)
+        .unwrap_or(0)
+}
+
+/// 把配置 JSON 迁移到当前版本并补齐缺失字段，返回迁移前的版本（已是当前版本时为 None）
+pub fn migrate(value: &mut Value) -> Result<Option<u32>, String> {
+    let config = value.as_object_mut().ok_or("配置文件不是 JSON 对象")?;
+    let from = version_of(config);
+    if from > CURRENT_VERSION {
+        // 新版本应用写入的配置：不降级，尽量按当前结构读取
+        eprintln!(
+            "[Tauri] ⚠️ 配置文件版本 {} 高于当前支持的版本 {}，部分设置可能被忽略",
+            from, CURRENT_VERSION
+        );
+        return Ok(None);
+    }
+
+    // 缺失的顶层字段用默认值补齐（旧文件缺少后来新增的必填字段）
+    let defaults = serde_json::to_value(AppConfig::default()).map_err(|e| format!("序列化默认配置失败: {}", e))?;
+    let mut filled = false;
+    if let Value::Object(defaults) = defaults {
+        for (key, default) in defaults {
+            if !config.contains_key(&key) {
+                config.insert(key, default);
+                filled = true;
+            }
+        }
+    }
+    if from == CURRENT_VERSION {
+        return Ok(filled.then_some(from));
+    }
+
+    for step in &MIGRATIONS[from as usize..] {
+        step(config);
+    }
+    config.insert("version".to_string(), Value::from(CURRENT_VERSION));
+    Ok(Some(from))
+}
+
+/// 迁移前备份原配置文件：config.json → config.v<版本>.bak.json
+pub fn backup(path: &Path, from: u32) -> Result<(), String> {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("config");
+    let backup = path.with_file_name(format!("{}.v{}.bak.json", stem, from));
+    std::fs::copy(path, &backup).map_err(|e| format!("备份配置文件失败: {}", e))?;
+    eprintln!("[Tauri] 💾 已备份迁移前的配置: {}", backup.display());
+    Ok(())
+}
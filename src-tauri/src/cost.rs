@@ -0,0 +1,130 @@
+//! 模型费用估算与单任务费用上限
+//!
+//! Python 服务每次调用模型后上报 usage 事件，这里按模型单价累计本任务费用，
+//! 超出上限时由调用方中止任务。单价为公开标价的近似值，仅用于限额控制。
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent_event::UsageEvent;
+use crate::config;
+use crate::event_sink::EventSink;
+use crate::usage;
+
+/// 模型名关键字 → (输入, 输出) 每百万 token 美元单价，按顺序匹配，越具体越靠前
+const MODEL_PRICES: &[(&str, f64, f64)] = &[
+    ("claude-opus", 15.0, 75.0),
+    ("claude-3-opus", 15.0, 75.0),
+    ("haiku", 0.8, 4.0),
+    ("claude", 3.0, 15.0),
+    ("gpt-4o-mini", 0.15, 0.6),
+    ("gpt-4.1-nano", 0.1, 0.4),
+    ("gpt-4.1-mini", 0.4, 1.6),
+    ("gpt-4.1", 2.0, 8.0),
+    ("gpt-4o", 2.5, 10.0),
+    ("o1", 15.0, 60.0),
+    ("o3-mini", 1.1, 4.4),
+    ("o4-mini", 1.1, 4.4),
+    ("o3", 2.0, 8.0),
+    ("gpt-3.5", 0.5, 1.5),
+    ("gpt", 2.5, 10.0),
+    ("deepseek-reasoner", 0.55, 2.19),
+    ("deepseek", 0.27, 1.1),
+    ("grok", 3.0, 15.0),
+];
+
+/// 未知模型按较高单价估算，宁可提前中止也不超支
+const FALLBACK_PRICE: (f64, f64) = (3.0, 15.0);
+
+/// 配置中的模型单价，优先于内置单价（如本地模型可设为 0）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// 模型名关键字，模型名包含该关键字即匹配
+    pub model: String,
+    /// 每百万输入 token 美元单价
+    pub input: f64,
+    /// 每百万输出 token 美元单价
+    pub output: f64,
+}
+
+/// 估算一次调用的费用（美元），先按配置的单价匹配，再按内置单价
+pub fn estimate_cost(prices: &[ModelPrice], model: &str, input_tokens: u64, output_tokens: u64) -> f64 {
+    let model = model.to_lowercase();
+    let (input_price, output_price) = prices
+        .iter()
+        .find(|p| !p.model.is_empty() && model.contains(&p.model.to_lowercase()))
+        .map(|p| (p.input, p.output))
+        .or_else(|| {
+            MODEL_PRICES
+                .iter()
+                .find(|(key, _, _)| model.contains(key))
+                .map(|(_, i, o)| (*i, *o))
+        })
+        .unwrap_or(FALLBACK_PRICE);
+    (input_tokens as f64 * input_price + output_tokens as f64 * output_price) / 1_000_000.0
+}
+
+/// task-usage 事件负载
+#[derive(Debug, Clone, Serialize)]
+struct UsagePayload<'a> {
+    request_id: &'a str,
+    model: &'a str,
+    input_tokens: u64,
+    output_tokens: u64,
+    cost_usd: f64,
+    total_cost_usd: f64,
+    max_cost_usd: Option<f64>,
+}
+
+/// 单个任务的费用累计
+pub struct CostTracker {
+    request_id: String,
+    max_cost_usd: Option<f64>,
+    total_cost_usd: f64,
+    prices: Vec<ModelPrice>,
+}
+
+impl CostTracker {
+    pub fn new(request_id: &str, max_cost_usd: Option<f64>) -> Self {
+        CostTracker {
+            request_id: request_id.to_string(),
+            max_cost_usd: max_cost_usd.filter(|c| *c > 0.0),
+            total_cost_usd: 0.0,
+            prices: config::load_config().map(|c| c.model_prices).unwrap_or_default(),
+        }
+    }
+
+    pub fn total_cost_usd(&self) -> f64 {
+        self.total_cost_usd
+    }
+
+    pub fn max_cost_usd(&self) -> Option<f64> {
+        self.max_cost_usd
+    }
+
+    /// 累计一条 usage 事件并以 task-usage 通知前端，超出上限时返回 true
+    pub fn record(&mut self, sink: &impl EventSink, event: &UsageEvent) -> bool {
+        let model = event.model.as_str();
+        let input_tokens = event.input_tokens;
+        let output_tokens = event.output_tokens;
+
+        let cost_usd = estimate_cost(&self.prices, model, input_tokens, output_tokens);
+        self.total_cost_usd += cost_usd;
+        usage::record(&self.request_id, model, input_tokens, output_tokens, cost_usd);
+
+        sink.send(
+            "task-usage",
+            UsagePayload {
+                request_id: &self.request_id,
+                model,
+                input_tokens,
+                output_tokens,
+                cost_usd,
+                total_cost_usd: self.total_cost_usd,
+                max_cost_usd: self.max_cost_usd,
+            },
+        );
+
+        self.max_cost_usd
+            .is_some_and(|max| self.total_cost_usd >= max)
+    }
+}
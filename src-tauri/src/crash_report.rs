@@ -0,0 +1,160 @@
+//! 崩溃报告：Python 服务在执行任务时崩溃后保存现场
+//!
+//! 服务 stderr 的最后 200 行（已脱敏）保存在环形缓冲区中，崩溃时连同指令、
+//! 退出码一起写入 ~/.deskjarvis/crashes/<id>.json，供事后排查。
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::{config, history, telemetry};
+
+/// 保留的 stderr 行数
+const TAIL_LINES: usize = 200;
+
+/// 等待进程退出和 stderr 读完的最长时间（毫秒）：进程退出后管道里可能还有未转发的行
+const DRAIN_TIMEOUT_MILLIS: u64 = 1000;
+
+/// Python 服务 stderr 的最后若干行
+#[derive(Default)]
+pub struct StderrTail {
+    lines: Mutex<VecDeque<String>>,
+    closed: AtomicBool,
+}
+
+impl StderrTail {
+    /// 追加一行（超出容量时丢弃最旧的行）
+    pub fn push(&self, line: &str) {
+        if let Ok(mut lines) = self.lines.lock() {
+            if lines.len() >= TAIL_LINES {
+                lines.pop_front();
+            }
+            lines.push_back(line.trim_end().to_string());
+        }
+    }
+
+    /// stderr 已读到 EOF
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    /// 等 stderr 读完（最多 1 秒）后取出全部行
+    async fn snapshot(&self) -> Vec<String> {
+        let deadline =
+            std::time::Instant::now() + std::time::Duration::from_millis(DRAIN_TIMEOUT_MILLIS);
+        while !self.closed.load(Ordering::SeqCst) && std::time::Instant::now() < deadline {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        self.lines
+            .lock()
+            .map(|lines| lines.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// 崩溃报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    /// 崩溃时间（毫秒时间戳）
+    pub timestamp: u64,
+    pub request_id: String,
+    pub instruction: String,
+    /// 进程退出码，被信号终止或仍未退出时为 None
+    pub exit_code: Option<i32>,
+    pub stderr_tail: Vec<String>,
+}
+
+/// 崩溃报告列表项（不含 stderr）
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashReportSummary {
+    pub id: String,
+    pub timestamp: u64,
+    pub request_id: String,
+    pub instruction: String,
+    pub exit_code: Option<i32>,
+}
+
+fn crashes_dir() -> Result<PathBuf, String> {
+    Ok(config::get_data_dir()?.join("crashes"))
+}
+
+/// id 用作文件名，只允许字母、数字和下划线
+fn report_path(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("无效的崩溃报告 ID: {}", id));
+    }
+    Ok(crashes_dir()?.join(format!("{}.json", id)))
+}
+
+/// 服务崩溃后写入报告
+pub async fn capture(server: &mut crate::PythonServer, request: &crate::TaskRequest) {
+    // stdout 读到 EOF 时进程可能尚未退出完毕，稍等以取得退出码
+    let drain = std::time::Duration::from_millis(DRAIN_TIMEOUT_MILLIS);
+    let exit_code = tokio::time::timeout(drain, server.child.wait())
+        .await
+        .ok()
+        .and_then(|status| status.ok())
+        .and_then(|status| status.code());
+    telemetry::server_crashed();
+    let timestamp = history::now_millis();
+    let report = CrashReport {
+        id: format!("crash_{}", timestamp),
+        timestamp,
+        request_id: request.id.clone(),
+        instruction: request.instruction.clone(),
+        exit_code,
+        stderr_tail: server.stderr_tail.snapshot().await,
+    };
+
+    let result = report_path(&report.id).and_then(|path| {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(&report)
+            .map_err(|e| format!("序列化崩溃报告失败: {}", e))?;
+        std::fs::write(&path, content).map_err(|e| format!("写入崩溃报告失败: {}", e))?;
+        Ok(path)
+    });
+    match result {
+        Ok(path) => eprintln!("[Tauri] 📝 已保存崩溃报告: {}", path.display()),
+        Err(e) => eprintln!("[Tauri] ⚠️ {}", e),
+    }
+}
+
+/// 列出崩溃报告（最新的在前）
+#[tauri::command]
+pub async fn list_crash_reports() -> Result<Vec<CrashReportSummary>, String> {
+    let dir = crashes_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries = std::fs::read_dir(&dir).map_err(|e| format!("读取崩溃报告目录失败: {}", e))?;
+    let mut reports: Vec<CrashReportSummary> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str::<CrashReport>(&content).ok())
+        .map(|report| CrashReportSummary {
+            id: report.id,
+            timestamp: report.timestamp,
+            request_id: report.request_id,
+            instruction: report.instruction,
+            exit_code: report.exit_code,
+        })
+        .collect();
+    reports.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
+    Ok(reports)
+}
+
+/// 用系统默认程序打开崩溃报告
+#[tauri::command]
+pub async fn open_crash_report(id: String) -> Result<(), String> {
+    let path = report_path(&id)?;
+    if !path.exists() {
+        return Err(format!("未找到崩溃报告: {}", id));
+    }
+    crate::file_actions::open_path(&path).map_err(String::from)
+}
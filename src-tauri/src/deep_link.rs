@@ -0,0 +1,219 @@
+//! deskjarvis:// 链接：从浏览器书签或其他应用触发任务
+//!
+//! 支持 `deskjarvis://run?instruction=...`。未开启 auto_confirm 时，
+//! 先通过 deep-link-request 事件请前端确认，再由 respond_deep_link 决定是否执行。
+//!
+//! macOS 通过 Info.plist 注册协议，链接以 RunEvent::Opened 送达；
+//! Windows / Linux 在启动时注册协议，链接作为命令行参数启动新进程，再经单实例转交。
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Url};
+
+use crate::config;
+use crate::history::{new_task_id, now_millis};
+use crate::window_manager;
+
+/// 协议名
+pub const SCHEME: &str = "deskjarvis";
+
+/// 等待前端确认的链接请求（请求 ID → 指令）
+static PENDING: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// 首次启动时命令行中的链接，等窗口加载后处理
+static LAUNCH_URLS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// deep-link-request 事件负载
+#[derive(Debug, Clone, Serialize)]
+struct DeepLinkRequest {
+    request_id: String,
+    instruction: String,
+    url: String,
+}
+
+/// 是否为本应用的链接
+pub fn is_deep_link(arg: &str) -> bool {
+    arg.to_lowercase().starts_with(&format!("{}://", SCHEME))
+}
+
+/// 解析链接中的指令
+fn parse_instruction(raw: &str) -> Result<String, String> {
+    let url = Url::parse(raw).map_err(|e| format!("无效的链接 {}: {}", raw, e))?;
+    if url.scheme() != SCHEME {
+        return Err(format!("不支持的协议: {}", url.scheme()));
+    }
+    // deskjarvis://run?... 中 run 被解析为 host
+    let action = url.host_str().unwrap_or_else(|| url.path().trim_matches('/'));
+    if action != "run" {
+        return Err(format!("不支持的链接操作: {}", action));
+    }
+
+    url.query_pairs()
+        .find(|(key, _)| key == "instruction")
+        .map(|(_, value)| value.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| "链接缺少 instruction 参数".to_string())
+}
+
+/// 在后台执行链接中的指令
+fn run_instruction(app: &AppHandle, instruction: String) {
+    let Some(window) = window_manager::main_window(app) else {
+        eprintln!("[Tauri] ⚠️ 主窗口不存在，无法执行链接中的指令");
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<crate::AppState>();
+        let task_id = new_task_id();
+        if let Err(e) =
+            crate::run_tracked_task(
+                &window,
+                &state,
+                task_id,
+                instruction,
+                None,
+                crate::TaskOptions::default(),
+            )
+            .await
+        {
+            eprintln!("[Tauri] ⚠️ 执行链接中的指令失败: {}", e);
+        }
+    });
+}
+
+/// 处理一条 deskjarvis:// 链接：聚焦主窗口，按 auto_confirm 直接执行或请求确认
+pub fn handle_url(app: &AppHandle, raw: &str) {
+    eprintln!("[Tauri] 🔗 收到链接: {}", raw);
+    if let Err(e) = window_manager::focus_main_window(app) {
+        eprintln!("[Tauri] ⚠️ {}", e);
+    }
+
+    let instruction = match parse_instruction(raw) {
+        Ok(instruction) => instruction,
+        Err(e) => {
+            eprintln!("[Tauri] ⚠️ {}", e);
+            let _ = app.emit("deep-link-error", &e);
+            return;
+        }
+    };
+
+    let auto_confirm = config::load_config()
+        .map(|c| c.auto_confirm)
+        .unwrap_or(false);
+    if auto_confirm {
+        run_instruction(app, instruction);
+        return;
+    }
+
+    let request_id = format!("link_{}", now_millis());
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.insert(request_id.clone(), instruction.clone());
+    }
+    let _ = app.emit(
+        "deep-link-request",
+        DeepLinkRequest {
+            request_id,
+            instruction,
+            url: raw.to_string(),
+        },
+    );
+}
+
+/// 记录首次启动命令行中的链接
+pub fn queue_launch_urls(args: &[String]) {
+    if let Ok(mut urls) = LAUNCH_URLS.lock() {
+        urls.extend(args.iter().filter(|a| is_deep_link(a)).cloned());
+    }
+}
+
+/// 处理首次启动时的链接（窗口加载后调用）
+pub fn flush_launch_urls(app: &AppHandle) {
+    let urls = LAUNCH_URLS
+        .lock()
+        .map(|mut urls| std::mem::take(&mut *urls))
+        .unwrap_or_default();
+    for url in urls {
+        handle_url(app, &url);
+    }
+}
+
+/// 向系统注册 deskjarvis:// 协议（macOS 由 Info.plist 声明，无需处理）
+pub fn register_scheme() -> Result<(), String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("获取程序路径失败: {}", e))?;
+    register_for_platform(&exe.to_string_lossy())
+}
+
+#[cfg(target_os = "windows")]
+fn register_for_platform(exe: &str) -> Result<(), String> {
+    let key = format!(r"HKCU\Software\Classes\{}", SCHEME);
+    let command = format!("\"{}\" \"%1\"", exe);
+    let command_key = format!(r"{}\shell\open\command", key);
+    let entries: [&[&str]; 3] = [
+        &["add", &key, "/ve", "/d", "URL:DeskJarvis", "/f"],
+        &["add", &key, "/v", "URL Protocol", "/d", "", "/f"],
+        &["add", &command_key, "/ve", "/d", &command, "/f"],
+    ];
+    for args in entries {
+        let status = std::process::Command::new("reg")
+            .args(args)
+            .status()
+            .map_err(|e| format!("注册链接协议失败: {}", e))?;
+        if !status.success() {
+            return Err(format!("注册链接协议失败: reg 退出码 {:?}", status.code()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn register_for_platform(exe: &str) -> Result<(), String> {
+    let home = dirs::home_dir().ok_or("无法获取用户主目录")?;
+    let apps_dir = home.join(".local/share/applications");
+    std::fs::create_dir_all(&apps_dir)
+        .map_err(|e| format!("创建目录失败: {}", e))?;
+
+    let desktop_name = format!("{}-url-handler.desktop", SCHEME);
+    let content = format!(
+        "[Desktop Entry]\nType=Application\nName=DeskJarvis\nExec=\"{}\" %u\nNoDisplay=true\nMimeType=x-scheme-handler/{};\n",
+        exe, SCHEME
+    );
+    std::fs::write(apps_dir.join(&desktop_name), content)
+        .map_err(|e| format!("写入链接协议配置失败: {}", e))?;
+
+    let status = std::process::Command::new("xdg-mime")
+        .args(["default", &desktop_name, &format!("x-scheme-handler/{}", SCHEME)])
+        .status()
+        .map_err(|e| format!("注册链接协议失败: {}", e))?;
+    if !status.success() {
+        return Err(format!("注册链接协议失败: xdg-mime 退出码 {:?}", status.code()));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn register_for_platform(_exe: &str) -> Result<(), String> {
+    Ok(())
+}
+
+/// 前端确认或拒绝链接触发的任务
+#[tauri::command]
+pub async fn respond_deep_link(
+    app: AppHandle,
+    request_id: String,
+    approved: bool,
+) -> Result<(), String> {
+    let instruction = PENDING
+        .lock()
+        .map_err(|_| "链接请求状态不可用")?
+        .remove(&request_id)
+        .ok_or_else(|| format!("未找到链接请求: {}", request_id))?;
+
+    if approved {
+        run_instruction(&app, instruction);
+    } else {
+        eprintln!("[Tauri] 🚫 用户拒绝执行链接中的指令");
+    }
+    Ok(())
+}
@@ -0,0 +1,487 @@
+//! 脱离任务：长时间任务在独立的 Python 进程中运行，退出应用后继续执行，下次启动时重新接上
+//!
+//! execute_task_detached 以独立进程组启动 server.py：stdin 是只含一条 execute 命令的文件，
+//! stdout（协议版本 1，每行一个 JSON 事件）追加到 ~/.deskjarvis/detached/<id>/events.jsonl，
+//! 任务结束、读到 stdin 末尾后进程自行退出。暂停、停止和审批本来就经文件交互（task_control、
+//! file_access），不依赖管道。state.json 记录进程 ID 和已转发到的日志位置；下次启动时
+//! resume_all 从头重放日志恢复费用与已完成步骤（不重复发事件），再从记录的位置继续转发。
+//! 没有使用套接字重新接上的原因见 docs/DECISIONS.md 的 ADR-010。
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, System};
+use tauri::{AppHandle, Manager, Window};
+use tokio::io::AsyncBufReadExt;
+
+use crate::agent_event::AgentEvent;
+use crate::error::DeskJarvisError;
+use crate::event_sink::EventSink;
+use crate::{
+    budget, config, cost, file_guard, focus, framing, history, language, policy, prompt_log,
+    provider_health, redaction, sandbox, sandbox_watch, screenshot, stream, task_control, tool_server,
+    webhooks, window_manager,
+    StepResult, TaskRequest, TaskResult,
+};
+
+/// 读到日志末尾后的轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 检查进程是否仍在运行的间隔
+const ALIVE_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 保存转发位置的最短间隔
+const SAVE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 脱离任务的持久化状态（state.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DetachedState {
+    request_id: String,
+    instruction: String,
+    work_dir: Option<PathBuf>,
+    max_cost_usd: Option<f64>,
+    pid: u32,
+    started_at: u64,
+    /// 已转发到前端的日志字节数
+    #[serde(default)]
+    offset: u64,
+}
+
+impl DetachedState {
+    fn request(&self) -> TaskRequest {
+        TaskRequest {
+            id: self.request_id.clone(),
+            instruction: self.instruction.clone(),
+            context: None,
+            work_dir: self.work_dir.clone(),
+            max_cost_usd: self.max_cost_usd,
+            session_id: None,
+            log_prompts: false,
+            resume: None,
+            email_oauth: None,
+            interactive: false,
+        }
+    }
+}
+
+fn detached_dir() -> Result<PathBuf, String> {
+    Ok(config::get_data_dir()?.join("detached"))
+}
+
+/// 任务目录，request_id 只允许字母、数字、下划线和连字符
+fn task_dir(request_id: &str) -> Result<PathBuf, String> {
+    let valid = !request_id.is_empty()
+        && request_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(format!("无效的任务 ID: {}", request_id));
+    }
+    Ok(detached_dir()?.join(request_id))
+}
+
+fn save_state(dir: &Path, state: &DetachedState) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("序列化脱离任务状态失败: {}", e))?;
+    // 先写临时文件再改名，避免退出时留下半个文件
+    let tmp = dir.join("state.json.tmp");
+    std::fs::write(&tmp, content).map_err(|e| format!("保存脱离任务状态失败: {}", e))?;
+    std::fs::rename(&tmp, dir.join("state.json"))
+        .map_err(|e| format!("保存脱离任务状态失败: {}", e))
+}
+
+fn load_states() -> Vec<(PathBuf, DetachedState)> {
+    let Ok(entries) = detached_dir()
+        .and_then(|d| std::fs::read_dir(d).map_err(|e| format!("读取脱离任务目录失败: {}", e)))
+    else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let dir = entry.path();
+            let content = std::fs::read_to_string(dir.join("state.json")).ok()?;
+            match serde_json::from_str(&content) {
+                Ok(state) => Some((dir, state)),
+                Err(e) => {
+                    eprintln!("[Tauri] ⚠️ 解析脱离任务状态失败 {}: {}", dir.display(), e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn detach(cmd: &mut std::process::Command) {
+    use std::os::unix::process::CommandExt;
+    // 独立进程组：应用退出或终端发送 SIGINT 时不会一起结束
+    cmd.process_group(0);
+}
+
+#[cfg(windows)]
+fn detach(cmd: &mut std::process::Command) {
+    use std::os::windows::process::CommandExt;
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    cmd.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(not(any(unix, windows)))]
+fn detach(_cmd: &mut std::process::Command) {}
+
+/// 启动脱离的 Python 服务进程，返回进程 ID
+fn spawn_process(request: &TaskRequest, dir: &Path) -> Result<u32, String> {
+    let python_path = crate::get_python_path()?;
+    let server_path = crate::find_script("server.py")?;
+    let extra_env = config::resolve()
+        .map(|c| crate::launch_env::resolve(&c))
+        .unwrap_or_default();
+
+    let command_path = dir.join("command.jsonl");
+    let mut command_file =
+        std::fs::File::create(&command_path).map_err(|e| format!("写入任务命令失败: {}", e))?;
+    writeln!(command_file, "{}", request.to_command())
+        .map_err(|e| format!("写入任务命令失败: {}", e))?;
+    let open = |name: &str| {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(name))
+            .map_err(|e| format!("打开脱离任务日志失败: {}", e))
+    };
+    let stdin =
+        std::fs::File::open(&command_path).map_err(|e| format!("读取任务命令失败: {}", e))?;
+
+    // detach 会重新设置创建标志，DETACHED_PROCESS 本身就不带控制台窗口
+    let mut cmd = python_path.command();
+    cmd.arg(&server_path)
+        .envs(extra_env)
+        // 日志文件按行读取，不需要帧编码
+        .env(framing::PROTOCOL_ENV, "1")
+        .stdin(stdin)
+        .stdout(open("events.jsonl")?)
+        .stderr(open("stderr.log")?);
+    detach(&mut cmd);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("启动脱离的 Python 服务失败: {}", e))?;
+    let pid = child.id();
+    // 应用运行期间回收退出的子进程，避免僵尸进程被当作仍在运行
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(pid)
+}
+
+fn is_alive(system: &mut System, pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing(),
+    );
+    system
+        .process(pid)
+        .is_some_and(|p| !matches!(p.status(), ProcessStatus::Zombie | ProcessStatus::Dead))
+}
+
+fn kill(system: &mut System, pid: u32) {
+    if is_alive(system, pid) {
+        if let Some(process) = system.process(Pid::from_u32(pid)) {
+            process.kill();
+        }
+    }
+}
+
+/// 重放已转发过的日志时使用：只恢复状态，不再发送事件
+struct Silent;
+
+impl EventSink for Silent {
+    fn send<S: Serialize + Clone>(&self, _event: &str, _payload: S) {}
+
+    fn interactive(&self) -> bool {
+        false
+    }
+}
+
+/// 跟随日志直到收到结果、进程退出或费用超限
+async fn follow_journal(
+    window: &Window,
+    state: &mut DetachedState,
+    dir: &Path,
+) -> Result<TaskResult, String> {
+    let request = state.request();
+    let file = tokio::fs::File::open(dir.join("events.jsonl"))
+        .await
+        .map_err(|e| format!("打开脱离任务日志失败: {}", e))?;
+    let mut reader = tokio::io::BufReader::new(file);
+    let mut system = System::new();
+    let mut line_buf = String::new();
+    // 日志中已完整读取的字节数
+    let mut position: u64 = 0;
+    let mut pending: u64 = 0;
+    let mut stream_buf = stream::StreamBuffer::new(&request.id);
+    let mut cost = cost::CostTracker::new(&request.id, request.max_cost_usd);
+    let mut completed: Vec<StepResult> = Vec::new();
+    let mut last_save = Instant::now();
+    let mut last_alive_check = Instant::now();
+    let mut exited = false;
+
+    loop {
+        let bytes_read = reader
+            .read_line(&mut line_buf)
+            .await
+            .map_err(|e| format!("读取脱离任务日志失败: {}", e))?;
+        pending += bytes_read as u64;
+        if !line_buf.ends_with('\n') {
+            // 读到末尾（可能留下半行）：进程已退出且再无新内容时按崩溃处理
+            if bytes_read == 0 && exited {
+                return match crate::crashed(&request, completed) {
+                    Err(_) => Err("脱离任务的 Python 进程意外退出".to_string()),
+                    result => result,
+                };
+            }
+            stream_buf.flush(window);
+            if position > state.offset && last_save.elapsed() >= SAVE_INTERVAL {
+                state.offset = position;
+                if let Err(e) = save_state(dir, state) {
+                    eprintln!("[Tauri] ⚠️ {}", e);
+                }
+                last_save = Instant::now();
+            }
+            if last_alive_check.elapsed() >= ALIVE_CHECK_INTERVAL {
+                // 进程退出后再读一轮，避免漏掉最后写入的事件
+                exited = !is_alive(&mut system, state.pid);
+                last_alive_check = Instant::now();
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+            continue;
+        }
+        position += pending;
+        pending = 0;
+        let line = std::mem::take(&mut line_buf);
+        let replaying = position <= state.offset;
+
+        let event = match AgentEvent::parse(line.trim(), &request.id) {
+            Ok(Some(event)) => event,
+            Ok(None) => continue,
+            Err(warning) => {
+                if !replaying {
+                    window.send("task-warning", warning);
+                }
+                continue;
+            }
+        };
+        if let AgentEvent::StepSucceeded { data, .. } = &event {
+            completed.push(StepResult::completed(data));
+        }
+
+        match event {
+            AgentEvent::Usage(event) => {
+                let over_budget = if replaying {
+                    cost.record(&Silent, &event)
+                } else {
+                    cost.record(window, &event)
+                };
+                if !over_budget {
+                    continue;
+                }
+                stream_buf.flush(window);
+                kill(&mut system, state.pid);
+                let max = cost.max_cost_usd().unwrap_or_default();
+                eprintln!(
+                    "[Tauri] 💸 脱离任务 {} 费用 ${:.4} 超出上限 ${:.2}，已中止",
+                    request.id,
+                    cost.total_cost_usd(),
+                    max
+                );
+                return Ok(TaskResult::partial(
+                    &request,
+                    completed,
+                    history::TerminationReason::Budget,
+                    format!(
+                        "任务费用 ${:.4} 超出上限 ${:.2}，已中止",
+                        cost.total_cost_usd(),
+                        max
+                    ),
+                ));
+            }
+            AgentEvent::Result { data, .. } => {
+                stream_buf.flush(window);
+                return Ok(data);
+            }
+            _ if replaying => {}
+            AgentEvent::Stream { delta, .. } => stream_buf.push(window, &delta),
+            AgentEvent::ConfirmationRequest(event) => {
+                policy::handle_confirmation_request(window, &request.id, &event);
+            }
+            AgentEvent::FileAccessRequest(event) => {
+                file_guard::handle_request(window, &request.id, &event);
+            }
+            AgentEvent::ScreenshotRequest(event) => {
+                screenshot::handle_request(window, request.work_dir.as_deref(), &event);
+            }
+            AgentEvent::ToolCall(event) => {
+                tool_server::handle_call(&request.id, request.work_dir.as_deref(), &event);
+            }
+            AgentEvent::ApiCall(event) => provider_health::record(&event),
+            AgentEvent::Prompt(event) => {
+                if let Err(e) = prompt_log::append(&request.id, &event) {
+                    eprintln!("[Tauri] ⚠️ {}", e);
+                }
+            }
+            AgentEvent::Paused { .. } | AgentEvent::Resumed { .. } => {
+                stream_buf.flush(window);
+                let paused = matches!(event, AgentEvent::Paused { .. });
+                crate::send_pause_event(window, &request.id, paused);
+            }
+            event if event.is_progress() => {
+                stream_buf.flush(window);
+                let Ok(mut payload) = serde_json::to_value(&event) else {
+                    continue;
+                };
+                redaction::redact_json(&mut payload);
+                window.send("task-progress", &payload);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 跟随脱离任务，结束后登记结果并删除任务目录
+fn spawn_follower(app: AppHandle, mut state: DetachedState, dir: PathBuf) {
+    tauri::async_runtime::spawn(async move {
+        let Some(window) = window_manager::main_window(&app) else {
+            eprintln!(
+                "[Tauri] ⚠️ 主窗口不存在，无法接上脱离任务 {}",
+                state.request_id
+            );
+            return;
+        };
+        let request = state.request();
+        let mut result = {
+            let _watch = sandbox_watch::start(&window, &request.id, request.work_dir.as_deref());
+            follow_journal(&window, &mut state, &dir).await.map_err(DeskJarvisError::from)
+        };
+        let app_state = app.state::<crate::AppState>();
+        crate::finish_task(&window, &app_state, &request, &mut result);
+        window.send(
+            "task-detached-finished",
+            serde_json::json!({
+                "request_id": request.id,
+                "result": result.as_ref().ok(),
+                "error": result.as_ref().err().map(DeskJarvisError::message),
+            }),
+        );
+        match &result {
+            Ok(r) => eprintln!("[Tauri] ✅ 脱离任务 {} 结束: {}", request.id, r.message),
+            Err(e) => eprintln!("[Tauri] ⚠️ 脱离任务 {} 失败: {}", request.id, e),
+        }
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            eprintln!("[Tauri] ⚠️ 删除脱离任务目录失败: {}", e);
+        }
+    });
+}
+
+/// 启动时重新接上仍在记录中的脱离任务（进程已退出的直接读取日志中的结果）
+pub fn resume_all(app: &AppHandle) {
+    for (dir, state) in load_states() {
+        eprintln!(
+            "[Tauri] 🔗 重新接上脱离任务 {}（日志位置 {}）",
+            state.request_id, state.offset
+        );
+        spawn_follower(app.clone(), state, dir);
+    }
+}
+
+/// 以脱离模式执行任务：立即返回任务 ID，进度与结果仍以 task-* 事件发送
+#[tauri::command]
+pub async fn execute_task_detached(
+    window: Window,
+    state: tauri::State<'_, crate::AppState>,
+    instruction: String,
+    context: Option<serde_json::Value>,
+    max_cost_usd: Option<f64>,
+) -> Result<String, DeskJarvisError> {
+    if focus::blocks_interactive() {
+        return Err(DeskJarvisError::FocusActive);
+    }
+    budget::admit(window.app_handle(), &window, &state.history)?;
+    let instruction = crate::project_context::apply_placeholder(
+        window.app_handle(),
+        instruction,
+        context.as_ref(),
+    )?;
+    let request_id = history::new_task_id();
+    let context = crate::mcp::apply_context(crate::attachments::apply_context(context));
+    let work_dir = match sandbox::create_task_dir(&request_id) {
+        Ok(dir) => Some(dir),
+        Err(e) => {
+            eprintln!("[Tauri] ⚠️ {}，使用共享沙盒目录", e);
+            None
+        }
+    };
+
+    let app_config = config::load_config().ok();
+    let response_language = language::response_language(&instruction, app_config.as_ref());
+    let request = TaskRequest {
+        id: request_id.clone(),
+        context: language::apply_hint(context, response_language),
+        instruction,
+        work_dir,
+        // 脱离任务可能在应用退出期间调用模型，不上报提示词
+        log_prompts: false,
+        max_cost_usd: max_cost_usd.or_else(|| app_config.and_then(|c| c.max_task_cost_usd)),
+        session_id: None,
+        resume: None,
+        // 命令会写入任务目录中的文件，不带邮箱访问令牌
+        email_oauth: None,
+        interactive: false,
+    };
+
+    let dir = task_dir(&request_id)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    let pid = match spawn_process(&request, &dir) {
+        Ok(pid) => pid,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&dir);
+            return Err(e.into());
+        }
+    };
+    let detached = DetachedState {
+        request_id: request_id.clone(),
+        instruction: request.instruction.clone(),
+        work_dir: request.work_dir.clone(),
+        max_cost_usd: request.max_cost_usd,
+        pid,
+        started_at: history::now_millis(),
+        offset: 0,
+    };
+    save_state(&dir, &detached)?;
+    state.history.record_start(
+        &request_id,
+        &request.instruction,
+        request
+            .work_dir
+            .as_ref()
+            .map(|d| d.to_string_lossy().to_string()),
+    );
+    crate::metrics::task_started();
+    webhooks::notify(state.history.get(&request_id));
+    eprintln!("[Tauri] 🔗 脱离任务 {} 已启动 (PID {})", request_id, pid);
+
+    spawn_follower(window.app_handle().clone(), detached, dir);
+    Ok(request_id)
+}
+
+/// 停止脱离任务：Python 在下一个步骤前读取停止命令并返回部分结果
+#[tauri::command]
+pub async fn stop_detached_task(request_id: String) -> Result<(), DeskJarvisError> {
+    if !task_dir(&request_id)?.join("state.json").exists() {
+        return Err(DeskJarvisError::TaskNotFound(request_id));
+    }
+    Ok(task_control::send_command(&request_id, "stop")?)
+}
@@ -0,0 +1,228 @@
+//! 运行环境诊断：首次启动或从托盘 "诊断" 菜单打开时，逐项检查 Python、Agent 脚本、沙盒、
+//! 网络、钥匙串和磁盘空间，返回前端可直接渲染的检查清单
+//!
+//! 各项互不依赖，一项失败不影响其他项；网络检查复用 test_proxy（按已保存的代理设置连通
+//! 当前提供商的 API 端口）。任一项为 Failed 时 ok 为 false，Warning 不影响 ok。
+//! 标题、消息和建议按当前界面语言返回。
+
+use std::path::Path;
+
+use serde::Serialize;
+use sysinfo::Disks;
+
+use crate::history::now_millis;
+use crate::{i18n, proxy, sandbox, secrets};
+
+/// 要求的最低 Python 版本
+const MIN_PYTHON: (u32, u32) = (3, 11);
+
+/// 可用空间低于此值时警告（字节）
+const LOW_DISK_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// 可用空间低于此值时判为失败（字节）
+const MIN_DISK_BYTES: u64 = 200 * 1024 * 1024;
+
+/// 检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Failed,
+}
+
+/// 一项检查
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticCheck {
+    /// 检查项：python / agent_script / sandbox / network / keychain / disk
+    pub id: &'static str,
+    pub title: String,
+    pub status: CheckStatus,
+    pub message: String,
+    /// 未通过时的处理建议
+    pub hint: Option<String>,
+}
+
+/// run_diagnostics 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsReport {
+    /// 没有失败项
+    pub ok: bool,
+    pub checked_at: u64,
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+impl DiagnosticCheck {
+    fn new(id: &'static str, title: &str, result: Result<String, String>, hint: &str) -> Self {
+        let title = i18n::tr(title);
+        match result {
+            Ok(message) => DiagnosticCheck {
+                id,
+                title,
+                status: CheckStatus::Ok,
+                message: i18n::tr(&message),
+                hint: None,
+            },
+            Err(message) => DiagnosticCheck {
+                id,
+                title,
+                status: CheckStatus::Failed,
+                message: i18n::tr(&message),
+                hint: Some(i18n::tr(hint)),
+            },
+        }
+    }
+}
+
+/// 解析 "Python 3.11.4" 中的主次版本号
+fn parse_python_version(output: &str) -> Option<(u32, u32)> {
+    let version = output.split_whitespace().nth(1)?;
+    let mut parts = version.split('.');
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
+fn check_python() -> DiagnosticCheck {
+    let result = crate::get_python_path().map_err(String::from).and_then(|launcher| {
+        let output = launcher
+            .command()
+            .arg("--version")
+            .output()
+            .map_err(|e| format!("执行 {} --version 失败: {}", launcher, e))?;
+        // 旧版本把版本号输出到 stderr
+        let text = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let text = text.trim();
+        match parse_python_version(text) {
+            Some(version) if version >= MIN_PYTHON => Ok(format!("{}（{}）", text, launcher)),
+            Some(_) => Err(format!("{} 版本过低，需要 Python {}.{}+", text, MIN_PYTHON.0, MIN_PYTHON.1)),
+            None => Err(format!("无法识别 Python 版本: {}", text)),
+        }
+    });
+    DiagnosticCheck::new(
+        "python",
+        "Python 解释器",
+        result,
+        "安装 Python 3.11 或更高版本，并确保 python3（Windows 上为 py）在 PATH 中",
+    )
+}
+
+fn check_agent_script() -> DiagnosticCheck {
+    DiagnosticCheck::new(
+        "agent_script",
+        "Agent 脚本",
+        crate::find_script("server.py").map_err(String::from),
+        "重新安装应用，或在设置中把 agent_path 指向包含 server.py 的 agent 目录",
+    )
+}
+
+fn check_sandbox() -> DiagnosticCheck {
+    let root = sandbox::sandbox_root();
+    let probe = root.join(format!(".diagnostics_{}", now_millis()));
+    let result = std::fs::create_dir_all(&root)
+        .and_then(|_| std::fs::write(&probe, b"ok"))
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map(|_| format!("{} 可写", root.display()))
+        .map_err(|e| format!("沙盒目录 {} 不可写: {}", root.display(), e));
+    DiagnosticCheck::new("sandbox", "沙盒目录", result, "检查目录权限，或在设置中更换沙盒路径")
+}
+
+async fn check_network() -> DiagnosticCheck {
+    let result = match proxy::test_proxy(None, None).await {
+        Ok(test) if test.ok => Ok(format!("{}（{} ms）", test.message, test.elapsed_ms)),
+        Ok(test) => Err(test.message),
+        Err(e) => Err(e),
+    };
+    DiagnosticCheck::new(
+        "network",
+        "提供商网络",
+        result,
+        "检查网络连接、代理设置（proxy_url / no_proxy）和 API 地址（base_url）",
+    )
+}
+
+fn check_keychain() -> DiagnosticCheck {
+    let result = secrets::get_secret("diagnostics_probe").map(|_| "可以访问系统钥匙串".to_string());
+    DiagnosticCheck::new(
+        "keychain",
+        "系统钥匙串",
+        result,
+        "Linux 上需要运行 Secret Service（如 gnome-keyring）；无法访问时密钥类环境变量不可用",
+    )
+}
+
+/// 包含 path 的磁盘（挂载点最长匹配）
+fn check_disk(path: &Path) -> DiagnosticCheck {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let disks = Disks::new_with_refreshed_list();
+    let disk = disks
+        .list()
+        .iter()
+        .filter(|d| path.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().as_os_str().len());
+    let title = i18n::tr("磁盘空间");
+    let hint = "清理沙盒中不再需要的任务产物，或把沙盒移到空间更大的磁盘";
+    let Some(disk) = disk else {
+        return DiagnosticCheck {
+            id: "disk",
+            title,
+            status: CheckStatus::Warning,
+            message: i18n::tr(&format!("无法确定 {} 所在的磁盘", path.display())),
+            hint: None,
+        };
+    };
+    let available = disk.available_space();
+    let message = i18n::tr(&format!(
+        "{} 可用 {:.1} GB",
+        disk.mount_point().display(),
+        available as f64 / (1024.0 * 1024.0 * 1024.0)
+    ));
+    let status = match available {
+        n if n < MIN_DISK_BYTES => CheckStatus::Failed,
+        n if n < LOW_DISK_BYTES => CheckStatus::Warning,
+        _ => CheckStatus::Ok,
+    };
+    DiagnosticCheck {
+        id: "disk",
+        title,
+        status,
+        message,
+        hint: (status != CheckStatus::Ok).then(|| i18n::tr(hint)),
+    }
+}
+
+/// 检查运行环境，返回检查清单
+#[tauri::command]
+pub async fn run_diagnostics() -> Result<DiagnosticsReport, String> {
+    let local = tauri::async_runtime::spawn_blocking(|| {
+        let sandbox = check_sandbox();
+        vec![
+            check_python(),
+            check_agent_script(),
+            sandbox,
+            check_keychain(),
+            check_disk(&sandbox::sandbox_root()),
+        ]
+    });
+    let network = check_network().await;
+    let mut checks = local.await.map_err(|e| format!("运行诊断失败: {}", e))?;
+    checks.insert(3, network);
+
+    let failed: Vec<&str> = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Failed)
+        .map(|c| c.id)
+        .collect();
+    if failed.is_empty() {
+        eprintln!("[Tauri] 🩺 环境诊断通过");
+    } else {
+        eprintln!("[Tauri] 🩺 环境诊断未通过: {}", failed.join("、"));
+    }
+    Ok(DiagnosticsReport {
+        ok: failed.is_empty(),
+        checked_at: now_millis(),
+        checks,
+    })
+}
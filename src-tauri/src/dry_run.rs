@@ -0,0 +1,188 @@
+//! 只规划不执行（dry-run）：plan_task 让 Python 服务规划步骤但不执行，用户审阅后用 execute_plan 只执行勾选的步骤
+//!
+//! 规划结果在内存中保留 PLAN_TTL，执行或过期后丢弃。execute_plan 把批准的步骤放在
+//! context.approved_plan 中以普通任务执行（预算检查、历史、审批照常），Python 侧据此跳过规划
+//! （见 agent/tools/dry_run.py）。未勾选的步骤不会执行，依赖它们的后续步骤可能因此失败。
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, Window};
+
+use crate::error::DeskJarvisError;
+use crate::history::now_millis;
+use crate::{attachments, config, language, mcp, project_context, server_pool, TaskResult};
+
+/// 等待规划结果的超时
+const PLAN_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// 规划结果的保留时间
+const PLAN_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// 一个待审阅的步骤
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedStep {
+    pub index: usize,
+    #[serde(rename = "type")]
+    pub step_type: String,
+    pub action: Option<String>,
+    pub description: Option<String>,
+    pub params: serde_json::Value,
+    /// 修改类步骤所需的能力（file_write / shell / email / input / system），只读步骤为 None
+    pub capability: Option<String>,
+}
+
+/// plan_task 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProposedPlan {
+    pub plan_id: String,
+    pub instruction: String,
+    pub steps: Vec<PlannedStep>,
+    /// 过期时间（毫秒时间戳），之后需要重新规划
+    pub expires_at: u64,
+}
+
+/// plan_result 事件
+#[derive(Deserialize)]
+struct PlanReply {
+    ok: bool,
+    #[serde(default)]
+    steps: Vec<serde_json::Value>,
+    message: Option<String>,
+}
+
+/// 保留的规划结果
+struct StoredPlan {
+    instruction: String,
+    context: Option<serde_json::Value>,
+    steps: Vec<serde_json::Value>,
+    expires_at: u64,
+}
+
+/// 待执行的规划结果：plan_id → 规划
+static PLANS: Mutex<BTreeMap<String, StoredPlan>> = Mutex::new(BTreeMap::new());
+
+fn planned_step(index: usize, step: &serde_json::Value) -> PlannedStep {
+    let text = |key: &str| step.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    PlannedStep {
+        index,
+        step_type: text("type").unwrap_or_default(),
+        action: text("action"),
+        description: text("description"),
+        params: step.get("params").cloned().unwrap_or(serde_json::Value::Null),
+        capability: text("capability"),
+    }
+}
+
+/// 只规划不执行，返回步骤供用户审阅
+#[tauri::command]
+pub async fn plan_task(
+    window: Window,
+    state: tauri::State<'_, crate::AppState>,
+    instruction: String,
+    context: Option<serde_json::Value>,
+) -> Result<ProposedPlan, String> {
+    let instruction =
+        project_context::apply_placeholder(window.app_handle(), instruction, context.as_ref())?;
+    if instruction.trim().is_empty() {
+        return Err("指令不能为空".to_string());
+    }
+    let app_config = config::load_config().ok();
+    let response_language = language::response_language(&instruction, app_config.as_ref());
+    let context = mcp::apply_context(attachments::apply_context(context));
+    let context = language::apply_hint(context, response_language);
+
+    let plan_id = format!("plan_{}", now_millis());
+    let cmd = serde_json::json!({
+        "cmd": "plan",
+        "id": plan_id,
+        "instruction": instruction,
+        "context": context,
+    });
+    let reply = server_pool::send_command(&state, &cmd, "plan_result", PLAN_TIMEOUT).await?;
+    let reply: PlanReply =
+        serde_json::from_value(reply).map_err(|e| format!("解析规划结果失败: {}", e))?;
+    if !reply.ok {
+        return Err(format!(
+            "规划失败: {}",
+            reply.message.as_deref().unwrap_or("未知错误")
+        ));
+    }
+    if reply.steps.is_empty() {
+        return Err("规划结果为空，没有需要执行的步骤".to_string());
+    }
+
+    let now = now_millis();
+    let expires_at = now + PLAN_TTL.as_millis() as u64;
+    let plan = ProposedPlan {
+        plan_id: plan_id.clone(),
+        instruction: instruction.clone(),
+        steps: reply.steps.iter().enumerate().map(|(i, s)| planned_step(i, s)).collect(),
+        expires_at,
+    };
+    let mut plans = PLANS.lock().map_err(|_| "规划状态不可用")?;
+    plans.retain(|_, p| p.expires_at > now);
+    plans.insert(
+        plan_id,
+        StoredPlan {
+            instruction,
+            context,
+            steps: reply.steps,
+            expires_at,
+        },
+    );
+    eprintln!("[Tauri] 📝 已规划 {} 个步骤，等待审阅: {}", plan.steps.len(), plan.plan_id);
+    Ok(plan)
+}
+
+/// 执行审阅过的规划，approved_steps 为批准的步骤序号（按原顺序执行）
+#[tauri::command]
+pub async fn execute_plan(
+    window: Window,
+    state: tauri::State<'_, crate::AppState>,
+    plan_id: String,
+    approved_steps: Vec<usize>,
+) -> Result<TaskResult, DeskJarvisError> {
+    if approved_steps.is_empty() {
+        return Err(DeskJarvisError::InvalidInput("请至少批准一个步骤".to_string()));
+    }
+    let plan = {
+        let mut plans = PLANS.lock().map_err(|_| "规划状态不可用".to_string())?;
+        let plan = plans
+            .get(&plan_id)
+            .filter(|p| p.expires_at > now_millis())
+            .ok_or_else(|| format!("规划不存在或已过期，请重新规划: {}", plan_id))?;
+        if let Some(index) = approved_steps.iter().find(|&&i| i >= plan.steps.len()) {
+            return Err(DeskJarvisError::InvalidInput(format!("步骤序号超出范围: {}", index)));
+        }
+        // 每个规划只执行一次
+        plans.remove(&plan_id).ok_or("规划状态不可用".to_string())?
+    };
+    let steps: Vec<serde_json::Value> = plan
+        .steps
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| approved_steps.contains(i))
+        .map(|(_, s)| s)
+        .collect();
+    eprintln!("[Tauri] ▶️ 执行审阅后的规划 {}（批准 {} 个步骤）", plan_id, steps.len());
+
+    let mut context = match plan.context {
+        Some(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    context.insert("approved_plan".to_string(), serde_json::Value::Array(steps));
+    context.insert("plan_id".to_string(), serde_json::Value::String(plan_id));
+    crate::execute_task(
+        window,
+        state,
+        plan.instruction,
+        Some(serde_json::Value::Object(context)),
+        None,
+        None,
+        None,
+    )
+    .await
+}
@@ -0,0 +1,252 @@
+//! 邮件配置测试：用配置的服务器和账号完成一次真实的 SMTP 握手和登录，不发送邮件
+//!
+//! 端口 465 使用 SSL，其他端口要求 STARTTLS（服务器不支持时拒绝，不以明文发送密码）。
+//! 登录方式跟随 email_auth_method：密码登录优先 AUTH PLAIN、其次 AUTH LOGIN，
+//! OAuth2 使用 AUTH XOAUTH2（令牌见 email_oauth）。失败时按阶段返回 DNS、连接、TLS、
+//! 登录、超时等错误（见 mail_conn::MailError），用户在任务发信失败之前就能排查配置。
+
+use std::time::Instant;
+
+use base64::Engine;
+use serde::Serialize;
+
+use crate::config;
+use crate::email_oauth;
+use crate::mail_conn::{Credential, MailConnection, MailError};
+
+/// 使用 SSL（而不是 STARTTLS）的 SMTP 端口
+const SMTPS_PORT: u16 = 465;
+
+/// test_email_config 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailTestResult {
+    pub ok: bool,
+    pub message: String,
+    /// 失败的阶段和原因
+    pub error: Option<MailError>,
+    /// 测试的服务器（主机:端口）
+    pub server: String,
+    /// 加密方式：ssl 或 starttls
+    pub encryption: String,
+    pub elapsed_ms: u64,
+}
+
+/// 一条（可能多行的）SMTP 响应
+struct Reply {
+    code: u16,
+    lines: Vec<String>,
+}
+
+impl Reply {
+    fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|l| l.get(4..).unwrap_or("").trim())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+async fn read_reply(conn: &mut MailConnection, stage: &str) -> Result<Reply, MailError> {
+    let mut lines = Vec::new();
+    loop {
+        let line = conn.read_line(stage).await?;
+        let code = line
+            .get(..3)
+            .and_then(|c| c.parse::<u16>().ok())
+            .ok_or_else(|| MailError::Protocol {
+                message: format!("无法识别的 SMTP 响应: {}", line),
+            })?;
+        // 多行响应的中间行形如 "250-PIPELINING"，最后一行为 "250 OK"
+        let last = line.as_bytes().get(3) != Some(&b'-');
+        lines.push(line);
+        if last {
+            return Ok(Reply { code, lines });
+        }
+    }
+}
+
+/// 发送命令并要求指定的响应码
+async fn command(conn: &mut MailConnection, line: &str, expect: u16, stage: &str) -> Result<Reply, MailError> {
+    conn.write_line(line).await?;
+    let reply = read_reply(conn, stage).await?;
+    if reply.code != expect {
+        return Err(MailError::Protocol {
+            message: format!("{}失败（{}）: {}", stage, reply.code, reply.text()),
+        });
+    }
+    Ok(reply)
+}
+
+/// EHLO，返回服务器支持的扩展（大写）
+async fn ehlo(conn: &mut MailConnection) -> Result<Vec<String>, MailError> {
+    let reply = command(conn, "EHLO [127.0.0.1]", 250, "EHLO").await?;
+    Ok(reply
+        .lines
+        .iter()
+        .skip(1)
+        .map(|l| l.get(4..).unwrap_or("").trim().to_ascii_uppercase())
+        .collect())
+}
+
+fn b64(data: &str) -> String {
+    base64::engine::general_purpose::STANDARD.encode(data)
+}
+
+fn auth_error(reply: &Reply) -> MailError {
+    MailError::Auth {
+        message: format!("{} {}", reply.code, reply.text()),
+    }
+}
+
+async fn authenticate(
+    conn: &mut MailConnection,
+    capabilities: &[String],
+    user: &str,
+    credential: &Credential,
+) -> Result<(), MailError> {
+    let mechanisms: Vec<&str> = capabilities
+        .iter()
+        .filter_map(|c| c.strip_prefix("AUTH").map(|m| m.trim_start_matches([' ', '='])))
+        .flat_map(str::split_whitespace)
+        .collect();
+    let reply = match credential {
+        Credential::OAuth2(token) => {
+            let payload = format!("user={}\x01auth=Bearer {}\x01\x01", user, token);
+            conn.write_line(&format!("AUTH XOAUTH2 {}", b64(&payload))).await?;
+            let reply = read_reply(conn, "登录").await?;
+            if reply.code == 334 {
+                // 令牌被拒绝时服务器先返回 334 和 base64 编码的错误详情，回空行后才给出最终结果
+                conn.write_line("").await?;
+                let detail = base64::engine::general_purpose::STANDARD
+                    .decode(reply.text())
+                    .map(|d| String::from_utf8_lossy(&d).to_string())
+                    .unwrap_or_default();
+                let last = read_reply(conn, "登录").await?;
+                return Err(MailError::Auth {
+                    message: format!("{} {} {}", last.code, last.text(), detail).trim().to_string(),
+                });
+            }
+            reply
+        }
+        Credential::Password(password) if mechanisms.contains(&"PLAIN") => {
+            conn.write_line(&format!("AUTH PLAIN {}", b64(&format!("\0{}\0{}", user, password))))
+                .await?;
+            read_reply(conn, "登录").await?
+        }
+        Credential::Password(password) if mechanisms.contains(&"LOGIN") => {
+            for step in [None, Some(user), Some(password.as_str())] {
+                let line = step.map(b64).unwrap_or_else(|| "AUTH LOGIN".to_string());
+                conn.write_line(&line).await?;
+                let reply = read_reply(conn, "登录").await?;
+                if reply.code != 334 {
+                    return if reply.code == 235 { Ok(()) } else { Err(auth_error(&reply)) };
+                }
+            }
+            return Err(MailError::Protocol {
+                message: "AUTH LOGIN 流程未结束".to_string(),
+            });
+        }
+        Credential::Password(_) => {
+            return Err(MailError::Protocol {
+                message: format!("服务器不支持 PLAIN 或 LOGIN 登录（支持: {}）", mechanisms.join(" ")),
+            })
+        }
+    };
+    if reply.code == 235 {
+        Ok(())
+    } else {
+        Err(auth_error(&reply))
+    }
+}
+
+/// 握手并登录，成功后 QUIT
+async fn handshake(host: &str, port: u16, user: &str, credential: &Credential) -> Result<(), MailError> {
+    let implicit_tls = port == SMTPS_PORT;
+    let mut conn = MailConnection::connect(host, port, implicit_tls).await?;
+    let greeting = read_reply(&mut conn, "等待服务器问候").await?;
+    if greeting.code != 220 {
+        return Err(MailError::Protocol {
+            message: format!("服务器拒绝连接（{}）: {}", greeting.code, greeting.text()),
+        });
+    }
+    let mut capabilities = ehlo(&mut conn).await?;
+    if !conn.is_tls() {
+        if !capabilities.iter().any(|c| c == "STARTTLS") {
+            return Err(MailError::Tls {
+                message: format!("服务器在端口 {} 上不支持 STARTTLS，SSL 请使用 {} 端口", port, SMTPS_PORT),
+            });
+        }
+        command(&mut conn, "STARTTLS", 220, "STARTTLS").await?;
+        conn = conn.start_tls().await?;
+        capabilities = ehlo(&mut conn).await?;
+    }
+    authenticate(&mut conn, &capabilities, user, credential).await?;
+    let _ = conn.write_line("QUIT").await;
+    Ok(())
+}
+
+/// 用配置（或表单中尚未保存的值）测试 SMTP 连接和登录
+#[tauri::command]
+pub async fn test_email_config(
+    smtp_server: Option<String>,
+    smtp_port: Option<u16>,
+    sender: Option<String>,
+    password: Option<String>,
+) -> Result<EmailTestResult, String> {
+    let mut app_config = config::load_config()?;
+    if let Some(sender) = sender {
+        app_config.email_sender = Some(sender);
+    }
+    let host = smtp_server
+        .or(app_config.email_smtp_server.clone())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or("未设置 SMTP 服务器")?;
+    let port = match smtp_port {
+        Some(port) => port,
+        None => u16::try_from(app_config.email_smtp_port.unwrap_or(587))
+            .map_err(|_| "SMTP 端口超出范围".to_string())?,
+    };
+    let user = app_config
+        .email_sender
+        .clone()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or("未设置发件人邮箱")?;
+
+    let started = Instant::now();
+    let credential = if email_oauth::uses_oauth(&app_config) {
+        email_oauth::credential(&app_config)
+            .await
+            .map(|c| Credential::OAuth2(c.access_token))
+            .map_err(|message| MailError::Auth { message })
+    } else {
+        let password = password
+            .or(app_config.email_password.clone())
+            .filter(|p| !p.is_empty())
+            .ok_or("未设置邮箱密码/授权码")?;
+        Ok(Credential::Password(password))
+    };
+    let result = match credential {
+        Ok(credential) => handshake(&host, port, &user, &credential).await,
+        Err(e) => Err(e),
+    };
+    let server = format!("{}:{}", host, port);
+    let encryption = if port == SMTPS_PORT { "ssl" } else { "starttls" };
+    match &result {
+        Ok(()) => eprintln!("[Tauri] ✅ 邮件配置测试通过: {}", server),
+        Err(e) => eprintln!("[Tauri] ⚠️ 邮件配置测试失败: {}: {}", server, e.message()),
+    }
+    Ok(EmailTestResult {
+        ok: result.is_ok(),
+        message: match &result {
+            Ok(()) => format!("已通过 {} 登录 {}", encryption.to_uppercase(), server),
+            Err(e) => e.message(),
+        },
+        error: result.err(),
+        server,
+        encryption: encryption.to_string(),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
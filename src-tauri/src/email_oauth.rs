@@ -0,0 +1,416 @@
+//! 邮箱 OAuth2 授权：Gmail、Outlook 逐步停用应用专用密码，改用 OAuth2（XOAUTH2）登录 SMTP / IMAP
+//!
+//! 配置 email_auth_method 为 "oauth2"，填写 email_oauth_provider（google / microsoft）和
+//! email_oauth_client_id（Google 还需 email_oauth_client_secret）后，start_email_oauth 在
+//! 127.0.0.1 的随机端口上等待回调，用系统浏览器打开授权页（PKCE），收到授权码后换取令牌
+//! 存入系统钥匙串，不写入配置文件。执行任务前按需用 refresh_token 刷新访问令牌，
+//! 以 execute 命令的 email_oauth 字段（{"user","access_token","expires_at"}）交给 Python。
+
+use std::time::Duration;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+use crate::config::{self, AppConfig};
+use crate::history::now_millis;
+use crate::{mail_conn, secrets};
+
+/// 钥匙串中保存令牌的条目
+const TOKEN_KEY: &str = "email_oauth_token";
+
+/// 等待用户在浏览器中完成授权的时间
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// 令牌接口请求超时
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 访问令牌剩余有效期不足这个值时提前刷新（毫秒）
+const REFRESH_MARGIN_MS: u64 = 5 * 60 * 1000;
+
+/// 回调页面
+const CALLBACK_PAGE: &str = "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>DeskJarvis</title></head>\
+<body style=\"font-family:sans-serif;text-align:center;padding-top:80px\"><h2>{}</h2><p>可以关闭此页面，回到 DeskJarvis。</p></body></html>";
+
+/// OAuth2 提供商的接口地址和邮件权限
+struct Provider {
+    auth_url: &'static str,
+    token_url: &'static str,
+    scope: &'static str,
+}
+
+fn provider(name: &str) -> Result<Provider, String> {
+    match name {
+        "google" => Ok(Provider {
+            auth_url: "https://accounts.google.com/o/oauth2/v2/auth",
+            token_url: "https://oauth2.googleapis.com/token",
+            scope: "https://mail.google.com/",
+        }),
+        "microsoft" => Ok(Provider {
+            auth_url: "https://login.microsoftonline.com/common/oauth2/v2.0/authorize",
+            token_url: "https://login.microsoftonline.com/common/oauth2/v2.0/token",
+            scope: "https://outlook.office.com/SMTP.Send https://outlook.office.com/IMAP.AccessAsUser.All offline_access",
+        }),
+        other => Err(format!("不支持的 OAuth2 提供商: {}（支持 google、microsoft）", other)),
+    }
+}
+
+/// 钥匙串中保存的令牌
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredToken {
+    provider: String,
+    access_token: String,
+    refresh_token: Option<String>,
+    /// 访问令牌过期时间（毫秒时间戳）
+    expires_at: u64,
+}
+
+/// 令牌接口的响应
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+}
+
+/// 令牌接口的错误响应
+#[derive(Deserialize)]
+struct TokenError {
+    error: String,
+    error_description: Option<String>,
+}
+
+/// 邮箱 OAuth2 授权状态
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailOAuthStatus {
+    /// 配置的登录方式（password / oauth2）
+    pub auth_method: String,
+    pub provider: Option<String>,
+    /// 钥匙串中有当前提供商的令牌
+    pub authorized: bool,
+    /// 访问令牌过期时间（毫秒时间戳），过期后执行任务时自动刷新
+    pub expires_at: Option<u64>,
+}
+
+/// 交给 Python 的 XOAUTH2 登录凭据
+#[derive(Debug, Clone, Serialize)]
+pub struct OAuthCredential {
+    pub user: String,
+    pub access_token: String,
+    pub expires_at: u64,
+}
+
+/// OAuth2 相关配置
+struct OAuthConfig {
+    provider_name: String,
+    provider: Provider,
+    client_id: String,
+    client_secret: Option<String>,
+}
+
+/// 是否配置为 OAuth2 登录
+pub fn uses_oauth(app_config: &AppConfig) -> bool {
+    app_config.email_auth_method.as_deref() == Some("oauth2")
+}
+
+fn oauth_config(app_config: &AppConfig) -> Result<OAuthConfig, String> {
+    let provider_name = app_config
+        .email_oauth_provider
+        .clone()
+        .filter(|p| !p.trim().is_empty())
+        .ok_or("未设置 email_oauth_provider")?;
+    let client_id = app_config
+        .email_oauth_client_id
+        .clone()
+        .filter(|c| !c.trim().is_empty())
+        .ok_or("未设置 email_oauth_client_id")?;
+    Ok(OAuthConfig {
+        provider: provider(&provider_name)?,
+        provider_name,
+        client_id,
+        client_secret: app_config.email_oauth_client_secret.clone().filter(|s| !s.is_empty()),
+    })
+}
+
+fn load_token() -> Result<Option<StoredToken>, String> {
+    match secrets::get_secret(TOKEN_KEY)? {
+        Some(raw) => serde_json::from_str(&raw)
+            .map(Some)
+            .map_err(|e| format!("解析邮箱令牌失败: {}", e)),
+        None => Ok(None),
+    }
+}
+
+fn save_token(token: &StoredToken) -> Result<(), String> {
+    let raw = serde_json::to_string(token).map_err(|e| format!("序列化邮箱令牌失败: {}", e))?;
+    secrets::set_secret(TOKEN_KEY, &raw)
+}
+
+/// 随机字节的 base64url 编码（无填充）
+fn random_token(len: usize) -> Result<String, String> {
+    let mut bytes = vec![0u8; len];
+    getrandom::fill(&mut bytes).map_err(|e| format!("生成随机数失败: {}", e))?;
+    Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// 用系统浏览器打开授权页
+fn open_browser(url: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let mut cmd = std::process::Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = std::process::Command::new("rundll32");
+        cmd.arg("url.dll,FileProtocolHandler");
+        cmd
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut cmd = std::process::Command::new("xdg-open");
+    cmd.arg(url)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("打开浏览器失败: {}", e))
+}
+
+/// 等待浏览器带着授权码访问回调地址，返回授权码
+async fn wait_for_code(listener: &TcpListener, state: &str) -> Result<String, String> {
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .map_err(|e| format!("接收授权回调失败: {}", e))?;
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).await.is_err() {
+            continue;
+        }
+        // 请求行形如 GET /callback?code=...&state=... HTTP/1.1，其他路径（如 favicon）直接忽略
+        let target = request_line.split_whitespace().nth(1).unwrap_or("");
+        if !target.starts_with("/callback") {
+            let _ = reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                .await;
+            continue;
+        }
+        let url = url::Url::parse(&format!("http://127.0.0.1{}", target))
+            .map_err(|e| format!("解析授权回调失败: {}", e))?;
+        let param = |key: &str| {
+            url.query_pairs()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.to_string())
+        };
+        let outcome = if param("state").as_deref() != Some(state) {
+            Err("授权回调的 state 不匹配，已忽略".to_string())
+        } else if let Some(error) = param("error") {
+            Err(format!(
+                "授权被拒绝: {}",
+                param("error_description").unwrap_or(error)
+            ))
+        } else {
+            param("code").ok_or_else(|| "授权回调缺少 code".to_string())
+        };
+        let title = match &outcome {
+            Ok(_) => "邮箱授权完成".to_string(),
+            Err(e) => e.clone(),
+        };
+        let body = CALLBACK_PAGE.replace("{}", &title);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let _ = reader.get_mut().write_all(response.as_bytes()).await;
+        return outcome;
+    }
+}
+
+/// 向令牌接口提交表单
+async fn request_token(token_url: &str, form: &[(&str, &str)]) -> Result<TokenResponse, String> {
+    mail_conn::ensure_crypto_provider();
+    let body = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(form)
+        .finish();
+    let client = reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let response = client
+        .post(token_url)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("请求令牌失败: {}", e))?;
+    let status = response.status();
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("读取令牌响应失败: {}", e))?;
+    if !status.is_success() {
+        let message = serde_json::from_str::<TokenError>(&text)
+            .map(|e| e.error_description.unwrap_or(e.error))
+            .unwrap_or_else(|_| text.chars().take(200).collect());
+        return Err(format!("获取令牌失败（HTTP {}）: {}", status.as_u16(), message));
+    }
+    serde_json::from_str(&text).map_err(|e| format!("解析令牌响应失败: {}", e))
+}
+
+fn stored_token(provider_name: &str, response: TokenResponse, previous_refresh: Option<String>) -> StoredToken {
+    StoredToken {
+        provider: provider_name.to_string(),
+        access_token: response.access_token,
+        // 刷新时提供商可能不返回新的 refresh_token，沿用原来的
+        refresh_token: response.refresh_token.or(previous_refresh),
+        expires_at: now_millis() + response.expires_in.unwrap_or(3600) * 1000,
+    }
+}
+
+/// 用 refresh_token 换取新的访问令牌并保存
+async fn refresh(oauth: &OAuthConfig, token: StoredToken) -> Result<StoredToken, String> {
+    let refresh_token = token
+        .refresh_token
+        .clone()
+        .ok_or("邮箱授权已过期且没有 refresh_token，请重新授权")?;
+    let mut form = vec![
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token.as_str()),
+        ("client_id", oauth.client_id.as_str()),
+    ];
+    if let Some(secret) = &oauth.client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+    let response = request_token(oauth.provider.token_url, &form).await?;
+    let token = stored_token(&oauth.provider_name, response, Some(refresh_token));
+    save_token(&token)?;
+    eprintln!("[Tauri] 🔑 已刷新邮箱访问令牌");
+    Ok(token)
+}
+
+/// 有效的访问令牌（快过期时先刷新）
+async fn access_token(app_config: &AppConfig) -> Result<StoredToken, String> {
+    let oauth = oauth_config(app_config)?;
+    let token = load_token()?
+        .filter(|t| t.provider == oauth.provider_name)
+        .ok_or("尚未完成邮箱 OAuth2 授权")?;
+    if token.expires_at > now_millis() + REFRESH_MARGIN_MS {
+        return Ok(token);
+    }
+    refresh(&oauth, token).await
+}
+
+/// 发件人的 XOAUTH2 凭据（快过期时先刷新）
+pub async fn credential(app_config: &AppConfig) -> Result<OAuthCredential, String> {
+    let user = app_config
+        .email_sender
+        .clone()
+        .filter(|s| !s.trim().is_empty())
+        .ok_or("未设置发件人邮箱")?;
+    let token = access_token(app_config).await?;
+    Ok(OAuthCredential {
+        user,
+        access_token: token.access_token,
+        expires_at: token.expires_at,
+    })
+}
+
+/// 执行任务时交给 Python 的凭据，未启用 OAuth2 或授权不可用时返回 None（邮件步骤会报登录失败）
+pub async fn task_credential(app_config: Option<&AppConfig>) -> Option<OAuthCredential> {
+    let app_config = app_config.filter(|c| uses_oauth(c))?;
+    match credential(app_config).await {
+        Ok(credential) => Some(credential),
+        Err(e) => {
+            eprintln!("[Tauri] ⚠️ 邮箱 OAuth2 令牌不可用: {}", e);
+            None
+        }
+    }
+}
+
+fn status(app_config: &AppConfig) -> EmailOAuthStatus {
+    let provider = app_config.email_oauth_provider.clone();
+    let token = load_token()
+        .ok()
+        .flatten()
+        .filter(|t| Some(&t.provider) == provider.as_ref());
+    EmailOAuthStatus {
+        auth_method: app_config
+            .email_auth_method
+            .clone()
+            .unwrap_or_else(|| "password".to_string()),
+        provider,
+        authorized: token.is_some(),
+        expires_at: token.map(|t| t.expires_at),
+    }
+}
+
+/// 打开浏览器完成邮箱 OAuth2 授权，令牌存入系统钥匙串
+#[tauri::command]
+pub async fn start_email_oauth() -> Result<EmailOAuthStatus, String> {
+    let app_config = config::load_config()?;
+    let oauth = oauth_config(&app_config)?;
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| format!("启动授权回调监听失败: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("启动授权回调监听失败: {}", e))?
+        .port();
+    let redirect_uri = format!("http://127.0.0.1:{}/callback", port);
+    let state = random_token(16)?;
+    let verifier = random_token(32)?;
+    let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+
+    let mut auth_url = url::Url::parse(oauth.provider.auth_url).map_err(|e| format!("无效的授权地址: {}", e))?;
+    auth_url
+        .query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", &oauth.client_id)
+        .append_pair("redirect_uri", &redirect_uri)
+        .append_pair("scope", oauth.provider.scope)
+        .append_pair("state", &state)
+        .append_pair("code_challenge", &challenge)
+        .append_pair("code_challenge_method", "S256")
+        // Google 只有带 access_type=offline 和 prompt=consent 时才返回 refresh_token
+        .append_pair("access_type", "offline")
+        .append_pair("prompt", "consent");
+    if let Some(sender) = app_config.email_sender.as_deref().filter(|s| !s.is_empty()) {
+        auth_url.query_pairs_mut().append_pair("login_hint", sender);
+    }
+    open_browser(auth_url.as_str())?;
+    eprintln!("[Tauri] 🔑 等待邮箱 OAuth2 授权（{}）", oauth.provider_name);
+
+    let code = tokio::time::timeout(CALLBACK_TIMEOUT, wait_for_code(&listener, &state))
+        .await
+        .map_err(|_| "等待浏览器授权超时".to_string())??;
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("client_id", oauth.client_id.as_str()),
+        ("code_verifier", verifier.as_str()),
+    ];
+    if let Some(secret) = &oauth.client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+    let response = request_token(oauth.provider.token_url, &form).await?;
+    let token = stored_token(&oauth.provider_name, response, None);
+    if token.refresh_token.is_none() {
+        eprintln!("[Tauri] ⚠️ 提供商未返回 refresh_token，访问令牌过期后需要重新授权");
+    }
+    save_token(&token)?;
+    eprintln!("[Tauri] ✅ 邮箱 OAuth2 授权完成");
+    Ok(status(&app_config))
+}
+
+/// 当前邮箱 OAuth2 授权状态
+#[tauri::command]
+pub async fn get_email_oauth_status() -> Result<EmailOAuthStatus, String> {
+    Ok(status(&config::load_config()?))
+}
+
+/// 删除钥匙串中的邮箱令牌
+#[tauri::command]
+pub async fn sign_out_email_oauth() -> Result<(), String> {
+    secrets::delete_secret(TOKEN_KEY)
+}
@@ -0,0 +1,175 @@
+//! 命令的结构化错误：序列化为 { code, message, details, retryable }，前端按 code 分支
+//! （如 PYTHON_NOT_FOUND 时引导安装 Python、SERVER_TIMEOUT 时提示重试），message 供显示
+//!
+//! 需要区分的错误在产生处构造对应的变体；仍以 String 传递错误的内部函数可直接用 `?` 转换
+//! （归为 INTERNAL）。转换回 String 时只保留 message，日志、任务历史等沿用原来的文本。
+//! 序列化时 message 按当前界面语言翻译（作为事件负载发出时不经前端的 localize_message）。
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::budget::BudgetExceeded;
+use crate::i18n;
+
+/// 结构化错误
+#[derive(Debug, Clone, Serialize)]
+#[serde(into = "ErrorPayload")]
+pub enum DeskJarvisError {
+    /// 找不到可用的 Python 解释器
+    PythonNotFound,
+    /// 找不到 agent 目录下的脚本
+    AgentScriptNotFound { script: String, message: String },
+    /// Python 服务未在限定时间内就绪
+    ServerTimeout { timeout_secs: u64 },
+    /// Python 服务启动失败或启动后立即退出
+    ServerStartFailed(String),
+    /// Python 服务处于降级状态，不再自动重启
+    ServerDegraded(String),
+    /// Python 服务未运行，无法发送控制命令
+    ServerNotRunning,
+    /// 向 Python 服务发送控制命令失败或未收到应答
+    ServerCommandFailed(String),
+    /// 超出每小时任务数或每日费用上限
+    BudgetExceeded(BudgetExceeded),
+    /// 专注时段拒绝界面发起的任务
+    FocusActive,
+    /// 没有正在执行的任务
+    NoActiveTask,
+    /// 找不到指定的任务
+    TaskNotFound(String),
+    /// 任务已结束，不能再控制
+    TaskFinished(String),
+    /// 任务当前状态不允许该操作（如已暂停时再暂停）
+    TaskStateConflict { task_id: String, message: String },
+    /// 读取、解析或写入配置失败
+    Config(String),
+    /// 参数无效
+    InvalidInput(String),
+    /// 其他错误
+    Internal(String),
+}
+
+/// 序列化的形式
+#[derive(Debug, Serialize)]
+struct ErrorPayload {
+    code: &'static str,
+    message: String,
+    details: Option<Value>,
+    retryable: bool,
+}
+
+impl DeskJarvisError {
+    /// 错误码（SCREAMING_SNAKE_CASE，前端据此分支）
+    pub fn code(&self) -> &'static str {
+        match self {
+            DeskJarvisError::PythonNotFound => "PYTHON_NOT_FOUND",
+            DeskJarvisError::AgentScriptNotFound { .. } => "AGENT_SCRIPT_NOT_FOUND",
+            DeskJarvisError::ServerTimeout { .. } => "SERVER_TIMEOUT",
+            DeskJarvisError::ServerStartFailed(_) => "SERVER_START_FAILED",
+            DeskJarvisError::ServerDegraded(_) => "SERVER_DEGRADED",
+            DeskJarvisError::ServerNotRunning => "SERVER_NOT_RUNNING",
+            DeskJarvisError::ServerCommandFailed(_) => "SERVER_COMMAND_FAILED",
+            DeskJarvisError::BudgetExceeded(_) => "BUDGET_EXCEEDED",
+            DeskJarvisError::FocusActive => "FOCUS_ACTIVE",
+            DeskJarvisError::NoActiveTask => "NO_ACTIVE_TASK",
+            DeskJarvisError::TaskNotFound(_) => "TASK_NOT_FOUND",
+            DeskJarvisError::TaskFinished(_) => "TASK_FINISHED",
+            DeskJarvisError::TaskStateConflict { .. } => "TASK_STATE_CONFLICT",
+            DeskJarvisError::Config(_) => "CONFIG_ERROR",
+            DeskJarvisError::InvalidInput(_) => "INVALID_INPUT",
+            DeskJarvisError::Internal(_) => "INTERNAL",
+        }
+    }
+
+    /// 显示用的错误信息（中文原文）
+    pub fn message(&self) -> String {
+        match self {
+            DeskJarvisError::PythonNotFound => "未找到 Python 解释器，请确保已安装 Python 3.11+".to_string(),
+            DeskJarvisError::ServerTimeout { timeout_secs } => {
+                format!("Python 服务启动超时({}s)", timeout_secs)
+            }
+            DeskJarvisError::BudgetExceeded(exceeded) => exceeded.message(),
+            DeskJarvisError::FocusActive => "专注时段内已暂停执行任务，可在托盘中结束专注".to_string(),
+            DeskJarvisError::ServerNotRunning => "Python 服务未运行".to_string(),
+            DeskJarvisError::NoActiveTask => "没有正在执行的任务".to_string(),
+            DeskJarvisError::TaskNotFound(task_id) => format!("未找到任务: {}", task_id),
+            DeskJarvisError::TaskFinished(task_id) => format!("任务 {} 已结束", task_id),
+            DeskJarvisError::AgentScriptNotFound { message, .. }
+            | DeskJarvisError::TaskStateConflict { message, .. }
+            | DeskJarvisError::ServerStartFailed(message)
+            | DeskJarvisError::ServerDegraded(message)
+            | DeskJarvisError::ServerCommandFailed(message)
+            | DeskJarvisError::Config(message)
+            | DeskJarvisError::InvalidInput(message)
+            | DeskJarvisError::Internal(message) => message.clone(),
+        }
+    }
+
+    /// 附加信息，如超出的预算、找不到的脚本名、相关的任务 ID
+    fn details(&self) -> Option<Value> {
+        match self {
+            DeskJarvisError::AgentScriptNotFound { script, .. } => Some(serde_json::json!({ "script": script })),
+            DeskJarvisError::ServerTimeout { timeout_secs } => {
+                Some(serde_json::json!({ "timeout_secs": timeout_secs }))
+            }
+            DeskJarvisError::BudgetExceeded(exceeded) => serde_json::to_value(exceeded).ok(),
+            DeskJarvisError::TaskNotFound(task_id)
+            | DeskJarvisError::TaskFinished(task_id)
+            | DeskJarvisError::TaskStateConflict { task_id, .. } => {
+                Some(serde_json::json!({ "task_id": task_id }))
+            }
+            _ => None,
+        }
+    }
+
+    /// 稍后原样重试可能成功（启动超时、启动失败、控制命令发送失败）
+    pub fn retryable(&self) -> bool {
+        matches!(
+            self,
+            DeskJarvisError::ServerTimeout { .. }
+                | DeskJarvisError::ServerStartFailed(_)
+                | DeskJarvisError::ServerCommandFailed(_)
+        )
+    }
+}
+
+impl From<DeskJarvisError> for ErrorPayload {
+    fn from(error: DeskJarvisError) -> Self {
+        ErrorPayload {
+            code: error.code(),
+            message: i18n::tr(&error.message()),
+            details: error.details(),
+            retryable: error.retryable(),
+        }
+    }
+}
+
+impl std::fmt::Display for DeskJarvisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+impl From<String> for DeskJarvisError {
+    fn from(message: String) -> Self {
+        DeskJarvisError::Internal(message)
+    }
+}
+
+impl From<&str> for DeskJarvisError {
+    fn from(message: &str) -> Self {
+        DeskJarvisError::Internal(message.to_string())
+    }
+}
+
+impl From<BudgetExceeded> for DeskJarvisError {
+    fn from(exceeded: BudgetExceeded) -> Self {
+        DeskJarvisError::BudgetExceeded(exceeded)
+    }
+}
+
+impl From<DeskJarvisError> for String {
+    fn from(error: DeskJarvisError) -> String {
+        error.message()
+    }
+}
@@ -0,0 +1,20 @@
+//! 任务事件的去向：界面模式发往窗口，命令行模式输出到终端
+
+use serde::Serialize;
+use tauri::{Emitter, Window};
+
+/// 接收任务执行过程中的事件（task-progress、task-stream、task-usage 等）
+pub trait EventSink {
+    fn send<S: Serialize + Clone>(&self, event: &str, payload: S);
+
+    /// 是否有用户可以回应确认类事件（如 file-access-request）
+    fn interactive(&self) -> bool {
+        true
+    }
+}
+
+impl EventSink for Window {
+    fn send<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        let _ = self.emit(event, payload);
+    }
+}
@@ -0,0 +1,58 @@
+//! 可选功能可用性：插件或子系统初始化失败时降级运行，并告知前端隐藏相应功能
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use serde::Serialize;
+use tauri::{plugin::Plugin, AppHandle, Wry};
+
+/// 单个功能的可用性
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureStatus {
+    available: bool,
+    /// 不可用时的原因
+    error: Option<String>,
+}
+
+static FEATURES: Mutex<BTreeMap<&'static str, FeatureStatus>> = Mutex::new(BTreeMap::new());
+
+/// 记录功能初始化结果
+pub fn record(feature: &'static str, result: Result<(), String>) {
+    let status = match result {
+        Ok(()) => FeatureStatus {
+            available: true,
+            error: None,
+        },
+        Err(e) => {
+            eprintln!("[Tauri] ⚠️ 功能 {} 初始化失败，已降级运行: {}", feature, e);
+            FeatureStatus {
+                available: false,
+                error: Some(e),
+            }
+        }
+    };
+    if let Ok(mut features) = FEATURES.lock() {
+        features.insert(feature, status);
+    }
+}
+
+/// 功能是否可用（未登记的功能视为不可用）
+pub fn is_available(feature: &str) -> bool {
+    FEATURES
+        .lock()
+        .map(|f| f.get(feature).map(|s| s.available).unwrap_or(false))
+        .unwrap_or(false)
+}
+
+/// 运行时注册插件，失败时仅记录，不中断启动
+pub fn register_plugin<P: Plugin<Wry> + 'static>(app: &AppHandle, feature: &'static str, plugin: P) {
+    record(feature, app.plugin(plugin).map_err(|e| e.to_string()));
+}
+
+/// 获取各可选功能的可用性
+#[tauri::command]
+pub async fn get_feature_availability() -> Result<BTreeMap<String, FeatureStatus>, String> {
+    FEATURES
+        .lock()
+        .map(|f| f.iter().map(|(k, v)| (k.to_string(), v.clone())).collect())
+        .map_err(|_| "功能状态不可用".to_string())
+}
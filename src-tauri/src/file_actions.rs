@@ -0,0 +1,181 @@
+//! 文件操作：用系统默认应用打开文件、在文件管理器中显示文件
+//!
+//! 前端传入的路径先检查是否存在，且须位于沙盒、配置 file_access_allowlist 中的目录、
+//! 已授权的文件夹（见 file_guard）或 DeskJarvis 数据目录内，避免任务结果中的任意路径
+//! 被直接启动。失败时返回带 kind 的结构化错误，前端可据此提示"文件已被移动或删除"。
+//! 应用内部已确认过路径的调用（崩溃报告、任务产物）使用 open_path，不再检查白名单。
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Serialize;
+
+use crate::{config, file_guard};
+
+/// 文件操作错误
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FileActionError {
+    /// 路径为空或无法解析
+    InvalidPath { path: String },
+    /// 文件不存在（可能已被移动或删除）
+    NotFound { path: String },
+    /// 不在沙盒或允许的目录内
+    NotAllowed { path: String },
+    /// 启动系统程序失败
+    LaunchFailed { path: String, message: String },
+}
+
+impl FileActionError {
+    pub fn message(&self) -> String {
+        match self {
+            FileActionError::InvalidPath { path } => format!("无效的路径: {}", path),
+            FileActionError::NotFound { path } => format!("文件不存在: {}", path),
+            FileActionError::NotAllowed { path } => {
+                format!("不允许打开沙盒和已授权目录以外的文件: {}", path)
+            }
+            FileActionError::LaunchFailed { message, .. } => message.clone(),
+        }
+    }
+}
+
+impl From<FileActionError> for String {
+    fn from(error: FileActionError) -> String {
+        error.message()
+    }
+}
+
+/// 展开 ~ 并检查路径存在、位于允许的目录内
+fn checked_path(path: &str) -> Result<PathBuf, FileActionError> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Err(FileActionError::InvalidPath {
+            path: path.to_string(),
+        });
+    }
+    let expanded = match trimmed.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .ok_or_else(|| FileActionError::InvalidPath {
+                path: path.to_string(),
+            })?
+            .join(rest),
+        None => PathBuf::from(trimmed),
+    };
+    // 规范化会解析 "../" 和符号链接，失败即文件不存在
+    let resolved = expanded
+        .canonicalize()
+        .map_err(|_| FileActionError::NotFound {
+            path: path.to_string(),
+        })?;
+    let in_data_dir = config::get_data_dir()
+        .ok()
+        .and_then(|dir| dir.canonicalize().ok())
+        .is_some_and(|dir| resolved.starts_with(dir));
+    let allowed = in_data_dir
+        || config::load_config()
+            .map(|c| file_guard::is_allowed(&resolved, &c))
+            .unwrap_or(false);
+    if !allowed {
+        return Err(FileActionError::NotAllowed {
+            path: path.to_string(),
+        });
+    }
+    Ok(resolved)
+}
+
+fn spawn(cmd: &mut Command, path: &Path) -> Result<(), FileActionError> {
+    cmd.spawn()
+        .map(|_| ())
+        .map_err(|e| FileActionError::LaunchFailed {
+            path: path.to_string_lossy().to_string(),
+            message: format!("打开文件失败: {}", e),
+        })
+}
+
+/// 用系统默认应用打开已确认的路径（不检查白名单）
+pub fn open_path(path: &Path) -> Result<(), FileActionError> {
+    if !path.exists() {
+        return Err(FileActionError::NotFound {
+            path: path.to_string_lossy().to_string(),
+        });
+    }
+    #[cfg(target_os = "macos")]
+    let mut cmd = Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut cmd = Command::new("explorer");
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut cmd = Command::new("xdg-open");
+    spawn(cmd.arg(path), path)
+}
+
+/// file:// URI（路径中的非 ASCII 字符和保留字符按 UTF-8 百分号编码）
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+/// 在文件管理器中显示并选中文件
+fn reveal_path(path: &Path) -> Result<(), FileActionError> {
+    #[cfg(target_os = "macos")]
+    {
+        spawn(Command::new("open").arg("-R").arg(path), path)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        // explorer 的 /select 参数不接受常规的参数转义，路径需原样加引号
+        let arg = format!("/select,\"{}\"", path.to_string_lossy());
+        spawn(Command::new("explorer").raw_arg(arg), path)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        // 支持 FileManager1 接口的文件管理器（Nautilus、Dolphin、Nemo 等）可选中文件
+        let shown = Command::new("dbus-send")
+            .args([
+                "--session",
+                "--print-reply",
+                "--dest=org.freedesktop.FileManager1",
+                "--type=method_call",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+            ])
+            .arg(format!("array:string:{}", file_uri(path)))
+            .arg("string:")
+            .output()
+            .is_ok_and(|o| o.status.success());
+        if shown {
+            return Ok(());
+        }
+        // 否则打开所在目录
+        let dir = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        };
+        spawn(Command::new("xdg-open").arg(dir), path)
+    }
+}
+
+/// 打开文件（使用系统默认应用）
+#[tauri::command]
+pub async fn open_file(path: String) -> Result<(), FileActionError> {
+    let resolved = checked_path(&path)?;
+    open_path(&resolved)
+}
+
+/// 在 Finder / 资源管理器 / 文件管理器中显示文件
+#[tauri::command]
+pub async fn reveal_in_folder(path: String) -> Result<(), FileActionError> {
+    let resolved = checked_path(&path)?;
+    reveal_path(&resolved)
+}
@@ -0,0 +1,239 @@
+//! 文件访问守卫：Agent 写入、移动、删除沙盒以外的文件前需经用户批准
+//!
+//! Python 文件管理器操作前发送 file_access_request 事件。路径位于沙盒目录、
+//! 配置 file_access_allowlist 中的目录或已授权的文件夹（见 permissions）内时直接放行，
+//! 否则以 file-access-request 事件请前端确认，前端通过 approve_file_access 回复
+//! “仅允许这一次”、“始终允许此文件夹”或“拒绝”。
+//! 执行任务期间 Python 服务不读取 stdin，审批结果写入 ~/.deskjarvis/file_access/<access_id>.json。
+//! 观察模式下一律拒绝，沙盒内也不例外。
+//! 获准覆盖已有文件时，旧文件先按产物命名规则改名保留（见 artifact_naming）。
+//! 获准的写入同时记入任务的撤销日志；获准的删除由这里把文件移入回收区，Python 随后不再删除（见 undo）。
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::agent_event::FileAccessRequestEvent;
+use crate::config;
+use crate::event_sink::EventSink;
+use crate::{artifact_naming, observer, permissions, sandbox, undo};
+
+/// 等待前端确认的访问请求（access_id → 请求）
+static PENDING: Mutex<BTreeMap<String, FileAccessRequest>> = Mutex::new(BTreeMap::new());
+
+/// file-access-request 事件负载
+#[derive(Debug, Clone, Serialize)]
+struct FileAccessRequest {
+    request_id: String,
+    access_id: String,
+    path: String,
+    operation: String,
+    /// 选择“始终允许此文件夹”时授权的目录
+    folder: String,
+}
+
+/// 用户对文件访问请求的选择
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessDecision {
+    /// 仅允许这一次
+    AllowOnce,
+    /// 始终允许此文件夹
+    AllowFolder,
+    Deny,
+}
+
+/// access_id 用作文件名，只允许字母、数字和下划线
+fn is_valid_access_id(access_id: &str) -> bool {
+    !access_id.is_empty()
+        && access_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 解析符号链接：规范化已存在的最深祖先目录，再拼接尚不存在的部分
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut existing = path.to_path_buf();
+    let mut missing = Vec::new();
+    while !existing.exists() {
+        match (existing.file_name(), existing.parent()) {
+            (Some(name), Some(parent)) => {
+                missing.push(name.to_os_string());
+                existing = parent.to_path_buf();
+            }
+            _ => break,
+        }
+    }
+    let mut resolved = existing.canonicalize().unwrap_or(existing);
+    for name in missing.iter().rev() {
+        resolved.push(name);
+    }
+    resolved
+}
+
+/// 路径是否在沙盒、白名单目录或已授权的文件夹内
+///
+/// normalize 不解析不存在部分中的 ".."（如 sandbox/nope/../../etc），含 ".." 的路径一律不放行。
+pub fn is_allowed(path: &Path, config: &config::AppConfig) -> bool {
+    if path.components().any(|c| c == Component::ParentDir) {
+        return false;
+    }
+    let path = normalize(path);
+    std::iter::once(config.sandbox_path.as_str())
+        .chain(config.file_access_allowlist.iter().map(String::as_str))
+        .filter(|dir| !dir.trim().is_empty())
+        .map(|dir| normalize(&sandbox::expand_sandbox_path(dir)))
+        .chain(permissions::granted_folders().iter().map(|dir| normalize(dir)))
+        .any(|dir| path.starts_with(dir))
+}
+
+/// “始终允许此文件夹”授权的目录：路径本身是目录时为其自身，否则为所在目录
+fn grant_folder_of(path: &str) -> PathBuf {
+    let path = normalize(Path::new(path));
+    if path.is_dir() {
+        path
+    } else {
+        path.parent().map(Path::to_path_buf).unwrap_or(path)
+    }
+}
+
+/// 写入审批结果，供 Python 轮询读取
+fn write_decision(access_id: &str, approved: bool) -> Result<(), String> {
+    let dir = config::get_data_dir()?.join("file_access");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    let content = serde_json::json!({ "access_id": access_id, "approved": approved });
+    std::fs::write(dir.join(format!("{}.json", access_id)), content.to_string())
+        .map_err(|e| format!("写入文件访问审批结果失败: {}", e))
+}
+
+/// 获准访问前的处理：删除时移入回收区；写入前记入撤销日志，覆盖已有文件时保留旧版本
+/// （失败时只记录，不阻止操作）
+fn before_access(request_id: &str, path: &str, operation: &str) {
+    if path.is_empty() {
+        return;
+    }
+    if operation == "delete" {
+        if let Err(e) = undo::remove(request_id, Path::new(path)) {
+            eprintln!("[Tauri] ⚠️ {}，将直接删除", e);
+        }
+        return;
+    }
+    undo::before_write(request_id, Path::new(path));
+    if operation != "write" {
+        return;
+    }
+    let naming = config::load_config()
+        .map(|c| c.artifact_naming)
+        .unwrap_or_default();
+    if let Err(e) = artifact_naming::preserve_existing(Path::new(path), request_id, &naming) {
+        eprintln!("[Tauri] ⚠️ {}", e);
+    }
+}
+
+/// 回复 Python 的审批请求（file_access_request 与 confirmation_request 共用）
+pub fn answer(access_id: &str, approved: bool) -> Result<(), String> {
+    if !is_valid_access_id(access_id) {
+        return Err(format!("无效的审批请求 ID: {}", access_id));
+    }
+    write_decision(access_id, approved)
+}
+
+/// 处理一条 file_access_request 事件：白名单内直接放行，否则请前端确认
+///
+/// 非交互模式（命令行）无人确认，白名单以外的路径直接拒绝。
+pub fn handle_request(sink: &impl EventSink, request_id: &str, event: &FileAccessRequestEvent) {
+    let access_id = event.access_id.clone();
+    if !is_valid_access_id(&access_id) {
+        eprintln!("[Tauri] ⚠️ 无效的文件访问请求 ID: {}", access_id);
+        return;
+    }
+    let path = event.path.clone();
+    let operation = event.operation.clone();
+
+    let allowed = !path.is_empty()
+        && config::load_config()
+            .map(|c| is_allowed(Path::new(&path), &c))
+            .unwrap_or(false);
+    let decision = if observer::is_enabled() {
+        eprintln!("[Tauri] 👁 观察模式，拒绝{}: {}", operation, path);
+        Some(false)
+    } else if allowed {
+        Some(true)
+    } else if !sink.interactive() {
+        eprintln!("[Tauri] 🚫 非交互模式，拒绝访问沙盒以外的路径: {}", path);
+        Some(false)
+    } else {
+        None
+    };
+    if let Some(approved) = decision {
+        if approved {
+            before_access(request_id, &path, &operation);
+        }
+        if let Err(e) = write_decision(&access_id, approved) {
+            eprintln!("[Tauri] ⚠️ {}", e);
+        }
+        return;
+    }
+
+    eprintln!("[Tauri] 🔐 任务 {} 请求{}沙盒以外的路径: {}", request_id, operation, path);
+    let request = FileAccessRequest {
+        request_id: request_id.to_string(),
+        access_id: access_id.clone(),
+        folder: grant_folder_of(&path).to_string_lossy().to_string(),
+        path,
+        operation,
+    };
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.insert(access_id, request.clone());
+    }
+    sink.send("file-access-request", request);
+}
+
+/// 任务结束后丢弃其未回复的请求（Python 侧已按超时或取消处理）
+pub fn clear_task(request_id: &str) {
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.retain(|_, r| r.request_id != request_id);
+    }
+}
+
+/// 回复文件访问请求：仅允许这一次、始终允许此文件夹或拒绝
+#[tauri::command]
+pub async fn approve_file_access(access_id: String, decision: AccessDecision) -> Result<(), String> {
+    let request = PENDING
+        .lock()
+        .map_err(|_| "文件访问请求状态不可用")?
+        .remove(&access_id)
+        .ok_or_else(|| format!("未找到文件访问请求: {}", access_id))?;
+    // 提示期间开启了观察模式时，批准也不生效
+    let approved = decision != AccessDecision::Deny && !observer::is_enabled();
+    if approved {
+        before_access(&request.request_id, &request.path, &request.operation);
+    }
+    if approved && decision == AccessDecision::AllowFolder {
+        if let Err(e) = permissions::grant_folder(Path::new(&request.folder)) {
+            eprintln!("[Tauri] ⚠️ {}", e);
+        }
+    }
+    write_decision(&access_id, approved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_parent_dir_escaping_the_sandbox() {
+        let sandbox = std::env::temp_dir().join("deskjarvis_file_guard_test");
+        std::fs::create_dir_all(&sandbox).expect("创建测试沙盒失败");
+        let config = config::AppConfig {
+            sandbox_path: sandbox.to_string_lossy().to_string(),
+            file_access_allowlist: Vec::new(),
+            ..config::AppConfig::default()
+        };
+        assert!(is_allowed(&sandbox.join("nope/new.txt"), &config));
+        for path in ["nope/../../etc/passwd", "../outside.txt", "a/../b.txt"] {
+            assert!(!is_allowed(&sandbox.join(path), &config), "应当拒绝: {:?}", path);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! 专注时段：限定时间内暂停定时任务、提醒与通知，到期自动恢复
+//!
+//! 会话写入 ~/.deskjarvis/focus.json，Python 端的提醒和 send_notification 读取该文件，
+//! 专注期间到点的提醒推迟到结束后触发，通知直接跳过。定时任务调度循环在专注期间
+//! 不取出到期任务，结束后补跑。allow_interactive 为 false 时界面发起的任务也被拒绝。
+//! 应用重启后未到期的会话继续生效。
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::{config, history, tray};
+
+/// 单次专注的最长分钟数
+const MAX_MINUTES: u32 = 24 * 60;
+
+static SESSION: Mutex<Option<FocusSession>> = Mutex::new(None);
+
+/// 会话代数：提前结束或重新开始后，旧的到期计时器不再生效
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// 专注会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusSession {
+    /// 开始时间（毫秒时间戳）
+    pub started_at: u64,
+    /// 结束时间（毫秒时间戳）
+    pub ends_at: u64,
+    /// 是否仍允许界面发起的任务
+    pub allow_interactive: bool,
+}
+
+fn session_path() -> Result<std::path::PathBuf, String> {
+    Ok(config::get_data_dir()?.join("focus.json"))
+}
+
+fn save_session(session: Option<&FocusSession>) -> Result<(), String> {
+    let path = session_path()?;
+    let Some(session) = session else {
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(|e| format!("删除专注状态失败: {}", e))?;
+        }
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(session)
+        .map_err(|e| format!("序列化专注状态失败: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("保存专注状态失败: {}", e))
+}
+
+/// 当前未到期的专注会话
+pub fn current() -> Option<FocusSession> {
+    let session = SESSION.lock().ok()?.clone()?;
+    (session.ends_at > history::now_millis()).then_some(session)
+}
+
+/// 是否处于专注时段（后台自动化据此暂停）
+pub fn is_active() -> bool {
+    current().is_some()
+}
+
+/// 专注时段是否拒绝界面发起的任务
+pub fn blocks_interactive() -> bool {
+    current().is_some_and(|s| !s.allow_interactive)
+}
+
+/// 结束时间的本地时刻文本（托盘显示）
+pub fn ends_at_text(ends_at: u64) -> String {
+    Local
+        .timestamp_millis_opt(ends_at as i64)
+        .single()
+        .map(|t| t.format("%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// 刷新托盘并通知前端
+fn notify(app: &AppHandle, session: Option<&FocusSession>) {
+    tray::set_focus_mode(app, session.map(|s| ends_at_text(s.ends_at)).as_deref());
+    let _ = app.emit("focus-session-changed", session);
+}
+
+/// 到期后自动结束
+fn spawn_timer(app: AppHandle, session: &FocusSession) {
+    let generation = GENERATION.load(Ordering::SeqCst);
+    let remaining = session.ends_at.saturating_sub(history::now_millis());
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(remaining)).await;
+        if GENERATION.load(Ordering::SeqCst) == generation {
+            eprintln!("[Tauri] 🔔 专注时段结束，恢复定时任务与通知");
+            end(&app);
+        }
+    });
+}
+
+/// 开始专注（已有会话时替换）
+pub fn start(app: &AppHandle, minutes: u32, allow_interactive: bool) -> Result<FocusSession, String> {
+    if minutes == 0 || minutes > MAX_MINUTES {
+        return Err(format!("专注时长需在 1 到 {} 分钟之间", MAX_MINUTES));
+    }
+    let started_at = history::now_millis();
+    let session = FocusSession {
+        started_at,
+        ends_at: started_at + u64::from(minutes) * 60 * 1000,
+        allow_interactive,
+    };
+    save_session(Some(&session))?;
+    *SESSION.lock().map_err(|_| "专注状态不可用")? = Some(session.clone());
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    eprintln!(
+        "[Tauri] 🎯 开始专注 {} 分钟，暂停定时任务与通知{}",
+        minutes,
+        if allow_interactive { "" } else { "，并拒绝新任务" }
+    );
+    spawn_timer(app.clone(), &session);
+    notify(app, Some(&session));
+    Ok(session)
+}
+
+/// 结束专注并恢复
+pub fn end(app: &AppHandle) {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    if let Ok(mut session) = SESSION.lock() {
+        *session = None;
+    }
+    if let Err(e) = save_session(None) {
+        eprintln!("[Tauri] ⚠️ {}", e);
+    }
+    notify(app, None);
+}
+
+/// 启动时恢复未到期的会话，已到期的清除
+pub fn restore(app: &AppHandle) {
+    let session = session_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<FocusSession>(&content).ok());
+    match session {
+        Some(session) if session.ends_at > history::now_millis() => {
+            eprintln!("[Tauri] 🎯 恢复专注时段，至 {} 结束", ends_at_text(session.ends_at));
+            if let Ok(mut current) = SESSION.lock() {
+                *current = Some(session.clone());
+            }
+            spawn_timer(app.clone(), &session);
+            notify(app, Some(&session));
+        }
+        Some(_) => {
+            if let Err(e) = save_session(None) {
+                eprintln!("[Tauri] ⚠️ {}", e);
+            }
+        }
+        None => {}
+    }
+}
+
+/// 开始专注时段；allow_interactive 默认为 true（仍可手动执行任务）
+#[tauri::command]
+pub async fn start_focus_session(
+    app: AppHandle,
+    minutes: u32,
+    allow_interactive: Option<bool>,
+) -> Result<FocusSession, String> {
+    start(&app, minutes, allow_interactive.unwrap_or(true))
+}
+
+/// 提前结束专注时段
+#[tauri::command]
+pub async fn end_focus_session(app: AppHandle) -> Result<(), String> {
+    if !is_active() {
+        return Err("当前没有进行中的专注时段".to_string());
+    }
+    eprintln!("[Tauri] 🎯 提前结束专注时段");
+    end(&app);
+    Ok(())
+}
+
+/// 查询当前专注时段
+#[tauri::command]
+pub async fn get_focus_session() -> Result<Option<FocusSession>, String> {
+    Ok(current())
+}
@@ -0,0 +1,55 @@
+//! Python 服务 stdout 协议分帧
+//!
+//! 版本 1 每行一个 JSON 事件；版本 2 每行为 "@dj:" + base64(UTF-8 JSON)，不带前缀的行
+//! 视为混入 stdout 的杂散输出并忽略。启动服务时以环境变量 DESKJARVIS_PROTOCOL 声明
+//! 支持的最高版本，服务在 ready 事件（始终按版本 1 发送）的 protocol 字段中给出
+//! 实际使用的版本；旧版服务不带该字段，按版本 1 处理。
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::redaction;
+
+/// 支持的最高协议版本
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// 向 Python 服务声明协议版本的环境变量
+pub const PROTOCOL_ENV: &str = "DESKJARVIS_PROTOCOL";
+
+/// 版本 2 的帧前缀
+const FRAME_PREFIX: &str = "@dj:";
+
+/// 杂散输出写入日志时保留的最大字符数
+const MAX_STRAY_CHARS: usize = 200;
+
+/// 从 ready 事件读取服务使用的协议版本
+pub fn negotiated_version(ready: &serde_json::Value) -> u32 {
+    ready
+        .get("protocol")
+        .and_then(|v| v.as_u64())
+        .map(|v| (v as u32).clamp(1, PROTOCOL_VERSION))
+        .unwrap_or(1)
+}
+
+/// 取出一行中的 JSON 文本；空行、杂散输出和无法解码的帧返回 None
+pub fn decode_line(line: &str, protocol: u32) -> Option<String> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let Some(payload) = trimmed.strip_prefix(FRAME_PREFIX) else {
+        if protocol < 2 {
+            return Some(trimmed.to_string());
+        }
+        let stray: String = trimmed.chars().take(MAX_STRAY_CHARS).collect();
+        eprintln!("[Tauri] ⚠️ 忽略 Python 服务 stdout 中的杂散输出: {}", redaction::redact(&stray));
+        return None;
+    };
+    match STANDARD.decode(payload).map(String::from_utf8) {
+        Ok(Ok(text)) => Some(text),
+        _ => {
+            eprintln!("[Tauri] ⚠️ 无法解码 Python 服务事件帧（{} 字节）", payload.len());
+            None
+        }
+    }
+}
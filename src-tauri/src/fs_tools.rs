@@ -0,0 +1,340 @@
+//! 文件工具：tool_call 通道上的 fs.read、fs.write、fs.list、fs.move、fs.delete
+//!
+//! 常见的文件读写由 Rust 直接完成，不再经过 Python 的文件管理代码。相对路径基于任务
+//! 工作目录。写入、移动、删除只允许在沙盒、file_access_allowlist 中的目录和已授权的
+//! 文件夹内（见 file_guard），其他位置仍走 Python 的文件访问审批；读取和列目录另外
+//! 允许用户主目录，与 Python 文件管理器一致。DeskJarvis 数据目录（配置、密钥、日志）
+//! 除沙盒外一律拒绝。读取超过上限的文件直接报错；非 UTF-8 内容以 base64 返回。
+//! 写入、移动、删除记入任务的撤销日志，删除的文件移入 sandbox/.trash/<task_id>（见 undo）。
+
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use base64::Engine;
+use serde::Serialize;
+
+use crate::file_guard::{self, normalize};
+use crate::{config, permissions, sandbox, undo};
+
+/// 单次读取的最大字节数
+const MAX_READ_BYTES: u64 = 10 * 1024 * 1024;
+
+/// 单次写入的最大字节数
+const MAX_WRITE_BYTES: usize = 50 * 1024 * 1024;
+
+/// 列目录默认和最多返回的条目数
+const DEFAULT_LIST_ENTRIES: usize = 1000;
+const MAX_LIST_ENTRIES: usize = 10_000;
+
+/// 目录条目
+#[derive(Debug, Clone, Serialize)]
+struct Entry {
+    name: String,
+    path: String,
+    is_dir: bool,
+    size: u64,
+    /// 修改时间（毫秒时间戳）
+    modified: Option<u64>,
+}
+
+fn str_arg<'a>(args: &'a serde_json::Value, key: &str) -> Result<&'a str, String> {
+    args.get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| format!("缺少参数 {}", key))
+}
+
+fn bool_arg(args: &serde_json::Value, key: &str) -> bool {
+    args.get(key).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+/// 展开 ~，相对路径基于任务工作目录（没有时为沙盒根目录）
+fn resolve(path: &str, work_dir: Option<&Path>) -> PathBuf {
+    let expanded = match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map(|home| home.join(rest)),
+        None if path == "~" => dirs::home_dir(),
+        None => None,
+    }
+    .unwrap_or_else(|| PathBuf::from(path));
+    if expanded.is_absolute() {
+        expanded
+    } else {
+        work_dir
+            .map(Path::to_path_buf)
+            .unwrap_or_else(sandbox::sandbox_root)
+            .join(expanded)
+    }
+}
+
+/// 检查路径边界，返回规范化后的路径
+pub(crate) fn checked(path: &str, work_dir: Option<&Path>, write: bool) -> Result<PathBuf, String> {
+    if path.split(['/', '\\']).any(|part| part == "..") {
+        return Err(format!("路径不能包含 \"..\": {}", path));
+    }
+    let resolved = normalize(&resolve(path, work_dir));
+    let app_config = config::load_config()?;
+    let sandbox_root = normalize(&sandbox::sandbox_root());
+    let in_data_dir = config::get_data_dir()
+        .map(|dir| normalize(&dir))
+        .is_ok_and(|dir| resolved.starts_with(dir));
+    if in_data_dir && !resolved.starts_with(&sandbox_root) {
+        return Err(format!("不允许访问 DeskJarvis 数据目录: {}", path));
+    }
+    let allowed = file_guard::is_allowed(&resolved, &app_config)
+        || (!write
+            && dirs::home_dir()
+                .map(|home| normalize(&home))
+                .is_some_and(|home| resolved.starts_with(home)));
+    if !allowed {
+        return Err(if write {
+            format!("只能修改沙盒和已授权目录内的文件: {}", path)
+        } else {
+            format!("只能读取用户目录、沙盒和已授权目录内的文件: {}", path)
+        });
+    }
+    Ok(resolved)
+}
+
+/// 不能整体删除或移走的目录：沙盒根目录、白名单目录和已授权的文件夹本身
+fn is_protected_root(path: &Path) -> bool {
+    let Ok(app_config) = config::load_config() else {
+        return true;
+    };
+    std::iter::once(app_config.sandbox_path.as_str())
+        .chain(app_config.file_access_allowlist.iter().map(String::as_str))
+        .filter(|dir| !dir.trim().is_empty())
+        .map(|dir| normalize(&sandbox::expand_sandbox_path(dir)))
+        .chain(permissions::granted_folders().iter().map(|d| normalize(d)))
+        .chain(dirs::home_dir().map(|home| normalize(&home)))
+        .any(|root| root == path)
+}
+
+fn modified_millis(meta: &std::fs::Metadata) -> Option<u64> {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+}
+
+/// fs.read：{path, encoding?: "utf-8" | "base64"}，非 UTF-8 内容自动以 base64 返回
+fn read(work_dir: Option<&Path>, args: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let path = checked(str_arg(args, "path")?, work_dir, false)?;
+    let meta = std::fs::metadata(&path).map_err(|_| format!("文件不存在: {}", path.display()))?;
+    if !meta.is_file() {
+        return Err(format!("路径不是文件: {}", path.display()));
+    }
+    if meta.len() > MAX_READ_BYTES {
+        return Err(format!(
+            "文件过大（{} 字节，上限 {} 字节）: {}",
+            meta.len(),
+            MAX_READ_BYTES,
+            path.display()
+        ));
+    }
+    let bytes = std::fs::read(&path).map_err(|e| format!("读取文件失败: {}", e))?;
+    let want_base64 = args.get("encoding").and_then(|e| e.as_str()) == Some("base64");
+    let (content, encoding) = match String::from_utf8(bytes) {
+        Ok(text) if !want_base64 => (text, "utf-8"),
+        Ok(text) => (base64::engine::general_purpose::STANDARD.encode(text), "base64"),
+        Err(e) => (
+            base64::engine::general_purpose::STANDARD.encode(e.into_bytes()),
+            "base64",
+        ),
+    };
+    Ok(serde_json::json!({
+        "path": path,
+        "content": content,
+        "encoding": encoding,
+        "size": meta.len(),
+    }))
+}
+
+/// fs.write：{path, content, encoding?: "utf-8" | "base64", append?, overwrite? (默认 true)}
+fn write(request_id: &str, work_dir: Option<&Path>, args: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let path = checked(str_arg(args, "path")?, work_dir, true)?;
+    let content = args
+        .get("content")
+        .and_then(|c| c.as_str())
+        .ok_or("缺少参数 content")?;
+    let bytes = match args.get("encoding").and_then(|e| e.as_str()) {
+        Some("base64") => base64::engine::general_purpose::STANDARD
+            .decode(content)
+            .map_err(|e| format!("base64 解码失败: {}", e))?,
+        _ => content.as_bytes().to_vec(),
+    };
+    if bytes.len() > MAX_WRITE_BYTES {
+        return Err(format!(
+            "写入内容过大（{} 字节，上限 {} 字节）",
+            bytes.len(),
+            MAX_WRITE_BYTES
+        ));
+    }
+    if path.is_dir() {
+        return Err(format!("路径是目录: {}", path.display()));
+    }
+    let append = bool_arg(args, "append");
+    let overwrite = args.get("overwrite").and_then(|v| v.as_bool()).unwrap_or(true);
+    if path.exists() && !append && !overwrite {
+        return Err(format!("文件已存在: {}", path.display()));
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    undo::before_write(request_id, &path);
+    if append {
+        use std::io::Write;
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut f| f.write_all(&bytes))
+            .map_err(|e| format!("写入文件失败: {}", e))?;
+    } else {
+        // 先写临时文件再改名，写入失败时不破坏原文件
+        let tmp = path.with_extension("deskjarvis-tmp");
+        std::fs::write(&tmp, &bytes).map_err(|e| format!("写入文件失败: {}", e))?;
+        std::fs::rename(&tmp, &path).map_err(|e| {
+            let _ = std::fs::remove_file(&tmp);
+            format!("写入文件失败: {}", e)
+        })?;
+    }
+    Ok(serde_json::json!({ "path": path, "size": bytes.len(), "appended": append }))
+}
+
+/// fs.list：{path, recursive?, max_entries?}，按路径排序
+fn list(work_dir: Option<&Path>, args: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let root = checked(str_arg(args, "path")?, work_dir, false)?;
+    if !root.is_dir() {
+        return Err(format!("目录不存在: {}", root.display()));
+    }
+    let recursive = bool_arg(args, "recursive");
+    let limit = args
+        .get("max_entries")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_LIST_ENTRIES)
+        .clamp(1, MAX_LIST_ENTRIES);
+    let mut entries = Vec::new();
+    let mut truncated = false;
+    let mut pending = vec![root.clone()];
+    'scan: while let Some(dir) = pending.pop() {
+        let read = std::fs::read_dir(&dir).map_err(|e| format!("读取目录失败: {}", e))?;
+        for item in read.flatten() {
+            if entries.len() >= limit {
+                truncated = true;
+                break 'scan;
+            }
+            let Ok(meta) = item.metadata() else {
+                continue;
+            };
+            let path = item.path();
+            if recursive && meta.is_dir() && !item.file_type().is_ok_and(|t| t.is_symlink()) {
+                pending.push(path.clone());
+            }
+            entries.push(Entry {
+                name: item.file_name().to_string_lossy().to_string(),
+                path: path.to_string_lossy().to_string(),
+                is_dir: meta.is_dir(),
+                size: if meta.is_dir() { 0 } else { meta.len() },
+                modified: modified_millis(&meta),
+            });
+        }
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(serde_json::json!({ "path": root, "entries": entries, "truncated": truncated }))
+}
+
+/// fs.move：{src, dst, overwrite?}，dst 为已存在的目录时移入其中
+fn move_path(request_id: &str, work_dir: Option<&Path>, args: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let src = checked(str_arg(args, "src")?, work_dir, true)?;
+    let mut dst = checked(str_arg(args, "dst")?, work_dir, true)?;
+    if !src.exists() {
+        return Err(format!("文件不存在: {}", src.display()));
+    }
+    if is_protected_root(&src) {
+        return Err(format!("不能移动该目录: {}", src.display()));
+    }
+    if dst.is_dir() {
+        if let Some(name) = src.file_name() {
+            dst = dst.join(name);
+        }
+    }
+    if dst.starts_with(&src) {
+        return Err("不能把目录移动到自身内部".to_string());
+    }
+    let mut replaced = None;
+    if dst.exists() {
+        if !bool_arg(args, "overwrite") || dst.is_dir() {
+            return Err(format!("目标已存在: {}", dst.display()));
+        }
+        // 被覆盖的文件移入撤销日志目录，失败时直接删除
+        match undo::set_aside(request_id, &dst) {
+            Ok(backup) => replaced = Some(backup),
+            Err(e) => {
+                eprintln!("[Tauri] ⚠️ {}，被覆盖的文件将无法恢复", e);
+                std::fs::remove_file(&dst).map_err(|e| format!("删除已有文件失败: {}", e))?;
+            }
+        }
+    }
+    if let Some(parent) = dst.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    if std::fs::rename(&src, &dst).is_err() {
+        // 跨磁盘时改为复制后删除（只支持文件）
+        if !src.is_file() {
+            return Err(format!("移动目录失败（可能跨磁盘）: {}", src.display()));
+        }
+        std::fs::copy(&src, &dst).map_err(|e| format!("移动文件失败: {}", e))?;
+        std::fs::remove_file(&src).map_err(|e| format!("删除源文件失败: {}", e))?;
+    }
+    undo::record(
+        request_id,
+        undo::FileChange::Moved {
+            from: src.clone(),
+            to: dst.clone(),
+            replaced,
+        },
+    );
+    Ok(serde_json::json!({ "src": src, "dst": dst }))
+}
+
+/// fs.delete：{path, recursive?}，删除目录需要 recursive
+fn delete(request_id: &str, work_dir: Option<&Path>, args: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let path = checked(str_arg(args, "path")?, work_dir, true)?;
+    let meta = std::fs::symlink_metadata(&path)
+        .map_err(|_| format!("文件不存在: {}", path.display()))?;
+    if is_protected_root(&path) {
+        return Err(format!("不能删除该目录: {}", path.display()));
+    }
+    if meta.is_dir() && !bool_arg(args, "recursive") {
+        return Err(format!("删除目录需要 recursive: {}", path.display()));
+    }
+    // 移入回收区，失败时（如无写入权限）直接删除
+    if let Err(e) = undo::remove(request_id, &path) {
+        eprintln!("[Tauri] ⚠️ {}，删除后将无法恢复", e);
+        if meta.is_dir() {
+            std::fs::remove_dir_all(&path).map_err(|e| format!("删除目录失败: {}", e))?;
+        } else {
+            std::fs::remove_file(&path).map_err(|e| format!("删除文件失败: {}", e))?;
+        }
+    }
+    Ok(serde_json::json!({ "path": path, "is_dir": meta.is_dir() }))
+}
+
+/// 执行 fs.* 工具，op 为点号后的操作名
+pub fn call(
+    request_id: &str,
+    op: &str,
+    work_dir: Option<&Path>,
+    args: &serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    match op {
+        "read" => read(work_dir, args),
+        "write" => write(request_id, work_dir, args),
+        "list" => list(work_dir, args),
+        "move" => move_path(request_id, work_dir, args),
+        "delete" => delete(request_id, work_dir, args),
+        other => Err(format!("未知的工具: fs.{}", other)),
+    }
+}
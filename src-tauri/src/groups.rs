@@ -0,0 +1,135 @@
+//! 任务分组：流水线、监视规则等批量产生的任务归入同一分组，统一汇总进度与结果
+
+use std::collections::HashMap;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::history::{new_id, now_millis, HistoryStore};
+
+/// 任务分组
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskGroup {
+    pub id: String,
+    pub name: String,
+    /// 分组来源（如 "pipeline"、"watcher"、"manual"）
+    pub origin: String,
+    pub created_at: u64,
+    pub task_ids: Vec<String>,
+}
+
+/// 分组汇总状态
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupStatus {
+    group_id: String,
+    name: String,
+    origin: String,
+    total: usize,
+    running: usize,
+    succeeded: usize,
+    failed: usize,
+    /// 组内任务全部结束
+    finished: bool,
+}
+
+/// 分组登记（仅保存在内存中，任务明细以任务历史为准）
+#[derive(Default)]
+pub struct GroupStore {
+    groups: std::sync::Mutex<HashMap<String, TaskGroup>>,
+}
+
+impl GroupStore {
+    /// 新建分组
+    pub fn create(&self, name: &str, origin: &str) -> Result<TaskGroup, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("分组名称不能为空".to_string());
+        }
+        let group = TaskGroup {
+            id: new_id("group"),
+            name: name.to_string(),
+            origin: origin.trim().to_string(),
+            created_at: now_millis(),
+            task_ids: Vec::new(),
+        };
+        let mut groups = self.groups.lock().map_err(|_| "分组状态不可用")?;
+        groups.insert(group.id.clone(), group.clone());
+        Ok(group)
+    }
+
+    /// 将任务加入分组
+    pub fn add_task(&self, group_id: &str, task_id: &str) -> Result<(), String> {
+        let mut groups = self.groups.lock().map_err(|_| "分组状态不可用")?;
+        let group = groups
+            .get_mut(group_id)
+            .ok_or_else(|| format!("未找到任务分组: {}", group_id))?;
+        group.task_ids.push(task_id.to_string());
+        Ok(())
+    }
+
+    /// 根据任务历史汇总分组状态
+    pub fn status(&self, group_id: &str, history: &HistoryStore) -> Result<GroupStatus, String> {
+        let group = self
+            .groups
+            .lock()
+            .map_err(|_| "分组状态不可用")?
+            .get(group_id)
+            .cloned()
+            .ok_or_else(|| format!("未找到任务分组: {}", group_id))?;
+
+        let mut status = GroupStatus {
+            group_id: group.id,
+            name: group.name,
+            origin: group.origin,
+            total: group.task_ids.len(),
+            running: 0,
+            succeeded: 0,
+            failed: 0,
+            finished: false,
+        };
+        for task_id in &group.task_ids {
+            match history.get(task_id).and_then(|r| r.success) {
+                Some(true) => status.succeeded += 1,
+                Some(false) => status.failed += 1,
+                None => status.running += 1,
+            }
+        }
+        status.finished = status.total > 0 && status.running == 0;
+        Ok(status)
+    }
+}
+
+/// 向前端推送分组进度，组内任务全部结束时额外推送分组结果
+pub fn emit_group_update(app: &AppHandle, groups: &GroupStore, history: &HistoryStore, group_id: &str) {
+    let status = match groups.status(group_id, history) {
+        Ok(status) => status,
+        Err(e) => {
+            eprintln!("[Tauri] ⚠️ {}", e);
+            return;
+        }
+    };
+    let _ = app.emit("group-progress", &status);
+    if status.finished {
+        let _ = app.emit("group-result", &status);
+    }
+}
+
+/// 新建任务分组，返回分组信息（执行任务时传入 group_id 即归入该组）
+#[tauri::command]
+pub async fn create_task_group(
+    state: tauri::State<'_, crate::AppState>,
+    name: String,
+    origin: Option<String>,
+) -> Result<TaskGroup, String> {
+    state
+        .groups
+        .create(&name, origin.as_deref().unwrap_or("manual"))
+}
+
+/// 获取分组的汇总状态
+#[tauri::command]
+pub async fn get_group_status(
+    state: tauri::State<'_, crate::AppState>,
+    group_id: String,
+) -> Result<GroupStatus, String> {
+    state.groups.status(&group_id, &state.history)
+}
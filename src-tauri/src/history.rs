@@ -0,0 +1,324 @@
+//! 任务历史登记：~/.deskjarvis/task_history.json
+//!
+//! 由 Rust 侧记录每个任务的起止时间、结果和工作目录，
+//! 与 Python 端的 history.json（收藏/常用指令）相互独立。
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::config;
+use crate::usage::Usage;
+
+/// 最多保留的历史条数（置顶的记录不计入，也不会被清理）
+const MAX_RECORDS: usize = 500;
+
+/// 当前毫秒时间戳
+pub fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// ID 的序号，同一毫秒内生成的 ID 不会重复
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// 生成 <前缀>_<毫秒时间戳>_<序号> 形式的 ID（任务、分组等共用）
+pub fn new_id(prefix: &str) -> String {
+    format!("{}_{}_{}", prefix, now_millis(), NEXT_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// 生成任务 ID（task_<毫秒时间戳>_<序号>），所有入口共用
+pub fn new_task_id() -> String {
+    new_id("task")
+}
+
+/// 任务提前结束的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub enum TerminationReason {
+    /// 用户取消
+    UserCancel,
+    /// 长时间无响应后取消
+    Timeout,
+    /// 超出单任务费用上限
+    Budget,
+    /// Python 服务执行中崩溃
+    Crash,
+}
+
+/// 单个任务的历史记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRecord {
+    pub id: String,
+    pub instruction: String,
+    /// 开始时间（毫秒时间戳）
+    pub started_at: u64,
+    /// 结束时间（毫秒时间戳），执行中为 None
+    pub finished_at: Option<u64>,
+    /// 执行结果，执行中为 None
+    pub success: Option<bool>,
+    pub message: Option<String>,
+    /// 任务专属工作目录（sandbox/task_<id>）
+    pub work_dir: Option<String>,
+    /// 任务结束时工作目录中的文件（相对路径）
+    #[serde(default)]
+    pub artifacts: Vec<String>,
+    /// 置顶：列表中排在最前，且不会因超出条数上限被清理
+    #[serde(default)]
+    pub pinned: bool,
+    /// 提前结束的原因，正常完成（含失败）时为 None
+    #[serde(default)]
+    pub termination_reason: Option<TerminationReason>,
+    /// 模型用量与估算费用，没有模型调用时为 None
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+/// 任务历史存储（内存缓存 + JSON 文件持久化）
+pub struct HistoryStore {
+    path: Option<PathBuf>,
+    records: std::sync::Mutex<Vec<TaskRecord>>,
+}
+
+impl HistoryStore {
+    /// 从磁盘加载历史，文件不存在或损坏时从空记录开始
+    pub fn load() -> Self {
+        let path = config::get_data_dir()
+            .map(|dir| dir.join("task_history.json"))
+            .ok();
+
+        let records = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|content| match serde_json::from_str::<Vec<TaskRecord>>(&content) {
+                Ok(records) => Some(records),
+                Err(e) => {
+                    eprintln!("[Tauri] ⚠️ 解析任务历史失败: {}，将重新记录", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        HistoryStore {
+            path,
+            records: std::sync::Mutex::new(records),
+        }
+    }
+
+    /// 写回磁盘（调用方持有锁）
+    fn persist(&self, records: &[TaskRecord]) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        match serde_json::to_string_pretty(records) {
+            Ok(content) => {
+                if let Err(e) = std::fs::write(path, content) {
+                    eprintln!("[Tauri] ⚠️ 写入任务历史失败: {}", e);
+                }
+            }
+            Err(e) => eprintln!("[Tauri] ⚠️ 序列化任务历史失败: {}", e),
+        }
+    }
+
+    /// 对记录做修改并持久化
+    fn update<F: FnOnce(&mut Vec<TaskRecord>)>(&self, f: F) {
+        let Ok(mut records) = self.records.lock() else {
+            return;
+        };
+        f(&mut records);
+        // 超出上限时从最旧的未置顶记录开始清理
+        let unpinned = records.iter().filter(|r| !r.pinned).count();
+        if unpinned > MAX_RECORDS {
+            let mut excess = unpinned - MAX_RECORDS;
+            records.retain(|r| {
+                if excess == 0 || r.pinned {
+                    return true;
+                }
+                excess -= 1;
+                false
+            });
+        }
+        self.persist(&records);
+    }
+
+    /// 登记任务开始
+    pub fn record_start(&self, id: &str, instruction: &str, work_dir: Option<String>) {
+        let record = TaskRecord {
+            id: id.to_string(),
+            instruction: instruction.to_string(),
+            started_at: now_millis(),
+            finished_at: None,
+            success: None,
+            message: None,
+            work_dir,
+            artifacts: Vec::new(),
+            pinned: false,
+            termination_reason: None,
+            usage: None,
+        };
+        self.update(|records| records.push(record));
+    }
+
+    /// 登记任务结束
+    pub fn record_finish(
+        &self,
+        id: &str,
+        success: bool,
+        message: &str,
+        termination_reason: Option<TerminationReason>,
+    ) {
+        self.update(|records| {
+            if let Some(record) = records.iter_mut().rev().find(|r| r.id == id) {
+                record.finished_at = Some(now_millis());
+                record.success = Some(success);
+                record.message = Some(message.to_string());
+                record.termination_reason = termination_reason;
+            }
+        });
+    }
+
+    /// 登记任务的模型用量
+    pub fn record_usage(&self, id: &str, usage: Usage) {
+        self.update(|records| {
+            if let Some(record) = records.iter_mut().rev().find(|r| r.id == id) {
+                record.usage = Some(usage);
+            }
+        });
+    }
+
+    /// 登记任务产物
+    pub fn record_artifacts(&self, id: &str, artifacts: Vec<String>) {
+        self.update(|records| {
+            if let Some(record) = records.iter_mut().rev().find(|r| r.id == id) {
+                record.artifacts = artifacts;
+            }
+        });
+    }
+
+    /// 置顶或取消置顶，记录不存在时返回 false
+    pub fn set_pinned(&self, id: &str, pinned: bool) -> bool {
+        let mut found = false;
+        self.update(|records| {
+            if let Some(record) = records.iter_mut().rev().find(|r| r.id == id) {
+                record.pinned = pinned;
+                found = true;
+            }
+        });
+        found
+    }
+
+    /// 批量删除记录：任一 ID 不存在时不做任何修改；成功时返回被删除的记录
+    pub fn remove_many(&self, ids: &[String]) -> Result<Vec<TaskRecord>, String> {
+        let mut records = self.records.lock().map_err(|_| "任务历史状态不可用")?;
+        let missing: Vec<&str> = ids
+            .iter()
+            .filter(|id| !records.iter().any(|r| &r.id == *id))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!("未找到任务记录: {}", missing.join(", ")));
+        }
+        let (removed, kept) = records.drain(..).partition(|r| ids.contains(&r.id));
+        *records = kept;
+        self.persist(&records);
+        Ok(removed)
+    }
+
+    /// 从产物登记中移除若干文件，removed 为 (任务 ID, 相对路径)
+    pub fn remove_artifacts(&self, removed: &[(String, String)]) {
+        self.update(|records| {
+            for record in records.iter_mut() {
+                record
+                    .artifacts
+                    .retain(|a| !removed.iter().any(|(id, name)| *id == record.id && name == a));
+            }
+        });
+    }
+
+    /// 列出最近的记录：置顶的在前，各自按时间倒序；pinned_only 为 true 时只列置顶记录
+    pub fn list(&self, limit: usize, pinned_only: bool) -> Vec<TaskRecord> {
+        let Ok(records) = self.records.lock() else {
+            return Vec::new();
+        };
+        let pinned = records.iter().rev().filter(|r| r.pinned);
+        let others = records
+            .iter()
+            .rev()
+            .filter(|r| !r.pinned && !pinned_only);
+        pinned.chain(others).take(limit).cloned().collect()
+    }
+
+    /// 某时间（毫秒时间戳）之后开始的任务数
+    pub fn started_since(&self, since: u64) -> usize {
+        self.records
+            .lock()
+            .map(|records| records.iter().filter(|r| r.started_at >= since).count())
+            .unwrap_or_default()
+    }
+
+    /// 按 ID 获取记录
+    pub fn get(&self, id: &str) -> Option<TaskRecord> {
+        self.records
+            .lock()
+            .ok()?
+            .iter()
+            .rev()
+            .find(|r| r.id == id)
+            .cloned()
+    }
+}
+
+/// 列出最近的任务历史（置顶的在前）；pinned 为 true 时只列置顶记录
+#[tauri::command]
+pub async fn list_task_history(
+    state: tauri::State<'_, crate::AppState>,
+    limit: Option<usize>,
+    pinned: Option<bool>,
+) -> Result<Vec<TaskRecord>, String> {
+    Ok(state
+        .history
+        .list(limit.unwrap_or(50), pinned.unwrap_or(false)))
+}
+
+/// 置顶任务记录
+#[tauri::command]
+pub async fn pin_task(
+    state: tauri::State<'_, crate::AppState>,
+    task_id: String,
+) -> Result<(), String> {
+    if !state.history.set_pinned(&task_id, true) {
+        return Err(format!("未找到任务记录: {}", task_id));
+    }
+    Ok(())
+}
+
+/// 取消置顶任务记录
+#[tauri::command]
+pub async fn unpin_task(
+    state: tauri::State<'_, crate::AppState>,
+    task_id: String,
+) -> Result<(), String> {
+    if !state.history.set_pinned(&task_id, false) {
+        return Err(format!("未找到任务记录: {}", task_id));
+    }
+    Ok(())
+}
+
+/// 获取单个任务的历史记录（含工作目录）
+#[tauri::command]
+pub async fn get_task_record(
+    state: tauri::State<'_, crate::AppState>,
+    task_id: String,
+) -> Result<TaskRecord, String> {
+    state
+        .history
+        .get(&task_id)
+        .ok_or_else(|| format!("未找到任务记录: {}", task_id))
+}
@@ -0,0 +1,390 @@
+//! 本机 HTTP 接口：供脚本、Raycast/Alfred、Home Assistant 等外部自动化触发任务
+//!
+//! 只监听 127.0.0.1（端口见 http_api_port），除 /health 外都要求
+//! `Authorization: Bearer <http_api_token>`。开启后未设置令牌时自动生成并写回配置。
+//!
+//! - `POST /tasks`：{"instruction": "...", "context"?, "max_cost_usd"?, "wait"?}，
+//!   与 execute_task 走同一执行路径；默认立即返回 202 和任务 ID，wait 为 true 时等待结果；
+//!   专注时段内返回 409，超出每小时任务数或每日费用上限（见 budget）时返回 429
+//! - `GET /tasks/{id}`：任务状态与历史记录
+//! - `GET /health`：服务状态
+//! - `GET /metrics`：Prometheus 文本格式的运行指标（见 metrics），抓取时配置 bearer_token
+//!
+//! 协议只实现所需的 HTTP/1.1 子集：每个连接处理一个请求后关闭。
+
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+use serde::Deserialize;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::{budget, config, focus, history, metrics, window_manager};
+
+/// 默认端口
+pub const DEFAULT_PORT: u16 = 17321;
+
+/// 请求体最大大小（字节）
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// 请求头最大行数
+const MAX_HEADERS: usize = 64;
+
+/// JSON 响应的 Content-Type
+const JSON_CONTENT_TYPE: &str = "application/json; charset=utf-8";
+
+/// Prometheus 文本格式的 Content-Type
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// 读取请求的超时
+const READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// 当前监听的端口和令牌，以及监听任务
+static LISTENER: Mutex<Option<(u16, String, tauri::async_runtime::JoinHandle<()>)>> =
+    Mutex::new(None);
+
+/// 已接受、尚未写入历史的任务（GET 时报告为 queued）
+static ACCEPTED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// POST /tasks 的请求体
+#[derive(Debug, Deserialize)]
+struct CreateTaskBody {
+    instruction: String,
+    #[serde(default)]
+    context: Option<serde_json::Value>,
+    #[serde(default)]
+    max_cost_usd: Option<f64>,
+    /// 等待任务结束后返回结果
+    #[serde(default)]
+    wait: bool,
+}
+
+/// 生成随机访问令牌（32 字节系统随机数的十六进制）
+fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).map_err(|e| format!("生成随机数失败: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 读取配置中的端口和令牌；未开启时返回 None，缺少令牌时生成并保存
+fn resolve_settings() -> Result<Option<(u16, String)>, String> {
+    let mut app_config = config::load_config()?;
+    if !app_config.http_api_enabled.unwrap_or(false) {
+        return Ok(None);
+    }
+    let port = app_config.http_api_port.unwrap_or(DEFAULT_PORT);
+    let token = match app_config.http_api_token.clone().filter(|t| !t.is_empty()) {
+        Some(token) => token,
+        None => {
+            let token = generate_token()?;
+            app_config.http_api_token = Some(token.clone());
+            config::write_config(&app_config)?;
+            eprintln!("[Tauri] 🔑 已生成 HTTP 接口令牌并写入配置");
+            token
+        }
+    };
+    Ok(Some((port, token)))
+}
+
+/// 按配置启动、重启或停止 HTTP 接口（启动时和保存配置后调用）
+pub fn apply_config(app: &AppHandle) {
+    let settings = match resolve_settings() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("[Tauri] ⚠️ 读取 HTTP 接口配置失败: {}", e);
+            return;
+        }
+    };
+    let Ok(mut listener) = LISTENER.lock() else {
+        return;
+    };
+    let unchanged = match (&*listener, &settings) {
+        (Some((port, token, _)), Some((new_port, new_token))) => {
+            port == new_port && token == new_token
+        }
+        (None, None) => true,
+        _ => false,
+    };
+    if unchanged {
+        return;
+    }
+    if let Some((port, _, handle)) = listener.take() {
+        handle.abort();
+        eprintln!("[Tauri] 🔌 HTTP 接口已停止（端口 {}）", port);
+    }
+    if let Some((port, token)) = settings {
+        let handle = tauri::async_runtime::spawn(serve(app.clone(), port, token.clone()));
+        *listener = Some((port, token, handle));
+    }
+}
+
+async fn serve(app: AppHandle, port: u16, token: String) {
+    let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("[Tauri] ⚠️ HTTP 接口监听 127.0.0.1:{} 失败: {}", port, e);
+            return;
+        }
+    };
+    eprintln!("[Tauri] 🌐 HTTP 接口已启动: http://127.0.0.1:{}", port);
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let app = app.clone();
+        let token = token.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = handle_connection(&app, stream, &token).await {
+                eprintln!("[Tauri] ⚠️ HTTP 请求处理失败: {}", e);
+            }
+        });
+    }
+}
+
+/// 解析后的请求
+struct Request {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+/// 请求处理结果：(状态码, JSON 响应体)
+type Response = (u16, serde_json::Value);
+
+fn error(status: u16, message: impl Into<String>) -> Response {
+    (status, serde_json::json!({ "error": message.into() }))
+}
+
+async fn read_request(reader: &mut BufReader<TcpStream>) -> Result<Request, Response> {
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .await
+        .map_err(|e| error(400, format!("读取请求失败: {}", e)))?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(error(400, "无效的请求行"));
+    };
+    let method = method.to_string();
+    let path = target.split('?').next().unwrap_or(target).to_string();
+
+    let mut content_length = 0usize;
+    let mut authorization = None;
+    for _ in 0..MAX_HEADERS {
+        line.clear();
+        reader
+            .read_line(&mut line)
+            .await
+            .map_err(|e| error(400, format!("读取请求头失败: {}", e)))?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            let mut body = vec![0u8; content_length];
+            reader
+                .read_exact(&mut body)
+                .await
+                .map_err(|e| error(400, format!("读取请求体失败: {}", e)))?;
+            return Ok(Request {
+                method,
+                path,
+                authorization,
+                body,
+            });
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value
+                .parse()
+                .map_err(|_| error(400, "无效的 Content-Length"))?;
+            if content_length > MAX_BODY_BYTES {
+                return Err(error(413, "请求体过大"));
+            }
+        } else if name.eq_ignore_ascii_case("authorization") {
+            authorization = Some(value.to_string());
+        }
+    }
+    Err(error(400, "请求头过多"))
+}
+
+/// 逐字节比较令牌，耗时与不匹配位置无关
+fn token_matches(authorization: Option<&str>, token: &str) -> bool {
+    let Some(provided) = authorization.and_then(|v| v.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    let provided = provided.trim().as_bytes();
+    provided.len() == token.len()
+        && provided
+            .iter()
+            .zip(token.as_bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn handle_connection(app: &AppHandle, stream: TcpStream, token: &str) -> Result<(), String> {
+    let mut reader = BufReader::new(stream);
+    let (status, content_type, body) =
+        match tokio::time::timeout(READ_TIMEOUT, read_request(&mut reader)).await {
+            Ok(Ok(request)) if request.path.trim_matches('/') == "metrics" => {
+                match metrics_text(&request, token) {
+                    Ok(text) => (200, METRICS_CONTENT_TYPE, text),
+                    Err(response) => json(response),
+                }
+            }
+            Ok(Ok(request)) => json(route(app, request, token).await),
+            Ok(Err(response)) => json(response),
+            Err(_) => json(error(408, "读取请求超时")),
+        };
+
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason_phrase(status),
+        content_type,
+        body.len(),
+        body
+    );
+    let stream = reader.get_mut();
+    stream
+        .write_all(response.as_bytes())
+        .await
+        .map_err(|e| format!("写入响应失败: {}", e))?;
+    let _ = stream.shutdown().await;
+    Ok(())
+}
+
+fn json((status, body): Response) -> (u16, &'static str, String) {
+    (status, JSON_CONTENT_TYPE, body.to_string())
+}
+
+/// GET /metrics：与其他接口一样要求访问令牌
+fn metrics_text(request: &Request, token: &str) -> Result<String, Response> {
+    if !token_matches(request.authorization.as_deref(), token) {
+        return Err(error(401, "缺少或错误的访问令牌"));
+    }
+    if request.method != "GET" {
+        return Err(error(405, "只支持 GET"));
+    }
+    Ok(metrics::render())
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        _ => "Internal Server Error",
+    }
+}
+
+async fn route(app: &AppHandle, request: Request, token: &str) -> Response {
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    if segments == ["health"] {
+        return match request.method.as_str() {
+            "GET" => (
+                200,
+                serde_json::json!({ "status": "ok", "version": env!("CARGO_PKG_VERSION") }),
+            ),
+            _ => error(405, "只支持 GET"),
+        };
+    }
+    if !token_matches(request.authorization.as_deref(), token) {
+        return error(401, "缺少或错误的访问令牌");
+    }
+    match (request.method.as_str(), segments.as_slice()) {
+        ("POST", ["tasks"]) => create_task(app, &request.body).await,
+        ("GET", ["tasks", id]) => get_task(app, id),
+        (_, ["tasks"]) => error(405, "只支持 POST"),
+        (_, ["tasks", _]) => error(405, "只支持 GET"),
+        _ => error(404, format!("未知路径: {}", request.path)),
+    }
+}
+
+async fn create_task(app: &AppHandle, body: &[u8]) -> Response {
+    let body: CreateTaskBody = match serde_json::from_slice(body) {
+        Ok(body) => body,
+        Err(e) => return error(400, format!("无效的请求体: {}", e)),
+    };
+    let instruction = body.instruction.trim().to_string();
+    if instruction.is_empty() {
+        return error(400, "instruction 不能为空");
+    }
+    if focus::blocks_interactive() {
+        return error(409, "专注时段内已暂停执行任务");
+    }
+    let Some(window) = window_manager::main_window(app) else {
+        return error(500, "主窗口不存在，无法执行任务");
+    };
+    // 接受任务前检查预算，超出时直接拒绝而不是返回 202 后再失败
+    if let Err(e) = budget::admit(app, &window, &app.state::<crate::AppState>().history) {
+        return error(429, e.message());
+    }
+
+    let task_id = history::new_task_id();
+    if let Ok(mut accepted) = ACCEPTED.lock() {
+        accepted.insert(task_id.clone());
+    }
+    eprintln!("[Tauri] 🌐 HTTP 接口提交任务: {}", task_id);
+
+    let app = app.clone();
+    let id = task_id.clone();
+    let run = async move {
+        let state = app.state::<crate::AppState>();
+        let options = crate::TaskOptions {
+            max_cost_usd: body.max_cost_usd,
+            budget_checked: true,
+            ..Default::default()
+        };
+        let result =
+            crate::run_tracked_task(&window, &state, id.clone(), instruction, body.context, options)
+                .await;
+        if let Ok(mut accepted) = ACCEPTED.lock() {
+            accepted.remove(&id);
+        }
+        result
+    };
+
+    if body.wait {
+        return match run.await {
+            Ok(result) => (200, serde_json::json!({ "id": task_id, "result": result })),
+            Err(e) => (500, serde_json::json!({ "id": task_id, "error": e.message() })),
+        };
+    }
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = run.await {
+            eprintln!("[Tauri] ⚠️ HTTP 接口提交的任务失败: {}", e);
+        }
+    });
+    (
+        202,
+        serde_json::json!({ "id": task_id, "status": "queued", "url": format!("/tasks/{}", task_id) }),
+    )
+}
+
+fn get_task(app: &AppHandle, id: &str) -> Response {
+    let state = app.state::<crate::AppState>();
+    match state.history.get(id) {
+        Some(record) => {
+            let status = match record.success {
+                None => "running",
+                Some(true) => "succeeded",
+                Some(false) => "failed",
+            };
+            (200, serde_json::json!({ "id": id, "status": status, "task": record }))
+        }
+        None if ACCEPTED.lock().is_ok_and(|a| a.contains(id)) => {
+            (200, serde_json::json!({ "id": id, "status": "queued" }))
+        }
+        None => error(404, format!("未找到任务: {}", id)),
+    }
+}
@@ -0,0 +1,171 @@
+//! 界面文字本地化：托盘菜单、系统通知、对话框和命令返回的错误信息按 locale 配置显示
+//!
+//! 源码中的文字以简体中文书写，即 zh-CN 目录本身；其他语言的目录（locales/*.json）以中文原文为键。
+//! 运行时生成的消息按模板匹配：键中的 `{}`（含 `{:.1}` 等格式）匹配任意文字，匹配到的参数递归翻译
+//! （如 "读取配置文件失败: {}" 中嵌套的下层错误），译文中的 `{}` 按顺序、`{0}` 按序号填入参数；
+//! 目录中没有的文字原样返回。Tauri 无法在 Rust 侧统一改写命令的返回值，字符串形式的命令错误由前端
+//! safeInvoke 经 localize_message 翻译。
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::{config, tray};
+
+/// en-US 目录
+const EN_US_CATALOG: &str = include_str!("../locales/en-US.json");
+
+/// 界面语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+/// 当前语言（启动时从配置读取，set_locale 修改）
+static CURRENT: RwLock<Locale> = RwLock::new(Locale::ZhCn);
+
+/// 带参数的目录条目
+struct Template {
+    pattern: Regex,
+    translation: String,
+}
+
+/// 解析后的目录：不含参数的按原文查找，带参数的按模板匹配
+struct Catalog {
+    exact: HashMap<String, String>,
+    templates: Vec<Template>,
+}
+
+fn placeholder() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\{[^{}]*\}").expect("占位符正则无效"))
+}
+
+impl Catalog {
+    fn parse(content: &str) -> Self {
+        let entries: HashMap<String, String> = serde_json::from_str(content).unwrap_or_else(|e| {
+            eprintln!("[Tauri] ⚠️ 解析语言目录失败: {}", e);
+            HashMap::new()
+        });
+        let mut exact = HashMap::new();
+        let mut templates = Vec::new();
+        for (source, translation) in entries {
+            if !placeholder().is_match(&source) {
+                exact.insert(source, translation);
+                continue;
+            }
+            let literals: Vec<&str> = placeholder().split(&source).collect();
+            let pattern = literals
+                .iter()
+                .map(|s| regex::escape(s))
+                .collect::<Vec<_>>()
+                .join("(.*?)");
+            match Regex::new(&format!("(?s)^{}$", pattern)) {
+                Ok(pattern) => templates.push((literals.concat().chars().count(), Template { pattern, translation })),
+                Err(e) => eprintln!("[Tauri] ⚠️ 语言目录条目无效 {}: {}", source, e),
+            }
+        }
+        // 固定文字越多的模板越具体，优先匹配（"读取文件失败: {}" 先于 "{}失败: {}"）
+        templates.sort_by_key(|(literal_chars, _)| std::cmp::Reverse(*literal_chars));
+        Catalog {
+            exact,
+            templates: templates.into_iter().map(|(_, t)| t).collect(),
+        }
+    }
+
+    fn translate(&self, text: &str) -> String {
+        if let Some(translation) = self.exact.get(text) {
+            return translation.clone();
+        }
+        for template in &self.templates {
+            let Some(captures) = template.pattern.captures(text) else {
+                continue;
+            };
+            // 参数总比原文短，递归必然结束
+            let args: Vec<String> = captures
+                .iter()
+                .skip(1)
+                .map(|m| self.translate(m.map_or("", |m| m.as_str())))
+                .collect();
+            let mut next = 0;
+            return placeholder()
+                .replace_all(&template.translation, |caps: &regex::Captures| {
+                    let index = caps[0][1..caps[0].len() - 1].parse().unwrap_or_else(|_| {
+                        next += 1;
+                        next - 1
+                    });
+                    args.get(index).cloned().unwrap_or_default()
+                })
+                .into_owned();
+        }
+        text.to_string()
+    }
+}
+
+fn catalog(locale: Locale) -> Option<&'static Catalog> {
+    static EN_US: OnceLock<Catalog> = OnceLock::new();
+    match locale {
+        Locale::ZhCn => None,
+        Locale::EnUs => Some(EN_US.get_or_init(|| Catalog::parse(EN_US_CATALOG))),
+    }
+}
+
+/// 当前语言
+pub fn current() -> Locale {
+    CURRENT.read().map(|l| *l).unwrap_or_default()
+}
+
+/// 把中文原文翻译为当前语言
+pub fn tr(text: &str) -> String {
+    match catalog(current()) {
+        Some(catalog) => catalog.translate(text),
+        None => text.to_string(),
+    }
+}
+
+/// 启动时读取配置中的语言（在创建托盘之前调用）
+pub fn init() {
+    let locale = config::load_config().ok().and_then(|c| c.locale).unwrap_or_default();
+    if let Ok(mut current) = CURRENT.write() {
+        *current = locale;
+    }
+}
+
+/// 切换语言：刷新托盘文字并通知前端
+pub fn apply(app: &AppHandle, locale: Locale) {
+    {
+        let Ok(mut current) = CURRENT.write() else {
+            return;
+        };
+        if *current == locale {
+            return;
+        }
+        *current = locale;
+    }
+    eprintln!("[Tauri] 🌐 界面语言已切换为 {:?}", locale);
+    tray::refresh_locale(app);
+    let _ = app.emit("locale-changed", locale);
+}
+
+/// 保存并立即切换界面语言
+#[tauri::command]
+pub async fn set_locale(app: AppHandle, locale: Locale) -> Result<(), String> {
+    let mut config = config::load_config()?;
+    config.locale = Some(locale);
+    config::write_config(&config)?;
+    apply(&app, locale);
+    Ok(())
+}
+
+/// 把 Rust 侧返回的消息翻译为当前语言（前端收到命令错误时调用）
+#[tauri::command]
+pub async fn localize_message(message: String) -> Result<String, String> {
+    Ok(tr(&message))
+}
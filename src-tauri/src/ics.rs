@@ -0,0 +1,446 @@
+//! iCalendar（RFC 5545）的最小实现：解析 VEVENT、展开常见的重复规则、生成新日程
+//!
+//! 只处理日程需要的属性（UID、SUMMARY、DTSTART、DTEND / DURATION、LOCATION、DESCRIPTION、RRULE、
+//! EXDATE、RECURRENCE-ID、STATUS）。带 TZID 的时间按 IANA 时区换算，无法识别的时区按本地时间处理。
+//! 重复规则支持 DAILY / WEEKLY（可带 BYDAY）/ MONTHLY / YEARLY 及 INTERVAL、COUNT、UNTIL，
+//! 其他规则只取第一次。
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+
+/// 展开重复日程时最多推算的周期数，防止没有 UNTIL / COUNT 的规则无限循环
+const MAX_PERIODS: u32 = 20000;
+
+/// 生成的每行最多字节数，超出时折行
+const MAX_LINE_BYTES: usize = 75;
+
+/// 一个日程（重复日程展开后是其中的一次）
+#[derive(Debug, Clone, Default)]
+pub struct VEvent {
+    pub uid: String,
+    pub summary: String,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub all_day: bool,
+    pub rrule: Option<String>,
+    pub exdates: Vec<DateTime<Local>>,
+    /// 单独修改过的某一次所替换的原开始时间
+    pub recurrence_id: Option<DateTime<Local>>,
+    pub cancelled: bool,
+}
+
+/// 一行属性：NAME;PARAM=VALUE:VALUE
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, key: &str) -> Option<&str> {
+        self.params.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    fn is_date(&self) -> bool {
+        self.param("VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE"))
+    }
+}
+
+/// 展开折行（以空格或制表符开头的行接在上一行后面）
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ if !line.is_empty() => lines.push(line.to_string()),
+            _ => {}
+        }
+    }
+    lines
+}
+
+fn parse_property(line: &str) -> Option<Property> {
+    // 参数值可能带引号，引号中的冒号不是分隔符
+    let mut in_quotes = false;
+    let (colon, _) = line.char_indices().find(|&(_, c)| {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        c == ':' && !in_quotes
+    })?;
+    let mut head = line[..colon].split(';');
+    let name = head.next()?.trim().to_ascii_uppercase();
+    let params = head
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.trim().to_ascii_uppercase(), v.trim_matches('"').to_string()))
+        .collect();
+    Some(Property {
+        name,
+        params,
+        value: line[colon + 1..].to_string(),
+    })
+}
+
+/// TEXT 值的反转义
+fn unescape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out.trim().to_string()
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// 本地时间（夏令时切换时取较早的一个，不存在的时间返回 None）
+pub fn local_from_naive(naive: NaiveDateTime) -> Option<DateTime<Local>> {
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// 解析 DATE / DATE-TIME 值，返回本地时间和是否为全天
+fn parse_time_value(value: &str, tzid: Option<&str>, date_only: bool) -> Option<(DateTime<Local>, bool)> {
+    let value = value.trim();
+    if date_only || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((local_from_naive(date.and_hms_opt(0, 0, 0)?)?, true));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((Utc.from_utc_datetime(&naive).with_timezone(&Local), false));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let time = match tzid.and_then(|t| t.trim_start_matches('/').parse::<chrono_tz::Tz>().ok()) {
+        Some(tz) => tz.from_local_datetime(&naive).earliest()?.with_timezone(&Local),
+        None => local_from_naive(naive)?,
+    };
+    Some((time, false))
+}
+
+fn parse_time(prop: &Property) -> Option<(DateTime<Local>, bool)> {
+    parse_time_value(&prop.value, prop.param("TZID"), prop.is_date())
+}
+
+/// 解析 DURATION，如 PT1H30M、P1D、-PT15M
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (negative, rest) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in rest.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match c {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    _ => Duration::seconds(n),
+                };
+            }
+            _ => return None,
+        }
+    }
+    Some(if negative { -total } else { total })
+}
+
+fn build_event(props: &[Property]) -> Option<VEvent> {
+    let get = |name: &str| props.iter().find(|p| p.name == name);
+    let text = |name: &str| get(name).map(|p| unescape_text(&p.value)).filter(|s| !s.is_empty());
+    let (start, all_day) = parse_time(get("DTSTART")?)?;
+    let end = match (
+        get("DTEND").and_then(parse_time),
+        get("DURATION").and_then(|p| parse_duration(&p.value)),
+    ) {
+        (Some((end, _)), _) => end,
+        (None, Some(duration)) => start + duration,
+        (None, None) if all_day => start + Duration::days(1),
+        (None, None) => start,
+    };
+    Some(VEvent {
+        uid: get("UID").map(|p| p.value.trim().to_string()).unwrap_or_default(),
+        summary: text("SUMMARY").unwrap_or_default(),
+        location: text("LOCATION"),
+        description: text("DESCRIPTION"),
+        start,
+        end: end.max(start),
+        all_day,
+        rrule: get("RRULE").map(|p| p.value.trim().to_ascii_uppercase()),
+        exdates: props
+            .iter()
+            .filter(|p| p.name == "EXDATE")
+            .flat_map(|p| {
+                p.value
+                    .split(',')
+                    .filter_map(|v| parse_time_value(v, p.param("TZID"), p.is_date()))
+                    .map(|(t, _)| t)
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+        recurrence_id: get("RECURRENCE-ID").and_then(parse_time).map(|(t, _)| t),
+        cancelled: get("STATUS").is_some_and(|p| p.value.trim().eq_ignore_ascii_case("CANCELLED")),
+    })
+}
+
+/// 解析 iCalendar 文本中的所有 VEVENT（忽略其中嵌套的 VALARM 等组件）
+pub fn parse(text: &str) -> Vec<VEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+    let mut nested = 0usize;
+    for line in unfold(text) {
+        let Some(prop) = parse_property(&line) else {
+            continue;
+        };
+        let component = prop.value.trim().to_ascii_uppercase();
+        match prop.name.as_str() {
+            "BEGIN" if component == "VEVENT" => {
+                current = Some(Vec::new());
+                nested = 0;
+            }
+            "END" if component == "VEVENT" => {
+                if let Some(event) = current.take().and_then(|props| build_event(&props)) {
+                    events.push(event);
+                }
+            }
+            "BEGIN" if current.is_some() => nested += 1,
+            "END" if current.is_some() => nested = nested.saturating_sub(1),
+            _ if nested == 0 => {
+                if let Some(props) = current.as_mut() {
+                    props.push(prop);
+                }
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+struct Rule {
+    freq: Freq,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<DateTime<Local>>,
+    by_day: Vec<Weekday>,
+}
+
+/// 解析 RRULE，不支持的规则返回 None（只取第一次）
+fn parse_rule(rrule: &str) -> Option<Rule> {
+    let mut freq = None;
+    let mut rule = Rule {
+        freq: Freq::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+    };
+    for (key, value) in rrule.split(';').filter_map(|part| part.split_once('=')) {
+        match key {
+            "FREQ" => {
+                freq = Some(match value {
+                    "DAILY" => Freq::Daily,
+                    "WEEKLY" => Freq::Weekly,
+                    "MONTHLY" => Freq::Monthly,
+                    "YEARLY" => Freq::Yearly,
+                    _ => return None,
+                })
+            }
+            "INTERVAL" => rule.interval = value.parse().ok().filter(|&n| n > 0)?,
+            "COUNT" => rule.count = Some(value.parse().ok()?),
+            "UNTIL" => rule.until = Some(parse_time_value(value, None, false)?.0),
+            "BYDAY" => {
+                // 只支持不带序号的星期（如 MO,WE,FR），"1MO" 这类按月序号的规则不支持
+                for day in value.split(',') {
+                    rule.by_day.push(match day {
+                        "MO" => Weekday::Mon,
+                        "TU" => Weekday::Tue,
+                        "WE" => Weekday::Wed,
+                        "TH" => Weekday::Thu,
+                        "FR" => Weekday::Fri,
+                        "SA" => Weekday::Sat,
+                        "SU" => Weekday::Sun,
+                        _ => return None,
+                    });
+                }
+            }
+            "WKST" => {}
+            _ => return None,
+        }
+    }
+    rule.freq = freq?;
+    if !rule.by_day.is_empty() && matches!(rule.freq, Freq::Monthly | Freq::Yearly) {
+        return None;
+    }
+    Some(rule)
+}
+
+/// 重复日程在 to 之前的每次开始时间，只保留结束时间晚于 after 的
+fn occurrence_starts(event: &VEvent, rule: &Rule, after: DateTime<Local>, to: DateTime<Local>) -> Vec<DateTime<Local>> {
+    let base = event.start.naive_local();
+    let duration = event.end - event.start;
+    let mut starts = Vec::new();
+    let mut produced = 0usize;
+    for period in 0..MAX_PERIODS {
+        let step = period.saturating_mul(rule.interval);
+        let anchor = match rule.freq {
+            Freq::Daily => base.checked_add_signed(Duration::days(i64::from(step))),
+            Freq::Weekly => base.checked_add_signed(Duration::weeks(i64::from(step))),
+            Freq::Monthly => base.checked_add_months(Months::new(step)),
+            Freq::Yearly => base.checked_add_months(Months::new(step.saturating_mul(12))),
+        };
+        let Some(anchor) = anchor else {
+            break;
+        };
+        let candidates: Vec<NaiveDateTime> = match rule.freq {
+            Freq::Weekly if !rule.by_day.is_empty() => {
+                let monday = anchor - Duration::days(i64::from(anchor.weekday().num_days_from_monday()));
+                let mut days: Vec<_> = rule
+                    .by_day
+                    .iter()
+                    .map(|d| monday + Duration::days(i64::from(d.num_days_from_monday())))
+                    .filter(|d| *d >= base)
+                    .collect();
+                days.sort();
+                days
+            }
+            Freq::Daily if !rule.by_day.is_empty() && !rule.by_day.contains(&anchor.weekday()) => Vec::new(),
+            // 按月 / 按年重复时，没有该日期的月份（如 31 日）跳过
+            Freq::Monthly | Freq::Yearly if anchor.day() != base.day() => Vec::new(),
+            _ => vec![anchor],
+        };
+        for naive in candidates {
+            let Some(start) = local_from_naive(naive) else {
+                continue;
+            };
+            if start >= to || rule.until.is_some_and(|u| start > u) || rule.count.is_some_and(|c| produced >= c) {
+                return starts;
+            }
+            produced += 1;
+            if start + duration > after || start >= after {
+                starts.push(start);
+            }
+        }
+    }
+    starts
+}
+
+/// 与 [from, to) 有重叠的每一次日程（重复日程逐次展开），按开始时间排序
+pub fn expand(events: &[VEvent], from: DateTime<Local>, to: DateTime<Local>) -> Vec<VEvent> {
+    // 单独修改过的某一次（RECURRENCE-ID）替换重复日程中对应的那一次
+    let overridden: HashSet<(&str, DateTime<Local>)> = events
+        .iter()
+        .filter_map(|e| e.recurrence_id.map(|r| (e.uid.as_str(), r)))
+        .collect();
+    let mut result = Vec::new();
+    for event in events.iter().filter(|e| !e.cancelled) {
+        let duration = event.end - event.start;
+        let rule = event.rrule.as_deref().and_then(parse_rule);
+        let starts = match (&rule, event.recurrence_id) {
+            (Some(rule), None) => occurrence_starts(event, rule, from, to),
+            _ => vec![event.start],
+        };
+        for start in starts {
+            let is_master = event.recurrence_id.is_none();
+            if is_master && (event.exdates.contains(&start) || overridden.contains(&(event.uid.as_str(), start))) {
+                continue;
+            }
+            let end = start + duration;
+            if start < to && (end > from || start >= from) {
+                result.push(VEvent {
+                    start,
+                    end,
+                    rrule: None,
+                    ..event.clone()
+                });
+            }
+        }
+    }
+    result.sort_by_key(|e| e.start);
+    result
+}
+
+/// 超过 75 字节的行折成多行（在字符边界处断开）
+fn fold(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_BYTES {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn format_time(time: DateTime<Local>, all_day: bool) -> String {
+    if all_day {
+        format!(";VALUE=DATE:{}", time.format("%Y%m%d"))
+    } else {
+        format!(":{}", time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ"))
+    }
+}
+
+/// 生成单个 VEVENT 组件（时间以 UTC 写入，全天日程写日期）
+pub fn to_vevent(event: &VEvent) -> String {
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", event.uid),
+        format!("DTSTAMP:{}", Utc::now().format("%Y%m%dT%H%M%SZ")),
+        format!("DTSTART{}", format_time(event.start, event.all_day)),
+        format!("DTEND{}", format_time(event.end, event.all_day)),
+        format!("SUMMARY:{}", escape_text(&event.summary)),
+    ];
+    if let Some(location) = &event.location {
+        lines.push(format!("LOCATION:{}", escape_text(location)));
+    }
+    if let Some(description) = &event.description {
+        lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+    }
+    lines.push("END:VEVENT".to_string());
+    let mut out = String::new();
+    for line in &lines {
+        fold(line, &mut out);
+    }
+    out
+}
+
+/// 用 VCALENDAR 包裹 VEVENT，生成完整的日历文件
+pub fn to_calendar(vevents: &str) -> String {
+    format!(
+        "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//DeskJarvis//Calendar//CN\r\nCALSCALE:GREGORIAN\r\n{}END:VCALENDAR\r\n",
+        vevents
+    )
+}
@@ -0,0 +1,486 @@
+//! IMAP 收信：「总结今天的未读邮件」这类任务需要读取收件箱，由 Rust 连接 IMAP 服务器取回邮件
+//!
+//! 服务器和端口取自 email_imap_server / email_imap_port（未设置时根据 SMTP 服务器推断，端口 993），
+//! 登录方式与发信相同（密码 / 授权码或 OAuth2 XOAUTH2）。以只读方式（EXAMINE + BODY.PEEK）
+//! 打开邮箱，不会把邮件标为已读。前端通过 fetch_recent_emails 命令、Agent 通过 email.fetch
+//! 工具调用获取邮件列表和正文，单次数量、每封读取的字节数和正文长度都有上限。
+
+use std::collections::HashMap;
+
+use base64::Engine;
+use mail_parser::{MessageParser, MimeHeaders};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, AppConfig};
+use crate::email_oauth;
+use crate::mail_conn::{Credential, MailConnection, MailError};
+
+/// 使用 SSL（而不是 STARTTLS）的 IMAP 端口
+const IMAPS_PORT: u16 = 993;
+
+/// 默认返回的邮件数
+const DEFAULT_LIMIT: usize = 20;
+
+/// 单次最多返回的邮件数
+const MAX_LIMIT: usize = 50;
+
+/// 按非 ASCII 关键词在本地过滤时，最多检查最近的邮件数
+const SCAN_LIMIT: usize = 200;
+
+/// 每封邮件最多读取的字节数（超出部分的正文和附件不读取）
+const MAX_FETCH_BYTES: usize = 256 * 1024;
+
+/// 单个字面量的上限，防止异常服务器返回超大数据
+const MAX_LITERAL_BYTES: usize = 1024 * 1024;
+
+/// 正文默认保留的字符数
+const DEFAULT_BODY_CHARS: usize = 2000;
+
+/// 正文最多保留的字符数
+const MAX_BODY_CHARS: usize = 20000;
+
+/// 邮件筛选条件，字段都可省略
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EmailFilter {
+    /// 邮箱文件夹，默认 INBOX
+    pub folder: Option<String>,
+    /// 只取未读邮件
+    pub unseen_only: bool,
+    /// 只取最近几天的邮件（0 表示今天）
+    pub since_days: Option<u32>,
+    /// 发件人包含的关键词
+    pub from: Option<String>,
+    /// 主题包含的关键词
+    pub subject: Option<String>,
+    /// 返回的邮件数，默认 20，最多 50
+    pub limit: Option<usize>,
+    /// 是否读取正文
+    pub include_body: bool,
+    /// 每封正文保留的字符数，默认 2000，最多 20000
+    pub max_body_chars: Option<usize>,
+}
+
+/// 一封邮件
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailMessage {
+    pub uid: u32,
+    pub subject: String,
+    /// 发件人邮箱
+    pub from: String,
+    /// 发件人名称
+    pub from_name: Option<String>,
+    pub to: Vec<String>,
+    /// 发送时间（RFC 3339）
+    pub date: Option<String>,
+    pub seen: bool,
+    /// 邮件大小（字节）
+    pub size: u64,
+    /// 附件文件名（只在读取正文时填写，超出读取上限的附件不列出）
+    pub attachments: Vec<String>,
+    /// 纯文本正文（HTML 邮件转为文本），未读取正文时为 None
+    pub body: Option<String>,
+    /// 正文因长度上限被截断
+    pub body_truncated: bool,
+}
+
+/// fetch_recent_emails 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FetchedEmails {
+    pub folder: String,
+    /// 符合条件的邮件总数（按非 ASCII 关键词过滤时只统计最近检查过的邮件）
+    pub total: usize,
+    /// 按时间从新到旧排列
+    pub emails: Vec<EmailMessage>,
+}
+
+/// 一条未加标签的响应，字面量按出现顺序单独保存
+struct Untagged {
+    text: String,
+    literals: Vec<Vec<u8>>,
+}
+
+/// 已登录的 IMAP 会话
+struct Session {
+    conn: MailConnection,
+    next_tag: u32,
+}
+
+impl Session {
+    /// 读取一行响应，行尾为 {n} 时继续读取字面量和剩余部分
+    async fn read_response(&mut self, stage: &str) -> Result<Untagged, MailError> {
+        let mut text = String::new();
+        let mut literals = Vec::new();
+        loop {
+            let line = self.conn.read_line(stage).await?;
+            let literal_len = line
+                .strip_suffix('}')
+                .and_then(|l| l.rsplit_once('{'))
+                .and_then(|(_, n)| n.parse::<usize>().ok());
+            text.push_str(&line);
+            match literal_len {
+                Some(len) if len > MAX_LITERAL_BYTES => {
+                    return Err(MailError::Protocol {
+                        message: format!("服务器返回的数据过大（{} 字节）", len),
+                    })
+                }
+                Some(len) => literals.push(self.conn.read_exact(len, stage).await?),
+                None => return Ok(Untagged { text, literals }),
+            }
+        }
+    }
+
+    /// 发送命令，返回未加标签的响应；服务器回复 NO / BAD 时返回错误
+    async fn command(&mut self, command: &str, stage: &str) -> Result<Vec<Untagged>, MailError> {
+        self.next_tag += 1;
+        let tag = format!("A{}", self.next_tag);
+        self.conn.write_line(&format!("{} {}", tag, command)).await?;
+        let mut responses = Vec::new();
+        let mut challenge = String::new();
+        loop {
+            let response = self.read_response(stage).await?;
+            if let Some(status) = response.text.strip_prefix(&format!("{} ", tag)) {
+                if status.starts_with("OK") {
+                    return Ok(responses);
+                }
+                let message = format!("{} {}", status.trim(), challenge).trim().to_string();
+                return Err(if stage == "登录" {
+                    MailError::Auth { message }
+                } else {
+                    MailError::Protocol {
+                        message: format!("{}失败: {}", stage, message),
+                    }
+                });
+            }
+            if let Some(data) = response.text.strip_prefix('+') {
+                // 只有 AUTHENTICATE 会收到继续请求：XOAUTH2 被拒绝时服务器先返回 base64 编码的错误详情，
+                // 回空行后才给出最终结果
+                challenge = base64::engine::general_purpose::STANDARD
+                    .decode(data.trim())
+                    .map(|d| String::from_utf8_lossy(&d).to_string())
+                    .unwrap_or_default();
+                self.conn.write_line("").await?;
+                continue;
+            }
+            responses.push(response);
+        }
+    }
+}
+
+/// IMAP 带引号的字符串
+fn quote(value: &str) -> Result<String, MailError> {
+    if value.contains(['\r', '\n']) {
+        return Err(MailError::Protocol {
+            message: "参数中不能包含换行".to_string(),
+        });
+    }
+    Ok(format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"")))
+}
+
+/// 文件夹名称的修改版 UTF-7 编码（RFC 3501 5.1.3），中文文件夹名需要
+fn encode_folder(name: &str) -> String {
+    let mut out = String::new();
+    let mut pending: Vec<u16> = Vec::new();
+    let flush = |pending: &mut Vec<u16>, out: &mut String| {
+        if pending.is_empty() {
+            return;
+        }
+        let bytes: Vec<u8> = pending.iter().flat_map(|u| u.to_be_bytes()).collect();
+        let encoded = base64::engine::general_purpose::STANDARD_NO_PAD.encode(bytes);
+        out.push('&');
+        out.push_str(&encoded.replace('/', ","));
+        out.push('-');
+        pending.clear();
+    };
+    for c in name.chars() {
+        if (' '..='~').contains(&c) {
+            flush(&mut pending, &mut out);
+            if c == '&' {
+                out.push_str("&-");
+            } else {
+                out.push(c);
+            }
+        } else {
+            pending.extend(c.encode_utf16(&mut [0; 2]).iter());
+        }
+    }
+    flush(&mut pending, &mut out);
+    out
+}
+
+/// 未设置 IMAP 服务器时根据 SMTP 服务器推断（与 Python 端一致）
+fn imap_server(app_config: &AppConfig) -> String {
+    if let Some(server) = app_config.email_imap_server.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        return server.to_string();
+    }
+    let smtp = app_config.email_smtp_server.as_deref().unwrap_or("smtp.gmail.com").trim();
+    match smtp {
+        s if s.contains("smtp.qq.com") => "imap.qq.com".to_string(),
+        s if s.contains("smtp.gmail.com") => "imap.gmail.com".to_string(),
+        s if s.contains("smtp.outlook.com") => "outlook.office365.com".to_string(),
+        s if s.starts_with("smtp.") => s.replacen("smtp.", "imap.", 1),
+        _ => "imap.gmail.com".to_string(),
+    }
+}
+
+/// 连接、加密并登录
+async fn login(host: &str, port: u16, user: &str, credential: &Credential) -> Result<Session, MailError> {
+    let conn = MailConnection::connect(host, port, port == IMAPS_PORT).await?;
+    let mut session = Session { conn, next_tag: 0 };
+    let greeting = session.read_response("等待服务器问候").await?.text;
+    if !greeting.starts_with("* OK") && !greeting.starts_with("* PREAUTH") {
+        return Err(MailError::Protocol {
+            message: format!("服务器拒绝连接: {}", greeting),
+        });
+    }
+    if !session.conn.is_tls() {
+        session.command("STARTTLS", "STARTTLS").await.map_err(|e| MailError::Tls {
+            message: format!("端口 {} 上 STARTTLS 失败（SSL 请使用 {} 端口）: {}", port, IMAPS_PORT, e.message()),
+        })?;
+        session.conn = session.conn.start_tls().await?;
+    }
+    let command = match credential {
+        Credential::Password(password) => format!("LOGIN {} {}", quote(user)?, quote(password)?),
+        Credential::OAuth2(token) => {
+            let payload = format!("user={}\x01auth=Bearer {}\x01\x01", user, token);
+            format!(
+                "AUTHENTICATE XOAUTH2 {}",
+                base64::engine::general_purpose::STANDARD.encode(payload)
+            )
+        }
+    };
+    session.command(&command, "登录").await?;
+    Ok(session)
+}
+
+/// UID SEARCH 条件，非 ASCII 的关键词返回给调用方在本地过滤
+fn search_criteria(filter: &EmailFilter) -> Result<String, MailError> {
+    let mut criteria = Vec::new();
+    if filter.unseen_only {
+        criteria.push("UNSEEN".to_string());
+    }
+    if let Some(days) = filter.since_days {
+        let since = chrono::Local::now().date_naive() - chrono::Duration::days(i64::from(days));
+        criteria.push(format!("SINCE {}", since.format("%d-%b-%Y")));
+    }
+    for (key, value) in [("FROM", &filter.from), ("SUBJECT", &filter.subject)] {
+        if let Some(value) = value.as_deref().filter(|v| !v.is_empty() && v.is_ascii()) {
+            criteria.push(format!("{} {}", key, quote(value)?));
+        }
+    }
+    if criteria.is_empty() {
+        criteria.push("ALL".to_string());
+    }
+    Ok(criteria.join(" "))
+}
+
+/// 本地过滤的关键词（不区分大小写）
+fn local_keywords(filter: &EmailFilter) -> Vec<(bool, String)> {
+    [(true, &filter.from), (false, &filter.subject)]
+        .into_iter()
+        .filter_map(|(is_from, value)| {
+            value
+                .as_deref()
+                .filter(|v| !v.is_empty() && !v.is_ascii())
+                .map(|v| (is_from, v.to_lowercase()))
+        })
+        .collect()
+}
+
+/// 截断到指定字符数
+fn truncate_chars(text: &str, max_chars: usize) -> (String, bool) {
+    match text.char_indices().nth(max_chars) {
+        Some((i, _)) => (text[..i].to_string(), true),
+        None => (text.to_string(), false),
+    }
+}
+
+/// 解析 FETCH 响应中的一封邮件
+fn parse_fetch(response: &Untagged, with_body: bool, max_body_chars: usize) -> Option<EmailMessage> {
+    let number = |pattern: &str| -> Option<u64> {
+        Regex::new(pattern)
+            .ok()?
+            .captures(&response.text)?
+            .get(1)?
+            .as_str()
+            .parse()
+            .ok()
+    };
+    let uid = number(r"\bUID (\d+)")? as u32;
+    let size = number(r"RFC822\.SIZE (\d+)").unwrap_or(0);
+    let seen = Regex::new(r"FLAGS \(([^)]*)\)")
+        .ok()
+        .and_then(|re| re.captures(&response.text))
+        .is_some_and(|c| c[1].split_whitespace().any(|f| f.eq_ignore_ascii_case("\\Seen")));
+    let raw = response.literals.first()?;
+    let message = MessageParser::default().parse(raw.as_slice())?;
+
+    let sender = message.from().and_then(|a| a.first());
+    let (body, body_truncated) = if with_body {
+        let text = message.body_text(0).map(|t| t.trim().to_string()).unwrap_or_default();
+        let (text, truncated) = truncate_chars(&text, max_body_chars);
+        (Some(text), truncated || raw.len() >= MAX_FETCH_BYTES)
+    } else {
+        (None, false)
+    };
+    Some(EmailMessage {
+        uid,
+        subject: message.subject().unwrap_or("").to_string(),
+        from: sender.and_then(|a| a.address()).unwrap_or("").to_string(),
+        from_name: sender.and_then(|a| a.name()).map(str::to_string),
+        to: message
+            .to()
+            .map(|to| to.iter().filter_map(|a| a.address()).map(str::to_string).collect())
+            .unwrap_or_default(),
+        date: message.date().map(|d| d.to_rfc3339()),
+        seen,
+        size,
+        attachments: if with_body {
+            message.attachments().filter_map(|a| a.attachment_name()).map(str::to_string).collect()
+        } else {
+            Vec::new()
+        },
+        body,
+        body_truncated,
+    })
+}
+
+/// UID FETCH 邮件头或正文（正文只读取前 MAX_FETCH_BYTES 字节），按 UID 返回
+async fn fetch_messages(
+    session: &mut Session,
+    uids: &[u32],
+    with_body: bool,
+    max_body_chars: usize,
+) -> Result<HashMap<u32, EmailMessage>, MailError> {
+    if uids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let set = uids.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+    let item = if with_body {
+        format!("BODY.PEEK[]<0.{}>", MAX_FETCH_BYTES)
+    } else {
+        "BODY.PEEK[HEADER]".to_string()
+    };
+    let responses = session
+        .command(&format!("UID FETCH {} (UID RFC822.SIZE FLAGS {})", set, item), "读取邮件")
+        .await?;
+    Ok(responses
+        .iter()
+        .filter(|r| r.text.contains(" FETCH "))
+        .filter_map(|r| parse_fetch(r, with_body, max_body_chars))
+        .map(|m| (m.uid, m))
+        .collect())
+}
+
+/// 按条件取回邮件
+async fn fetch(
+    session: &mut Session,
+    folder: &str,
+    filter: &EmailFilter,
+) -> Result<FetchedEmails, MailError> {
+    let limit = filter.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let max_body_chars = filter.max_body_chars.unwrap_or(DEFAULT_BODY_CHARS).clamp(1, MAX_BODY_CHARS);
+    session
+        .command(&format!("EXAMINE {}", quote(&encode_folder(folder))?), "打开邮箱")
+        .await?;
+    let responses = session
+        .command(&format!("UID SEARCH {}", search_criteria(filter)?), "搜索邮件")
+        .await?;
+    let mut uids: Vec<u32> = responses
+        .iter()
+        .filter_map(|r| r.text.strip_prefix("* SEARCH"))
+        .flat_map(|ids| ids.split_whitespace().filter_map(|id| id.parse().ok()))
+        .collect();
+    uids.sort_unstable();
+
+    // UID 递增，最新的邮件在最后
+    let keywords = local_keywords(filter);
+    let (total, selected) = if keywords.is_empty() {
+        (uids.len(), uids[uids.len().saturating_sub(limit)..].to_vec())
+    } else {
+        let scanned = &uids[uids.len().saturating_sub(SCAN_LIMIT)..];
+        let headers = fetch_messages(session, scanned, false, max_body_chars).await?;
+        let matched: Vec<u32> = scanned
+            .iter()
+            .copied()
+            .filter(|uid| {
+                headers.get(uid).is_some_and(|m| {
+                    keywords.iter().all(|(is_from, keyword)| {
+                        let field = if *is_from {
+                            format!("{} {}", m.from_name.as_deref().unwrap_or(""), m.from)
+                        } else {
+                            m.subject.clone()
+                        };
+                        field.to_lowercase().contains(keyword)
+                    })
+                })
+            })
+            .collect();
+        (matched.len(), matched[matched.len().saturating_sub(limit)..].to_vec())
+    };
+
+    let mut messages = fetch_messages(session, &selected, filter.include_body, max_body_chars).await?;
+    Ok(FetchedEmails {
+        folder: folder.to_string(),
+        total,
+        emails: selected.iter().rev().filter_map(|uid| messages.remove(uid)).collect(),
+    })
+}
+
+/// 用当前配置登录 IMAP 并按条件取回邮件
+pub async fn fetch_emails(filter: &EmailFilter) -> Result<FetchedEmails, String> {
+    let app_config = config::load_config()?;
+    let host = imap_server(&app_config);
+    let port = u16::try_from(app_config.email_imap_port.unwrap_or(i32::from(IMAPS_PORT)))
+        .map_err(|_| "IMAP 端口超出范围".to_string())?;
+    let user = app_config
+        .email_sender
+        .clone()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or("未设置邮箱账号")?;
+    let credential = if email_oauth::uses_oauth(&app_config) {
+        Credential::OAuth2(email_oauth::credential(&app_config).await?.access_token)
+    } else {
+        Credential::Password(
+            app_config
+                .email_password
+                .clone()
+                .filter(|p| !p.is_empty())
+                .ok_or("未设置邮箱密码/授权码")?,
+        )
+    };
+    let folder = filter
+        .folder
+        .as_deref()
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .unwrap_or("INBOX");
+
+    let mut session = login(&host, port, &user, &credential).await.map_err(|e| {
+        eprintln!("[Tauri] ⚠️ 连接 IMAP 失败: {}:{}: {}", host, port, e.message());
+        e.message()
+    })?;
+    let result = fetch(&mut session, folder, filter).await;
+    let _ = session.command("LOGOUT", "退出").await;
+    let fetched = result.map_err(|e| {
+        eprintln!("[Tauri] ⚠️ 读取邮件失败: {}", e.message());
+        e.message()
+    })?;
+    eprintln!("[Tauri] 📬 已读取 {} 封邮件（{}，共 {} 封符合条件）", fetched.emails.len(), folder, fetched.total);
+    Ok(fetched)
+}
+
+/// email.fetch 工具调用，args 即筛选条件
+pub fn call(args: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let filter: EmailFilter = serde_json::from_value(args.clone()).map_err(|e| format!("参数格式错误: {}", e))?;
+    let fetched = tauri::async_runtime::block_on(fetch_emails(&filter))?;
+    serde_json::to_value(fetched).map_err(|e| format!("序列化邮件失败: {}", e))
+}
+
+/// 按条件读取最近的邮件（只读，不会标为已读）
+#[tauri::command]
+pub async fn fetch_recent_emails(filter: Option<EmailFilter>) -> Result<FetchedEmails, String> {
+    fetch_emails(&filter.unwrap_or_default()).await
+}
@@ -0,0 +1,209 @@
+//! 单实例：第二次启动时聚焦已运行的窗口，并把命令行中的指令转交给它执行
+//!
+//! 首个实例监听本机固定端口；后续实例连接该端口发送一行 JSON 后退出。
+//! 端口被其他程序占用且握手失败时，不做单实例限制，照常启动。
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::history::new_task_id;
+use crate::{deep_link, window_manager};
+
+/// 单实例通信端口（仅监听 127.0.0.1）
+const INSTANCE_PORT: u16 = 47821;
+
+/// 覆盖通信端口的环境变量：测试实例设为其他端口，即可与日常实例同时运行
+const PORT_ENV: &str = "DESKJARVIS_INSTANCE_PORT";
+
+/// 握手标识，避免误连其他占用该端口的程序
+const APP_ID: &str = "deskjarvis";
+
+/// 连接与读写超时
+const IO_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 第二个实例发给首个实例的消息
+#[derive(Debug, Serialize, Deserialize)]
+struct ForwardMessage {
+    app: String,
+    args: Vec<String>,
+}
+
+/// 推送给前端的转交指令事件
+#[derive(Debug, Clone, Serialize)]
+struct ForwardedInstruction {
+    task_id: String,
+    instruction: String,
+}
+
+/// 启动时的实例角色
+pub enum InstanceRole {
+    /// 首个实例，持有监听端口
+    Primary(TcpListener),
+    /// 已有实例在运行，参数已转交
+    Secondary,
+    /// 无法确定（端口被占用但不是 DeskJarvis），照常启动
+    Standalone,
+}
+
+fn instance_port() -> u16 {
+    std::env::var(PORT_ENV)
+        .ok()
+        .and_then(|p| p.trim().parse().ok())
+        .unwrap_or(INSTANCE_PORT)
+}
+
+fn instance_addr() -> SocketAddr {
+    SocketAddr::from((Ipv4Addr::LOCALHOST, instance_port()))
+}
+
+/// 从命令行参数中取出指令：除选项和 deskjarvis:// 链接外的参数以空格拼接
+pub fn instruction_from_args(args: &[String]) -> Option<String> {
+    let words: Vec<&str> = args
+        .iter()
+        .map(|a| a.trim())
+        .filter(|a| !a.is_empty() && !a.starts_with('-') && !deep_link::is_deep_link(a))
+        .collect();
+    if words.is_empty() {
+        None
+    } else {
+        Some(words.join(" "))
+    }
+}
+
+/// 向已运行的实例发送参数，对方确认后返回 true
+fn forward_to_running(args: &[String]) -> Result<bool, String> {
+    let mut stream = TcpStream::connect_timeout(&instance_addr(), IO_TIMEOUT)
+        .map_err(|e| format!("连接已运行实例失败: {}", e))?;
+    stream.set_read_timeout(Some(IO_TIMEOUT)).ok();
+    stream.set_write_timeout(Some(IO_TIMEOUT)).ok();
+
+    let message = ForwardMessage {
+        app: APP_ID.to_string(),
+        args: args.to_vec(),
+    };
+    let line = serde_json::to_string(&message)
+        .map_err(|e| format!("序列化转交参数失败: {}", e))?;
+    writeln!(stream, "{}", line).map_err(|e| format!("发送转交参数失败: {}", e))?;
+
+    let mut reply = String::new();
+    BufReader::new(stream)
+        .read_line(&mut reply)
+        .map_err(|e| format!("读取实例应答失败: {}", e))?;
+    Ok(reply.trim() == APP_ID)
+}
+
+/// 确定本进程的实例角色（在创建窗口之前调用）
+pub fn acquire(args: &[String]) -> InstanceRole {
+    match TcpListener::bind(instance_addr()) {
+        Ok(listener) => InstanceRole::Primary(listener),
+        Err(_) => match forward_to_running(args) {
+            Ok(true) => InstanceRole::Secondary,
+            Ok(false) => {
+                eprintln!("[Tauri] ⚠️ 端口 {} 被其他程序占用，跳过单实例检查", instance_port());
+                InstanceRole::Standalone
+            }
+            Err(e) => {
+                eprintln!("[Tauri] ⚠️ {}，跳过单实例检查", e);
+                InstanceRole::Standalone
+            }
+        },
+    }
+}
+
+/// 读取一条转交消息并应答
+fn read_message(stream: TcpStream) -> Result<ForwardMessage, String> {
+    stream.set_read_timeout(Some(IO_TIMEOUT)).ok();
+    stream.set_write_timeout(Some(IO_TIMEOUT)).ok();
+
+    let mut reader = BufReader::new(
+        stream
+            .try_clone()
+            .map_err(|e| format!("读取连接失败: {}", e))?,
+    );
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .map_err(|e| format!("读取转交参数失败: {}", e))?;
+    let message: ForwardMessage = serde_json::from_str(line.trim())
+        .map_err(|e| format!("解析转交参数失败: {}", e))?;
+    if message.app != APP_ID {
+        return Err("未知来源的连接".to_string());
+    }
+
+    let mut stream = stream;
+    let _ = writeln!(stream, "{}", APP_ID);
+    Ok(message)
+}
+
+/// 处理第二个实例转交来的参数：聚焦主窗口，有指令时在后台执行
+fn handle_forward(app: &AppHandle, message: ForwardMessage) {
+    eprintln!("[Tauri] 🔁 收到第二个实例的启动请求");
+    if let Err(e) = window_manager::focus_main_window(app) {
+        eprintln!("[Tauri] ⚠️ {}", e);
+    }
+
+    // Windows / Linux 上点击链接会以链接为参数启动新进程
+    let links: Vec<&String> = message.args.iter().filter(|a| deep_link::is_deep_link(a)).collect();
+    if !links.is_empty() {
+        for link in links {
+            deep_link::handle_url(app, link);
+        }
+        return;
+    }
+
+    let Some(instruction) = instruction_from_args(&message.args) else {
+        return;
+    };
+    let Some(window) = window_manager::main_window(app) else {
+        eprintln!("[Tauri] ⚠️ 主窗口不存在，无法执行转交的指令");
+        return;
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let task_id = new_task_id();
+        let _ = app.emit(
+            "forwarded-instruction",
+            ForwardedInstruction {
+                task_id: task_id.clone(),
+                instruction: instruction.clone(),
+            },
+        );
+        let state = app.state::<crate::AppState>();
+        if let Err(e) =
+            crate::run_tracked_task(
+                &window,
+                &state,
+                task_id,
+                instruction,
+                None,
+                crate::TaskOptions::default(),
+            )
+            .await
+        {
+            eprintln!("[Tauri] ⚠️ 执行转交的指令失败: {}", e);
+        }
+    });
+}
+
+/// 在后台线程中接收后续实例的连接
+pub fn spawn_listener(app: AppHandle, listener: TcpListener) {
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("[Tauri] ⚠️ 接收实例连接失败: {}", e);
+                    continue;
+                }
+            };
+            match read_message(stream) {
+                Ok(message) => handle_forward(&app, message),
+                Err(e) => eprintln!("[Tauri] ⚠️ {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,85 @@
+//! 指令语言检测：决定 Agent 回复使用的语言
+//!
+//! 执行任务前检测指令语言，以 response_language 写入任务 context，
+//! Python 规划器据此要求模型用同一语言回复。配置 response_language 为具体语言时
+//! 直接使用该值；为空或 "auto" 时自动检测。
+//! 中英混写很常见（“把 report.pdf 移到 Documents”），汉字数不少于英文单词数时视为中文，
+//! 其余交给 whatlang 判断。短指令（“open chrome”）置信度很低，拉丁字母写成的按英文处理。
+
+use whatlang::Lang;
+
+use crate::config::AppConfig;
+
+/// 配置中表示自动检测的值
+const AUTO: &str = "auto";
+
+/// whatlang 置信度低于该值时不采用其结果
+const MIN_CONFIDENCE: f64 = 0.5;
+
+fn is_han(c: char) -> bool {
+    matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '\u{F900}'..='\u{FAFF}')
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{30FF}')
+}
+
+/// 提示给模型的语言名称
+fn display_name(lang: Lang) -> &'static str {
+    match lang {
+        Lang::Cmn => "中文",
+        Lang::Eng => "English",
+        Lang::Jpn => "日本語",
+        Lang::Kor => "한국어",
+        other => other.eng_name(),
+    }
+}
+
+/// 检测指令语言，无法可靠判断时返回 None
+pub fn detect(instruction: &str) -> Option<&'static str> {
+    let han = instruction.chars().filter(|c| is_han(*c)).count();
+    let has_kana = instruction.chars().any(is_kana);
+    if han > 0 && !has_kana {
+        let latin_words = instruction
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .filter(|w| !w.is_empty())
+            .count();
+        if han >= latin_words {
+            return Some(display_name(Lang::Cmn));
+        }
+    }
+    match whatlang::detect(instruction) {
+        Some(info) if info.confidence() >= MIN_CONFIDENCE => Some(display_name(info.lang())),
+        _ if instruction.chars().any(|c| c.is_ascii_alphabetic())
+            && instruction.chars().all(|c| c.is_ascii() || is_han(c)) =>
+        {
+            Some(display_name(Lang::Eng))
+        }
+        _ => None,
+    }
+}
+
+/// 本次任务的回复语言：配置指定优先，否则按指令检测
+pub fn response_language(instruction: &str, config: Option<&AppConfig>) -> Option<String> {
+    let configured = config
+        .and_then(|c| c.response_language.as_deref())
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.eq_ignore_ascii_case(AUTO));
+    match configured {
+        Some(language) => Some(language.to_string()),
+        None => detect(instruction).map(str::to_string),
+    }
+}
+
+/// 把回复语言写入任务 context（context 不是对象时保持原样）
+pub fn apply_hint(context: Option<serde_json::Value>, language: Option<String>) -> Option<serde_json::Value> {
+    let Some(language) = language else {
+        return context;
+    };
+    let mut context = context.unwrap_or_else(|| serde_json::json!({}));
+    if let Some(map) = context.as_object_mut() {
+        map.entry("response_language")
+            .or_insert(serde_json::Value::String(language));
+    }
+    Some(context)
+}
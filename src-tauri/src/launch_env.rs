@@ -0,0 +1,110 @@
+//! Agent 进程环境变量：全局配置 + 当前档案覆盖，敏感值从系统钥匙串读取
+
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+use crate::{proxy, redaction, secrets};
+
+/// 环境变量条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvVarEntry {
+    /// 变量值；secret 为 true 时保存在系统钥匙串中，此处为空
+    #[serde(default)]
+    pub value: String,
+    #[serde(default)]
+    pub secret: bool,
+}
+
+/// 环境变量表（变量名 → 条目）
+pub type EnvMap = BTreeMap<String, EnvVarEntry>;
+
+/// 钥匙串中环境变量的条目名
+fn secret_key(name: &str) -> String {
+    format!("env:{}", name)
+}
+
+/// 环境变量名只允许字母、数字和下划线，且不能以数字开头
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 将表中带值的敏感条目移入钥匙串并清空明文（保存配置前调用）
+fn extract_secrets(env: &mut EnvMap) -> Result<(), String> {
+    for (name, entry) in env.iter_mut() {
+        if !is_valid_name(name) {
+            return Err(format!("无效的环境变量名: {}", name));
+        }
+        if entry.secret && !entry.value.is_empty() {
+            secrets::set_secret(&secret_key(name), &entry.value)?;
+            entry.value.clear();
+        }
+    }
+    Ok(())
+}
+
+/// 保存配置前处理全局与各档案的环境变量表
+pub fn prepare_for_save(config: &mut AppConfig) -> Result<(), String> {
+    extract_secrets(&mut config.env)?;
+    for profile in config.profiles.iter_mut() {
+        extract_secrets(&mut profile.env)?;
+    }
+    Ok(())
+}
+
+/// 计算启动 Agent 进程时要注入的环境变量（当前档案覆盖全局同名变量，两者都覆盖代理设置）
+pub fn resolve(config: &AppConfig) -> Vec<(String, String)> {
+    let mut merged: EnvMap = proxy::env_vars(config)
+        .into_iter()
+        .map(|(name, value)| (name, EnvVarEntry { value, secret: false }))
+        .collect();
+    merged.extend(config.env.clone());
+    if let Some(profile) = config
+        .active_profile
+        .as_ref()
+        .and_then(|name| config.profiles.iter().find(|p| &p.name == name))
+    {
+        merged.extend(profile.env.clone());
+    }
+
+    merged
+        .into_iter()
+        .filter(|(name, _)| is_valid_name(name))
+        .filter_map(|(name, entry)| {
+            if !entry.secret {
+                return Some((name, entry.value));
+            }
+            match secrets::get_secret(&secret_key(&name)) {
+                Ok(Some(value)) => {
+                    redaction::add_secrets([value.clone()]);
+                    Some((name, value))
+                }
+                Ok(None) => {
+                    eprintln!("[Tauri] ⚠️ 钥匙串中没有环境变量 {} 的值，已跳过", name);
+                    None
+                }
+                Err(e) => {
+                    eprintln!("[Tauri] ⚠️ {}", e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// 将敏感环境变量的值写入系统钥匙串
+#[tauri::command]
+pub async fn set_env_secret(name: String, value: String) -> Result<(), String> {
+    if !is_valid_name(&name) {
+        return Err(format!("无效的环境变量名: {}", name));
+    }
+    secrets::set_secret(&secret_key(&name), &value)
+}
+
+/// 从系统钥匙串删除敏感环境变量
+#[tauri::command]
+pub async fn delete_env_secret(name: String) -> Result<(), String> {
+    secrets::delete_secret(&secret_key(&name))
+}
@@ -0,0 +1,153 @@
+//! 任务卡死检测：任务长时间没有任何事件时提示用户选择继续等待、取消或重启服务
+//!
+//! 每收到一个事件重置计时；超时后先探测服务进程，进程仍在则发送 task-stuck 事件，
+//! 前端通过 respond_stuck_task 回复，执行循环轮询该回复。
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::time::{Duration, Instant};
+
+use crate::config;
+use crate::error::DeskJarvisError;
+use crate::event_sink::EventSink;
+
+/// 默认无事件超时（秒）
+const DEFAULT_STUCK_TIMEOUT_SECS: u64 = 300;
+
+/// 提示后轮询用户回复的间隔
+const DECISION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 用户选择重启时返回的错误标记，调用方据此重启服务并重新执行
+pub const RESTART_REQUESTED: &str = "STUCK_RESTART_REQUESTED";
+
+/// 用户对卡死任务的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StuckAction {
+    /// 继续等待（重新计时）
+    Wait,
+    /// 取消任务
+    Cancel,
+    /// 重启 Python 服务并重新执行
+    Restart,
+}
+
+/// 已提示、等待用户回复的任务（任务 ID → 回复）
+static PROMPTS: Mutex<BTreeMap<String, Option<StuckAction>>> = Mutex::new(BTreeMap::new());
+
+/// task-stuck 事件负载
+#[derive(Debug, Clone, Serialize)]
+struct StuckPayload<'a> {
+    request_id: &'a str,
+    idle_secs: u64,
+    actions: [StuckAction; 3],
+}
+
+/// 单个任务的活跃计时
+pub struct Liveness {
+    task_id: String,
+    /// None 表示关闭检测
+    timeout: Option<Duration>,
+    last_event: Instant,
+    prompted: bool,
+}
+
+impl Liveness {
+    /// 超时时间读取配置 stuck_task_timeout_secs，为 0 时关闭检测
+    pub fn new(task_id: &str) -> Self {
+        let secs = config::load_config()
+            .ok()
+            .and_then(|c| c.stuck_task_timeout_secs)
+            .unwrap_or(DEFAULT_STUCK_TIMEOUT_SECS);
+        Liveness {
+            task_id: task_id.to_string(),
+            timeout: (secs > 0).then(|| Duration::from_secs(secs)),
+            last_event: Instant::now(),
+            prompted: false,
+        }
+    }
+
+    /// 收到事件，重新计时
+    pub fn reset(&mut self) {
+        self.last_event = Instant::now();
+        if self.prompted {
+            self.prompted = false;
+            clear_prompt(&self.task_id);
+        }
+    }
+
+    /// 下一次需要检查的时间
+    pub fn deadline(&self) -> Instant {
+        if self.prompted {
+            return Instant::now() + DECISION_POLL_INTERVAL;
+        }
+        match self.timeout {
+            Some(timeout) => self.last_event + timeout,
+            // 关闭检测时只需一个足够远的时间
+            None => Instant::now() + Duration::from_secs(24 * 60 * 60),
+        }
+    }
+
+    /// 是否已超过无事件时间
+    pub fn is_expired(&self) -> bool {
+        self.timeout
+            .is_some_and(|timeout| self.last_event.elapsed() >= timeout)
+    }
+
+    /// 超时后调用：首次发送提示，之后返回用户的回复（尚未回复时为 None）
+    pub fn poll(&mut self, sink: &impl EventSink) -> Option<StuckAction> {
+        if !self.prompted {
+            self.prompted = true;
+            if let Ok(mut prompts) = PROMPTS.lock() {
+                prompts.insert(self.task_id.clone(), None);
+            }
+            let idle_secs = self.last_event.elapsed().as_secs();
+            eprintln!("[Tauri] ⏳ 任务 {} 已 {} 秒没有任何进度", self.task_id, idle_secs);
+            sink.send(
+                "task-stuck",
+                StuckPayload {
+                    request_id: &self.task_id,
+                    idle_secs,
+                    actions: [StuckAction::Wait, StuckAction::Cancel, StuckAction::Restart],
+                },
+            );
+            return None;
+        }
+
+        let action = PROMPTS
+            .lock()
+            .ok()
+            .and_then(|prompts| prompts.get(&self.task_id).copied().flatten());
+        if action == Some(StuckAction::Wait) {
+            self.reset();
+        }
+        action
+    }
+}
+
+impl Drop for Liveness {
+    fn drop(&mut self) {
+        if self.prompted {
+            clear_prompt(&self.task_id);
+        }
+    }
+}
+
+fn clear_prompt(task_id: &str) {
+    if let Ok(mut prompts) = PROMPTS.lock() {
+        prompts.remove(task_id);
+    }
+}
+
+/// 回复卡死任务的提示
+#[tauri::command]
+pub async fn respond_stuck_task(task_id: String, action: StuckAction) -> Result<(), DeskJarvisError> {
+    let mut prompts = PROMPTS.lock().map_err(|_| "任务状态不可用")?;
+    let decision = prompts.get_mut(&task_id).ok_or_else(|| DeskJarvisError::TaskStateConflict {
+        message: format!("任务 {} 没有待处理的无响应提示", task_id),
+        task_id: task_id.clone(),
+    })?;
+    *decision = Some(action);
+    Ok(())
+}
@@ -0,0 +1,130 @@
+//! 本地模型发现：探测本机的 Ollama 和 LM Studio，列出已安装的模型
+//!
+//! 两者都提供 OpenAI 兼容接口，Agent 以 provider "ollama" / "lmstudio" 通过 base_url 调用，
+//! 不需要 API Key。保存配置时若选择了本地提供商但未填写 API 地址，自动填入默认地址。
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// 探测单个运行时的连接和读取超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 响应体大小上限
+const MAX_RESPONSE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// 本地运行时：(提供商名, 端口, 模型列表路径, OpenAI 兼容接口地址)
+const RUNTIMES: &[(&str, u16, &str, &str)] = &[
+    ("ollama", 11434, "/api/tags", "http://localhost:11434/v1"),
+    ("lmstudio", 1234, "/v1/models", "http://localhost:1234/v1"),
+];
+
+/// 已安装的本地模型
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalModel {
+    pub name: String,
+    /// 模型文件大小（字节），LM Studio 不提供时为空
+    pub size: Option<u64>,
+}
+
+/// 单个本地运行时的探测结果
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalRuntime {
+    /// 作为 provider 保存的名称
+    pub provider: String,
+    /// 保存配置时使用的 base_url
+    pub base_url: String,
+    pub available: bool,
+    pub models: Vec<LocalModel>,
+    pub error: Option<String>,
+}
+
+/// 本地提供商的默认 API 地址，非本地提供商返回 None
+pub fn default_base_url(provider: &str) -> Option<&'static str> {
+    let provider = provider.trim().to_lowercase();
+    RUNTIMES
+        .iter()
+        .find(|(name, _, _, _)| *name == provider)
+        .map(|(_, _, _, base_url)| *base_url)
+}
+
+/// 是否为本地提供商（不需要 API Key）
+pub fn is_local_provider(provider: &str) -> bool {
+    default_base_url(provider).is_some()
+}
+
+/// 向本机端口发起 GET 请求，返回 JSON 响应体（HTTP/1.0，避免分块传输）
+fn get_json(port: u16, path: &str) -> Result<serde_json::Value, String> {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let mut stream = TcpStream::connect_timeout(&addr, PROBE_TIMEOUT).map_err(|e| format!("未运行（{}）", e))?;
+    let _ = stream.set_read_timeout(Some(PROBE_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(PROBE_TIMEOUT));
+    let request = format!("GET {} HTTP/1.0\r\nHost: localhost:{}\r\nAccept: application/json\r\n\r\n", path, port);
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("请求失败: {}", e))?;
+    let mut response = Vec::new();
+    stream
+        .take(MAX_RESPONSE_BYTES)
+        .read_to_end(&mut response)
+        .map_err(|e| format!("读取响应失败: {}", e))?;
+    let response = String::from_utf8_lossy(&response);
+    let (head, body) = response.split_once("\r\n\r\n").ok_or("无效的 HTTP 响应")?;
+    let status = head.split_whitespace().nth(1).unwrap_or("");
+    if status != "200" {
+        return Err(format!("HTTP {}", status));
+    }
+    serde_json::from_str(body).map_err(|e| format!("解析响应失败: {}", e))
+}
+
+/// 从响应中取出模型列表：Ollama 为 models[].{name,size}，LM Studio 为 data[].id
+fn parse_models(provider: &str, body: &serde_json::Value) -> Vec<LocalModel> {
+    let (list, name_key) = if provider == "ollama" { ("models", "name") } else { ("data", "id") };
+    body.get(list)
+        .and_then(|m| m.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|m| {
+            let name = m.get(name_key).and_then(|n| n.as_str())?;
+            Some(LocalModel {
+                name: name.to_string(),
+                size: m.get("size").and_then(|s| s.as_u64()),
+            })
+        })
+        .collect()
+}
+
+/// 探测单个运行时
+fn probe((provider, port, path, base_url): &(&str, u16, &str, &str)) -> LocalRuntime {
+    let result = get_json(*port, path).map(|body| parse_models(provider, &body));
+    let (available, models, error) = match result {
+        Ok(models) => (true, models, None),
+        Err(e) => (false, Vec::new(), Some(e)),
+    };
+    LocalRuntime {
+        provider: provider.to_string(),
+        base_url: base_url.to_string(),
+        available,
+        models,
+        error,
+    }
+}
+
+/// 探测本机的 Ollama（11434 端口）和 LM Studio（1234 端口），列出已安装的模型
+#[tauri::command]
+pub async fn detect_local_models() -> Result<Vec<LocalRuntime>, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let handles: Vec<_> = RUNTIMES
+            .iter()
+            .map(|runtime| std::thread::spawn(move || probe(runtime)))
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|h| h.join().ok())
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("探测本地模型失败: {}", e))
+}
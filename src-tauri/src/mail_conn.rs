@@ -0,0 +1,257 @@
+//! 邮件服务器连接：SMTP 和 IMAP 共用的 TCP / TLS 连接、按行读写和按阶段区分的错误
+//!
+//! 端口 465（SMTP）和 993（IMAP）直接建立 TLS，其他端口先明文连接再由协议升级
+//! （SMTP STARTTLS / IMAP STARTTLS）。证书使用系统信任库校验。失败时返回带 kind 的
+//! 结构化错误（dns / connect / tls / auth / timeout / protocol），前端据此提示具体哪一步出错。
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, ReadBuf};
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::TlsConnector;
+
+/// 解析域名和建立 TCP 连接的超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 等待单条响应的超时
+const IO_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// 单行响应的最大长度，防止异常服务器一直不换行
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+/// 邮件服务器连接错误
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MailError {
+    /// 域名解析失败
+    Dns { host: String, message: String },
+    /// TCP 连接被拒绝或不可达
+    Connect { message: String },
+    /// TLS 握手或证书校验失败，或服务器不支持加密
+    Tls { message: String },
+    /// 用户名、密码或令牌被拒绝
+    Auth { message: String },
+    /// 某一步等待超时
+    Timeout { stage: String },
+    /// 服务器响应不符合协议
+    Protocol { message: String },
+}
+
+impl MailError {
+    pub fn message(&self) -> String {
+        match self {
+            MailError::Dns { host, message } => format!("无法解析服务器地址 {}: {}", host, message),
+            MailError::Connect { message } => format!("无法连接服务器: {}", message),
+            MailError::Tls { message } => format!("加密连接失败: {}", message),
+            MailError::Auth { message } => format!("登录失败: {}", message),
+            MailError::Timeout { stage } => format!("{}超时", stage),
+            MailError::Protocol { message } => format!("服务器响应异常: {}", message),
+        }
+    }
+}
+
+impl From<MailError> for String {
+    fn from(error: MailError) -> String {
+        error.message()
+    }
+}
+
+/// 登录凭据
+pub enum Credential {
+    Password(String),
+    /// XOAUTH2 访问令牌
+    OAuth2(String),
+}
+
+/// 明文或 TLS 连接
+enum Stream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Stream::Plain(s) => Pin::new(s).poll_read(cx, buf),
+            Stream::Tls(s) => Pin::new(s.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            Stream::Plain(s) => Pin::new(s).poll_write(cx, buf),
+            Stream::Tls(s) => Pin::new(s.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Stream::Plain(s) => Pin::new(s).poll_flush(cx),
+            Stream::Tls(s) => Pin::new(s.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Stream::Plain(s) => Pin::new(s).poll_shutdown(cx),
+            Stream::Tls(s) => Pin::new(s.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
+
+/// 与邮件服务器的连接
+pub struct MailConnection {
+    host: String,
+    reader: BufReader<Stream>,
+}
+
+/// rustls 未内置加密实现，与更新插件一样使用 ring
+pub fn ensure_crypto_provider() {
+    if rustls::crypto::CryptoProvider::get_default().is_none() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+    }
+}
+
+async fn tls_handshake(host: &str, tcp: TcpStream) -> Result<TlsStream<TcpStream>, MailError> {
+    use rustls_platform_verifier::ConfigVerifierExt;
+
+    ensure_crypto_provider();
+    let config = rustls::ClientConfig::with_platform_verifier().map_err(|e| MailError::Tls {
+        message: format!("加载系统证书失败: {}", e),
+    })?;
+    let server_name = rustls::pki_types::ServerName::try_from(host.to_string()).map_err(|e| MailError::Tls {
+        message: format!("无效的服务器名称 {}: {}", host, e),
+    })?;
+    tokio::time::timeout(CONNECT_TIMEOUT, TlsConnector::from(Arc::new(config)).connect(server_name, tcp))
+        .await
+        .map_err(|_| MailError::Timeout {
+            stage: "TLS 握手".to_string(),
+        })?
+        .map_err(|e| MailError::Tls { message: e.to_string() })
+}
+
+impl MailConnection {
+    /// 解析域名并连接，implicit_tls 为 true 时连接后立即握手
+    pub async fn connect(host: &str, port: u16, implicit_tls: bool) -> Result<Self, MailError> {
+        let addrs: Vec<_> = tokio::time::timeout(CONNECT_TIMEOUT, tokio::net::lookup_host((host, port)))
+            .await
+            .map_err(|_| MailError::Timeout {
+                stage: "解析服务器地址".to_string(),
+            })?
+            .map_err(|e| MailError::Dns {
+                host: host.to_string(),
+                message: e.to_string(),
+            })?
+            .collect();
+        if addrs.is_empty() {
+            return Err(MailError::Dns {
+                host: host.to_string(),
+                message: "没有可用的地址".to_string(),
+            });
+        }
+        let tcp = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(&addrs[..]))
+            .await
+            .map_err(|_| MailError::Timeout {
+                stage: format!("连接 {}:{}", host, port),
+            })?
+            .map_err(|e| MailError::Connect {
+                message: format!("{}:{}: {}", host, port, e),
+            })?;
+        let stream = if implicit_tls {
+            Stream::Tls(Box::new(tls_handshake(host, tcp).await?))
+        } else {
+            Stream::Plain(tcp)
+        };
+        Ok(MailConnection {
+            host: host.to_string(),
+            reader: BufReader::new(stream),
+        })
+    }
+
+    /// 是否已加密
+    pub fn is_tls(&self) -> bool {
+        matches!(self.reader.get_ref(), Stream::Tls(_))
+    }
+
+    /// 协议协商 STARTTLS 后升级为 TLS 连接
+    pub async fn start_tls(self) -> Result<Self, MailError> {
+        let MailConnection { host, reader } = self;
+        let stream = match reader.into_inner() {
+            Stream::Plain(tcp) => Stream::Tls(Box::new(tls_handshake(&host, tcp).await?)),
+            tls => tls,
+        };
+        Ok(MailConnection {
+            host,
+            reader: BufReader::new(stream),
+        })
+    }
+
+    /// 读取一行（去掉行尾的 CRLF），stage 用于超时提示
+    pub async fn read_line(&mut self, stage: &str) -> Result<String, MailError> {
+        let mut line = Vec::new();
+        let read = tokio::time::timeout(IO_TIMEOUT, async {
+            loop {
+                let buf = self.reader.fill_buf().await?;
+                if buf.is_empty() {
+                    return Ok::<_, std::io::Error>(false);
+                }
+                match buf.iter().position(|&b| b == b'\n') {
+                    Some(i) => {
+                        line.extend_from_slice(&buf[..=i]);
+                        self.reader.consume(i + 1);
+                        return Ok(true);
+                    }
+                    None => {
+                        let len = buf.len();
+                        line.extend_from_slice(buf);
+                        self.reader.consume(len);
+                        if line.len() > MAX_LINE_BYTES {
+                            return Ok(true);
+                        }
+                    }
+                }
+            }
+        })
+        .await
+        .map_err(|_| MailError::Timeout { stage: stage.to_string() })?
+        .map_err(|e| MailError::Connect { message: e.to_string() })?;
+        if !read && line.is_empty() {
+            return Err(MailError::Protocol {
+                message: format!("{}时服务器关闭了连接", stage),
+            });
+        }
+        Ok(String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n']).to_string())
+    }
+
+    /// 读取指定字节数（IMAP 字面量）
+    pub async fn read_exact(&mut self, len: usize, stage: &str) -> Result<Vec<u8>, MailError> {
+        let mut data = vec![0u8; len];
+        tokio::time::timeout(IO_TIMEOUT, self.reader.read_exact(&mut data))
+            .await
+            .map_err(|_| MailError::Timeout { stage: stage.to_string() })?
+            .map_err(|e| MailError::Connect { message: e.to_string() })?;
+        Ok(data)
+    }
+
+    /// 发送一行（自动补 CRLF）
+    pub async fn write_line(&mut self, line: &str) -> Result<(), MailError> {
+        let stream = self.reader.get_mut();
+        tokio::time::timeout(IO_TIMEOUT, async {
+            stream.write_all(line.as_bytes()).await?;
+            stream.write_all(b"\r\n").await?;
+            stream.flush().await
+        })
+        .await
+        .map_err(|_| MailError::Timeout {
+            stage: "发送命令".to_string(),
+        })?
+        .map_err(|e| MailError::Connect { message: e.to_string() })
+    }
+}
@@ -4,45 +4,176 @@
 use std::path::PathBuf;
 use std::process::Stdio;
 use serde::{Deserialize, Serialize};
-use tauri::{
-    AppHandle, Window, Emitter, Manager,
-    tray::{TrayIconBuilder, MouseButton, MouseButtonState, TrayIconEvent},
-    menu::{MenuBuilder, MenuItemBuilder},
-};
+use tauri::{AppHandle, Window, Manager, webview::PageLoadEvent};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader as TokioBufReader};
 use tokio::process::{Child as TokioChild, ChildStdin, ChildStdout, Command as TokioCommand};
 use tokio::sync::Mutex;
+use ts_rs::TS;
+
+mod active_window;
+mod agent_event;
+mod artifact_naming;
+mod attachments;
+mod audit;
+mod automation_pack;
+mod autostart;
+mod backup;
+mod budget;
+mod bulk;
+mod calendar;
+mod cli;
+mod clipboard;
+mod config;
+mod config_migration;
+mod cost;
+mod crash_report;
+mod deep_link;
+mod detached;
+mod diagnostics;
+mod dry_run;
+mod email_check;
+mod email_oauth;
+mod error;
+mod event_sink;
+mod features;
+mod file_actions;
+mod file_guard;
+mod focus;
+mod framing;
+mod fs_tools;
+mod groups;
+mod history;
+mod http_api;
+mod i18n;
+mod ics;
+mod imap_client;
+mod instance;
+mod language;
+mod launch_env;
+mod liveness;
+mod local_models;
+mod mail_conn;
+mod mcp;
+mod metrics;
+mod observer;
+mod ocr;
+mod open_with;
+mod permissions;
+mod policy;
+mod preflight;
+mod project_context;
+mod prompt_log;
+mod provider_health;
+mod proxy;
+mod quick_eval;
+mod redaction;
+mod report;
+mod resource_monitor;
+mod retry;
+mod sandbox;
+mod sandbox_watch;
+mod scheduler;
+mod screenshot;
+mod secrets;
+mod server_log;
+mod server_pool;
+mod sessions;
+mod settings_bundle;
+mod standby;
+mod startup;
+mod supervisor;
+mod system_info;
+mod task_control;
+mod telemetry;
+mod templates;
+mod stream;
+mod tool_server;
+mod transcript;
+mod tray;
+mod tts;
+mod undo;
+mod updater;
+mod usage;
+mod validation;
+mod voice;
+mod warmup;
+mod webhooks;
+mod widget;
+mod window_manager;
+mod window_state;
+
+use agent_event::AgentEvent;
+use config::AppConfig;
+use error::DeskJarvisError;
+use event_sink::EventSink;
+use tray::AgentStatus;
 
 /// 任务执行结果
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
 struct TaskResult {
     success: bool,
     message: String,
     steps: Vec<StepResult>,
     user_instruction: String,
+    /// 因超出单任务费用上限而被中止
+    #[serde(default)]
+    truncated_by_budget: bool,
+    /// 提前结束的原因；此时 steps 为已完成的步骤
+    #[serde(default)]
+    termination_reason: Option<history::TerminationReason>,
+    /// 任务工作目录中的文件（相对路径），任务结束后由 Tauri 填写
+    #[serde(default)]
+    artifacts: Vec<String>,
 }
 
 /// 步骤结果
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export, export_to = "../../src/types/bindings/")]
 struct StepResult {
     step: serde_json::Value,
     result: Option<serde_json::Value>,
 }
 
-/// 应用配置
-#[derive(Debug, Serialize, Deserialize)]
-struct AppConfig {
-    provider: String,
-    api_key: String,
-    model: String,
-    sandbox_path: String,
-    auto_confirm: bool,
-    log_level: String,
-    // 邮件服务配置 (可选，以兼容旧配置)
-    email_sender: Option<String>,
-    email_password: Option<String>,
-    email_smtp_server: Option<String>,
-    email_smtp_port: Option<i32>,
+impl StepResult {
+    /// 由 success 进度事件记录的已完成步骤（服务中止时作为部分结果返回）
+    fn completed(data: &agent_event::StepData) -> Self {
+        StepResult {
+            step: serde_json::json!({
+                "description": data.description,
+                "step_index": data.step_index,
+            }),
+            result: Some(serde_json::json!({ "success": true })),
+        }
+    }
+}
+
+impl TaskResult {
+    /// 提前结束的任务结果，保留已完成的步骤
+    fn partial(
+        request: &TaskRequest,
+        steps: Vec<StepResult>,
+        reason: history::TerminationReason,
+        message: String,
+    ) -> Self {
+        TaskResult {
+            success: false,
+            message,
+            steps,
+            user_instruction: request.instruction.clone(),
+            truncated_by_budget: reason == history::TerminationReason::Budget,
+            termination_reason: Some(reason),
+            artifacts: Vec::new(),
+        }
+    }
+
+    /// 把执行过的步骤追加到审计日志
+    fn record_audit(&self, task_id: &str) {
+        audit::record_task_steps(
+            task_id,
+            self.steps.iter().map(|s| (&s.step, s.result.as_ref())),
+        );
+    }
 }
 
 // ==================== 常驻 Python 服务进程 ====================
@@ -52,32 +183,117 @@ struct PythonServer {
     child: TokioChild,
     stdin: ChildStdin,
     reader: TokioBufReader<ChildStdout>,
+    /// stderr 最后若干行，崩溃时写入报告
+    stderr_tail: std::sync::Arc<crash_report::StderrTail>,
+    /// ready 事件中协商的 stdout 协议版本
+    protocol: u32,
 }
 
 /// 应用全局状态（通过 Tauri .manage() 注入）
 struct AppState {
     server: Mutex<Option<PythonServer>>,
-    current_task_id: Mutex<Option<String>>,  // 当前正在执行的任务ID
+    /// 主服务忙时使用的额外工作进程（server_pool_size > 1 时启用）
+    pool: server_pool::ServerPool,
+    /// 正在执行的任务及其所在的进程
+    running: server_pool::RunningTasks,
+    /// 已暂停的任务
+    paused: task_control::PausedTasks,
+    history: history::HistoryStore,
+    groups: groups::GroupStore,
+    schedules: scheduler::ScheduleStore,
+    sessions: sessions::SessionStore,
+}
+
+/// 一次任务执行请求
+struct TaskRequest {
+    id: String,
+    instruction: String,
+    context: Option<serde_json::Value>,
+    /// 任务专属工作目录（sandbox/task_<id>）
+    work_dir: Option<PathBuf>,
+    /// 单任务费用上限（美元），仅常驻服务模式生效
+    max_cost_usd: Option<f64>,
+    /// 所属会话，Python 端据此读写会话记忆
+    session_id: Option<String>,
+    /// 是否上报并记录完整提示词（见 prompt_log）
+    log_prompts: bool,
+    /// 临时失败后重试时从哪一步继续（见 retry）
+    resume: Option<retry::Resume>,
+    /// 邮箱使用 OAuth2 登录时的访问令牌（见 email_oauth）
+    email_oauth: Option<email_oauth::OAuthCredential>,
+    /// 逐步执行：每个步骤完成后等待 continue_task / abort_task（见 task_control），仅常驻服务模式生效
+    interactive: bool,
+}
+
+/// run_tracked_task 的可选参数
+#[derive(Debug, Default)]
+struct TaskOptions {
+    group_id: Option<String>,
+    max_cost_usd: Option<f64>,
+    session_id: Option<String>,
+    /// 本任务不记录提示词日志
+    skip_prompt_log: bool,
+    /// 逐步执行
+    interactive: bool,
+    /// 调用方已在接受任务前通过 budget::admit 检查预算（HTTP 接口、定时任务）
+    budget_checked: bool,
+}
+
+impl TaskRequest {
+    /// 构建发送给 Python 服务的 execute 命令
+    fn to_command(&self) -> serde_json::Value {
+        serde_json::json!({
+            "cmd": "execute",
+            "id": self.id,
+            "instruction": self.instruction,
+            "context": self.context,
+            "work_dir": self.work_dir,
+            "session_id": self.session_id,
+            "log_prompts": self.log_prompts,
+            "resume_from_step": self.resume.as_ref().map(|r| r.from_step),
+            "completed_steps": self.resume.as_ref().map(|r| &r.completed_steps),
+            "email_oauth": self.email_oauth,
+            "interactive": self.interactive,
+        })
+    }
+}
+
+/// 等待 Python 服务 ready 信号的超时（秒）
+const SERVER_READY_TIMEOUT_SECS: u64 = 30;
+
+/// 启动主服务进程，交给资源监控采样并按配置预热
+async fn launch_python_server() -> Result<PythonServer, DeskJarvisError> {
+    let mut server = spawn_python_server().await?;
+    resource_monitor::set_server_pid(server.child.id());
+    warmup::send_on_start(&mut server).await;
+    Ok(server)
 }
 
 /// 启动常驻 Python 服务进程
 ///
 /// 等待 "ready" 信号后返回，确保 Agent 完全初始化。
-/// 超时 30 秒。
-async fn launch_python_server() -> Result<PythonServer, String> {
+/// 超时 SERVER_READY_TIMEOUT_SECS 秒。
+async fn spawn_python_server() -> Result<PythonServer, DeskJarvisError> {
     let python_path = get_python_path()?;
     let server_path = find_script("server.py")?;
 
+    let extra_env = config::resolve()
+        .map(|c| launch_env::resolve(&c))
+        .unwrap_or_default();
+
     eprintln!("[Tauri] 启动 Python 服务: {} {}", python_path, server_path);
 
-    let mut child = TokioCommand::new(&python_path)
+    let mut child = python_path
+        .tokio_command()
         .arg(&server_path)
+        .envs(extra_env)
+        .env(framing::PROTOCOL_ENV, framing::PROTOCOL_VERSION.to_string())
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .kill_on_drop(true) // 父进程退出时自动杀死子进程
         .spawn()
-        .map_err(|e| format!("启动 Python 服务失败: {}", e))?;
+        .map_err(|e| DeskJarvisError::ServerStartFailed(format!("启动 Python 服务失败: {}", e)))?;
 
     let stdin = child
         .stdin
@@ -92,7 +308,11 @@ async fn launch_python_server() -> Result<PythonServer, String> {
         .take()
         .ok_or("无法获取 Python 服务 stderr")?;
 
-    // 后台任务：读取 stderr 并打印（Python 日志输出）
+    // 后台任务：读取 stderr 并打印（Python 日志输出），同时保留最后若干行供崩溃报告使用，
+    // 并送入调试控制台（见 server_log）
+    let stderr_tail = std::sync::Arc::new(crash_report::StderrTail::default());
+    let tail = stderr_tail.clone();
+    let mut feed = server_log::Feed::new(child.id());
     tauri::async_runtime::spawn(async move {
         let mut reader = TokioBufReader::new(stderr);
         let mut line = String::new();
@@ -100,39 +320,49 @@ async fn launch_python_server() -> Result<PythonServer, String> {
             line.clear();
             match reader.read_line(&mut line).await {
                 Ok(0) => break,        // EOF
-                Ok(_) => eprint!("{}", line), // 转发到 Tauri 控制台
+                Ok(_) => {
+                    let redacted = redaction::redact(&line);
+                    eprint!("{}", redacted); // 脱敏后转发到 Tauri 控制台
+                    tail.push(&redacted);
+                    feed.push(&redacted);
+                }
                 Err(_) => break,
             }
         }
+        tail.close();
     });
 
     let mut reader = TokioBufReader::new(stdout);
 
-    // 等待 "ready" 信号（最多 30 秒）
+    // 等待 "ready" 信号
     let ready_result = tokio::time::timeout(
-        std::time::Duration::from_secs(30),
+        std::time::Duration::from_secs(SERVER_READY_TIMEOUT_SECS),
         wait_for_ready(&mut reader),
     )
     .await;
 
     match ready_result {
-        Ok(Ok(())) => {
-            eprintln!("[Tauri] ✅ Python 服务已就绪");
+        Ok(Ok(protocol)) => {
+            eprintln!("[Tauri] ✅ Python 服务已就绪（协议版本 {}）", protocol);
             Ok(PythonServer {
                 child,
                 stdin,
                 reader,
+                stderr_tail,
+                protocol,
             })
         }
-        Ok(Err(e)) => Err(format!("Python 服务初始化失败: {}", e)),
-        Err(_) => Err("Python 服务启动超时(30s)".to_string()),
+        Ok(Err(e)) => Err(DeskJarvisError::ServerStartFailed(format!("Python 服务初始化失败: {}", e))),
+        Err(_) => Err(DeskJarvisError::ServerTimeout {
+            timeout_secs: SERVER_READY_TIMEOUT_SECS,
+        }),
     }
 }
 
-/// 从 stdout 读取行直到收到 "ready" 事件
+/// 从 stdout 读取行直到收到 "ready" 事件，返回协商的协议版本
 async fn wait_for_ready(
     reader: &mut TokioBufReader<ChildStdout>,
-) -> Result<(), String> {
+) -> Result<u32, String> {
     let mut buf = String::new();
     loop {
         buf.clear();
@@ -150,7 +380,7 @@ async fn wait_for_ready(
                 .and_then(|v| v.as_str())
                 .unwrap_or("");
             if event_type == "ready" {
-                return Ok(());
+                return Ok(framing::negotiated_version(&event));
             }
             if event_type == "error" {
                 let msg = event
@@ -166,7 +396,7 @@ async fn wait_for_ready(
 /// 确保 Python 服务进程正在运行，必要时自动重启
 async fn ensure_server_alive(
     server_opt: &mut Option<PythonServer>,
-) -> Result<(), String> {
+) -> Result<(), DeskJarvisError> {
     let needs_restart = match server_opt.as_mut() {
         Some(s) => {
             match s.child.try_wait() {
@@ -186,30 +416,69 @@ async fn ensure_server_alive(
 
     if needs_restart {
         *server_opt = None;
-        let new_server = launch_python_server().await?;
+        // 降级状态或超出重启预算时不再启动，由调用方降级为单次进程模式
+        supervisor::admit_restart()?;
+        let new_server = match standby::take_spare() {
+            Some(spare) => {
+                eprintln!("[Tauri] 🧊 备用 Python 服务已接替");
+                spare
+            }
+            None => launch_python_server().await.inspect_err(|e| {
+                supervisor::record_failure(&e.message());
+            })?,
+        };
+        supervisor::record_started();
+        standby::refill();
         *server_opt = Some(new_server);
     }
 
     Ok(())
 }
 
-/// 后台静默重启 Python 服务（崩溃后自动恢复）
-fn spawn_background_restart(app_handle: AppHandle) {
+/// 后台重启 Python 服务（崩溃后自动恢复）
+///
+/// failure 为崩溃原因时按连续失败次数指数退避，主动中止的进程则 1 秒后重启；
+/// 重启预算与降级状态见 supervisor。
+fn spawn_background_restart(app_handle: AppHandle, failure: Option<&str>) {
+    let delay = match failure {
+        Some(reason) => supervisor::record_failure(reason),
+        None => std::time::Duration::from_secs(1),
+    };
+    supervisor::spawn_restart(app_handle, delay);
+}
+
+/// Python 服务看门狗检查间隔
+const WATCHDOG_INTERVAL_SECS: u64 = 5;
+
+/// 后台看门狗：定期检查 Python 服务进程，同步托盘 "服务未运行" 状态
+///
+/// 任务执行期间服务锁被占用，此时跳过本轮检查。
+fn spawn_server_watchdog(app_handle: AppHandle) {
     tauri::async_runtime::spawn(async move {
-        // 稍等一下再重启，避免连续崩溃
-        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-        let state = app_handle.state::<AppState>();
-        let mut guard = state.server.lock().await;
-        if guard.is_none() {
-            eprintln!("[Tauri] 🔄 后台自动重启 Python 服务...");
-            match launch_python_server().await {
-                Ok(s) => {
-                    *guard = Some(s);
-                    eprintln!("[Tauri] ✅ Python 服务后台重启成功");
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(WATCHDOG_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let state = app_handle.state::<AppState>();
+            let alive = {
+                let Ok(mut guard) = state.server.try_lock() else {
+                    continue;
+                };
+                match guard.as_mut() {
+                    Some(s) => matches!(s.child.try_wait(), Ok(None)),
+                    None => false,
                 }
-                Err(e) => {
-                    eprintln!("[Tauri] ❌ Python 服务后台重启失败: {}", e);
+            };
+
+            match (alive, tray::current_status(&app_handle)) {
+                (false, Some(AgentStatus::Idle)) | (false, Some(AgentStatus::Error { .. })) => {
+                    eprintln!("[Tauri] ⚠️ 看门狗检测到 Python 服务未运行");
+                    tray::set_status(&app_handle, AgentStatus::ServerDown);
+                }
+                (true, Some(AgentStatus::ServerDown)) => {
+                    tray::set_status(&app_handle, AgentStatus::Idle);
                 }
+                _ => {}
             }
         }
     });
@@ -219,19 +488,12 @@ fn spawn_background_restart(app_handle: AppHandle) {
 
 /// 通过常驻 Python 服务执行任务
 async fn execute_via_server(
-    window: &Window,
+    sink: &impl EventSink,
     server: &mut PythonServer,
-    instruction: &str,
-    context: &Option<serde_json::Value>,
-    request_id: &str,
+    request: &TaskRequest,
 ) -> Result<TaskResult, String> {
     // 构建 JSON 命令
-    let cmd = serde_json::json!({
-        "cmd": "execute",
-        "id": request_id,
-        "instruction": instruction,
-        "context": context,
-    });
+    let cmd = request.to_command();
     let cmd_line = cmd.to_string() + "\n";
 
     // 写入 stdin
@@ -248,76 +510,307 @@ async fn execute_via_server(
 
     // 读取 stdout 直到收到 result 事件
     let mut line_buf = String::new();
+    let mut stream_buf = stream::StreamBuffer::new(&request.id);
+    let mut cost = cost::CostTracker::new(&request.id, request.max_cost_usd);
+    let mut liveness = liveness::Liveness::new(&request.id);
+    // 已完成的步骤：服务中止或崩溃时作为部分结果返回
+    let mut completed: Vec<StepResult> = Vec::new();
+    // 任务暂停期间不做卡死检测
+    let mut paused = false;
     loop {
         line_buf.clear();
-        let bytes_read = server
-            .reader
-            .read_line(&mut line_buf)
-            .await
-            .map_err(|e| format!("读取响应失败: {}", e))?;
+        let bytes_read = loop {
+            let read = server.reader.read_line(&mut line_buf);
+            // 有待发送的流式输出时，到截止时间仍无新行则先发送（已读到的半行保留在 line_buf）
+            let deadline = match stream_buf.flush_deadline() {
+                Some(flush_at) => flush_at.min(liveness.deadline()),
+                None => liveness.deadline(),
+            };
+            match tokio::time::timeout_at(deadline, read).await {
+                Ok(r) => break r,
+                Err(_) => stream_buf.flush(sink),
+            }
+            if paused {
+                liveness.reset();
+                continue;
+            }
+            if !liveness.is_expired() {
+                continue;
+            }
+
+            // 长时间无事件：先确认进程仍在，再询问用户
+            if let Ok(Some(_)) = server.child.try_wait() {
+                return crashed(request, completed);
+            }
+            match liveness.poll(sink) {
+                Some(liveness::StuckAction::Cancel) => {
+                    eprintln!("[Tauri] 🛑 任务 {} 无响应，用户选择取消", request.id);
+                    let _ = server.child.kill().await;
+                    return Ok(TaskResult::partial(
+                        request,
+                        completed,
+                        history::TerminationReason::Timeout,
+                        "任务长时间无响应，已取消".to_string(),
+                    ));
+                }
+                Some(liveness::StuckAction::Restart) => {
+                    eprintln!("[Tauri] 🔄 任务 {} 无响应，用户选择重启服务", request.id);
+                    let _ = server.child.kill().await;
+                    return Err(liveness::RESTART_REQUESTED.to_string());
+                }
+                Some(liveness::StuckAction::Wait) | None => {}
+            }
+        }
+        .map_err(|e| format!("读取响应失败: {}", e))?;
 
         if bytes_read == 0 {
             // EOF - Python 服务崩溃
-            return Err("PROCESS_CRASHED".to_string());
+            return crashed(request, completed);
         }
 
-        let trimmed = line_buf.trim();
-        if trimmed.is_empty() {
+        let Some(payload) = framing::decode_line(&line_buf, server.protocol) else {
             continue;
-        }
-
-        // 解析 JSON 事件
-        let event: serde_json::Value = match serde_json::from_str(trimmed) {
-            Ok(v) => v,
-            Err(_) => continue, // 跳过非 JSON 行
         };
 
-        let event_type = event
-            .get("type")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-
-        match event_type {
-            "ready" | "pong" | "shutdown_ack" => {
-                // 协议控制事件，跳过
+        // 解析为 AgentEvent，形状不符的事件以 task-warning 提示后丢弃
+        let event = match AgentEvent::parse(&payload, &request.id) {
+            Ok(Some(event)) => event,
+            Ok(None) => continue, // 跳过非 JSON 行
+            Err(warning) => {
+                liveness.reset();
+                eprintln!("[Tauri] ⚠️ {} ({})", warning.message, warning.event_type);
+                sink.send("task-warning", warning);
                 continue;
             }
-            "result" => {
-                // 最终结果
-                if let Some(data) = event.get("data") {
-                    return serde_json::from_value::<TaskResult>(data.clone())
-                        .map_err(|e| format!("解析 TaskResult 失败: {}", e));
+        };
+        liveness.reset();
+        match &event {
+            AgentEvent::StepSucceeded { data, .. } => completed.push(StepResult::completed(data)),
+            // 失败原因留给重试判断是否为临时失败
+            AgentEvent::Error { message, data, .. } => {
+                let reason = data.as_ref().and_then(|d| d.message.as_ref()).or(message.as_ref());
+                if let Some(reason) = reason {
+                    retry::note_error(&request.id, reason);
                 }
-                return Err("result 事件缺少 data 字段".to_string());
+            }
+            _ => {}
+        }
+
+        match event {
+            AgentEvent::Stream { delta, .. } => {
+                // LLM 流式输出 → 缓冲后以 task-stream 转发
+                stream_buf.push(sink, &delta);
+            }
+            AgentEvent::ConfirmationRequest(event) => {
+                // 修改类步骤的审批请求，按确认策略放行、拒绝或请用户确认
+                policy::handle_confirmation_request(sink, &request.id, &event);
+            }
+            AgentEvent::ApiCall(event) => {
+                // 单次模型调用的耗时与结果 → 提供商健康统计
+                provider_health::record(&event);
+            }
+            AgentEvent::Prompt(event) => {
+                // 完整提示词与响应 → 脱敏后写入任务的提示词日志
+                if let Err(e) = prompt_log::append(&request.id, &event) {
+                    eprintln!("[Tauri] ⚠️ {}", e);
+                }
+            }
+            AgentEvent::FileAccessRequest(event) => {
+                // 写沙盒以外的路径前的审批请求，结果以文件交回 Python
+                file_guard::handle_request(sink, &request.id, &event);
+            }
+            AgentEvent::ScreenshotRequest(event) => {
+                // 系统层截图，保存到任务工作目录，路径以文件交回 Python
+                screenshot::handle_request(sink, request.work_dir.as_deref(), &event);
+            }
+            AgentEvent::ToolCall(event) => {
+                // 内置工具（shell 等）统一在 Rust 侧执行，结果以文件交回 Python
+                tool_server::handle_call(&request.id, request.work_dir.as_deref(), &event);
+            }
+            AgentEvent::Usage(event) => {
+                // 模型用量 → 累计费用，超出上限时中止服务进程（执行中不读取 stop 命令）
+                if !cost.record(sink, &event) {
+                    continue;
+                }
+                stream_buf.flush(sink);
+                let _ = server.child.kill().await;
+                let max = cost.max_cost_usd().unwrap_or_default();
+                eprintln!(
+                    "[Tauri] 💸 任务 {} 费用 ${:.4} 超出上限 ${:.2}，已中止",
+                    request.id,
+                    cost.total_cost_usd(),
+                    max
+                );
+                return Ok(TaskResult::partial(
+                    request,
+                    completed,
+                    history::TerminationReason::Budget,
+                    format!(
+                        "任务费用 ${:.4} 超出上限 ${:.2}，已中止",
+                        cost.total_cost_usd(),
+                        max
+                    ),
+                ));
+            }
+            AgentEvent::Paused { .. } | AgentEvent::Resumed { .. } => {
+                paused = matches!(event, AgentEvent::Paused { .. });
+                stream_buf.flush(sink);
+                send_pause_event(sink, &request.id, paused);
+            }
+            AgentEvent::StepGate(event) => {
+                // 等待用户继续期间同样不做卡死检测，继续后 Python 发出 resumed
+                paused = true;
+                stream_buf.flush(sink);
+                send_step_gate_event(sink, &request.id, &event);
+            }
+            AgentEvent::Result { data, .. } => {
+                // 最终结果
+                stream_buf.flush(sink);
+                return Ok(data);
+            }
+            event if event.is_progress() => {
+                // 进度事件 → 脱敏后转发到前端（先发送积压的流式输出，保证顺序）
+                stream_buf.flush(sink);
+                let Ok(mut payload) = serde_json::to_value(&event) else {
+                    continue;
+                };
+                redaction::redact_json(&mut payload);
+                widget::record_progress(&payload);
+                sink.send("task-progress", &payload);
             }
             _ => {
-                // 进度事件 → 转发到前端
-                let _ = window.emit("task-progress", &event);
+                // 协议控制事件（ready、pong、*_ack 等），跳过
             }
         }
     }
 }
 
+/// 任务暂停或恢复 → task-paused / task-resumed
+fn send_pause_event(sink: &impl EventSink, request_id: &str, paused: bool) {
+    let event = if paused { "task-paused" } else { "task-resumed" };
+    sink.send(event, serde_json::json!({ "request_id": request_id }));
+}
+
+/// 逐步执行时一个步骤已完成 → step-completed-awaiting-next
+fn send_step_gate_event(sink: &impl EventSink, request_id: &str, event: &agent_event::StepGateEvent) {
+    task_control::set_awaiting_next(request_id, true);
+    sink.send(
+        "step-completed-awaiting-next",
+        serde_json::json!({
+            "request_id": request_id,
+            "step_index": event.step_index,
+            "total_steps": event.total_steps,
+            "description": event.description.as_deref().map(redaction::redact),
+        }),
+    );
+}
+
+/// 服务执行中崩溃：已有完成的步骤时返回部分结果，避免重复执行；否则交给调用方降级重试
+fn crashed(request: &TaskRequest, completed: Vec<StepResult>) -> Result<TaskResult, String> {
+    if completed.is_empty() {
+        return Err("PROCESS_CRASHED".to_string());
+    }
+    Ok(TaskResult::partial(
+        request,
+        completed,
+        history::TerminationReason::Crash,
+        "Python 服务在执行中崩溃，已返回完成的部分".to_string(),
+    ))
+}
+
+/// 向常驻服务发送控制命令（非 execute），等待同一 id 的应答事件
+///
+/// 期间收到的其他事件被忽略；超时或服务返回 error 事件时返回错误。
+async fn send_control_command(
+    server: &mut PythonServer,
+    cmd: &serde_json::Value,
+    reply_type: &str,
+    timeout: std::time::Duration,
+) -> Result<serde_json::Value, String> {
+    let cmd_id = cmd.get("id").and_then(|v| v.as_str()).unwrap_or("").to_string();
+    let cmd_line = cmd.to_string() + "\n";
+    server
+        .stdin
+        .write_all(cmd_line.as_bytes())
+        .await
+        .map_err(|e| format!("写入命令失败: {}", e))?;
+    server
+        .stdin
+        .flush()
+        .await
+        .map_err(|e| format!("刷新 stdin 失败: {}", e))?;
+
+    let wait_reply = async {
+        let mut line_buf = String::new();
+        loop {
+            line_buf.clear();
+            let bytes_read = server
+                .reader
+                .read_line(&mut line_buf)
+                .await
+                .map_err(|e| format!("读取响应失败: {}", e))?;
+            if bytes_read == 0 {
+                return Err("PROCESS_CRASHED".to_string());
+            }
+            let Some(payload) = framing::decode_line(&line_buf, server.protocol) else {
+                continue;
+            };
+            let Ok(event) = serde_json::from_str::<serde_json::Value>(&payload) else {
+                continue;
+            };
+            if event.get("id").and_then(|v| v.as_str()) != Some(cmd_id.as_str()) {
+                continue;
+            }
+            match event.get("type").and_then(|v| v.as_str()) {
+                Some(t) if t == reply_type => return Ok(event),
+                Some("error") => {
+                    let msg = event
+                        .get("message")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("未知错误");
+                    return Err(msg.to_string());
+                }
+                _ => continue,
+            }
+        }
+    };
+
+    tokio::time::timeout(timeout, wait_reply)
+        .await
+        .map_err(|_| format!("等待 {} 应答超时({}s)", reply_type, timeout.as_secs()))?
+}
+
 /// 单次进程模式（降级方案：当常驻进程不可用时使用）
 async fn execute_oneshot(
-    window: &Window,
-    instruction: &str,
-    context: &Option<serde_json::Value>,
-) -> Result<TaskResult, String> {
+    sink: &impl EventSink,
+    request: &TaskRequest,
+) -> Result<TaskResult, DeskJarvisError> {
     let python_path = get_python_path()?;
     let agent_path = find_script("main.py")?;
 
-    let mut cmd_args = vec![agent_path, "--json".to_string(), instruction.to_string()];
+    let mut cmd_args = vec![agent_path, "--json".to_string(), request.instruction.clone()];
 
-    if let Some(ctx) = context {
+    if let Some(ctx) = &request.context {
         if let Ok(ctx_str) = serde_json::to_string(ctx) {
             cmd_args.push("--context".to_string());
             cmd_args.push(ctx_str);
         }
     }
+    if let Some(session_id) = &request.session_id {
+        cmd_args.push("--session".to_string());
+        cmd_args.push(session_id.clone());
+    }
+
+    let extra_env = config::resolve()
+        .map(|c| launch_env::resolve(&c))
+        .unwrap_or_default();
 
-    let mut child = std::process::Command::new(python_path)
-        .args(&cmd_args)
+    let mut command = python_path.command();
+    command.args(&cmd_args).envs(extra_env);
+    if let Some(dir) = &request.work_dir {
+        command.current_dir(dir);
+    }
+    let mut child = command
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
@@ -329,32 +822,40 @@ async fn execute_oneshot(
     let reader = std::io::BufRead::lines(std::io::BufReader::new(stdout));
     let mut final_result: Option<TaskResult> = None;
     let mut stdout_lines = Vec::new();
+    let mut stream_buf = stream::StreamBuffer::new(&request.id);
 
     for line in reader {
         let line = line.map_err(|e| format!("读取 stdout 失败: {}", e))?;
         stdout_lines.push(line.clone());
 
-        if let Ok(event) = serde_json::from_str::<serde_json::Value>(&line) {
-            let event_type = event
-                .get("type")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-            if event_type != "" {
-                let _ = window.emit("task-progress", &event);
-            }
-        }
-
+        // 最终结果是不带 type 的 TaskResult，其余带 type 的行按 AgentEvent 处理
         if let Ok(result) = serde_json::from_str::<TaskResult>(&line) {
             final_result = Some(result);
+            continue;
+        }
+        match AgentEvent::parse(&line, &request.id) {
+            Ok(Some(AgentEvent::Stream { delta, .. })) => stream_buf.push(sink, &delta),
+            Ok(Some(event)) if event.is_progress() => {
+                stream_buf.flush(sink);
+                if let Ok(mut payload) = serde_json::to_value(&event) {
+                    redaction::redact_json(&mut payload);
+                    sink.send("task-progress", &payload);
+                }
+            }
+            Ok(_) => {}
+            Err(warning) => {
+                eprintln!("[Tauri] ⚠️ {} ({})", warning.message, warning.event_type);
+                sink.send("task-warning", warning);
+            }
         }
     }
 
+    stream_buf.flush(sink);
+
     let stderr_reader = std::io::BufRead::lines(std::io::BufReader::new(stderr));
     let mut stderr_lines = Vec::new();
-    for line in stderr_reader {
-        if let Ok(line) = line {
-            stderr_lines.push(line);
-        }
+    for line in stderr_reader.map_while(Result::ok) {
+        stderr_lines.push(line);
     }
 
     let _status = child
@@ -362,7 +863,10 @@ async fn execute_oneshot(
         .map_err(|e| format!("等待进程结束失败: {}", e))?;
 
     if !stderr_lines.is_empty() {
-        eprintln!("[oneshot] Python stderr: {}", stderr_lines.join("\n"));
+        eprintln!(
+            "[oneshot] Python stderr: {}",
+            redaction::redact(&stderr_lines.join("\n"))
+        );
     }
 
     if let Some(result) = final_result {
@@ -370,8 +874,9 @@ async fn execute_oneshot(
     } else {
         let stdout_content = stdout_lines.join("\n");
         let json_str = extract_json_from_output(&stdout_content)?;
-        serde_json::from_str::<TaskResult>(&json_str)
-            .map_err(|e| format!("解析 JSON 失败: {}。原始输出: {}", e, stdout_content))
+        let result = serde_json::from_str::<TaskResult>(&json_str)
+            .map_err(|e| format!("解析 JSON 失败: {}。原始输出: {}", e, stdout_content))?;
+        Ok(result)
     }
 }
 
@@ -384,134 +889,446 @@ async fn execute_task(
     state: tauri::State<'_, AppState>,
     instruction: String,
     context: Option<serde_json::Value>,
-) -> Result<TaskResult, String> {
-    let request_id = format!(
-        "task_{}",
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis()
+    group_id: Option<String>,
+    max_cost_usd: Option<f64>,
+    skip_prompt_log: Option<bool>,
+) -> Result<TaskResult, DeskJarvisError> {
+    start_task(
+        &window,
+        &state,
+        instruction,
+        context,
+        TaskOptions {
+            group_id,
+            max_cost_usd,
+            skip_prompt_log: skip_prompt_log.unwrap_or(false),
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+/// 逐步执行用户指令：每个步骤完成后发出 step-completed-awaiting-next，
+/// 等待 continue_task 或 abort_task 后再继续
+#[tauri::command]
+async fn execute_task_interactive(
+    window: Window,
+    state: tauri::State<'_, AppState>,
+    instruction: String,
+    context: Option<serde_json::Value>,
+    group_id: Option<String>,
+) -> Result<TaskResult, DeskJarvisError> {
+    start_task(
+        &window,
+        &state,
+        instruction,
+        context,
+        TaskOptions {
+            group_id,
+            interactive: true,
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+/// 前端发起任务：检查专注时段，补充上下文后执行
+async fn start_task(
+    window: &Window,
+    state: &AppState,
+    instruction: String,
+    context: Option<serde_json::Value>,
+    options: TaskOptions,
+) -> Result<TaskResult, DeskJarvisError> {
+    if focus::blocks_interactive() {
+        return Err(DeskJarvisError::FocusActive);
+    }
+    let instruction =
+        project_context::apply_placeholder(window.app_handle(), instruction, context.as_ref())?;
+    let request_id = history::new_task_id();
+    let context = mcp::apply_context(attachments::apply_context(context));
+    let context = active_window::apply_context(context).await;
+    run_tracked_task(window, state, request_id, instruction, context, options).await
+}
+
+/// 检查预算后执行任务并登记历史、分组与托盘状态（所有入口共用）
+async fn run_tracked_task(
+    window: &Window,
+    state: &AppState,
+    request_id: String,
+    instruction: String,
+    context: Option<serde_json::Value>,
+    options: TaskOptions,
+) -> Result<TaskResult, DeskJarvisError> {
+    let TaskOptions {
+        group_id,
+        max_cost_usd,
+        session_id,
+        skip_prompt_log,
+        interactive,
+        budget_checked,
+    } = options;
+    let app_handle = window.app_handle().clone();
+    if !budget_checked {
+        budget::admit(&app_handle, window, &state.history)?;
+    }
+    if let Some(group_id) = &group_id {
+        state.groups.add_task(group_id, &request_id)?;
+    }
+
+    tray::set_status(
+        &app_handle,
+        AgentStatus::Running {
+            instruction: instruction.clone(),
+        },
     );
 
-    // 设置当前任务ID
-    {
-        let mut current_id = state.current_task_id.lock().await;
-        *current_id = Some(request_id.clone());
+    // 简单指令直接在本地执行（见 preflight），会话和逐步执行的任务仍交给 Agent
+    let app_config = config::load_config().ok();
+    let intent = if session_id.is_none() && !interactive {
+        preflight::classify(&instruction, app_config.as_ref())
+    } else {
+        None
+    };
+
+    // 为任务创建独立工作目录，失败时退回共享沙盒；直接执行的指令不需要
+    let work_dir = if intent.is_some() {
+        None
+    } else {
+        match sandbox::create_task_dir(&request_id) {
+            Ok(dir) => Some(dir),
+            Err(e) => {
+                eprintln!("[Tauri] ⚠️ {}，使用共享沙盒目录", e);
+                None
+            }
+        }
+    };
+
+    state.history.record_start(
+        &request_id,
+        &instruction,
+        work_dir.as_ref().map(|d| d.to_string_lossy().to_string()),
+    );
+    metrics::task_started();
+    webhooks::notify(state.history.get(&request_id));
+    if let Some(group_id) = &group_id {
+        groups::emit_group_update(&app_handle, &state.groups, &state.history, group_id);
+    }
+
+    let response_language = language::response_language(&instruction, app_config.as_ref());
+    let context = clipboard::apply_context(context, &instruction, app_config.as_ref()).await;
+    let email_oauth = match intent {
+        Some(_) => None,
+        None => email_oauth::task_credential(app_config.as_ref()).await,
+    };
+    let mut request = TaskRequest {
+        id: request_id,
+        context: language::apply_hint(context, response_language),
+        instruction,
+        work_dir,
+        log_prompts: prompt_log::enabled(app_config.as_ref(), skip_prompt_log),
+        max_cost_usd: max_cost_usd.or_else(|| app_config.and_then(|c| c.max_task_cost_usd)),
+        session_id,
+        resume: None,
+        email_oauth,
+        interactive,
+    };
+    let mut result = match intent {
+        Some(intent) => Ok(preflight::execute(&request.instruction, intent)),
+        None => {
+            // 执行期间把沙盒中的文件变化实时发给前端
+            let _watch = sandbox_watch::start(window, &request.id, request.work_dir.as_deref());
+            run_with_retry(window, state, &mut request).await
+        }
+    };
+    finish_task(window, state, &request, &mut result);
+    if let Some(group_id) = &group_id {
+        groups::emit_group_update(&app_handle, &state.groups, &state.history, group_id);
     }
 
+    if let Ok(r) = &result {
+        tts::announce_result(&app_handle, r);
+    }
+
+    let status = match &result {
+        Ok(r) if r.success => AgentStatus::Idle,
+        Ok(r) => AgentStatus::Error {
+            message: r.message.clone(),
+        },
+        Err(e) => AgentStatus::Error { message: e.message() },
+    };
+    tray::set_status(&app_handle, status);
+
+    result
+}
+
+/// 任务结束后的收尾：清理审批与暂停状态，登记历史、审计和产物，发送 Webhook 通知（脱离任务结束时同样调用）
+fn finish_task(
+    window: &Window,
+    state: &AppState,
+    request: &TaskRequest,
+    result: &mut Result<TaskResult, DeskJarvisError>,
+) {
+    file_guard::clear_task(&request.id);
+    policy::clear_task(&request.id);
+    state.paused.finish(&request.id);
+
+    match &*result {
+        Ok(r) => {
+            state
+                .history
+                .record_finish(&request.id, r.success, &r.message, r.termination_reason);
+            r.record_audit(&request.id);
+        }
+        Err(e) => state.history.record_finish(&request.id, false, &e.message(), None),
+    }
+    let outcome = match &*result {
+        Ok(r) if r.termination_reason.is_some() => telemetry::TaskOutcome::Terminated,
+        Ok(r) if r.success => telemetry::TaskOutcome::Succeeded,
+        _ => telemetry::TaskOutcome::Failed,
+    };
+    let started_at = state.history.get(&request.id).map(|r| r.started_at);
+    metrics::task_finished(outcome == telemetry::TaskOutcome::Succeeded, started_at);
+    telemetry::task_finished(outcome, started_at);
+    if let Some(usage) = usage::finish(window, &request.id) {
+        state.history.record_usage(&request.id, usage);
+    }
+    if let Some(dir) = &request.work_dir {
+        let artifacts = sandbox::scan_artifacts(dir);
+        let names: Vec<String> = artifacts.iter().map(|a| a.name.clone()).collect();
+        state.history.record_artifacts(&request.id, names.clone());
+        if let Ok(r) = result {
+            r.artifacts = names;
+        }
+        window.send(
+            "task-artifacts",
+            serde_json::json!({ "request_id": request.id, "artifacts": artifacts }),
+        );
+    }
+    if let Ok(r) = result {
+        report::save_result(&request.id, r);
+    }
+    webhooks::notify(state.history.get(&request.id));
+    tray::refresh_recent_tasks(window.app_handle());
+}
+
+/// 执行任务，网络错误、限流等临时失败时从失败的步骤自动重试（见 retry）
+async fn run_with_retry(
+    window: &Window,
+    state: &AppState,
+    request: &mut TaskRequest,
+) -> Result<TaskResult, DeskJarvisError> {
+    let _running = state.running.enter(&request.id);
+    let max_attempts = retry::max_attempts();
+    let mut attempt = 0;
+    loop {
+        let result = run_task(window, state, request).await;
+        let Ok(r) = &result else {
+            return result;
+        };
+        let Some((kind, reason)) = retry::transient_failure(&request.id, r) else {
+            return result;
+        };
+        if attempt >= max_attempts || task_control::stop_requested(&request.id) {
+            return result;
+        }
+        attempt += 1;
+        let resume = retry::Resume::after(request.resume.as_ref(), r);
+        let delay = retry::delay(kind, attempt);
+        eprintln!(
+            "[Tauri] 🔁 任务 {} 临时失败（{}），{} 秒后第 {}/{} 次重试，从第 {} 步继续",
+            request.id,
+            reason,
+            delay.as_secs(),
+            attempt,
+            max_attempts,
+            resume.from_step + 1
+        );
+        window.send(
+            "task-retrying",
+            retry::RetryingEvent {
+                request_id: request.id.clone(),
+                attempt,
+                max_attempts,
+                kind,
+                reason,
+                delay_ms: delay.as_millis() as u64,
+                resume_from_step: resume.from_step,
+            },
+        );
+        // 等待期间不占用服务进程，仍可通过 stop_task 取消
+        state.running.assign(&request.id, None);
+        tokio::time::sleep(delay).await;
+        if task_control::stop_requested(&request.id) {
+            return result;
+        }
+        request.resume = Some(resume);
+    }
+}
+
+/// 执行任务：常驻进程优先，失败时降级为单次进程
+async fn run_task(
+    window: &Window,
+    state: &AppState,
+    request: &TaskRequest,
+) -> Result<TaskResult, DeskJarvisError> {
     // ---------- 尝试常驻进程模式 ----------
     {
-        let mut guard = state.server.lock().await;
+        let (worker, mut guard) = state.pool.acquire(&state.server).await;
+        state.running.assign(&request.id, Some(worker));
+        // 额外工作进程出错后不后台重启，下次分派时按需启动
+        let primary = worker == server_pool::Worker::Primary;
 
         // 确保服务进程存活
-        if let Err(e) = ensure_server_alive(&mut guard).await {
+        let alive = if primary {
+            ensure_server_alive(&mut guard).await
+        } else {
+            server_pool::ensure_worker(&mut guard).await
+        };
+        if let Err(e) = alive {
             eprintln!("[Tauri] ⚠️ 无法启动常驻服务: {}，降级为单次模式", e);
             drop(guard);
-            return execute_oneshot(&window, &instruction, &context).await;
+            state.running.assign(&request.id, None);
+            return execute_oneshot(window, request).await;
         }
 
-        let server = guard.as_mut().unwrap();
-        let _result: Result<TaskResult, String> = match execute_via_server(&window, server, &instruction, &context, &request_id).await {
+        let mut outcome = execute_via_server(window, guard.as_mut().unwrap(), request).await;
+
+        // 任务无响应且用户选择重启：重启服务后重新执行一次
+        if matches!(&outcome, Err(e) if e == liveness::RESTART_REQUESTED) {
+            *guard = None;
+            let restarted = if primary {
+                ensure_server_alive(&mut guard).await
+            } else {
+                server_pool::ensure_worker(&mut guard).await
+            };
+            outcome = match restarted {
+                Ok(()) => execute_via_server(window, guard.as_mut().unwrap(), request).await,
+                Err(e) => Err(e.message()),
+            };
+        }
+        state.pool.touch(worker);
+
+        let _result: Result<TaskResult, String> = match outcome {
             Ok(r) => {
-                // 清除当前任务ID
-                {
-                    let mut current_id = state.current_task_id.lock().await;
-                    *current_id = None;
+                // 执行中崩溃但已返回部分结果：保存崩溃报告后后台重启
+                if r.termination_reason == Some(history::TerminationReason::Crash) {
+                    eprintln!("[Tauri] ⚠️ Python 服务在执行中崩溃，返回已完成的 {} 个步骤", r.steps.len());
+                    if let Some(server) = guard.as_mut() {
+                        crash_report::capture(server, request).await;
+                    }
+                    *guard = None;
+                    if primary {
+                        spawn_background_restart(window.app_handle().clone(), Some("执行中崩溃"));
+                    }
+                    return Ok(r);
+                }
+                // 超出费用上限或用户取消无响应任务时服务进程已被中止，后台重启
+                let exited = guard
+                    .as_mut()
+                    .map(|s| matches!(s.child.try_wait(), Ok(Some(_))))
+                    .unwrap_or(false);
+                if exited {
+                    *guard = None;
+                    if primary {
+                        spawn_background_restart(window.app_handle().clone(), None);
+                    }
+                } else if primary {
+                    supervisor::record_healthy();
                 }
                 return Ok(r);
             },
             Err(ref e) if e == "PROCESS_CRASHED" => {
                 eprintln!("[Tauri] ⚠️ Python 服务在执行中崩溃");
-                *guard = None;
-                // 清除当前任务ID
-                {
-                    let mut current_id = state.current_task_id.lock().await;
-                    *current_id = None;
+                if let Some(server) = guard.as_mut() {
+                    crash_report::capture(server, request).await;
                 }
+                *guard = None;
                 // 后台静默重启
-                spawn_background_restart(window.app_handle().clone());
+                if primary {
+                    spawn_background_restart(window.app_handle().clone(), Some("执行中崩溃"));
+                }
                 Err(e.clone())
             }
             Err(e) => {
                 eprintln!("[Tauri] ⚠️ 常驻进程执行失败: {}", e);
                 // 可能是 stdin 写入失败等，标记需要重启
                 *guard = None;
-                // 清除当前任务ID
-                {
-                    let mut current_id = state.current_task_id.lock().await;
-                    *current_id = None;
+                if primary {
+                    spawn_background_restart(window.app_handle().clone(), Some(&e));
                 }
-                spawn_background_restart(window.app_handle().clone());
                 Err(e)
             }
         };
         
-        // 清除当前任务ID（如果执行失败）
-        {
-            let mut current_id = state.current_task_id.lock().await;
-            *current_id = None;
-        }
-        
         // 继续降级处理
         drop(guard);
     }
 
     // ---------- 降级为单次进程模式 ----------
     eprintln!("[Tauri] 🔄 降级为单次进程模式执行");
-    let result = execute_oneshot(&window, &instruction, &context).await;
-    
-    // 清除当前任务ID
-    {
-        let mut current_id = state.current_task_id.lock().await;
-        *current_id = None;
-    }
-    
-    result
+    state.running.assign(&request.id, None);
+    execute_oneshot(window, request).await
 }
 
-/// 停止当前正在执行的任务
+/// 停止正在执行的任务；不指定 request_id 时停止唯一正在执行的任务
 #[tauri::command]
 async fn stop_task(
     state: tauri::State<'_, AppState>,
-) -> Result<(), String> {
-    // 获取当前任务ID
-    let current_id = {
-        let current_id = state.current_task_id.lock().await;
-        current_id.clone()
+    request_id: Option<String>,
+) -> Result<(), DeskJarvisError> {
+    let task_id = match request_id {
+        Some(id) if state.running.contains(&id) => id,
+        Some(id) if state.history.get(&id).is_some() => return Err(DeskJarvisError::TaskFinished(id)),
+        Some(id) => return Err(DeskJarvisError::TaskNotFound(id)),
+        None => match state.running.ids().as_slice() {
+            [] => {
+                eprintln!("[Tauri] ⚠️ 没有正在执行的任务");
+                return Err(DeskJarvisError::NoActiveTask);
+            }
+            [id] => id.clone(),
+            _ => {
+                return Err(DeskJarvisError::InvalidInput(
+                    "有多个任务正在执行，请指定要停止的任务".to_string(),
+                ))
+            }
+        },
     };
-    
-    if let Some(task_id) = current_id {
-        eprintln!("[Tauri] 🛑 停止任务: {}", task_id);
-        
-        // 通过常驻进程发送停止命令
-        let mut guard = state.server.lock().await;
-        if let Some(server) = guard.as_mut() {
-            let cmd = serde_json::json!({
-                "cmd": "stop",
-                "id": task_id,
-            });
-            let cmd_line = cmd.to_string() + "\n";
-            
-            if let Err(e) = server.stdin.write_all(cmd_line.as_bytes()).await {
-                eprintln!("[Tauri] ⚠️ 发送停止命令失败: {}", e);
-                return Err(format!("发送停止命令失败: {}", e));
-            }
-            
-            if let Err(e) = server.stdin.flush().await {
-                eprintln!("[Tauri] ⚠️ 刷新停止命令失败: {}", e);
-                return Err(format!("刷新停止命令失败: {}", e));
-            }
-            
-            eprintln!("[Tauri] ✅ 停止命令已发送");
-            Ok(())
-        } else {
-            eprintln!("[Tauri] ⚠️ Python 服务未运行，无法发送停止命令");
-            Err("Python 服务未运行".to_string())
-        }
-    } else {
-        eprintln!("[Tauri] ⚠️ 没有正在执行的任务");
-        Err("没有正在执行的任务".to_string())
+    eprintln!("[Tauri] 🛑 停止任务: {}", task_id);
+
+    // 执行中不读取 stdin：先写入控制文件，任务在下一个步骤前（含暂停中）停止
+    if let Err(e) = task_control::send_command(&task_id, "stop") {
+        eprintln!("[Tauri] ⚠️ {}", e);
+    }
+
+    // 通过执行该任务的常驻进程发送停止命令
+    let Some(worker) = state.running.worker(&task_id) else {
+        eprintln!("[Tauri] ✅ 任务 {} 不在常驻服务中执行，已写入停止命令", task_id);
+        return Ok(());
+    };
+    let mut guard = state.pool.slot(&state.server, worker).lock().await;
+    let Some(server) = guard.as_mut() else {
+        eprintln!("[Tauri] ⚠️ Python 服务未运行，无法发送停止命令");
+        return Err(DeskJarvisError::ServerNotRunning);
+    };
+    let cmd = serde_json::json!({
+        "cmd": "stop",
+        "id": task_id,
+    });
+    let cmd_line = cmd.to_string() + "\n";
+
+    if let Err(e) = server.stdin.write_all(cmd_line.as_bytes()).await {
+        eprintln!("[Tauri] ⚠️ 发送停止命令失败: {}", e);
+        return Err(DeskJarvisError::ServerCommandFailed(format!("发送停止命令失败: {}", e)));
     }
+
+    if let Err(e) = server.stdin.flush().await {
+        eprintln!("[Tauri] ⚠️ 刷新停止命令失败: {}", e);
+        return Err(DeskJarvisError::ServerCommandFailed(format!("刷新停止命令失败: {}", e)));
+    }
+
+    eprintln!("[Tauri] ✅ 停止命令已发送");
+    Ok(())
 }
 
 // ==================== 工具函数 ====================
@@ -530,69 +1347,119 @@ fn extract_json_from_output(output: &str) -> Result<String, String> {
     Err(format!("未找到有效的 JSON 输出。输出内容: {}", output))
 }
 
-/// 获取 Python 解释器路径
-fn get_python_path() -> Result<String, String> {
-    // 按优先级查找
-    let candidates = [
-        "/usr/local/bin/python3.12",
-        "python3.12",
-        "python3",
-        "python",
-    ];
-
-    for candidate in &candidates {
-        if std::process::Command::new(candidate)
-            .arg("--version")
-            .output()
-            .is_ok()
+/// Windows 上启动 Python 时不弹出控制台窗口
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// Python 解释器：程序及其固定参数（如 Windows 的 `py -3`）
+struct PythonLauncher {
+    program: &'static str,
+    args: &'static [&'static str],
+}
+
+impl PythonLauncher {
+    /// 带固定参数的同步命令
+    fn command(&self) -> std::process::Command {
+        let mut cmd = std::process::Command::new(self.program);
+        cmd.args(self.args);
+        #[cfg(windows)]
         {
-            return Ok(candidate.to_string());
+            use std::os::windows::process::CommandExt;
+            cmd.creation_flags(CREATE_NO_WINDOW);
         }
+        cmd
     }
 
-    Err("未找到 Python 解释器，请确保已安装 Python 3.11+".to_string())
+    /// 带固定参数的异步命令
+    fn tokio_command(&self) -> TokioCommand {
+        let mut cmd = TokioCommand::new(self.program);
+        cmd.args(self.args);
+        #[cfg(windows)]
+        cmd.creation_flags(CREATE_NO_WINDOW);
+        cmd
+    }
 }
 
-/// 查找 agent 目录下的脚本文件
-fn find_script(name: &str) -> Result<String, String> {
-    let current_dir = std::env::current_dir()
-        .map_err(|e| format!("获取当前目录失败: {}", e))?;
+impl std::fmt::Display for PythonLauncher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.program)?;
+        for arg in self.args {
+            write!(f, " {}", arg)?;
+        }
+        Ok(())
+    }
+}
 
-    let exe_path = std::env::current_exe().ok();
-    let exe_dir = exe_path.as_ref().and_then(|p| p.parent());
+/// 候选解释器（按优先级）：Windows 优先使用 py 启动器
+#[cfg(windows)]
+const PYTHON_CANDIDATES: &[PythonLauncher] = &[
+    PythonLauncher { program: "py", args: &["-3"] },
+    PythonLauncher { program: "python.exe", args: &[] },
+    PythonLauncher { program: "python3", args: &[] },
+    PythonLauncher { program: "python", args: &[] },
+];
+
+#[cfg(not(windows))]
+const PYTHON_CANDIDATES: &[PythonLauncher] = &[
+    PythonLauncher { program: "/usr/local/bin/python3.12", args: &[] },
+    PythonLauncher { program: "python3.12", args: &[] },
+    PythonLauncher { program: "python3", args: &[] },
+    PythonLauncher { program: "python", args: &[] },
+];
+
+/// 获取 Python 解释器
+///
+/// 以 `--version` 能成功退出为准（Windows 应用商店的 python 占位程序会以非 0 退出）。
+fn get_python_path() -> Result<&'static PythonLauncher, DeskJarvisError> {
+    PYTHON_CANDIDATES
+        .iter()
+        .find(|candidate| {
+            candidate
+                .command()
+                .arg("--version")
+                .output()
+                .is_ok_and(|output| output.status.success())
+        })
+        .ok_or(DeskJarvisError::PythonNotFound)
+}
 
-    let mut possible_paths = Vec::new();
+/// 打包的资源目录（setup 时记录），agent/ 作为资源随应用打包
+static RESOURCE_DIR: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
 
-    // 1. 当前工作目录下
-    possible_paths.push(current_dir.join("agent").join(name));
+/// 查找 agent 目录下的脚本文件
+///
+/// 优先使用配置的 agent_path，其次是打包资源中的 agent/，
+/// 最后是开发环境（tauri dev 的工作目录为 src-tauri）下的项目 agent/ 目录。
+fn find_script(name: &str) -> Result<String, DeskJarvisError> {
+    if let Some(dir) = config::load_config()
+        .ok()
+        .and_then(|c| c.agent_path)
+        .filter(|p| !p.trim().is_empty())
+    {
+        let path = config::expand_home(dir.trim()).join(name);
+        return path
+            .canonicalize()
+            .map(|p| p.to_string_lossy().to_string())
+            .map_err(|e| DeskJarvisError::AgentScriptNotFound {
+                script: name.to_string(),
+                message: format!("配置的 agent_path 中未找到 {}（{}）: {}", name, path.display(), e),
+            });
+    }
 
-    // 2. 可执行文件目录下
-    if let Some(dir) = exe_dir {
+    let mut possible_paths = Vec::new();
+    if let Some(dir) = RESOURCE_DIR.get() {
         possible_paths.push(dir.join("agent").join(name));
-        if let Some(parent) = dir.parent() {
+    }
+    if let Ok(current_dir) = std::env::current_dir() {
+        possible_paths.push(current_dir.join("agent").join(name));
+        if let Some(parent) = current_dir.parent() {
             possible_paths.push(parent.join("agent").join(name));
         }
     }
-
-    // 3. 相对路径
-    possible_paths.push(PathBuf::from("agent").join(name));
-
-    // 4. 绝对路径（项目根目录）
-    if let Ok(home) = std::env::var("HOME") {
-        possible_paths.push(
-            PathBuf::from(&home)
-                .join("Desktop")
-                .join("DeskJarvis")
-                .join("agent")
-                .join(name),
-        );
+    if let Some(exe_dir) = std::env::current_exe().ok().as_ref().and_then(|p| p.parent()) {
+        possible_paths.push(exe_dir.join("agent").join(name));
     }
 
-    let path_strings: Vec<String> = possible_paths
-        .iter()
-        .map(|p| p.to_string_lossy().to_string())
-        .collect();
-
     for path in &possible_paths {
         if path.exists() {
             let abs_path = path
@@ -602,102 +1469,107 @@ fn find_script(name: &str) -> Result<String, String> {
         }
     }
 
-    Err(format!(
-        "未找到 {}。已尝试路径: {:?}",
-        name, path_strings
-    ))
+    let path_strings: Vec<String> = possible_paths
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    Err(DeskJarvisError::AgentScriptNotFound {
+        script: name.to_string(),
+        message: format!("未找到 {}，可在设置中指定 agent_path。已尝试路径: {:?}", name, path_strings),
+    })
 }
 
 /// 获取配置
 #[tauri::command]
-async fn get_config() -> Result<AppConfig, String> {
-    let config_path = get_config_path()?;
-
-    if !config_path.exists() {
-        return Ok(AppConfig {
-            provider: "claude".to_string(),
-            api_key: "".to_string(),
-            model: "claude-3-5-sonnet-20241022".to_string(),
-            sandbox_path: get_default_sandbox_path(),
-            auto_confirm: false,
-            log_level: "INFO".to_string(),
-            email_sender: None,
-            email_password: None,
-            email_smtp_server: Some("smtp.gmail.com".to_string()),
-            email_smtp_port: Some(587),
-        });
-    }
-
-    let content = std::fs::read_to_string(&config_path)
-        .map_err(|e| format!("读取配置文件失败: {}", e))?;
-    let config: AppConfig = serde_json::from_str(&content)
-        .map_err(|e| format!("解析配置文件失败: {}", e))?;
-    Ok(config)
+async fn get_config() -> Result<AppConfig, DeskJarvisError> {
+    config::resolve().map_err(DeskJarvisError::Config)
 }
 
 /// 保存配置
+///
+/// 保存后在后台通知常驻服务热加载（若有任务在执行，会等任务结束后再加载）；
+/// 启动环境变量有变化时改为热备重启（见 standby），备用进程按新配置重建。
 #[tauri::command]
-async fn save_config(config: AppConfig) -> Result<(), String> {
-    let config_path = get_config_path()?;
-
-    if let Some(parent) = config_path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("创建配置目录失败: {}", e))?;
-    }
+async fn save_config(app: AppHandle, config: AppConfig) -> Result<(), DeskJarvisError> {
+    let locale = config.locale.unwrap_or_default();
+    let launch_env_before = config::resolve().map(|c| launch_env::resolve(&c)).ok();
+    config::save_user_config(config).map_err(DeskJarvisError::Config)?;
+    http_api::apply_config(&app);
+    i18n::apply(&app, locale);
 
-    let content = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("序列化配置失败: {}", e))?;
-    std::fs::write(&config_path, content)
-        .map_err(|e| format!("写入配置文件失败: {}", e))?;
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        standby::discard_spare().await;
+        let launch_env_after = config::resolve().map(|c| launch_env::resolve(&c)).ok();
+        let running = state.server.lock().await.is_some();
+        if running && launch_env_before != launch_env_after {
+            if let Err(e) = standby::replace(&app, "启动环境变量已变更").await {
+                eprintln!("[Tauri] ⚠️ 按新环境变量重启 Python 服务失败: {}", e);
+            }
+        } else if let Err(e) = reload_server_config(&state).await {
+            eprintln!("[Tauri] ⚠️ 通知 Python 服务重新加载配置失败: {}", e);
+        }
+        standby::refill();
+        // 额外工作进程不热加载，结束空闲的进程，下次分派时按新配置启动
+        state.pool.shutdown_idle(true).await;
+    });
     Ok(())
 }
 
-/// 获取配置文件路径
-fn get_config_path() -> Result<PathBuf, String> {
-    let home =
-        std::env::var("HOME").map_err(|_| "无法获取 HOME 环境变量".to_string())?;
-    Ok(PathBuf::from(&home)
-        .join(".deskjarvis")
-        .join("config.json"))
-}
+/// 让常驻 Python 服务重新加载配置，返回是否已通知到运行中的服务
+///
+/// 服务未运行时无需处理，下次启动会直接读取新配置。
+/// 注意：环境变量只在进程启动时注入，修改后需重启服务才生效（save_config 会自动热备重启）。
+async fn reload_server_config(state: &AppState) -> Result<bool, DeskJarvisError> {
+    let mut guard = state.server.lock().await;
+    let Some(server) = guard.as_mut() else {
+        return Ok(false);
+    };
 
-/// 获取默认沙盒路径
-fn get_default_sandbox_path() -> String {
-    if let Ok(home) = std::env::var("HOME") {
-        format!("{}/.deskjarvis/sandbox", home)
-    } else {
-        "./sandbox".to_string()
+    let cmd = serde_json::json!({
+        "cmd": "reload_config",
+        "id": format!("reload_{}", history::now_millis()),
+    });
+    match send_control_command(server, &cmd, "reload_ack", std::time::Duration::from_secs(30)).await {
+        Ok(_) => {
+            eprintln!("[Tauri] ✅ Python 服务已重新加载配置");
+            Ok(true)
+        }
+        Err(e) if e == "PROCESS_CRASHED" => {
+            *guard = None;
+            Err(DeskJarvisError::ServerCommandFailed(
+                "Python 服务已退出，下次执行任务时会自动重启".to_string(),
+            ))
+        }
+        Err(e) => Err(DeskJarvisError::ServerCommandFailed(e)),
     }
 }
 
-/// 打开文件（使用系统默认应用）
-#[tauri::command]
-async fn open_file(path: String) -> Result<(), String> {
-    #[cfg(target_os = "macos")]
-    {
-        std::process::Command::new("open")
-            .arg(&path)
-            .spawn()
-            .map_err(|e| format!("打开文件失败: {}", e))?;
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        std::process::Command::new("explorer")
-            .arg(&path)
-            .spawn()
-            .map_err(|e| format!("打开文件失败: {}", e))?;
+/// 关闭一个服务进程：先发送 shutdown 等待应答，失败时直接结束进程
+async fn shutdown_server(mut server: PythonServer) {
+    let cmd = serde_json::json!({
+        "cmd": "shutdown",
+        "id": format!("bye_{}", history::now_millis()),
+    });
+    if send_control_command(&mut server, &cmd, "shutdown_ack", std::time::Duration::from_secs(5)).await.is_err() {
+        let _ = server.child.kill().await;
     }
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        std::process::Command::new("xdg-open")
-            .arg(&path)
-            .spawn()
-            .map_err(|e| format!("打开文件失败: {}", e))?;
+/// 重启或退出应用前关闭 Python 服务（含备用进程）
+async fn shutdown_python_servers(state: &AppState) {
+    let server = state.server.lock().await.take();
+    if let Some(server) = server {
+        shutdown_server(server).await;
     }
+    standby::discard_spare().await;
+    state.pool.shutdown_idle(true).await;
+}
 
-    Ok(())
+/// 手动让 Agent 重新加载配置
+#[tauri::command]
+async fn reload_agent_config(state: tauri::State<'_, AppState>) -> Result<bool, DeskJarvisError> {
+    reload_server_config(&state).await
 }
 
 /// 提交用户输入（用于登录、验证码等交互场景）
@@ -757,109 +1629,314 @@ async fn cancel_user_input(request_id: String) -> Result<bool, String> {
 
 // ==================== 应用入口 ====================
 
+/// 非关键子系统：主窗口加载完成后再初始化，不阻塞首屏
+fn init_deferred(app: &AppHandle) {
+    startup::phase("palette_shortcut", || {
+        if let Err(e) = window_manager::register_palette_shortcut(app) {
+            eprintln!("[Tauri] ⚠️ {}", e);
+        }
+    });
+    startup::phase("voice_shortcut", || {
+        if let Err(e) = voice::register_shortcut(app) {
+            eprintln!("[Tauri] ⚠️ {}", e);
+        }
+    });
+    startup::phase("server_watchdog", || spawn_server_watchdog(app.clone()));
+    startup::phase("resource_monitor", || resource_monitor::spawn(app.clone()));
+    startup::phase("server_pool", || server_pool::spawn_idle_reaper(app.clone()));
+    startup::phase("focus", || focus::restore(app));
+    startup::phase("scheduler", || scheduler::spawn_scheduler(app.clone()));
+    startup::phase("templates", || templates::init_tray(app));
+    startup::phase("recent_tasks", || tray::refresh_recent_tasks(app));
+    startup::phase("backup", || backup::spawn(app.clone()));
+    startup::phase("telemetry", || telemetry::init(app));
+    startup::phase("detached_tasks", || detached::resume_all(app));
+    startup::phase("mcp", mcp::spawn_refresh);
+    startup::phase("deep_link", || {
+        features::record("deep_link", deep_link::register_scheme());
+        deep_link::flush_launch_urls(app);
+    });
+}
+
 fn main() {
+    startup::mark_process_start();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // 命令行模式：不打开窗口，执行完即退出
+    if let Some(options) = cli::parse_args(&args) {
+        std::process::exit(cli::run(options));
+    }
+
+    // 已有实例在运行时，把参数转交给它后直接退出
+    let instance_listener = match instance::acquire(&args) {
+        instance::InstanceRole::Primary(listener) => Some(listener),
+        instance::InstanceRole::Secondary => {
+            eprintln!("[Tauri] DeskJarvis 已在运行，已转交给现有实例");
+            return;
+        }
+        instance::InstanceRole::Standalone => None,
+    };
+    deep_link::queue_launch_urls(&args);
+
+    startup::phase("redaction_rules", redaction::init);
+    let history = startup::phase("task_history", history::HistoryStore::load);
+    let schedules = startup::phase("schedules", scheduler::ScheduleStore::load);
+    let sessions = startup::phase("sessions", sessions::SessionStore::load);
+
     tauri::Builder::default()
-        .plugin(tauri_plugin_fs::init())
-        .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_os::init())
-        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
-        .plugin(tauri_plugin_notification::init())
         // 注入全局状态
         .manage(AppState {
             server: Mutex::new(None),
-            current_task_id: Mutex::new(None),
+            pool: server_pool::ServerPool::default(),
+            running: server_pool::RunningTasks::default(),
+            paused: task_control::PausedTasks::default(),
+            history,
+            groups: groups::GroupStore::default(),
+            schedules,
+            sessions,
         })
-        .setup(|app| {
+        .on_window_event(|window, event| {
+            if window.label() != window_manager::MAIN_LABEL {
+                return;
+            }
+            match event {
+                // 拖到主窗口的文件复制进沙盒，带入下一个任务
+                tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                    attachments::on_drop(window, paths.clone());
+                }
+                tauri::WindowEvent::CloseRequested { api, .. } => {
+                    window_manager::on_main_close_requested(window, api);
+                }
+                tauri::WindowEvent::Focused(focused) => {
+                    active_window::on_main_focus_changed(*focused);
+                }
+                tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) => {
+                    window_state::on_moved_or_resized(window);
+                }
+                _ => {}
+            }
+        })
+        .on_page_load(|webview, payload| {
+            if webview.label() == "main" && payload.event() == PageLoadEvent::Finished {
+                startup::on_window_ready(webview.app_handle(), init_deferred);
+            }
+        })
+        .setup(move |app| {
+            startup::record("tauri_init", startup::process_start());
+            supervisor::init(app.handle().clone());
+            if let Ok(dir) = app.path().resource_dir() {
+                let _ = RESOURCE_DIR.set(dir);
+            }
+
+            if let Some(listener) = instance_listener {
+                instance::spawn_listener(app.handle().clone(), listener);
+            }
+
+            // ========== 可选插件（失败时降级运行） ==========
+            startup::phase("plugins", || {
+                let handle = app.handle();
+                features::register_plugin(handle, "fs", tauri_plugin_fs::init());
+                features::register_plugin(handle, "shell", tauri_plugin_shell::init());
+                features::register_plugin(handle, "os", tauri_plugin_os::init());
+                features::register_plugin(
+                    handle,
+                    "global_shortcut",
+                    tauri_plugin_global_shortcut::Builder::new().build(),
+                );
+                features::register_plugin(handle, "notification", tauri_plugin_notification::init());
+                features::register_plugin(handle, "autostart", autostart::plugin());
+                features::register_plugin(handle, "updater", tauri_plugin_updater::Builder::new().build());
+                features::register_plugin(handle, "dialog", tauri_plugin_dialog::init());
+            });
+            startup::phase("window_state", || window_state::restore(app.handle()));
+            autostart::apply_launch_window(app.handle());
+
+            // ========== 创建系统托盘 ==========
+            startup::phase("locale", i18n::init);
+            let tray_result = startup::phase("tray", || tray::setup_tray(app));
+            features::record("tray", tray_result.map_err(|e| e.to_string()));
+
             // ========== 后台启动常驻 Python 服务 ==========
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 eprintln!("[Tauri] 🚀 正在后台启动 Python 服务...");
                 let state = app_handle.state::<AppState>();
                 let mut guard = state.server.lock().await;
+                let started = std::time::Instant::now();
                 match launch_python_server().await {
                     Ok(s) => {
                         *guard = Some(s);
+                        startup::record("python_server", started);
+                        startup::mark_python_ready();
                         eprintln!("[Tauri] ✅ Python 服务已在后台启动完成");
+                        standby::refill();
                     }
                     Err(e) => {
                         eprintln!(
                             "[Tauri] ⚠️ Python 服务后台启动失败: {}（首次任务时将自动重试）",
                             e
                         );
+                        tray::set_status(&app_handle, AgentStatus::ServerDown);
                     }
                 }
             });
 
-            // ========== 创建系统托盘 ==========
-            let show_item = MenuItemBuilder::new("显示主窗口")
-                .id("show")
-                .build(app)?;
-            let hide_item = MenuItemBuilder::new("隐藏到后台")
-                .id("hide")
-                .build(app)?;
-            let quit_item = MenuItemBuilder::new("退出 DeskJarvis")
-                .id("quit")
-                .build(app)?;
-
-            let menu = MenuBuilder::new(app)
-                .item(&show_item)
-                .item(&hide_item)
-                .separator()
-                .item(&quit_item)
-                .build()?;
-
-            let _tray = TrayIconBuilder::new()
-                .icon(app.default_window_icon().unwrap().clone())
-                .menu(&menu)
-                .tooltip("DeskJarvis - AI 桌面助手")
-                .on_menu_event(|app, event| match event.id().as_ref() {
-                    "show" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                    "hide" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.hide();
-                        }
-                    }
-                    "quit" => {
-                        app.exit(0);
-                    }
-                    _ => {}
-                })
-                .on_tray_icon_event(|tray, event| {
-                    if let TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        button_state: MouseButtonState::Up,
-                        ..
-                    } = event
-                    {
-                        let app = tray.app_handle();
-                        if let Some(window) = app.get_webview_window("main") {
-                            if window.is_visible().unwrap_or(false) {
-                                let _ = window.hide();
-                            } else {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
-                        }
-                    }
-                })
-                .build(app)?;
+            // ========== 本机 HTTP 接口（按配置开启） ==========
+            http_api::apply_config(app.handle());
+
+            // ========== 延迟初始化兜底（窗口未加载时） ==========
+            startup::schedule_deferred_fallback(app.handle().clone(), init_deferred);
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             execute_task,
+            execute_task_interactive,
             stop_task,
             get_config,
             save_config,
-            open_file,
+            reload_agent_config,
+            file_actions::open_file,
+            file_actions::reveal_in_folder,
             submit_user_input,
-            cancel_user_input
+            cancel_user_input,
+            automation_pack::export_automation_pack,
+            automation_pack::import_automation_pack,
+            settings_bundle::export_settings,
+            settings_bundle::import_settings,
+            backup::create_backup,
+            backup::list_backups,
+            backup::restore_backup,
+            backup::restart_app,
+            autostart::get_autostart_status,
+            autostart::set_autostart,
+            updater::check_for_updates,
+            updater::download_update,
+            updater::install_update_and_restart,
+            widget::show_widget,
+            widget::hide_widget,
+            widget::get_widget_state,
+            window_state::reset_window_layout,
+            voice::start_recording,
+            voice::stop_recording,
+            tts::speak,
+            tts::stop_speaking,
+            ocr::ocr_image,
+            active_window::get_active_window,
+            email_oauth::start_email_oauth,
+            email_oauth::get_email_oauth_status,
+            email_oauth::sign_out_email_oauth,
+            email_check::test_email_config,
+            imap_client::fetch_recent_emails,
+            calendar::list_upcoming_events,
+            calendar::create_calendar_event,
+            features::get_feature_availability,
+            file_guard::approve_file_access,
+            permissions::list_file_grants,
+            permissions::revoke_file_grant,
+            groups::create_task_group,
+            groups::get_group_status,
+            config::list_profiles,
+            config::save_profile,
+            config::delete_profile,
+            config::switch_profile,
+            deep_link::respond_deep_link,
+            launch_env::set_env_secret,
+            launch_env::delete_env_secret,
+            liveness::respond_stuck_task,
+            observer::set_observer_mode,
+            observer::get_observer_mode,
+            focus::start_focus_session,
+            focus::end_focus_session,
+            focus::get_focus_session,
+            project_context::get_project_context,
+            sessions::create_session,
+            sessions::execute_in_session,
+            sessions::list_sessions,
+            sessions::delete_session,
+            transcript::export_session,
+            transcript::import_session,
+            telemetry::get_telemetry_preview,
+            telemetry::set_telemetry_enabled,
+            diagnostics::run_diagnostics,
+            i18n::set_locale,
+            i18n::localize_message,
+            server_log::subscribe_server_logs,
+            supervisor::restart_python_server,
+            crash_report::list_crash_reports,
+            crash_report::open_crash_report,
+            policy::respond_confirmation,
+            policy::get_confirmation_policy,
+            policy::save_confirmation_policy,
+            provider_health::get_provider_health,
+            local_models::detect_local_models,
+            mcp::list_mcp_tools,
+            usage::get_usage_stats,
+            budget::override_budget_once,
+            proxy::test_proxy,
+            mcp::test_mcp_server,
+            history::list_task_history,
+            history::pin_task,
+            history::unpin_task,
+            history::get_task_record,
+            bulk::delete_tasks,
+            bulk::export_tasks,
+            bulk::delete_artifacts,
+            quick_eval::palette_query,
+            redaction::list_redaction_rules,
+            redaction::save_redaction_rule,
+            redaction::delete_redaction_rule,
+            redaction::validate_redaction_pattern,
+            redaction::test_redaction,
+            sandbox::list_task_artifacts,
+            sandbox::open_task_artifact,
+            undo::undo_task,
+            undo::list_trashed_files,
+            undo::restore_trashed_file,
+            open_with::list_artifact_handlers,
+            open_with::open_artifact_with,
+            task_control::pause_task,
+            task_control::resume_task,
+            task_control::continue_task,
+            task_control::abort_task,
+            prompt_log::get_task_prompts,
+            report::export_task_report,
+            warmup::warmup_agent,
+            detached::execute_task_detached,
+            detached::stop_detached_task,
+            screenshot::capture_screenshot,
+            attachments::get_attachments,
+            attachments::remove_attachment,
+            clipboard::read_clipboard,
+            clipboard::write_clipboard,
+            audit::export_audit_log,
+            audit::verify_audit_log,
+            scheduler::create_schedule,
+            scheduler::list_schedules,
+            scheduler::pause_schedule,
+            scheduler::delete_schedule,
+            scheduler::defer_task,
+            templates::list_templates,
+            templates::save_template,
+            templates::delete_template,
+            templates::run_template,
+            dry_run::plan_task,
+            dry_run::execute_plan,
+            startup::get_startup_report,
+            validation::validate_config,
+            window_manager::list_monitors,
+            window_manager::show_palette,
+            window_manager::hide_palette
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, _event| {
+            // macOS 通过系统事件送达 deskjarvis:// 链接
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = _event {
+                for url in urls {
+                    deep_link::handle_url(_app, url.as_str());
+                }
+            }
+        });
 }
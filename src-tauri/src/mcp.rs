@@ -0,0 +1,640 @@
+//! MCP 客户端：连接用户配置的 MCP 服务器（stdio 或 SSE），列出工具并代 Agent 调用
+//!
+//! 每个服务器保持一个会话（JSON-RPC 2.0），配置变化时重新连接。启动后在后台刷新一次工具列表，
+//! 执行任务时把缓存的工具写入 context.mcp_tools 供规划器选用；Agent 以 tool_call 事件
+//! （工具名 mcp，参数 {server, tool, arguments}）请求调用，由 tool_server 转到这里执行。
+//! SSE 传输只支持 http://（本机或内网服务器），https 需要通过 stdio 桥接。
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config;
+
+/// MCP 协议版本
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// 连接和列出工具的超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// 单次工具调用的超时（短于 Python 等待 tool_result 的 270 秒）
+const CALL_TIMEOUT: Duration = Duration::from_secs(240);
+
+/// tools/list 最多翻页次数
+const MAX_PAGES: usize = 20;
+
+/// 传输方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum McpTransport {
+    #[default]
+    Stdio,
+    Sse,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// MCP 服务器配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpServerConfig {
+    /// 服务器名称，Agent 调用时以此指定服务器
+    pub name: String,
+    #[serde(default)]
+    pub transport: McpTransport,
+    /// stdio：启动命令
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// stdio：额外的环境变量
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// sse：SSE 端点地址
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// MCP 工具
+#[derive(Debug, Clone, Serialize)]
+pub struct McpTool {
+    pub server: String,
+    pub name: String,
+    pub description: String,
+    pub input_schema: Value,
+}
+
+/// test_mcp_server 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct McpTestResult {
+    pub ok: bool,
+    pub message: String,
+    pub server_info: Option<Value>,
+    pub tool_count: usize,
+    pub elapsed_ms: u64,
+}
+
+/// 服务器发来的消息
+enum Incoming {
+    Message(Value),
+    Closed(String),
+}
+
+/// 发送通道
+enum Sender {
+    Stdio { child: Child, stdin: ChildStdin },
+    Sse { endpoint: String },
+}
+
+/// 一个已初始化的 MCP 会话
+struct Session {
+    config: McpServerConfig,
+    sender: Sender,
+    incoming: Receiver<Incoming>,
+    next_id: u64,
+    server_info: Option<Value>,
+}
+
+/// 已连接的会话（服务器名 → 会话）
+static SESSIONS: Mutex<Option<HashMap<String, Arc<Mutex<Session>>>>> = Mutex::new(None);
+
+/// 最近一次列出的工具（服务器名 → 工具）
+static TOOLS: Mutex<BTreeMap<String, Vec<McpTool>>> = Mutex::new(BTreeMap::new());
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if let Sender::Stdio { child, .. } = &mut self.sender {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Session {
+    /// 建立连接并完成 initialize 握手
+    fn connect(config: &McpServerConfig) -> Result<Session, String> {
+        let (sender, incoming) = match config.transport {
+            McpTransport::Stdio => spawn_stdio(config)?,
+            McpTransport::Sse => open_sse(config)?,
+        };
+        let mut session = Session {
+            config: config.clone(),
+            sender,
+            incoming,
+            next_id: 1,
+            server_info: None,
+        };
+        let result = session.request(
+            "initialize",
+            serde_json::json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "DeskJarvis", "version": env!("CARGO_PKG_VERSION") },
+            }),
+            CONNECT_TIMEOUT,
+        )?;
+        session.server_info = result.get("serverInfo").cloned();
+        session.send(&serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))?;
+        Ok(session)
+    }
+
+    /// 发送一条 JSON-RPC 消息
+    fn send(&mut self, message: &Value) -> Result<(), String> {
+        match &mut self.sender {
+            Sender::Stdio { stdin, .. } => {
+                let line = format!("{}\n", message);
+                stdin
+                    .write_all(line.as_bytes())
+                    .and_then(|_| stdin.flush())
+                    .map_err(|e| format!("发送 MCP 消息失败: {}", e))
+            }
+            Sender::Sse { endpoint } => http_post(endpoint, &message.to_string()),
+        }
+    }
+
+    /// 发送请求并等待对应 id 的响应
+    fn request(&mut self, method: &str, params: Value, timeout: Duration) -> Result<Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let message = match self.incoming.recv_timeout(remaining) {
+                Ok(Incoming::Message(message)) => message,
+                Ok(Incoming::Closed(reason)) => return Err(format!("MCP 服务器已断开: {}", reason)),
+                Err(RecvTimeoutError::Timeout) => return Err(format!("MCP 请求超时: {}", method)),
+                Err(RecvTimeoutError::Disconnected) => return Err("MCP 服务器已断开".to_string()),
+            };
+            // 服务器发起的请求（如 ping）回复空结果，通知直接忽略
+            if message.get("method").is_some() {
+                if let Some(request_id) = message.get("id") {
+                    let reply = serde_json::json!({ "jsonrpc": "2.0", "id": request_id, "result": {} });
+                    self.send(&reply)?;
+                }
+                continue;
+            }
+            if message.get("id").and_then(|v| v.as_u64()) != Some(id) {
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                let text = error.get("message").and_then(|m| m.as_str()).unwrap_or("未知错误");
+                return Err(format!("MCP 服务器返回错误: {}", text));
+            }
+            return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+        }
+    }
+
+    /// 列出全部工具（按 nextCursor 翻页）
+    fn list_tools(&mut self) -> Result<Vec<McpTool>, String> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_PAGES {
+            let params = match &cursor {
+                Some(c) => serde_json::json!({ "cursor": c }),
+                None => serde_json::json!({}),
+            };
+            let result = self.request("tools/list", params, CONNECT_TIMEOUT)?;
+            for tool in result.get("tools").and_then(|t| t.as_array()).into_iter().flatten() {
+                let Some(name) = tool.get("name").and_then(|n| n.as_str()) else {
+                    continue;
+                };
+                tools.push(McpTool {
+                    server: self.config.name.clone(),
+                    name: name.to_string(),
+                    description: tool.get("description").and_then(|d| d.as_str()).unwrap_or("").to_string(),
+                    input_schema: tool.get("inputSchema").cloned().unwrap_or(Value::Null),
+                });
+            }
+            cursor = result.get("nextCursor").and_then(|c| c.as_str()).map(str::to_string);
+            if cursor.is_none() {
+                break;
+            }
+        }
+        Ok(tools)
+    }
+}
+
+/// 启动 stdio 服务器进程，后台线程逐行读取 stdout
+fn spawn_stdio(config: &McpServerConfig) -> Result<(Sender, Receiver<Incoming>), String> {
+    let command = config
+        .command
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .ok_or("stdio 服务器缺少启动命令")?;
+    // Windows 上 npx 等是 .cmd 脚本，需要经 cmd 启动
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    };
+    #[cfg(not(windows))]
+    let mut cmd = Command::new(command);
+    cmd.args(&config.args)
+        .envs(&config.env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("启动 MCP 服务器失败: {}", e))?;
+    let stdin = child.stdin.take().ok_or("无法获取 MCP 服务器 stdin")?;
+    let stdout = child.stdout.take().ok_or("无法获取 MCP 服务器 stdout")?;
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    let _ = tx.send(Incoming::Closed(e.to_string()));
+                    return;
+                }
+            };
+            // 忽略非 JSON 输出（部分服务器会向 stdout 打印日志）
+            if let Ok(message) = serde_json::from_str::<Value>(line.trim()) {
+                if tx.send(Incoming::Message(message)).is_err() {
+                    return;
+                }
+            }
+        }
+        let _ = tx.send(Incoming::Closed("进程已退出".to_string()));
+    });
+    Ok((Sender::Stdio { child, stdin }, rx))
+}
+
+/// 拆分 http:// 地址为 (主机:端口, 路径)
+fn split_url(url: &str) -> Result<(String, String), String> {
+    let rest = match url.strip_prefix("http://") {
+        Some(rest) => rest,
+        None if url.starts_with("https://") => return Err("SSE 传输暂不支持 https，请改用 stdio 桥接".to_string()),
+        None => return Err(format!("无效的 SSE 地址: {}", url)),
+    };
+    let (host, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    if host.is_empty() {
+        return Err(format!("无效的 SSE 地址: {}", url));
+    }
+    let host = if host.contains(':') { host.to_string() } else { format!("{}:80", host) };
+    Ok((host, path.to_string()))
+}
+
+/// 把 endpoint 事件给出的地址解析为完整地址
+fn resolve_endpoint(base: &str, endpoint: &str) -> String {
+    if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+        return endpoint.to_string();
+    }
+    let origin_end = base["http://".len()..]
+        .find('/')
+        .map(|i| i + "http://".len())
+        .unwrap_or(base.len());
+    format!("{}{}", &base[..origin_end], endpoint)
+}
+
+/// 读取 HTTP 响应头，返回状态码和是否分块传输
+fn read_http_head(reader: &mut impl BufRead) -> Result<(u16, bool), String> {
+    let mut status_line = String::new();
+    reader
+        .read_line(&mut status_line)
+        .map_err(|e| format!("读取 HTTP 响应失败: {}", e))?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse::<u16>().ok())
+        .ok_or_else(|| format!("无效的 HTTP 响应: {}", status_line.trim()))?;
+    let mut chunked = false;
+    loop {
+        let mut line = String::new();
+        let n = reader
+            .read_line(&mut line)
+            .map_err(|e| format!("读取 HTTP 响应失败: {}", e))?;
+        if n == 0 || line.trim().is_empty() {
+            break;
+        }
+        let lower = line.to_ascii_lowercase();
+        if lower.starts_with("transfer-encoding:") && lower.contains("chunked") {
+            chunked = true;
+        }
+    }
+    Ok((status, chunked))
+}
+
+/// 分块传输解码后的读取器
+struct ChunkedReader<R> {
+    inner: R,
+    remaining: usize,
+    done: bool,
+}
+
+impl<R: BufRead> Read for ChunkedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.done {
+            return Ok(0);
+        }
+        if self.remaining == 0 {
+            let mut size_line = String::new();
+            // 跳过上一块结尾的空行
+            while size_line.trim().is_empty() {
+                size_line.clear();
+                if self.inner.read_line(&mut size_line)? == 0 {
+                    self.done = true;
+                    return Ok(0);
+                }
+            }
+            let size = usize::from_str_radix(size_line.trim().split(';').next().unwrap_or(""), 16)
+                .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "无效的分块大小"))?;
+            if size == 0 {
+                self.done = true;
+                return Ok(0);
+            }
+            self.remaining = size;
+        }
+        let len = buf.len().min(self.remaining);
+        let n = self.inner.read(&mut buf[..len])?;
+        if n == 0 {
+            self.done = true;
+        }
+        self.remaining -= n;
+        Ok(n)
+    }
+}
+
+/// 打开 SSE 连接，等待 endpoint 事件后由后台线程转发 message 事件
+fn open_sse(config: &McpServerConfig) -> Result<(Sender, Receiver<Incoming>), String> {
+    let url = config
+        .url
+        .as_deref()
+        .map(str::trim)
+        .filter(|u| !u.is_empty())
+        .ok_or("SSE 服务器缺少地址")?
+        .to_string();
+    let (host, path) = split_url(&url)?;
+    let mut stream = TcpStream::connect(&host).map_err(|e| format!("连接 MCP 服务器失败: {}", e))?;
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nAccept: text/event-stream\r\nCache-Control: no-cache\r\n\r\n",
+        path, host
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("连接 MCP 服务器失败: {}", e))?;
+    let mut reader = BufReader::new(stream);
+    let (status, chunked) = read_http_head(&mut reader)?;
+    if status != 200 {
+        return Err(format!("MCP 服务器返回 HTTP {}", status));
+    }
+    let body: Box<dyn BufRead + Send> = if chunked {
+        Box::new(BufReader::new(ChunkedReader { inner: reader, remaining: 0, done: false }))
+    } else {
+        Box::new(reader)
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let (endpoint_tx, endpoint_rx) = mpsc::channel::<String>();
+    std::thread::spawn(move || {
+        let mut event = String::new();
+        let mut data = String::new();
+        for line in body.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    let _ = tx.send(Incoming::Closed(e.to_string()));
+                    return;
+                }
+            };
+            if let Some(value) = line.strip_prefix("event:") {
+                event = value.trim().to_string();
+            } else if let Some(value) = line.strip_prefix("data:") {
+                if !data.is_empty() {
+                    data.push('\n');
+                }
+                data.push_str(value.trim_start());
+            } else if line.is_empty() && !data.is_empty() {
+                // 一个事件结束
+                if event == "endpoint" {
+                    let _ = endpoint_tx.send(std::mem::take(&mut data));
+                } else if let Ok(message) = serde_json::from_str::<Value>(&data) {
+                    if tx.send(Incoming::Message(message)).is_err() {
+                        return;
+                    }
+                }
+                event.clear();
+                data.clear();
+            }
+        }
+        let _ = tx.send(Incoming::Closed("SSE 连接已关闭".to_string()));
+    });
+
+    let endpoint = endpoint_rx
+        .recv_timeout(CONNECT_TIMEOUT)
+        .map_err(|_| "MCP 服务器未返回 endpoint 事件".to_string())?;
+    let endpoint = resolve_endpoint(&url, endpoint.trim());
+    Ok((Sender::Sse { endpoint }, rx))
+}
+
+/// 向 SSE 服务器的消息端点 POST 一条消息（响应通过 SSE 流返回）
+fn http_post(url: &str, body: &str) -> Result<(), String> {
+    let (host, path) = split_url(url)?;
+    let mut stream = TcpStream::connect(&host).map_err(|e| format!("发送 MCP 消息失败: {}", e))?;
+    let _ = stream.set_read_timeout(Some(CONNECT_TIMEOUT));
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        host,
+        body.len(),
+        body
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("发送 MCP 消息失败: {}", e))?;
+    let (status, _) = read_http_head(&mut BufReader::new(stream))?;
+    if !(200..300).contains(&status) {
+        return Err(format!("MCP 服务器返回 HTTP {}", status));
+    }
+    Ok(())
+}
+
+/// 读取配置中启用的服务器
+fn configured_servers() -> Vec<McpServerConfig> {
+    config::load_config()
+        .map(|c| c.mcp_servers.into_iter().filter(|s| s.enabled).collect())
+        .unwrap_or_default()
+}
+
+/// 获取服务器会话，未连接或配置已变化时重新连接
+fn session_for(config: &McpServerConfig) -> Result<Arc<Mutex<Session>>, String> {
+    let mut sessions = SESSIONS.lock().map_err(|_| "MCP 会话状态不可用".to_string())?;
+    let sessions = sessions.get_or_insert_with(HashMap::new);
+    if let Some(existing) = sessions.get(&config.name) {
+        let same = existing.lock().map(|s| s.config == *config).unwrap_or(false);
+        if same {
+            return Ok(existing.clone());
+        }
+    }
+    let session = Arc::new(Mutex::new(Session::connect(config)?));
+    sessions.insert(config.name.clone(), session.clone());
+    Ok(session)
+}
+
+/// 丢弃会话（出错后下次调用重新连接）
+fn drop_session(name: &str) {
+    if let Ok(mut sessions) = SESSIONS.lock() {
+        if let Some(sessions) = sessions.as_mut() {
+            sessions.remove(name);
+        }
+    }
+}
+
+/// 列出单个服务器的工具并更新缓存
+fn list_server_tools(config: &McpServerConfig) -> Result<Vec<McpTool>, String> {
+    let session = session_for(config)?;
+    let result = session
+        .lock()
+        .map_err(|_| "MCP 会话状态不可用".to_string())?
+        .list_tools();
+    match &result {
+        Ok(tools) => {
+            if let Ok(mut cache) = TOOLS.lock() {
+                cache.insert(config.name.clone(), tools.clone());
+            }
+        }
+        Err(_) => drop_session(&config.name),
+    }
+    result
+}
+
+/// 刷新全部已启用服务器的工具缓存（启动后在后台执行）
+pub fn refresh_all() {
+    let servers = configured_servers();
+    if let Ok(mut cache) = TOOLS.lock() {
+        cache.retain(|name, _| servers.iter().any(|s| &s.name == name));
+    }
+    for server in servers {
+        if let Err(e) = list_server_tools(&server) {
+            eprintln!("[Tauri] ⚠️ MCP 服务器 {} 不可用: {}", server.name, e);
+        }
+    }
+}
+
+/// 启动后在后台连接 MCP 服务器并缓存工具列表
+pub fn spawn_refresh() {
+    std::thread::spawn(refresh_all);
+}
+
+/// 把缓存的 MCP 工具写入任务 context.mcp_tools
+pub fn apply_context(context: Option<Value>) -> Option<Value> {
+    let tools: Vec<McpTool> = match TOOLS.lock() {
+        Ok(cache) => cache.values().flatten().cloned().collect(),
+        Err(_) => return context,
+    };
+    if tools.is_empty() {
+        return context;
+    }
+    let mut context = match context {
+        Some(Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    context.insert("mcp_tools".to_string(), serde_json::to_value(tools).unwrap_or_default());
+    Some(Value::Object(context))
+}
+
+/// 调用 MCP 工具（tool_server 的 mcp 工具），args 为 {server, tool, arguments}
+pub fn call(args: &Value) -> Result<Value, String> {
+    let server = args
+        .get("server")
+        .and_then(|s| s.as_str())
+        .ok_or("缺少参数 server")?;
+    let tool = args
+        .get("tool")
+        .and_then(|t| t.as_str())
+        .ok_or("缺少参数 tool")?;
+    let arguments = args.get("arguments").cloned().unwrap_or_else(|| serde_json::json!({}));
+    let config = configured_servers()
+        .into_iter()
+        .find(|s| s.name == server)
+        .ok_or_else(|| format!("未配置或未启用的 MCP 服务器: {}", server))?;
+    let session = session_for(&config)?;
+    let result = session
+        .lock()
+        .map_err(|_| "MCP 会话状态不可用".to_string())?
+        .request(
+            "tools/call",
+            serde_json::json!({ "name": tool, "arguments": arguments }),
+            CALL_TIMEOUT,
+        );
+    if result.is_err() {
+        drop_session(server);
+    }
+    result
+}
+
+/// 列出 MCP 工具：指定 server 时只列该服务器，否则列出全部已启用服务器
+#[tauri::command]
+pub async fn list_mcp_tools(server: Option<String>) -> Result<Vec<McpTool>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let servers: Vec<McpServerConfig> = configured_servers()
+            .into_iter()
+            .filter(|s| server.as_ref().is_none_or(|name| &s.name == name))
+            .collect();
+        if let Some(name) = &server {
+            if servers.is_empty() {
+                return Err(format!("未配置或未启用的 MCP 服务器: {}", name));
+            }
+        }
+        let mut tools = Vec::new();
+        for config in &servers {
+            match list_server_tools(config) {
+                Ok(list) => tools.extend(list),
+                // 指定服务器时返回错误，列全部时跳过不可用的服务器
+                Err(e) if server.is_some() => return Err(e),
+                Err(e) => eprintln!("[Tauri] ⚠️ MCP 服务器 {} 不可用: {}", config.name, e),
+            }
+        }
+        Ok(tools)
+    })
+    .await
+    .map_err(|e| format!("列出 MCP 工具失败: {}", e))?
+}
+
+/// 测试 MCP 服务器配置：建立独立连接、握手并列出工具（不影响已有会话）
+#[tauri::command]
+pub async fn test_mcp_server(server: McpServerConfig) -> Result<McpTestResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = Instant::now();
+        let result = Session::connect(&server).and_then(|mut session| {
+            let tools = session.list_tools()?;
+            Ok((session.server_info.clone(), tools.len()))
+        });
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        match result {
+            Ok((server_info, tool_count)) => McpTestResult {
+                ok: true,
+                message: format!("连接成功，共 {} 个工具", tool_count),
+                server_info,
+                tool_count,
+                elapsed_ms,
+            },
+            Err(e) => McpTestResult {
+                ok: false,
+                message: e,
+                server_info: None,
+                tool_count: 0,
+                elapsed_ms,
+            },
+        }
+    })
+    .await
+    .map_err(|e| format!("测试 MCP 服务器失败: {}", e))
+}
@@ -0,0 +1,177 @@
+//! 运行指标：任务数、任务耗时、Python 服务重启、排队深度和 token 用量，以 Prometheus 文本格式
+//! 通过本机 HTTP 接口的 `GET /metrics` 提供（同样需要访问令牌），便于在 Grafana 中绘图
+//!
+//! 指标只保存在内存中，应用重启后从零开始（Prometheus 的 counter 语义允许重置）。
+//! 计数在任务登记开始（run_tracked_task、脱离任务）和 finish_task 中更新，
+//! token 用量在每次模型调用上报时（usage::record）累加。
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::history::now_millis;
+
+/// 任务耗时直方图的桶上界（秒）
+const DURATION_BUCKETS: &[f64] = &[1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0, 3600.0];
+
+static TASKS_STARTED: AtomicU64 = AtomicU64::new(0);
+static TASKS_SUCCEEDED: AtomicU64 = AtomicU64::new(0);
+static TASKS_FAILED: AtomicU64 = AtomicU64::new(0);
+static TASKS_RUNNING: AtomicI64 = AtomicI64::new(0);
+static QUEUE_DEPTH: AtomicI64 = AtomicI64::new(0);
+static PYTHON_RESTARTS: AtomicU64 = AtomicU64::new(0);
+
+/// 任务耗时直方图
+struct Histogram {
+    /// 各桶计数（不累计），最后一项为 +Inf
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+static DURATIONS: Mutex<Histogram> = Mutex::new(Histogram {
+    buckets: Vec::new(),
+    sum: 0.0,
+    count: 0,
+});
+
+/// 模型 → (输入 token, 输出 token, 费用)
+static TOKENS: Mutex<BTreeMap<String, (u64, u64, f64)>> = Mutex::new(BTreeMap::new());
+
+/// 任务已登记开始
+pub fn task_started() {
+    TASKS_STARTED.fetch_add(1, Ordering::Relaxed);
+    TASKS_RUNNING.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 任务结束，started_at 为历史记录中的开始时间（毫秒时间戳）
+pub fn task_finished(success: bool, started_at: Option<u64>) {
+    let counter = if success { &TASKS_SUCCEEDED } else { &TASKS_FAILED };
+    counter.fetch_add(1, Ordering::Relaxed);
+    // 应用启动前开始的脱离任务没有登记开始，不减到负数
+    let _ = TASKS_RUNNING.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| Some((n - 1).max(0)));
+
+    let Some(started_at) = started_at else {
+        return;
+    };
+    let seconds = now_millis().saturating_sub(started_at) as f64 / 1000.0;
+    if let Ok(mut histogram) = DURATIONS.lock() {
+        if histogram.buckets.is_empty() {
+            histogram.buckets = vec![0; DURATION_BUCKETS.len() + 1];
+        }
+        let index = DURATION_BUCKETS
+            .iter()
+            .position(|&le| seconds <= le)
+            .unwrap_or(DURATION_BUCKETS.len());
+        histogram.buckets[index] += 1;
+        histogram.sum += seconds;
+        histogram.count += 1;
+    }
+}
+
+/// 等待空闲 Python 进程的任务：创建时排队深度加一，丢弃时减一
+pub struct Queued(());
+
+impl Queued {
+    pub fn enter() -> Self {
+        QUEUE_DEPTH.fetch_add(1, Ordering::Relaxed);
+        Queued(())
+    }
+}
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        QUEUE_DEPTH.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// 主 Python 服务重启成功（自动或手动）
+pub fn python_restarted() {
+    PYTHON_RESTARTS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 累加一次模型调用的 token 用量
+pub fn record_tokens(model: &str, input_tokens: u64, output_tokens: u64, cost_usd: f64) {
+    if let Ok(mut tokens) = TOKENS.lock() {
+        let entry = tokens.entry(model.to_string()).or_default();
+        entry.0 += input_tokens;
+        entry.1 += output_tokens;
+        entry.2 += cost_usd;
+    }
+}
+
+/// 标签值转义（反斜杠、双引号、换行）
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn scalar(out: &mut String, name: &str, kind: &str, help: &str, value: impl std::fmt::Display) {
+    header(out, name, kind, help);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+/// 以 Prometheus 文本格式（0.0.4）输出全部指标
+pub fn render() -> String {
+    let mut out = String::new();
+    let load = |n: &AtomicU64| n.load(Ordering::Relaxed);
+    scalar(&mut out, "deskjarvis_tasks_started_total", "counter", "已开始的任务数", load(&TASKS_STARTED));
+    scalar(&mut out, "deskjarvis_tasks_succeeded_total", "counter", "成功结束的任务数", load(&TASKS_SUCCEEDED));
+    scalar(&mut out, "deskjarvis_tasks_failed_total", "counter", "失败或提前结束的任务数", load(&TASKS_FAILED));
+    scalar(
+        &mut out,
+        "deskjarvis_tasks_running",
+        "gauge",
+        "正在执行的任务数",
+        TASKS_RUNNING.load(Ordering::Relaxed).max(0),
+    );
+    scalar(
+        &mut out,
+        "deskjarvis_task_queue_depth",
+        "gauge",
+        "等待空闲 Python 进程的任务数",
+        QUEUE_DEPTH.load(Ordering::Relaxed).max(0),
+    );
+    scalar(
+        &mut out,
+        "deskjarvis_python_restarts_total",
+        "counter",
+        "主 Python 服务的重启次数",
+        load(&PYTHON_RESTARTS),
+    );
+
+    header(&mut out, "deskjarvis_task_duration_seconds", "histogram", "任务耗时（秒）");
+    let (buckets, sum, count) = match DURATIONS.lock() {
+        Ok(h) => (h.buckets.clone(), h.sum, h.count),
+        Err(_) => (Vec::new(), 0.0, 0),
+    };
+    let mut cumulative = 0;
+    for (i, le) in DURATION_BUCKETS.iter().enumerate() {
+        cumulative += buckets.get(i).copied().unwrap_or(0);
+        let _ = writeln!(out, "deskjarvis_task_duration_seconds_bucket{{le=\"{}\"}} {}", le, cumulative);
+    }
+    let _ = writeln!(out, "deskjarvis_task_duration_seconds_bucket{{le=\"+Inf\"}} {}", count);
+    let _ = writeln!(out, "deskjarvis_task_duration_seconds_sum {}", sum);
+    let _ = writeln!(out, "deskjarvis_task_duration_seconds_count {}", count);
+
+    let tokens = TOKENS.lock().map(|t| t.clone()).unwrap_or_default();
+    header(&mut out, "deskjarvis_llm_tokens_total", "counter", "模型调用的 token 用量");
+    for (model, (input, output, _)) in &tokens {
+        let model = escape_label(model);
+        let _ = writeln!(out, "deskjarvis_llm_tokens_total{{model=\"{}\",direction=\"input\"}} {}", model, input);
+        let _ = writeln!(out, "deskjarvis_llm_tokens_total{{model=\"{}\",direction=\"output\"}} {}", model, output);
+    }
+    header(&mut out, "deskjarvis_llm_cost_usd_total", "counter", "模型调用的估算费用（美元）");
+    for (model, (_, _, cost)) in &tokens {
+        let _ = writeln!(out, "deskjarvis_llm_cost_usd_total{{model=\"{}\"}} {}", escape_label(model), cost);
+    }
+    out
+}
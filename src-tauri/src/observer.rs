@@ -0,0 +1,66 @@
+//! 只读观察模式：屏幕共享等场景下禁止 Agent 修改任何东西，只允许问答类任务
+//!
+//! 开启后 Python 发来的 confirmation_request（写文件、执行脚本、发邮件、模拟输入、系统控制）
+//! 与 file_access_request 一律拒绝，优先于确认策略，不论 Agent 如何规划。
+//! 仅对本次运行有效，重启后恢复关闭。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::event_sink::EventSink;
+use crate::tray;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// capability-blocked 事件负载
+#[derive(Debug, Clone, Serialize)]
+struct CapabilityBlocked<'a> {
+    request_id: &'a str,
+    capability: &'a str,
+    step_type: &'a str,
+}
+
+/// 是否处于观察模式
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// 切换观察模式，刷新托盘并通知前端
+pub fn set_enabled(app: &AppHandle, enabled: bool) {
+    if ENABLED.swap(enabled, Ordering::SeqCst) == enabled {
+        return;
+    }
+    eprintln!(
+        "[Tauri] 👁 观察模式已{}",
+        if enabled { "开启，禁止所有修改操作" } else { "关闭" }
+    );
+    tray::set_observer_mode(app, enabled);
+    let _ = app.emit("observer-mode-changed", enabled);
+}
+
+/// 记录并通知前端：观察模式拒绝了一个修改类步骤
+pub fn report_blocked(sink: &impl EventSink, request_id: &str, capability: &str, step_type: &str) {
+    eprintln!("[Tauri] 👁 观察模式，拒绝任务 {} 的 {} 步骤", request_id, step_type);
+    sink.send(
+        "capability-blocked",
+        CapabilityBlocked {
+            request_id,
+            capability,
+            step_type,
+        },
+    );
+}
+
+/// 开启或关闭观察模式
+#[tauri::command]
+pub async fn set_observer_mode(app: AppHandle, enabled: bool) -> Result<bool, String> {
+    set_enabled(&app, enabled);
+    Ok(is_enabled())
+}
+
+/// 查询是否处于观察模式
+#[tauri::command]
+pub async fn get_observer_mode() -> Result<bool, String> {
+    Ok(is_enabled())
+}
@@ -0,0 +1,64 @@
+//! 图片文字识别：转发给 Python 服务（ocr 命令）用 Tesseract 识别，返回全文和逐行的位置
+//!
+//! 逐行的像素坐标可用于还原表格等版面。主服务执行任务时使用空闲的额外工作进程，
+//! 都忙时排队等待（见 server_pool::send_command）。
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent_event::OcrBlock;
+use crate::{config, history, server_pool};
+
+/// 等待识别结果的超时
+const OCR_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// ocr_image 的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcrOutput {
+    /// 全文，每行一个文本块
+    pub text: String,
+    pub blocks: Vec<OcrBlock>,
+}
+
+/// ocr_result 事件中的识别结果
+#[derive(Deserialize)]
+struct OcrReply {
+    ok: bool,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    blocks: Vec<OcrBlock>,
+    message: Option<String>,
+}
+
+/// 识别图片中的文字，lang 为语言代码（如 "zh"、"en"、"zh+en"），为空时中英文混合识别
+#[tauri::command]
+pub async fn ocr_image(
+    state: tauri::State<'_, crate::AppState>,
+    path: String,
+    lang: Option<String>,
+) -> Result<OcrOutput, String> {
+    let path = config::expand_home(&path);
+    if !path.is_file() {
+        return Err(format!("图片不存在: {}", path.display()));
+    }
+    let cmd = serde_json::json!({
+        "cmd": "ocr",
+        "id": format!("ocr_{}", history::now_millis()),
+        "path": path.to_string_lossy(),
+        "lang": lang.filter(|l| !l.trim().is_empty()),
+    });
+    let reply = server_pool::send_command(&state, &cmd, "ocr_result", OCR_TIMEOUT).await?;
+    let reply: OcrReply =
+        serde_json::from_value(reply).map_err(|e| format!("解析识别结果失败: {}", e))?;
+    if !reply.ok {
+        return Err(format!(
+            "文字识别失败: {}",
+            reply.message.as_deref().unwrap_or("未知错误")
+        ));
+    }
+    eprintln!("[Tauri] 🔍 文字识别完成: {}（{} 行）", path.display(), reply.blocks.len());
+    Ok(OcrOutput {
+        text: reply.text,
+        blocks: reply.blocks,
+    })
+}
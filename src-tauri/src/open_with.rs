@@ -0,0 +1,288 @@
+//! 用指定应用打开任务产物（"打开方式…"），报告可直接交给 Excel、预览或 VS Code
+//!
+//! 能打开该文件的应用按平台查询：macOS 通过 NSWorkspace（osascript JXA），
+//! Linux 读取 xdg-mime 与各应用目录的 mimeinfo.cache，Windows 读取注册表 OpenWithProgids。
+//! 打开时只接受查询结果中的应用，不执行前端传入的任意命令。
+
+use std::path::Path;
+use serde::Serialize;
+
+use crate::sandbox;
+
+/// 可打开某类文件的应用
+#[derive(Debug, Clone, Serialize)]
+pub struct AppHandler {
+    /// 应用标识：macOS 为 .app 路径，Linux 为 .desktop 文件 ID，Windows 为 ProgID
+    pub id: String,
+    pub name: String,
+    /// 是否为该文件类型的默认应用
+    pub is_default: bool,
+}
+
+#[cfg(target_os = "macos")]
+fn handlers_for(path: &Path) -> Result<Vec<AppHandler>, String> {
+    let script = r#"ObjC.import('AppKit');
+function run(argv) {
+    var ws = $.NSWorkspace.sharedWorkspace;
+    var url = $.NSURL.fileURLWithPath(argv[0]);
+    var apps = ws.URLsForApplicationsToOpenURL(url);
+    var def = ws.URLForApplicationToOpenURL(url);
+    var out = [];
+    for (var i = 0; i < apps.count; i++) {
+        out.push(apps.objectAtIndex(i).path.js);
+    }
+    return JSON.stringify({ default: def.isNil() ? null : def.path.js, apps: out });
+}"#;
+    let output = std::process::Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", script])
+        .arg(path)
+        .output()
+        .map_err(|e| format!("查询打开方式失败: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "查询打开方式失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("解析打开方式失败: {}", e))?;
+    let default = value.get("default").and_then(|v| v.as_str());
+    Ok(value
+        .get("apps")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str())
+        .map(|app| AppHandler {
+            id: app.to_string(),
+            name: Path::new(app)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| app.to_string()),
+            is_default: Some(app) == default,
+        })
+        .collect())
+}
+
+#[cfg(target_os = "macos")]
+fn launch(handler: &AppHandler, path: &Path) -> Result<(), String> {
+    std::process::Command::new("open")
+        .arg("-a")
+        .arg(&handler.id)
+        .arg(path)
+        .spawn()
+        .map_err(|e| format!("用 {} 打开失败: {}", handler.name, e))?;
+    Ok(())
+}
+
+/// xdg 应用目录（用户目录在前）
+#[cfg(target_os = "linux")]
+fn application_dirs() -> Vec<std::path::PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".local/share")));
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    data_home
+        .into_iter()
+        .chain(data_dirs.split(':').map(std::path::PathBuf::from))
+        .map(|d| d.join("applications"))
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn xdg_mime(args: &[&str]) -> Option<String> {
+    std::process::Command::new("xdg-mime")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// .desktop 文件中 [Desktop Entry] 的 Name
+#[cfg(target_os = "linux")]
+fn desktop_name(dirs: &[std::path::PathBuf], id: &str) -> Option<String> {
+    let content = dirs
+        .iter()
+        .find_map(|dir| std::fs::read_to_string(dir.join(id)).ok())?;
+    content
+        .lines()
+        .skip_while(|line| line.trim() != "[Desktop Entry]")
+        .find_map(|line| line.strip_prefix("Name="))
+        .map(|name| name.trim().to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn handlers_for(path: &Path) -> Result<Vec<AppHandler>, String> {
+    let path_str = path.to_string_lossy();
+    let mime = xdg_mime(&["query", "filetype", &path_str])
+        .ok_or("无法识别文件类型（需要 xdg-utils）")?;
+    let default = xdg_mime(&["query", "default", &mime]);
+    let dirs = application_dirs();
+
+    let mut ids: Vec<String> = default.iter().cloned().collect();
+    for dir in &dirs {
+        let Ok(cache) = std::fs::read_to_string(dir.join("mimeinfo.cache")) else {
+            continue;
+        };
+        let entries = cache
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .filter(|(key, _)| *key == mime)
+            .flat_map(|(_, value)| value.split(';'))
+            .filter(|id| !id.is_empty());
+        for id in entries {
+            if !ids.iter().any(|existing| existing == id) {
+                ids.push(id.to_string());
+            }
+        }
+    }
+
+    Ok(ids
+        .into_iter()
+        .map(|id| AppHandler {
+            name: desktop_name(&dirs, &id)
+                .unwrap_or_else(|| id.trim_end_matches(".desktop").to_string()),
+            is_default: default.as_deref() == Some(id.as_str()),
+            id,
+        })
+        .collect())
+}
+
+#[cfg(target_os = "linux")]
+fn launch(handler: &AppHandler, path: &Path) -> Result<(), String> {
+    std::process::Command::new("gtk-launch")
+        .arg(&handler.id)
+        .arg(path)
+        .spawn()
+        .map_err(|e| format!("用 {} 打开失败: {}", handler.name, e))?;
+    Ok(())
+}
+
+/// reg query 输出的值列表：(名称, 数据)
+#[cfg(target_os = "windows")]
+fn reg_values(key: &str, extra: &[&str]) -> Vec<(String, String)> {
+    let Ok(output) = std::process::Command::new("reg")
+        .args(["query", key])
+        .args(extra)
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.starts_with(' '))
+        .filter_map(|line| {
+            let mut parts = line.trim().splitn(3, "    ");
+            let name = parts.next()?.to_string();
+            let _kind = parts.next()?;
+            Some((name, parts.next().unwrap_or("").to_string()))
+        })
+        .collect()
+}
+
+/// 注册表项的默认值
+#[cfg(target_os = "windows")]
+fn reg_default(key: &str) -> Option<String> {
+    reg_values(key, &["/ve"])
+        .into_iter()
+        .next()
+        .map(|(_, value)| value)
+        .filter(|v| !v.is_empty())
+}
+
+#[cfg(target_os = "windows")]
+fn handlers_for(path: &Path) -> Result<Vec<AppHandler>, String> {
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .ok_or("文件没有扩展名，无法查询打开方式")?;
+    let ext_key = format!("HKCR\\{}", ext);
+    let default = reg_default(&ext_key);
+
+    let mut progids: Vec<String> = default.iter().cloned().collect();
+    for (progid, _) in reg_values(&format!("{}\\OpenWithProgids", ext_key), &[]) {
+        if !progids.contains(&progid) {
+            progids.push(progid);
+        }
+    }
+    Ok(progids
+        .into_iter()
+        .filter(|progid| reg_default(&format!("HKCR\\{}\\shell\\open\\command", progid)).is_some())
+        .map(|progid| AppHandler {
+            name: reg_default(&format!("HKCR\\{}", progid)).unwrap_or_else(|| progid.clone()),
+            is_default: default.as_deref() == Some(progid.as_str()),
+            id: progid,
+        })
+        .collect())
+}
+
+#[cfg(target_os = "windows")]
+fn launch(handler: &AppHandler, path: &Path) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+
+    let command = reg_default(&format!("HKCR\\{}\\shell\\open\\command", handler.id))
+        .ok_or_else(|| format!("{} 没有注册打开命令", handler.name))?;
+    let path = path.to_string_lossy();
+    let command = command.replace("%1", &path).replace("%L", &path);
+    // 命令形如 "C:\...\app.exe" "%1"，拆出可执行文件，其余参数原样传递
+    let (exe, args) = match command.strip_prefix('"') {
+        Some(rest) => rest.split_once('"').unwrap_or((rest, "")),
+        None => command.split_once(' ').unwrap_or((command.as_str(), "")),
+    };
+    std::process::Command::new(exe)
+        .raw_arg(args.trim())
+        .spawn()
+        .map_err(|e| format!("用 {} 打开失败: {}", handler.name, e))?;
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn handlers_for(_path: &Path) -> Result<Vec<AppHandler>, String> {
+    Err("当前平台不支持选择打开方式".to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn launch(_handler: &AppHandler, _path: &Path) -> Result<(), String> {
+    Err("当前平台不支持选择打开方式".to_string())
+}
+
+/// 列出能打开任务产物的应用（默认应用在前）
+#[tauri::command]
+pub async fn list_artifact_handlers(
+    state: tauri::State<'_, crate::AppState>,
+    task_id: String,
+    name: String,
+) -> Result<Vec<AppHandler>, String> {
+    let path = sandbox::resolve_artifact(&state, &task_id, &name)?;
+    let mut handlers = tauri::async_runtime::spawn_blocking(move || handlers_for(&path))
+        .await
+        .map_err(|e| format!("查询打开方式失败: {}", e))??;
+    handlers.sort_by_key(|h| !h.is_default);
+    Ok(handlers)
+}
+
+/// 用指定应用打开任务产物，app 为 list_artifact_handlers 返回的 id
+#[tauri::command]
+pub async fn open_artifact_with(
+    state: tauri::State<'_, crate::AppState>,
+    task_id: String,
+    name: String,
+    app: String,
+) -> Result<(), String> {
+    let path = sandbox::resolve_artifact(&state, &task_id, &name)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let handler = handlers_for(&path)?
+            .into_iter()
+            .find(|h| h.id == app)
+            .ok_or_else(|| format!("{} 不能打开该文件", app))?;
+        eprintln!("[Tauri] 📂 用 {} 打开 {}", handler.name, path.display());
+        launch(&handler, &path)
+    })
+    .await
+    .map_err(|e| format!("打开文件失败: {}", e))?
+}
@@ -0,0 +1,95 @@
+//! 范围授权：用户在文件访问确认中选择“始终允许此文件夹”后保存的授权
+//!
+//! 授权保存在 ~/.deskjarvis/permissions.json，由 file_guard 与沙盒目录、
+//! 配置 file_access_allowlist 一起判断路径是否可直接放行。
+//! “仅允许这一次”不会保存，只对当次请求有效。
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::{config, history};
+
+/// 串行化读改写
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 对一个文件夹（含子目录）的授权
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderGrant {
+    pub folder: String,
+    /// 授权时间（毫秒时间戳）
+    pub granted_at: u64,
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(config::get_data_dir()?.join("permissions.json"))
+}
+
+fn load_grants() -> Result<Vec<FolderGrant>, String> {
+    let path = store_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("读取授权记录失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析授权记录失败: {}", e))
+}
+
+fn save_grants(grants: &[FolderGrant]) -> Result<(), String> {
+    let path = store_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(grants)
+        .map_err(|e| format!("序列化授权记录失败: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("保存授权记录失败: {}", e))
+}
+
+/// 已授权的文件夹
+pub fn granted_folders() -> Vec<PathBuf> {
+    match load_grants() {
+        Ok(grants) => grants.into_iter().map(|g| PathBuf::from(g.folder)).collect(),
+        Err(e) => {
+            eprintln!("[Tauri] ⚠️ {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// 始终允许访问该文件夹
+pub fn grant_folder(folder: &Path) -> Result<(), String> {
+    let _guard = STORE_LOCK.lock().map_err(|_| "授权记录状态不可用")?;
+    let folder = folder.to_string_lossy().to_string();
+    let mut grants = load_grants()?;
+    if grants.iter().any(|g| g.folder == folder) {
+        return Ok(());
+    }
+    grants.push(FolderGrant {
+        folder: folder.clone(),
+        granted_at: history::now_millis(),
+    });
+    save_grants(&grants)?;
+    eprintln!("[Tauri] ✅ 已始终允许访问文件夹: {}", folder);
+    Ok(())
+}
+
+/// 列出已保存的文件夹授权
+#[tauri::command]
+pub async fn list_file_grants() -> Result<Vec<FolderGrant>, String> {
+    load_grants()
+}
+
+/// 撤销文件夹授权
+#[tauri::command]
+pub async fn revoke_file_grant(folder: String) -> Result<(), String> {
+    let _guard = STORE_LOCK.lock().map_err(|_| "授权记录状态不可用")?;
+    let mut grants = load_grants()?;
+    let before = grants.len();
+    grants.retain(|g| g.folder != folder);
+    if grants.len() == before {
+        return Err(format!("未找到文件夹授权: {}", folder));
+    }
+    save_grants(&grants)?;
+    eprintln!("[Tauri] 🔒 已撤销文件夹授权: {}", folder);
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! 确认策略：按步骤类型或能力决定修改类步骤自动放行、询问用户还是直接拒绝
+//!
+//! Python 执行写文件、执行脚本、发邮件等步骤前发送 confirmation_request 事件。
+//! 规则按顺序匹配步骤类型（如 "file_delete"）或能力（如 "shell"），第一条命中的规则生效，
+//! 都不命中时使用 default_action。需要询问时以 needs-confirmation 事件请前端确认，
+//! 前端通过 respond_confirmation 回复。
+//! 执行任务期间 Python 服务不读取 stdin，结果与文件访问审批一样写入
+//! ~/.deskjarvis/file_access/<access_id>.json。
+//! 未配置策略时：auto_confirm 开启则全部放行，否则删除文件、发邮件、执行脚本需确认，其余放行。
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::agent_event::ConfirmationRequestEvent;
+use crate::config::{self, AppConfig};
+use crate::event_sink::EventSink;
+use crate::{file_guard, observer};
+
+/// 等待前端确认的请求（access_id → 请求）
+static PENDING: Mutex<BTreeMap<String, ConfirmationRequest>> = Mutex::new(BTreeMap::new());
+
+/// 规则动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    /// 自动放行
+    Auto,
+    /// 询问用户
+    Ask,
+    /// 直接拒绝
+    Deny,
+}
+
+/// 一条规则：target 为步骤类型或能力（file_write / shell / email / input / system）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub target: String,
+    pub action: PolicyAction,
+}
+
+/// 确认策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmationPolicy {
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+    #[serde(default = "default_action")]
+    pub default_action: PolicyAction,
+}
+
+fn default_action() -> PolicyAction {
+    PolicyAction::Auto
+}
+
+impl ConfirmationPolicy {
+    /// 内置策略：删除文件、发邮件、执行脚本需确认，其余放行
+    pub fn builtin() -> Self {
+        let ask = |target: &str| PolicyRule {
+            target: target.to_string(),
+            action: PolicyAction::Ask,
+        };
+        ConfirmationPolicy {
+            rules: vec![ask("file_delete"), ask("email"), ask("shell")],
+            default_action: PolicyAction::Auto,
+        }
+    }
+
+    /// 全部放行（auto_confirm 开启时）
+    fn allow_all() -> Self {
+        ConfirmationPolicy {
+            rules: Vec::new(),
+            default_action: PolicyAction::Auto,
+        }
+    }
+
+    /// 第一条匹配步骤类型或能力的规则生效
+    pub fn evaluate(&self, capability: &str, step_type: &str) -> PolicyAction {
+        self.rules
+            .iter()
+            .find(|r| r.target == step_type || r.target == capability)
+            .map(|r| r.action)
+            .unwrap_or(self.default_action)
+    }
+}
+
+/// 当前生效的策略：显式配置优先，否则按 auto_confirm 选择
+pub fn effective_policy(config: &AppConfig) -> ConfirmationPolicy {
+    match &config.confirmation_policy {
+        Some(policy) => policy.clone(),
+        None if config.auto_confirm => ConfirmationPolicy::allow_all(),
+        None => ConfirmationPolicy::builtin(),
+    }
+}
+
+/// needs-confirmation 事件负载
+#[derive(Debug, Clone, Serialize)]
+struct ConfirmationRequest {
+    /// 确认请求 ID（回复 respond_confirmation 时使用）
+    request_id: String,
+    task_id: String,
+    capability: String,
+    step_type: String,
+    /// 脱敏后的步骤参数摘要
+    summary: String,
+}
+
+/// 处理一条 confirmation_request 事件：按策略放行、拒绝或请前端确认
+///
+/// 观察模式优先于策略；非交互模式（命令行）无人确认，需询问的步骤直接拒绝。
+pub fn handle_confirmation_request(
+    sink: &impl EventSink,
+    task_id: &str,
+    event: &ConfirmationRequestEvent,
+) {
+    let access_id = event.access_id.clone();
+    let capability = event.capability.clone();
+    let step_type = event.step_type.clone();
+
+    let action = if observer::is_enabled() {
+        observer::report_blocked(sink, task_id, &capability, &step_type);
+        PolicyAction::Deny
+    } else {
+        let policy = config::load_config()
+            .map(|c| effective_policy(&c))
+            .unwrap_or_else(|_| ConfirmationPolicy::builtin());
+        match policy.evaluate(&capability, &step_type) {
+            PolicyAction::Ask if !sink.interactive() => {
+                eprintln!("[Tauri] 🚫 非交互模式，拒绝需确认的 {} 步骤", step_type);
+                PolicyAction::Deny
+            }
+            action => action,
+        }
+    };
+    if action != PolicyAction::Ask {
+        if action == PolicyAction::Deny {
+            eprintln!("[Tauri] 🚫 确认策略拒绝任务 {} 的 {} 步骤", task_id, step_type);
+        }
+        if let Err(e) = file_guard::answer(&access_id, action == PolicyAction::Auto) {
+            eprintln!("[Tauri] ⚠️ {}", e);
+        }
+        return;
+    }
+
+    eprintln!("[Tauri] 🔐 任务 {} 的 {} 步骤等待确认", task_id, step_type);
+    let request = ConfirmationRequest {
+        request_id: access_id.clone(),
+        task_id: task_id.to_string(),
+        capability,
+        step_type,
+        summary: event.summary.clone(),
+    };
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.insert(access_id, request.clone());
+    }
+    sink.send("needs-confirmation", request);
+}
+
+/// 任务结束后丢弃其未回复的确认请求（Python 侧已按超时或取消处理）
+pub fn clear_task(task_id: &str) {
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.retain(|_, r| r.task_id != task_id);
+    }
+}
+
+/// 回复 needs-confirmation 请求
+#[tauri::command]
+pub async fn respond_confirmation(request_id: String, approved: bool) -> Result<(), String> {
+    PENDING
+        .lock()
+        .map_err(|_| "确认请求状态不可用")?
+        .remove(&request_id)
+        .ok_or_else(|| format!("未找到确认请求: {}", request_id))?;
+    // 提示期间开启了观察模式时，批准也不生效
+    file_guard::answer(&request_id, approved && !observer::is_enabled())
+}
+
+/// 获取当前生效的确认策略
+#[tauri::command]
+pub async fn get_confirmation_policy() -> Result<ConfirmationPolicy, String> {
+    Ok(effective_policy(&config::load_config()?))
+}
+
+/// 保存确认策略；传 None 时恢复按 auto_confirm 选择的默认策略
+#[tauri::command]
+pub async fn save_confirmation_policy(policy: Option<ConfirmationPolicy>) -> Result<(), String> {
+    if let Some(policy) = &policy {
+        if policy.rules.iter().any(|r| r.target.trim().is_empty()) {
+            return Err("确认策略规则的目标不能为空".to_string());
+        }
+    }
+    let mut config = config::load_config()?;
+    config.confirmation_policy = policy;
+    config::write_config(&config)
+}
@@ -0,0 +1,168 @@
+//! 指令预检：打开常用文件夹、询问时间日期等简单指令直接在本地执行，不经过 Agent 和模型
+//!
+//! 整条指令（去掉首尾空白和句末标点）须与规则完全匹配，附带其他要求的指令仍交给 Agent。
+//! 结果与 Agent 的 TaskResult 形式相同，同样登记任务历史、审计和 Webhook。会话中的任务和
+//! 逐步执行的任务不走预检；配置 preflight_enabled 为 false 时关闭。
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use chrono::{Datelike, Local};
+use regex::Regex;
+
+use crate::config::AppConfig;
+use crate::{file_actions, i18n, StepResult, TaskResult};
+
+/// 可以直接执行的指令
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Intent {
+    /// 在文件管理器中打开常用文件夹
+    OpenFolder(Folder),
+    /// 询问当前时间或日期
+    Clock,
+}
+
+/// 常用文件夹
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Folder {
+    Downloads,
+    Desktop,
+    Documents,
+    Pictures,
+    Music,
+    Videos,
+    Home,
+}
+
+impl Folder {
+    fn parse(word: &str) -> Option<Self> {
+        Some(match word {
+            "下载" | "下載" | "download" | "downloads" => Folder::Downloads,
+            "桌面" | "desktop" => Folder::Desktop,
+            "文档" | "文檔" | "document" | "documents" => Folder::Documents,
+            "图片" | "圖片" | "picture" | "pictures" => Folder::Pictures,
+            "音乐" | "音樂" | "music" => Folder::Music,
+            "视频" | "影片" | "video" | "videos" => Folder::Videos,
+            "主目录" | "用户目录" | "home" => Folder::Home,
+            _ => return None,
+        })
+    }
+
+    fn path(self) -> Option<PathBuf> {
+        match self {
+            Folder::Downloads => dirs::download_dir(),
+            Folder::Desktop => dirs::desktop_dir(),
+            Folder::Documents => dirs::document_dir(),
+            Folder::Pictures => dirs::picture_dir(),
+            Folder::Music => dirs::audio_dir(),
+            Folder::Videos => dirs::video_dir(),
+            Folder::Home => dirs::home_dir(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Folder::Downloads => "下载文件夹",
+            Folder::Desktop => "桌面文件夹",
+            Folder::Documents => "文档文件夹",
+            Folder::Pictures => "图片文件夹",
+            Folder::Music => "音乐文件夹",
+            Folder::Videos => "视频文件夹",
+            Folder::Home => "用户主目录",
+        }
+    }
+}
+
+fn open_folder_pattern() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"^(?:请|帮我|請)?(?:打开|打開|open)\s*(?:我的|the|my)?\s*(?P<folder>[^\s]+?)\s*(?:文件夹|文件夾|目录|folder|directory)?$",
+        )
+        .expect("打开文件夹正则无效")
+    })
+}
+
+fn clock_pattern() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(concat!(
+            r"^(?:现在是|现在|当前)?(?:几点|幾點)(?:了|钟|鐘)?$",
+            r"|^(?:现在|当前)?(?:是)?什么时间(?:了)?$",
+            r"|^今天(?:是)?(?:几号|幾號|几月几号|星期几|周几|礼拜几)$",
+            r"|^what(?:'s| is) the (?:time|date)(?: today| now)?$",
+            r"|^what time is it(?: now)?$",
+            r"|^what day is (?:it|today)$",
+        ))
+        .expect("时间正则无效")
+    })
+}
+
+/// 去掉首尾空白和句末标点，英文转为小写
+fn normalize(instruction: &str) -> String {
+    instruction
+        .trim()
+        .trim_end_matches(['?', '？', '。', '.', '!', '！', '~', ' '])
+        .to_lowercase()
+}
+
+/// 判断指令能否直接执行
+pub fn classify(instruction: &str, app_config: Option<&AppConfig>) -> Option<Intent> {
+    if !app_config.and_then(|c| c.preflight_enabled).unwrap_or(true) {
+        return None;
+    }
+    let text = normalize(instruction);
+    if clock_pattern().is_match(&text) {
+        return Some(Intent::Clock);
+    }
+    let captures = open_folder_pattern().captures(&text)?;
+    Folder::parse(&captures["folder"]).map(Intent::OpenFolder)
+}
+
+const WEEKDAYS: [&str; 7] = ["星期一", "星期二", "星期三", "星期四", "星期五", "星期六", "星期日"];
+
+/// 执行预检匹配到的指令
+pub fn execute(instruction: &str, intent: Intent) -> TaskResult {
+    let (step, outcome) = match intent {
+        Intent::OpenFolder(folder) => {
+            let path = folder.path();
+            let outcome = match &path {
+                Some(path) => file_actions::open_path(path)
+                    .map(|_| format!("已打开{}: {}", folder.name(), path.display()))
+                    .map_err(|e| e.message()),
+                None => Err(format!("无法确定{}的位置", folder.name())),
+            };
+            let step = serde_json::json!({
+                "type": "open_folder",
+                "params": { "path": path },
+                "description": format!("打开{}", folder.name()),
+            });
+            (step, outcome)
+        }
+        Intent::Clock => {
+            let now = Local::now();
+            let weekday = WEEKDAYS[now.weekday().num_days_from_monday() as usize];
+            let step = serde_json::json!({
+                "type": "clock",
+                "params": {},
+                "description": "查询当前时间",
+            });
+            (step, Ok(format!("现在是 {}，{}", now.format("%Y-%m-%d %H:%M"), weekday)))
+        }
+    };
+    let success = outcome.is_ok();
+    let message = i18n::tr(&outcome.unwrap_or_else(|e| e));
+    eprintln!("[Tauri] ⚡ 预检直接执行: {}", message);
+    TaskResult {
+        success,
+        message: message.clone(),
+        steps: vec![StepResult {
+            step,
+            result: Some(serde_json::json!({ "success": success, "message": message })),
+        }],
+        user_instruction: instruction.to_string(),
+        truncated_by_budget: false,
+        termination_reason: None,
+        artifacts: Vec::new(),
+    }
+}
@@ -0,0 +1,245 @@
+//! 项目上下文：召唤快捷面板时检测前台终端或编辑器正在使用的项目目录
+//!
+//! 面板获得焦点前记录前台应用的进程和窗口标题（macOS 用 System Events，Linux 用 xdotool），
+//! 再在后台推断项目目录：优先取窗口标题中的路径，其次取该应用子进程（终端里的 shell、
+//! 编辑器的语言服务等）的工作目录，并向上查找 .git、Cargo.toml 等标记确定项目根。
+//! 结果以 project-context 事件发给面板，用户采用后作为 context.project_path 随任务传入。
+//!
+//! 指令中含 {project_path} 占位符（模板声明需要项目目录）时，执行前替换为 context 中的
+//! project_path 或最近检测到的目录；都没有时发出 project-path-required 事件请前端选择目录。
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::Serialize;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use tauri::{AppHandle, Emitter};
+
+use crate::window_manager::PALETTE_LABEL;
+
+/// 模板中表示项目目录的占位符
+pub const PLACEHOLDER: &str = "{project_path}";
+
+/// 判定项目根目录的标记文件
+const PROJECT_MARKERS: &[&str] = &[
+    ".git",
+    "Cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "go.mod",
+    "pom.xml",
+    "build.gradle",
+    ".hg",
+];
+
+/// 最近一次检测结果
+static DETECTED: Mutex<Option<ProjectContext>> = Mutex::new(None);
+
+/// 检测到的项目上下文
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectContext {
+    /// 前台应用名称
+    pub app_name: String,
+    pub window_title: String,
+    /// 项目目录，未能推断时为 None
+    pub path: Option<String>,
+    /// 推断来源：window_title 或 process_cwd
+    pub source: Option<String>,
+}
+
+/// 前台窗口
+pub(crate) struct FrontWindow {
+    pub pid: u32,
+    pub app_name: String,
+    pub title: String,
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn front_window() -> Option<FrontWindow> {
+    let script = r#"tell application "System Events"
+    set p to first application process whose frontmost is true
+    set t to ""
+    try
+        set t to name of front window of p
+    end try
+    return (unix id of p as text) & linefeed & name of p & linefeed & t
+end tell"#;
+    let output = std::process::Command::new("osascript")
+        .args(["-e", script])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut lines = text.lines();
+    Some(FrontWindow {
+        pid: lines.next()?.trim().parse().ok()?,
+        app_name: lines.next().unwrap_or_default().trim().to_string(),
+        title: lines.next().unwrap_or_default().trim().to_string(),
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn front_window() -> Option<FrontWindow> {
+    let xdotool = |query: &str| {
+        std::process::Command::new("xdotool")
+            .args(["getactivewindow", query])
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    };
+    let pid: u32 = xdotool("getwindowpid")?.parse().ok()?;
+    Some(FrontWindow {
+        pid,
+        app_name: String::new(),
+        title: xdotool("getwindowname").unwrap_or_default(),
+    })
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub(crate) fn front_window() -> Option<FrontWindow> {
+    None
+}
+
+/// 向上查找项目标记，找不到时返回原目录
+fn project_root(dir: &Path) -> PathBuf {
+    let home = dirs::home_dir();
+    for ancestor in dir.ancestors() {
+        if Some(ancestor) == home.as_deref() || ancestor.parent().is_none() {
+            break;
+        }
+        if PROJECT_MARKERS.iter().any(|m| ancestor.join(m).exists()) {
+            return ancestor.to_path_buf();
+        }
+    }
+    dir.to_path_buf()
+}
+
+/// 是否可作为项目目录（排除根目录和用户主目录）
+fn is_candidate(dir: &Path) -> bool {
+    dir.is_dir() && dir.parent().is_some() && Some(dir) != dirs::home_dir().as_deref()
+}
+
+/// 窗口标题中的路径，如终端的 "user@host: ~/code/app" 或编辑器的 "main.rs — /code/app"
+fn path_from_title(title: &str) -> Option<PathBuf> {
+    title
+        .split([':', '—', '–', '|'])
+        .chain(title.split(" - "))
+        .map(str::trim)
+        .filter_map(|segment| match segment.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().map(|h| h.join(rest)),
+            None if segment.starts_with('/') => Some(PathBuf::from(segment)),
+            None => None,
+        })
+        .map(|path| match path.parent() {
+            Some(parent) if path.is_file() => parent.to_path_buf(),
+            _ => path,
+        })
+        .find(|path| is_candidate(path))
+}
+
+/// 前台应用子进程的工作目录：优先目录名出现在窗口标题中的，其次最近启动的
+fn path_from_processes(pid: u32, title: &str) -> Option<PathBuf> {
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_cwd(UpdateKind::Always),
+    );
+
+    let root = Pid::from_u32(pid);
+    let mut candidates: Vec<(u64, PathBuf)> = system
+        .processes()
+        .values()
+        .filter(|process| {
+            let mut parent = process.parent();
+            while let Some(p) = parent {
+                if p == root {
+                    return true;
+                }
+                parent = system.process(p).and_then(|p| p.parent());
+            }
+            false
+        })
+        .filter_map(|process| {
+            let cwd = process.cwd()?;
+            is_candidate(cwd).then(|| (process.start_time(), project_root(cwd)))
+        })
+        .collect();
+    candidates.sort_by_key(|(started, _)| std::cmp::Reverse(*started));
+
+    let in_title = candidates.iter().find(|(_, dir)| {
+        dir.file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| title.contains(name))
+    });
+    in_title.or(candidates.first()).map(|(_, dir)| dir.clone())
+}
+
+/// 召唤面板前调用：记录前台窗口，后台推断项目目录后通知面板
+pub fn capture(app: &AppHandle) {
+    let Some(front) = front_window() else {
+        return;
+    };
+    // 面板或主窗口已在前台时沿用上次检测结果
+    if front.pid == std::process::id() {
+        return;
+    }
+    crate::active_window::record(&front);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let (path, source) = match path_from_title(&front.title) {
+            Some(dir) => (Some(project_root(&dir)), Some("window_title")),
+            None => match path_from_processes(front.pid, &front.title) {
+                Some(dir) => (Some(dir), Some("process_cwd")),
+                None => (None, None),
+            },
+        };
+        let context = ProjectContext {
+            app_name: front.app_name,
+            window_title: front.title,
+            path: path.map(|p| p.to_string_lossy().to_string()),
+            source: source.map(str::to_string),
+        };
+        if let Some(path) = &context.path {
+            eprintln!("[Tauri] 📁 检测到当前项目: {}", path);
+        }
+        let _ = app.emit_to(PALETTE_LABEL, "project-context", &context);
+        if let Ok(mut detected) = DETECTED.lock() {
+            *detected = Some(context);
+        }
+    });
+}
+
+/// 替换指令中的 {project_path} 占位符；需要项目目录却未检测到时通知前端并返回错误
+pub fn apply_placeholder(
+    app: &AppHandle,
+    instruction: String,
+    context: Option<&serde_json::Value>,
+) -> Result<String, String> {
+    if !instruction.contains(PLACEHOLDER) {
+        return Ok(instruction);
+    }
+    let path = context
+        .and_then(|c| c.get("project_path"))
+        .and_then(|v| v.as_str())
+        .filter(|p| !p.trim().is_empty())
+        .map(str::to_string)
+        .or_else(|| DETECTED.lock().ok()?.as_ref()?.path.clone());
+    match path {
+        Some(path) => Ok(instruction.replace(PLACEHOLDER, &path)),
+        None => {
+            let _ = app.emit(
+                "project-path-required",
+                serde_json::json!({ "instruction": instruction }),
+            );
+            Err("该指令需要项目目录，但未检测到当前项目，请选择项目目录后重试".to_string())
+        }
+    }
+}
+
+/// 最近一次检测到的项目上下文
+#[tauri::command]
+pub async fn get_project_context() -> Result<Option<ProjectContext>, String> {
+    Ok(DETECTED.lock().ok().and_then(|d| d.clone()))
+}
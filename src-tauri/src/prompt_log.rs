@@ -0,0 +1,152 @@
+//! 提示词日志：按任务保存 Agent 上报的完整提示词与模型响应，便于排查规划问题
+//!
+//! Python 服务每次调用模型后发出 prompt 事件，execute_via_server 按脱敏规则处理后追加到
+//! ~/.deskjarvis/prompt_logs/<task_id>.jsonl。单条内容、单个任务和整个目录都有大小上限，
+//! 超出目录上限时删除最早的任务日志。配置 prompt_log_enabled 为 false 时全局关闭，
+//! 执行任务时也可单独关闭；关闭后 Python 端不上报 prompt 事件。
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::agent_event::PromptEvent;
+use crate::{config, redaction};
+
+/// 单条消息或响应的最大长度（字符）
+const MAX_CONTENT_CHARS: usize = 32_000;
+
+/// 单个任务日志的最大大小（字节），超出后不再追加
+const MAX_TASK_BYTES: u64 = 2 * 1024 * 1024;
+
+/// 日志目录的最大总大小（字节），超出时删除最早的任务日志
+const MAX_TOTAL_BYTES: u64 = 50 * 1024 * 1024;
+
+/// 串行化追加和清理
+static APPEND_LOCK: Mutex<()> = Mutex::new(());
+
+/// 任务是否记录提示词：全局开关（默认开启）且任务未单独关闭
+pub fn enabled(app_config: Option<&config::AppConfig>, skip: bool) -> bool {
+    !skip
+        && app_config
+            .and_then(|c| c.prompt_log_enabled)
+            .unwrap_or(true)
+}
+
+fn log_dir() -> Result<PathBuf, String> {
+    Ok(config::get_data_dir()?.join("prompt_logs"))
+}
+
+/// 任务日志路径，task_id 只允许字母、数字、下划线和连字符
+fn log_path(task_id: &str) -> Result<PathBuf, String> {
+    let valid = !task_id.is_empty()
+        && task_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(format!("无效的任务 ID: {}", task_id));
+    }
+    Ok(log_dir()?.join(format!("{}.jsonl", task_id)))
+}
+
+/// 脱敏并截断，返回是否截断过
+fn sanitize(text: &mut String) -> bool {
+    *text = redaction::redact(text);
+    if text.chars().count() <= MAX_CONTENT_CHARS {
+        return false;
+    }
+    let mut truncated: String = text.chars().take(MAX_CONTENT_CHARS).collect();
+    truncated.push('…');
+    *text = truncated;
+    true
+}
+
+/// 目录总大小超出上限时，从最早修改的任务日志开始删除（保留当前任务）
+fn enforce_total_cap(dir: &std::path::Path, keep: &std::path::Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<(std::time::SystemTime, u64, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some((meta.modified().ok()?, meta.len(), entry.path()))
+        })
+        .collect();
+    let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+    files.sort_by_key(|(modified, _, _)| *modified);
+    for (_, len, path) in files {
+        if total <= MAX_TOTAL_BYTES {
+            break;
+        }
+        if path == keep {
+            continue;
+        }
+        if std::fs::remove_file(&path).is_ok() {
+            total = total.saturating_sub(len);
+        }
+    }
+}
+
+/// 追加一条提示词记录
+pub fn append(task_id: &str, event: &PromptEvent) -> Result<(), String> {
+    let _guard = APPEND_LOCK.lock().map_err(|_| "提示词日志状态不可用")?;
+    let path = log_path(task_id)?;
+    let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    if size >= MAX_TASK_BYTES {
+        return Ok(());
+    }
+
+    let mut entry = event.clone();
+    entry.id = Some(task_id.to_string());
+    let mut truncated = false;
+    for message in &mut entry.messages {
+        truncated |= sanitize(&mut message.content);
+    }
+    if let Some(response) = &mut entry.response {
+        truncated |= sanitize(response);
+    }
+    entry.truncated = truncated;
+
+    let line =
+        serde_json::to_string(&entry).map_err(|e| format!("序列化提示词记录失败: {}", e))?;
+    let dir = log_dir()?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("打开提示词日志失败: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("写入提示词日志失败: {}", e))?;
+    if size + line.len() as u64 + 1 >= MAX_TASK_BYTES {
+        eprintln!("[Tauri] ⚠️ 任务 {} 的提示词日志已达上限，后续调用不再记录", task_id);
+    }
+
+    // 每个任务的第一条记录时检查目录总大小
+    if size == 0 {
+        enforce_total_cap(&dir, &path);
+    }
+    Ok(())
+}
+
+/// 删除任务的提示词日志（删除任务历史时调用）
+pub fn remove(task_id: &str) {
+    if let Ok(path) = log_path(task_id) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// 读取任务的全部提示词记录，任务未记录时返回空列表
+#[tauri::command]
+pub async fn get_task_prompts(task_id: String) -> Result<Vec<PromptEvent>, String> {
+    let path = log_path(&task_id)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("读取提示词日志失败: {}", e))?;
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
@@ -0,0 +1,182 @@
+//! 提供商健康状况：按提供商统计最近模型调用的失败率和耗时
+//!
+//! Python 服务每次调用模型后上报 api_call 事件（提供商、模型、耗时、失败原因），
+//! 这里按提供商保留最近的调用样本，供前端在提交长任务前提示“某提供商异常”。
+//! 仅保存在内存中，重启后重新统计。
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use serde::Serialize;
+
+use crate::agent_event::ApiCallEvent;
+use crate::history;
+
+/// 每个提供商保留的最近调用数
+const MAX_SAMPLES: usize = 50;
+
+/// 只统计该时长内的调用（毫秒）
+const WINDOW_MILLIS: u64 = 30 * 60 * 1000;
+
+/// 失败率达到该值视为不可用
+const DOWN_ERROR_RATE: f64 = 0.5;
+
+/// 失败率达到该值视为不稳定
+const DEGRADED_ERROR_RATE: f64 = 0.2;
+
+/// 耗时中位数超过该值视为不稳定（毫秒）
+const SLOW_LATENCY_MS: u64 = 30_000;
+
+/// 最近连续失败达到该次数视为不可用
+const DOWN_CONSECUTIVE_FAILURES: usize = 3;
+
+static SAMPLES: Mutex<BTreeMap<String, VecDeque<CallSample>>> = Mutex::new(BTreeMap::new());
+
+/// 一次模型调用
+#[derive(Debug, Clone)]
+struct CallSample {
+    at: u64,
+    model: String,
+    latency_ms: u64,
+    error: Option<String>,
+}
+
+/// 健康状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// 近期无调用记录
+    Unknown,
+    Healthy,
+    Degraded,
+    Down,
+}
+
+/// 最近一次失败
+#[derive(Debug, Clone, Serialize)]
+pub struct LastFailure {
+    /// 失败时间（毫秒时间戳）
+    pub at: u64,
+    pub model: String,
+    pub reason: String,
+}
+
+/// 单个提供商的健康统计
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderHealth {
+    pub provider: String,
+    pub status: HealthStatus,
+    /// 统计窗口内的调用数
+    pub calls: usize,
+    pub errors: usize,
+    pub error_rate: f64,
+    /// 成功调用耗时中位数（毫秒）
+    pub p50_latency_ms: Option<u64>,
+    pub p95_latency_ms: Option<u64>,
+    pub last_failure: Option<LastFailure>,
+}
+
+/// 记录一条 api_call 事件
+pub fn record(event: &ApiCallEvent) {
+    let provider = event.provider.as_str();
+    if provider.is_empty() {
+        return;
+    }
+    let sample = CallSample {
+        at: history::now_millis(),
+        model: event.model.clone(),
+        latency_ms: event.latency_ms,
+        error: (!event.ok).then(|| match event.error.as_deref() {
+            None | Some("") => "未知错误".to_string(),
+            Some(reason) => reason.to_string(),
+        }),
+    };
+    if let Some(reason) = &sample.error {
+        eprintln!("[Tauri] ⚠️ {} 调用失败: {}", provider, reason);
+    }
+
+    if let Ok(mut samples) = SAMPLES.lock() {
+        let recent = samples.entry(provider.to_string()).or_default();
+        recent.push_back(sample);
+        while recent.len() > MAX_SAMPLES {
+            recent.pop_front();
+        }
+    }
+}
+
+/// 已排序数组的分位数
+fn percentile(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted.get(index).copied()
+}
+
+/// 汇总单个提供商的样本
+fn summarize(provider: &str, samples: &VecDeque<CallSample>, now: u64) -> ProviderHealth {
+    let recent: Vec<&CallSample> = samples
+        .iter()
+        .filter(|s| now.saturating_sub(s.at) <= WINDOW_MILLIS)
+        .collect();
+    let errors = recent.iter().filter(|s| s.error.is_some()).count();
+    let error_rate = if recent.is_empty() {
+        0.0
+    } else {
+        errors as f64 / recent.len() as f64
+    };
+    let mut latencies: Vec<u64> = recent
+        .iter()
+        .filter(|s| s.error.is_none())
+        .map(|s| s.latency_ms)
+        .collect();
+    latencies.sort_unstable();
+    let p50 = percentile(&latencies, 0.5);
+    let consecutive_failures = recent
+        .iter()
+        .rev()
+        .take_while(|s| s.error.is_some())
+        .count();
+
+    let status = if recent.is_empty() {
+        HealthStatus::Unknown
+    } else if consecutive_failures >= DOWN_CONSECUTIVE_FAILURES
+        || (recent.len() >= 2 && error_rate >= DOWN_ERROR_RATE)
+    {
+        HealthStatus::Down
+    } else if error_rate >= DEGRADED_ERROR_RATE || p50.is_some_and(|l| l > SLOW_LATENCY_MS) {
+        HealthStatus::Degraded
+    } else {
+        HealthStatus::Healthy
+    };
+
+    // 最近一次失败不受统计窗口限制，便于展示“上次出错”
+    let last_failure = samples.iter().rev().find_map(|s| {
+        s.error.as_ref().map(|reason| LastFailure {
+            at: s.at,
+            model: s.model.clone(),
+            reason: reason.clone(),
+        })
+    });
+
+    ProviderHealth {
+        provider: provider.to_string(),
+        status,
+        calls: recent.len(),
+        errors,
+        error_rate,
+        p50_latency_ms: p50,
+        p95_latency_ms: percentile(&latencies, 0.95),
+        last_failure,
+    }
+}
+
+/// 获取各提供商的健康统计（按提供商名排序）
+#[tauri::command]
+pub async fn get_provider_health() -> Result<Vec<ProviderHealth>, String> {
+    let samples = SAMPLES.lock().map_err(|_| "提供商健康状态不可用")?;
+    let now = history::now_millis();
+    Ok(samples
+        .iter()
+        .map(|(provider, recent)| summarize(provider, recent, now))
+        .collect())
+}
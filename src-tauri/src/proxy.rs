@@ -0,0 +1,234 @@
+//! 代理设置：把 proxy_url / no_proxy 以环境变量传给 Agent 进程，并提供连通性测试
+//!
+//! Agent 使用的 HTTP 客户端会读取 HTTP_PROXY / HTTPS_PROXY / ALL_PROXY / NO_PROXY，
+//! 支持 http:// 和 socks5:// 代理（地址中可带 user:pass@）。test_proxy 经代理与提供商
+//! API 的 443 端口建立隧道（HTTP CONNECT 或 SOCKS5），只验证能否连通，不发起 API 请求。
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use serde::Serialize;
+
+use crate::config::{self, AppConfig};
+
+/// 连接与握手超时
+const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 各提供商的默认 API 主机
+const PROVIDER_HOSTS: &[(&str, &str)] = &[
+    ("claude", "api.anthropic.com"),
+    ("anthropic", "api.anthropic.com"),
+    ("openai", "api.openai.com"),
+    ("chatgpt", "api.openai.com"),
+    ("deepseek", "api.deepseek.com"),
+    ("grok", "api.x.ai"),
+];
+
+/// test_proxy 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyTestResult {
+    pub ok: bool,
+    pub message: String,
+    /// 测试的目标（主机:端口）
+    pub target: String,
+    /// 是否经过代理（目标命中 no_proxy 或未配置代理时为 false）
+    pub via_proxy: bool,
+    pub elapsed_ms: u64,
+}
+
+/// 解析后的代理地址
+struct ProxyUrl {
+    socks: bool,
+    host: String,
+    port: u16,
+    credentials: Option<(String, String)>,
+}
+
+fn parse_proxy(url: &str) -> Result<ProxyUrl, String> {
+    let (scheme, rest) = url.split_once("://").ok_or_else(|| format!("无效的代理地址: {}", url))?;
+    let (socks, default_port) = match scheme.to_ascii_lowercase().as_str() {
+        "http" => (false, 80),
+        "socks5" | "socks5h" => (true, 1080),
+        _ => return Err(format!("不支持的代理类型: {}（支持 http、socks5）", scheme)),
+    };
+    let authority = rest.split('/').next().unwrap_or("");
+    let (credentials, host_port) = match authority.rsplit_once('@') {
+        Some((auth, host_port)) => {
+            let (user, pass) = auth.split_once(':').unwrap_or((auth, ""));
+            (Some((user.to_string(), pass.to_string())), host_port)
+        }
+        None => (None, authority),
+    };
+    let (host, port) = match host_port.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse::<u16>().map_err(|_| format!("无效的代理端口: {}", port))?,
+        ),
+        None => (host_port, default_port),
+    };
+    if host.is_empty() {
+        return Err(format!("无效的代理地址: {}", url));
+    }
+    Ok(ProxyUrl {
+        socks,
+        host: host.to_string(),
+        port,
+        credentials,
+    })
+}
+
+/// 主机是否命中 no_proxy（逗号分隔，支持 "*"、域名后缀和 ".example.com"）
+fn bypasses(host: &str, no_proxy: Option<&str>) -> bool {
+    let host = host.to_ascii_lowercase();
+    no_proxy
+        .unwrap_or("")
+        .split(',')
+        .map(|p| p.trim().trim_start_matches('.').to_ascii_lowercase())
+        .filter(|p| !p.is_empty())
+        .any(|p| p == "*" || host == p || host.ends_with(&format!(".{}", p)))
+}
+
+/// 启动 Agent 进程时注入的代理环境变量（大小写两种写法都设置）
+pub fn env_vars(config: &AppConfig) -> Vec<(String, String)> {
+    let mut vars = Vec::new();
+    if let Some(url) = config.proxy_url.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+        for name in ["HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY"] {
+            vars.push((name.to_string(), url.to_string()));
+            vars.push((name.to_ascii_lowercase(), url.to_string()));
+        }
+    }
+    if let Some(list) = config.no_proxy.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        vars.push(("NO_PROXY".to_string(), list.to_string()));
+        vars.push(("no_proxy".to_string(), list.to_string()));
+    }
+    vars
+}
+
+/// 当前提供商 API 的主机和端口
+fn provider_target(config: &AppConfig) -> Result<(String, u16), String> {
+    if let Some(url) = config.base_url.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+        let (scheme, rest) = url.split_once("://").ok_or_else(|| format!("无效的 API 地址: {}", url))?;
+        let authority = rest.split('/').next().unwrap_or("");
+        let default_port = if scheme.eq_ignore_ascii_case("http") { 80 } else { 443 };
+        return Ok(match authority.rsplit_once(':') {
+            Some((host, port)) => (host.to_string(), port.parse().unwrap_or(default_port)),
+            None => (authority.to_string(), default_port),
+        });
+    }
+    let provider = config.provider.trim().to_lowercase();
+    PROVIDER_HOSTS
+        .iter()
+        .find(|(name, _)| *name == provider)
+        .map(|(_, host)| (host.to_string(), 443))
+        .ok_or_else(|| format!("无法确定提供商 {} 的 API 地址，请设置 base_url", config.provider))
+}
+
+fn connect(host: &str, port: u16) -> Result<TcpStream, String> {
+    let addr = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("解析 {} 失败: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("解析 {} 失败", host))?;
+    let stream = TcpStream::connect_timeout(&addr, TEST_TIMEOUT).map_err(|e| format!("连接 {}:{} 失败: {}", host, port, e))?;
+    let _ = stream.set_read_timeout(Some(TEST_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(TEST_TIMEOUT));
+    Ok(stream)
+}
+
+/// 经 HTTP 代理 CONNECT 到目标
+fn http_connect(proxy: &ProxyUrl, host: &str, port: u16) -> Result<(), String> {
+    let mut stream = connect(&proxy.host, proxy.port)?;
+    let mut request = format!("CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n");
+    if let Some((user, pass)) = &proxy.credentials {
+        let token = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, pass));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", token));
+    }
+    request.push_str("\r\n");
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("发送 CONNECT 失败: {}", e))?;
+    let mut status_line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut status_line)
+        .map_err(|e| format!("读取代理响应失败: {}", e))?;
+    match status_line.split_whitespace().nth(1) {
+        Some("200") => Ok(()),
+        Some("407") => Err("代理需要认证或用户名密码错误（HTTP 407）".to_string()),
+        Some(code) => Err(format!("代理拒绝连接 {}:{}（HTTP {}）", host, port, code)),
+        None => Err("代理返回了无效的响应".to_string()),
+    }
+}
+
+/// 经 SOCKS5 代理连接目标（由代理解析域名）
+fn socks5_connect(proxy: &ProxyUrl, host: &str, port: u16) -> Result<(), String> {
+    let mut stream = connect(&proxy.host, proxy.port)?;
+    let io_err = |e: std::io::Error| format!("SOCKS5 握手失败: {}", e);
+    let method: u8 = if proxy.credentials.is_some() { 0x02 } else { 0x00 };
+    stream.write_all(&[0x05, 0x01, method]).map_err(io_err)?;
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).map_err(io_err)?;
+    if reply[1] != method {
+        return Err("SOCKS5 代理不接受所用的认证方式".to_string());
+    }
+    if let Some((user, pass)) = &proxy.credentials {
+        let mut auth = vec![0x01, user.len() as u8];
+        auth.extend_from_slice(user.as_bytes());
+        auth.push(pass.len() as u8);
+        auth.extend_from_slice(pass.as_bytes());
+        stream.write_all(&auth).map_err(io_err)?;
+        stream.read_exact(&mut reply).map_err(io_err)?;
+        if reply[1] != 0x00 {
+            return Err("SOCKS5 用户名或密码错误".to_string());
+        }
+    }
+    let mut request = vec![0x05, 0x01, 0x00, 0x03, host.len() as u8];
+    request.extend_from_slice(host.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).map_err(io_err)?;
+    let mut head = [0u8; 4];
+    stream.read_exact(&mut head).map_err(io_err)?;
+    if head[1] != 0x00 {
+        return Err(format!("SOCKS5 代理无法连接 {}:{}（错误码 {}）", host, port, head[1]));
+    }
+    Ok(())
+}
+
+/// 测试能否经代理（或直连）连通当前提供商的 API
+///
+/// proxy_url / no_proxy 未传时使用已保存的配置，便于保存前先测试。
+#[tauri::command]
+pub async fn test_proxy(proxy_url: Option<String>, no_proxy: Option<String>) -> Result<ProxyTestResult, String> {
+    let config = config::load_config()?;
+    let proxy_url = proxy_url.or(config.proxy_url.clone()).filter(|u| !u.trim().is_empty());
+    let no_proxy = no_proxy.or(config.no_proxy.clone());
+    let (host, port) = provider_target(&config)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = Instant::now();
+        let proxy = match proxy_url.as_deref().map(|u| parse_proxy(u.trim())).transpose() {
+            Ok(proxy) => proxy.filter(|_| !bypasses(&host, no_proxy.as_deref())),
+            Err(e) => return Err(e),
+        };
+        let result = match &proxy {
+            Some(p) if p.socks => socks5_connect(p, &host, port),
+            Some(p) => http_connect(p, &host, port),
+            None => connect(&host, port).map(|_| ()),
+        };
+        let target = format!("{}:{}", host, port);
+        let via = if proxy.is_some() { "经代理" } else { "直连" };
+        Ok(ProxyTestResult {
+            ok: result.is_ok(),
+            message: match result {
+                Ok(()) => format!("{}连通 {}", via, target),
+                Err(e) => e,
+            },
+            target,
+            via_proxy: proxy.is_some(),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    })
+    .await
+    .map_err(|e| format!("测试代理失败: {}", e))?
+}
@@ -0,0 +1,435 @@
+//! 快捷面板本地速算：算式、百分比和单位换算直接在本地计算，不经过 LLM
+//!
+//! 支持 "(3+4)*2"、"2^10"、"sqrt(2)"、"15% of 2380"、"2380 的 15%"、
+//! "2.5 inch to cm"、"5公斤转磅"、"100 f to c" 等。无法识别时交给 Agent 执行。
+
+use std::sync::OnceLock;
+use regex::Regex;
+use serde::Serialize;
+use tauri::Window;
+
+/// 速算结果类别
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuickKind {
+    Math,
+    Percent,
+    Unit,
+}
+
+/// 本地速算结果
+#[derive(Debug, Clone, Serialize)]
+pub struct QuickAnswer {
+    pub kind: QuickKind,
+    /// 规范化后的算式，如 "2.5 in → cm"
+    pub expression: String,
+    pub value: f64,
+    /// 展示文本，如 "6.35 cm"
+    pub answer: String,
+}
+
+/// 快捷面板查询结果：本地速算或 Agent 执行
+#[derive(Debug, Serialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum PaletteResult {
+    /// 本地速算（fast path），未调用 LLM
+    Local { answer: QuickAnswer },
+    /// 交给 Agent 执行
+    Agent { result: crate::TaskResult },
+}
+
+/// 单位量纲
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Dimension {
+    Length,
+    Mass,
+    Volume,
+    Data,
+    Temperature,
+}
+
+/// (别名, 量纲, 显示名, 换算到基准单位的系数)；温度的系数不使用
+const UNITS: &[(&[&str], Dimension, &str, f64)] = &[
+    (&["mm", "毫米"], Dimension::Length, "mm", 0.001),
+    (&["cm", "厘米"], Dimension::Length, "cm", 0.01),
+    (&["m", "meter", "meters", "米"], Dimension::Length, "m", 1.0),
+    (&["km", "公里", "千米"], Dimension::Length, "km", 1000.0),
+    (&["in", "inch", "inches", "\"", "英寸"], Dimension::Length, "in", 0.0254),
+    (&["ft", "foot", "feet", "'", "英尺"], Dimension::Length, "ft", 0.3048),
+    (&["yd", "yard", "yards", "码"], Dimension::Length, "yd", 0.9144),
+    (&["mi", "mile", "miles", "英里"], Dimension::Length, "mi", 1609.344),
+    (&["mg", "毫克"], Dimension::Mass, "mg", 0.001),
+    (&["g", "gram", "grams", "克"], Dimension::Mass, "g", 1.0),
+    (&["kg", "kilogram", "kilograms", "千克", "公斤"], Dimension::Mass, "kg", 1000.0),
+    (&["斤"], Dimension::Mass, "斤", 500.0),
+    (&["lb", "lbs", "pound", "pounds", "磅"], Dimension::Mass, "lb", 453.59237),
+    (&["oz", "ounce", "ounces", "盎司"], Dimension::Mass, "oz", 28.349523125),
+    (&["ml", "毫升"], Dimension::Volume, "ml", 0.001),
+    (&["l", "liter", "liters", "litre", "升"], Dimension::Volume, "L", 1.0),
+    (&["gal", "gallon", "gallons", "加仑"], Dimension::Volume, "gal", 3.785411784),
+    (&["cup", "cups", "杯"], Dimension::Volume, "cup", 0.2365882365),
+    (&["b", "byte", "bytes", "字节"], Dimension::Data, "B", 1.0),
+    (&["kb"], Dimension::Data, "KB", 1024.0),
+    (&["mb"], Dimension::Data, "MB", 1024.0 * 1024.0),
+    (&["gb"], Dimension::Data, "GB", 1024.0 * 1024.0 * 1024.0),
+    (&["tb"], Dimension::Data, "TB", 1024.0 * 1024.0 * 1024.0 * 1024.0),
+    (&["c", "°c", "celsius", "摄氏度"], Dimension::Temperature, "°C", 0.0),
+    (&["f", "°f", "fahrenheit", "华氏度"], Dimension::Temperature, "°F", 0.0),
+    (&["k", "kelvin", "开尔文"], Dimension::Temperature, "K", 0.0),
+];
+
+fn find_unit(name: &str) -> Option<(Dimension, &'static str, f64)> {
+    let name = name.trim().to_lowercase();
+    UNITS
+        .iter()
+        .find(|(aliases, ..)| aliases.contains(&name.as_str()))
+        .map(|(_, dimension, display, factor)| (*dimension, *display, *factor))
+}
+
+/// 温度换算（经摄氏度中转）
+fn convert_temperature(value: f64, from: &str, to: &str) -> f64 {
+    let celsius = match from {
+        "°F" => (value - 32.0) * 5.0 / 9.0,
+        "K" => value - 273.15,
+        _ => value,
+    };
+    match to {
+        "°F" => celsius * 9.0 / 5.0 + 32.0,
+        "K" => celsius + 273.15,
+        _ => celsius,
+    }
+}
+
+/// 数值格式化：最多 6 位小数，去掉末尾的 0
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        return format!("{}", value as i64);
+    }
+    let text = format!("{:.6}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+// ==================== 算式求值 ====================
+
+/// 递归下降求值器
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_spaces(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_spaces();
+        self.chars.peek().copied()
+    }
+
+    /// expr = term (('+' | '-') term)*
+    fn expr(&mut self) -> Option<f64> {
+        let mut value = self.term()?;
+        while let Some(op) = self.peek().filter(|c| matches!(c, '+' | '-')) {
+            self.chars.next();
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Some(value)
+    }
+
+    /// term = unary (('*' | '/' | '×' | '÷') unary)*
+    fn term(&mut self) -> Option<f64> {
+        let mut value = self.unary()?;
+        while let Some(op) = self.peek().filter(|c| matches!(c, '*' | '/' | '×' | '÷')) {
+            self.chars.next();
+            let rhs = self.unary()?;
+            value = if matches!(op, '*' | '×') { value * rhs } else { value / rhs };
+        }
+        Some(value)
+    }
+
+    /// unary = '-' unary | power（-3^2 = -9）
+    fn unary(&mut self) -> Option<f64> {
+        if self.peek() == Some('-') {
+            self.chars.next();
+            return Some(-self.unary()?);
+        }
+        self.power()
+    }
+
+    /// power = postfix ('^' unary)?（右结合）
+    fn power(&mut self) -> Option<f64> {
+        let base = self.postfix()?;
+        if self.peek() == Some('^') {
+            self.chars.next();
+            return Some(base.powf(self.unary()?));
+        }
+        Some(base)
+    }
+
+    /// postfix = primary '%'?
+    fn postfix(&mut self) -> Option<f64> {
+        let value = self.primary()?;
+        if self.peek() == Some('%') {
+            self.chars.next();
+            return Some(value / 100.0);
+        }
+        Some(value)
+    }
+
+    /// primary = number | '(' expr ')' | 'sqrt' '(' expr ')'
+    fn primary(&mut self) -> Option<f64> {
+        match self.peek()? {
+            '(' => {
+                self.chars.next();
+                let value = self.expr()?;
+                (self.peek() == Some(')')).then(|| self.chars.next())?;
+                Some(value)
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut text = String::new();
+                while let Some(c) = self.chars.peek().copied().filter(|c| c.is_ascii_digit() || *c == '.') {
+                    text.push(c);
+                    self.chars.next();
+                }
+                text.parse().ok()
+            }
+            c if c.is_ascii_alphabetic() => {
+                let mut name = String::new();
+                while let Some(c) = self.chars.peek().copied().filter(char::is_ascii_alphabetic) {
+                    name.push(c);
+                    self.chars.next();
+                }
+                if name != "sqrt" || self.peek() != Some('(') {
+                    return None;
+                }
+                self.primary().map(f64::sqrt)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 求值算式，整串都必须被解析
+fn eval_expression(text: &str) -> Option<f64> {
+    let mut parser = Parser { chars: text.chars().peekable() };
+    let value = parser.expr()?;
+    (parser.peek().is_none() && value.is_finite()).then_some(value)
+}
+
+/// 去掉数字中的千分位逗号（如 "2,380"）
+fn strip_thousands(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .iter()
+        .enumerate()
+        .filter(|(i, c)| {
+            !(**c == ','
+                && *i > 0
+                && chars[i - 1].is_ascii_digit()
+                && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit()))
+        })
+        .map(|(_, c)| *c)
+        .collect()
+}
+
+/// 去掉 "=?"、"等于多少" 等首尾修饰
+fn normalize_query(query: &str) -> String {
+    let mut text = strip_thousands(query.trim());
+    for prefix in ["=", "计算", "算一下"] {
+        if let Some(rest) = text.strip_prefix(prefix) {
+            text = rest.trim().to_string();
+        }
+    }
+    // 后缀可能叠加（如 "=?"），逐个去掉直到没有
+    while let Some(rest) = ["等于多少", "是多少", "等于几", "=", "?", "？"]
+        .iter()
+        .find_map(|suffix| text.strip_suffix(suffix))
+    {
+        text = rest.trim().to_string();
+    }
+    text
+}
+
+fn percent_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)^(?:(?P<p1>[\d.]+)\s*%\s*of\s*(?P<b1>.+)|(?P<b2>.+?)\s*的\s*(?P<p2>[\d.]+)\s*%)$")
+            .expect("百分比正则无效")
+    })
+}
+
+fn unit_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)^(?P<value>-?[\d.]+)\s*(?P<from>[^\d\s]+?)\s*(?:to|in|into|->|=|转换为|转换成|换算成|转成|换成|转|等于多少)\s*(?P<to>[^\d\s]+?)$",
+        )
+        .expect("单位换算正则无效")
+    })
+}
+
+fn eval_percent(text: &str) -> Option<QuickAnswer> {
+    let caps = percent_regex().captures(text)?;
+    let percent: f64 = caps.name("p1").or(caps.name("p2"))?.as_str().parse().ok()?;
+    let base_text = caps.name("b1").or(caps.name("b2"))?.as_str().trim();
+    let base = eval_expression(base_text)?;
+    let value = base * percent / 100.0;
+    Some(QuickAnswer {
+        kind: QuickKind::Percent,
+        expression: format!("{}% × {}", format_number(percent), base_text),
+        value,
+        answer: format_number(value),
+    })
+}
+
+fn eval_unit(text: &str) -> Option<QuickAnswer> {
+    let caps = unit_regex().captures(text)?;
+    let amount: f64 = caps["value"].parse().ok()?;
+    let (from_dim, from, from_factor) = find_unit(&caps["from"])?;
+    let (to_dim, to, to_factor) = find_unit(&caps["to"])?;
+    if from_dim != to_dim {
+        return None;
+    }
+    let value = if from_dim == Dimension::Temperature {
+        convert_temperature(amount, from, to)
+    } else {
+        amount * from_factor / to_factor
+    };
+    Some(QuickAnswer {
+        kind: QuickKind::Unit,
+        expression: format!("{} {} → {}", format_number(amount), from, to),
+        value,
+        answer: format!("{} {}", format_number(value), to),
+    })
+}
+
+fn eval_math(text: &str) -> Option<QuickAnswer> {
+    // 单独一个数字不算速算问题
+    if !text.chars().any(|c| "+-*/×÷^%(".contains(c)) {
+        return None;
+    }
+    let value = eval_expression(text)?;
+    Some(QuickAnswer {
+        kind: QuickKind::Math,
+        expression: text.to_string(),
+        value,
+        answer: format_number(value),
+    })
+}
+
+/// 尝试本地计算，无法识别时返回 None
+pub fn evaluate(query: &str) -> Option<QuickAnswer> {
+    let text = normalize_query(query);
+    if text.is_empty() || text.chars().count() > 200 {
+        return None;
+    }
+    eval_percent(&text)
+        .or_else(|| eval_unit(&text))
+        .or_else(|| eval_math(&text))
+}
+
+/// 快捷面板查询：先尝试本地速算，无法回答时交给 Agent 执行
+#[tauri::command]
+pub async fn palette_query(
+    window: Window,
+    state: tauri::State<'_, crate::AppState>,
+    query: String,
+) -> Result<PaletteResult, String> {
+    if let Some(answer) = evaluate(&query) {
+        eprintln!("[Tauri] ⚡ 本地速算: {} = {}", answer.expression, answer.answer);
+        return Ok(PaletteResult::Local { answer });
+    }
+    let result = crate::execute_task(window, state, query, None, None, None, None).await?;
+    Ok(PaletteResult::Agent { result })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(query: &str) -> QuickAnswer {
+        evaluate(query).unwrap_or_else(|| panic!("应当本地计算: {:?}", query))
+    }
+
+    fn close(actual: f64, expected: f64) -> bool {
+        (actual - expected).abs() < 1e-9
+    }
+
+    #[test]
+    fn converts_units() {
+        let result = answer("2.5 inch to cm");
+        assert!(matches!(result.kind, QuickKind::Unit));
+        assert!(close(result.value, 6.35));
+        assert_eq!(result.answer, "6.35 cm");
+        assert_eq!(result.expression, "2.5 in → cm");
+        assert!(close(answer("5公斤转磅").value, 5000.0 / 453.59237));
+        assert_eq!(answer("100 f to c").answer, "37.777778 °C");
+        assert_eq!(answer("1 GB to MB").answer, "1024 MB");
+    }
+
+    #[test]
+    fn computes_percentages() {
+        for query in ["15% of 2380", "15 % OF 2,380", "2380 的 15%", "15% of 2380?"] {
+            let result = answer(query);
+            assert!(matches!(result.kind, QuickKind::Percent), "{:?}", query);
+            assert!(close(result.value, 357.0), "{:?}", query);
+            assert_eq!(result.answer, "357");
+        }
+        assert!(close(answer("10% of (200+300)").value, 50.0));
+    }
+
+    #[test]
+    fn respects_operator_precedence() {
+        for (query, expected) in [
+            ("2+3*4", 14.0),
+            ("(2+3)*4", 20.0),
+            ("10-4-3", 3.0),
+            ("8/4/2", 1.0),
+            ("2*3^2", 18.0),
+            ("2^3^2", 512.0),
+            ("-3^2", -9.0),
+            ("2^-1", 0.5),
+            ("6÷2×3", 9.0),
+            ("50%*8", 4.0),
+            ("sqrt(16)+1", 5.0),
+            ("=(3+4)*2=?", 14.0),
+            ("1,000+1 等于多少", 1001.0),
+        ] {
+            let result = answer(query);
+            assert!(matches!(result.kind, QuickKind::Math), "{:?}", query);
+            assert!(close(result.value, expected), "{:?} = {}", query, result.value);
+        }
+    }
+
+    #[test]
+    fn division_by_zero_falls_through() {
+        assert!(evaluate("1/0").is_none());
+        assert!(evaluate("0/0").is_none());
+        assert!(evaluate("5 % of (1/0)").is_none());
+    }
+
+    #[test]
+    fn unparseable_queries_fall_through() {
+        for query in [
+            "",
+            "   ",
+            "42",
+            "打开下载文件夹",
+            "what time is it",
+            "3 +",
+            "(1+2",
+            "1+2)",
+            "sqrt 4",
+            "foo(2)",
+            "1..2+3",
+            "5 kg to cm",
+            "5 parsecs to km",
+        ] {
+            assert!(evaluate(query).is_none(), "应当交给 Agent: {:?}", query);
+        }
+        assert!(evaluate(&"1+".repeat(150)).is_none());
+    }
+}
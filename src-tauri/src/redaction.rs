@@ -0,0 +1,340 @@
+//! 脱敏规则：用户自定义的正则/字面量规则，应用于日志、进度事件和导出内容
+//!
+//! 规则持久化在 ~/.deskjarvis/redaction_rules.json，编译后缓存在进程级的
+//! RULES 中，供 stderr 转发任务等无法拿到 AppHandle 的位置直接调用。
+//!
+//! 用户规则之前先做两步内置脱敏：替换配置中的密钥值（API Key、邮箱密码、令牌等，写入配置或
+//! 从钥匙串注入环境变量时登记，见 add_config_secrets / add_secrets），再按常见令牌格式
+//! （sk-…、Bearer、GitHub/Slack/AWS/Google 令牌、JWT、URL 中的用户名密码、key=value）替换。
+//! 登记过的密钥在进程内一直保留，更换 API Key 后旧值仍会被替换。
+
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, AppConfig};
+use crate::history::now_millis;
+use crate::settings_bundle;
+
+/// 默认替换文本
+const DEFAULT_REPLACEMENT: &str = "[REDACTED]";
+
+/// 短于此长度的密钥值不做字面替换，避免误伤普通文本
+const MIN_SECRET_CHARS: usize = 6;
+
+/// 内置的常见令牌格式：(正则, 替换文本)
+const TOKEN_PATTERNS: &[(&str, &str)] = &[
+    // key=value / "key": "value" 形式，保留键名
+    (
+        r#"(?i)((?:api[_-]?key|access[_-]?token|refresh[_-]?token|client[_-]?secret|secret[_-]?key|password|passwd)["']?\s*[:=]\s*["']?)[^\s"'&,;]{4,}"#,
+        "${1}[REDACTED]",
+    ),
+    (r"(?i)\b(bearer\s+)[A-Za-z0-9._~+/\-]{16,}=*", "${1}[REDACTED]"),
+    // URL 中的用户名密码（代理地址、CalDAV 地址等）
+    (r"(://)[^/\s:@]+:[^/\s@]+@", "${1}[REDACTED]@"),
+    (r"\bsk-[A-Za-z0-9_\-]{16,}", "[REDACTED]"),
+    (r"\b(?:gh[pousr]_[A-Za-z0-9]{20,}|github_pat_[A-Za-z0-9_]{20,})", "[REDACTED]"),
+    (r"\bxox[abeprs]-[A-Za-z0-9\-]{10,}", "[REDACTED]"),
+    (r"\bAKIA[0-9A-Z]{16}\b", "[REDACTED]"),
+    (r"\bAIza[0-9A-Za-z_\-]{35}", "[REDACTED]"),
+    (r"\bya29\.[0-9A-Za-z_\-]{20,}", "[REDACTED]"),
+    (r"\beyJ[A-Za-z0-9_\-]{10,}\.[A-Za-z0-9_\-]{10,}\.[A-Za-z0-9_\-]{10,}", "[REDACTED]"),
+];
+
+/// 规则类型
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleKind {
+    /// 正则表达式
+    Regex,
+    /// 字面量字符串
+    Literal,
+}
+
+/// 脱敏规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionRule {
+    /// 规则 ID，新建时可留空由后端生成
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub kind: RuleKind,
+    pub pattern: String,
+    /// 替换文本，为空时使用 "[REDACTED]"
+    #[serde(default)]
+    pub replacement: Option<String>,
+    #[serde(default)]
+    pub case_insensitive: bool,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 编译后的规则
+struct CompiledRule {
+    id: String,
+    regex: Regex,
+    replacement: String,
+}
+
+/// 已启用规则的编译缓存
+static RULES: RwLock<Vec<CompiledRule>> = RwLock::new(Vec::new());
+
+/// 登记的密钥值，按长度从长到短（先替换较长的值，避免只替换掉前缀）
+static SECRETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+fn token_patterns() -> &'static [(Regex, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        TOKEN_PATTERNS
+            .iter()
+            .map(|(pattern, replacement)| (Regex::new(pattern).expect("内置令牌正则无效"), *replacement))
+            .collect()
+    })
+}
+
+/// 单条规则在测试样本中的命中情况
+#[derive(Debug, Serialize)]
+pub struct RuleMatch {
+    rule_id: String,
+    count: usize,
+}
+
+/// test_redaction 的返回结果
+#[derive(Debug, Serialize)]
+pub struct RedactionTestResult {
+    redacted: String,
+    matches: Vec<RuleMatch>,
+}
+
+/// 规则文件路径
+fn rules_path() -> Result<PathBuf, String> {
+    Ok(config::get_data_dir()?.join("redaction_rules.json"))
+}
+
+/// 编译单条规则，同时用于校验
+pub(crate) fn compile_rule(rule: &RedactionRule) -> Result<Regex, String> {
+    if rule.pattern.is_empty() {
+        return Err("规则内容不能为空".to_string());
+    }
+    let source = match rule.kind {
+        RuleKind::Regex => rule.pattern.clone(),
+        RuleKind::Literal => regex::escape(&rule.pattern),
+    };
+    let regex = RegexBuilder::new(&source)
+        .case_insensitive(rule.case_insensitive)
+        .size_limit(1 << 20)
+        .build()
+        .map_err(|e| format!("无效的正则表达式: {}", e))?;
+    if regex.is_match("") {
+        return Err("规则不能匹配空字符串".to_string());
+    }
+    Ok(regex)
+}
+
+/// 读取规则文件
+pub(crate) fn load_rules() -> Result<Vec<RedactionRule>, String> {
+    let path = rules_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("读取脱敏规则失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析脱敏规则失败: {}", e))
+}
+
+/// 写入规则文件并刷新编译缓存
+pub(crate) fn store_rules(rules: &[RedactionRule]) -> Result<(), String> {
+    let path = rules_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(rules)
+        .map_err(|e| format!("序列化脱敏规则失败: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("写入脱敏规则失败: {}", e))?;
+    apply_rules(rules);
+    Ok(())
+}
+
+/// 用规则列表替换编译缓存（跳过禁用或无效的规则）
+fn apply_rules(rules: &[RedactionRule]) {
+    let compiled: Vec<CompiledRule> = rules
+        .iter()
+        .filter(|r| r.enabled)
+        .filter_map(|r| match compile_rule(r) {
+            Ok(regex) => Some(CompiledRule {
+                id: r.id.clone(),
+                regex,
+                replacement: r
+                    .replacement
+                    .clone()
+                    .filter(|s| !s.is_empty())
+                    .unwrap_or_else(|| DEFAULT_REPLACEMENT.to_string()),
+            }),
+            Err(e) => {
+                eprintln!("[Tauri] ⚠️ 跳过脱敏规则 {}: {}", r.id, e);
+                None
+            }
+        })
+        .collect();
+
+    if let Ok(mut cache) = RULES.write() {
+        *cache = compiled;
+    }
+}
+
+/// 登记需要替换的密钥值（忽略过短的值）
+pub fn add_secrets(values: impl IntoIterator<Item = String>) {
+    let Ok(mut secrets) = SECRETS.write() else {
+        return;
+    };
+    for value in values {
+        let value = value.trim();
+        if value.chars().count() >= MIN_SECRET_CHARS && !secrets.iter().any(|s| s == value) {
+            secrets.push(value.to_string());
+        }
+    }
+    secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+}
+
+/// 登记配置中的密钥值（与设置包导出时抹去的字段相同）
+pub fn add_config_secrets(config: &AppConfig) {
+    let mut values = Vec::new();
+    settings_bundle::visit_secrets(&mut config.clone(), |_, value| values.push(value.clone()));
+    add_secrets(values);
+}
+
+/// 启动时加载规则并登记配置中的密钥
+pub fn init() {
+    match load_rules() {
+        Ok(rules) => apply_rules(&rules),
+        Err(e) => eprintln!("[Tauri] ⚠️ {}", e),
+    }
+    match config::resolve() {
+        Ok(config) => add_config_secrets(&config),
+        Err(e) => eprintln!("[Tauri] ⚠️ {}", e),
+    }
+}
+
+/// 对文本依次应用密钥替换、内置令牌格式和所有已启用规则
+pub fn redact(text: &str) -> String {
+    let mut output = text.to_string();
+    if let Ok(secrets) = SECRETS.read() {
+        for secret in secrets.iter() {
+            if output.contains(secret.as_str()) {
+                output = output.replace(secret.as_str(), DEFAULT_REPLACEMENT);
+            }
+        }
+    }
+    for (regex, replacement) in token_patterns() {
+        if regex.is_match(&output) {
+            output = regex.replace_all(&output, *replacement).into_owned();
+        }
+    }
+    let Ok(rules) = RULES.read() else {
+        return output;
+    };
+    for rule in rules.iter() {
+        if rule.regex.is_match(&output) {
+            output = rule
+                .regex
+                .replace_all(&output, rule.replacement.as_str())
+                .into_owned();
+        }
+    }
+    output
+}
+
+/// 对 JSON 中的所有字符串值应用规则
+pub fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::String(s) => {
+            let redacted = redact(s);
+            if redacted != *s {
+                *s = redacted;
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_json),
+        serde_json::Value::Object(map) => map.values_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// 列出全部脱敏规则
+#[tauri::command]
+pub async fn list_redaction_rules() -> Result<Vec<RedactionRule>, String> {
+    load_rules()
+}
+
+/// 新建或更新脱敏规则（按 ID 匹配），返回保存后的规则
+#[tauri::command]
+pub async fn save_redaction_rule(mut rule: RedactionRule) -> Result<RedactionRule, String> {
+    compile_rule(&rule)?;
+
+    let mut rules = load_rules()?;
+    if rule.id.is_empty() {
+        rule.id = format!("rule_{}", now_millis());
+    }
+    match rules.iter_mut().find(|r| r.id == rule.id) {
+        Some(existing) => *existing = rule.clone(),
+        None => rules.push(rule.clone()),
+    }
+    store_rules(&rules)?;
+    Ok(rule)
+}
+
+/// 删除脱敏规则
+#[tauri::command]
+pub async fn delete_redaction_rule(id: String) -> Result<(), String> {
+    let mut rules = load_rules()?;
+    let before = rules.len();
+    rules.retain(|r| r.id != id);
+    if rules.len() == before {
+        return Err(format!("未找到脱敏规则: {}", id));
+    }
+    store_rules(&rules)
+}
+
+/// 校验规则内容（不保存）
+#[tauri::command]
+pub async fn validate_redaction_pattern(
+    kind: RuleKind,
+    pattern: String,
+    case_insensitive: Option<bool>,
+) -> Result<(), String> {
+    let rule = RedactionRule {
+        id: String::new(),
+        name: String::new(),
+        kind,
+        pattern,
+        replacement: None,
+        case_insensitive: case_insensitive.unwrap_or(false),
+        enabled: true,
+    };
+    compile_rule(&rule).map(|_| ())
+}
+
+/// 用当前已启用规则处理样本文本，返回脱敏结果与各规则命中次数
+#[tauri::command]
+pub async fn test_redaction(sample: String) -> Result<RedactionTestResult, String> {
+    let matches = {
+        let rules = RULES.read().map_err(|_| "脱敏规则缓存不可用".to_string())?;
+        rules
+            .iter()
+            .map(|r| RuleMatch {
+                rule_id: r.id.clone(),
+                count: r.regex.find_iter(&sample).count(),
+            })
+            .filter(|m| m.count > 0)
+            .collect()
+    };
+
+    Ok(RedactionTestResult {
+        redacted: redact(&sample),
+        matches,
+    })
+}
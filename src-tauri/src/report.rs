@@ -0,0 +1,400 @@
+//! 任务报告：把任务的指令、步骤、输出和产物导出为 Markdown、HTML 或 PDF
+//!
+//! 任务结束时 finish_task 把完整的 TaskResult（脱敏后）保存到
+//! ~/.deskjarvis/task_results/<task_id>.json，历史记录只保存结论和产物清单。
+//! 导出时读取保存的结果，早于此功能的任务只有历史记录中的信息，不含步骤。
+//! PDF 由本机的 Chrome / Chromium / Edge 以无头模式打印 HTML 报告生成，未安装时返回错误。
+//! 报告默认保存在 sandbox/reports/ 下，dest 只能指向沙盒内的路径。
+
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use chrono::{Local, TimeZone};
+use serde::Deserialize;
+
+use crate::history::{self, TaskRecord};
+use crate::{config, redaction, sandbox, TaskResult};
+
+/// 单个步骤输出在报告中的最大长度（字符）
+const MAX_OUTPUT_CHARS: usize = 4000;
+
+/// 报告格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[serde(alias = "md")]
+    Markdown,
+    Html,
+    Pdf,
+}
+
+impl ReportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+            ReportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+fn results_dir() -> Result<PathBuf, String> {
+    Ok(config::get_data_dir()?.join("task_results"))
+}
+
+/// 结果文件路径，task_id 只允许字母、数字、下划线和连字符
+fn result_path(task_id: &str) -> Result<PathBuf, String> {
+    let valid = !task_id.is_empty()
+        && task_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(format!("无效的任务 ID: {}", task_id));
+    }
+    Ok(results_dir()?.join(format!("{}.json", task_id)))
+}
+
+/// 保存任务的完整结果（步骤参数和输出已脱敏），失败只记录日志
+pub fn save_result(task_id: &str, result: &TaskResult) {
+    let saved = (|| -> Result<(), String> {
+        let path = result_path(task_id)?;
+        std::fs::create_dir_all(results_dir()?).map_err(|e| format!("创建目录失败: {}", e))?;
+        let mut value = serde_json::to_value(result).map_err(|e| format!("序列化失败: {}", e))?;
+        redaction::redact_json(&mut value);
+        let content = serde_json::to_string(&value).map_err(|e| format!("序列化失败: {}", e))?;
+        std::fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))
+    })();
+    if let Err(e) = saved {
+        eprintln!("[Tauri] ⚠️ 保存任务结果失败: {}", e);
+    }
+}
+
+/// 读取保存的任务结果，不存在时返回 None
+pub fn load_result(task_id: &str) -> Option<TaskResult> {
+    let content = std::fs::read_to_string(result_path(task_id).ok()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 删除任务的结果文件（删除任务历史时调用）
+pub fn remove(task_id: &str) {
+    if let Ok(path) = result_path(task_id) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// 报告内容，由历史记录和保存的结果合并而来
+struct Report {
+    record: TaskRecord,
+    steps: Vec<ReportStep>,
+}
+
+struct ReportStep {
+    kind: String,
+    description: String,
+    success: Option<bool>,
+    output: String,
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_OUTPUT_CHARS {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(MAX_OUTPUT_CHARS).collect();
+    truncated.push('…');
+    truncated
+}
+
+impl Report {
+    fn new(record: TaskRecord, result: Option<TaskResult>) -> Self {
+        let steps = result
+            .map(|r| {
+                r.steps
+                    .iter()
+                    .map(|s| {
+                        let text = |v: &serde_json::Value, key: &str| {
+                            v.get(key).and_then(|x| x.as_str()).unwrap_or_default().to_string()
+                        };
+                        let result = s.result.as_ref();
+                        let message = result.map(|r| text(r, "message")).unwrap_or_default();
+                        let data = result
+                            .and_then(|r| r.get("data"))
+                            .filter(|d| !d.is_null())
+                            .and_then(|d| serde_json::to_string_pretty(d).ok());
+                        let output = match data {
+                            Some(data) if message.is_empty() => data,
+                            Some(data) => format!("{}\n{}", message, data),
+                            None => message,
+                        };
+                        ReportStep {
+                            kind: text(&s.step, "type"),
+                            description: text(&s.step, "description"),
+                            success: result.and_then(|r| r.get("success")).and_then(|v| v.as_bool()),
+                            output: truncate(&output),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Report { record, steps }
+    }
+
+    fn time(millis: Option<u64>) -> String {
+        millis
+            .and_then(|ms| Local.timestamp_millis_opt(ms as i64).single())
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "-".to_string())
+    }
+
+    fn status(&self) -> &'static str {
+        match (self.record.success, self.record.termination_reason) {
+            (None, _) => "执行中",
+            (_, Some(history::TerminationReason::UserCancel)) => "已取消",
+            (_, Some(history::TerminationReason::Timeout)) => "超时取消",
+            (_, Some(history::TerminationReason::Budget)) => "超出费用上限",
+            (_, Some(history::TerminationReason::Crash)) => "服务崩溃",
+            (Some(true), None) => "成功",
+            (Some(false), None) => "失败",
+        }
+    }
+
+    fn step_title(index: usize, step: &ReportStep) -> String {
+        let mark = match step.success {
+            Some(true) => "✅",
+            Some(false) => "❌",
+            None => "•",
+        };
+        let description = if step.description.is_empty() {
+            step.kind.as_str()
+        } else {
+            step.description.as_str()
+        };
+        format!("{} {}. {}", mark, index + 1, description)
+    }
+
+    fn markdown(&self) -> String {
+        let r = &self.record;
+        let mut out = format!("# 任务报告：{}\n\n", r.instruction.replace('\n', " "));
+        out.push_str(&format!("- 任务 ID：`{}`\n", r.id));
+        out.push_str(&format!("- 状态：{}\n", self.status()));
+        out.push_str(&format!("- 开始时间：{}\n", Self::time(Some(r.started_at))));
+        out.push_str(&format!("- 结束时间：{}\n", Self::time(r.finished_at)));
+        if let Some(dir) = &r.work_dir {
+            out.push_str(&format!("- 工作目录：`{}`\n", dir));
+        }
+        if let Some(message) = r.message.as_deref().filter(|m| !m.is_empty()) {
+            out.push_str(&format!("\n## 结果\n\n{}\n", message));
+        }
+        out.push_str("\n## 步骤\n\n");
+        if self.steps.is_empty() {
+            out.push_str("没有记录步骤详情。\n");
+        }
+        for (i, step) in self.steps.iter().enumerate() {
+            out.push_str(&format!("### {}\n\n", Self::step_title(i, step)));
+            if !step.kind.is_empty() {
+                out.push_str(&format!("类型：`{}`\n\n", step.kind));
+            }
+            if !step.output.is_empty() {
+                out.push_str(&format!("```\n{}\n```\n\n", step.output.replace("```", "`\u{200b}``")));
+            }
+        }
+        if !r.artifacts.is_empty() {
+            out.push_str("\n## 产物\n\n");
+            for name in &r.artifacts {
+                out.push_str(&format!("- `{}`\n", name));
+            }
+        }
+        out
+    }
+
+    fn html(&self) -> String {
+        let r = &self.record;
+        let mut body = format!("<h1>任务报告：{}</h1>\n<table>\n", escape(&r.instruction));
+        let mut row = |name: &str, value: &str| {
+            body.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", name, escape(value)));
+        };
+        row("任务 ID", &r.id);
+        row("状态", self.status());
+        row("开始时间", &Self::time(Some(r.started_at)));
+        row("结束时间", &Self::time(r.finished_at));
+        if let Some(dir) = &r.work_dir {
+            row("工作目录", dir);
+        }
+        body.push_str("</table>\n");
+        if let Some(message) = r.message.as_deref().filter(|m| !m.is_empty()) {
+            body.push_str(&format!("<h2>结果</h2>\n<p>{}</p>\n", escape(message)));
+        }
+        body.push_str("<h2>步骤</h2>\n");
+        if self.steps.is_empty() {
+            body.push_str("<p>没有记录步骤详情。</p>\n");
+        }
+        for (i, step) in self.steps.iter().enumerate() {
+            body.push_str(&format!("<h3>{}</h3>\n", escape(&Self::step_title(i, step))));
+            if !step.kind.is_empty() {
+                body.push_str(&format!("<p class=\"kind\">{}</p>\n", escape(&step.kind)));
+            }
+            if !step.output.is_empty() {
+                body.push_str(&format!("<pre>{}</pre>\n", escape(&step.output)));
+            }
+        }
+        if !r.artifacts.is_empty() {
+            body.push_str("<h2>产物</h2>\n<ul>\n");
+            for name in &r.artifacts {
+                body.push_str(&format!("<li><code>{}</code></li>\n", escape(name)));
+            }
+            body.push_str("</ul>\n");
+        }
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n<title>任务报告 {}</title>\n<style>\n{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+            escape(&r.id),
+            STYLE,
+            body
+        )
+    }
+}
+
+const STYLE: &str = "body { font-family: -apple-system, 'PingFang SC', 'Microsoft YaHei', sans-serif; max-width: 860px; margin: 2em auto; padding: 0 1em; color: #222; line-height: 1.6; }
+table { border-collapse: collapse; }
+th, td { text-align: left; padding: 4px 12px 4px 0; vertical-align: top; }
+th { color: #666; font-weight: normal; white-space: nowrap; }
+pre { background: #f5f5f7; padding: 10px; border-radius: 6px; white-space: pre-wrap; word-break: break-all; font-size: 12px; }
+.kind { color: #888; font-family: monospace; margin: 0; }
+";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 报告保存路径：默认 sandbox/reports/<task_id>_<时间>.<扩展名>，dest 须位于沙盒内
+fn output_path(task_id: &str, format: ReportFormat, dest: Option<&str>) -> Result<PathBuf, String> {
+    let root = sandbox::sandbox_root();
+    std::fs::create_dir_all(&root).map_err(|e| format!("创建沙盒目录失败: {}", e))?;
+    let root = root
+        .canonicalize()
+        .map_err(|e| format!("无法规范化路径: {}", e))?;
+    let reports = root.join("reports");
+    let path = match dest.map(str::trim).filter(|d| !d.is_empty()) {
+        None => reports.join(format!(
+            "{}_{}.{}",
+            task_id,
+            Local::now().format("%Y%m%d_%H%M%S"),
+            format.extension()
+        )),
+        Some(dest) => {
+            let dest = Path::new(dest);
+            if dest.components().any(|c| c == Component::ParentDir) {
+                return Err(format!("报告路径不能包含 \"..\": {}", dest.display()));
+            }
+            let path = if dest.is_absolute() {
+                dest.to_path_buf()
+            } else {
+                reports.join(dest)
+            };
+            if !path.starts_with(&root) {
+                return Err(format!("报告只能保存在沙盒内: {}", path.display()));
+            }
+            if path.extension().is_some() {
+                path
+            } else {
+                path.with_extension(format.extension())
+            }
+        }
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    Ok(path)
+}
+
+/// 本机可用于无头打印的浏览器
+fn find_browser() -> Option<PathBuf> {
+    #[cfg(target_os = "macos")]
+    let candidates = [
+        "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+        "/Applications/Chromium.app/Contents/MacOS/Chromium",
+        "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+    ];
+    #[cfg(target_os = "windows")]
+    let candidates = [
+        r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe",
+        r"C:\Program Files\Microsoft\Edge\Application\msedge.exe",
+        r"C:\Program Files\Google\Chrome\Application\chrome.exe",
+    ];
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let candidates = [
+        "google-chrome",
+        "google-chrome-stable",
+        "chromium",
+        "chromium-browser",
+        "microsoft-edge",
+    ];
+
+    candidates.iter().map(PathBuf::from).find(|p| {
+        if p.is_absolute() {
+            p.exists()
+        } else {
+            Command::new("which")
+                .arg(p)
+                .output()
+                .is_ok_and(|o| o.status.success())
+        }
+    })
+}
+
+/// 用无头浏览器把 HTML 打印为 PDF
+fn print_pdf(html: &str, output: &Path) -> Result<(), String> {
+    let browser = find_browser()
+        .ok_or("导出 PDF 需要安装 Chrome、Chromium 或 Edge，可改为导出 HTML 后用浏览器打印")?;
+    let source = output.with_extension("print.html");
+    std::fs::write(&source, html).map_err(|e| format!("写入文件失败: {}", e))?;
+    let printed = Command::new(&browser)
+        .args(["--headless", "--disable-gpu", "--no-pdf-header-footer"])
+        .arg(format!("--print-to-pdf={}", output.display()))
+        .arg(&source)
+        .output();
+    let _ = std::fs::remove_file(&source);
+    let printed = printed.map_err(|e| format!("启动浏览器失败: {}", e))?;
+    if !output.exists() {
+        return Err(format!(
+            "打印 PDF 失败: {}",
+            String::from_utf8_lossy(&printed.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// 导出任务报告，返回报告路径
+///
+/// format 为 markdown（或 md）、html、pdf；dest 为沙盒内的保存路径，相对路径基于 sandbox/reports
+#[tauri::command]
+pub async fn export_task_report(
+    state: tauri::State<'_, crate::AppState>,
+    task_id: String,
+    format: ReportFormat,
+    dest: Option<String>,
+) -> Result<String, String> {
+    let record = state
+        .history
+        .get(&task_id)
+        .ok_or_else(|| format!("未找到任务记录: {}", task_id))?;
+    let report = Report::new(record, load_result(&task_id));
+    let path = output_path(&task_id, format, dest.as_deref())?;
+
+    match format {
+        ReportFormat::Markdown => std::fs::write(&path, report.markdown())
+            .map_err(|e| format!("写入报告失败: {}", e))?,
+        ReportFormat::Html => std::fs::write(&path, report.html())
+            .map_err(|e| format!("写入报告失败: {}", e))?,
+        ReportFormat::Pdf => {
+            let html = report.html();
+            let output = path.clone();
+            tokio::task::spawn_blocking(move || print_pdf(&html, &output))
+                .await
+                .map_err(|e| format!("导出 PDF 失败: {}", e))??
+        }
+    }
+    eprintln!("[Tauri] 📄 已导出任务报告: {}", path.display());
+    Ok(path.to_string_lossy().to_string())
+}
@@ -0,0 +1,136 @@
+//! Python 服务资源监控：定期采样 CPU、内存（RSS）和打开的文件数
+//!
+//! 每次采样以 server-stats 事件发给前端。内存超过配置 server_memory_limit_mb
+//! （默认 2048，为 0 时关闭）时记录事件到 ~/.deskjarvis/server_incidents.log 并重启服务：
+//! 空闲时直接结束进程后后台重启；执行任务期间服务锁被占用，结束进程后由任务流程
+//! 按服务崩溃处理（降级执行并后台重启）。
+
+use std::io::Write;
+use std::sync::atomic::{AtomicU32, Ordering};
+use serde::Serialize;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{config, history, AppState};
+
+/// 采样间隔
+const SAMPLE_INTERVAL_SECS: u64 = 5;
+
+/// 默认内存上限（MB）
+const DEFAULT_MEMORY_LIMIT_MB: u64 = 2048;
+
+/// 当前 Python 服务进程 PID，0 表示未运行
+static SERVER_PID: AtomicU32 = AtomicU32::new(0);
+
+/// server-stats 事件负载
+#[derive(Debug, Clone, Serialize)]
+struct ServerStats {
+    pid: u32,
+    /// CPU 占用（百分比，多核可超过 100）
+    cpu_percent: f32,
+    memory_bytes: u64,
+    /// 打开的文件描述符数，平台不支持时为 None
+    open_files: Option<usize>,
+    memory_limit_mb: u64,
+}
+
+/// 记录新启动的服务进程
+pub fn set_server_pid(pid: Option<u32>) {
+    SERVER_PID.store(pid.unwrap_or(0), Ordering::SeqCst);
+}
+
+fn memory_limit_mb() -> u64 {
+    config::load_config()
+        .ok()
+        .and_then(|c| c.server_memory_limit_mb)
+        .unwrap_or(DEFAULT_MEMORY_LIMIT_MB)
+}
+
+/// 追加一条事件记录
+fn log_incident(stats: &ServerStats) {
+    let record = serde_json::json!({
+        "timestamp": history::now_millis(),
+        "event": "memory_limit_exceeded",
+        "pid": stats.pid,
+        "memory_bytes": stats.memory_bytes,
+        "memory_limit_mb": stats.memory_limit_mb,
+        "cpu_percent": stats.cpu_percent,
+        "open_files": stats.open_files,
+    });
+    let result = config::get_data_dir().and_then(|dir| {
+        std::fs::create_dir_all(&dir).map_err(|e| format!("创建目录失败: {}", e))?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join("server_incidents.log"))
+            .map_err(|e| format!("打开服务事件日志失败: {}", e))?;
+        writeln!(file, "{}", record).map_err(|e| format!("写入服务事件日志失败: {}", e))
+    });
+    if let Err(e) = result {
+        eprintln!("[Tauri] ⚠️ {}", e);
+    }
+}
+
+/// 内存超限：结束服务进程并安排重启
+async fn restart_server(app: &AppHandle, system: &System, pid: u32) {
+    let state = app.state::<AppState>();
+    if let Ok(mut guard) = state.server.try_lock() {
+        // 空闲：直接结束并后台重启
+        if let Some(mut server) = guard.take() {
+            let _ = server.child.kill().await;
+        }
+        SERVER_PID.store(0, Ordering::SeqCst);
+        drop(guard);
+        crate::spawn_background_restart(app.clone(), Some("内存超限"));
+        return;
+    }
+    // 执行任务中：结束进程，任务流程读到 EOF 后按崩溃处理
+    if let Some(process) = system.process(Pid::from_u32(pid)) {
+        process.kill();
+    }
+    SERVER_PID.store(0, Ordering::SeqCst);
+}
+
+/// 后台监控任务
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut system = System::new();
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(SAMPLE_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let pid = SERVER_PID.load(Ordering::SeqCst);
+            if pid == 0 {
+                continue;
+            }
+            let sys_pid = Pid::from_u32(pid);
+            system.refresh_processes_specifics(
+                ProcessesToUpdate::Some(&[sys_pid]),
+                true,
+                ProcessRefreshKind::nothing().with_cpu().with_memory(),
+            );
+            let Some(process) = system.process(sys_pid) else {
+                continue;
+            };
+            let stats = ServerStats {
+                pid,
+                cpu_percent: process.cpu_usage(),
+                memory_bytes: process.memory(),
+                open_files: process.open_files(),
+                memory_limit_mb: memory_limit_mb(),
+            };
+            let _ = app.emit("server-stats", &stats);
+
+            let limit_bytes = stats.memory_limit_mb.saturating_mul(1024 * 1024);
+            if limit_bytes > 0 && stats.memory_bytes > limit_bytes {
+                eprintln!(
+                    "[Tauri] ⚠️ Python 服务内存 {} MB 超过上限 {} MB，正在重启",
+                    stats.memory_bytes / 1024 / 1024,
+                    stats.memory_limit_mb
+                );
+                log_incident(&stats);
+                restart_server(&app, &system, pid).await;
+            }
+        }
+    });
+}
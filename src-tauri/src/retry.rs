@@ -0,0 +1,174 @@
+//! 自动重试：任务因网络错误、限流等临时原因失败时，从失败的步骤继续执行
+//!
+//! execute_via_server 把 error 事件中的失败原因记在 note_error 里，任务失败后
+//! transient_failure 结合任务结果判断是否为临时失败。是临时失败且未超出配置
+//! retry_max_attempts（默认 2）时，发送 task-retrying 事件、按退避时间等待，再以
+//! resume_from_step 和已完成的步骤重新发送任务，Python 规划时跳过已完成的步骤。
+//! 用户取消、费用超限、卡死取消等提前结束的任务不重试。
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::{config, StepResult, TaskResult};
+
+/// 默认重试次数
+const DEFAULT_MAX_ATTEMPTS: u32 = 2;
+
+/// 首次重试前的等待时间，之后每次翻倍
+const BASE_DELAY: Duration = Duration::from_secs(3);
+
+/// 限流时的首次等待时间
+const RATE_LIMIT_DELAY: Duration = Duration::from_secs(15);
+
+/// 各任务最近一次 error 事件的失败原因
+static LAST_ERRORS: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// 临时失败的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransientKind {
+    /// 连接失败、DNS、连接被重置等
+    Network,
+    /// 429 或提供商限流
+    RateLimit,
+    /// 提供商过载或 5xx
+    Overloaded,
+    /// 请求超时
+    Timeout,
+}
+
+/// 失败原因中的关键词（小写比较）
+const PATTERNS: &[(TransientKind, &[&str])] = &[
+    (
+        TransientKind::RateLimit,
+        &["rate limit", "rate_limit", "ratelimit", "429", "too many requests", "限流", "请求过于频繁"],
+    ),
+    (
+        TransientKind::Overloaded,
+        &["overloaded", "502", "503", "504", "529", "bad gateway", "service unavailable", "服务繁忙", "过载"],
+    ),
+    (
+        TransientKind::Timeout,
+        &["timed out", "timeout", "超时"],
+    ),
+    (
+        TransientKind::Network,
+        &[
+            "connection error",
+            "connection reset",
+            "connection refused",
+            "connection aborted",
+            "network",
+            "name resolution",
+            "temporarily unavailable",
+            "apiconnectionerror",
+            "网络",
+            "连接失败",
+        ],
+    ),
+];
+
+/// 按关键词判断失败原因是否为临时失败
+pub fn classify(message: &str) -> Option<TransientKind> {
+    let lower = message.to_lowercase();
+    PATTERNS
+        .iter()
+        .find(|(_, words)| words.iter().any(|w| lower.contains(w)))
+        .map(|(kind, _)| *kind)
+}
+
+/// 记录 error 事件中的失败原因（步骤失败的 data.message 或服务级 message）
+pub fn note_error(request_id: &str, message: &str) {
+    if let Ok(mut errors) = LAST_ERRORS.lock() {
+        errors.insert(request_id.to_string(), message.to_string());
+    }
+}
+
+/// 取出并清除任务最近一次的失败原因
+fn take_error(request_id: &str) -> Option<String> {
+    LAST_ERRORS.lock().ok()?.remove(request_id)
+}
+
+/// 任务失败且原因为临时失败时返回类型和原因；同时清除记录的失败原因
+pub fn transient_failure(request_id: &str, result: &TaskResult) -> Option<(TransientKind, String)> {
+    let last_error = take_error(request_id);
+    if result.success || result.termination_reason.is_some() {
+        return None;
+    }
+    last_error
+        .into_iter()
+        .chain(std::iter::once(result.message.clone()))
+        .find_map(|message| classify(&message).map(|kind| (kind, message)))
+}
+
+/// 配置的最大重试次数
+pub fn max_attempts() -> u32 {
+    config::load_config()
+        .ok()
+        .and_then(|c| c.retry_max_attempts)
+        .unwrap_or(DEFAULT_MAX_ATTEMPTS)
+}
+
+/// 第 attempt 次重试（从 1 开始）前的等待时间
+pub fn delay(kind: TransientKind, attempt: u32) -> Duration {
+    let base = if kind == TransientKind::RateLimit {
+        RATE_LIMIT_DELAY
+    } else {
+        BASE_DELAY
+    };
+    base * 2u32.saturating_pow(attempt.saturating_sub(1)).min(8)
+}
+
+/// 步骤是否执行成功
+fn step_succeeded(step: &StepResult) -> bool {
+    step.result
+        .as_ref()
+        .and_then(|r| r.get("success"))
+        .and_then(|s| s.as_bool())
+        .unwrap_or(false)
+}
+
+/// 从失败处继续执行所需的信息
+#[derive(Debug, Clone, Default)]
+pub struct Resume {
+    /// 下一个要执行的步骤序号（即开头连续成功的步骤数）
+    pub from_step: usize,
+    /// 已完成的步骤，Python 规划时据此跳过
+    pub completed_steps: Vec<serde_json::Value>,
+}
+
+impl Resume {
+    /// 取失败结果开头连续成功的步骤；之前的重试已完成的步骤排在前面
+    pub fn after(previous: Option<&Resume>, result: &TaskResult) -> Resume {
+        let mut completed_steps: Vec<serde_json::Value> = previous
+            .map(|p| p.completed_steps.clone())
+            .unwrap_or_default();
+        completed_steps.extend(
+            result
+                .steps
+                .iter()
+                .take_while(|s| step_succeeded(s))
+                .map(|s| s.step.clone()),
+        );
+        Resume {
+            from_step: completed_steps.len(),
+            completed_steps,
+        }
+    }
+}
+
+/// task-retrying 事件负载
+#[derive(Debug, Clone, Serialize)]
+pub struct RetryingEvent {
+    pub request_id: String,
+    /// 第几次重试（从 1 开始）
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub kind: TransientKind,
+    pub reason: String,
+    pub delay_ms: u64,
+    pub resume_from_step: usize,
+}
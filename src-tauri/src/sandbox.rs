@@ -0,0 +1,168 @@
+//! 沙盒目录管理：为每个任务创建独立的工作目录，避免任务间文件互相覆盖
+//!
+//! 任务结束后扫描其工作目录，把生成的文件登记为任务产物。
+
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+
+use crate::config;
+
+/// 展开配置中的沙盒路径（支持 "~/" 前缀，为空时使用默认路径）
+pub fn expand_sandbox_path(configured: &str) -> PathBuf {
+    let path = if configured.trim().is_empty() {
+        config::get_default_sandbox_path()
+    } else {
+        configured.to_string()
+    };
+
+    config::expand_home(&path)
+}
+
+/// 获取沙盒根目录（配置中的 sandbox_path，可被 DESKJARVIS_SANDBOX 覆盖）
+pub fn sandbox_root() -> PathBuf {
+    let configured = config::resolve()
+        .map(|c| c.sandbox_path)
+        .unwrap_or_default();
+    expand_sandbox_path(&configured)
+}
+
+/// 任务 ID 只允许字母、数字、下划线和连字符，防止路径穿越
+pub fn is_valid_task_id(task_id: &str) -> bool {
+    !task_id.is_empty()
+        && task_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// 任务工作目录路径（sandbox/task_<id>），不保证已创建
+pub fn task_dir(task_id: &str) -> Result<PathBuf, String> {
+    if !is_valid_task_id(task_id) {
+        return Err(format!("无效的任务ID: {}", task_id));
+    }
+    let dir_name = if task_id.starts_with("task_") {
+        task_id.to_string()
+    } else {
+        format!("task_{}", task_id)
+    };
+    Ok(sandbox_root().join(dir_name))
+}
+
+/// 创建任务工作目录并返回其绝对路径
+pub fn create_task_dir(task_id: &str) -> Result<PathBuf, String> {
+    let dir = task_dir(task_id)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("创建任务工作目录失败: {}", e))?;
+    dir.canonicalize()
+        .map_err(|e| format!("无法规范化路径: {}", e))
+}
+
+/// 单次列出的产物上限，防止任务生成大量文件时卡住界面
+const MAX_ARTIFACTS: usize = 1000;
+
+/// 任务工作目录中的产物文件
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskArtifact {
+    /// 相对任务工作目录的路径
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    /// 最后修改时间（毫秒时间戳）
+    pub modified_at: Option<u64>,
+}
+
+/// 递归列出目录中的文件（不跟随符号链接），按相对路径排序
+pub fn scan_artifacts(dir: &Path) -> Vec<TaskArtifact> {
+    let mut artifacts = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+            let metadata = entry.metadata().ok();
+            artifacts.push(TaskArtifact {
+                name: path
+                    .strip_prefix(dir)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .to_string(),
+                path: path.to_string_lossy().to_string(),
+                size: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+                modified_at: metadata
+                    .and_then(|m| m.modified().ok())
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|d| d.as_millis() as u64),
+            });
+            if artifacts.len() >= MAX_ARTIFACTS {
+                eprintln!("[Tauri] ⚠️ 任务产物超过 {} 个，只列出前 {} 个", MAX_ARTIFACTS, MAX_ARTIFACTS);
+                artifacts.sort_by(|a, b| a.name.cmp(&b.name));
+                return artifacts;
+            }
+        }
+    }
+    artifacts.sort_by(|a, b| a.name.cmp(&b.name));
+    artifacts
+}
+
+/// 任务实际使用的工作目录：优先取历史记录中的路径
+fn resolve_task_dir(state: &crate::AppState, task_id: &str) -> Result<PathBuf, String> {
+    match state.history.get(task_id).and_then(|r| r.work_dir) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => task_dir(task_id),
+    }
+}
+
+/// 列出任务工作目录中的文件
+#[tauri::command]
+pub async fn list_task_artifacts(
+    state: tauri::State<'_, crate::AppState>,
+    task_id: String,
+) -> Result<Vec<TaskArtifact>, String> {
+    let dir = resolve_task_dir(&state, &task_id)?;
+    if !dir.is_dir() {
+        return Err(format!("任务 {} 没有工作目录", task_id));
+    }
+    Ok(scan_artifacts(&dir))
+}
+
+/// 任务产物的绝对路径，name 为 list_task_artifacts 返回的相对路径
+pub fn resolve_artifact(
+    state: &crate::AppState,
+    task_id: &str,
+    name: &str,
+) -> Result<PathBuf, String> {
+    let dir = resolve_task_dir(state, task_id)?
+        .canonicalize()
+        .map_err(|e| format!("任务工作目录不存在: {}", e))?;
+    let path = dir
+        .join(name)
+        .canonicalize()
+        .map_err(|e| format!("产物文件不存在: {}", e))?;
+    // 拒绝 "../" 或符号链接指向工作目录之外的文件
+    if !path.starts_with(&dir) {
+        return Err(format!("不允许打开任务工作目录之外的文件: {}", name));
+    }
+    Ok(path)
+}
+
+/// 用系统默认应用打开任务产物，name 为 list_task_artifacts 返回的相对路径
+#[tauri::command]
+pub async fn open_task_artifact(
+    state: tauri::State<'_, crate::AppState>,
+    task_id: String,
+    name: String,
+) -> Result<(), String> {
+    let path = resolve_artifact(&state, &task_id, &name)?;
+    crate::file_actions::open_path(&path).map_err(String::from)
+}
@@ -0,0 +1,220 @@
+//! 沙盒监视：任务执行期间把沙盒中新建、修改、删除的文件以 sandbox-changed 事件实时发给前端
+//!
+//! 通过 notify 订阅文件系统事件，notify-debouncer-mini 合并同一路径在去抖间隔内的多次事件。
+//! 事件只给出路径，这里重新扫描变化的路径并与已知文件（路径 → 大小、修改时间）比较，
+//! 区分新建、修改和删除。只监视沙盒根目录下的非任务目录和当前任务的工作目录，其他任务的
+//! task_* 目录和撤销日志目录（.trash）不监视，避免历史任务较多时占用过多监视句柄。
+//! 任务结束时（监视句柄被丢弃）再等待一个去抖间隔，报告最后的变化后退出。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, SystemTime};
+
+use notify_debouncer_mini::notify::RecursiveMode;
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult};
+use serde::Serialize;
+use tauri::{Emitter, Window};
+
+use crate::{sandbox, undo};
+
+/// 去抖间隔：同一文件在间隔内的多次写入只报告一次
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 单次扫描的文件上限
+const MAX_ENTRIES: usize = 5000;
+
+/// sandbox-changed 事件负载
+#[derive(Debug, Clone, Serialize)]
+struct SandboxChange {
+    request_id: String,
+    created: Vec<String>,
+    modified: Vec<String>,
+    deleted: Vec<String>,
+}
+
+type Snapshot = HashMap<PathBuf, (u64, Option<SystemTime>)>;
+
+/// 沙盒根目录下的子目录是否需要监视（跳过撤销日志和其他任务的工作目录）
+fn tracked(root: &Path, own_dir: Option<&Path>, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else {
+        return false;
+    };
+    let Some(first) = relative.components().next() else {
+        return true;
+    };
+    let name = first.as_os_str().to_string_lossy();
+    let top = root.join(&*name);
+    name != undo::TRASH_DIR && (!name.starts_with("task_") || own_dir == Some(top.as_path()))
+}
+
+/// 扫描沙盒中的一个路径（文件或目录），跳过不监视的目录
+fn snapshot(root: &Path, own_dir: Option<&Path>, start: &Path) -> Snapshot {
+    let mut files = Snapshot::new();
+    if !tracked(root, own_dir, start) {
+        return files;
+    }
+    if start.is_file() {
+        if let Ok(meta) = start.metadata() {
+            files.insert(start.to_path_buf(), (meta.len(), meta.modified().ok()));
+        }
+        return files;
+    }
+    let mut pending = vec![start.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                if current != root || tracked(root, own_dir, &path) {
+                    pending.push(path);
+                }
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+            let meta = entry.metadata().ok();
+            files.insert(
+                path,
+                (
+                    meta.as_ref().map(|m| m.len()).unwrap_or(0),
+                    meta.and_then(|m| m.modified().ok()),
+                ),
+            );
+            if files.len() >= MAX_ENTRIES {
+                return files;
+            }
+        }
+    }
+    files
+}
+
+/// 比较两次快照，没有变化时返回 None
+fn diff(request_id: &str, before: &Snapshot, after: &Snapshot) -> Option<SandboxChange> {
+    let text = |p: &PathBuf| p.to_string_lossy().to_string();
+    let mut change = SandboxChange {
+        request_id: request_id.to_string(),
+        created: Vec::new(),
+        modified: Vec::new(),
+        deleted: Vec::new(),
+    };
+    for (path, stat) in after {
+        match before.get(path) {
+            None => change.created.push(text(path)),
+            Some(old) if old != stat => change.modified.push(text(path)),
+            Some(_) => {}
+        }
+    }
+    change.deleted = before
+        .keys()
+        .filter(|path| !after.contains_key(*path))
+        .map(text)
+        .collect();
+    if change.created.is_empty() && change.modified.is_empty() && change.deleted.is_empty() {
+        return None;
+    }
+    change.created.sort();
+    change.modified.sort();
+    change.deleted.sort();
+    Some(change)
+}
+
+/// 监视句柄，丢弃时停止监视
+pub struct SandboxWatch {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for SandboxWatch {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+/// 监视沙盒根目录（不递归）及其下需要监视的子目录（递归）
+fn watch_dirs(
+    watcher: &mut dyn notify_debouncer_mini::notify::Watcher,
+    root: &Path,
+    own_dir: Option<&Path>,
+) {
+    if let Err(e) = watcher.watch(root, RecursiveMode::NonRecursive) {
+        eprintln!("[Tauri] ⚠️ 监视沙盒目录失败: {}", e);
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() && tracked(root, own_dir, &path) {
+            let _ = watcher.watch(&path, RecursiveMode::Recursive);
+        }
+    }
+}
+
+/// 开始监视沙盒，直到返回的句柄被丢弃
+pub fn start(window: &Window, request_id: &str, work_dir: Option<&Path>) -> SandboxWatch {
+    let stop = Arc::new(AtomicBool::new(false));
+    let flag = stop.clone();
+    let window = window.clone();
+    let request_id = request_id.to_string();
+    let root = sandbox::sandbox_root();
+    let root = root.canonicalize().unwrap_or(root);
+    let own_dir = work_dir.map(|dir| dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf()));
+    std::thread::spawn(move || {
+        let own_dir = own_dir.as_deref();
+        let (tx, rx) = mpsc::channel::<DebounceEventResult>();
+        let mut debouncer = match new_debouncer(DEBOUNCE, tx) {
+            Ok(debouncer) => debouncer,
+            Err(e) => {
+                eprintln!("[Tauri] ⚠️ 创建沙盒监视失败: {}", e);
+                return;
+            }
+        };
+        watch_dirs(debouncer.watcher(), &root, own_dir);
+        let mut known = snapshot(&root, own_dir, &root);
+        loop {
+            let stopping = flag.load(Ordering::SeqCst);
+            let events = match rx.recv_timeout(DEBOUNCE) {
+                Ok(Ok(events)) => events,
+                Ok(Err(e)) => {
+                    eprintln!("[Tauri] ⚠️ 沙盒监视出错: {}", e);
+                    continue;
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) if !stopping => continue,
+                Err(_) => break,
+            };
+            let mut before = Snapshot::new();
+            let mut after = Snapshot::new();
+            for event in events {
+                let path = event.path;
+                if !tracked(&root, own_dir, &path) {
+                    continue;
+                }
+                // 根目录下新建的子目录需要单独开始监视
+                if path.parent() == Some(root.as_path()) && path.is_dir() {
+                    let _ = debouncer.watcher().watch(&path, RecursiveMode::Recursive);
+                }
+                before.extend(
+                    known
+                        .iter()
+                        .filter(|(known_path, _)| known_path.starts_with(&path))
+                        .map(|(known_path, stat)| (known_path.clone(), *stat)),
+                );
+                after.extend(snapshot(&root, own_dir, &path));
+            }
+            if let Some(change) = diff(&request_id, &before, &after) {
+                let _ = window.emit("sandbox-changed", &change);
+            }
+            known.retain(|path, _| !before.contains_key(path));
+            known.extend(after);
+        }
+    });
+    SandboxWatch { stop }
+}
@@ -0,0 +1,664 @@
+//! 定时任务：按 cron 表达式或固定间隔在后台执行指令，持久化到 ~/.deskjarvis/schedules.json
+//!
+//! cron 表达式为 5 段（分 时 日 月 周），按本地时间计算，支持 `*`、`a-b`、`a,b`、`*/n`。
+//! 应用未运行期间错过的执行不会补跑，启动后从当前时间重新计算下一次执行时间；
+//! 一次性任务（如延后执行的任务）例外，错过时间会在启动后立即执行，执行后自动删除。
+
+use std::path::PathBuf;
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config;
+use crate::history::{new_task_id, now_millis};
+use crate::{budget, focus, tray, window_manager};
+
+/// 检查到期任务的间隔
+const TICK_SECS: u64 = 15;
+
+/// 固定间隔的最小值（秒）
+const MIN_INTERVAL_SECS: u64 = 60;
+
+/// 向后查找 cron 下一次触发时间的上限（分钟数，约 4 年，覆盖 2 月 29 日）
+const CRON_SEARCH_LIMIT_MINUTES: i64 = 4 * 366 * 24 * 60;
+
+/// 触发方式
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScheduleTrigger {
+    /// 5 段 cron 表达式，如 "0 9 * * 1-5"（工作日 9:00）
+    Cron { expression: String },
+    /// 固定间隔（秒）
+    Interval { seconds: u64 },
+    /// 指定时间执行一次（毫秒时间戳）
+    Once { at: u64 },
+}
+
+/// 定时任务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    pub id: String,
+    pub name: String,
+    pub instruction: String,
+    pub trigger: ScheduleTrigger,
+    #[serde(default)]
+    pub paused: bool,
+    pub created_at: u64,
+    /// 上次触发时间（毫秒时间戳）
+    #[serde(default)]
+    pub last_run_at: Option<u64>,
+    /// 下次触发时间（毫秒时间戳），暂停或无法计算时为 None
+    #[serde(default)]
+    pub next_run_at: Option<u64>,
+    /// 从自动化包导入时记录包名
+    #[serde(default)]
+    pub source_pack: Option<String>,
+}
+
+/// scheduled-task-started / scheduled-task-finished 事件负载
+#[derive(Debug, Clone, Serialize)]
+struct ScheduledTaskEvent {
+    schedule_id: String,
+    task_id: String,
+    instruction: String,
+    success: Option<bool>,
+    message: Option<String>,
+}
+
+/// cron 单个字段允许的取值
+#[derive(Debug, Clone)]
+struct CronField {
+    allowed: Vec<bool>,
+    /// 字段为 `*`（用于日/周字段的“或”语义）
+    any: bool,
+}
+
+impl CronField {
+    fn parse(spec: &str, min: u32, max: u32, name: &str) -> Result<Self, String> {
+        let mut allowed = vec![false; max as usize + 1];
+        for part in spec.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => {
+                    let step: u32 = step
+                        .parse()
+                        .map_err(|_| format!("cron {} 字段步长无效: {}", name, part))?;
+                    if step == 0 {
+                        return Err(format!("cron {} 字段步长不能为 0", name));
+                    }
+                    (range, step)
+                }
+                None => (part, 1),
+            };
+
+            let (start, end) = if range == "*" {
+                (min, max)
+            } else if let Some((a, b)) = range.split_once('-') {
+                let a = a.parse().map_err(|_| format!("cron {} 字段无效: {}", name, part))?;
+                let b = b.parse().map_err(|_| format!("cron {} 字段无效: {}", name, part))?;
+                (a, b)
+            } else {
+                let v = range
+                    .parse()
+                    .map_err(|_| format!("cron {} 字段无效: {}", name, part))?;
+                // "5/15" 表示从 5 开始每 15 个单位
+                if step > 1 { (v, max) } else { (v, v) }
+            };
+
+            if start < min || end > max || start > end {
+                return Err(format!("cron {} 字段超出范围 {}-{}: {}", name, min, max, part));
+            }
+            for v in (start..=end).step_by(step as usize) {
+                allowed[v as usize] = true;
+            }
+        }
+        Ok(CronField {
+            allowed,
+            any: spec == "*",
+        })
+    }
+
+    fn matches(&self, value: u32) -> bool {
+        self.allowed.get(value as usize).copied().unwrap_or(false)
+    }
+}
+
+/// 解析后的 cron 表达式
+#[derive(Debug, Clone)]
+struct CronSpec {
+    minute: CronField,
+    hour: CronField,
+    day: CronField,
+    month: CronField,
+    weekday: CronField,
+}
+
+impl CronSpec {
+    fn parse(expression: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields.as_slice() else {
+            return Err(format!("cron 表达式需要 5 段（分 时 日 月 周）: {}", expression));
+        };
+
+        let mut weekday = CronField::parse(weekday, 0, 7, "周")?;
+        // 0 和 7 都表示周日
+        if weekday.allowed[7] {
+            weekday.allowed[0] = true;
+        }
+
+        Ok(CronSpec {
+            minute: CronField::parse(minute, 0, 59, "分")?,
+            hour: CronField::parse(hour, 0, 23, "时")?,
+            day: CronField::parse(day, 1, 31, "日")?,
+            month: CronField::parse(month, 1, 12, "月")?,
+            weekday,
+        })
+    }
+
+    /// 日与周都有限定时满足其一即可（与标准 cron 一致）
+    fn day_matches(&self, time: &DateTime<Local>) -> bool {
+        let day = self.day.matches(time.day());
+        let weekday = self.weekday.matches(time.weekday().num_days_from_sunday());
+        match (self.day.any, self.weekday.any) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// after 之后（不含）的第一个触发时间
+    fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        // 用时长加减取整：with_minute 等在夏令时回拨的重复时段内返回 None
+        let start = after
+            - Duration::seconds(after.second().into())
+            - Duration::nanoseconds(after.nanosecond().into())
+            + Duration::minutes(1);
+        let mut time = start;
+        while time - start < Duration::minutes(CRON_SEARCH_LIMIT_MINUTES) {
+            if !self.month.matches(time.month()) || !self.day_matches(&time) {
+                time = next_local_midnight(time)?;
+            } else if !self.hour.matches(time.hour()) {
+                time = time - Duration::minutes(time.minute().into()) + Duration::hours(1);
+            } else if !self.minute.matches(time.minute()) {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+}
+
+/// 次日本地 0 点（夏令时跳过 0 点时取最早的有效时间）
+fn next_local_midnight(time: DateTime<Local>) -> Option<DateTime<Local>> {
+    let date = time.date_naive().succ_opt()?;
+    let mut naive = date.and_hms_opt(0, 0, 0)?;
+    for _ in 0..4 {
+        if let Some(t) = Local.from_local_datetime(&naive).earliest() {
+            return Some(t);
+        }
+        naive += Duration::hours(1);
+    }
+    None
+}
+
+/// 校验触发方式
+fn validate_trigger(trigger: &ScheduleTrigger) -> Result<(), String> {
+    match trigger {
+        ScheduleTrigger::Cron { expression } => CronSpec::parse(expression).map(|_| ()),
+        ScheduleTrigger::Interval { seconds } if *seconds < MIN_INTERVAL_SECS => Err(format!(
+            "执行间隔不能小于 {} 秒",
+            MIN_INTERVAL_SECS
+        )),
+        ScheduleTrigger::Interval { .. } | ScheduleTrigger::Once { .. } => Ok(()),
+    }
+}
+
+/// 计算 after_ms 之后的下一次触发时间
+fn next_run(trigger: &ScheduleTrigger, after_ms: u64) -> Option<u64> {
+    match trigger {
+        ScheduleTrigger::Interval { seconds } => Some(after_ms.saturating_add(seconds.saturating_mul(1000))),
+        ScheduleTrigger::Once { at } => Some((*at).max(after_ms)),
+        ScheduleTrigger::Cron { expression } => {
+            let spec = CronSpec::parse(expression).ok()?;
+            let after = Local.timestamp_millis_opt(after_ms as i64).single()?;
+            spec.next_after(after).map(|t| t.timestamp_millis() as u64)
+        }
+    }
+}
+
+/// 毫秒时间戳格式化为本地时间（如 "10-16 09:00"）
+fn format_local(ms: u64) -> String {
+    Local
+        .timestamp_millis_opt(ms as i64)
+        .single()
+        .map(|t| t.format("%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// 新建定时任务（未保存）
+pub(crate) fn new_schedule(
+    name: Option<String>,
+    instruction: String,
+    trigger: ScheduleTrigger,
+) -> Result<Schedule, String> {
+    let instruction = instruction.trim().to_string();
+    if instruction.is_empty() {
+        return Err("指令不能为空".to_string());
+    }
+    validate_trigger(&trigger)?;
+
+    let now = now_millis();
+    let next_run_at = next_run(&trigger, now);
+    if next_run_at.is_none() {
+        return Err("该 cron 表达式没有可执行的时间".to_string());
+    }
+    Ok(Schedule {
+        id: format!("schedule_{}", now),
+        name: name
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| instruction.chars().take(30).collect()),
+        instruction,
+        trigger,
+        paused: false,
+        created_at: now,
+        last_run_at: None,
+        next_run_at,
+        source_pack: None,
+    })
+}
+
+/// 定时任务存储（内存缓存 + JSON 文件持久化）
+pub struct ScheduleStore {
+    path: Option<PathBuf>,
+    schedules: std::sync::Mutex<Vec<Schedule>>,
+}
+
+impl ScheduleStore {
+    /// 从磁盘加载，并从当前时间重新计算下一次执行时间
+    pub fn load() -> Self {
+        let path = config::get_data_dir()
+            .map(|dir| dir.join("schedules.json"))
+            .ok();
+
+        let mut schedules = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|content| match serde_json::from_str::<Vec<Schedule>>(&content) {
+                Ok(schedules) => Some(schedules),
+                Err(e) => {
+                    eprintln!("[Tauri] ⚠️ 解析定时任务失败: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        let now = now_millis();
+        for schedule in schedules.iter_mut() {
+            schedule.next_run_at = if schedule.paused {
+                None
+            } else {
+                next_run(&schedule.trigger, now)
+            };
+        }
+
+        ScheduleStore {
+            path,
+            schedules: std::sync::Mutex::new(schedules),
+        }
+    }
+
+    /// 写回磁盘（调用方持有锁）
+    fn persist(&self, schedules: &[Schedule]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Err("无法确定定时任务文件路径".to_string());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(schedules)
+            .map_err(|e| format!("序列化定时任务失败: {}", e))?;
+        std::fs::write(path, content).map_err(|e| format!("写入定时任务失败: {}", e))
+    }
+
+    /// 对定时任务做修改并持久化
+    pub(crate) fn update<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&mut Vec<Schedule>) -> Result<T, String>,
+    {
+        let mut schedules = self.schedules.lock().map_err(|_| "定时任务状态不可用")?;
+        let value = f(&mut schedules)?;
+        self.persist(&schedules)?;
+        Ok(value)
+    }
+
+    pub fn add(&self, schedule: Schedule) -> Result<(), String> {
+        self.update(|schedules| {
+            schedules.push(schedule);
+            Ok(())
+        })
+    }
+
+    pub fn list(&self) -> Vec<Schedule> {
+        self.schedules
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_default()
+    }
+
+    /// 取出已到期的任务并推进其下一次执行时间
+    fn take_due(&self, now: u64) -> Vec<Schedule> {
+        let result = self.update(|schedules| {
+            let mut due = Vec::new();
+            for schedule in schedules.iter_mut() {
+                if schedule.paused || schedule.next_run_at.is_none_or(|t| t > now) {
+                    continue;
+                }
+                schedule.last_run_at = Some(now);
+                schedule.next_run_at = next_run(&schedule.trigger, now);
+                due.push(schedule.clone());
+            }
+            // 一次性任务触发后即删除
+            schedules.retain(|s| {
+                !(matches!(s.trigger, ScheduleTrigger::Once { .. }) && s.last_run_at.is_some())
+            });
+            Ok(due)
+        });
+        result.unwrap_or_else(|e| {
+            eprintln!("[Tauri] ⚠️ {}", e);
+            Vec::new()
+        })
+    }
+}
+
+/// 定时任务变化后刷新托盘 "下一个定时任务" 并通知前端
+pub(crate) fn notify_changed(app: &AppHandle) {
+    let schedules = app.state::<crate::AppState>().schedules.list();
+    let next = schedules
+        .iter()
+        .filter(|s| !s.paused)
+        .filter_map(|s| s.next_run_at.map(|t| (t, s)))
+        .min_by_key(|(t, _)| *t)
+        .map(|(t, s)| format!("{} {}", format_local(t), s.name));
+    tray::set_next_schedule(app, next.as_deref());
+    let _ = app.emit("schedules-changed", &schedules);
+}
+
+/// 在主窗口上执行一次定时任务，并推送开始/结束事件
+async fn run_schedule(app: AppHandle, schedule: Schedule) {
+    let Some(window) = window_manager::main_window(&app) else {
+        eprintln!("[Tauri] ⚠️ 主窗口不存在，跳过定时任务 {}", schedule.name);
+        return;
+    };
+    let state = app.state::<crate::AppState>();
+    if let Err(e) = budget::admit(&app, &window, &state.history) {
+        eprintln!("[Tauri] 💸 {}，跳过定时任务 {}", e, schedule.name);
+        return;
+    }
+
+    let task_id = new_task_id();
+    let mut event = ScheduledTaskEvent {
+        schedule_id: schedule.id.clone(),
+        task_id: task_id.clone(),
+        instruction: schedule.instruction.clone(),
+        success: None,
+        message: None,
+    };
+    eprintln!("[Tauri] ⏰ 执行定时任务: {} ({})", schedule.name, task_id);
+    let _ = app.emit("scheduled-task-started", &event);
+
+    let context = serde_json::json!({ "schedule_id": schedule.id });
+    let result = crate::run_tracked_task(
+        &window,
+        &state,
+        task_id,
+        schedule.instruction,
+        Some(context),
+        crate::TaskOptions {
+            budget_checked: true,
+            ..Default::default()
+        },
+    )
+    .await;
+
+    match result {
+        Ok(r) => {
+            event.success = Some(r.success);
+            event.message = Some(r.message);
+        }
+        Err(e) => {
+            event.success = Some(false);
+            event.message = Some(e.message());
+        }
+    }
+    let _ = app.emit("scheduled-task-finished", &event);
+}
+
+/// 启动定时任务调度循环
+pub fn spawn_scheduler(app: AppHandle) {
+    notify_changed(&app);
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(TICK_SECS));
+        loop {
+            interval.tick().await;
+            // 专注时段内不取出到期任务，结束后补跑
+            if focus::is_active() {
+                continue;
+            }
+            let due = app.state::<crate::AppState>().schedules.take_due(now_millis());
+            if !due.is_empty() {
+                notify_changed(&app);
+            }
+            for schedule in due {
+                tauri::async_runtime::spawn(run_schedule(app.clone(), schedule));
+            }
+        }
+    });
+}
+
+/// 新建定时任务
+#[tauri::command]
+pub async fn create_schedule(
+    app: AppHandle,
+    state: tauri::State<'_, crate::AppState>,
+    name: Option<String>,
+    instruction: String,
+    trigger: ScheduleTrigger,
+) -> Result<Schedule, String> {
+    let schedule = new_schedule(name, instruction, trigger)?;
+    state.schedules.add(schedule.clone())?;
+    notify_changed(&app);
+    Ok(schedule)
+}
+
+/// 列出所有定时任务
+#[tauri::command]
+pub async fn list_schedules(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<Vec<Schedule>, String> {
+    Ok(state.schedules.list())
+}
+
+/// 暂停或恢复定时任务（paused 默认为 true）
+#[tauri::command]
+pub async fn pause_schedule(
+    app: AppHandle,
+    state: tauri::State<'_, crate::AppState>,
+    schedule_id: String,
+    paused: Option<bool>,
+) -> Result<Schedule, String> {
+    let paused = paused.unwrap_or(true);
+    let result = state.schedules.update(|schedules| {
+        let schedule = schedules
+            .iter_mut()
+            .find(|s| s.id == schedule_id)
+            .ok_or_else(|| format!("未找到定时任务: {}", schedule_id))?;
+        schedule.paused = paused;
+        schedule.next_run_at = if paused {
+            None
+        } else {
+            next_run(&schedule.trigger, now_millis())
+        };
+        Ok(schedule.clone())
+    });
+    notify_changed(&app);
+    result
+}
+
+/// 删除定时任务
+#[tauri::command]
+pub async fn delete_schedule(
+    app: AppHandle,
+    state: tauri::State<'_, crate::AppState>,
+    schedule_id: String,
+) -> Result<(), String> {
+    state.schedules.update(|schedules| {
+        let before = schedules.len();
+        schedules.retain(|s| s.id != schedule_id);
+        if schedules.len() == before {
+            return Err(format!("未找到定时任务: {}", schedule_id));
+        }
+        Ok(())
+    })?;
+    notify_changed(&app);
+    Ok(())
+}
+
+/// 将任务延后到指定时间（毫秒时间戳）执行，返回生成的一次性定时任务
+///
+/// 任务正在执行时先发送停止命令，到时间后以原指令重新执行。
+#[tauri::command]
+pub async fn defer_task(
+    app: AppHandle,
+    state: tauri::State<'_, crate::AppState>,
+    task_id: String,
+    until: u64,
+) -> Result<Schedule, String> {
+    if until <= now_millis() {
+        return Err("延后时间必须晚于当前时间".to_string());
+    }
+    let record = state
+        .history
+        .get(&task_id)
+        .ok_or_else(|| format!("未找到任务记录: {}", task_id))?;
+
+    let schedule = new_schedule(
+        Some(format!("延后：{}", record.instruction)),
+        record.instruction,
+        ScheduleTrigger::Once { at: until },
+    )?;
+    state.schedules.add(schedule.clone())?;
+    notify_changed(&app);
+    eprintln!("[Tauri] 💤 任务 {} 已延后至 {}", task_id, format_local(until));
+
+    if state.running.contains(&task_id) {
+        // 停止命令需等待服务锁，放到后台发送
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = crate::stop_task(app.state::<crate::AppState>(), Some(task_id)).await {
+                eprintln!("[Tauri] ⚠️ 停止被延后的任务失败: {}", e);
+            }
+        });
+    }
+    Ok(schedule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .single()
+            .expect("测试时间无效")
+    }
+
+    fn next(expression: &str, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        CronSpec::parse(expression).expect("cron 表达式无效").next_after(after)
+    }
+
+    fn values(field: &CronField) -> Vec<u32> {
+        (0..field.allowed.len() as u32).filter(|v| field.matches(*v)).collect()
+    }
+
+    #[test]
+    fn parses_ranges_lists_and_steps() {
+        assert_eq!(values(&CronField::parse("1-5", 0, 23, "时").unwrap()), [1, 2, 3, 4, 5]);
+        assert_eq!(values(&CronField::parse("1,3,9", 0, 23, "时").unwrap()), [1, 3, 9]);
+        assert_eq!(values(&CronField::parse("*/15", 0, 59, "分").unwrap()), [0, 15, 30, 45]);
+        assert_eq!(values(&CronField::parse("5/20", 0, 59, "分").unwrap()), [5, 25, 45]);
+        assert_eq!(values(&CronField::parse("10-30/10", 0, 59, "分").unwrap()), [10, 20, 30]);
+        assert_eq!(values(&CronField::parse("1-3,20-22", 0, 23, "时").unwrap()), [1, 2, 3, 20, 21, 22]);
+        assert!(CronField::parse("*", 1, 12, "月").unwrap().any);
+        assert!(!CronField::parse("*/2", 1, 12, "月").unwrap().any);
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expression in [
+            "",
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * 32 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+            "1-x * * * *",
+            "*/x * * * *",
+            "1,,2 * * * *",
+        ] {
+            assert!(CronSpec::parse(expression).is_err(), "应当拒绝: {:?}", expression);
+        }
+    }
+
+    #[test]
+    fn weekday_zero_and_seven_are_sunday() {
+        // 2026-10-18 是星期日
+        let saturday = at(2026, 10, 17, 12, 0);
+        assert_eq!(next("0 9 * * 0", saturday), Some(at(2026, 10, 18, 9, 0)));
+        assert_eq!(next("0 9 * * 7", saturday), Some(at(2026, 10, 18, 9, 0)));
+        assert_eq!(next("0 9 * * 5-7", at(2026, 10, 16, 12, 0)), Some(at(2026, 10, 17, 9, 0)));
+    }
+
+    #[test]
+    fn restricted_day_and_weekday_match_either() {
+        // 2026-10-01 是星期四；13 号或星期五，先到的是 10-02（星期五）
+        let start = at(2026, 10, 1, 12, 0);
+        assert_eq!(next("0 0 13 * 5", start), Some(at(2026, 10, 2, 0, 0)));
+        assert_eq!(next("0 0 13 * 5", at(2026, 10, 9, 12, 0)), Some(at(2026, 10, 13, 0, 0)));
+        // 只限定其一时按该字段匹配
+        assert_eq!(next("0 0 13 * *", start), Some(at(2026, 10, 13, 0, 0)));
+        assert_eq!(next("0 0 * * 5", start), Some(at(2026, 10, 2, 0, 0)));
+        // 带步长的 * 不算不限定
+        assert_eq!(next("0 0 */10 * 5", start), Some(at(2026, 10, 2, 0, 0)));
+    }
+
+    #[test]
+    fn next_after_excludes_the_current_minute() {
+        let exact = at(2026, 10, 16, 9, 30);
+        assert_eq!(next("30 9 * * *", exact), Some(at(2026, 10, 17, 9, 30)));
+        let within = exact + Duration::seconds(45);
+        assert_eq!(next("* * * * *", within), Some(at(2026, 10, 16, 9, 31)));
+        assert_eq!(next("*/15 * * * *", at(2026, 10, 16, 9, 44)), Some(at(2026, 10, 16, 9, 45)));
+        assert_eq!(next("0 * * * *", at(2026, 10, 16, 9, 59)), Some(at(2026, 10, 16, 10, 0)));
+    }
+
+    #[test]
+    fn next_after_rolls_over_days_months_and_years() {
+        assert_eq!(next("0 0 * * *", at(2026, 10, 16, 23, 59)), Some(at(2026, 10, 17, 0, 0)));
+        assert_eq!(next("15 8 * * *", at(2026, 10, 31, 9, 0)), Some(at(2026, 11, 1, 8, 15)));
+        // 9 月没有 31 号
+        assert_eq!(next("0 0 31 * *", at(2026, 9, 15, 0, 0)), Some(at(2026, 10, 31, 0, 0)));
+        assert_eq!(next("0 0 1 1 *", at(2026, 12, 31, 23, 59)), Some(at(2027, 1, 1, 0, 0)));
+        // 2 月 29 日要等到闰年
+        assert_eq!(next("0 12 29 2 *", at(2026, 3, 1, 0, 0)), Some(at(2028, 2, 29, 12, 0)));
+        // 永远不会出现的日期在搜索上限内找不到
+        assert_eq!(next("0 0 31 2 *", at(2026, 1, 1, 0, 0)), None);
+    }
+}
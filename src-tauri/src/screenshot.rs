@@ -0,0 +1,256 @@
+//! 截图：在系统层截取全屏、当前窗口或用户框选的区域，保存到沙盒并返回路径
+//!
+//! 按平台调用系统工具：macOS 用 screencapture（当前窗口的位置经 osascript 获取），
+//! Linux 依次尝试 grim/slurp（Wayland）、gnome-screenshot、scrot，Windows 用 PowerShell
+//! （不支持框选区域）。前端通过 capture_screenshot 调用；Agent 执行任务时发送
+//! screenshot_request 事件，截图结果写入 ~/.deskjarvis/screenshot_requests/<access_id>.json，
+//! 路径作为步骤结果交给后续步骤（如"截图并发邮件给我"）。
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::agent_event::ScreenshotRequestEvent;
+use crate::event_sink::EventSink;
+use crate::{config, history, observer, sandbox};
+
+/// 截图范围
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../../src/types/bindings/")]
+pub enum ScreenshotMode {
+    /// 全部屏幕
+    #[default]
+    Full,
+    /// 当前激活的窗口
+    ActiveWindow,
+    /// 用户框选的区域（交互式）
+    Region,
+}
+
+/// 运行截图工具，工具不存在时返回 Ok(false)
+fn run(program: &str, args: &[&str]) -> Result<bool, String> {
+    let output = match Command::new(program).args(args).output() {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(format!("运行 {} 失败: {}", program, e)),
+    };
+    if !output.status.success() {
+        return Err(format!(
+            "{} 执行失败: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(true)
+}
+
+#[cfg(target_os = "macos")]
+fn capture_to(mode: ScreenshotMode, path: &str) -> Result<(), String> {
+    match mode {
+        ScreenshotMode::Full => run("screencapture", &["-x", path])?,
+        ScreenshotMode::Region => run("screencapture", &["-i", "-x", path])?,
+        ScreenshotMode::ActiveWindow => {
+            let script = r#"tell application "System Events"
+    set p to first application process whose frontmost is true
+    tell front window of p
+        set {x, y} to position
+        set {w, h} to size
+    end tell
+end tell
+return (x as text) & "," & (y as text) & "," & (w as text) & "," & (h as text)"#;
+            let output = Command::new("osascript")
+                .args(["-e", script])
+                .output()
+                .map_err(|e| format!("获取当前窗口位置失败: {}", e))?;
+            if !output.status.success() {
+                return Err("获取当前窗口位置失败，请在系统设置中允许辅助功能权限".to_string());
+            }
+            let rect = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            run("screencapture", &["-x", "-R", &rect, path])?
+        }
+    };
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn capture_to(mode: ScreenshotMode, path: &str) -> Result<(), String> {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    if wayland && mode != ScreenshotMode::ActiveWindow {
+        let geometry = match mode {
+            ScreenshotMode::Region => match Command::new("slurp").output() {
+                Ok(output) if output.status.success() => {
+                    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+                }
+                Ok(_) => return Err("已取消截图".to_string()),
+                Err(_) => None,
+            },
+            _ => None,
+        };
+        let found = match &geometry {
+            Some(geometry) => run("grim", &["-g", geometry, path])?,
+            None if mode == ScreenshotMode::Full => run("grim", &[path])?,
+            None => false,
+        };
+        if found {
+            return Ok(());
+        }
+    }
+    let candidates: &[(&str, &[&str])] = match mode {
+        ScreenshotMode::Full => &[("gnome-screenshot", &["-f"]), ("scrot", &["-o"])],
+        ScreenshotMode::ActiveWindow => &[
+            ("gnome-screenshot", &["-w", "-f"]),
+            ("scrot", &["-u", "-o"]),
+        ],
+        ScreenshotMode::Region => &[
+            ("gnome-screenshot", &["-a", "-f"]),
+            ("scrot", &["-s", "-o"]),
+        ],
+    };
+    for (program, flags) in candidates {
+        let mut args = flags.to_vec();
+        args.push(path);
+        if run(program, &args)? {
+            return Ok(());
+        }
+    }
+    Err("未找到截图工具，请安装 gnome-screenshot、scrot 或 grim".to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn capture_to(mode: ScreenshotMode, path: &str) -> Result<(), String> {
+    let bounds = match mode {
+        ScreenshotMode::Full => "$b = [System.Windows.Forms.SystemInformation]::VirtualScreen",
+        ScreenshotMode::ActiveWindow => {
+            r#"Add-Type @'
+using System;
+using System.Runtime.InteropServices;
+public struct RECT { public int Left, Top, Right, Bottom; }
+public static class Win {
+    [DllImport("user32.dll")] public static extern IntPtr GetForegroundWindow();
+    [DllImport("user32.dll")] public static extern bool GetWindowRect(IntPtr h, out RECT r);
+}
+'@
+$r = New-Object RECT
+[void][Win]::GetWindowRect([Win]::GetForegroundWindow(), [ref]$r)
+$b = New-Object System.Drawing.Rectangle($r.Left, $r.Top, ($r.Right - $r.Left), ($r.Bottom - $r.Top))"#
+        }
+        ScreenshotMode::Region => {
+            return Err("Windows 暂不支持框选区域截图，请使用全屏或当前窗口".to_string())
+        }
+    };
+    let script = format!(
+        r#"Add-Type -AssemblyName System.Windows.Forms, System.Drawing
+{}
+$bmp = New-Object System.Drawing.Bitmap($b.Width, $b.Height)
+$g = [System.Drawing.Graphics]::FromImage($bmp)
+$g.CopyFromScreen($b.Left, $b.Top, 0, 0, $bmp.Size)
+$bmp.Save('{}', [System.Drawing.Imaging.ImageFormat]::Png)"#,
+        bounds,
+        path.replace('\'', "''")
+    );
+    run(
+        "powershell",
+        &["-NoProfile", "-NonInteractive", "-Command", &script],
+    )?;
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn capture_to(_mode: ScreenshotMode, _path: &str) -> Result<(), String> {
+    Err("当前平台不支持截图".to_string())
+}
+
+/// 截图保存目录：任务工作目录，没有时为沙盒下的 screenshots
+fn output_dir(work_dir: Option<&Path>) -> PathBuf {
+    match work_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => sandbox::sandbox_root().join("screenshots"),
+    }
+}
+
+/// 截图并返回保存路径（框选区域时阻塞到用户完成选择）
+pub fn capture(mode: ScreenshotMode, work_dir: Option<&Path>) -> Result<PathBuf, String> {
+    let dir = output_dir(work_dir);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    let path = dir.join(format!("screenshot_{}.png", history::now_millis()));
+    capture_to(mode, &path.to_string_lossy())?;
+    // 框选时按 Esc 取消，工具正常退出但不生成文件
+    if !path.is_file() {
+        return Err("已取消截图".to_string());
+    }
+    eprintln!("[Tauri] 📸 截图已保存: {}", path.display());
+    Ok(path)
+}
+
+/// access_id 用作文件名，只允许字母、数字和下划线
+fn is_valid_access_id(access_id: &str) -> bool {
+    !access_id.is_empty()
+        && access_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 写入截图结果，供 Python 轮询读取
+fn write_response(access_id: &str, result: &Result<PathBuf, String>) -> Result<(), String> {
+    let dir = config::get_data_dir()?.join("screenshot_requests");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    let content = match result {
+        Ok(path) => serde_json::json!({ "access_id": access_id, "path": path }),
+        Err(e) => serde_json::json!({ "access_id": access_id, "error": e }),
+    };
+    // 先写临时文件再改名，避免 Python 读到半个文件
+    let path = dir.join(format!("{}.json", access_id));
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, content.to_string()).map_err(|e| format!("写入截图结果失败: {}", e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("写入截图结果失败: {}", e))
+}
+
+/// 处理一条 screenshot_request 事件：在后台线程截图，不阻塞事件读取
+///
+/// 观察模式下拒绝；非交互模式（命令行）无人框选，区域截图直接失败。
+pub fn handle_request(
+    sink: &impl EventSink,
+    work_dir: Option<&Path>,
+    event: &ScreenshotRequestEvent,
+) {
+    let access_id = event.access_id.clone();
+    if !is_valid_access_id(&access_id) {
+        eprintln!("[Tauri] ⚠️ 无效的截图请求 ID: {}", access_id);
+        return;
+    }
+    let denied = if observer::is_enabled() {
+        Some("观察模式下不允许截图")
+    } else if !sink.interactive() && event.mode == ScreenshotMode::Region {
+        Some("非交互模式不支持框选区域截图")
+    } else {
+        None
+    };
+    if let Some(reason) = denied {
+        if let Err(e) = write_response(&access_id, &Err(reason.to_string())) {
+            eprintln!("[Tauri] ⚠️ {}", e);
+        }
+        return;
+    }
+
+    let mode = event.mode;
+    let work_dir = work_dir.map(Path::to_path_buf);
+    std::thread::spawn(move || {
+        let result = capture(mode, work_dir.as_deref());
+        if let Err(e) = write_response(&access_id, &result) {
+            eprintln!("[Tauri] ⚠️ {}", e);
+        }
+    });
+}
+
+/// 截图并返回保存路径（默认全屏）
+#[tauri::command]
+pub async fn capture_screenshot(mode: Option<ScreenshotMode>) -> Result<String, String> {
+    let mode = mode.unwrap_or_default();
+    let path = tauri::async_runtime::spawn_blocking(move || capture(mode, None))
+        .await
+        .map_err(|e| format!("截图失败: {}", e))??;
+    Ok(path.to_string_lossy().to_string())
+}
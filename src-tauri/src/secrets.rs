@@ -0,0 +1,32 @@
+//! 系统钥匙串访问（macOS Keychain / Windows Credential Manager / Secret Service）
+
+/// 钥匙串服务名
+const SERVICE: &str = "DeskJarvis";
+
+fn entry(key: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, key).map_err(|e| format!("访问系统钥匙串失败: {}", e))
+}
+
+/// 读取密钥，不存在时返回 None
+pub fn get_secret(key: &str) -> Result<Option<String>, String> {
+    match entry(key)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("读取钥匙串条目 {} 失败: {}", key, e)),
+    }
+}
+
+/// 写入密钥
+pub fn set_secret(key: &str, value: &str) -> Result<(), String> {
+    entry(key)?
+        .set_password(value)
+        .map_err(|e| format!("写入钥匙串条目 {} 失败: {}", key, e))
+}
+
+/// 删除密钥（不存在时视为成功）
+pub fn delete_secret(key: &str) -> Result<(), String> {
+    match entry(key)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("删除钥匙串条目 {} 失败: {}", key, e)),
+    }
+}
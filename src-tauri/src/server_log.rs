@@ -0,0 +1,133 @@
+//! 调试控制台：Python 服务的 stderr 日志按行解析级别，保存在环形缓冲区中，
+//! 前端订阅期间以 server-log 事件实时转发
+//!
+//! 日志由 spawn_python_server 的 stderr 读取任务送入（已脱敏），主服务和额外工作进程共用，
+//! 以 pid 区分。级别取自 Python logging 格式中的 DEBUG / INFO / WARNING / ERROR / CRITICAL，
+//! 没有级别的行（如异常堆栈）沿用同一进程上一行的级别。subscribe_server_logs 开启订阅时
+//! 返回缓冲区中最后若干行，供控制台打开时补齐历史。
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::history::now_millis;
+
+/// 缓冲区保留的行数
+const BUFFER_LINES: usize = 2000;
+
+/// 开启订阅时默认返回的行数
+const DEFAULT_TAIL: usize = 200;
+
+/// 最近的日志行
+static BUFFER: Mutex<VecDeque<ServerLogLine>> = Mutex::new(VecDeque::new());
+
+/// 下一行的序号
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// 前端是否在订阅
+static SUBSCRIBED: AtomicBool = AtomicBool::new(false);
+
+/// 订阅时登记的 AppHandle，用于发送事件
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// 日志级别
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl LogLevel {
+    /// 从一行日志开头（时间、logger 名之后）找出 logging 的级别名
+    fn parse(line: &str) -> Option<Self> {
+        line.split(|c: char| c.is_whitespace() || matches!(c, '-' | '[' | ']' | ':'))
+            .filter(|word| !word.is_empty())
+            .take(12)
+            .find_map(|word| match word {
+                "DEBUG" => Some(LogLevel::Debug),
+                "INFO" => Some(LogLevel::Info),
+                "WARNING" | "WARN" => Some(LogLevel::Warning),
+                "ERROR" | "CRITICAL" => Some(LogLevel::Error),
+                _ => None,
+            })
+    }
+}
+
+/// 一行日志，也是 server-log 事件的负载
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerLogLine {
+    /// 递增序号，前端据此去重
+    pub seq: u64,
+    pub timestamp: u64,
+    /// 输出该行的 Python 进程
+    pub pid: Option<u32>,
+    pub level: LogLevel,
+    pub message: String,
+}
+
+/// 一个 Python 进程的日志来源（每个 stderr 读取任务一个）
+pub struct Feed {
+    pid: Option<u32>,
+    last_level: LogLevel,
+}
+
+impl Feed {
+    pub fn new(pid: Option<u32>) -> Self {
+        Feed {
+            pid,
+            last_level: LogLevel::Info,
+        }
+    }
+
+    /// 记录一行已脱敏的日志，订阅中时转发给前端
+    pub fn push(&mut self, line: &str) {
+        let message = line.trim_end();
+        if message.is_empty() {
+            return;
+        }
+        if let Some(level) = LogLevel::parse(message) {
+            self.last_level = level;
+        }
+        let entry = ServerLogLine {
+            seq: NEXT_SEQ.fetch_add(1, Ordering::Relaxed),
+            timestamp: now_millis(),
+            pid: self.pid,
+            level: self.last_level,
+            message: message.to_string(),
+        };
+        if SUBSCRIBED.load(Ordering::Relaxed) {
+            if let Some(app) = APP.get() {
+                let _ = app.emit("server-log", &entry);
+            }
+        }
+        if let Ok(mut buffer) = BUFFER.lock() {
+            if buffer.len() >= BUFFER_LINES {
+                buffer.pop_front();
+            }
+            buffer.push_back(entry);
+        }
+    }
+}
+
+/// 开启或关闭日志转发；开启时返回最近 tail 行（默认 200 行）
+#[tauri::command]
+pub async fn subscribe_server_logs(
+    app: AppHandle,
+    active: bool,
+    tail: Option<usize>,
+) -> Result<Vec<ServerLogLine>, String> {
+    let _ = APP.set(app);
+    SUBSCRIBED.store(active, Ordering::Relaxed);
+    if !active {
+        return Ok(Vec::new());
+    }
+    let buffer = BUFFER.lock().map_err(|_| "服务日志缓冲区不可用")?;
+    let skip = buffer.len().saturating_sub(tail.unwrap_or(DEFAULT_TAIL));
+    Ok(buffer.iter().skip(skip).cloned().collect())
+}
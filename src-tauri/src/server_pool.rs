@@ -0,0 +1,292 @@
+//! 常驻服务工作进程池：主服务忙时把任务分派给额外的 Python 服务进程
+//!
+//! 配置 server_pool_size 默认为 1，只使用主服务（AppState.server），行为与单进程一致。
+//! 大于 1 时最多再维护 size - 1 个工作进程：主服务忙时优先分派给已启动的空闲进程，
+//! 没有则在空位上启动新进程，全部繁忙时排队等待主服务。额外进程空闲超过
+//! server_pool_idle_secs（默认 300）后结束。主服务仍负责健康检查、资源监控与配置热加载，
+//! 额外进程崩溃后不自动重启，下次分派时按需启动；保存配置后空闲的额外进程被结束，
+//! 之后按新配置启动。
+//!
+//! 多个任务可能同时在不同进程中执行，RunningTasks 记录每个正在执行的任务及其所在的进程，
+//! stop_task 据此把停止命令发给执行该任务的进程。
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::error::DeskJarvisError;
+use crate::{config, metrics, AppState, PythonServer};
+
+/// 进程池上限（含主服务）
+const MAX_POOL_SIZE: usize = 8;
+
+/// 默认空闲回收时间（秒）
+const DEFAULT_IDLE_SECS: u64 = 300;
+
+/// 空闲回收检查间隔
+const REAP_INTERVAL_SECS: u64 = 30;
+
+/// 任务分派到的进程
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Worker {
+    /// 主服务（AppState.server）
+    Primary,
+    /// 额外工作进程的槽位
+    Extra(usize),
+}
+
+/// 正在执行的任务及其所在的进程（排队、单次进程模式和重试等待期间为 None）
+#[derive(Default)]
+pub struct RunningTasks {
+    tasks: std::sync::Mutex<HashMap<String, Option<Worker>>>,
+}
+
+/// 任务执行期间持有，结束时移除登记
+pub struct Running<'a> {
+    tasks: &'a RunningTasks,
+    request_id: String,
+}
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut tasks) = self.tasks.tasks.lock() {
+            tasks.remove(&self.request_id);
+        }
+    }
+}
+
+impl RunningTasks {
+    /// 登记开始执行的任务
+    pub fn enter(&self, request_id: &str) -> Running<'_> {
+        self.assign(request_id, None);
+        Running {
+            tasks: self,
+            request_id: request_id.to_string(),
+        }
+    }
+
+    /// 记录任务分派到的进程
+    pub fn assign(&self, request_id: &str, worker: Option<Worker>) {
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.insert(request_id.to_string(), worker);
+        }
+    }
+
+    pub fn contains(&self, request_id: &str) -> bool {
+        self.tasks
+            .lock()
+            .map(|tasks| tasks.contains_key(request_id))
+            .unwrap_or(false)
+    }
+
+    /// 是否有任务正在执行
+    pub fn any(&self) -> bool {
+        self.tasks.lock().map(|tasks| !tasks.is_empty()).unwrap_or(false)
+    }
+
+    /// 正在执行的任务 ID
+    pub fn ids(&self) -> Vec<String> {
+        self.tasks
+            .lock()
+            .map(|tasks| tasks.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 执行任务的进程，任务不在执行或不在常驻服务中时返回 None
+    pub fn worker(&self, request_id: &str) -> Option<Worker> {
+        self.tasks
+            .lock()
+            .ok()
+            .and_then(|tasks| tasks.get(request_id).copied().flatten())
+    }
+}
+
+/// 额外工作进程池
+pub struct ServerPool {
+    workers: Vec<Mutex<Option<PythonServer>>>,
+    last_used: std::sync::Mutex<Vec<Instant>>,
+}
+
+impl Default for ServerPool {
+    fn default() -> Self {
+        ServerPool {
+            workers: (1..MAX_POOL_SIZE).map(|_| Mutex::new(None)).collect(),
+            last_used: std::sync::Mutex::new(vec![Instant::now(); MAX_POOL_SIZE - 1]),
+        }
+    }
+}
+
+/// 配置的额外进程数（不含主服务）
+fn extra_workers() -> usize {
+    let size = config::load_config()
+        .ok()
+        .and_then(|c| c.server_pool_size)
+        .unwrap_or(1) as usize;
+    size.clamp(1, MAX_POOL_SIZE) - 1
+}
+
+fn idle_timeout() -> Duration {
+    let secs = config::load_config()
+        .ok()
+        .and_then(|c| c.server_pool_idle_secs)
+        .unwrap_or(DEFAULT_IDLE_SECS);
+    Duration::from_secs(secs)
+}
+
+impl ServerPool {
+    /// 为任务取得一个进程槽位：主服务空闲时用主服务，否则用空闲的额外进程，都忙时等待主服务
+    pub async fn acquire<'a>(
+        &'a self,
+        primary: &'a Mutex<Option<PythonServer>>,
+    ) -> (Worker, MutexGuard<'a, Option<PythonServer>>) {
+        if let Ok(guard) = primary.try_lock() {
+            return (Worker::Primary, guard);
+        }
+        let extra = extra_workers();
+        if extra == 0 {
+            return (Worker::Primary, wait_for(primary).await);
+        }
+
+        let mut vacant = None;
+        for (index, slot) in self.workers[..extra].iter().enumerate() {
+            let Ok(guard) = slot.try_lock() else {
+                continue;
+            };
+            if guard.is_some() {
+                self.touch(Worker::Extra(index));
+                return (Worker::Extra(index), guard);
+            }
+            if vacant.is_none() {
+                vacant = Some((index, guard));
+            }
+        }
+        if let Some((index, guard)) = vacant {
+            self.touch(Worker::Extra(index));
+            return (Worker::Extra(index), guard);
+        }
+        (Worker::Primary, wait_for(primary).await)
+    }
+
+    /// 进程槽位：主服务或额外进程
+    pub fn slot<'a>(
+        &'a self,
+        primary: &'a Mutex<Option<PythonServer>>,
+        worker: Worker,
+    ) -> &'a Mutex<Option<PythonServer>> {
+        match worker {
+            Worker::Primary => primary,
+            Worker::Extra(index) => &self.workers[index],
+        }
+    }
+
+    /// 记录额外进程最近一次使用时间
+    pub fn touch(&self, worker: Worker) {
+        if let Worker::Extra(index) = worker {
+            if let Ok(mut last_used) = self.last_used.lock() {
+                last_used[index] = Instant::now();
+            }
+        }
+    }
+
+    /// 结束空闲的额外进程；force 为 false 时只结束超过空闲时间或超出配置数量的进程
+    pub async fn shutdown_idle(&self, force: bool) {
+        let extra = extra_workers();
+        let timeout = idle_timeout();
+        for (index, slot) in self.workers.iter().enumerate() {
+            let Ok(mut guard) = slot.try_lock() else {
+                continue;
+            };
+            if guard.is_none() {
+                continue;
+            }
+            let expired = self
+                .last_used
+                .lock()
+                .map(|last_used| last_used[index].elapsed() >= timeout)
+                .unwrap_or(false);
+            if force || index >= extra || expired {
+                if let Some(mut server) = guard.take() {
+                    let _ = server.child.kill().await;
+                    eprintln!("[Tauri] 💤 结束空闲的 Python 工作进程 #{}", index + 1);
+                }
+            }
+        }
+    }
+}
+
+/// 排队等待进程空闲（计入排队深度指标）
+async fn wait_for(server: &Mutex<Option<PythonServer>>) -> MutexGuard<'_, Option<PythonServer>> {
+    let _queued = metrics::Queued::enter();
+    server.lock().await
+}
+
+/// 确保额外工作进程正在运行，不计入主服务的重启预算
+pub async fn ensure_worker(server_opt: &mut Option<PythonServer>) -> Result<(), DeskJarvisError> {
+    let alive = server_opt
+        .as_mut()
+        .is_some_and(|s| matches!(s.child.try_wait(), Ok(None)));
+    if !alive {
+        *server_opt = None;
+        eprintln!("[Tauri] 🚀 启动额外的 Python 工作进程...");
+        *server_opt = Some(crate::spawn_python_server().await?);
+    }
+    Ok(())
+}
+
+/// 向空闲的服务进程发送控制命令并等待应答：主服务忙时用额外进程，都忙时排队等待主服务
+pub async fn send_command(
+    state: &AppState,
+    cmd: &serde_json::Value,
+    reply_type: &str,
+    timeout: Duration,
+) -> Result<serde_json::Value, String> {
+    let (worker, mut guard) = state.pool.acquire(&state.server).await;
+    if worker == Worker::Primary {
+        crate::ensure_server_alive(&mut guard).await?;
+    } else {
+        ensure_worker(&mut guard).await?;
+    }
+    let server = guard.as_mut().ok_or("Python 服务未运行")?;
+    let reply = crate::send_control_command(server, cmd, reply_type, timeout).await;
+    state.pool.touch(worker);
+    reply
+}
+
+/// 后台定期回收空闲的额外工作进程
+pub fn spawn_idle_reaper(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(REAP_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            app.state::<AppState>().pool.shutdown_idle(false).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn running_tasks_track_each_task_separately() {
+        let running = RunningTasks::default();
+        let first = running.enter("task_1");
+        let second = running.enter("task_2");
+        running.assign("task_1", Some(Worker::Primary));
+        running.assign("task_2", Some(Worker::Extra(0)));
+        assert_eq!(running.worker("task_1"), Some(Worker::Primary));
+        assert_eq!(running.worker("task_2"), Some(Worker::Extra(0)));
+
+        drop(first);
+        assert!(!running.contains("task_1"));
+        assert!(running.any());
+        assert_eq!(running.ids(), ["task_2"]);
+
+        running.assign("task_2", None);
+        assert!(running.contains("task_2"));
+        assert_eq!(running.worker("task_2"), None);
+        drop(second);
+        assert!(!running.any());
+    }
+}
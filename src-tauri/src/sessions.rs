@@ -0,0 +1,207 @@
+//! 会话：同一会话内的任务共享上下文，支持 "再把它压缩一下" 这类追问
+//!
+//! Rust 侧在 ~/.deskjarvis/sessions.json 保存会话元数据，并在 execute 命令中带上 session_id；
+//! Python 端按 session_id 在 ~/.deskjarvis/sessions/<id>.json 保存对话和最近操作的文件，
+//! 执行时并入 context（见 agent/tools/session_memory.py）。删除会话时两者一并删除。
+
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, Window};
+
+use crate::error::DeskJarvisError;
+use crate::history::{new_id, new_task_id, now_millis};
+use crate::{config, focus, project_context, TaskOptions, TaskResult};
+
+/// 未命名会话的默认标题，首个任务后改为指令摘要
+const DEFAULT_TITLE: &str = "新会话";
+
+/// 自动生成标题的最大字符数
+const TITLE_MAX_CHARS: usize = 30;
+
+/// 会话元数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    pub title: String,
+    pub created_at: u64,
+    /// 最近一次执行任务的时间
+    pub updated_at: u64,
+    pub task_ids: Vec<String>,
+}
+
+/// 会话存储（内存缓存 + JSON 文件持久化）
+pub struct SessionStore {
+    path: Option<PathBuf>,
+    sessions: std::sync::Mutex<Vec<Session>>,
+}
+
+/// Python 端保存的会话记忆文件
+pub fn memory_path(id: &str) -> Result<PathBuf, String> {
+    Ok(config::get_data_dir()?
+        .join("sessions")
+        .join(format!("{}.json", id)))
+}
+
+impl SessionStore {
+    /// 从磁盘加载会话，文件不存在或损坏时从空列表开始
+    pub fn load() -> Self {
+        let path = config::get_data_dir()
+            .map(|dir| dir.join("sessions.json"))
+            .ok();
+        let sessions = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|content| match serde_json::from_str::<Vec<Session>>(&content) {
+                Ok(sessions) => Some(sessions),
+                Err(e) => {
+                    eprintln!("[Tauri] ⚠️ 解析会话列表失败: {}，将重新记录", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        SessionStore {
+            path,
+            sessions: std::sync::Mutex::new(sessions),
+        }
+    }
+
+    /// 写回磁盘（调用方持有锁）
+    fn persist(&self, sessions: &[Session]) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        match serde_json::to_string_pretty(sessions) {
+            Ok(content) => {
+                if let Err(e) = std::fs::write(path, content) {
+                    eprintln!("[Tauri] ⚠️ 写入会话列表失败: {}", e);
+                }
+            }
+            Err(e) => eprintln!("[Tauri] ⚠️ 序列化会话列表失败: {}", e),
+        }
+    }
+
+    /// 新建会话
+    pub fn create(&self, title: Option<String>) -> Result<Session, String> {
+        let now = now_millis();
+        let session = Session {
+            id: new_id("session"),
+            title: title
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .unwrap_or_else(|| DEFAULT_TITLE.to_string()),
+            created_at: now,
+            updated_at: now,
+            task_ids: Vec::new(),
+        };
+        let mut sessions = self.sessions.lock().map_err(|_| "会话状态不可用")?;
+        sessions.push(session.clone());
+        self.persist(&sessions);
+        Ok(session)
+    }
+
+    /// 登记会话中的新任务；未命名会话以首个指令作为标题
+    pub fn add_task(&self, id: &str, task_id: &str, instruction: &str) -> Result<(), String> {
+        let mut sessions = self.sessions.lock().map_err(|_| "会话状态不可用")?;
+        let session = sessions
+            .iter_mut()
+            .find(|s| s.id == id)
+            .ok_or_else(|| format!("未找到会话: {}", id))?;
+        if session.task_ids.is_empty() && session.title == DEFAULT_TITLE {
+            session.title = instruction.trim().chars().take(TITLE_MAX_CHARS).collect();
+        }
+        session.task_ids.push(task_id.to_string());
+        session.updated_at = now_millis();
+        self.persist(&sessions);
+        Ok(())
+    }
+
+    /// 按最近使用时间倒序列出会话
+    pub fn list(&self) -> Vec<Session> {
+        let mut sessions = self
+            .sessions
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_default();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+        sessions
+    }
+
+    /// 删除会话元数据
+    pub fn remove(&self, id: &str) -> Result<Session, String> {
+        let mut sessions = self.sessions.lock().map_err(|_| "会话状态不可用")?;
+        let index = sessions
+            .iter()
+            .position(|s| s.id == id)
+            .ok_or_else(|| format!("未找到会话: {}", id))?;
+        let session = sessions.remove(index);
+        self.persist(&sessions);
+        Ok(session)
+    }
+}
+
+/// 新建会话
+#[tauri::command]
+pub async fn create_session(
+    state: tauri::State<'_, crate::AppState>,
+    title: Option<String>,
+) -> Result<Session, String> {
+    state.sessions.create(title)
+}
+
+/// 在会话中执行指令，Agent 可引用同一会话之前的对话和文件
+#[tauri::command]
+pub async fn execute_in_session(
+    window: Window,
+    state: tauri::State<'_, crate::AppState>,
+    session_id: String,
+    instruction: String,
+    context: Option<serde_json::Value>,
+    max_cost_usd: Option<f64>,
+) -> Result<TaskResult, DeskJarvisError> {
+    if focus::blocks_interactive() {
+        return Err(DeskJarvisError::FocusActive);
+    }
+    let instruction =
+        project_context::apply_placeholder(window.app_handle(), instruction, context.as_ref())?;
+    let request_id = new_task_id();
+    state.sessions.add_task(&session_id, &request_id, &instruction)?;
+    crate::run_tracked_task(
+        &window,
+        &state,
+        request_id,
+        instruction,
+        context,
+        TaskOptions {
+            max_cost_usd,
+            session_id: Some(session_id),
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+/// 列出会话（最近使用的在前）
+#[tauri::command]
+pub async fn list_sessions(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<Vec<Session>, String> {
+    Ok(state.sessions.list())
+}
+
+/// 删除会话及 Agent 保存的会话记忆（任务历史保留）
+#[tauri::command]
+pub async fn delete_session(
+    state: tauri::State<'_, crate::AppState>,
+    session_id: String,
+) -> Result<(), String> {
+    let session = state.sessions.remove(&session_id)?;
+    let path = memory_path(&session.id)?;
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("删除会话记忆失败: {}", e))?;
+    }
+    eprintln!("[Tauri] 🗑 已删除会话: {}", session.title);
+    Ok(())
+}
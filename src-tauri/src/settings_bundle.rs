@@ -0,0 +1,400 @@
+//! 设置包：把配置、定时任务、脱敏规则和自定义指令（工作流模板、快捷指令）导出为一个 JSON 文件，
+//! 便于在多台电脑之间迁移
+//!
+//! 导出时抹去 API Key、邮箱密码、日历密码、HTTP 接口令牌、Webhook 地址和签名密钥、MCP 服务器的密钥类环境变量和代理地址中的
+//! 用户名密码，并在 redacted 中记录被抹去的字段。导入时这些字段沿用本机已有的值，本机也没有的
+//! 在报告中列出，需要重新填写。钥匙串中的环境变量密钥本来就不写入配置，不随包迁移。
+//!
+//! 导入先整体校验再写入：配置整体替换（沙盒路径与本机相关，保留本机设置），定时任务、工作流、
+//! 快捷指令和脱敏规则按名称（脱敏规则按 ID）合并，同名条目以包中为准，本机独有的条目保留。
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::automation_pack::{read_json, write_json};
+use crate::config::{self, AppConfig};
+use crate::history::now_millis;
+use crate::redaction::{self, RedactionRule};
+use crate::scheduler::{self, ScheduleTrigger};
+use crate::{config_migration, http_api};
+
+/// 当前设置包格式版本
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// MCP 服务器环境变量名包含这些片段时视为密钥
+const SECRET_ENV_HINTS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "PASSWD", "AUTH", "CREDENTIAL"];
+
+/// 定时任务规则（不含运行状态）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleSchedule {
+    pub name: String,
+    pub instruction: String,
+    pub trigger: ScheduleTrigger,
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// 设置包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub format_version: u32,
+    /// 导出时间（毫秒时间戳）
+    pub exported_at: u64,
+    /// 导出时的应用版本
+    #[serde(default)]
+    pub app_version: String,
+    /// 配置（敏感字段已抹去），保留 JSON 形式以便按 version 迁移
+    pub config: serde_json::Value,
+    /// 被抹去的敏感字段，如 "api_key"、"profiles.work.api_key"
+    #[serde(default)]
+    pub redacted: Vec<String>,
+    #[serde(default)]
+    pub schedules: Vec<BundleSchedule>,
+    #[serde(default)]
+    pub redaction_rules: Vec<RedactionRule>,
+    /// workflows.json 的内容（名称 → 工作流）
+    #[serde(default)]
+    pub workflows: serde_json::Map<String, serde_json::Value>,
+    /// favorites.json 的内容（快捷指令）
+    #[serde(default)]
+    pub favorites: Vec<serde_json::Value>,
+}
+
+/// 导入报告
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsImportReport {
+    pub exported_at: u64,
+    pub app_version: String,
+    pub schedules: usize,
+    pub redaction_rules: usize,
+    pub workflows: usize,
+    pub favorites: usize,
+    /// 沿用本机已有值的敏感字段
+    pub kept_secrets: Vec<String>,
+    /// 本机没有对应值、需要重新填写的敏感字段
+    pub missing_secrets: Vec<String>,
+}
+
+fn is_secret_env(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    SECRET_ENV_HINTS.iter().any(|hint| name.contains(hint))
+}
+
+/// 依次访问配置中的敏感字段：(字段路径, 值)
+pub(crate) fn visit_secrets(config: &mut AppConfig, mut f: impl FnMut(String, &mut String)) {
+    f("api_key".to_string(), &mut config.api_key);
+    for profile in config.profiles.iter_mut() {
+        f(format!("profiles.{}.api_key", profile.name), &mut profile.api_key);
+    }
+    for (path, field) in [
+        ("email_password", &mut config.email_password),
+        ("email_oauth_client_secret", &mut config.email_oauth_client_secret),
+        ("http_api_token", &mut config.http_api_token),
+    ] {
+        if let Some(value) = field.as_mut() {
+            f(path.to_string(), value);
+        }
+    }
+    for calendar in config.calendars.iter_mut() {
+        if let Some(password) = calendar.password.as_mut() {
+            f(format!("calendars.{}.password", calendar.name), password);
+        }
+    }
+    // 机器人的 Webhook 地址本身带有令牌
+    for hook in config.webhooks.iter_mut() {
+        f(format!("webhooks.{}.url", hook.name), &mut hook.url);
+        if let Some(secret) = hook.secret.as_mut() {
+            f(format!("webhooks.{}.secret", hook.name), secret);
+        }
+    }
+    for server in config.mcp_servers.iter_mut() {
+        for (name, value) in server.env.iter_mut().filter(|(name, _)| is_secret_env(name)) {
+            f(format!("mcp_servers.{}.env.{}", server.name, name), value);
+        }
+    }
+}
+
+/// 去掉代理地址中的用户名密码，没有时返回 None
+fn strip_proxy_credentials(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let (authority, path) = rest.split_once('/').map_or((rest, None), |(a, p)| (a, Some(p)));
+    let (_, host_port) = authority.rsplit_once('@')?;
+    Some(match path {
+        Some(path) => format!("{}://{}/{}", scheme, host_port, path),
+        None => format!("{}://{}", scheme, host_port),
+    })
+}
+
+/// 抹去敏感字段，返回被抹去的字段路径
+fn redact_secrets(config: &mut AppConfig) -> Vec<String> {
+    let mut redacted = Vec::new();
+    visit_secrets(config, |path, value| {
+        if !value.is_empty() {
+            value.clear();
+            redacted.push(path);
+        }
+    });
+    if let Some(stripped) = config.proxy_url.as_deref().and_then(strip_proxy_credentials) {
+        config.proxy_url = Some(stripped);
+        redacted.push("proxy_url".to_string());
+    }
+    // 钥匙串中的密钥本来就不写入配置，这里只是确保不带出
+    for entry in config.env.values_mut().chain(config.profiles.iter_mut().flat_map(|p| p.env.values_mut())) {
+        if entry.secret {
+            entry.value.clear();
+        }
+    }
+    redacted
+}
+
+/// 用本机配置补回被抹去的敏感字段，返回 (沿用的字段, 缺失的字段)
+fn restore_secrets(config: &mut AppConfig, local: &AppConfig, redacted: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut local_values = BTreeMap::new();
+    visit_secrets(&mut local.clone(), |path, value| {
+        local_values.insert(path, value.clone());
+    });
+
+    let (mut kept, mut missing) = (Vec::new(), Vec::new());
+    visit_secrets(config, |path, value| {
+        if !value.is_empty() || !redacted.contains(&path) {
+            return;
+        }
+        match local_values.get(&path).filter(|v| !v.is_empty()) {
+            Some(local_value) => {
+                *value = local_value.clone();
+                kept.push(path);
+            }
+            None => missing.push(path),
+        }
+    });
+
+    // 代理地址只有主机相同时才沿用本机的用户名密码
+    if redacted.iter().any(|p| p == "proxy_url") {
+        let local_proxy = local.proxy_url.clone().unwrap_or_default();
+        if strip_proxy_credentials(&local_proxy).is_some_and(|stripped| Some(&stripped) == config.proxy_url.as_ref()) {
+            config.proxy_url = Some(local_proxy);
+            kept.push("proxy_url".to_string());
+        } else {
+            missing.push("proxy_url".to_string());
+        }
+    }
+
+    // 扁平的 api_key 是激活档案的镜像，以补回后的档案为准
+    if let Some(active) = config.active_profile.clone() {
+        if let Some(profile) = config.profiles.iter().find(|p| p.name == active) {
+            config.api_key = profile.api_key.clone();
+        }
+    }
+    (kept, missing)
+}
+
+/// 取出快捷指令的名称
+fn favorite_name(favorite: &serde_json::Value) -> &str {
+    favorite.get("name").and_then(|v| v.as_str()).unwrap_or("")
+}
+
+/// 整体校验设置包，返回迁移后的配置
+fn validate_bundle(bundle: &SettingsBundle) -> Result<AppConfig, String> {
+    let mut errors = Vec::new();
+    if bundle.format_version == 0 || bundle.format_version > BUNDLE_FORMAT_VERSION {
+        errors.push(format!(
+            "不支持的设置包格式版本 {}（当前支持 {}）",
+            bundle.format_version, BUNDLE_FORMAT_VERSION
+        ));
+    }
+
+    let mut value = bundle.config.clone();
+    let config = config_migration::migrate(&mut value)
+        .and_then(|_| serde_json::from_value::<AppConfig>(value).map_err(|e| format!("解析配置失败: {}", e)));
+    if let Err(e) = &config {
+        errors.push(e.clone());
+    }
+
+    for schedule in &bundle.schedules {
+        if let Err(e) = scheduler::new_schedule(
+            Some(schedule.name.clone()),
+            schedule.instruction.clone(),
+            schedule.trigger.clone(),
+        ) {
+            errors.push(format!("定时任务 {}: {}", schedule.name, e));
+        }
+    }
+    for rule in &bundle.redaction_rules {
+        if rule.id.is_empty() {
+            errors.push(format!("脱敏规则 {} 缺少 ID", rule.name));
+        } else if let Err(e) = redaction::compile_rule(rule) {
+            errors.push(format!("脱敏规则 {}: {}", rule.name, e));
+        }
+    }
+    for (name, workflow) in &bundle.workflows {
+        if !workflow.is_object() {
+            errors.push(format!("工作流 {} 格式无效", name));
+        }
+    }
+    for favorite in &bundle.favorites {
+        if favorite_name(favorite).trim().is_empty() {
+            errors.push("快捷指令名称不能为空".to_string());
+        }
+    }
+
+    match config {
+        Ok(config) if errors.is_empty() => Ok(config),
+        _ => Err(format!("设置包校验失败：{}", errors.join("；"))),
+    }
+}
+
+/// 合并定时任务：同名的以包中为准
+fn import_schedules(state: &crate::AppState, bundle: &SettingsBundle) -> Result<(), String> {
+    let now = now_millis();
+    state.schedules.update(|schedules| {
+        for (index, rule) in bundle.schedules.iter().enumerate() {
+            schedules.retain(|s| s.name != rule.name);
+            let mut schedule = scheduler::new_schedule(
+                Some(rule.name.clone()),
+                rule.instruction.clone(),
+                rule.trigger.clone(),
+            )?;
+            // 同一毫秒内批量创建，需要区分 ID
+            schedule.id = format!("schedule_{}_{}", now, index);
+            if rule.paused {
+                schedule.paused = true;
+                schedule.next_run_at = None;
+            }
+            schedules.push(schedule);
+        }
+        Ok(())
+    })
+}
+
+/// 合并脱敏规则：同 ID 的以包中为准
+fn import_redaction_rules(bundle: &SettingsBundle) -> Result<(), String> {
+    let mut rules = redaction::load_rules()?;
+    for rule in &bundle.redaction_rules {
+        match rules.iter_mut().find(|r| r.id == rule.id) {
+            Some(existing) => *existing = rule.clone(),
+            None => rules.push(rule.clone()),
+        }
+    }
+    redaction::store_rules(&rules)
+}
+
+/// 合并工作流模板和快捷指令：同名的以包中为准
+fn import_prompts(bundle: &SettingsBundle) -> Result<(), String> {
+    if !bundle.workflows.is_empty() {
+        let mut workflows: serde_json::Map<String, serde_json::Value> = read_json("workflows.json")?;
+        workflows.extend(bundle.workflows.clone());
+        write_json("workflows.json", &workflows)?;
+    }
+    if !bundle.favorites.is_empty() {
+        let mut favorites: Vec<serde_json::Value> = read_json("favorites.json")?;
+        favorites.retain(|fav| !bundle.favorites.iter().any(|b| favorite_name(b) == favorite_name(fav)));
+        favorites.extend(bundle.favorites.iter().cloned());
+        write_json("favorites.json", &favorites)?;
+    }
+    Ok(())
+}
+
+/// 导出配置、定时任务、脱敏规则、工作流模板和快捷指令到指定文件（敏感字段已抹去）
+///
+/// 一次性定时任务只对本机有意义，不导出。
+#[tauri::command]
+pub async fn export_settings(
+    app: AppHandle,
+    state: tauri::State<'_, crate::AppState>,
+    path: String,
+) -> Result<SettingsBundle, String> {
+    let mut config = config::load_config()?;
+    let redacted = redact_secrets(&mut config);
+    let schedules = state
+        .schedules
+        .list()
+        .into_iter()
+        .filter(|s| !matches!(s.trigger, ScheduleTrigger::Once { .. }))
+        .map(|s| BundleSchedule {
+            name: s.name,
+            instruction: s.instruction,
+            trigger: s.trigger,
+            paused: s.paused,
+        })
+        .collect();
+
+    let bundle = SettingsBundle {
+        format_version: BUNDLE_FORMAT_VERSION,
+        exported_at: now_millis(),
+        app_version: app.package_info().version.to_string(),
+        config: serde_json::to_value(&config).map_err(|e| format!("序列化配置失败: {}", e))?,
+        redacted,
+        schedules,
+        redaction_rules: redaction::load_rules()?,
+        workflows: read_json("workflows.json")?,
+        favorites: read_json("favorites.json")?,
+    };
+
+    let path = PathBuf::from(path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| format!("序列化设置包失败: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("写入设置包失败: {}", e))?;
+    eprintln!(
+        "[Tauri] 📦 已导出设置包 {}（抹去 {} 个敏感字段）",
+        path.display(),
+        bundle.redacted.len()
+    );
+    Ok(bundle)
+}
+
+/// 从文件导入设置包：敏感字段沿用本机已有的值，报告中列出需要重新填写的字段
+#[tauri::command]
+pub async fn import_settings(
+    app: AppHandle,
+    state: tauri::State<'_, crate::AppState>,
+    path: String,
+) -> Result<SettingsImportReport, String> {
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("读取设置包失败: {}", e))?;
+    let bundle: SettingsBundle = serde_json::from_str(&content)
+        .map_err(|e| format!("解析设置包失败: {}", e))?;
+    let mut imported = validate_bundle(&bundle)?;
+
+    let local = config::load_config()?;
+    let (kept_secrets, missing_secrets) = restore_secrets(&mut imported, &local, &bundle.redacted);
+    imported.sandbox_path = local.sandbox_path.clone();
+    config::save_user_config(imported)?;
+    http_api::apply_config(&app);
+
+    import_redaction_rules(&bundle)?;
+    import_prompts(&bundle)?;
+    if !bundle.schedules.is_empty() {
+        import_schedules(&state, &bundle)?;
+        scheduler::notify_changed(&app);
+    }
+
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<crate::AppState>();
+        if let Err(e) = crate::reload_server_config(&state).await {
+            eprintln!("[Tauri] ⚠️ 通知 Python 服务重新加载配置失败: {}", e);
+        }
+        state.pool.shutdown_idle(true).await;
+    });
+
+    if !missing_secrets.is_empty() {
+        eprintln!("[Tauri] ⚠️ 导入的设置缺少以下密钥，需要重新填写: {}", missing_secrets.join(", "));
+    }
+    eprintln!("[Tauri] 📦 已导入设置包 {}", path);
+    Ok(SettingsImportReport {
+        exported_at: bundle.exported_at,
+        app_version: bundle.app_version,
+        schedules: bundle.schedules.len(),
+        redaction_rules: bundle.redaction_rules.len(),
+        workflows: bundle.workflows.len(),
+        favorites: bundle.favorites.len(),
+        kept_secrets,
+        missing_secrets,
+    })
+}
@@ -0,0 +1,121 @@
+//! 热备重启：需要重启 Python 服务时先在后台启动新进程并等待 ready，再替换主服务，
+//! 旧进程收尾后结束，任务不会遇到冷启动
+//!
+//! 手动重启和保存了影响启动环境变量的配置时调用 replace：新进程就绪后取得主服务锁
+//! （执行中的任务结束后）再交换，旧进程先发送 shutdown 等待应答，失败时直接结束。
+//! 配置 warm_standby 开启时另外保持一个已就绪的备用进程：主服务崩溃或退出后立即顶替
+//! （见 supervisor、ensure_server_alive），随后在后台补充新的备用进程。备用进程按启动时的
+//! 配置运行，保存配置后丢弃重建。同一时间只进行一次替换。
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager};
+
+use crate::error::DeskJarvisError;
+use crate::tray::{self, AgentStatus};
+use crate::{config, resource_monitor, supervisor, warmup, AppState, PythonServer};
+
+/// 已就绪的备用进程
+static SPARE: Mutex<Option<PythonServer>> = Mutex::new(None);
+
+/// 正在启动备用进程
+static FILLING: AtomicBool = AtomicBool::new(false);
+
+/// 丢弃备用进程时递增，启动期间配置变化的备用进程不再使用
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// 正在替换主服务
+static REPLACING: AtomicBool = AtomicBool::new(false);
+
+/// 是否保持备用进程（默认关闭，多占用一个进程的内存）
+fn enabled() -> bool {
+    config::load_config()
+        .ok()
+        .and_then(|c| c.warm_standby)
+        .unwrap_or(false)
+}
+
+/// 启动一个新进程并预热（不登记到资源监控，成为主服务时再登记）
+async fn start() -> Result<PythonServer, DeskJarvisError> {
+    let mut server = crate::spawn_python_server().await?;
+    warmup::send_on_start(&mut server).await;
+    Ok(server)
+}
+
+/// 取出仍在运行的备用进程，作为主服务登记到资源监控
+pub fn take_spare() -> Option<PythonServer> {
+    let mut spare = SPARE.lock().ok()?.take()?;
+    if !matches!(spare.child.try_wait(), Ok(None)) {
+        eprintln!("[Tauri] ⚠️ 备用 Python 服务已退出，丢弃");
+        return None;
+    }
+    resource_monitor::set_server_pid(spare.child.id());
+    Some(spare)
+}
+
+/// 开启 warm_standby 且没有备用进程时在后台启动一个
+pub fn refill() {
+    if !enabled() || supervisor::is_degraded() {
+        return;
+    }
+    if SPARE.lock().map(|s| s.is_some()).unwrap_or(true) || FILLING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let generation = GENERATION.load(Ordering::SeqCst);
+    tauri::async_runtime::spawn(async move {
+        match start().await {
+            Ok(server) if GENERATION.load(Ordering::SeqCst) != generation => {
+                crate::shutdown_server(server).await;
+            }
+            Ok(server) => {
+                eprintln!("[Tauri] 🧊 备用 Python 服务已就绪");
+                if let Ok(mut spare) = SPARE.lock() {
+                    *spare = Some(server);
+                }
+            }
+            Err(e) => eprintln!("[Tauri] ⚠️ 启动备用 Python 服务失败: {}", e),
+        }
+        FILLING.store(false, Ordering::SeqCst);
+    });
+}
+
+/// 结束备用进程（保存配置后重建、退出应用前调用）
+pub async fn discard_spare() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    let spare = SPARE.lock().ok().and_then(|mut s| s.take());
+    if let Some(server) = spare {
+        crate::shutdown_server(server).await;
+    }
+}
+
+/// 启动新进程替换主服务，旧进程在后台收尾后结束
+pub async fn replace(app: &AppHandle, reason: &str) -> Result<(), DeskJarvisError> {
+    if REPLACING.swap(true, Ordering::SeqCst) {
+        eprintln!("[Tauri] ⏭️ Python 服务正在替换中，忽略本次重启（{}）", reason);
+        return Ok(());
+    }
+    let result = swap_in(app, reason).await;
+    REPLACING.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn swap_in(app: &AppHandle, reason: &str) -> Result<(), DeskJarvisError> {
+    eprintln!("[Tauri] 🔄 {}，在后台启动新的 Python 服务...", reason);
+    let server = start().await?;
+    let state = app.state::<AppState>();
+    let old = {
+        let mut guard = state.server.lock().await;
+        resource_monitor::set_server_pid(server.child.id());
+        guard.replace(server)
+    };
+    supervisor::record_started();
+    if tray::current_status(app) == Some(AgentStatus::ServerDown) {
+        tray::set_status(app, AgentStatus::Idle);
+    }
+    eprintln!("[Tauri] ✅ 新的 Python 服务已接替");
+    if let Some(old) = old {
+        tauri::async_runtime::spawn(crate::shutdown_server(old));
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+//! 启动性能统计：以 tracing span 包裹各启动阶段并记录耗时，供 get_startup_report 查询
+//!
+//! 非关键子系统在主窗口首次加载完成后延迟初始化（见 run_deferred_once）。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use serde::Serialize;
+use tauri::AppHandle;
+
+/// 页面加载事件未触发时（如启动即隐藏窗口），延迟初始化的兜底等待时间
+const DEFERRED_FALLBACK_SECS: u64 = 3;
+
+/// 单个启动阶段
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupPhase {
+    name: String,
+    /// 阶段开始时距进程启动的毫秒数
+    offset_ms: u64,
+    duration_ms: u64,
+}
+
+/// 启动报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StartupReport {
+    phases: Vec<StartupPhase>,
+    /// 主窗口首次加载完成（距进程启动毫秒数）
+    window_ready_ms: Option<u64>,
+    /// Python 服务就绪
+    python_ready_ms: Option<u64>,
+    /// 延迟初始化完成
+    deferred_ready_ms: Option<u64>,
+}
+
+static PROCESS_START: OnceLock<Instant> = OnceLock::new();
+static REPORT: Mutex<Option<StartupReport>> = Mutex::new(None);
+static DEFERRED_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// 记录进程启动时间（main 入口第一时间调用）
+pub fn mark_process_start() {
+    PROCESS_START.get_or_init(Instant::now);
+}
+
+/// 进程启动时间
+pub fn process_start() -> Instant {
+    *PROCESS_START.get_or_init(Instant::now)
+}
+
+fn ms_since_start(at: Instant) -> u64 {
+    at.saturating_duration_since(process_start()).as_millis() as u64
+}
+
+fn with_report<F: FnOnce(&mut StartupReport)>(f: F) {
+    if let Ok(mut guard) = REPORT.lock() {
+        f(guard.get_or_insert_with(StartupReport::default));
+    }
+}
+
+/// 记录一个已结束的阶段（用于跨 await 的异步阶段）
+pub fn record(name: &str, started: Instant) {
+    let phase = StartupPhase {
+        name: name.to_string(),
+        offset_ms: ms_since_start(started),
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+    eprintln!("[Tauri] ⏱️ 启动阶段 {} 耗时 {}ms", phase.name, phase.duration_ms);
+    with_report(|r| r.phases.push(phase));
+}
+
+/// 在 tracing span 中执行同步阶段并记录耗时
+pub fn phase<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let span = tracing::info_span!("startup", phase = name);
+    let _entered = span.enter();
+    let started = Instant::now();
+    let output = f();
+    record(name, started);
+    output
+}
+
+/// 标记 Python 服务就绪
+pub fn mark_python_ready() {
+    let now = ms_since_start(Instant::now());
+    with_report(|r| {
+        r.python_ready_ms.get_or_insert(now);
+    });
+}
+
+/// 主窗口加载完成后执行一次延迟初始化
+pub fn run_deferred_once(app: &AppHandle, init: fn(&AppHandle)) {
+    if DEFERRED_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let _span = tracing::info_span!("startup.deferred").entered();
+    let started = Instant::now();
+    init(app);
+    record("deferred_init", started);
+    let now = ms_since_start(Instant::now());
+    with_report(|r| r.deferred_ready_ms = Some(now));
+}
+
+/// 主窗口首次加载完成：记录时间并触发延迟初始化
+pub fn on_window_ready(app: &AppHandle, init: fn(&AppHandle)) {
+    let now = ms_since_start(Instant::now());
+    with_report(|r| {
+        r.window_ready_ms.get_or_insert(now);
+    });
+    run_deferred_once(app, init);
+}
+
+/// 兜底：窗口未加载（如启动即隐藏）时，超时后仍执行延迟初始化
+pub fn schedule_deferred_fallback(app: AppHandle, init: fn(&AppHandle)) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(DEFERRED_FALLBACK_SECS)).await;
+        run_deferred_once(&app, init);
+    });
+}
+
+/// 获取启动性能报告
+#[tauri::command]
+pub async fn get_startup_report() -> Result<StartupReport, String> {
+    REPORT
+        .lock()
+        .map(|r| r.clone().unwrap_or_default())
+        .map_err(|_| "启动报告不可用".to_string())
+}
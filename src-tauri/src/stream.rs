@@ -0,0 +1,73 @@
+//! LLM 流式输出通道：合并 Python 端细碎的 stream 事件，批量以 task-stream 转发
+
+use serde::Serialize;
+use tokio::time::{Duration, Instant};
+
+use crate::event_sink::EventSink;
+use crate::redaction;
+
+/// 缓冲超过该字节数立即发送
+const FLUSH_BYTES: usize = 256;
+
+/// 缓冲最长停留时间
+const FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// task-stream 事件负载
+#[derive(Debug, Clone, Serialize)]
+struct StreamPayload<'a> {
+    request_id: &'a str,
+    delta: String,
+}
+
+/// 流式输出缓冲区
+pub struct StreamBuffer {
+    request_id: String,
+    pending: String,
+    first_pending_at: Option<Instant>,
+}
+
+impl StreamBuffer {
+    pub fn new(request_id: &str) -> Self {
+        StreamBuffer {
+            request_id: request_id.to_string(),
+            pending: String::new(),
+            first_pending_at: None,
+        }
+    }
+
+    /// 追加增量文本，达到大小或时间阈值时发送
+    pub fn push(&mut self, sink: &impl EventSink, delta: &str) {
+        if delta.is_empty() {
+            return;
+        }
+        self.pending.push_str(delta);
+        self.first_pending_at.get_or_insert_with(Instant::now);
+
+        if self.pending.len() >= FLUSH_BYTES
+            || self.first_pending_at.map(|t| t.elapsed() >= FLUSH_INTERVAL).unwrap_or(false)
+        {
+            self.flush(sink);
+        }
+    }
+
+    /// 缓冲区需要被发送的最晚时间，无待发送内容时为 None
+    pub fn flush_deadline(&self) -> Option<Instant> {
+        self.first_pending_at.map(|t| t + FLUSH_INTERVAL)
+    }
+
+    /// 立即发送缓冲中的内容
+    pub fn flush(&mut self, sink: &impl EventSink) {
+        self.first_pending_at = None;
+        if self.pending.is_empty() {
+            return;
+        }
+        let delta = redaction::redact(&std::mem::take(&mut self.pending));
+        sink.send(
+            "task-stream",
+            StreamPayload {
+                request_id: &self.request_id,
+                delta,
+            },
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Python 服务重启监督：指数退避与重启预算
+//!
+//! 服务崩溃或启动失败后按 1s、2s、4s…（最长 60s）退避重启；一分钟内重启超过
+//! MAX_RESTARTS_PER_MINUTE 次时进入降级状态，不再自动重启，任务改用单次进程模式执行，
+//! 直到用户调用 restart_python_server 手动恢复。状态变化以 server-state 事件通知前端。
+//! 有备用进程时崩溃后立即接替，手动重启也先启动新进程再替换（见 standby）。
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::DeskJarvisError;
+use crate::{metrics, standby};
+use crate::tray::{self, AgentStatus};
+
+/// 首次重启前的等待时间
+const BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// 退避等待的上限
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// 统计重启次数的窗口
+const BUDGET_WINDOW: Duration = Duration::from_secs(60);
+
+/// 窗口内允许的最多重启次数
+const MAX_RESTARTS_PER_MINUTE: usize = 3;
+
+static APP: OnceLock<AppHandle> = OnceLock::new();
+
+static STATE: Mutex<SupervisorState> = Mutex::new(SupervisorState {
+    restarts: VecDeque::new(),
+    consecutive_failures: 0,
+    degraded: false,
+});
+
+struct SupervisorState {
+    /// 最近的重启时间
+    restarts: VecDeque<Instant>,
+    /// 连续失败次数（启动失败或执行中崩溃），决定退避时长
+    consecutive_failures: u32,
+    degraded: bool,
+}
+
+/// 服务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ServerState {
+    Running,
+    Restarting,
+    Degraded,
+}
+
+/// server-state 事件负载
+#[derive(Debug, Clone, Serialize)]
+struct ServerStatePayload {
+    state: ServerState,
+    restarts_last_minute: usize,
+    /// 下次自动重启前的等待时间（毫秒）
+    retry_in_ms: Option<u64>,
+    reason: Option<String>,
+}
+
+/// 启动时登记 AppHandle，用于发送事件
+pub fn init(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+fn emit(state: ServerState, restarts: usize, retry_in: Option<Duration>, reason: Option<&str>) {
+    if let Some(app) = APP.get() {
+        let payload = ServerStatePayload {
+            state,
+            restarts_last_minute: restarts,
+            retry_in_ms: retry_in.map(|d| d.as_millis() as u64),
+            reason: reason.map(str::to_string),
+        };
+        let _ = app.emit("server-state", payload);
+    }
+}
+
+/// 是否处于降级状态（不再自动重启）
+pub fn is_degraded() -> bool {
+    STATE.lock().map(|s| s.degraded).unwrap_or(false)
+}
+
+/// 下次重启前的退避时间
+fn backoff(consecutive_failures: u32) -> Duration {
+    BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(consecutive_failures))
+        .min(MAX_DELAY)
+}
+
+/// 登记一次重启；降级或超出预算时返回错误（超出预算时进入降级状态）
+pub fn admit_restart() -> Result<(), DeskJarvisError> {
+    let mut state = STATE.lock().map_err(|_| "服务监督状态不可用")?;
+    if state.degraded {
+        return Err(DeskJarvisError::ServerDegraded(
+            "Python 服务处于降级状态，已停止自动重启".to_string(),
+        ));
+    }
+    let now = Instant::now();
+    while state
+        .restarts
+        .front()
+        .is_some_and(|t| now.duration_since(*t) > BUDGET_WINDOW)
+    {
+        state.restarts.pop_front();
+    }
+    if state.restarts.len() >= MAX_RESTARTS_PER_MINUTE {
+        state.degraded = true;
+        let reason = format!(
+            "一分钟内已重启 {} 次，停止自动重启",
+            state.restarts.len()
+        );
+        eprintln!("[Tauri] ❌ Python 服务{}，进入降级状态", reason);
+        emit(ServerState::Degraded, state.restarts.len(), None, Some(&reason));
+        if let Some(app) = APP.get() {
+            tray::set_status(app, AgentStatus::ServerDown);
+        }
+        return Err(DeskJarvisError::ServerDegraded(format!("Python 服务{}", reason)));
+    }
+    state.restarts.push_back(now);
+    Ok(())
+}
+
+/// 服务启动成功
+pub fn record_started() {
+    metrics::python_restarted();
+    if let Ok(state) = STATE.lock() {
+        emit(ServerState::Running, state.restarts.len(), None, None);
+    }
+}
+
+/// 服务成功完成了一个任务，清零连续失败次数
+pub fn record_healthy() {
+    if let Ok(mut state) = STATE.lock() {
+        state.consecutive_failures = 0;
+    }
+}
+
+/// 服务启动失败或执行中崩溃，返回下次重启前的退避时间
+pub fn record_failure(reason: &str) -> Duration {
+    let Ok(mut state) = STATE.lock() else {
+        return MAX_DELAY;
+    };
+    let delay = backoff(state.consecutive_failures);
+    state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+    if !state.degraded {
+        emit(ServerState::Restarting, state.restarts.len(), Some(delay), Some(reason));
+    }
+    delay
+}
+
+/// 后台按退避时间重启服务，启动失败时继续退避重试，直到成功或进入降级状态；
+/// 有就绪的备用进程时立即由其接替（见 standby）
+pub fn spawn_restart(app: AppHandle, delay: Duration) {
+    if is_degraded() {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let spare = standby::take_spare();
+        if spare.is_none() {
+            tokio::time::sleep(delay).await;
+        }
+        let state = app.state::<crate::AppState>();
+        let mut guard = state.server.lock().await;
+        if guard.is_some() || admit_restart().is_err() {
+            if let Some(spare) = spare {
+                drop(guard);
+                crate::shutdown_server(spare).await;
+            }
+            return;
+        }
+        let launched = match spare {
+            Some(spare) => {
+                eprintln!("[Tauri] 🧊 备用 Python 服务已接替");
+                Ok(spare)
+            }
+            None => {
+                eprintln!("[Tauri] 🔄 后台自动重启 Python 服务...");
+                crate::launch_python_server().await
+            }
+        };
+        match launched {
+            Ok(s) => {
+                *guard = Some(s);
+                record_started();
+                standby::refill();
+                eprintln!("[Tauri] ✅ Python 服务后台重启成功");
+            }
+            Err(e) => {
+                eprintln!("[Tauri] ❌ Python 服务后台重启失败: {}", e);
+                tray::set_status(&app, AgentStatus::ServerDown);
+                drop(guard);
+                let delay = record_failure(&e.message());
+                spawn_restart(app.clone(), delay);
+            }
+        }
+    });
+}
+
+/// 手动重启 Python 服务，同时清除降级状态和重启计数
+///
+/// 新进程就绪后才替换旧进程（见 standby），重启期间任务仍由旧进程执行。
+#[tauri::command]
+pub async fn restart_python_server(app: AppHandle) -> Result<(), DeskJarvisError> {
+    if let Ok(mut supervisor) = STATE.lock() {
+        supervisor.restarts.clear();
+        supervisor.consecutive_failures = 0;
+        supervisor.degraded = false;
+    }
+    standby::replace(&app, "手动重启 Python 服务")
+        .await
+        .inspect_err(|_| tray::set_status(&app, AgentStatus::ServerDown))?;
+    standby::refill();
+    Ok(())
+}
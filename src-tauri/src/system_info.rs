@@ -0,0 +1,300 @@
+//! 系统信息工具：tool_call 通道上的 system.info
+//!
+//! 返回系统版本、CPU / 内存 / 磁盘占用、电池和网络状态，Agent 回答"还剩多少磁盘空间"
+//! 这类问题时不必在 Python 里按平台调用 df、pmset 等命令。参数 sections 指定要返回的部分
+//! （os、cpu、memory、disk、battery、network），省略时全部返回。电池信息 Linux 读取
+//! /sys/class/power_supply，macOS 解析 pmset，Windows 调用 GetSystemPowerStatus；
+//! 没有电池时 battery 为 null。
+
+use std::net::IpAddr;
+
+use serde::Serialize;
+use sysinfo::{Disks, Networks, System};
+
+/// 支持的信息类型
+const SECTIONS: &[&str] = &["os", "cpu", "memory", "disk", "battery", "network"];
+
+#[derive(Debug, Clone, Serialize)]
+struct OsInfo {
+    name: Option<String>,
+    version: Option<String>,
+    long_version: Option<String>,
+    kernel_version: Option<String>,
+    host_name: Option<String>,
+    arch: String,
+    uptime_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct CpuInfo {
+    brand: String,
+    logical_cores: usize,
+    physical_cores: Option<usize>,
+    /// 全部核心的平均占用（百分比）
+    usage_percent: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct MemoryInfo {
+    total_bytes: u64,
+    used_bytes: u64,
+    available_bytes: u64,
+    swap_total_bytes: u64,
+    swap_used_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DiskInfo {
+    name: String,
+    mount_point: String,
+    file_system: String,
+    total_bytes: u64,
+    available_bytes: u64,
+    used_percent: f32,
+    removable: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct InterfaceInfo {
+    name: String,
+    mac_address: String,
+    addresses: Vec<String>,
+    received_bytes: u64,
+    transmitted_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct NetworkInfo {
+    /// 是否有非回环、非链路本地地址的网卡
+    connected: bool,
+    interfaces: Vec<InterfaceInfo>,
+}
+
+/// 电池状态，各字段在平台无法提供时为 None
+#[derive(Debug, Clone, Default, Serialize)]
+struct BatteryInfo {
+    percent: Option<u8>,
+    charging: Option<bool>,
+    on_ac_power: Option<bool>,
+    /// 预计剩余使用时间（分钟），仅放电时提供
+    time_remaining_mins: Option<u32>,
+}
+
+fn os_info() -> OsInfo {
+    OsInfo {
+        name: System::name(),
+        version: System::os_version(),
+        long_version: System::long_os_version(),
+        kernel_version: System::kernel_version(),
+        host_name: System::host_name(),
+        arch: System::cpu_arch(),
+        uptime_secs: System::uptime(),
+    }
+}
+
+/// CPU 占用需要间隔两次采样
+fn cpu_info() -> CpuInfo {
+    let mut system = System::new();
+    system.refresh_cpu_usage();
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    system.refresh_cpu_usage();
+    CpuInfo {
+        brand: system
+            .cpus()
+            .first()
+            .map(|c| c.brand().trim().to_string())
+            .unwrap_or_default(),
+        logical_cores: system.cpus().len(),
+        physical_cores: System::physical_core_count(),
+        usage_percent: system.global_cpu_usage(),
+    }
+}
+
+fn memory_info() -> MemoryInfo {
+    let mut system = System::new();
+    system.refresh_memory();
+    MemoryInfo {
+        total_bytes: system.total_memory(),
+        used_bytes: system.used_memory(),
+        available_bytes: system.available_memory(),
+        swap_total_bytes: system.total_swap(),
+        swap_used_bytes: system.used_swap(),
+    }
+}
+
+fn disk_info() -> Vec<DiskInfo> {
+    Disks::new_with_refreshed_list()
+        .list()
+        .iter()
+        .filter(|d| d.total_space() > 0)
+        .map(|d| DiskInfo {
+            name: d.name().to_string_lossy().to_string(),
+            mount_point: d.mount_point().to_string_lossy().to_string(),
+            file_system: d.file_system().to_string_lossy().to_string(),
+            total_bytes: d.total_space(),
+            available_bytes: d.available_space(),
+            used_percent: (d.total_space() - d.available_space().min(d.total_space())) as f32 * 100.0
+                / d.total_space() as f32,
+            removable: d.is_removable(),
+        })
+        .collect()
+}
+
+/// 可用于上网的地址（排除回环和链路本地地址）
+fn is_routable(addr: &IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(v4) => !v4.is_loopback() && !v4.is_link_local(),
+        IpAddr::V6(v6) => !v6.is_loopback() && (v6.segments()[0] & 0xffc0) != 0xfe80,
+    }
+}
+
+fn network_info() -> NetworkInfo {
+    let networks = Networks::new_with_refreshed_list();
+    let mut interfaces: Vec<InterfaceInfo> = networks
+        .list()
+        .iter()
+        .map(|(name, data)| InterfaceInfo {
+            name: name.clone(),
+            mac_address: data.mac_address().to_string(),
+            addresses: data.ip_networks().iter().map(|n| n.addr.to_string()).collect(),
+            received_bytes: data.total_received(),
+            transmitted_bytes: data.total_transmitted(),
+        })
+        .collect();
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    let connected = networks
+        .list()
+        .values()
+        .any(|data| data.ip_networks().iter().any(|n| is_routable(&n.addr)));
+    NetworkInfo { connected, interfaces }
+}
+
+#[cfg(target_os = "linux")]
+fn battery_info() -> Option<BatteryInfo> {
+    let read = |path: &std::path::Path, name: &str| {
+        std::fs::read_to_string(path.join(name))
+            .ok()
+            .map(|s| s.trim().to_string())
+    };
+    let mut battery = None;
+    let mut on_ac_power = None;
+    for entry in std::fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let path = entry.path();
+        match read(&path, "type").as_deref() {
+            // scope 为 Device 的是鼠标、键盘等外设的电池
+            Some("Battery") if battery.is_none() && read(&path, "scope").as_deref() != Some("Device") => {
+                let status = read(&path, "status").unwrap_or_default();
+                let discharging = status == "Discharging";
+                let number = |name: &str| read(&path, name).and_then(|v| v.parse::<u64>().ok());
+                let time_remaining_mins = match (number("energy_now"), number("power_now")) {
+                    (Some(energy), Some(power)) if discharging && power > 0 => Some((energy * 60 / power) as u32),
+                    _ => None,
+                };
+                battery = Some(BatteryInfo {
+                    percent: number("capacity").map(|c| c.min(100) as u8),
+                    charging: Some(status == "Charging"),
+                    on_ac_power: None,
+                    time_remaining_mins,
+                });
+            }
+            Some("Mains") => {
+                on_ac_power = Some(on_ac_power.unwrap_or(false) || read(&path, "online").as_deref() == Some("1"));
+            }
+            _ => {}
+        }
+    }
+    battery.map(|b| BatteryInfo { on_ac_power, ..b })
+}
+
+#[cfg(target_os = "macos")]
+fn battery_info() -> Option<BatteryInfo> {
+    // 输出形如：Now drawing from 'AC Power'
+    //  -InternalBattery-0 (id=1234)	85%; charging; 1:20 remaining present: true
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let re = regex::Regex::new(r"(\d+)%;\s*([^;]+);\s*(?:(\d+):(\d+) remaining)?").ok()?;
+    let caps = re.captures(&text)?;
+    let status = caps[2].trim().to_lowercase();
+    let time_remaining_mins = match (caps.get(3), caps.get(4)) {
+        (Some(h), Some(m)) if status == "discharging" => Some(
+            h.as_str().parse::<u32>().unwrap_or(0) * 60 + m.as_str().parse::<u32>().unwrap_or(0),
+        ),
+        _ => None,
+    };
+    Some(BatteryInfo {
+        percent: caps[1].parse::<u8>().ok(),
+        charging: Some(status == "charging" || status == "finishing charge"),
+        on_ac_power: Some(text.contains("'AC Power'")),
+        time_remaining_mins,
+    })
+}
+
+#[cfg(windows)]
+fn battery_info() -> Option<BatteryInfo> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    // 以下取值含义见 SYSTEM_POWER_STATUS 文档：255 / u32::MAX 表示未知
+    const NO_BATTERY: u8 = 128;
+    const CHARGING: u8 = 8;
+    const UNKNOWN: u8 = 255;
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+    if status.BatteryFlag == UNKNOWN || status.BatteryFlag & NO_BATTERY != 0 {
+        return None;
+    }
+    Some(BatteryInfo {
+        percent: (status.BatteryLifePercent != UNKNOWN).then_some(status.BatteryLifePercent),
+        charging: Some(status.BatteryFlag & CHARGING != 0),
+        on_ac_power: match status.ACLineStatus {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        },
+        time_remaining_mins: (status.BatteryLifeTime != u32::MAX).then(|| status.BatteryLifeTime / 60),
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn battery_info() -> Option<BatteryInfo> {
+    None
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<serde_json::Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// system.info 工具：按 sections 收集系统信息
+pub fn call(args: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let requested: Vec<String> = match args.get("sections").and_then(|s| s.as_array()) {
+        Some(list) if !list.is_empty() => list
+            .iter()
+            .filter_map(|s| s.as_str())
+            .map(|s| s.trim().to_lowercase())
+            .collect(),
+        _ => SECTIONS.iter().map(|s| s.to_string()).collect(),
+    };
+    if let Some(unknown) = requested.iter().find(|s| !SECTIONS.contains(&s.as_str())) {
+        return Err(format!("未知的系统信息类型: {}（支持 {}）", unknown, SECTIONS.join("、")));
+    }
+
+    let mut output = serde_json::Map::new();
+    for section in SECTIONS.iter().filter(|s| requested.iter().any(|r| r == *s)) {
+        let value = match *section {
+            "os" => to_value(&os_info())?,
+            "cpu" => to_value(&cpu_info())?,
+            "memory" => to_value(&memory_info())?,
+            "disk" => to_value(&disk_info())?,
+            "battery" => to_value(&battery_info())?,
+            _ => to_value(&network_info())?,
+        };
+        output.insert(section.to_string(), value);
+    }
+    Ok(serde_json::Value::Object(output))
+}
@@ -0,0 +1,188 @@
+//! 暂停与恢复执行中的任务，便于在高成本步骤前停下、稍后继续
+//!
+//! 执行任务期间 Python 服务不读取 stdin，控制消息与文件访问审批一样写入
+//! ~/.deskjarvis/task_control/<request_id>.json（{"cmd":"pause"|"resume"|"stop","id":...}）。
+//! Python 在步骤之间检查该文件，暂停后发出 paused 事件并等待，恢复后发出 resumed 事件，
+//! Tauri 转发为 task-paused / task-resumed；暂停期间不做卡死检测。
+//!
+//! 逐步执行（execute_task_interactive）时 Python 每完成一个步骤发出 step_gate 事件并等待，
+//! Tauri 转发为 step-completed-awaiting-next；continue_task 写入 resume 继续下一步，
+//! abort_task 写入 stop 中止任务（已完成的步骤作为部分结果返回）。
+
+use std::collections::{BTreeSet, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::config;
+use crate::error::DeskJarvisError;
+
+/// 已请求暂停、尚未恢复的任务
+#[derive(Default)]
+pub struct PausedTasks {
+    ids: Mutex<HashSet<String>>,
+}
+
+/// 逐步执行中等待继续的任务
+static AWAITING_NEXT: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+impl PausedTasks {
+    pub fn is_paused(&self, request_id: &str) -> bool {
+        self.ids
+            .lock()
+            .map(|ids| ids.contains(request_id))
+            .unwrap_or(false)
+    }
+
+    fn set(&self, request_id: &str, paused: bool) {
+        if let Ok(mut ids) = self.ids.lock() {
+            if paused {
+                ids.insert(request_id.to_string());
+            } else {
+                ids.remove(request_id);
+            }
+        }
+    }
+
+    /// 任务结束：清除暂停与等待继续的状态和控制文件
+    pub fn finish(&self, request_id: &str) {
+        self.set(request_id, false);
+        set_awaiting_next(request_id, false);
+        if let Ok(path) = control_path(request_id) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// 记录任务是否在等待继续下一步（收到 step_gate 事件时置位）
+pub fn set_awaiting_next(request_id: &str, awaiting: bool) {
+    if let Ok(mut ids) = AWAITING_NEXT.lock() {
+        if awaiting {
+            ids.insert(request_id.to_string());
+        } else {
+            ids.remove(request_id);
+        }
+    }
+}
+
+fn is_awaiting_next(request_id: &str) -> bool {
+    AWAITING_NEXT
+        .lock()
+        .map(|ids| ids.contains(request_id))
+        .unwrap_or(false)
+}
+
+/// 控制文件路径，request_id 只允许字母、数字、下划线和连字符
+fn control_path(request_id: &str) -> Result<PathBuf, String> {
+    let valid = !request_id.is_empty()
+        && request_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(format!("无效的任务 ID: {}", request_id));
+    }
+    Ok(config::get_data_dir()?
+        .join("task_control")
+        .join(format!("{}.json", request_id)))
+}
+
+/// 写入控制命令，Python 在下一个步骤前读取
+pub fn send_command(request_id: &str, cmd: &str) -> Result<(), String> {
+    let path = control_path(request_id)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::json!({ "cmd": cmd, "id": request_id }).to_string();
+    // 先写临时文件再改名，避免 Python 读到半个文件
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, content).map_err(|e| format!("写入任务控制命令失败: {}", e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("写入任务控制命令失败: {}", e))
+}
+
+/// 是否已写入停止命令（Python 尚未处理时控制文件仍在）
+pub fn stop_requested(request_id: &str) -> bool {
+    control_path(request_id)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .is_some_and(|value| value.get("cmd").and_then(|c| c.as_str()) == Some("stop"))
+}
+
+/// 任务是否仍在执行
+fn ensure_running(state: &crate::AppState, request_id: &str) -> Result<(), DeskJarvisError> {
+    match state.history.get(request_id) {
+        Some(record) if record.finished_at.is_none() => Ok(()),
+        Some(_) => Err(DeskJarvisError::TaskFinished(request_id.to_string())),
+        None => Err(DeskJarvisError::TaskNotFound(request_id.to_string())),
+    }
+}
+
+/// 任务当前状态不允许该操作
+fn state_conflict(request_id: &str, message: String) -> DeskJarvisError {
+    DeskJarvisError::TaskStateConflict {
+        task_id: request_id.to_string(),
+        message,
+    }
+}
+
+/// 暂停任务：当前步骤完成后停下
+#[tauri::command]
+pub async fn pause_task(
+    state: tauri::State<'_, crate::AppState>,
+    request_id: String,
+) -> Result<(), DeskJarvisError> {
+    ensure_running(&state, &request_id)?;
+    if state.paused.is_paused(&request_id) {
+        return Err(state_conflict(&request_id, format!("任务 {} 已暂停", request_id)));
+    }
+    send_command(&request_id, "pause")?;
+    state.paused.set(&request_id, true);
+    eprintln!("[Tauri] ⏸ 请求暂停任务: {}", request_id);
+    Ok(())
+}
+
+/// 恢复已暂停的任务
+#[tauri::command]
+pub async fn resume_task(
+    state: tauri::State<'_, crate::AppState>,
+    request_id: String,
+) -> Result<(), DeskJarvisError> {
+    if !state.paused.is_paused(&request_id) {
+        return Err(state_conflict(&request_id, format!("任务 {} 未暂停", request_id)));
+    }
+    send_command(&request_id, "resume")?;
+    state.paused.set(&request_id, false);
+    eprintln!("[Tauri] ▶️ 恢复任务: {}", request_id);
+    Ok(())
+}
+
+/// 逐步执行：继续下一步
+#[tauri::command]
+pub async fn continue_task(
+    state: tauri::State<'_, crate::AppState>,
+    request_id: String,
+) -> Result<(), DeskJarvisError> {
+    ensure_running(&state, &request_id)?;
+    if !is_awaiting_next(&request_id) {
+        return Err(state_conflict(&request_id, format!("任务 {} 未在等待继续", request_id)));
+    }
+    send_command(&request_id, "resume")?;
+    set_awaiting_next(&request_id, false);
+    eprintln!("[Tauri] ⏭ 继续下一步: {}", request_id);
+    Ok(())
+}
+
+/// 逐步执行：中止任务，已完成的步骤作为部分结果返回
+#[tauri::command]
+pub async fn abort_task(
+    state: tauri::State<'_, crate::AppState>,
+    request_id: String,
+) -> Result<(), DeskJarvisError> {
+    ensure_running(&state, &request_id)?;
+    if !is_awaiting_next(&request_id) {
+        return Err(state_conflict(&request_id, format!("任务 {} 未在等待继续", request_id)));
+    }
+    send_command(&request_id, "stop")?;
+    set_awaiting_next(&request_id, false);
+    eprintln!("[Tauri] 🛑 中止逐步执行的任务: {}", request_id);
+    Ok(())
+}
@@ -0,0 +1,273 @@
+//! 匿名使用统计（默认关闭）：开启后记录任务结果、耗时区间、Python 服务崩溃和应用启动次数，
+//! 定期批量发送
+//!
+//! 事件只含类别、日期（UTC，不含时刻）、耗时区间和结果，不含指令、文件名、消息或任何配置；
+//! 批次附带随机生成的匿名 ID（与更新用的安装 ID 无关，关闭时丢弃）、应用版本、操作系统和架构。
+//! get_telemetry_preview 返回下次将发送的完整内容。待发送的事件保存在
+//! ~/.deskjarvis/telemetry.json，最多保留 MAX_PENDING 条。上报地址在构建时由
+//! DESKJARVIS_TELEMETRY_ENDPOINT 指定，未指定的构建不发送任何数据，事件只在本机预览。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use crate::automation_pack::{read_json, write_json};
+use crate::history::now_millis;
+use crate::mail_conn;
+
+/// 状态文件名（数据目录下）
+const TELEMETRY_FILE: &str = "telemetry.json";
+
+/// 上报地址（构建时指定）
+const ENDPOINT: Option<&str> = option_env!("DESKJARVIS_TELEMETRY_ENDPOINT");
+
+/// 待发送事件的上限，超出时丢弃最早的
+const MAX_PENDING: usize = 1000;
+
+/// 发送间隔
+const SEND_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// 发送请求的超时
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 是否开启（启动时从状态文件读取）
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// 读写状态文件的锁
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 应用版本（init 时记录）
+static APP_VERSION: OnceLock<String> = OnceLock::new();
+
+/// 任务结果
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskOutcome {
+    Succeeded,
+    Failed,
+    /// 用户停止、超出预算等提前结束
+    Terminated,
+}
+
+/// 一条匿名事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TelemetryEvent {
+    AppStarted {
+        day: String,
+    },
+    TaskFinished {
+        day: String,
+        outcome: TaskOutcome,
+        /// 耗时区间，如 "10s-1m"
+        duration: String,
+    },
+    ServerCrash {
+        day: String,
+    },
+}
+
+/// 持久化的状态
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TelemetryState {
+    #[serde(default)]
+    enabled: bool,
+    /// 匿名 ID，开启时生成，关闭时丢弃
+    #[serde(default)]
+    client_id: Option<String>,
+    #[serde(default)]
+    pending: Vec<TelemetryEvent>,
+    #[serde(default)]
+    last_sent_at: Option<u64>,
+}
+
+/// 一次发送的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryBatch {
+    pub client_id: String,
+    pub app_version: String,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub events: Vec<TelemetryEvent>,
+}
+
+/// get_telemetry_preview 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryPreview {
+    pub enabled: bool,
+    /// 上报地址，此构建不上报时为 None
+    pub endpoint: Option<&'static str>,
+    pub last_sent_at: Option<u64>,
+    /// 下次将发送的内容，未开启时为 None
+    pub batch: Option<TelemetryBatch>,
+}
+
+fn today() -> String {
+    chrono::Utc::now().format("%Y-%m-%d").to_string()
+}
+
+/// 耗时区间
+fn duration_bucket(millis: u64) -> &'static str {
+    match millis / 1000 {
+        0..10 => "<10s",
+        10..60 => "10s-1m",
+        60..300 => "1m-5m",
+        300..1800 => "5m-30m",
+        _ => ">30m",
+    }
+}
+
+fn new_client_id() -> String {
+    format!("{:x}", Sha256::digest(format!("telemetry-{}-{}", now_millis(), std::process::id())))
+}
+
+fn load() -> TelemetryState {
+    read_json(TELEMETRY_FILE).unwrap_or_else(|e| {
+        eprintln!("[Tauri] ⚠️ {}", e);
+        TelemetryState::default()
+    })
+}
+
+fn batch(state: &TelemetryState) -> Option<TelemetryBatch> {
+    Some(TelemetryBatch {
+        client_id: state.client_id.clone()?,
+        app_version: APP_VERSION.get().cloned().unwrap_or_default(),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        events: state.pending.clone(),
+    })
+}
+
+/// 开启时记录一条事件
+fn record(event: TelemetryEvent) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let Ok(_guard) = STATE_LOCK.lock() else {
+        return;
+    };
+    let mut state = load();
+    if !state.enabled {
+        return;
+    }
+    state.pending.push(event);
+    if state.pending.len() > MAX_PENDING {
+        let excess = state.pending.len() - MAX_PENDING;
+        state.pending.drain(..excess);
+    }
+    if let Err(e) = write_json(TELEMETRY_FILE, &state) {
+        eprintln!("[Tauri] ⚠️ {}", e);
+    }
+}
+
+/// 任务结束（finish_task 调用），started_at 为开始时间（毫秒时间戳）
+pub fn task_finished(outcome: TaskOutcome, started_at: Option<u64>) {
+    let duration = started_at.map_or("unknown", |t| duration_bucket(now_millis().saturating_sub(t)));
+    record(TelemetryEvent::TaskFinished {
+        day: today(),
+        outcome,
+        duration: duration.to_string(),
+    });
+}
+
+/// Python 服务在任务中崩溃
+pub fn server_crashed() {
+    record(TelemetryEvent::ServerCrash { day: today() });
+}
+
+/// 发送待发送的事件，成功后从状态中移除已发送的部分
+async fn send_pending() -> Result<(), String> {
+    let Some(endpoint) = ENDPOINT else {
+        return Ok(());
+    };
+    let batch = {
+        let _guard = STATE_LOCK.lock().map_err(|_| "统计状态不可用")?;
+        let state = load();
+        match batch(&state).filter(|b| state.enabled && !b.events.is_empty()) {
+            Some(batch) => batch,
+            None => return Ok(()),
+        }
+    };
+
+    mail_conn::ensure_crypto_provider();
+    let client = reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let response = client
+        .post(endpoint)
+        .json(&batch)
+        .send()
+        .await
+        .map_err(|e| format!("发送使用统计失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("发送使用统计失败: HTTP {}", response.status()));
+    }
+
+    let _guard = STATE_LOCK.lock().map_err(|_| "统计状态不可用")?;
+    let mut state = load();
+    // 发送期间可能记录了新事件，只移除已发送的部分
+    let sent = batch.events.len().min(state.pending.len());
+    state.pending.drain(..sent);
+    state.last_sent_at = Some(now_millis());
+    write_json(TELEMETRY_FILE, &state)?;
+    eprintln!("[Tauri] 📊 已发送 {} 条匿名使用统计", sent);
+    Ok(())
+}
+
+/// 启动时读取开关、记录应用启动，并开始定期发送
+pub fn init(app: &AppHandle) {
+    let _ = APP_VERSION.set(app.package_info().version.to_string());
+    ENABLED.store(load().enabled, Ordering::Relaxed);
+    record(TelemetryEvent::AppStarted { day: today() });
+    if ENDPOINT.is_none() {
+        return;
+    }
+    tauri::async_runtime::spawn(async {
+        let mut interval = tokio::time::interval(SEND_INTERVAL);
+        loop {
+            interval.tick().await;
+            if !ENABLED.load(Ordering::Relaxed) {
+                continue;
+            }
+            if let Err(e) = send_pending().await {
+                eprintln!("[Tauri] ⚠️ {}", e);
+            }
+        }
+    });
+}
+
+/// 查看下次将发送的内容
+#[tauri::command]
+pub async fn get_telemetry_preview() -> Result<TelemetryPreview, String> {
+    let _guard = STATE_LOCK.lock().map_err(|_| "统计状态不可用")?;
+    let state: TelemetryState = read_json(TELEMETRY_FILE)?;
+    Ok(TelemetryPreview {
+        enabled: state.enabled,
+        endpoint: ENDPOINT,
+        last_sent_at: state.last_sent_at,
+        batch: batch(&state).filter(|_| state.enabled),
+    })
+}
+
+/// 开启或关闭匿名使用统计；关闭时丢弃未发送的事件和匿名 ID
+#[tauri::command]
+pub async fn set_telemetry_enabled(enabled: bool) -> Result<(), String> {
+    let _guard = STATE_LOCK.lock().map_err(|_| "统计状态不可用")?;
+    let mut state: TelemetryState = read_json(TELEMETRY_FILE)?;
+    if enabled {
+        state.client_id.get_or_insert_with(new_client_id);
+    } else {
+        state.client_id = None;
+        state.pending.clear();
+    }
+    state.enabled = enabled;
+    write_json(TELEMETRY_FILE, &state)?;
+    ENABLED.store(enabled, Ordering::Relaxed);
+    eprintln!("[Tauri] 📊 匿名使用统计已{}", if enabled { "开启" } else { "关闭" });
+    Ok(())
+}
@@ -0,0 +1,261 @@
+//! 指令模板：保存带占位符的常用指令，填入参数后执行，持久化到 ~/.deskjarvis/templates.json
+//!
+//! 占位符写作 `{{名称}}`（如 "把 {{file}} 发给 {{recipient}}"），名称可含字母、数字、下划线、
+//! 连字符和中文。执行时由 params 提供取值，未提供的使用模板的 defaults，仍缺少的报错。
+//! 固定（pinned）的模板出现在托盘的 "指令模板" 菜单中，缺少参数时改为打开主窗口填写。
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Window};
+
+use crate::automation_pack::{read_json, write_json};
+use crate::error::DeskJarvisError;
+use crate::history::{new_id, now_millis};
+use crate::{tray, window_manager, TaskResult};
+
+/// 模板文件名（数据目录下）
+const TEMPLATES_FILE: &str = "templates.json";
+
+/// 指令模板
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstructionTemplate {
+    pub id: String,
+    pub name: String,
+    /// 带 {{占位符}} 的指令
+    pub instruction: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 占位符的默认值
+    #[serde(default)]
+    pub defaults: BTreeMap<String, String>,
+    /// 是否固定到托盘菜单
+    #[serde(default)]
+    pub pinned: bool,
+    pub created_at: u64,
+    pub updated_at: u64,
+    /// 指令中的占位符（按出现顺序，保存时计算）
+    #[serde(default)]
+    pub placeholders: Vec<String>,
+}
+
+/// save_template 的参数：没有 id 时新建，有 id 时更新
+#[derive(Debug, Clone, Deserialize)]
+pub struct TemplateInput {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    pub instruction: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub defaults: BTreeMap<String, String>,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+fn placeholder_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\{\{\s*([\w\-]+)\s*\}\}").expect("占位符正则无效"))
+}
+
+/// 指令中的占位符名称（去重，按出现顺序）
+pub fn placeholders(instruction: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for caps in placeholder_regex().captures_iter(instruction) {
+        let name = caps[1].to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// 用参数（其次是默认值）替换占位符，有缺少的参数时返回错误
+pub fn render(template: &InstructionTemplate, params: &BTreeMap<String, String>) -> Result<String, String> {
+    let value = |name: &str| {
+        params
+            .get(name)
+            .or_else(|| template.defaults.get(name))
+            .filter(|v| !v.trim().is_empty())
+    };
+    let missing: Vec<&str> = template
+        .placeholders
+        .iter()
+        .map(String::as_str)
+        .filter(|name| value(name).is_none())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("模板 {} 缺少参数: {}", template.name, missing.join("、")));
+    }
+    let rendered = placeholder_regex().replace_all(&template.instruction, |caps: &regex::Captures| {
+        value(&caps[1]).cloned().unwrap_or_default()
+    });
+    Ok(rendered.trim().to_string())
+}
+
+fn load() -> Result<Vec<InstructionTemplate>, String> {
+    read_json(TEMPLATES_FILE)
+}
+
+fn find(id: &str) -> Result<InstructionTemplate, String> {
+    load()?
+        .into_iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| format!("未找到模板: {}", id))
+}
+
+/// 刷新托盘中的固定模板并通知前端
+fn notify_changed(app: &AppHandle, templates: &[InstructionTemplate]) {
+    refresh_tray(app, templates);
+    let _ = app.emit("templates-changed", templates);
+}
+
+fn refresh_tray(app: &AppHandle, templates: &[InstructionTemplate]) {
+    let pinned: Vec<(String, String)> = templates
+        .iter()
+        .filter(|t| t.pinned)
+        .map(|t| (t.id.clone(), t.name.clone()))
+        .collect();
+    tray::set_pinned_templates(app, &pinned);
+}
+
+/// 启动时把固定的模板加入托盘菜单
+pub fn init_tray(app: &AppHandle) {
+    match load() {
+        Ok(templates) => refresh_tray(app, &templates),
+        Err(e) => eprintln!("[Tauri] ⚠️ {}", e),
+    }
+}
+
+/// 从托盘执行固定的模板：参数齐全时直接执行，否则打开主窗口填写参数
+pub fn run_from_tray(app: &AppHandle, id: &str) {
+    let template = match find(id) {
+        Ok(template) => template,
+        Err(e) => {
+            eprintln!("[Tauri] ⚠️ {}", e);
+            return;
+        }
+    };
+    let instruction = match render(&template, &BTreeMap::new()) {
+        Ok(instruction) => instruction,
+        Err(_) => {
+            let _ = window_manager::focus_main_window(app);
+            let _ = app.emit("template-params-required", &template);
+            return;
+        }
+    };
+    let Some(window) = window_manager::main_window(app) else {
+        eprintln!("[Tauri] ⚠️ 主窗口不存在，无法执行模板 {}", template.name);
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        eprintln!("[Tauri] 📋 从托盘执行模板: {}", template.name);
+        let context = serde_json::json!({ "template_id": template.id });
+        let state = app.state::<crate::AppState>();
+        if let Err(e) = crate::execute_task(window, state, instruction, Some(context), None, None, None).await {
+            eprintln!("[Tauri] ⚠️ 模板 {} 执行失败: {}", template.name, String::from(e));
+        }
+    });
+}
+
+/// 列出所有模板
+#[tauri::command]
+pub async fn list_templates() -> Result<Vec<InstructionTemplate>, String> {
+    load()
+}
+
+/// 新建或更新模板（名称不能与其他模板重复）
+#[tauri::command]
+pub async fn save_template(app: AppHandle, template: TemplateInput) -> Result<InstructionTemplate, String> {
+    let name = template.name.trim().to_string();
+    let instruction = template.instruction.trim().to_string();
+    if name.is_empty() {
+        return Err("模板名称不能为空".to_string());
+    }
+    if instruction.is_empty() {
+        return Err("模板指令不能为空".to_string());
+    }
+    let mut templates = load()?;
+    if templates
+        .iter()
+        .any(|t| t.name == name && Some(&t.id) != template.id.as_ref())
+    {
+        return Err(format!("已存在同名模板: {}", name));
+    }
+
+    let now = now_millis();
+    let placeholders = placeholders(&instruction);
+    // 只保留指令中仍在使用的占位符的默认值
+    let defaults = template
+        .defaults
+        .into_iter()
+        .filter(|(k, _)| placeholders.contains(k))
+        .collect();
+    let saved = match template.id {
+        Some(id) => {
+            let existing = templates
+                .iter_mut()
+                .find(|t| t.id == id)
+                .ok_or_else(|| format!("未找到模板: {}", id))?;
+            existing.name = name;
+            existing.instruction = instruction;
+            existing.description = template.description;
+            existing.defaults = defaults;
+            existing.pinned = template.pinned;
+            existing.placeholders = placeholders;
+            existing.updated_at = now;
+            existing.clone()
+        }
+        None => {
+            let saved = InstructionTemplate {
+                id: new_id("template"),
+                name,
+                instruction,
+                description: template.description,
+                defaults,
+                pinned: template.pinned,
+                created_at: now,
+                updated_at: now,
+                placeholders,
+            };
+            templates.push(saved.clone());
+            saved
+        }
+    };
+    write_json(TEMPLATES_FILE, &templates)?;
+    notify_changed(&app, &templates);
+    Ok(saved)
+}
+
+/// 删除模板
+#[tauri::command]
+pub async fn delete_template(app: AppHandle, id: String) -> Result<(), String> {
+    let mut templates = load()?;
+    let before = templates.len();
+    templates.retain(|t| t.id != id);
+    if templates.len() == before {
+        return Err(format!("未找到模板: {}", id));
+    }
+    write_json(TEMPLATES_FILE, &templates)?;
+    notify_changed(&app, &templates);
+    Ok(())
+}
+
+/// 填入参数并执行模板（与 execute_task 相同的预算检查和历史记录）
+#[tauri::command]
+pub async fn run_template(
+    window: Window,
+    state: tauri::State<'_, crate::AppState>,
+    id: String,
+    params: Option<BTreeMap<String, String>>,
+    group_id: Option<String>,
+) -> Result<TaskResult, DeskJarvisError> {
+    let template = find(&id)?;
+    let instruction = render(&template, &params.unwrap_or_default())?;
+    let context = serde_json::json!({ "template_id": template.id });
+    crate::execute_task(window, state, instruction, Some(context), group_id, None, None).await
+}
@@ -0,0 +1,1218 @@
+//! 内置工具：Agent 以 tool_call 事件请 Rust 执行命令和常用文件操作，所有命令执行都经过这一处
+//!
+//! 文件操作 fs.read / fs.write / fs.list / fs.move / fs.delete 见 fs_tools，系统信息 system.info 见 system_info，
+//! 读取邮件 email.fetch 见 imap_client，日程 calendar.list / calendar.create 见 calendar。
+//! Python 不再自行启动 shell，而是发出 {"type":"tool_call","call_id":...,"name":"shell",
+//! "args":{"cmd":...}}，这里检查程序白名单后执行，限制运行时间和输出大小，写入审计日志，
+//! 再把 tool_result 写入 ~/.deskjarvis/tool_results/<call_id>.json 供 Python 轮询读取
+//! （执行任务期间 Python 不读取 stdin）。观察模式下拒绝执行；任务被停止时结束命令。
+//!
+//! 白名单之外还拒绝能间接执行程序或写文件的用法：命令替换、输出重定向、find -exec、
+//! awk 的 system() 和管道、sed 的 e / w 命令等；cp、mv、mkdir、touch、sed -i、sort -o、uniq、
+//! tar、unzip、zip、gzip 和 git clone 写入的路径与 fs.write 一样经过文件访问边界检查（见 fs_tools::checked）。
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::agent_event::ToolCallEvent;
+use crate::{audit, calendar, config, fs_tools, imap_client, mcp, observer, system_info, task_control};
+
+/// 默认超时
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// 超时上限（短于卡死检测的默认 300 秒）
+const MAX_TIMEOUT_SECS: u64 = 240;
+
+/// stdout、stderr 各自保留的最大字节数，超出部分丢弃
+const MAX_OUTPUT_BYTES: usize = 256 * 1024;
+
+/// 未配置 shell_allowlist 时允许的程序（不含解释器、包管理器和下载工具，它们能执行任意代码；
+/// 也不含 git（配置、别名和钩子都能执行命令）和 open / xdg-open（能启动任意应用）
+const DEFAULT_ALLOWLIST: &[&str] = &[
+    "cd", "ls", "cat", "head", "tail", "wc", "grep", "find", "echo", "pwd", "date", "whoami", "uname",
+    "df", "du", "sort", "uniq", "cut", "tr", "sed", "awk", "diff", "file", "stat", "which",
+    "basename", "dirname", "mkdir", "touch", "cp", "mv", "tar", "zip", "unzip", "gzip",
+    "ping", "ps", "mdfind", "dir", "type", "where",
+];
+
+/// 能借选项执行其他程序或绕过文件访问审批写入、删除文件的程序及这些选项
+const UNSAFE_OPTIONS: &[(&str, &[&str])] = &[
+    (
+        "find",
+        &["-exec", "-execdir", "-ok", "-okdir", "-delete", "-fprint", "-fprint0", "-fprintf", "-fls"],
+    ),
+    (
+        "tar",
+        &[
+            "--to-command", "--checkpoint-action", "--use-compress-program", "-I", "--rsh-command",
+            "--info-script", "--new-volume-script", "-F",
+        ],
+    ),
+    ("zip", &["-TT", "--unzip-command"]),
+    ("sort", &["--compress-program"]),
+    // git 需要用户自行加入 shell_allowlist，仍拒绝直接指定要执行的程序的选项
+    (
+        "git",
+        &["-c", "--config-env", "--exec-path", "--upload-pack", "--receive-pack", "--exec"],
+    ),
+];
+
+/// 命令的执行结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ShellOutput {
+    /// 被超时或停止结束时为 None
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    /// 输出超出上限被截断
+    pub truncated: bool,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+}
+
+/// 命令按未加引号的 |、&、;、括号和换行拆出的一段
+#[derive(Debug, Default)]
+struct Segment {
+    /// 去掉引号后的词
+    words: Vec<String>,
+    /// 重定向（运算符, 目标），如 (">", "out.txt")、(">&", "1")
+    redirects: Vec<(String, String)>,
+}
+
+#[derive(Default)]
+struct Lexer {
+    segments: Vec<Segment>,
+    current: Segment,
+    word: String,
+    in_word: bool,
+    quoted: bool,
+    /// 等待目标的重定向运算符
+    redirect: Option<String>,
+}
+
+impl Lexer {
+    fn end_word(&mut self) {
+        if !self.in_word {
+            return;
+        }
+        let word = std::mem::take(&mut self.word);
+        match self.redirect.take() {
+            Some(op) => self.current.redirects.push((op, word)),
+            None => self.current.words.push(word),
+        }
+        self.in_word = false;
+        self.quoted = false;
+    }
+
+    fn end_segment(&mut self) {
+        self.end_word();
+        if let Some(op) = self.redirect.take() {
+            self.current.redirects.push((op, String::new()));
+        }
+        let segment = std::mem::take(&mut self.current);
+        if !segment.words.is_empty() || !segment.redirects.is_empty() {
+            self.segments.push(segment);
+        }
+    }
+}
+
+/// 按 sh 的引号规则拆分命令（不展开变量和通配符）
+fn segments(cmd: &str) -> Vec<Segment> {
+    let mut lexer = Lexer::default();
+    let mut chars = cmd.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                lexer.in_word = true;
+                lexer.quoted = true;
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                    lexer.word.push(c);
+                }
+            }
+            '"' => {
+                lexer.in_word = true;
+                lexer.quoted = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' if matches!(chars.peek(), Some('"' | '\\' | '$' | '`')) => {
+                            lexer.word.extend(chars.next());
+                        }
+                        c => lexer.word.push(c),
+                    }
+                }
+            }
+            // 反斜杠加换行是续行
+            '\\' => match chars.next() {
+                Some('\n') | None => {}
+                Some(next) => {
+                    lexer.in_word = true;
+                    lexer.quoted = true;
+                    lexer.word.push(next);
+                }
+            },
+            '>' | '<' => {
+                // 紧挨着的数字是文件描述符，如 2>
+                let is_fd = lexer.in_word
+                    && !lexer.quoted
+                    && !lexer.word.is_empty()
+                    && lexer.word.chars().all(|c| c.is_ascii_digit());
+                if is_fd {
+                    lexer.word.clear();
+                    lexer.in_word = false;
+                } else {
+                    lexer.end_word();
+                }
+                if let Some(op) = lexer.redirect.take() {
+                    lexer.current.redirects.push((op, String::new()));
+                }
+                let mut op = c.to_string();
+                if let Some(&next) = chars.peek() {
+                    if next == c || next == '&' || next == '|' || (c == '<' && next == '>') {
+                        op.push(next);
+                        chars.next();
+                    }
+                }
+                lexer.redirect = Some(op);
+            }
+            '|' | '&' | ';' | '\n' | '(' | ')' => lexer.end_segment(),
+            c if c.is_whitespace() => lexer.end_word(),
+            c => {
+                lexer.in_word = true;
+                lexer.word.push(c);
+            }
+        }
+    }
+    lexer.end_segment();
+    lexer.segments
+}
+
+/// 段中的程序及其参数（跳过开头的环境变量赋值）
+fn command(segment: &Segment) -> Option<(&str, &[String])> {
+    let start = segment
+        .words
+        .iter()
+        .position(|word| !word.contains('=') || word.starts_with('='))?;
+    Some((segment.words[start].as_str(), &segment.words[start + 1..]))
+}
+
+/// 命令中出现的程序
+fn programs(cmd: &str) -> Vec<String> {
+    segments(cmd)
+        .iter()
+        .filter_map(command)
+        .map(|(program, _)| program.to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// 允许的重定向：读取输入、复制文件描述符（2>&1）和丢弃输出（>/dev/null）
+fn is_allowed_redirect(op: &str, target: &str) -> bool {
+    if op.starts_with('<') && op != "<>" {
+        return true;
+    }
+    if op.ends_with('&') {
+        return target == "-" || (!target.is_empty() && target.chars().all(|c| c.is_ascii_digit()));
+    }
+    matches!(target, "/dev/null" | "NUL" | "nul")
+}
+
+/// 跳过 sed 中以 delimiter 结尾的一段（正则或替换文本）
+fn skip_delimited(chars: &mut std::iter::Peekable<std::str::Chars>, delimiter: char) {
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == delimiter {
+            break;
+        }
+    }
+}
+
+/// sed 脚本是否执行命令（e、s///e）或写文件（w、W、s///w）
+fn sed_runs_or_writes(script: &str) -> bool {
+    let mut chars = script.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' => skip_delimited(&mut chars, '/'),
+            '\\' => {
+                if let Some(delimiter) = chars.next() {
+                    skip_delimited(&mut chars, delimiter);
+                }
+            }
+            's' | 'y' => {
+                let Some(delimiter) = chars.next() else {
+                    return false;
+                };
+                skip_delimited(&mut chars, delimiter);
+                skip_delimited(&mut chars, delimiter);
+                if c == 's' {
+                    while let Some(&flag) = chars.peek() {
+                        if matches!(flag, ';' | '\n' | '}') {
+                            break;
+                        }
+                        if matches!(flag, 'e' | 'w') {
+                            return true;
+                        }
+                        chars.next();
+                    }
+                }
+            }
+            'e' | 'w' | 'W' => return true,
+            // 参数读到行尾：追加的文本、读取的文件名、注释
+            'a' | 'i' | 'c' | 'r' | 'R' | '#' => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            // 标签读到 ; 或行尾
+            'b' | 't' | 'T' | ':' => {
+                while chars.next_if(|c| *c != ';' && *c != '\n').is_some() {}
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// sed 的参数：脚本、文件和是否原地修改
+#[derive(Debug, Default)]
+struct SedArgs {
+    scripts: Vec<String>,
+    files: Vec<String>,
+    in_place: bool,
+}
+
+fn sed_arguments(args: &[String]) -> Result<SedArgs, String> {
+    let mut sed = SedArgs::default();
+    let mut operands = Vec::new();
+    let mut explicit_script = false;
+    let mut options_done = false;
+    let mut iter = args.iter().peekable();
+    while let Some(arg) = iter.next() {
+        if options_done || arg == "-" || !arg.starts_with('-') {
+            operands.push(arg.clone());
+            continue;
+        }
+        if arg == "--" {
+            options_done = true;
+            continue;
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            match name {
+                "expression" => {
+                    explicit_script = true;
+                    sed.scripts.extend(value.or_else(|| iter.next().cloned()));
+                }
+                "file" => return Err("不允许使用 sed 的脚本文件（-f），请直接写出脚本".to_string()),
+                "in-place" => sed.in_place = true,
+                "line-length" if value.is_none() => {
+                    iter.next();
+                }
+                _ => {}
+            }
+            continue;
+        }
+        // 短选项可以合写，如 -ne、-i.bak
+        for (i, flag) in arg.char_indices().skip(1) {
+            let rest = &arg[i + flag.len_utf8()..];
+            match flag {
+                'e' | 'l' => {
+                    let value = if rest.is_empty() { iter.next().cloned() } else { Some(rest.to_string()) };
+                    if flag == 'e' {
+                        explicit_script = true;
+                        sed.scripts.extend(value);
+                    }
+                    break;
+                }
+                'f' => return Err("不允许使用 sed 的脚本文件（-f），请直接写出脚本".to_string()),
+                'i' | 'I' => {
+                    sed.in_place = true;
+                    // BSD sed 的备份后缀是单独的参数，如 -i '' 或 -i .bak
+                    if rest.is_empty() && iter.peek().is_some_and(|next| next.is_empty() || next.starts_with('.')) {
+                        iter.next();
+                    }
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+    if !explicit_script && !operands.is_empty() {
+        sed.scripts.push(operands.remove(0));
+    }
+    sed.files = operands;
+    Ok(sed)
+}
+
+/// awk 程序是否执行命令或写文件：system()、管道（| 而非 ||）、print 重定向到文件
+fn awk_runs_or_writes(program: &str) -> bool {
+    let calls_system = program.match_indices("system").any(|(i, _)| {
+        program[i + "system".len()..].trim_start().starts_with('(')
+    });
+    let chars: Vec<char> = program.chars().collect();
+    let pipes = chars.iter().enumerate().any(|(i, c)| {
+        *c == '|' && chars.get(i + 1) != Some(&'|') && (i == 0 || chars[i - 1] != '|')
+    });
+    // > 所在语句以 print 开头时是输出重定向，否则是比较
+    let prints_to_file = chars.iter().enumerate().any(|(i, c)| {
+        if *c != '>' {
+            return false;
+        }
+        let start = chars[..i]
+            .iter()
+            .rposition(|c| matches!(c, ';' | '{' | '}' | '\n'))
+            .map_or(0, |p| p + 1);
+        chars[start..i].iter().collect::<String>().contains("print")
+    });
+    calls_system || pipes || prints_to_file
+}
+
+/// 检查程序参数中能执行其他程序或绕过文件访问审批的用法
+fn check_arguments(program: &str, args: &[String]) -> Result<(), String> {
+    if let Some((_, options)) = UNSAFE_OPTIONS.iter().find(|(p, _)| *p == program) {
+        let unsafe_arg = args.iter().find(|arg| {
+            options.iter().any(|option| {
+                arg.as_str() == *option || arg.strip_prefix(option).is_some_and(|rest| rest.starts_with('='))
+            })
+        });
+        if let Some(arg) = unsafe_arg {
+            return Err(format!(
+                "不允许使用 {} 的 {} 选项（会执行其他程序或绕过文件访问审批）",
+                program, arg
+            ));
+        }
+    }
+    match program {
+        "awk" | "gawk" | "mawk" | "nawk" => {
+            for arg in args {
+                let loads_file = ["-f", "--file", "-E", "--exec", "-l", "--load", "-i", "--include"]
+                    .iter()
+                    .any(|option| arg.starts_with(option));
+                if loads_file {
+                    return Err(format!("不允许使用 {} 的 {} 选项，请直接写出程序", program, arg));
+                }
+                if awk_runs_or_writes(arg) {
+                    return Err(format!(
+                        "{} 程序中不允许使用 system()、管道或输出重定向，请改用 fs.write 写入文件",
+                        program
+                    ));
+                }
+            }
+            Ok(())
+        }
+        "sed" | "gsed" => {
+            if sed_arguments(args)?.scripts.iter().any(|script| sed_runs_or_writes(script)) {
+                return Err("sed 脚本中不允许使用 e、w 命令或 s///e、s///w 标志".to_string());
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// shell_allowlist 中含 "*" 时不做任何检查
+fn is_unrestricted(allowlist: Option<&[String]>) -> bool {
+    allowlist.is_some_and(|list| list.iter().any(|p| p == "*"))
+}
+
+/// 按白名单检查命令，不允许时返回原因
+fn check_allowed(cmd: &str, allowlist: Option<&[String]>) -> Result<(), String> {
+    if is_unrestricted(allowlist) {
+        return Ok(());
+    }
+    // 命令替换可以绕过白名单执行任意程序
+    if cmd.contains('`') || cmd.contains("$(") {
+        return Err("命令中不允许使用命令替换（` 或 $()），或在 shell_allowlist 中设置 \"*\"".to_string());
+    }
+    let allowed = |program: &str| match allowlist {
+        Some(list) => list.iter().any(|p| p == program),
+        None => DEFAULT_ALLOWLIST.contains(&program),
+    };
+    let denied: Vec<String> = programs(cmd).into_iter().filter(|p| !allowed(p)).collect();
+    if !denied.is_empty() {
+        return Err(format!(
+            "程序不在允许列表中: {}（可在设置的 shell_allowlist 中添加）",
+            denied.join(", ")
+        ));
+    }
+    for segment in segments(cmd) {
+        if let Some((op, target)) = segment
+            .redirects
+            .iter()
+            .find(|(op, target)| !is_allowed_redirect(op, target))
+        {
+            return Err(format!(
+                "命令中不允许把输出重定向到文件（{}{}），请改用 fs.write",
+                op, target
+            ));
+        }
+        if let Some((program, args)) = command(&segment) {
+            check_arguments(program, args)?;
+        }
+    }
+    Ok(())
+}
+
+/// 写入路径中不能有运行时才展开的部分（变量、花括号、~用户名）
+fn is_literal_path(path: &str) -> bool {
+    !path.contains(['$', '{', '}']) && (!path.starts_with('~') || path == "~" || path.starts_with("~/"))
+}
+
+/// 相对路径拼接到 cd 后的目录（空目录表示任务工作目录）
+fn join_path(dir: &Path, path: &str) -> String {
+    if Path::new(path).is_absolute() || path == "~" || path.starts_with("~/") || dir.as_os_str().is_empty() {
+        path.to_string()
+    } else {
+        dir.join(path).to_string_lossy().to_string()
+    }
+}
+
+/// 选项以外的参数，value_options 中的选项跳过其后的值
+fn operands(args: &[String], value_options: &[&str]) -> Vec<String> {
+    let mut operands = Vec::new();
+    let mut options_done = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if options_done || arg == "-" || !arg.starts_with('-') {
+            operands.push(arg.clone());
+        } else if arg == "--" {
+            options_done = true;
+        } else if value_options.contains(&arg.as_str()) {
+            iter.next();
+        }
+    }
+    operands
+}
+
+/// cp、mv 写入的路径：目标（-t 或最后一个参数），mv 还包括被移走的源文件
+fn copy_targets(args: &[String], is_move: bool) -> Vec<String> {
+    let mut target_dir = None;
+    let mut sources = Vec::new();
+    let mut options_done = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if options_done || arg == "-" || !arg.starts_with('-') {
+            sources.push(arg.clone());
+            continue;
+        }
+        match arg.as_str() {
+            "--" => options_done = true,
+            "-t" | "--target-directory" => target_dir = iter.next().cloned(),
+            "-S" | "--suffix" => {
+                iter.next();
+            }
+            _ => {
+                if let Some(dir) = arg.strip_prefix("--target-directory=") {
+                    target_dir = Some(dir.to_string());
+                }
+            }
+        }
+    }
+    match target_dir {
+        Some(dir) if is_move => std::iter::once(dir).chain(sources).collect(),
+        Some(dir) => vec![dir],
+        None if sources.len() < 2 => Vec::new(),
+        None if is_move => sources,
+        None => sources.pop().into_iter().collect(),
+    }
+}
+
+/// sort 的输出文件（-o）
+fn sort_targets(args: &[String]) -> Vec<String> {
+    // 带值的短选项
+    const VALUE_FLAGS: &str = "kotST";
+    let mut targets = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            break;
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            if long == "output" {
+                targets.extend(iter.next().cloned());
+            } else if let Some(file) = long.strip_prefix("output=") {
+                targets.push(file.to_string());
+            }
+            continue;
+        }
+        let Some(flags) = arg.strip_prefix('-') else {
+            continue;
+        };
+        // 短选项可以合写，如 -uo out 或 -oout
+        for (i, flag) in flags.char_indices() {
+            if VALUE_FLAGS.contains(flag) {
+                let rest = &flags[i + flag.len_utf8()..];
+                let value = if rest.is_empty() { iter.next().cloned() } else { Some(rest.to_string()) };
+                if flag == 'o' {
+                    targets.extend(value);
+                }
+                break;
+            }
+        }
+    }
+    targets
+}
+
+/// tar 写入的路径：创建、追加时是归档文件，解压时是 -C 指定的目录（默认当前目录）
+fn tar_targets(args: &[String]) -> Vec<String> {
+    // 带值的短选项
+    const VALUE_FLAGS: &str = "bCfFgHIKLNTVX";
+    let mut writes_archive = false;
+    let mut extracts = false;
+    let mut archive = None;
+    let mut directory = None;
+    let mut apply = |flag: char, value: Option<String>| match flag {
+        'c' | 'r' | 'u' | 'A' => writes_archive = true,
+        'x' => extracts = true,
+        'f' => archive = value,
+        'C' => directory = value,
+        _ => {}
+    };
+    let mut iter = args.iter().peekable();
+    // 旧式写法的第一个参数不带 -，如 tar czf out.tar.gz docs，选项的值依次取后面的参数
+    if let Some(first) = iter.next_if(|arg| !arg.starts_with('-')) {
+        let flags: Vec<char> = first.chars().collect();
+        for flag in flags {
+            let value = VALUE_FLAGS.contains(flag).then(|| iter.next().cloned()).flatten();
+            apply(flag, value);
+        }
+    }
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            break;
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            match name {
+                "create" | "append" | "update" | "catenate" | "concatenate" => apply('c', None),
+                "extract" | "get" => apply('x', None),
+                "file" => apply('f', value.or_else(|| iter.next().cloned())),
+                "directory" => apply('C', value.or_else(|| iter.next().cloned())),
+                _ => {}
+            }
+        } else if let Some(flags) = arg.strip_prefix('-') {
+            for (i, flag) in flags.char_indices() {
+                if VALUE_FLAGS.contains(flag) {
+                    let rest = &flags[i + flag.len_utf8()..];
+                    let value = if rest.is_empty() { iter.next().cloned() } else { Some(rest.to_string()) };
+                    apply(flag, value);
+                    break;
+                }
+                apply(flag, None);
+            }
+        }
+    }
+    let mut targets = Vec::new();
+    if writes_archive {
+        targets.extend(archive.filter(|file| file != "-"));
+    }
+    if extracts {
+        targets.push(directory.unwrap_or_else(|| ".".to_string()));
+    }
+    targets
+}
+
+/// unzip 解压到的目录（-d，默认当前目录）；只列出、测试或输出到标准输出时不写入
+fn unzip_targets(args: &[String]) -> Vec<String> {
+    let mut directory = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let Some(flags) = arg.strip_prefix('-') else {
+            continue;
+        };
+        for (i, flag) in flags.char_indices() {
+            match flag {
+                'd' => {
+                    let rest = &flags[i + 1..];
+                    directory = if rest.is_empty() { iter.next().cloned() } else { Some(rest.to_string()) };
+                    break;
+                }
+                'l' | 't' | 'v' | 'p' | 'c' | 'Z' => return Vec::new(),
+                _ => {}
+            }
+        }
+    }
+    vec![directory.unwrap_or_else(|| ".".to_string())]
+}
+
+/// zip 写入的归档文件：-O 指定的输出文件，否则是第一个参数
+fn zip_targets(args: &[String]) -> Vec<String> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "-O" || arg == "--output-file" {
+            return iter.next().cloned().into_iter().collect();
+        }
+    }
+    operands(
+        args,
+        &["-b", "-n", "-t", "-tt", "-P", "-Z", "-s", "--temp-path", "--suffixes", "--password", "--compression-method"],
+    )
+    .into_iter()
+    .take(1)
+    .collect()
+}
+
+/// gzip 原地压缩、解压的文件（输出文件与源文件在同一目录）；-c、-l、-t 不写入
+fn gzip_targets(args: &[String]) -> Vec<String> {
+    let no_write = args.iter().any(|arg| {
+        matches!(arg.as_str(), "--stdout" | "--to-stdout" | "--list" | "--test")
+            || (arg.starts_with('-') && !arg.starts_with("--") && arg[1..].contains(['c', 'l', 't']))
+    });
+    if no_write {
+        return Vec::new();
+    }
+    operands(args, &["-S", "--suffix"])
+}
+
+/// git clone 写入的目录：第二个参数，否则按仓库地址推断；-C 改变起始目录
+fn git_targets(args: &[String]) -> Vec<String> {
+    let mut start = PathBuf::new();
+    let mut iter = args.iter();
+    let mut subcommand = None;
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-C" => {
+                if let Some(dir) = iter.next() {
+                    start = PathBuf::from(join_path(&start, dir));
+                }
+            }
+            "--git-dir" | "--work-tree" | "--namespace" => {
+                iter.next();
+            }
+            arg if arg.starts_with('-') => {}
+            arg => {
+                subcommand = Some(arg);
+                break;
+            }
+        }
+    }
+    if subcommand != Some("clone") {
+        return Vec::new();
+    }
+    let rest: Vec<String> = iter.cloned().collect();
+    let operands = operands(
+        &rest,
+        &[
+            "-o", "-b", "-u", "-j", "--origin", "--branch", "--depth", "--reference", "--reference-if-able",
+            "--template", "--separate-git-dir", "--config", "--shallow-since", "--shallow-exclude",
+            "--jobs", "--filter", "--server-option", "--bundle-uri",
+        ],
+    );
+    let target = match operands.as_slice() {
+        [_, dir, ..] => dir.clone(),
+        [url] => {
+            let url = url.trim_end_matches('/');
+            let url = url.strip_suffix("/.git").unwrap_or(url);
+            let name = url.rsplit(['/', ':', '\\']).next().unwrap_or(url);
+            name.strip_suffix(".git").unwrap_or(name).to_string()
+        }
+        [] => return Vec::new(),
+    };
+    vec![join_path(&start, &target)]
+}
+
+/// 命令中写入文件的程序（cp、mv、mkdir、touch、sed -i、sort -o、uniq、tar、unzip、zip、gzip、git clone）
+/// 写入的路径，相对路径按命令中的 cd 拼接
+fn write_targets(cmd: &str) -> Result<Vec<String>, String> {
+    // None 表示 cd 到了无法确定的目录
+    let mut dir = Some(PathBuf::new());
+    let mut targets = Vec::new();
+    for segment in segments(cmd) {
+        let Some((program, args)) = command(&segment) else {
+            continue;
+        };
+        let paths = match program {
+            "cd" => {
+                let target = args.iter().find(|a| !a.starts_with('-')).map_or("~", String::as_str);
+                dir = if !is_literal_path(target) {
+                    None
+                } else if Path::new(target).is_absolute() || target.starts_with('~') {
+                    Some(PathBuf::from(target))
+                } else {
+                    dir.map(|d| PathBuf::from(join_path(&d, target)))
+                };
+                continue;
+            }
+            "cp" => copy_targets(args, false),
+            "mv" => copy_targets(args, true),
+            "mkdir" => operands(args, &["-m", "--mode"]),
+            "touch" => operands(args, &["-d", "-r", "-t", "--date", "--reference"]),
+            "sed" | "gsed" => {
+                let sed = sed_arguments(args)?;
+                if sed.in_place {
+                    sed.files
+                } else {
+                    Vec::new()
+                }
+            }
+            "sort" => sort_targets(args),
+            // uniq [选项] [输入 [输出]]
+            "uniq" => operands(
+                args,
+                &["-f", "-s", "-w", "--skip-fields", "--skip-chars", "--check-chars"],
+            )
+            .into_iter()
+            .skip(1)
+            .take(1)
+            .collect(),
+            "tar" => tar_targets(args),
+            "unzip" => unzip_targets(args),
+            "zip" => zip_targets(args),
+            "gzip" => gzip_targets(args),
+            "git" => git_targets(args),
+            _ => continue,
+        };
+        for path in paths {
+            if !is_literal_path(&path) {
+                return Err(format!("写入的路径中不允许使用变量、花括号或 ~用户名: {}", path));
+            }
+            match &dir {
+                Some(dir) => targets.push(join_path(dir, &path)),
+                None if Path::new(&path).is_absolute() => targets.push(path),
+                None => return Err(format!("cd 到无法确定的目录后不能写入相对路径: {}", path)),
+            }
+        }
+    }
+    Ok(targets)
+}
+
+/// 在后台线程读取输出，超出上限的部分读取后丢弃（避免子进程因管道写满而阻塞）
+fn capture(mut reader: impl Read + Send + 'static) -> std::thread::JoinHandle<(Vec<u8>, bool)> {
+    std::thread::spawn(move || {
+        let mut kept = Vec::new();
+        let mut truncated = false;
+        let mut buf = [0u8; 8192];
+        while let Ok(n) = reader.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            let room = MAX_OUTPUT_BYTES.saturating_sub(kept.len());
+            if n > room {
+                truncated = true;
+            }
+            kept.extend_from_slice(&buf[..n.min(room)]);
+        }
+        (kept, truncated)
+    })
+}
+
+#[cfg(unix)]
+fn shell_command(cmd: &str) -> Command {
+    use std::os::unix::process::CommandExt;
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(cmd);
+    // 独立进程组，超时时连同子进程一起结束
+    command.process_group(0);
+    command
+}
+
+#[cfg(windows)]
+fn shell_command(cmd: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let mut command = Command::new("cmd");
+    command.arg("/C").raw_arg(cmd);
+    command.creation_flags(CREATE_NO_WINDOW);
+    command
+}
+
+/// 结束命令及其子进程
+fn kill_tree(child: &mut std::process::Child) {
+    #[cfg(unix)]
+    let _ = Command::new("kill")
+        .arg("-KILL")
+        .arg(format!("-{}", child.id()))
+        .status();
+    #[cfg(windows)]
+    let _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &child.id().to_string()])
+        .status();
+    let _ = child.kill();
+}
+
+/// 执行一条命令，超时或任务被停止时结束
+pub fn run_shell(
+    request_id: &str,
+    cmd: &str,
+    cwd: Option<&Path>,
+    timeout: Duration,
+) -> Result<ShellOutput, String> {
+    let mut command = shell_command(cmd);
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(dir) = cwd {
+        command.current_dir(dir);
+    }
+    let started = Instant::now();
+    let mut child = command.spawn().map_err(|e| format!("启动命令失败: {}", e))?;
+    let stdout = child.stdout.take().map(capture);
+    let stderr = child.stderr.take().map(capture);
+
+    let mut timed_out = false;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) => {}
+            Err(e) => {
+                kill_tree(&mut child);
+                return Err(format!("等待命令结束失败: {}", e));
+            }
+        }
+        if started.elapsed() >= timeout {
+            timed_out = true;
+        }
+        if timed_out || task_control::stop_requested(request_id) {
+            kill_tree(&mut child);
+            let _ = child.wait();
+            break None;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    let collect = |handle: Option<std::thread::JoinHandle<(Vec<u8>, bool)>>| {
+        handle
+            .and_then(|h| h.join().ok())
+            .map(|(bytes, truncated)| (String::from_utf8_lossy(&bytes).to_string(), truncated))
+            .unwrap_or_default()
+    };
+    let (stdout, stdout_truncated) = collect(stdout);
+    let (stderr, stderr_truncated) = collect(stderr);
+    Ok(ShellOutput {
+        exit_code: status.and_then(|s| s.code()),
+        stdout,
+        stderr,
+        truncated: stdout_truncated || stderr_truncated,
+        timed_out,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// shell 工具：检查参数和白名单后执行
+fn shell(request_id: &str, work_dir: Option<&Path>, args: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let cmd = args
+        .get("cmd")
+        .and_then(|c| c.as_str())
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .ok_or("缺少参数 cmd")?;
+    let app_config = config::load_config().ok();
+    let allowlist = app_config.as_ref().and_then(|c| c.shell_allowlist.as_deref());
+    check_allowed(cmd, allowlist)?;
+    let timeout_secs = args
+        .get("timeout")
+        .and_then(|t| t.as_u64())
+        .or_else(|| app_config.as_ref().and_then(|c| c.shell_timeout_secs))
+        .unwrap_or(DEFAULT_TIMEOUT_SECS)
+        .clamp(1, MAX_TIMEOUT_SECS);
+    // 相对路径基于任务工作目录
+    let cwd: Option<PathBuf> = match args.get("cwd").and_then(|c| c.as_str()) {
+        Some(dir) => Some(work_dir.map(|w| w.join(dir)).unwrap_or_else(|| PathBuf::from(dir))),
+        None => work_dir.map(Path::to_path_buf),
+    };
+    // 写入的路径与 fs.write 同样受文件访问边界限制
+    if !is_unrestricted(allowlist) {
+        for target in write_targets(cmd)? {
+            fs_tools::checked(&target, cwd.as_deref(), true)?;
+        }
+    }
+    let output = run_shell(request_id, cmd, cwd.as_deref(), Duration::from_secs(timeout_secs))?;
+    eprintln!(
+        "[Tauri] 🐚 任务 {} 执行命令（{} ms，退出码 {:?}）: {}",
+        request_id, output.duration_ms, output.exit_code, cmd
+    );
+    serde_json::to_value(&output).map_err(|e| format!("序列化失败: {}", e))
+}
+
+/// 执行工具，返回结果或失败原因
+fn dispatch(request_id: &str, work_dir: Option<&Path>, event: &ToolCallEvent) -> Result<serde_json::Value, String> {
+    match event.name.as_str() {
+        "shell" => shell(request_id, work_dir, &event.args),
+        "mcp" => mcp::call(&event.args),
+        "system.info" => system_info::call(&event.args),
+        "email.fetch" => imap_client::call(&event.args),
+        "calendar.list" => calendar::call_list(&event.args),
+        "calendar.create" => calendar::call_create(&event.args),
+        name => match name.strip_prefix("fs.") {
+            Some(op) => fs_tools::call(request_id, op, work_dir, &event.args),
+            None => Err(format!("未知的工具: {}", name)),
+        },
+    }
+}
+
+/// call_id 用作文件名，只允许字母、数字和下划线
+fn is_valid_call_id(call_id: &str) -> bool {
+    !call_id.is_empty()
+        && call_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 写入 tool_result，供 Python 轮询读取
+fn write_result(call_id: &str, result: &Result<serde_json::Value, String>) -> Result<(), String> {
+    let dir = config::get_data_dir()?.join("tool_results");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    let content = match result {
+        Ok(output) => serde_json::json!({ "type": "tool_result", "call_id": call_id, "ok": true, "output": output }),
+        Err(e) => serde_json::json!({ "type": "tool_result", "call_id": call_id, "ok": false, "error": e }),
+    };
+    // 先写临时文件再改名，避免 Python 读到半个文件
+    let path = dir.join(format!("{}.json", call_id));
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, content.to_string()).map_err(|e| format!("写入工具结果失败: {}", e))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("写入工具结果失败: {}", e))
+}
+
+/// 处理一条 tool_call 事件：在后台线程执行，不阻塞事件读取
+pub fn handle_call(request_id: &str, work_dir: Option<&Path>, event: &ToolCallEvent) {
+    let call_id = event.call_id.clone();
+    if !is_valid_call_id(&call_id) {
+        eprintln!("[Tauri] ⚠️ 无效的工具调用 ID: {}", call_id);
+        return;
+    }
+    let request_id = request_id.to_string();
+    let work_dir = work_dir.map(Path::to_path_buf);
+    let event = event.clone();
+    std::thread::spawn(move || {
+        // system.info 只读取状态，观察模式下也允许
+        let result = if observer::is_enabled() && event.name != "system.info" {
+            Err("观察模式下不允许执行工具".to_string())
+        } else {
+            dispatch(&request_id, work_dir.as_deref(), &event)
+        };
+        let summary = match &result {
+            Ok(output) => output
+                .get("exit_code")
+                .map(|code| format!("exit_code={}", code))
+                .unwrap_or_else(|| "ok".to_string()),
+            Err(e) => e.clone(),
+        };
+        // 写入的内容不记入审计日志，只记录长度
+        let mut arguments = event.args.clone();
+        if let Some(content) = arguments.get_mut("content") {
+            let len = content.as_str().map(str::len).unwrap_or_default();
+            *content = serde_json::json!(format!("<{} 字节>", len));
+        }
+        if let Err(e) = audit::record_step(
+            &request_id,
+            &format!("tool:{}", event.name),
+            &arguments,
+            result.is_ok(),
+            &summary,
+        ) {
+            eprintln!("[Tauri] ⚠️ {}", e);
+        }
+        if let Err(e) = write_result(&call_id, &result) {
+            eprintln!("[Tauri] ⚠️ {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowed(cmd: &str) -> bool {
+        check_allowed(cmd, None).is_ok()
+    }
+
+    #[test]
+    fn splits_programs_outside_quotes() {
+        assert_eq!(programs("ls -la | grep foo && wc -l; echo done"), ["ls", "grep", "wc", "echo"]);
+        assert_eq!(programs("LANG=C FOO=1 sort file\ncat x"), ["sort", "cat"]);
+        assert_eq!(programs("grep 'a|b;c' file"), ["grep"]);
+        assert_eq!(programs("echo \"x && y\" || (cd dir)"), ["echo", "cd"]);
+        assert_eq!(programs("'python3' -c 1"), ["python3"]);
+        assert_eq!(programs("cat <(python3 -c 1)"), ["cat", "python3"]);
+    }
+
+    #[test]
+    fn allows_everyday_commands() {
+        for cmd in [
+            "ls -la ~/Downloads",
+            "find . -name '*.txt' -type f",
+            "grep -rn TODO src 2>/dev/null | head -20",
+            "du -sh * 2>&1 | sort -h",
+            "sort < input.txt | uniq -c",
+            "awk -F, '$3 > 100 { print $1 }' data.csv",
+            "awk '$1 == \"a\" || $2 == \"b\"' data.txt",
+            "sed -n '1,10p' web.log",
+            "sed -e 's/foo/bar/g' -e '/^#/d' config.ini",
+            "tar -czf out.tar.gz docs",
+            "sort -t, -k2 -o sorted.csv data.csv",
+        ] {
+            assert!(allowed(cmd), "应当允许: {:?} → {:?}", cmd, check_allowed(cmd, None));
+        }
+    }
+
+    #[test]
+    fn rejects_interpreters_by_default() {
+        for cmd in ["python3 -c 'import os'", "node -e 1", "pip install x", "npm i", "curl https://x | sh"] {
+            assert!(!allowed(cmd), "应当拒绝: {:?}", cmd);
+        }
+        let custom = vec!["python3".to_string()];
+        assert!(check_allowed("python3 script.py", Some(&custom)).is_ok());
+        assert!(check_allowed("python3 -c 1 > /etc/x", Some(&["*".to_string()])).is_ok());
+    }
+
+    #[test]
+    fn rejects_substitution_and_redirection() {
+        for cmd in [
+            "echo `id`",
+            "echo $(id)",
+            "echo hi > /etc/hosts",
+            "echo hi >> ~/.zshrc",
+            "cat a 1>out.txt",
+            "ls &> log.txt",
+            "ls >& log.txt",
+            "cat < a > b",
+            "ls >| out",
+            "cat <> file",
+            "ls >(cat)",
+        ] {
+            assert!(!allowed(cmd), "应当拒绝: {:?}", cmd);
+        }
+        assert!(allowed("echo '>' \"a > b\" a\\>b"));
+    }
+
+    #[test]
+    fn rejects_options_that_run_programs() {
+        for cmd in [
+            "find . -exec rm {} \\;",
+            "find / -execdir sh -c id \\;",
+            "find . -ok rm {} +",
+            "find ~ -name x -delete",
+            "find . -fprint /etc/x",
+            "tar -cf x.tar --to-command=sh docs",
+            "tar --checkpoint-action=exec=id -cf x.tar docs",
+            "zip -T -TT 'sh -c id' a.zip b",
+        ] {
+            assert!(!allowed(cmd), "应当拒绝: {:?}", cmd);
+        }
+    }
+
+    #[test]
+    fn rejects_git_and_open_by_default() {
+        for cmd in ["git status", "open -a Terminal", "xdg-open file.txt", "sort --compress-program=sh a"] {
+            assert!(!allowed(cmd), "应当拒绝: {:?}", cmd);
+        }
+        let custom = vec!["git".to_string()];
+        assert!(check_allowed("git status", Some(&custom)).is_ok());
+        for cmd in [
+            "git -c core.fsmonitor=id status",
+            "git -c core.pager=id log",
+            "git -c alias.x='!sh -c id' x",
+            "git --config-env=core.pager=CMD log",
+            "git --exec-path=/tmp/bin status",
+            "git clone --upload-pack=id https://example.com/r.git",
+            "git push --receive-pack id origin",
+            "git push --exec=id origin",
+        ] {
+            assert!(check_allowed(cmd, Some(&custom)).is_err(), "应当拒绝: {:?}", cmd);
+        }
+    }
+
+    #[test]
+    fn rejects_awk_and_sed_execution() {
+        for cmd in [
+            "awk 'BEGIN{system(\"id\")}'",
+            "awk 'BEGIN { system (\"id\") }'",
+            "awk '{ print | \"sh\" }' file",
+            "awk 'BEGIN { \"id\" | getline x }'",
+            "awk '{ print $1 > \"/etc/x\" }' file",
+            "awk '{ printf \"%s\", $0 >> \"out\" }' file",
+            "awk -f prog.awk file",
+            "sed e file",
+            "sed '1e id' file",
+            "sed 's/x/id/e' file",
+            "sed -n 's/a/b/w /etc/x' file",
+            "sed 'w /etc/x' file",
+            "sed -e p -e '$W out' file",
+            "sed --expression='1e id' file",
+            "sed -ne '1e id' file",
+            "sed -f script.sed file",
+            "sed -i '' 'w /etc/x' file",
+        ] {
+            assert!(!allowed(cmd), "应当拒绝: {:?}", cmd);
+        }
+        // 文本、标签和文件名中的 e / w 不是命令
+        for cmd in [
+            "sed 's/we/ew/g' file",
+            "sed '/web/d' file",
+            "sed '$a new line' file",
+            "sed ':loop; /x/b loop' file",
+            "sed 'y/ew/we/' file",
+            "sed -n '/^e/p' web.log",
+        ] {
+            assert!(allowed(cmd), "应当允许: {:?} → {:?}", cmd, check_allowed(cmd, None));
+        }
+    }
+
+    #[test]
+    fn collects_write_targets() {
+        assert_eq!(write_targets("cp a.txt b.txt ~/Desktop").unwrap(), ["~/Desktop"]);
+        assert_eq!(write_targets("cp -t /tmp/out a b").unwrap(), ["/tmp/out"]);
+        assert_eq!(write_targets("mv a b dir").unwrap(), ["a", "b", "dir"]);
+        assert_eq!(write_targets("mkdir -p -m 755 x/y z").unwrap(), ["x/y", "z"]);
+        assert_eq!(write_targets("touch -d yesterday f").unwrap(), ["f"]);
+        assert_eq!(write_targets("sed -i.bak 's/a/b/' one two").unwrap(), ["one", "two"]);
+        assert_eq!(write_targets("sed -i '' 's/a/b/' one").unwrap(), ["one"]);
+        assert!(write_targets("sed 's/a/b/' one").unwrap().is_empty());
+        assert!(write_targets("ls; cat a | grep b").unwrap().is_empty());
+    }
+
+    #[test]
+    fn collects_archive_and_output_targets() {
+        assert_eq!(write_targets("sort -o /etc/x a").unwrap(), ["/etc/x"]);
+        assert_eq!(write_targets("sort -uo/etc/x a").unwrap(), ["/etc/x"]);
+        assert_eq!(write_targets("sort --output=out -t o -k2 a").unwrap(), ["out"]);
+        assert!(write_targets("sort -to -k2 a").unwrap().is_empty());
+        assert_eq!(write_targets("uniq -c in.txt /etc/out").unwrap(), ["/etc/out"]);
+        assert_eq!(write_targets("uniq -f 1 in.txt out").unwrap(), ["out"]);
+        assert!(write_targets("uniq -c in.txt").unwrap().is_empty());
+        assert_eq!(write_targets("tar -xf a.tar -C /etc").unwrap(), ["/etc"]);
+        assert_eq!(write_targets("tar --extract --file=a.tar --directory /etc").unwrap(), ["/etc"]);
+        assert_eq!(write_targets("tar xzf a.tgz").unwrap(), ["."]);
+        assert_eq!(write_targets("tar czf /etc/out.tgz docs").unwrap(), ["/etc/out.tgz"]);
+        assert!(write_targets("tar -tf a.tar").unwrap().is_empty());
+        assert_eq!(write_targets("unzip -o a.zip -d /etc").unwrap(), ["/etc"]);
+        assert_eq!(write_targets("unzip a.zip").unwrap(), ["."]);
+        assert!(write_targets("unzip -l a.zip").unwrap().is_empty());
+        assert_eq!(write_targets("zip -r /etc/out.zip docs").unwrap(), ["/etc/out.zip"]);
+        assert_eq!(write_targets("gzip -k a.txt b.txt").unwrap(), ["a.txt", "b.txt"]);
+        assert!(write_targets("gzip -c a.txt").unwrap().is_empty());
+        assert_eq!(write_targets("git clone https://example.com/repo.git /etc/r").unwrap(), ["/etc/r"]);
+        assert_eq!(write_targets("git -C /etc clone git@example.com:me/repo.git").unwrap(), ["/etc/repo"]);
+        assert_eq!(write_targets("cd /tmp && git clone --depth 1 https://example.com/x/").unwrap(), ["/tmp/x"]);
+        assert!(write_targets("git log").unwrap().is_empty());
+    }
+
+    #[test]
+    fn write_targets_follow_cd() {
+        assert_eq!(write_targets("cd /etc && touch x").unwrap(), ["/etc/x"]);
+        assert_eq!(write_targets("cd sub; cd deeper; mkdir x").unwrap(), ["sub/deeper/x"]);
+        assert_eq!(write_targets("cd; touch x").unwrap(), ["~/x"]);
+        assert_eq!(write_targets("cd $HOME && touch /tmp/x").unwrap(), ["/tmp/x"]);
+        assert!(write_targets("cd $HOME && touch x").is_err());
+        assert!(write_targets("touch $HOME/x").is_err());
+        assert!(write_targets("cp a ~root/x").is_err());
+        assert!(write_targets("mkdir {a,b}").is_err());
+    }
+}
@@ -0,0 +1,309 @@
+//! 会话记录：把会话中的指令、Agent 回复、步骤和产物导出为可携带的 JSON 或 Markdown，便于存档或反馈问题时复现
+//!
+//! 导出路径以 .md / .markdown 结尾时写 Markdown（只供阅读），否则写 JSON。指令、回复和步骤输出先经过
+//! 脱敏规则，步骤来自任务结束时保存的结果（见 report），早于该功能的任务没有步骤。
+//! 导入只接受 JSON：新建一个会话并写回 Agent 的会话记忆，之后可在该会话中继续追问；
+//! 原会话的任务不写入本机任务历史，完整记录在导入结果中返回。
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::history::{now_millis, TerminationReason};
+use crate::sessions::{self, Session};
+use crate::{redaction, report, TaskResult};
+
+/// 当前会话记录格式版本
+const TRANSCRIPT_FORMAT_VERSION: u32 = 1;
+
+/// 单个步骤输出在记录中的最大长度（字符）
+const MAX_OUTPUT_CHARS: usize = 2000;
+
+/// 记录中的一个步骤
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptStep {
+    #[serde(rename = "type")]
+    pub step_type: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub success: Option<bool>,
+    #[serde(default)]
+    pub message: String,
+}
+
+/// 记录中的一轮：一条指令及其结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptTurn {
+    pub task_id: String,
+    pub instruction: String,
+    pub started_at: u64,
+    #[serde(default)]
+    pub finished_at: Option<u64>,
+    #[serde(default)]
+    pub success: Option<bool>,
+    /// Agent 的回复（任务结果消息）
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub termination_reason: Option<TerminationReason>,
+    #[serde(default)]
+    pub steps: Vec<TranscriptStep>,
+    /// 工作目录中生成的文件（相对路径，文件本身不随记录导出）
+    #[serde(default)]
+    pub artifacts: Vec<String>,
+}
+
+/// 会话记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTranscript {
+    pub format_version: u32,
+    /// 导出时间（毫秒时间戳）
+    pub exported_at: u64,
+    #[serde(default)]
+    pub app_version: String,
+    pub session_id: String,
+    pub title: String,
+    pub created_at: u64,
+    pub turns: Vec<TranscriptTurn>,
+    /// Agent 的会话记忆（最近的对话轮次），导入后用于继续追问
+    #[serde(default)]
+    pub memory: Vec<serde_json::Value>,
+}
+
+/// import_session 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedSession {
+    /// 新建的会话
+    pub session: Session,
+    pub transcript: SessionTranscript,
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_OUTPUT_CHARS) {
+        Some((i, _)) => format!("{}…", &text[..i]),
+        None => text.to_string(),
+    }
+}
+
+fn transcript_steps(result: &TaskResult) -> Vec<TranscriptStep> {
+    let text = |v: &serde_json::Value, key: &str| {
+        v.get(key).and_then(|x| x.as_str()).unwrap_or_default().to_string()
+    };
+    result
+        .steps
+        .iter()
+        .map(|s| {
+            let outcome = s.result.as_ref();
+            TranscriptStep {
+                step_type: text(&s.step, "type"),
+                description: text(&s.step, "description"),
+                success: outcome.and_then(|r| r.get("success")).and_then(|v| v.as_bool()),
+                message: truncate(&outcome.map(|r| text(r, "message")).unwrap_or_default()),
+            }
+        })
+        .collect()
+}
+
+/// Agent 保存的会话记忆轮次
+fn load_memory(session_id: &str) -> Vec<serde_json::Value> {
+    sessions::memory_path(session_id)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|data| data.get("turns").and_then(|t| t.as_array()).cloned())
+        .unwrap_or_default()
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("md") || e.eq_ignore_ascii_case("markdown"))
+}
+
+impl SessionTranscript {
+    fn markdown(&self) -> String {
+        let time = |millis: u64| {
+            chrono::TimeZone::timestamp_millis_opt(&chrono::Local, millis as i64)
+                .single()
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_else(|| "-".to_string())
+        };
+        let mut out = format!("# 会话记录：{}\n\n", self.title.replace('\n', " "));
+        out.push_str(&format!("- 会话 ID：`{}`\n", self.session_id));
+        out.push_str(&format!("- 创建时间：{}\n", time(self.created_at)));
+        out.push_str(&format!("- 导出时间：{}\n", time(self.exported_at)));
+        if !self.app_version.is_empty() {
+            out.push_str(&format!("- 应用版本：{}\n", self.app_version));
+        }
+        if self.turns.is_empty() {
+            out.push_str("\n会话中还没有任务。\n");
+        }
+        for (i, turn) in self.turns.iter().enumerate() {
+            let status = match (turn.success, turn.termination_reason) {
+                (None, _) => "执行中",
+                (_, Some(_)) => "提前结束",
+                (Some(true), None) => "成功",
+                (Some(false), None) => "失败",
+            };
+            out.push_str(&format!("\n## {}. {}\n\n", i + 1, turn.instruction.replace('\n', " ")));
+            out.push_str(&format!("- 任务 ID：`{}`\n", turn.task_id));
+            out.push_str(&format!("- 时间：{}\n", time(turn.started_at)));
+            out.push_str(&format!("- 状态：{}\n", status));
+            if let Some(message) = turn.message.as_deref().filter(|m| !m.is_empty()) {
+                out.push_str("\n**DeskJarvis：**\n\n");
+                for line in message.lines() {
+                    out.push_str(&format!("> {}\n", line));
+                }
+            }
+            if !turn.steps.is_empty() {
+                out.push_str("\n步骤：\n\n");
+                for (n, step) in turn.steps.iter().enumerate() {
+                    let mark = match step.success {
+                        Some(true) => "✅",
+                        Some(false) => "❌",
+                        None => "•",
+                    };
+                    let title = if step.description.is_empty() {
+                        &step.step_type
+                    } else {
+                        &step.description
+                    };
+                    out.push_str(&format!("{}. {} {} `{}`\n", n + 1, mark, title, step.step_type));
+                }
+            }
+            if !turn.artifacts.is_empty() {
+                out.push_str("\n产物：\n\n");
+                for name in &turn.artifacts {
+                    out.push_str(&format!("- `{}`\n", name));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// 导出会话记录，路径以 .md 结尾时写 Markdown，否则写 JSON
+#[tauri::command]
+pub async fn export_session(
+    app: AppHandle,
+    state: tauri::State<'_, crate::AppState>,
+    session_id: String,
+    path: String,
+) -> Result<SessionTranscript, String> {
+    let session = state
+        .sessions
+        .list()
+        .into_iter()
+        .find(|s| s.id == session_id)
+        .ok_or_else(|| format!("未找到会话: {}", session_id))?;
+    let turns = session
+        .task_ids
+        .iter()
+        .filter_map(|id| state.history.get(id))
+        .map(|record| TranscriptTurn {
+            steps: report::load_result(&record.id)
+                .map(|r| transcript_steps(&r))
+                .unwrap_or_default(),
+            task_id: record.id,
+            instruction: redaction::redact(&record.instruction),
+            started_at: record.started_at,
+            finished_at: record.finished_at,
+            success: record.success,
+            message: record.message.as_deref().map(redaction::redact),
+            termination_reason: record.termination_reason,
+            artifacts: record.artifacts,
+        })
+        .collect();
+    let mut memory = load_memory(&session.id);
+    memory.iter_mut().for_each(redaction::redact_json);
+    let transcript = SessionTranscript {
+        format_version: TRANSCRIPT_FORMAT_VERSION,
+        exported_at: now_millis(),
+        app_version: app.package_info().version.to_string(),
+        session_id: session.id,
+        title: session.title,
+        created_at: session.created_at,
+        turns,
+        memory,
+    };
+
+    let path = PathBuf::from(path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = if is_markdown(&path) {
+        transcript.markdown()
+    } else {
+        serde_json::to_string_pretty(&transcript).map_err(|e| format!("序列化会话记录失败: {}", e))?
+    };
+    std::fs::write(&path, content).map_err(|e| format!("写入会话记录失败: {}", e))?;
+    eprintln!(
+        "[Tauri] 💬 已导出会话记录 {}（{} 轮）: {}",
+        transcript.title,
+        transcript.turns.len(),
+        path.display()
+    );
+    Ok(transcript)
+}
+
+/// 导入 JSON 会话记录：新建会话并恢复 Agent 的会话记忆
+#[tauri::command]
+pub async fn import_session(
+    state: tauri::State<'_, crate::AppState>,
+    path: String,
+) -> Result<ImportedSession, String> {
+    let path = PathBuf::from(path);
+    if is_markdown(&path) {
+        return Err("Markdown 会话记录只供阅读，请导入 JSON 格式的记录".to_string());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("读取会话记录失败: {}", e))?;
+    let transcript: SessionTranscript =
+        serde_json::from_str(&content).map_err(|e| format!("解析会话记录失败: {}", e))?;
+    if transcript.format_version > TRANSCRIPT_FORMAT_VERSION {
+        return Err(format!(
+            "会话记录格式版本 {} 高于当前支持的 {}，请升级应用后再导入",
+            transcript.format_version, TRANSCRIPT_FORMAT_VERSION
+        ));
+    }
+
+    // 没有会话记忆的记录（如手工整理的）由各轮指令和回复生成
+    let memory = if transcript.memory.is_empty() {
+        transcript
+            .turns
+            .iter()
+            .map(|turn| {
+                serde_json::json!({
+                    "instruction": turn.instruction,
+                    "message": turn.message.clone().unwrap_or_default(),
+                    "success": turn.success.unwrap_or(false),
+                    "files": [],
+                    "timestamp": turn.started_at as f64 / 1000.0,
+                })
+            })
+            .collect()
+    } else {
+        transcript.memory.clone()
+    };
+
+    let session = state
+        .sessions
+        .create(Some(format!("{}（导入）", transcript.title.trim())))?;
+    let memory_path = sessions::memory_path(&session.id)?;
+    if let Some(parent) = memory_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::json!({ "session_id": session.id, "turns": memory }).to_string();
+    std::fs::write(&memory_path, content).map_err(|e| format!("写入会话记忆失败: {}", e))?;
+    eprintln!(
+        "[Tauri] 💬 已导入会话记录 {}（{} 轮）为 {}",
+        transcript.title,
+        transcript.turns.len(),
+        session.id
+    );
+    Ok(ImportedSession {
+        session,
+        transcript,
+    })
+}
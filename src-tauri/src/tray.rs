@@ -0,0 +1,525 @@
+//! 系统托盘：菜单、图标与 Agent 状态指示
+
+use serde::Serialize;
+use tauri::{
+    image::Image,
+    menu::{
+        CheckMenuItem, CheckMenuItemBuilder, Menu, MenuBuilder, MenuItem, MenuItemBuilder,
+        Submenu, SubmenuBuilder,
+    },
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    App, AppHandle, Emitter, Manager, Wry,
+};
+
+use crate::i18n;
+
+/// 托盘图标 ID
+pub const TRAY_ID: &str = "main";
+
+/// 托盘提示前缀
+const TOOLTIP_PREFIX: &str = "DeskJarvis - AI 桌面助手";
+
+/// 观察模式下附加在托盘提示末尾的说明
+const OBSERVER_TOOLTIP: &str = "👁 观察模式：只读，禁止一切修改操作";
+
+/// 托盘菜单一键开始专注的时长（分钟）
+const FOCUS_MINUTES: u32 = 25;
+
+/// 托盘中指令预览的最大字符数
+const PREVIEW_CHARS: usize = 24;
+
+/// 固定模板菜单项 ID 的前缀，后接模板 ID
+const TEMPLATE_ITEM_PREFIX: &str = "template:";
+
+/// 最近任务菜单项 ID 的前缀，后接任务 ID
+const RECENT_ITEM_PREFIX: &str = "recent:";
+
+/// "最近任务" 子菜单的条目数（相同指令只保留最近一次）
+const RECENT_TASKS: usize = 8;
+
+/// 固定文字的菜单项 (ID, 中文原文)，切换界面语言时按 ID 刷新
+const MENU_LABELS: &[(&str, &str)] = &[
+    ("observer_mode", "观察模式（只读）"),
+    ("templates", "指令模板"),
+    ("recent", "最近任务"),
+    ("show", "显示主窗口"),
+    ("hide", "隐藏到后台"),
+    ("widget", "显示任务小窗"),
+    ("diagnostics", "诊断"),
+    ("quit", "退出 DeskJarvis"),
+];
+
+/// Agent 运行状态（托盘图标、提示与 "当前任务" 菜单项据此刷新）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum AgentStatus {
+    /// 空闲
+    Idle,
+    /// 正在执行任务
+    Running { instruction: String },
+    /// 上一个任务失败
+    Error { message: String },
+    /// Python 服务未运行
+    ServerDown,
+}
+
+impl AgentStatus {
+    /// 角标颜色，None 表示使用原始图标
+    fn badge_color(&self) -> Option<[u8; 3]> {
+        match self {
+            AgentStatus::Idle => None,
+            AgentStatus::Running { .. } => Some([0x3b, 0x82, 0xf6]),
+            AgentStatus::Error { .. } => Some([0xef, 0x44, 0x44]),
+            AgentStatus::ServerDown => Some([0x9c, 0xa3, 0xaf]),
+        }
+    }
+
+    fn tooltip(&self) -> String {
+        match self {
+            AgentStatus::Idle => format!("{}（空闲）", TOOLTIP_PREFIX),
+            AgentStatus::Running { instruction } => {
+                format!("{}\n正在执行：{}", TOOLTIP_PREFIX, preview(instruction))
+            }
+            AgentStatus::Error { message } => {
+                format!("{}\n上次任务失败：{}", TOOLTIP_PREFIX, preview(message))
+            }
+            AgentStatus::ServerDown => format!("{}\nPython 服务未运行", TOOLTIP_PREFIX),
+        }
+    }
+
+    fn menu_text(&self) -> String {
+        match self {
+            AgentStatus::Running { instruction } => i18n::tr(&format!("当前任务：{}", preview(instruction))),
+            _ => i18n::tr("当前任务：空闲"),
+        }
+    }
+}
+
+/// 托盘句柄与当前状态（通过 .manage() 注入）
+struct TrayState {
+    status: std::sync::Mutex<AgentStatus>,
+    current_task_item: MenuItem<Wry>,
+    next_schedule_item: MenuItem<Wry>,
+    observer_item: CheckMenuItem<Wry>,
+    focus_item: MenuItem<Wry>,
+    templates_menu: Submenu<Wry>,
+    recent_menu: Submenu<Wry>,
+    menu: Menu<Wry>,
+    /// 最近一次设置的 "下一个定时任务"，切换语言时重新生成菜单项文本
+    next_schedule: std::sync::Mutex<Option<String>>,
+    base_icon: Image<'static>,
+}
+
+/// 截断为托盘可显示的预览文本
+fn preview(text: &str) -> String {
+    let text = text.trim().replace('\n', " ");
+    if text.chars().count() > PREVIEW_CHARS {
+        let cut: String = text.chars().take(PREVIEW_CHARS).collect();
+        format!("{}…", cut)
+    } else {
+        text
+    }
+}
+
+/// "下一个定时任务" 菜单项文本
+fn next_schedule_text(next: Option<&str>) -> String {
+    match next {
+        Some(next) => i18n::tr(&format!("下一个定时任务：{}", preview(next))),
+        None => i18n::tr("下一个定时任务：无"),
+    }
+}
+
+/// "专注" 菜单项文本
+fn focus_text(ends_at: Option<&str>) -> String {
+    let text = match ends_at {
+        Some(ends_at) => format!("结束专注（{} 结束）", ends_at),
+        None => format!("专注 {} 分钟", FOCUS_MINUTES),
+    };
+    i18n::tr(&text)
+}
+
+/// 托盘提示：观察模式、专注时段下附加醒目说明
+fn tooltip_text(status: &AgentStatus) -> String {
+    let mut text = i18n::tr(&status.tooltip());
+    if crate::observer::is_enabled() {
+        text = format!("{}\n{}", text, i18n::tr(OBSERVER_TOOLTIP));
+    }
+    if let Some(session) = crate::focus::current() {
+        let focus = format!(
+            "🎯 专注中，{} 结束，定时任务与通知已暂停",
+            crate::focus::ends_at_text(session.ends_at)
+        );
+        text = format!("{}\n{}", text, i18n::tr(&focus));
+    }
+    text
+}
+
+/// 在图标右下角绘制状态角标
+fn badge_icon(base: &Image<'_>, color: [u8; 3]) -> Image<'static> {
+    let (width, height) = (base.width() as i64, base.height() as i64);
+    let mut rgba = base.rgba().to_vec();
+
+    let radius = width.min(height) / 5;
+    let (cx, cy) = (width - radius - 1, height - radius - 1);
+    let border = (radius / 4).max(1);
+
+    for y in (cy - radius).max(0)..height {
+        for x in (cx - radius).max(0)..width {
+            let (dx, dy) = (x - cx, y - cy);
+            let dist2 = dx * dx + dy * dy;
+            if dist2 > radius * radius {
+                continue;
+            }
+            let fill = if dist2 > (radius - border) * (radius - border) {
+                [0xff, 0xff, 0xff]
+            } else {
+                color
+            };
+            let i = ((y * width + x) * 4) as usize;
+            rgba[i..i + 3].copy_from_slice(&fill);
+            rgba[i + 3] = 0xff;
+        }
+    }
+
+    Image::new_owned(rgba, base.width(), base.height())
+}
+
+/// 固定菜单项的当前语言文字
+fn label(id: &str) -> String {
+    let text = MENU_LABELS.iter().find(|(key, _)| *key == id).map_or(id, |(_, text)| text);
+    i18n::tr(text)
+}
+
+/// 子菜单为空时的占位菜单项
+fn placeholder_item(app: &AppHandle, id: &str, text: &str) -> tauri::Result<MenuItem<Wry>> {
+    MenuItemBuilder::new(i18n::tr(text)).id(id).enabled(false).build(app)
+}
+
+/// 用 (菜单项 ID, 文本) 重建子菜单，没有条目时显示占位项
+fn fill_submenu(app: &AppHandle, menu: &Submenu<Wry>, entries: &[(String, String)], empty: (&str, &str)) {
+    while let Ok(Some(_)) = menu.remove_at(0) {}
+    let items = if entries.is_empty() {
+        placeholder_item(app, empty.0, empty.1).map(|item| vec![item])
+    } else {
+        entries
+            .iter()
+            .map(|(id, text)| MenuItemBuilder::new(text).id(id).build(app))
+            .collect()
+    };
+    match items {
+        Ok(items) => {
+            for item in &items {
+                let _ = menu.append(item);
+            }
+        }
+        Err(e) => eprintln!("[Tauri] ⚠️ 创建托盘菜单项失败: {}", e),
+    }
+}
+
+/// 重新执行历史任务的指令
+fn rerun_task(app: &AppHandle, task_id: &str) {
+    let state = app.state::<crate::AppState>();
+    let Some(record) = state.history.get(task_id) else {
+        eprintln!("[Tauri] ⚠️ 未找到任务记录: {}", task_id);
+        return;
+    };
+    let Some(window) = crate::window_manager::main_window(app) else {
+        eprintln!("[Tauri] ⚠️ 主窗口不存在，无法重新执行任务");
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        eprintln!("[Tauri] 🔁 从托盘重新执行: {}", preview(&record.instruction));
+        let context = serde_json::json!({ "rerun_of": record.id });
+        let state = app.state::<crate::AppState>();
+        if let Err(e) = crate::execute_task(window, state, record.instruction, Some(context), None, None, None).await {
+            eprintln!("[Tauri] ⚠️ 重新执行失败: {}", String::from(e));
+        }
+    });
+}
+
+/// 显示并聚焦主窗口
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// 创建系统托盘
+pub fn setup_tray(app: &App) -> tauri::Result<()> {
+    let current_task_item = MenuItemBuilder::new(AgentStatus::Idle.menu_text())
+        .id("current_task")
+        .enabled(false)
+        .build(app)?;
+    let next_schedule_item = MenuItemBuilder::new(next_schedule_text(None))
+        .id("next_schedule")
+        .enabled(false)
+        .build(app)?;
+    let observer_item = CheckMenuItemBuilder::new(label("observer_mode"))
+        .id("observer_mode")
+        .checked(false)
+        .build(app)?;
+    let focus_item = MenuItemBuilder::new(focus_text(None))
+        .id("focus")
+        .build(app)?;
+    let templates_menu = SubmenuBuilder::with_id(app, "templates", label("templates"))
+        .item(&placeholder_item(app.handle(), "templates_empty", "暂无固定的模板")?)
+        .build()?;
+    let recent_menu = SubmenuBuilder::with_id(app, "recent", label("recent"))
+        .item(&placeholder_item(app.handle(), "recent_empty", "暂无任务")?)
+        .build()?;
+    let show_item = MenuItemBuilder::new(label("show"))
+        .id("show")
+        .build(app)?;
+    let hide_item = MenuItemBuilder::new(label("hide"))
+        .id("hide")
+        .build(app)?;
+    let widget_item = MenuItemBuilder::new(label("widget"))
+        .id("widget")
+        .build(app)?;
+    let diagnostics_item = MenuItemBuilder::new(label("diagnostics"))
+        .id("diagnostics")
+        .build(app)?;
+    let quit_item = MenuItemBuilder::new(label("quit"))
+        .id("quit")
+        .build(app)?;
+
+    let menu = MenuBuilder::new(app)
+        .item(&current_task_item)
+        .item(&next_schedule_item)
+        .separator()
+        .item(&observer_item)
+        .item(&focus_item)
+        .item(&templates_menu)
+        .item(&recent_menu)
+        .separator()
+        .item(&show_item)
+        .item(&hide_item)
+        .item(&widget_item)
+        .item(&diagnostics_item)
+        .separator()
+        .item(&quit_item)
+        .build()?;
+
+    let base_icon = app.default_window_icon().unwrap().clone().to_owned();
+
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
+        .icon(base_icon.clone())
+        .menu(&menu)
+        .tooltip(tooltip_text(&AgentStatus::Idle))
+        .on_menu_event(|app, event| match event.id().as_ref() {
+            "current_task" | "show" => show_main_window(app),
+            "observer_mode" => {
+                crate::observer::set_enabled(app, !crate::observer::is_enabled());
+            }
+            "focus" => {
+                if crate::focus::is_active() {
+                    crate::focus::end(app);
+                } else if let Err(e) = crate::focus::start(app, FOCUS_MINUTES, true) {
+                    eprintln!("[Tauri] ⚠️ {}", e);
+                }
+            }
+            "hide" => {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.hide();
+                }
+            }
+            "widget" => {
+                if let Err(e) = crate::widget::show(app) {
+                    eprintln!("[Tauri] ⚠️ {}", e);
+                }
+            }
+            "diagnostics" => {
+                // 前端收到后调用 run_diagnostics 并显示检查清单
+                show_main_window(app);
+                let _ = app.emit_to("main", "open-diagnostics", ());
+            }
+            "quit" => {
+                app.exit(0);
+            }
+            id => {
+                if let Some(template_id) = id.strip_prefix(TEMPLATE_ITEM_PREFIX) {
+                    crate::templates::run_from_tray(app, template_id);
+                } else if let Some(task_id) = id.strip_prefix(RECENT_ITEM_PREFIX) {
+                    rerun_task(app, task_id);
+                }
+            }
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                let app = tray.app_handle();
+                if let Some(window) = app.get_webview_window("main") {
+                    if window.is_visible().unwrap_or(false) {
+                        let _ = window.hide();
+                    } else {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                    }
+                }
+            }
+        })
+        .build(app)?;
+
+    app.manage(TrayState {
+        status: std::sync::Mutex::new(AgentStatus::Idle),
+        current_task_item,
+        next_schedule_item,
+        observer_item,
+        focus_item,
+        templates_menu,
+        recent_menu,
+        menu,
+        next_schedule: std::sync::Mutex::new(None),
+        base_icon,
+    });
+
+    Ok(())
+}
+
+/// 当前托盘状态
+pub fn current_status(app: &AppHandle) -> Option<AgentStatus> {
+    let state = app.try_state::<TrayState>()?;
+    let status = state.status.lock().ok()?.clone();
+    Some(status)
+}
+
+/// 更新 Agent 状态：刷新托盘图标、提示、"当前任务" 菜单项，并通知前端
+pub fn set_status(app: &AppHandle, status: AgentStatus) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+
+    {
+        let Ok(mut current) = state.status.lock() else {
+            return;
+        };
+        if *current == status {
+            return;
+        }
+        *current = status.clone();
+    }
+    if matches!(status, AgentStatus::Running { .. }) {
+        crate::widget::clear_progress();
+    }
+
+    let _ = state.current_task_item.set_text(status.menu_text());
+    let _ = state
+        .current_task_item
+        .set_enabled(matches!(status, AgentStatus::Running { .. }));
+
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let icon = match status.badge_color() {
+            Some(color) => badge_icon(&state.base_icon, color),
+            None => state.base_icon.clone(),
+        };
+        let _ = tray.set_icon(Some(icon));
+        let _ = tray.set_tooltip(Some(tooltip_text(&status)));
+    }
+
+    let _ = app.emit("agent-status", &status);
+}
+
+/// 更新 "下一个定时任务" 菜单项（如 "10-16 09:00 整理收件箱"）
+pub fn set_next_schedule(app: &AppHandle, next: Option<&str>) {
+    if let Some(state) = app.try_state::<TrayState>() {
+        let _ = state.next_schedule_item.set_text(next_schedule_text(next));
+        if let Ok(mut current) = state.next_schedule.lock() {
+            *current = next.map(str::to_string);
+        }
+    }
+}
+
+/// 刷新观察模式的菜单勾选状态与托盘提示
+pub fn set_observer_mode(app: &AppHandle, enabled: bool) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    let _ = state.observer_item.set_checked(enabled);
+    let status = state.status.lock().map(|s| s.clone()).unwrap_or(AgentStatus::Idle);
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(tooltip_text(&status)));
+    }
+}
+
+/// 刷新专注菜单项与托盘提示，ends_at 为结束时刻文本，未在专注时为 None
+pub fn set_focus_mode(app: &AppHandle, ends_at: Option<&str>) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    let _ = state.focus_item.set_text(focus_text(ends_at));
+    let status = state.status.lock().map(|s| s.clone()).unwrap_or(AgentStatus::Idle);
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(tooltip_text(&status)));
+    }
+}
+
+/// 重建 "指令模板" 子菜单，templates 为固定模板的 (ID, 名称)
+pub fn set_pinned_templates(app: &AppHandle, templates: &[(String, String)]) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    let entries: Vec<(String, String)> = templates
+        .iter()
+        .map(|(id, name)| (format!("{}{}", TEMPLATE_ITEM_PREFIX, id), preview(name)))
+        .collect();
+    fill_submenu(app, &state.templates_menu, &entries, ("templates_empty", "暂无固定的模板"));
+}
+
+/// 按任务历史重建 "最近任务" 子菜单（任务结束、删除历史后调用）
+pub fn refresh_recent_tasks(app: &AppHandle) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    let mut records = app.state::<crate::AppState>().history.list(usize::MAX, false);
+    records.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+    let mut seen = std::collections::HashSet::new();
+    let entries: Vec<(String, String)> = records
+        .into_iter()
+        .filter(|r| r.finished_at.is_some() && seen.insert(r.instruction.trim().to_string()))
+        .take(RECENT_TASKS)
+        .map(|r| {
+            let mark = if r.success == Some(true) { "✅" } else { "❌" };
+            (format!("{}{}", RECENT_ITEM_PREFIX, r.id), format!("{} {}", mark, preview(&r.instruction)))
+        })
+        .collect();
+    fill_submenu(app, &state.recent_menu, &entries, ("recent_empty", "暂无任务"));
+}
+
+/// 切换界面语言后刷新托盘菜单文字与提示
+pub fn refresh_locale(app: &AppHandle) {
+    let Some(state) = app.try_state::<TrayState>() else {
+        return;
+    };
+    for item in state.menu.items().unwrap_or_default() {
+        let id = item.id().as_ref();
+        if !MENU_LABELS.iter().any(|(key, _)| *key == id) {
+            continue;
+        }
+        let text = label(id);
+        let _ = match (item.as_menuitem(), item.as_check_menuitem(), item.as_submenu()) {
+            (Some(item), _, _) => item.set_text(text),
+            (_, Some(item), _) => item.set_text(text),
+            (_, _, Some(menu)) => menu.set_text(text),
+            _ => Ok(()),
+        };
+    }
+
+    let status = state.status.lock().map(|s| s.clone()).unwrap_or(AgentStatus::Idle);
+    let _ = state.current_task_item.set_text(status.menu_text());
+    let next = state.next_schedule.lock().ok().and_then(|n| n.clone());
+    let _ = state.next_schedule_item.set_text(next_schedule_text(next.as_deref()));
+    let ends_at = crate::focus::current().map(|s| crate::focus::ends_at_text(s.ends_at));
+    let _ = state.focus_item.set_text(focus_text(ends_at.as_deref()));
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(tooltip_text(&status)));
+    }
+    // 子菜单的占位项随重建更新
+    crate::templates::init_tray(app);
+    refresh_recent_tasks(app);
+}
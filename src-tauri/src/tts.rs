@@ -0,0 +1,146 @@
+//! 朗读：调用系统自带的语音合成朗读文字
+//!
+//! macOS 使用 say，Windows 通过 PowerShell 调用 SAPI（System.Speech），
+//! Linux 使用 speech-dispatcher 的 spd-say（没有时退回 espeak）。同一时间只朗读一段，
+//! 新的朗读会打断上一段。配置 speak_results 后，任务结束时主窗口不在前台（隐藏、最小化
+//! 或切到了其他应用）则朗读结果消息。
+
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+
+use tauri::AppHandle;
+
+use crate::config;
+use crate::window_manager;
+use crate::TaskResult;
+
+/// 单次朗读的最大字符数
+const MAX_SPEAK_CHARS: usize = 500;
+
+/// 正在朗读的进程
+static SPEAKING: Mutex<Option<Child>> = Mutex::new(None);
+
+/// 去掉 Markdown 标记符号、合并空白并截断，避免读出符号
+fn speakable(text: &str) -> String {
+    let cleaned: String = text
+        .chars()
+        .map(|c| if matches!(c, '#' | '*' | '`' | '>' | '|' | '_' | '~') { ' ' } else { c })
+        .collect();
+    cleaned
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(MAX_SPEAK_CHARS)
+        .collect()
+}
+
+/// 启动系统语音合成进程，文字通过参数或 stdin 传入
+fn spawn_speech(text: &str) -> Result<Child, String> {
+    #[cfg(target_os = "macos")]
+    let child = Command::new("say")
+        .arg("--")
+        .arg(text)
+        .stdin(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("启动 say 失败: {}", e))?;
+
+    #[cfg(windows)]
+    let child = {
+        use std::io::Write;
+        use std::os::windows::process::CommandExt;
+        const SCRIPT: &str = "Add-Type -AssemblyName System.Speech; \
+            [Console]::InputEncoding = [Text.Encoding]::UTF8; \
+            (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak([Console]::In.ReadToEnd())";
+        let mut child = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+            .stdin(Stdio::piped())
+            .creation_flags(crate::CREATE_NO_WINDOW)
+            .spawn()
+            .map_err(|e| format!("启动 PowerShell 语音合成失败: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .map_err(|e| format!("写入朗读文字失败: {}", e))?;
+        }
+        child
+    };
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let child = Command::new("spd-say")
+        .args(["--wait", "--"])
+        .arg(text)
+        .stdin(Stdio::null())
+        .spawn()
+        .or_else(|_| {
+            use std::io::Write;
+            let mut child = Command::new("espeak").stdin(Stdio::piped()).spawn()?;
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(text.as_bytes())?;
+            }
+            Ok(child)
+        })
+        .map_err(|e: std::io::Error| format!("未找到 spd-say 或 espeak: {}", e))?;
+
+    Ok(child)
+}
+
+/// 停止当前朗读
+fn stop() {
+    if let Ok(mut speaking) = SPEAKING.lock() {
+        if let Some(mut child) = speaking.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+            // spd-say 只是客户端，结束进程不会打断 speech-dispatcher 中的朗读
+            #[cfg(all(unix, not(target_os = "macos")))]
+            let _ = Command::new("spd-say").arg("--cancel").status();
+        }
+    }
+}
+
+/// 朗读文字，打断正在进行的朗读
+pub fn speak_text(text: &str) -> Result<(), String> {
+    let text = speakable(text);
+    if text.is_empty() {
+        return Ok(());
+    }
+    stop();
+    let child = spawn_speech(&text)?;
+    if let Ok(mut speaking) = SPEAKING.lock() {
+        *speaking = Some(child);
+    }
+    Ok(())
+}
+
+/// 任务结束时按 speak_results 朗读结果（主窗口在前台时不读）
+pub fn announce_result(app: &AppHandle, result: &TaskResult) {
+    let enabled = config::load_config()
+        .ok()
+        .and_then(|c| c.speak_results)
+        .unwrap_or(false);
+    if !enabled {
+        return;
+    }
+    let in_front = window_manager::main_window(app)
+        .map(|w| w.is_visible().unwrap_or(false) && w.is_focused().unwrap_or(false))
+        .unwrap_or(false);
+    if in_front {
+        return;
+    }
+    if let Err(e) = speak_text(&result.message) {
+        eprintln!("[Tauri] ⚠️ 朗读任务结果失败: {}", e);
+    }
+}
+
+/// 用系统语音朗读文字
+#[tauri::command]
+pub async fn speak(text: String) -> Result<(), String> {
+    speak_text(&text)
+}
+
+/// 停止朗读
+#[tauri::command]
+pub async fn stop_speaking() -> Result<(), String> {
+    stop();
+    Ok(())
+}
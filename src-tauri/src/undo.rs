@@ -0,0 +1,392 @@
+//! 撤销任务的文件修改：按任务记录写入、移动、删除，undo_task 按相反顺序恢复
+//!
+//! 日志和旧版本保存在 sandbox/.trash/<task_id>/（journal.json 与编号的旧文件）。覆盖或追加
+//! 已有文件前复制旧内容，删除时把文件移入该目录而不是直接删除。记录来自 Rust 侧的 fs.* 工具
+//! （见 fs_tools）和 Python 文件管理器获准的写入（见 file_guard）；shell 命令等其他方式的修改不会记录。
+//! 撤销时原位置已被占用的项跳过并保留在日志中，其余恢复后从日志移除，全部恢复后删除该目录。
+//!
+//! 该目录同时是 Agent 删除文件的回收区：fs.delete 和 Python 文件管理器获准的删除都移入这里
+//! 并记入审计日志，list_trashed_files 列出、restore_trashed_file 单独放回。
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::history::now_millis;
+use crate::{audit, sandbox};
+
+/// 沙盒下保存日志和旧版本的目录名
+pub const TRASH_DIR: &str = ".trash";
+
+/// 日志文件名
+const JOURNAL_FILE: &str = "journal.json";
+
+/// 读写日志与保存旧版本时加锁（工具调用在各自的线程中执行）
+static JOURNAL_LOCK: Mutex<()> = Mutex::new(());
+
+/// 一次文件修改
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FileChange {
+    /// 新建文件，撤销时删除
+    Created { path: PathBuf },
+    /// 覆盖或追加已有文件，撤销时换回旧版本
+    Modified { path: PathBuf, backup: PathBuf },
+    /// 删除文件或目录，撤销时放回原处
+    Deleted { path: PathBuf, backup: PathBuf },
+    /// 移动，撤销时移回；覆盖了目标位置的文件时一并放回
+    Moved {
+        from: PathBuf,
+        to: PathBuf,
+        replaced: Option<PathBuf>,
+    },
+}
+
+/// 日志中的一条记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// 记录时间（毫秒时间戳）
+    pub at: u64,
+    #[serde(flatten)]
+    pub change: FileChange,
+}
+
+/// undo_task 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct UndoReport {
+    pub task_id: String,
+    /// 已恢复的修改数
+    pub restored: usize,
+    /// 未能恢复的修改及原因（仍保留在日志中，可处理后再次撤销）
+    pub skipped: Vec<String>,
+}
+
+/// 回收区中的一个文件或目录
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashedFile {
+    pub task_id: String,
+    /// 删除前的位置
+    pub original_path: PathBuf,
+    /// 在回收区中的位置，restore_trashed_file 以此指定
+    pub trashed_path: PathBuf,
+    /// 删除时间（毫秒时间戳）
+    pub deleted_at: u64,
+    pub is_dir: bool,
+    /// 文件大小，目录为 0
+    pub size: u64,
+}
+
+/// 任务的日志目录（sandbox/.trash/<task_id>），不保证已创建
+pub fn trash_dir(task_id: &str) -> Result<PathBuf, String> {
+    if !sandbox::is_valid_task_id(task_id) {
+        return Err(format!("无效的任务ID: {}", task_id));
+    }
+    Ok(sandbox::sandbox_root().join(TRASH_DIR).join(task_id))
+}
+
+fn load(dir: &Path) -> Vec<JournalEntry> {
+    std::fs::read_to_string(dir.join(JOURNAL_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(dir: &Path, entries: &[JournalEntry]) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    let content =
+        serde_json::to_string_pretty(entries).map_err(|e| format!("序列化撤销日志失败: {}", e))?;
+    std::fs::write(dir.join(JOURNAL_FILE), content).map_err(|e| format!("写入撤销日志失败: {}", e))
+}
+
+/// 日志目录中尚未使用的旧版本路径（<序号>_<文件名>）
+fn backup_path(dir: &Path, path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "file".to_string());
+    (0..)
+        .map(|n| dir.join(format!("{}_{}", n, name)))
+        .find(|p| !p.exists())
+        .unwrap_or_else(|| dir.join(name))
+}
+
+/// 复制文件或目录
+fn copy_all(src: &Path, dst: &Path) -> std::io::Result<()> {
+    if !src.is_dir() {
+        return std::fs::copy(src, dst).map(|_| ());
+    }
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        copy_all(&entry.path(), &dst.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+/// 移动文件或目录，跨磁盘时改为复制后删除
+fn move_all(src: &Path, dst: &Path) -> std::io::Result<()> {
+    if let Some(parent) = dst.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::rename(src, dst).is_ok() {
+        return Ok(());
+    }
+    copy_all(src, dst)?;
+    if src.is_dir() {
+        std::fs::remove_dir_all(src)
+    } else {
+        std::fs::remove_file(src)
+    }
+}
+
+/// 加锁后在任务的日志目录中操作
+fn with_journal<T>(task_id: &str, f: impl FnOnce(&Path) -> Result<T, String>) -> Result<T, String> {
+    let dir = trash_dir(task_id)?;
+    let _guard = JOURNAL_LOCK.lock().map_err(|_| "撤销日志不可用".to_string())?;
+    f(&dir)
+}
+
+fn append(dir: &Path, change: FileChange) -> Result<(), String> {
+    let mut entries = load(dir);
+    entries.push(JournalEntry {
+        at: now_millis(),
+        change,
+    });
+    save(dir, &entries)
+}
+
+/// 把文件或目录保存到日志目录，keep 为 true 时复制，否则移入；返回保存的路径
+fn stash(dir: &Path, path: &Path, keep: bool) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    let backup = backup_path(dir, path);
+    let result = if keep {
+        copy_all(path, &backup)
+    } else {
+        move_all(path, &backup)
+    };
+    result.map_err(|e| format!("保存旧版本失败（{}）: {}", path.display(), e))?;
+    Ok(backup)
+}
+
+/// 记录一次修改（失败时只记录错误，不影响操作本身）
+pub fn record(task_id: &str, change: FileChange) {
+    if let Err(e) = with_journal(task_id, |dir| append(dir, change)) {
+        eprintln!("[Tauri] ⚠️ {}", e);
+    }
+}
+
+/// 写入文件前调用：已有文件时复制旧内容（Modified），否则记为新建（Created）
+pub fn before_write(task_id: &str, path: &Path) {
+    if path.is_dir() {
+        return;
+    }
+    let result = with_journal(task_id, |dir| {
+        let change = if path.exists() {
+            FileChange::Modified {
+                path: path.to_path_buf(),
+                backup: stash(dir, path, true)?,
+            }
+        } else {
+            FileChange::Created {
+                path: path.to_path_buf(),
+            }
+        };
+        append(dir, change)
+    });
+    if let Err(e) = result {
+        eprintln!("[Tauri] ⚠️ {}，该修改将无法撤销", e);
+    }
+}
+
+/// 删除文件或目录：移入回收区并记录（Deleted）和审计，失败时返回错误，由调用方决定是否直接删除
+pub fn remove(task_id: &str, path: &Path) -> Result<PathBuf, String> {
+    let backup = with_journal(task_id, |dir| {
+        let backup = stash(dir, path, false)?;
+        append(
+            dir,
+            FileChange::Deleted {
+                path: path.to_path_buf(),
+                backup: backup.clone(),
+            },
+        )?;
+        Ok(backup)
+    })?;
+    let arguments = serde_json::json!({ "path": path, "trashed_path": backup });
+    if let Err(e) = audit::record_step(task_id, "trash", &arguments, true, "已移入回收区") {
+        eprintln!("[Tauri] ⚠️ {}", e);
+    }
+    Ok(backup)
+}
+
+/// 移动时将被覆盖的目标文件先移入日志目录，返回保存的路径
+pub fn set_aside(task_id: &str, path: &Path) -> Result<PathBuf, String> {
+    with_journal(task_id, |dir| stash(dir, path, false))
+}
+
+/// 删除任务的撤销日志和保存的旧版本（删除任务历史时调用）
+pub fn discard(task_id: &str) {
+    let Ok(dir) = trash_dir(task_id) else {
+        return;
+    };
+    if let Err(e) = std::fs::remove_dir_all(&dir) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            eprintln!("[Tauri] ⚠️ 删除撤销日志目录失败: {}", e);
+        }
+    }
+}
+
+/// 恢复一条修改
+fn revert(change: &FileChange) -> Result<(), String> {
+    match change {
+        FileChange::Created { path } => {
+            if path.is_file() {
+                std::fs::remove_file(path).map_err(|e| format!("删除 {} 失败: {}", path.display(), e))?;
+            }
+            Ok(())
+        }
+        FileChange::Modified { path, backup } => {
+            if !backup.exists() {
+                return Err(format!("旧版本已不存在: {}", backup.display()));
+            }
+            move_all(backup, path).map_err(|e| format!("恢复 {} 失败: {}", path.display(), e))
+        }
+        FileChange::Deleted { path, backup } => {
+            if path.exists() {
+                return Err(format!("原位置已有同名文件: {}", path.display()));
+            }
+            if !backup.exists() {
+                return Err(format!("已删除的文件不在回收区: {}", backup.display()));
+            }
+            move_all(backup, path).map_err(|e| format!("恢复 {} 失败: {}", path.display(), e))
+        }
+        FileChange::Moved { from, to, replaced } => {
+            if from.exists() {
+                return Err(format!("原位置已有同名文件: {}", from.display()));
+            }
+            if !to.exists() {
+                return Err(format!("移动后的文件已不存在: {}", to.display()));
+            }
+            move_all(to, from).map_err(|e| format!("移回 {} 失败: {}", from.display(), e))?;
+            if let Some(replaced) = replaced.as_ref().filter(|r| r.exists()) {
+                move_all(replaced, to).map_err(|e| format!("恢复 {} 失败: {}", to.display(), e))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// 撤销任务的文件修改，按相反顺序恢复
+#[tauri::command]
+pub async fn undo_task(
+    state: tauri::State<'_, crate::AppState>,
+    task_id: String,
+) -> Result<UndoReport, String> {
+    if let Some(record) = state.history.get(&task_id) {
+        if record.finished_at.is_none() {
+            return Err(format!("任务 {} 仍在执行，结束后才能撤销", task_id));
+        }
+    }
+    let dir = trash_dir(&task_id)?;
+    let _guard = JOURNAL_LOCK.lock().map_err(|_| "撤销日志不可用")?;
+    let entries = load(&dir);
+    if entries.is_empty() {
+        return Err(format!("任务 {} 没有可撤销的文件修改", task_id));
+    }
+
+    let mut restored = 0;
+    let mut skipped = Vec::new();
+    let mut remaining = Vec::new();
+    for entry in entries.into_iter().rev() {
+        match revert(&entry.change) {
+            Ok(()) => restored += 1,
+            Err(e) => {
+                skipped.push(e);
+                remaining.push(entry);
+            }
+        }
+    }
+    if remaining.is_empty() {
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            eprintln!("[Tauri] ⚠️ 删除撤销日志目录失败: {}", e);
+        }
+    } else {
+        remaining.reverse();
+        save(&dir, &remaining)?;
+    }
+    eprintln!(
+        "[Tauri] ↩️ 已撤销任务 {} 的 {} 项文件修改（{} 项未能恢复）",
+        task_id,
+        restored,
+        skipped.len()
+    );
+    Ok(UndoReport {
+        task_id,
+        restored,
+        skipped,
+    })
+}
+
+/// 列出回收区中的文件（按删除时间从新到旧）
+#[tauri::command]
+pub async fn list_trashed_files() -> Result<Vec<TrashedFile>, String> {
+    let root = sandbox::sandbox_root().join(TRASH_DIR);
+    let Ok(dirs) = std::fs::read_dir(&root) else {
+        return Ok(Vec::new());
+    };
+    let _guard = JOURNAL_LOCK.lock().map_err(|_| "撤销日志不可用")?;
+    let mut files = Vec::new();
+    for dir in dirs.flatten() {
+        let task_id = dir.file_name().to_string_lossy().to_string();
+        for entry in load(&dir.path()) {
+            let FileChange::Deleted { path, backup } = entry.change else {
+                continue;
+            };
+            let Ok(meta) = std::fs::metadata(&backup) else {
+                continue;
+            };
+            files.push(TrashedFile {
+                task_id: task_id.clone(),
+                original_path: path,
+                trashed_path: backup,
+                deleted_at: entry.at,
+                is_dir: meta.is_dir(),
+                size: if meta.is_dir() { 0 } else { meta.len() },
+            });
+        }
+    }
+    files.sort_by_key(|f| std::cmp::Reverse(f.deleted_at));
+    Ok(files)
+}
+
+/// 把回收区中的文件放回原处，返回恢复后的路径
+#[tauri::command]
+pub async fn restore_trashed_file(task_id: String, trashed_path: String) -> Result<PathBuf, String> {
+    let dir = trash_dir(&task_id)?;
+    let trashed_path = PathBuf::from(trashed_path);
+    let _guard = JOURNAL_LOCK.lock().map_err(|_| "撤销日志不可用")?;
+    let mut entries = load(&dir);
+    let (index, path) = entries
+        .iter()
+        .enumerate()
+        .find_map(|(i, e)| match &e.change {
+            FileChange::Deleted { path, backup } if *backup == trashed_path => Some((i, path.clone())),
+            _ => None,
+        })
+        .ok_or_else(|| format!("回收区中没有该文件: {}", trashed_path.display()))?;
+    revert(&entries[index].change)?;
+    entries.remove(index);
+    if entries.is_empty() {
+        if let Err(e) = std::fs::remove_dir_all(&dir) {
+            eprintln!("[Tauri] ⚠️ 删除撤销日志目录失败: {}", e);
+        }
+    } else {
+        save(&dir, &entries)?;
+    }
+    let arguments = serde_json::json!({ "path": path, "trashed_path": trashed_path });
+    if let Err(e) = audit::record_step(&task_id, "trash_restore", &arguments, true, "已从回收区恢复") {
+        eprintln!("[Tauri] ⚠️ {}", e);
+    }
+    eprintln!("[Tauri] ♻️ 已从回收区恢复: {}", path.display());
+    Ok(path)
+}
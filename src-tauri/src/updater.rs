@@ -0,0 +1,236 @@
+//! 应用内更新：按 release_channel（stable / beta）检查、下载并安装新版本
+//!
+//! 更新包由 updater 插件校验签名，公钥在 tauri.conf.json 的 plugins.updater.pubkey 中配置，
+//! 未配置时插件初始化失败，更新功能不可用。发布清单中可带 "rollout"（0~1）做分批推送：
+//! 按本机安装 ID 的哈希分桶，未轮到的安装视为暂无更新。
+//! 安装前先关闭 Python 服务，安装完成后重启应用（Windows 上由安装程序负责退出并重启）。
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Url};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::config;
+use crate::features;
+use crate::history::now_millis;
+
+/// 各渠道的发布清单地址
+const STABLE_ENDPOINT: &str =
+    "https://github.com/jinwandalaohu940-netizen/DeskJarvis/releases/latest/download/latest.json";
+const BETA_ENDPOINT: &str =
+    "https://github.com/jinwandalaohu940-netizen/DeskJarvis/releases/download/beta/latest.json";
+
+/// 下载进度事件的最小间隔（字节）
+const PROGRESS_STEP_BYTES: usize = 256 * 1024;
+
+/// 已检查到、尚未下载的更新
+static PENDING: Mutex<Option<Update>> = Mutex::new(None);
+
+/// 已下载并校验的更新包
+static DOWNLOADED: Mutex<Option<(Update, Vec<u8>)>> = Mutex::new(None);
+
+/// 发布渠道
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+impl ReleaseChannel {
+    fn endpoint(self) -> &'static str {
+        match self {
+            ReleaseChannel::Stable => STABLE_ENDPOINT,
+            ReleaseChannel::Beta => BETA_ENDPOINT,
+        }
+    }
+}
+
+/// check_for_updates 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub channel: ReleaseChannel,
+    pub current_version: String,
+    /// 有可安装的新版本
+    pub available: bool,
+    /// 新版本号（分批推送未轮到时也会给出）
+    pub version: Option<String>,
+    /// 发布时间（毫秒时间戳）
+    pub date: Option<u64>,
+    pub notes: Option<String>,
+    /// 新版本正在分批推送且本机尚未轮到
+    pub rollout_pending: bool,
+}
+
+/// update-download-progress 事件负载
+#[derive(Debug, Clone, Serialize)]
+struct DownloadProgress {
+    version: String,
+    downloaded: u64,
+    /// 总大小未知时为 None
+    total: Option<u64>,
+}
+
+fn ensure_available() -> Result<(), String> {
+    if features::is_available("updater") {
+        Ok(())
+    } else {
+        Err("更新功能不可用（未配置更新公钥或插件初始化失败）".to_string())
+    }
+}
+
+/// 本机在分批推送中的位置（0~1），由持久化的安装 ID 决定
+fn rollout_bucket() -> f64 {
+    let id = config::get_data_dir()
+        .map(|dir| dir.join("install_id"))
+        .map(|path| match std::fs::read_to_string(&path) {
+            Ok(id) if !id.trim().is_empty() => id.trim().to_string(),
+            _ => {
+                let id = format!("{:x}", Sha256::digest(format!("{}-{}", now_millis(), std::process::id())));
+                let _ = std::fs::write(&path, &id);
+                id
+            }
+        })
+        .unwrap_or_default();
+    let digest = Sha256::digest(id.as_bytes());
+    u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) as f64 / u32::MAX as f64
+}
+
+/// 检查当前渠道是否有新版本
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<UpdateInfo, String> {
+    ensure_available()?;
+    let config = config::load_config()?;
+    let channel = config.release_channel.unwrap_or_default();
+    let endpoint = Url::parse(channel.endpoint()).map_err(|e| format!("无效的更新地址: {}", e))?;
+    let mut builder = app
+        .updater_builder()
+        .endpoints(vec![endpoint])
+        .map_err(|e| format!("配置更新地址失败: {}", e))?;
+    if let Some(proxy) = config
+        .proxy_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .and_then(|p| Url::parse(p).ok())
+    {
+        builder = builder.proxy(proxy);
+    }
+    let update = builder
+        .build()
+        .map_err(|e| format!("初始化更新检查失败: {}", e))?
+        .check()
+        .await
+        .map_err(|e| format!("检查更新失败: {}", e))?;
+
+    let current_version = app.package_info().version.to_string();
+    let Some(update) = update else {
+        return Ok(UpdateInfo {
+            channel,
+            current_version,
+            available: false,
+            version: None,
+            date: None,
+            notes: None,
+            rollout_pending: false,
+        });
+    };
+
+    let rollout = update.raw_json.get("rollout").and_then(|r| r.as_f64());
+    let rollout_pending = rollout.is_some_and(|r| rollout_bucket() >= r.clamp(0.0, 1.0));
+    let info = UpdateInfo {
+        channel,
+        current_version,
+        available: !rollout_pending,
+        version: Some(update.version.clone()),
+        date: update
+            .date
+            .map(|d| (d.unix_timestamp().max(0) as u64) * 1000),
+        notes: update.body.clone(),
+        rollout_pending,
+    };
+    eprintln!(
+        "[Tauri] ⬆️ 发现新版本 {}（{:?} 渠道{}）",
+        update.version,
+        channel,
+        if rollout_pending { "，分批推送尚未轮到本机" } else { "" }
+    );
+    if let Ok(mut pending) = PENDING.lock() {
+        *pending = (!rollout_pending).then_some(update);
+    }
+    Ok(info)
+}
+
+/// 下载已检查到的更新并校验签名，过程中发出 update-download-progress 事件，完成后发出 update-downloaded
+#[tauri::command]
+pub async fn download_update(app: AppHandle) -> Result<String, String> {
+    ensure_available()?;
+    let update = PENDING
+        .lock()
+        .map_err(|_| "更新状态不可用")?
+        .clone()
+        .ok_or("没有可下载的更新，请先检查更新")?;
+    let version = update.version.clone();
+
+    let mut downloaded: u64 = 0;
+    let mut since_last = 0usize;
+    let bytes = update
+        .download(
+            |chunk, total| {
+                downloaded += chunk as u64;
+                since_last += chunk;
+                if since_last >= PROGRESS_STEP_BYTES || total == Some(downloaded) {
+                    since_last = 0;
+                    let _ = app.emit(
+                        "update-download-progress",
+                        DownloadProgress {
+                            version: version.clone(),
+                            downloaded,
+                            total,
+                        },
+                    );
+                }
+            },
+            || {},
+        )
+        .await
+        .map_err(|e| format!("下载更新失败: {}", e))?;
+
+    eprintln!("[Tauri] ⬆️ 新版本 {} 已下载（{} 字节）", version, bytes.len());
+    if let Ok(mut slot) = DOWNLOADED.lock() {
+        *slot = Some((update, bytes));
+    }
+    let _ = app.emit("update-downloaded", &version);
+    Ok(version)
+}
+
+/// 安装已下载的更新并重启应用：先关闭 Python 服务，执行任务期间不能安装
+#[tauri::command]
+pub async fn install_update_and_restart(
+    app: AppHandle,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<(), String> {
+    ensure_available()?;
+    if state.running.any() {
+        return Err("有任务正在执行，请等待任务结束后再安装更新".to_string());
+    }
+    let (update, bytes) = DOWNLOADED
+        .lock()
+        .map_err(|_| "更新状态不可用")?
+        .take()
+        .ok_or("没有已下载的更新，请先下载")?;
+
+    eprintln!("[Tauri] ⬆️ 正在安装新版本 {}，关闭 Python 服务...", update.version);
+    crate::shutdown_python_servers(&state).await;
+    if let Err(e) = update.install(&bytes) {
+        // 安装失败时保留下载的更新包，服务在下次任务时按需重新启动
+        if let Ok(mut slot) = DOWNLOADED.lock() {
+            *slot = Some((update, bytes));
+        }
+        return Err(format!("安装更新失败: {}", e));
+    }
+    app.restart()
+}
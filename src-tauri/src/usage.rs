@@ -0,0 +1,224 @@
+//! 模型用量统计：按任务和按天累计 token 数与估算费用
+//!
+//! CostTracker 每收到一条 usage 事件就记入当前任务（重试的多次执行累计到同一任务），
+//! 任务结束时 finish_task 取出本任务的用量写入任务历史，并累加到
+//! ~/.deskjarvis/usage_daily.json 的当日汇总，再以 usage-updated 事件发出当日统计。
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use chrono::{Duration, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::event_sink::EventSink;
+use crate::metrics;
+
+/// 按天汇总最多保留的天数
+const MAX_DAYS: usize = 400;
+
+/// 执行中任务的用量（任务 ID → 用量）
+static RUNNING: Mutex<BTreeMap<String, Usage>> = Mutex::new(BTreeMap::new());
+
+/// 按天汇总文件的读写锁，避免并发任务同时结束时互相覆盖
+static DAILY_LOCK: Mutex<()> = Mutex::new(());
+
+/// 单个模型的用量
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelUsage {
+    pub calls: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// 用量合计及按模型的明细
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Usage {
+    pub calls: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    #[serde(default)]
+    pub models: BTreeMap<String, ModelUsage>,
+}
+
+impl Usage {
+    fn add_call(&mut self, model: &str, input_tokens: u64, output_tokens: u64, cost_usd: f64) {
+        let call = ModelUsage {
+            calls: 1,
+            input_tokens,
+            output_tokens,
+            cost_usd,
+        };
+        self.add_model(model, &call);
+    }
+
+    fn add_model(&mut self, model: &str, usage: &ModelUsage) {
+        self.calls += usage.calls;
+        self.input_tokens += usage.input_tokens;
+        self.output_tokens += usage.output_tokens;
+        self.cost_usd += usage.cost_usd;
+        let entry = self.models.entry(model.to_string()).or_default();
+        entry.calls += usage.calls;
+        entry.input_tokens += usage.input_tokens;
+        entry.output_tokens += usage.output_tokens;
+        entry.cost_usd += usage.cost_usd;
+    }
+
+    fn merge(&mut self, other: &Usage) {
+        for (model, usage) in &other.models {
+            self.add_model(model, usage);
+        }
+    }
+}
+
+/// 一天的用量
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DayUsage {
+    /// 有模型调用的任务数
+    pub tasks: u64,
+    #[serde(flatten)]
+    pub usage: Usage,
+}
+
+/// 统计区间
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsagePeriod {
+    #[default]
+    Today,
+    /// 最近 7 天（含今天）
+    Week,
+    /// 最近 30 天（含今天）
+    Month,
+    All,
+}
+
+/// 统计区间内某一天的用量
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyUsage {
+    /// 本地日期 YYYY-MM-DD
+    pub date: String,
+    #[serde(flatten)]
+    pub day: DayUsage,
+}
+
+/// get_usage_stats 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageStats {
+    pub period: UsagePeriod,
+    /// 区间起始日期，All 时为 None
+    pub since: Option<String>,
+    pub tasks: u64,
+    #[serde(flatten)]
+    pub usage: Usage,
+    /// 按天明细（日期升序）
+    pub days: Vec<DailyUsage>,
+}
+
+/// 记一次模型调用到任务用量
+pub fn record(request_id: &str, model: &str, input_tokens: u64, output_tokens: u64, cost_usd: f64) {
+    metrics::record_tokens(model, input_tokens, output_tokens, cost_usd);
+    if let Ok(mut running) = RUNNING.lock() {
+        running
+            .entry(request_id.to_string())
+            .or_default()
+            .add_call(model, input_tokens, output_tokens, cost_usd);
+    }
+}
+
+fn daily_path() -> Result<std::path::PathBuf, String> {
+    Ok(config::get_data_dir()?.join("usage_daily.json"))
+}
+
+/// 读取按天汇总（日期 → 用量），文件不存在或损坏时为空
+fn load_daily() -> BTreeMap<String, DayUsage> {
+    daily_path()
+        .ok()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_daily(daily: &BTreeMap<String, DayUsage>) -> Result<(), String> {
+    let path = daily_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(daily).map_err(|e| format!("序列化用量统计失败: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("写入用量统计失败: {}", e))
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// 任务结束：取出本任务的用量并累加到当日汇总，发出 usage-updated 事件
+///
+/// 没有模型调用的任务返回 None。
+pub fn finish(sink: &impl EventSink, request_id: &str) -> Option<Usage> {
+    let usage = RUNNING.lock().ok()?.remove(request_id)?;
+    {
+        let _guard = DAILY_LOCK.lock();
+        let mut daily = load_daily();
+        let day = daily.entry(today()).or_default();
+        day.tasks += 1;
+        day.usage.merge(&usage);
+        while daily.len() > MAX_DAYS {
+            daily.pop_first();
+        }
+        if let Err(e) = save_daily(&daily) {
+            eprintln!("[Tauri] ⚠️ {}", e);
+        }
+    }
+    sink.send("usage-updated", stats(UsagePeriod::Today));
+    Some(usage)
+}
+
+/// 今日估算费用：已结束任务的当日汇总加上执行中任务的累计
+pub fn today_cost_usd() -> f64 {
+    let running: f64 = RUNNING
+        .lock()
+        .map(|r| r.values().map(|u| u.cost_usd).sum())
+        .unwrap_or_default();
+    stats(UsagePeriod::Today).usage.cost_usd + running
+}
+
+/// 统计区间内的用量
+pub fn stats(period: UsagePeriod) -> UsageStats {
+    let since = match period {
+        UsagePeriod::Today => Some(0),
+        UsagePeriod::Week => Some(6),
+        UsagePeriod::Month => Some(29),
+        UsagePeriod::All => None,
+    }
+    .map(|days| (Local::now() - Duration::days(days)).format("%Y-%m-%d").to_string());
+
+    let daily = {
+        let _guard = DAILY_LOCK.lock();
+        load_daily()
+    };
+    let mut stats = UsageStats {
+        period,
+        since: since.clone(),
+        tasks: 0,
+        usage: Usage::default(),
+        days: Vec::new(),
+    };
+    for (date, day) in daily {
+        if since.as_ref().is_some_and(|s| date < *s) {
+            continue;
+        }
+        stats.tasks += day.tasks;
+        stats.usage.merge(&day.usage);
+        stats.days.push(DailyUsage { date, day });
+    }
+    stats
+}
+
+/// 获取模型用量统计：period 为 today / week / month / all，默认 today
+#[tauri::command]
+pub async fn get_usage_stats(period: Option<UsagePeriod>) -> Result<UsageStats, String> {
+    Ok(stats(period.unwrap_or_default()))
+}
@@ -0,0 +1,240 @@
+//! 配置校验：保存前检查各字段，并可选地通过 Python 服务测试提供商连通性
+
+use serde::Serialize;
+
+use crate::config::AppConfig;
+use crate::error::DeskJarvisError;
+use crate::history::now_millis;
+use crate::{local_models, sandbox};
+
+/// 连通性测试超时
+const CONNECTION_TEST_TIMEOUT_SECS: u64 = 20;
+
+/// Python Agent 支持的提供商及其模型名前缀
+const PROVIDER_MODEL_PREFIXES: &[(&str, &[&str])] = &[
+    ("claude", &["claude"]),
+    ("openai", &["gpt", "o1", "o3", "o4", "chatgpt"]),
+    ("chatgpt", &["gpt", "o1", "o3", "o4", "chatgpt"]),
+    ("deepseek", &["deepseek"]),
+    ("grok", &["grok"]),
+];
+
+/// 单个字段的问题
+#[derive(Debug, Serialize)]
+pub struct FieldIssue {
+    field: String,
+    message: String,
+}
+
+/// 提供商连通性测试结果
+#[derive(Debug, Serialize)]
+pub struct ConnectionCheck {
+    ok: bool,
+    message: String,
+    latency_ms: u64,
+}
+
+/// 校验报告
+#[derive(Debug, Default, Serialize)]
+pub struct ValidationReport {
+    valid: bool,
+    errors: Vec<FieldIssue>,
+    warnings: Vec<FieldIssue>,
+    /// 仅在请求连通性测试且本地校验通过时存在
+    connection: Option<ConnectionCheck>,
+}
+
+impl ValidationReport {
+    fn error(&mut self, field: &str, message: impl Into<String>) {
+        self.errors.push(FieldIssue {
+            field: field.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn warning(&mut self, field: &str, message: impl Into<String>) {
+        self.warnings.push(FieldIssue {
+            field: field.to_string(),
+            message: message.into(),
+        });
+    }
+}
+
+/// 提供商与模型
+fn check_provider(config: &AppConfig, report: &mut ValidationReport) {
+    let provider = config.provider.trim().to_lowercase();
+    // 本地模型（Ollama、LM Studio）名称任意，也不需要 API Key
+    if local_models::is_local_provider(&provider) {
+        if config.model.trim().is_empty() {
+            report.error("model", "模型名称不能为空");
+        }
+        check_base_url(config, report);
+        return;
+    }
+    let Some((_, prefixes)) = PROVIDER_MODEL_PREFIXES
+        .iter()
+        .find(|(name, _)| *name == provider)
+    else {
+        let supported: Vec<&str> = PROVIDER_MODEL_PREFIXES
+            .iter()
+            .map(|(n, _)| *n)
+            .chain(["ollama", "lmstudio"])
+            .collect();
+        report.error(
+            "provider",
+            format!("不支持的提供商: {}（支持: {}）", config.provider, supported.join(", ")),
+        );
+        return;
+    };
+
+    let model = config.model.trim().to_lowercase();
+    if model.is_empty() {
+        report.error("model", "模型名称不能为空");
+    } else if !prefixes.iter().any(|p| model.starts_with(p)) {
+        let message = format!("模型 {} 与提供商 {} 不匹配", config.model, config.provider);
+        // 自定义 API 地址可能托管任意模型，仅提示
+        if config.base_url.as_deref().map(|u| !u.trim().is_empty()).unwrap_or(false) {
+            report.warning("model", message);
+        } else {
+            report.error("model", message);
+        }
+    }
+
+    if config.api_key.trim().is_empty() {
+        report.error("api_key", "API Key 不能为空");
+    }
+
+    check_base_url(config, report);
+}
+
+/// 自定义 API 地址
+fn check_base_url(config: &AppConfig, report: &mut ValidationReport) {
+    if let Some(url) = config.base_url.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            report.error("base_url", "API 地址必须以 http:// 或 https:// 开头");
+        }
+    }
+}
+
+/// 沙盒目录可写
+fn check_sandbox(config: &AppConfig, report: &mut ValidationReport) {
+    let dir = sandbox::expand_sandbox_path(&config.sandbox_path);
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        report.error("sandbox_path", format!("无法创建沙盒目录 {}: {}", dir.display(), e));
+        return;
+    }
+    let probe = dir.join(format!(".write_test_{}", now_millis()));
+    match std::fs::write(&probe, b"ok") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+        }
+        Err(e) => report.error("sandbox_path", format!("沙盒目录不可写 {}: {}", dir.display(), e)),
+    }
+}
+
+/// 邮件配置
+fn check_email(config: &AppConfig, report: &mut ValidationReport) {
+    if let Some(port) = config.email_smtp_port {
+        if !(1..=65535).contains(&port) {
+            report.error("email_smtp_port", format!("SMTP 端口超出范围: {}", port));
+        }
+    }
+
+    let sender = config.email_sender.as_deref().map(str::trim).unwrap_or("");
+    if sender.is_empty() {
+        return;
+    }
+    if !sender.contains('@') {
+        report.error("email_sender", format!("发件人邮箱格式无效: {}", sender));
+    }
+    if config.email_smtp_server.as_deref().map(str::trim).unwrap_or("").is_empty() {
+        report.error("email_smtp_server", "已设置发件人但未设置 SMTP 服务器");
+    }
+    match config.email_auth_method.as_deref() {
+        Some("oauth2") => {
+            if config.email_oauth_client_id.as_deref().map(str::trim).unwrap_or("").is_empty() {
+                report.error("email_oauth_client_id", "使用 OAuth2 登录但未设置客户端 ID");
+            }
+            if !matches!(config.email_oauth_provider.as_deref(), Some("google" | "microsoft")) {
+                report.error("email_oauth_provider", "OAuth2 提供商只支持 google、microsoft");
+            }
+        }
+        None | Some("password") => {
+            if config.email_password.as_deref().unwrap_or("").is_empty() {
+                report.warning("email_password", "已设置发件人但未设置邮箱密码/授权码");
+            }
+        }
+        Some(other) => report.error("email_auth_method", format!("未知的邮箱登录方式: {}（支持 password、oauth2）", other)),
+    }
+}
+
+/// 通过 Python 服务发起一次最小化的提供商调用
+async fn check_connection(
+    state: &crate::AppState,
+    config: &AppConfig,
+) -> Result<ConnectionCheck, String> {
+    let mut guard = state.server.lock().await;
+    crate::ensure_server_alive(&mut guard).await?;
+    let server = guard.as_mut().ok_or("Python 服务未运行")?;
+
+    let cmd = serde_json::json!({
+        "cmd": "validate",
+        "id": format!("validate_{}", now_millis()),
+        "config": {
+            "provider": config.provider,
+            "api_key": config.api_key,
+            "model": config.model,
+            "base_url": config.base_url,
+        },
+    });
+
+    let started = std::time::Instant::now();
+    let reply = crate::send_control_command(
+        server,
+        &cmd,
+        "validate_result",
+        std::time::Duration::from_secs(CONNECTION_TEST_TIMEOUT_SECS),
+    )
+    .await;
+    if matches!(&reply, Err(e) if e == "PROCESS_CRASHED") {
+        *guard = None;
+    }
+    let reply = reply?;
+
+    Ok(ConnectionCheck {
+        ok: reply.get("ok").and_then(|v| v.as_bool()).unwrap_or(false),
+        message: reply
+            .get("message")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        latency_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// 校验配置（不保存）；test_connection 为 true 时额外测试提供商连通性
+#[tauri::command]
+pub async fn validate_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: AppConfig,
+    test_connection: Option<bool>,
+) -> Result<ValidationReport, DeskJarvisError> {
+    let mut report = ValidationReport::default();
+    check_provider(&config, &mut report);
+    check_sandbox(&config, &mut report);
+    check_email(&config, &mut report);
+    report.valid = report.errors.is_empty();
+
+    if report.valid && test_connection.unwrap_or(false) {
+        report.connection = Some(match check_connection(&state, &config).await {
+            Ok(check) => check,
+            Err(e) => ConnectionCheck {
+                ok: false,
+                message: e,
+                latency_ms: 0,
+            },
+        });
+    }
+
+    Ok(report)
+}
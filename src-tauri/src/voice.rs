@@ -0,0 +1,333 @@
+//! 语音输入：用默认麦克风录音（cpal），保存为 16kHz 单声道 WAV 后转写成文字，用于预填指令输入框
+//!
+//! 配置了 whisper_cpp_path 和 whisper_model_path 时用本地 whisper.cpp 转写，
+//! 否则发给 Python 服务（transcribe 命令）。配置 voice_shortcut 后按住快捷键录音、
+//! 松开后转写，结果通过 voice-transcribed 事件发给前端。
+//! cpal 的录音流不能跨线程传递，录音在独立线程中进行，通过通道结束。
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+use crate::{config, features, history};
+
+/// 单次录音的最长时间，超时自动结束
+const MAX_RECORDING_SECS: u64 = 120;
+
+/// 转写使用的采样率（whisper 要求 16kHz）
+const TARGET_SAMPLE_RATE: u32 = 16_000;
+
+/// 等待转写结果的超时（模型首次加载可能较慢）
+const TRANSCRIBE_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// 进行中的录音
+struct Recording {
+    stop: mpsc::Sender<()>,
+    handle: std::thread::JoinHandle<Result<PathBuf, String>>,
+}
+
+static RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+
+/// voice-recording 事件负载
+#[derive(Debug, Clone, Serialize)]
+struct RecordingEvent {
+    recording: bool,
+}
+
+/// 录音文件所在的临时目录
+fn temp_dir() -> PathBuf {
+    std::env::temp_dir().join("deskjarvis_voice")
+}
+
+/// 把输入流的样本转为 i16 追加到缓冲区
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    buffer: Arc<Mutex<Vec<i16>>>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    i16: FromSample<T>,
+{
+    device.build_input_stream(
+        config,
+        move |data: &[T], _| {
+            if let Ok(mut samples) = buffer.lock() {
+                samples.extend(data.iter().map(|s| s.to_sample::<i16>()));
+            }
+        },
+        |e| eprintln!("[Tauri] ⚠️ 录音出错: {}", e),
+        None,
+    )
+}
+
+/// 交错的多声道样本混为单声道并线性重采样到 16kHz
+fn to_mono_16k(samples: &[i16], channels: u16, sample_rate: u32) -> Vec<i16> {
+    let channels = channels.max(1) as usize;
+    let mono: Vec<f32> = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().map(|&s| s as f32).sum::<f32>() / frame.len() as f32)
+        .collect();
+    if sample_rate == TARGET_SAMPLE_RATE || mono.is_empty() {
+        return mono.into_iter().map(|s| s as i16).collect();
+    }
+    let ratio = sample_rate as f64 / TARGET_SAMPLE_RATE as f64;
+    let len = (mono.len() as f64 / ratio) as usize;
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            let index = pos as usize;
+            let next = mono.get(index + 1).copied().unwrap_or(mono[index]);
+            let frac = (pos - index as f64) as f32;
+            (mono[index] + (next - mono[index]) * frac) as i16
+        })
+        .collect()
+}
+
+fn write_wav(path: &Path, samples: &[i16]) -> Result<(), String> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: TARGET_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).map_err(|e| format!("创建录音文件失败: {}", e))?;
+    for &sample in samples {
+        writer
+            .write_sample(sample)
+            .map_err(|e| format!("写入录音文件失败: {}", e))?;
+    }
+    writer
+        .finalize()
+        .map_err(|e| format!("保存录音文件失败: {}", e))
+}
+
+/// 录音线程：开始后通过 ready 报告结果，收到停止信号或超时后写出 WAV
+fn record(ready: mpsc::Sender<Result<(), String>>, stop: mpsc::Receiver<()>) -> Result<PathBuf, String> {
+    let started = (|| {
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or("未找到麦克风")?;
+        let supported = device
+            .default_input_config()
+            .map_err(|e| format!("读取麦克风配置失败: {}", e))?;
+        let config: cpal::StreamConfig = supported.config();
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let stream = match supported.sample_format() {
+            cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config, buffer.clone()),
+            cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config, buffer.clone()),
+            cpal::SampleFormat::I32 => build_stream::<i32>(&device, &config, buffer.clone()),
+            cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config, buffer.clone()),
+            other => return Err(format!("不支持的麦克风采样格式: {}", other)),
+        }
+        .map_err(|e| format!("打开麦克风失败: {}", e))?;
+        stream.play().map_err(|e| format!("开始录音失败: {}", e))?;
+        Ok::<_, String>((stream, config, buffer))
+    })();
+    let (stream, config, buffer) = match started {
+        Ok(started) => {
+            let _ = ready.send(Ok(()));
+            started
+        }
+        Err(e) => {
+            let _ = ready.send(Err(e.clone()));
+            return Err(e);
+        }
+    };
+
+    // 收到停止信号、通道断开或超时都结束录音
+    let _ = stop.recv_timeout(Duration::from_secs(MAX_RECORDING_SECS));
+    drop(stream);
+
+    let samples = buffer.lock().map(|b| b.clone()).unwrap_or_default();
+    if samples.is_empty() {
+        return Err("没有录到声音".to_string());
+    }
+    let dir = temp_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建录音目录失败: {}", e))?;
+    let path = dir.join(format!("voice_{}.wav", history::now_millis()));
+    write_wav(&path, &to_mono_16k(&samples, config.channels, config.sample_rate.0))?;
+    Ok(path)
+}
+
+/// 开始录音
+fn start(app: &AppHandle) -> Result<(), String> {
+    let mut slot = RECORDING.lock().map_err(|_| "录音状态不可用")?;
+    if slot.is_some() {
+        return Err("正在录音".to_string());
+    }
+    let (ready_tx, ready_rx) = mpsc::channel();
+    let (stop_tx, stop_rx) = mpsc::channel();
+    let handle = std::thread::spawn(move || record(ready_tx, stop_rx));
+    ready_rx
+        .recv()
+        .map_err(|_| "录音线程意外退出".to_string())??;
+
+    *slot = Some(Recording { stop: stop_tx, handle });
+    eprintln!("[Tauri] 🎙️ 开始录音");
+    let _ = app.emit("voice-recording", RecordingEvent { recording: true });
+    Ok(())
+}
+
+/// 结束录音并返回 WAV 路径
+async fn finish(app: &AppHandle) -> Result<PathBuf, String> {
+    let recording = RECORDING
+        .lock()
+        .map_err(|_| "录音状态不可用")?
+        .take()
+        .ok_or("当前没有在录音")?;
+    let _ = recording.stop.send(());
+    let result = tauri::async_runtime::spawn_blocking(move || recording.handle.join())
+        .await
+        .map_err(|e| format!("结束录音失败: {}", e))?
+        .map_err(|_| "录音线程意外退出".to_string())?;
+    let _ = app.emit("voice-recording", RecordingEvent { recording: false });
+    result
+}
+
+/// 用本地 whisper.cpp 命令行转写
+async fn transcribe_local(binary: &str, model: &str, wav: &Path, language: &str) -> Result<String, String> {
+    let mut command = tokio::process::Command::new(config::expand_home(binary));
+    command
+        .arg("-m")
+        .arg(config::expand_home(model))
+        .arg("-f")
+        .arg(wav)
+        .args(["-l", language, "-nt", "-np"])
+        .kill_on_drop(true);
+    #[cfg(windows)]
+    command.creation_flags(crate::CREATE_NO_WINDOW);
+    let output = tokio::time::timeout(TRANSCRIBE_TIMEOUT, command.output())
+        .await
+        .map_err(|_| format!("语音转写超时({}s)", TRANSCRIBE_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("启动 whisper.cpp 失败: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "whisper.cpp 转写失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+/// 通过 Python 服务转写（执行任务期间服务忙，无法转写）
+async fn transcribe_via_server(app: &AppHandle, wav: &Path, language: &str) -> Result<String, String> {
+    let state = app.state::<crate::AppState>();
+    let Ok(mut guard) = state.server.try_lock() else {
+        return Err("有任务正在执行，暂时无法转写语音".to_string());
+    };
+    crate::ensure_server_alive(&mut guard).await?;
+    let server = guard.as_mut().ok_or("Python 服务未运行")?;
+    let cmd = serde_json::json!({
+        "cmd": "transcribe",
+        "id": format!("voice_{}", history::now_millis()),
+        "path": wav.to_string_lossy(),
+        "language": language,
+    });
+    let reply = crate::send_control_command(server, &cmd, "transcribe_result", TRANSCRIBE_TIMEOUT).await?;
+    if !reply.get("ok").and_then(|v| v.as_bool()).unwrap_or(false) {
+        let message = reply.get("message").and_then(|v| v.as_str()).unwrap_or("未知错误");
+        return Err(format!("语音转写失败: {}", message));
+    }
+    Ok(reply
+        .get("text")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .trim()
+        .to_string())
+}
+
+/// 转写录音文件，完成后删除录音
+async fn transcribe(app: &AppHandle, wav: PathBuf) -> Result<String, String> {
+    let config = config::load_config()?;
+    let language = config
+        .voice_language
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .unwrap_or("auto")
+        .to_string();
+    let result = match (config.whisper_cpp_path.as_deref(), config.whisper_model_path.as_deref()) {
+        (Some(binary), Some(model)) if !binary.trim().is_empty() && !model.trim().is_empty() => {
+            transcribe_local(binary, model, &wav, &language).await
+        }
+        _ => transcribe_via_server(app, &wav, &language).await,
+    };
+    let _ = std::fs::remove_file(&wav);
+    if let Ok(text) = &result {
+        eprintln!("[Tauri] 🎙️ 语音转写完成（{} 字）", text.chars().count());
+    }
+    result
+}
+
+/// 注册按住说话的全局快捷键（读取配置中的 voice_shortcut，未配置时跳过）
+pub fn register_shortcut(app: &AppHandle) -> Result<(), String> {
+    let Some(shortcut) = config::load_config()
+        .ok()
+        .and_then(|c| c.voice_shortcut)
+        .filter(|s| !s.trim().is_empty())
+    else {
+        return Ok(());
+    };
+    if !features::is_available("global_shortcut") {
+        return Err("全局快捷键不可用，跳过语音输入快捷键注册".to_string());
+    }
+
+    app.global_shortcut()
+        .on_shortcut(shortcut.as_str(), |app, _shortcut, event| match event.state {
+            ShortcutState::Pressed => {
+                if let Err(e) = start(app) {
+                    eprintln!("[Tauri] ⚠️ {}", e);
+                }
+            }
+            ShortcutState::Released => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let result = match finish(&app).await {
+                        Ok(wav) => transcribe(&app, wav).await,
+                        Err(e) => Err(e),
+                    };
+                    match result {
+                        Ok(text) if !text.is_empty() => {
+                            let _ = crate::window_manager::focus_main_window(&app);
+                            let _ = app.emit("voice-transcribed", &text);
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            eprintln!("[Tauri] ⚠️ {}", e);
+                            let _ = app.emit("voice-error", &e);
+                        }
+                    }
+                });
+            }
+        })
+        .map_err(|e| format!("注册快捷键 {} 失败: {}", shortcut, e))?;
+
+    eprintln!("[Tauri] ⌨️ 语音输入快捷键已注册: {}", shortcut);
+    Ok(())
+}
+
+/// 开始录音（默认麦克风）
+#[tauri::command]
+pub async fn start_recording(app: AppHandle) -> Result<(), String> {
+    start(&app)
+}
+
+/// 结束录音并转写，返回识别出的文字
+#[tauri::command]
+pub async fn stop_recording(app: AppHandle) -> Result<String, String> {
+    let wav = finish(&app).await?;
+    transcribe(&app, wav).await
+}
@@ -0,0 +1,56 @@
+//! 预热：提前让 Python 服务加载嵌入模型、记忆库等重资源，避免启动后第一个任务变慢
+//!
+//! 主服务就绪后按配置 warmup_on_start（默认开启）立即发送 warmup 命令，不等待应答：
+//! 服务按顺序处理命令，之后的任务会在预热完成后开始，应答由读取方忽略。
+//! 前端也可在用户开始输入时调用 warmup_agent，已加载的资源会立即返回。
+
+use tokio::io::AsyncWriteExt;
+
+use crate::agent_event::WarmupAckEvent;
+use crate::{config, history, PythonServer};
+
+/// 等待 warmup_ack 的超时（嵌入模型首次加载可能需要下载）
+const WARMUP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(180);
+
+fn warmup_command() -> serde_json::Value {
+    serde_json::json!({ "cmd": "warmup", "id": format!("warmup_{}", history::now_millis()) })
+}
+
+/// 服务就绪后按配置发送预热命令（不等待应答）
+pub async fn send_on_start(server: &mut PythonServer) {
+    let enabled = config::load_config()
+        .ok()
+        .and_then(|c| c.warmup_on_start)
+        .unwrap_or(true);
+    if !enabled {
+        return;
+    }
+    let line = warmup_command().to_string() + "\n";
+    let sent = async {
+        server.stdin.write_all(line.as_bytes()).await?;
+        server.stdin.flush().await
+    };
+    match sent.await {
+        Ok(()) => eprintln!("[Tauri] 🔥 已请求 Python 服务预热"),
+        Err(e) => eprintln!("[Tauri] ⚠️ 发送预热命令失败: {}", e),
+    }
+}
+
+/// 预热主服务（服务未运行时先启动）；正在执行任务时跳过并返回 None
+#[tauri::command]
+pub async fn warmup_agent(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<Option<WarmupAckEvent>, String> {
+    let Ok(mut guard) = state.server.try_lock() else {
+        return Ok(None);
+    };
+    crate::ensure_server_alive(&mut guard).await?;
+    let server = guard.as_mut().ok_or("Python 服务未运行")?;
+    let reply =
+        crate::send_control_command(server, &warmup_command(), "warmup_ack", WARMUP_TIMEOUT)
+            .await?;
+    let ack: WarmupAckEvent =
+        serde_json::from_value(reply).map_err(|e| format!("解析预热结果失败: {}", e))?;
+    eprintln!("[Tauri] 🔥 预热完成，耗时 {}ms", ack.elapsed_ms);
+    Ok(Some(ack))
+}
@@ -0,0 +1,299 @@
+//! Webhook 通知：任务开始、成功、失败时向配置的地址 POST 任务信息，便于接入 Slack、飞书、钉钉等
+//!
+//! 在配置 webhooks 中添加 {"name", "url", "format", "events", "secret"}。format 为 json（默认）时
+//! 发送完整的 WebhookPayload，设置 secret 后带 X-DeskJarvis-Timestamp 和
+//! X-DeskJarvis-Signature: sha256=<HMAC-SHA256(secret, "时间戳.请求体") 的十六进制>；slack / feishu / dingtalk
+//! 按各自机器人的消息格式发送文本，飞书和钉钉的签名按其机器人的加签规则计算。
+//! 指令和摘要先经过脱敏规则。发送在后台进行，网络错误、429 和 5xx 时按退避重试，不影响任务本身。
+
+use std::time::Duration;
+
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::history::{now_millis, TaskRecord, TerminationReason};
+use crate::{config, mail_conn, redaction};
+
+/// 请求超时
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 最多发送次数（含第一次）
+const MAX_ATTEMPTS: u32 = 3;
+
+/// 第一次重试前的等待时间，之后每次翻倍
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// 消息中摘要的最大字符数
+const MAX_SUMMARY_CHARS: usize = 1000;
+
+/// 消息格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// 完整的 WebhookPayload
+    #[default]
+    Json,
+    /// Slack Incoming Webhook
+    Slack,
+    /// 飞书自定义机器人
+    Feishu,
+    /// 钉钉自定义机器人
+    Dingtalk,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Webhook 配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    /// 订阅的事件：started / succeeded / failed，为空时全部订阅
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// 签名密钥
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    Started,
+    Succeeded,
+    Failed,
+}
+
+impl WebhookEvent {
+    fn name(self) -> &'static str {
+        match self {
+            WebhookEvent::Started => "started",
+            WebhookEvent::Succeeded => "succeeded",
+            WebhookEvent::Failed => "failed",
+        }
+    }
+}
+
+/// json 格式发送的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    /// task.started / task.succeeded / task.failed
+    pub event: String,
+    pub task_id: String,
+    pub instruction: String,
+    pub status: WebhookEvent,
+    /// 开始时间（毫秒时间戳）
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+    /// 任务结束时为执行时长
+    pub duration_ms: Option<u64>,
+    /// 结果摘要
+    pub summary: Option<String>,
+    pub termination_reason: Option<TerminationReason>,
+    /// 工作目录中生成的文件
+    pub artifacts: Vec<String>,
+    pub cost_usd: Option<f64>,
+}
+
+/// 截断到指定字符数
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((i, _)) => format!("{}…", &text[..i]),
+        None => text.to_string(),
+    }
+}
+
+impl WebhookPayload {
+    fn from_record(record: &TaskRecord) -> Self {
+        let status = match record.success {
+            None => WebhookEvent::Started,
+            Some(true) => WebhookEvent::Succeeded,
+            Some(false) => WebhookEvent::Failed,
+        };
+        WebhookPayload {
+            event: format!("task.{}", status.name()),
+            task_id: record.id.clone(),
+            instruction: redaction::redact(&record.instruction),
+            status,
+            started_at: record.started_at,
+            finished_at: record.finished_at,
+            duration_ms: record.finished_at.map(|f| f.saturating_sub(record.started_at)),
+            summary: record
+                .message
+                .as_deref()
+                .map(|m| truncate(&redaction::redact(m), MAX_SUMMARY_CHARS)),
+            termination_reason: record.termination_reason,
+            artifacts: record.artifacts.clone(),
+            cost_usd: record.usage.as_ref().map(|u| u.cost_usd),
+        }
+    }
+
+    /// 聊天机器人使用的文本
+    fn text(&self) -> String {
+        let title = match self.status {
+            WebhookEvent::Started => "🚀 DeskJarvis 任务开始",
+            WebhookEvent::Succeeded => "✅ DeskJarvis 任务完成",
+            WebhookEvent::Failed => "❌ DeskJarvis 任务失败",
+        };
+        let mut lines = vec![format!("{}：{}", title, truncate(&self.instruction, 200))];
+        if let Some(duration) = self.duration_ms {
+            lines.push(format!("耗时 {:.1} 秒", duration as f64 / 1000.0));
+        }
+        if let Some(summary) = self.summary.as_deref().filter(|s| !s.is_empty()) {
+            lines.push(summary.to_string());
+        }
+        if !self.artifacts.is_empty() {
+            lines.push(format!("生成文件：{}", self.artifacts.join("、")));
+        }
+        lines.join("\n")
+    }
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    // HMAC 接受任意长度的密钥，new_from_slice 不会失败
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC 接受任意长度的密钥");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// 签名后待发送的请求
+struct SignedRequest {
+    url: String,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+}
+
+/// 按格式构造请求体并签名
+fn build_request(hook: &WebhookConfig, payload: &WebhookPayload) -> Result<SignedRequest, String> {
+    let secret = hook.secret.as_deref().filter(|s| !s.is_empty());
+    let timestamp_ms = now_millis();
+    let mut url = hook.url.trim().to_string();
+    let mut headers = Vec::new();
+    let body = match hook.format {
+        WebhookFormat::Json => {
+            let body = serde_json::to_string(payload).map_err(|e| format!("序列化失败: {}", e))?;
+            if let Some(secret) = secret {
+                let timestamp = (timestamp_ms / 1000).to_string();
+                let signed = format!("{}.{}", timestamp, body);
+                let signature: String = hmac_sha256(secret.as_bytes(), signed.as_bytes())
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect();
+                headers.push(("X-DeskJarvis-Timestamp", timestamp));
+                headers.push(("X-DeskJarvis-Signature", format!("sha256={}", signature)));
+            }
+            body
+        }
+        WebhookFormat::Slack => serde_json::json!({ "text": payload.text() }).to_string(),
+        WebhookFormat::Feishu => {
+            let mut body = serde_json::json!({ "msg_type": "text", "content": { "text": payload.text() } });
+            if let Some(secret) = secret {
+                // 飞书：以 "时间戳\n密钥" 为 HMAC 密钥对空串签名，时间戳为秒
+                let timestamp = (timestamp_ms / 1000).to_string();
+                let sign = hmac_sha256(format!("{}\n{}", timestamp, secret).as_bytes(), b"");
+                body["timestamp"] = serde_json::json!(timestamp);
+                body["sign"] = serde_json::json!(base64::engine::general_purpose::STANDARD.encode(sign));
+            }
+            body.to_string()
+        }
+        WebhookFormat::Dingtalk => {
+            if let Some(secret) = secret {
+                // 钉钉：以密钥对 "时间戳\n密钥" 签名，时间戳为毫秒，放在地址参数中
+                let sign = hmac_sha256(secret.as_bytes(), format!("{}\n{}", timestamp_ms, secret).as_bytes());
+                let mut parsed = url::Url::parse(&url).map_err(|e| format!("无效的地址: {}", e))?;
+                parsed
+                    .query_pairs_mut()
+                    .append_pair("timestamp", &timestamp_ms.to_string())
+                    .append_pair("sign", &base64::engine::general_purpose::STANDARD.encode(sign));
+                url = parsed.to_string();
+            }
+            serde_json::json!({ "msgtype": "text", "text": { "content": payload.text() } }).to_string()
+        }
+    };
+    Ok(SignedRequest { url, headers, body })
+}
+
+/// 发送一次，返回是否值得重试和错误信息
+async fn send_once(client: &reqwest::Client, hook: &WebhookConfig, payload: &WebhookPayload) -> Result<(), (bool, String)> {
+    let signed = build_request(hook, payload).map_err(|e| (false, e))?;
+    let mut request = client
+        .post(&signed.url)
+        .header("Content-Type", "application/json; charset=utf-8")
+        .header("User-Agent", "DeskJarvis-Webhook")
+        .body(signed.body);
+    for (name, value) in signed.headers {
+        request = request.header(name, value);
+    }
+    let response = request.send().await.map_err(|e| (true, e.to_string()))?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let retry = status.as_u16() == 429 || status.is_server_error();
+    Err((retry, format!("HTTP {}", status.as_u16())))
+}
+
+/// 向一个地址发送，失败时按退避重试
+async fn deliver(client: &reqwest::Client, hook: &WebhookConfig, payload: &WebhookPayload) {
+    let mut delay = RETRY_DELAY;
+    for attempt in 1..=MAX_ATTEMPTS {
+        match send_once(client, hook, payload).await {
+            Ok(()) => return,
+            Err((retry, message)) => {
+                if !retry || attempt == MAX_ATTEMPTS {
+                    eprintln!("[Tauri] ⚠️ Webhook {} 发送失败（{}）: {}", hook.name, payload.event, message);
+                    return;
+                }
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+    }
+}
+
+/// 订阅了该事件的已启用地址
+fn subscribers(event: WebhookEvent) -> Vec<WebhookConfig> {
+    config::load_config()
+        .map(|c| c.webhooks)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|h| h.enabled && !h.url.trim().is_empty())
+        .filter(|h| h.events.is_empty() || h.events.iter().any(|e| e == event.name()))
+        .collect()
+}
+
+/// 依次向订阅的地址发送任务当前状态（开始或结束），等待发送完成
+pub async fn send(record: &TaskRecord) {
+    let payload = WebhookPayload::from_record(record);
+    let hooks = subscribers(payload.status);
+    if hooks.is_empty() {
+        return;
+    }
+    mail_conn::ensure_crypto_provider();
+    let client = match reqwest::Client::builder().timeout(HTTP_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("[Tauri] ⚠️ 创建 HTTP 客户端失败: {}", e);
+            return;
+        }
+    };
+    for hook in &hooks {
+        deliver(&client, hook, &payload).await;
+    }
+}
+
+/// 在后台发送，不阻塞任务流程
+pub fn notify(record: Option<TaskRecord>) {
+    if let Some(record) = record {
+        tauri::async_runtime::spawn(async move { send(&record).await });
+    }
+}
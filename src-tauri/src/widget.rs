@@ -0,0 +1,165 @@
+//! 任务小窗：无边框、置顶的小窗口，显示在托盘附近，展示当前任务的最新进度
+//!
+//! 小窗页面为 index.html?view=widget，实时进度通过全局的 task-progress / agent-status 事件获得；
+//! 打开小窗时用 get_widget_state 取回当前状态和最近一条进度事件。
+//! 托盘位置拿不到时（部分 Linux 桌面）放在主显示器工作区右下角。
+
+use std::sync::Mutex;
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+use crate::i18n;
+use crate::tray::{self, AgentStatus};
+
+/// 小窗标签
+pub const WIDGET_LABEL: &str = "widget";
+
+/// 小窗尺寸（逻辑像素）
+const WIDGET_WIDTH: f64 = 320.0;
+const WIDGET_HEIGHT: f64 = 96.0;
+
+/// 与托盘图标、屏幕边缘的间距（逻辑像素）
+const WIDGET_MARGIN: f64 = 8.0;
+
+/// 当前任务最近一条进度事件（已脱敏）
+static LATEST_PROGRESS: Mutex<Option<Value>> = Mutex::new(None);
+
+/// get_widget_state 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct WidgetState {
+    pub status: Option<AgentStatus>,
+    pub progress: Option<Value>,
+}
+
+/// 记录最新进度事件（转发 task-progress 时调用）
+pub fn record_progress(payload: &Value) {
+    if let Ok(mut latest) = LATEST_PROGRESS.lock() {
+        *latest = Some(payload.clone());
+    }
+}
+
+/// 新任务开始时清空上一个任务的进度
+pub fn clear_progress() {
+    if let Ok(mut latest) = LATEST_PROGRESS.lock() {
+        *latest = None;
+    }
+}
+
+/// 托盘图标所在的矩形（物理像素），平台不支持时为 None
+fn tray_rect(app: &AppHandle) -> Option<(PhysicalPosition<i32>, (i32, i32))> {
+    let rect = app.tray_by_id(tray::TRAY_ID)?.rect().ok()??;
+    let scale = app
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .map(|m| m.scale_factor())
+        .unwrap_or(1.0);
+    let position = rect.position.to_physical::<i32>(scale);
+    let size = rect.size.to_physical::<i32>(scale);
+    Some((position, (size.width, size.height)))
+}
+
+/// 把窗口限制在显示器工作区内
+fn clamp_to_work_area(monitor: &Monitor, x: i64, y: i64, width: i64, height: i64) -> PhysicalPosition<i32> {
+    let area = monitor.work_area();
+    let (left, top) = (area.position.x as i64, area.position.y as i64);
+    let right = (left + area.size.width as i64 - width).max(left);
+    let bottom = (top + area.size.height as i64 - height).max(top);
+    PhysicalPosition::new(x.clamp(left, right) as i32, y.clamp(top, bottom) as i32)
+}
+
+/// 计算小窗位置：托盘在屏幕上半部时放在图标下方，否则放在上方；水平对齐图标中心
+fn widget_position(app: &AppHandle) -> Option<PhysicalPosition<i32>> {
+    if let Some((tray_pos, (tray_w, tray_h))) = tray_rect(app) {
+        let monitor = app
+            .monitor_from_point(tray_pos.x as f64, tray_pos.y as f64)
+            .ok()
+            .flatten()
+            .or_else(|| app.primary_monitor().ok().flatten())?;
+        let scale = monitor.scale_factor();
+        let (width, height) = ((WIDGET_WIDTH * scale) as i64, (WIDGET_HEIGHT * scale) as i64);
+        let margin = (WIDGET_MARGIN * scale) as i64;
+        let screen_mid = monitor.position().y as i64 + monitor.size().height as i64 / 2;
+
+        let x = tray_pos.x as i64 + tray_w as i64 / 2 - width / 2;
+        let y = if (tray_pos.y as i64) < screen_mid {
+            tray_pos.y as i64 + tray_h as i64 + margin
+        } else {
+            tray_pos.y as i64 - height - margin
+        };
+        return Some(clamp_to_work_area(&monitor, x, y, width, height));
+    }
+
+    let monitor = app.primary_monitor().ok().flatten()?;
+    let area = monitor.work_area();
+    let scale = monitor.scale_factor();
+    let (width, height) = ((WIDGET_WIDTH * scale) as i64, (WIDGET_HEIGHT * scale) as i64);
+    let margin = (WIDGET_MARGIN * scale) as i64;
+    let x = area.position.x as i64 + area.size.width as i64 - width - margin;
+    let y = area.position.y as i64 + area.size.height as i64 - height - margin;
+    Some(clamp_to_work_area(&monitor, x, y, width, height))
+}
+
+/// 获取小窗，不存在时创建（初始隐藏）
+fn get_or_create_widget(app: &AppHandle) -> Result<WebviewWindow, String> {
+    if let Some(window) = app.get_webview_window(WIDGET_LABEL) {
+        return Ok(window);
+    }
+
+    WebviewWindowBuilder::new(app, WIDGET_LABEL, WebviewUrl::App("index.html?view=widget".into()))
+        .title(i18n::tr("DeskJarvis 任务进度"))
+        .inner_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .focused(false)
+        .visible(false)
+        .build()
+        .map_err(|e| format!("创建任务小窗失败: {}", e))
+}
+
+/// 在托盘附近显示任务小窗（不抢焦点）
+pub fn show(app: &AppHandle) -> Result<(), String> {
+    let window = get_or_create_widget(app)?;
+    if let Some(position) = widget_position(app) {
+        window
+            .set_position(position)
+            .map_err(|e| format!("移动任务小窗失败: {}", e))?;
+    }
+    window
+        .show()
+        .map_err(|e| format!("显示任务小窗失败: {}", e))
+}
+
+/// 显示任务小窗
+#[tauri::command]
+pub async fn show_widget(app: AppHandle) -> Result<(), String> {
+    show(&app)
+}
+
+/// 隐藏任务小窗
+#[tauri::command]
+pub async fn hide_widget(app: AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(WIDGET_LABEL) {
+        window
+            .hide()
+            .map_err(|e| format!("隐藏任务小窗失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 小窗打开时获取当前状态和最近一条进度事件
+#[tauri::command]
+pub async fn get_widget_state(app: AppHandle) -> Result<WidgetState, String> {
+    let progress = LATEST_PROGRESS
+        .lock()
+        .map_err(|_| "任务进度不可用")?
+        .clone();
+    Ok(WidgetState {
+        status: tray::current_status(&app),
+        progress,
+    })
+}
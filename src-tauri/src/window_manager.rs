@@ -0,0 +1,313 @@
+//! 窗口管理：显示器枚举、快捷面板召唤、多显示器定位与主窗口关闭行为
+
+use serde::Serialize;
+use tauri::{
+    AppHandle, CloseRequestApi, Manager, Monitor, PhysicalPosition, WebviewUrl, Window,
+    WebviewWindow, WebviewWindowBuilder,
+};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+use crate::config::{self, AppConfig};
+use crate::{features, i18n};
+use crate::tray::{self, AgentStatus};
+
+/// 主窗口标签
+pub const MAIN_LABEL: &str = "main";
+
+/// 快捷面板窗口标签
+pub const PALETTE_LABEL: &str = "palette";
+
+/// 默认召唤快捷键
+const DEFAULT_PALETTE_SHORTCUT: &str = "Alt+Space";
+
+/// 快捷面板逻辑尺寸
+const PALETTE_WIDTH: f64 = 680.0;
+const PALETTE_HEIGHT: f64 = 420.0;
+
+/// 关闭确认对话框的按钮文字
+const CLOSE_HIDE_TEXT: &str = "隐藏到托盘";
+const CLOSE_QUIT_TEXT: &str = "中止并退出";
+
+/// 快捷面板的显示器偏好
+#[derive(Debug, Clone, PartialEq)]
+enum MonitorPreference {
+    /// 鼠标所在显示器（默认）
+    Cursor,
+    /// 系统主显示器
+    Primary,
+    /// 指定名称的显示器，断开时回退到鼠标所在显示器
+    Named(String),
+}
+
+impl MonitorPreference {
+    fn from_config(config: &AppConfig) -> Self {
+        match config.palette_monitor.as_deref().map(str::trim) {
+            None | Some("") | Some("cursor") => MonitorPreference::Cursor,
+            Some("primary") => MonitorPreference::Primary,
+            Some(name) => MonitorPreference::Named(name.to_string()),
+        }
+    }
+}
+
+/// 显示器信息（供设置界面选择偏好显示器）
+#[derive(Debug, Serialize)]
+pub struct MonitorInfo {
+    name: Option<String>,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    scale_factor: f64,
+    is_primary: bool,
+    has_cursor: bool,
+}
+
+/// 判断两个 Monitor 是否为同一块屏幕（Monitor 未实现 PartialEq）
+fn same_monitor(a: &Monitor, b: &Monitor) -> bool {
+    a.name() == b.name() && a.position() == b.position()
+}
+
+/// 鼠标当前所在的显示器
+fn cursor_monitor(app: &AppHandle) -> Option<Monitor> {
+    let cursor = app.cursor_position().ok()?;
+    app.monitor_from_point(cursor.x, cursor.y).ok().flatten()
+}
+
+/// 按偏好选出目标显示器，找不到时依次回退到鼠标所在显示器、主显示器
+fn resolve_target_monitor(app: &AppHandle, preference: &MonitorPreference) -> Option<Monitor> {
+    let preferred = match preference {
+        MonitorPreference::Cursor => cursor_monitor(app),
+        MonitorPreference::Primary => app.primary_monitor().ok().flatten(),
+        MonitorPreference::Named(name) => app
+            .available_monitors()
+            .ok()
+            .and_then(|monitors| {
+                monitors
+                    .into_iter()
+                    .find(|m| m.name().map(|n| n == name).unwrap_or(false))
+            })
+            .or_else(|| {
+                eprintln!("[Tauri] ⚠️ 未找到显示器 {}，改用鼠标所在显示器", name);
+                cursor_monitor(app)
+            }),
+    };
+
+    preferred.or_else(|| app.primary_monitor().ok().flatten())
+}
+
+/// 计算面板在显示器工作区内的位置：水平居中，距顶部 1/5 处
+fn palette_position(monitor: &Monitor) -> PhysicalPosition<i32> {
+    let area = monitor.work_area();
+    let scale = monitor.scale_factor();
+    let width = (PALETTE_WIDTH * scale) as i64;
+    let height = (PALETTE_HEIGHT * scale) as i64;
+
+    let x = area.position.x as i64 + (area.size.width as i64 - width).max(0) / 2;
+    let y = area.position.y as i64 + (area.size.height as i64 - height).max(0) / 5;
+    PhysicalPosition::new(x as i32, y as i32)
+}
+
+/// 获取快捷面板窗口，不存在时创建（初始隐藏）
+fn get_or_create_palette(app: &AppHandle) -> Result<WebviewWindow, String> {
+    if let Some(window) = app.get_webview_window(PALETTE_LABEL) {
+        return Ok(window);
+    }
+
+    WebviewWindowBuilder::new(
+        app,
+        PALETTE_LABEL,
+        WebviewUrl::App("index.html?view=palette".into()),
+    )
+    .title("DeskJarvis")
+    .inner_size(PALETTE_WIDTH, PALETTE_HEIGHT)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .visible(false)
+    .build()
+    .map_err(|e| format!("创建快捷面板失败: {}", e))
+}
+
+/// 获取主窗口（供后台任务发送进度事件）
+pub fn main_window(app: &AppHandle) -> Option<Window> {
+    app.get_webview_window(MAIN_LABEL)
+        .map(|w| w.as_ref().window())
+}
+
+/// 显示并聚焦主窗口（最小化时先还原）
+pub fn focus_main_window(app: &AppHandle) -> Result<(), String> {
+    let window = app
+        .get_webview_window(MAIN_LABEL)
+        .ok_or("主窗口不存在")?;
+    let _ = window.unminimize();
+    window
+        .show()
+        .map_err(|e| format!("显示主窗口失败: {}", e))?;
+    let _ = window.set_focus();
+    Ok(())
+}
+
+/// 点击主窗口关闭按钮：close_to_tray（默认开启）时隐藏到托盘，任务继续执行；
+/// 否则退出应用，有任务在执行时先弹窗确认（可改为隐藏到托盘或取消）
+pub fn on_main_close_requested(window: &Window, api: &CloseRequestApi) {
+    let close_to_tray = config::load_config()
+        .ok()
+        .and_then(|c| c.close_to_tray)
+        .unwrap_or(true);
+    api.prevent_close();
+    if close_to_tray {
+        let _ = window.hide();
+        return;
+    }
+
+    let app = window.app_handle().clone();
+    let running = matches!(tray::current_status(&app), Some(AgentStatus::Running { .. }));
+    if !running {
+        quit(app);
+        return;
+    }
+    if !features::is_available("dialog") {
+        // 无法确认时不中止任务，隐藏到托盘
+        let _ = window.hide();
+        return;
+    }
+    let hide_target = window.clone();
+    let hide_text = i18n::tr(CLOSE_HIDE_TEXT);
+    let quit_text = i18n::tr(CLOSE_QUIT_TEXT);
+    app.dialog()
+        .message(i18n::tr("有任务正在执行，退出会中止该任务。"))
+        .title(i18n::tr("退出 DeskJarvis"))
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::YesNoCancelCustom(
+            hide_text.clone(),
+            quit_text.clone(),
+            i18n::tr("取消"),
+        ))
+        .parent(window)
+        // 自定义按钮在部分平台以按钮文字返回
+        .show_with_result(move |result| match result {
+            MessageDialogResult::Yes => {
+                let _ = hide_target.hide();
+            }
+            MessageDialogResult::Custom(choice) if choice == hide_text => {
+                let _ = hide_target.hide();
+            }
+            MessageDialogResult::No => quit(app),
+            MessageDialogResult::Custom(choice) if choice == quit_text => quit(app),
+            _ => {}
+        });
+}
+
+/// 关闭 Python 服务后退出应用
+fn quit(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        crate::shutdown_python_servers(&app.state::<crate::AppState>()).await;
+        app.exit(0);
+    });
+}
+
+/// 在目标显示器上显示快捷面板并聚焦
+pub fn summon_palette(app: &AppHandle) -> Result<(), String> {
+    let preference = config::load_config()
+        .map(|c| MonitorPreference::from_config(&c))
+        .unwrap_or(MonitorPreference::Cursor);
+
+    // 面板获得焦点前记录前台应用，用于检测当前项目
+    crate::project_context::capture(app);
+    let window = get_or_create_palette(app)?;
+
+    if let Some(monitor) = resolve_target_monitor(app, &preference) {
+        window
+            .set_position(palette_position(&monitor))
+            .map_err(|e| format!("移动快捷面板失败: {}", e))?;
+    }
+
+    window
+        .show()
+        .map_err(|e| format!("显示快捷面板失败: {}", e))?;
+    let _ = window.set_focus();
+    Ok(())
+}
+
+/// 快捷键切换：面板可见且聚焦时隐藏，否则召唤到目标显示器
+fn toggle_palette(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(PALETTE_LABEL) {
+        let visible = window.is_visible().unwrap_or(false);
+        let focused = window.is_focused().unwrap_or(false);
+        if visible && focused {
+            return window
+                .hide()
+                .map_err(|e| format!("隐藏快捷面板失败: {}", e));
+        }
+    }
+    summon_palette(app)
+}
+
+/// 注册召唤快捷面板的全局快捷键（读取配置中的 palette_shortcut）
+pub fn register_palette_shortcut(app: &AppHandle) -> Result<(), String> {
+    if !features::is_available("global_shortcut") {
+        return Err("全局快捷键不可用，跳过快捷面板快捷键注册".to_string());
+    }
+
+    let shortcut = config::load_config()
+        .ok()
+        .and_then(|c| c.palette_shortcut)
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PALETTE_SHORTCUT.to_string());
+
+    app.global_shortcut()
+        .on_shortcut(shortcut.as_str(), |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                if let Err(e) = toggle_palette(app) {
+                    eprintln!("[Tauri] ⚠️ 召唤快捷面板失败: {}", e);
+                }
+            }
+        })
+        .map_err(|e| format!("注册快捷键 {} 失败: {}", shortcut, e))?;
+
+    eprintln!("[Tauri] ⌨️ 快捷面板快捷键已注册: {}", shortcut);
+    Ok(())
+}
+
+/// 列出所有显示器
+#[tauri::command]
+pub async fn list_monitors(app: AppHandle) -> Result<Vec<MonitorInfo>, String> {
+    let monitors = app
+        .available_monitors()
+        .map_err(|e| format!("获取显示器列表失败: {}", e))?;
+    let primary = app.primary_monitor().ok().flatten();
+    let under_cursor = cursor_monitor(&app);
+
+    Ok(monitors
+        .iter()
+        .map(|m| MonitorInfo {
+            name: m.name().cloned(),
+            x: m.position().x,
+            y: m.position().y,
+            width: m.size().width,
+            height: m.size().height,
+            scale_factor: m.scale_factor(),
+            is_primary: primary.as_ref().map(|p| same_monitor(p, m)).unwrap_or(false),
+            has_cursor: under_cursor.as_ref().map(|c| same_monitor(c, m)).unwrap_or(false),
+        })
+        .collect())
+}
+
+/// 显示快捷面板（前端或托盘调用）
+#[tauri::command]
+pub async fn show_palette(app: AppHandle) -> Result<(), String> {
+    summon_palette(&app)
+}
+
+/// 隐藏快捷面板
+#[tauri::command]
+pub async fn hide_palette(app: AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(PALETTE_LABEL) {
+        window
+            .hide()
+            .map_err(|e| format!("隐藏快捷面板失败: {}", e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,185 @@
+//! 主窗口布局持久化：移动、缩放后（防抖）把位置、大小和最大化状态写入
+//! ~/.deskjarvis/window_state.json，启动时恢复
+//!
+//! 保存的位置所在的显示器已断开时，把窗口移到距离最近的可见显示器工作区内。
+//! 最大化期间只更新 maximized，保留还原后的位置和大小。
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, Window};
+
+use crate::config;
+use crate::window_manager::MAIN_LABEL;
+
+/// 状态文件名
+const STATE_FILE: &str = "window_state.json";
+
+/// 移动、缩放停止多久后写入（毫秒）
+const SAVE_DEBOUNCE_MS: u64 = 500;
+
+/// 重置布局时的默认尺寸（与 tauri.conf.json 一致，逻辑像素）
+const DEFAULT_WIDTH: f64 = 1000.0;
+const DEFAULT_HEIGHT: f64 = 700.0;
+
+/// 窗口至少要有这么多像素落在某个显示器内才视为可见
+const MIN_VISIBLE_PX: i64 = 100;
+
+/// 每次移动、缩放递增，防抖任务只在期间没有新事件时写入
+static SAVE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// 主窗口布局（物理像素）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct WindowState {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    #[serde(default)]
+    maximized: bool,
+}
+
+fn state_path() -> Result<std::path::PathBuf, String> {
+    Ok(config::get_data_dir()?.join(STATE_FILE))
+}
+
+fn load() -> Option<WindowState> {
+    let content = std::fs::read_to_string(state_path().ok()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn store(state: &WindowState) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(state).map_err(|e| format!("序列化窗口布局失败: {}", e))?;
+    std::fs::write(state_path()?, content).map_err(|e| format!("保存窗口布局失败: {}", e))
+}
+
+/// 矩形与显示器工作区的重叠面积的宽、高
+fn overlap(monitor: &Monitor, state: &WindowState) -> (i64, i64) {
+    let area = monitor.work_area();
+    let (left, top) = (area.position.x as i64, area.position.y as i64);
+    let (right, bottom) = (left + area.size.width as i64, top + area.size.height as i64);
+    let w = (right.min(state.x as i64 + state.width as i64) - left.max(state.x as i64)).max(0);
+    let h = (bottom.min(state.y as i64 + state.height as i64) - top.max(state.y as i64)).max(0);
+    (w, h)
+}
+
+/// 窗口中心到显示器工作区中心的距离平方
+fn distance(monitor: &Monitor, state: &WindowState) -> i64 {
+    let area = monitor.work_area();
+    let cx = area.position.x as i64 + area.size.width as i64 / 2;
+    let cy = area.position.y as i64 + area.size.height as i64 / 2;
+    let dx = state.x as i64 + state.width as i64 / 2 - cx;
+    let dy = state.y as i64 + state.height as i64 / 2 - cy;
+    dx * dx + dy * dy
+}
+
+/// 窗口不在任何显示器上可见时，移到最近显示器的工作区内（尺寸超出时一并缩小）
+fn clamp_to_monitors(app: &AppHandle, mut state: WindowState) -> WindowState {
+    let Ok(monitors) = app.available_monitors() else {
+        return state;
+    };
+    let visible = monitors.iter().any(|m| {
+        let (w, h) = overlap(m, &state);
+        w >= MIN_VISIBLE_PX && h >= MIN_VISIBLE_PX
+    });
+    if visible {
+        return state;
+    }
+    let Some(nearest) = monitors.iter().min_by_key(|m| distance(m, &state)) else {
+        return state;
+    };
+
+    let area = nearest.work_area();
+    state.width = state.width.min(area.size.width);
+    state.height = state.height.min(area.size.height);
+    let (left, top) = (area.position.x as i64, area.position.y as i64);
+    let right = left + area.size.width as i64 - state.width as i64;
+    let bottom = top + area.size.height as i64 - state.height as i64;
+    state.x = (state.x as i64).clamp(left, right) as i32;
+    state.y = (state.y as i64).clamp(top, bottom) as i32;
+    eprintln!(
+        "[Tauri] 🪟 上次的窗口位置不在可见显示器上，已移到 {}",
+        nearest.name().map(String::as_str).unwrap_or("最近的显示器")
+    );
+    state
+}
+
+/// 启动时恢复主窗口布局
+pub fn restore(app: &AppHandle) {
+    let Some(saved) = load() else {
+        return;
+    };
+    let Some(window) = app.get_webview_window(MAIN_LABEL) else {
+        return;
+    };
+    let state = clamp_to_monitors(app, saved);
+    let _ = window.set_size(PhysicalSize::new(state.width, state.height));
+    let _ = window.set_position(PhysicalPosition::new(state.x, state.y));
+    if state.maximized {
+        let _ = window.maximize();
+    }
+}
+
+/// 读取窗口当前布局；最大化或最小化时沿用已保存的位置和大小
+fn capture(window: &Window) -> Option<WindowState> {
+    if window.is_minimized().unwrap_or(false) {
+        return None;
+    }
+    let maximized = window.is_maximized().unwrap_or(false);
+    if maximized {
+        let previous = load()?;
+        return Some(WindowState { maximized, ..previous });
+    }
+    let position = window.outer_position().ok()?;
+    let size = window.inner_size().ok()?;
+    Some(WindowState {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized,
+    })
+}
+
+/// 主窗口移动或缩放：停止变化 SAVE_DEBOUNCE_MS 后写入
+pub fn on_moved_or_resized(window: &Window) {
+    let generation = SAVE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(SAVE_DEBOUNCE_MS)).await;
+        if SAVE_GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        if let Some(state) = capture(&window) {
+            if let Err(e) = store(&state) {
+                eprintln!("[Tauri] ⚠️ {}", e);
+            }
+        }
+    });
+}
+
+/// 重置主窗口布局：删除保存的状态，恢复默认大小并居中
+#[tauri::command]
+pub async fn reset_window_layout(app: AppHandle) -> Result<(), String> {
+    // 使尚未执行的防抖写入失效
+    SAVE_GENERATION.fetch_add(1, Ordering::SeqCst);
+    let path = state_path()?;
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("删除窗口布局失败: {}", e))?;
+    }
+
+    let window = app
+        .get_webview_window(MAIN_LABEL)
+        .ok_or("主窗口不存在")?;
+    let _ = window.unmaximize();
+    window
+        .set_size(tauri::LogicalSize::new(DEFAULT_WIDTH, DEFAULT_HEIGHT))
+        .map_err(|e| format!("重置窗口大小失败: {}", e))?;
+    window
+        .center()
+        .map_err(|e| format!("窗口居中失败: {}", e))?;
+    let _ = window.show();
+    eprintln!("[Tauri] 🪟 已重置主窗口布局");
+    Ok(())
+}
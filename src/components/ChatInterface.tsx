@@ -14,8 +14,8 @@ import React, { useState, useRef, useEffect } from "react";
 import { motion, AnimatePresence } from "framer-motion";
 import ReactMarkdown from "react-markdown";
 import remarkGfm from "remark-gfm";
-import { ChatMessage, TaskStatus, AppConfig, LogEntry, TaskResult, AgentType, LiveNotice } from "../types";
-import { executeTask, isTauriEnvironment } from "../utils/tauri";
+import { ChatMessage, TaskStatus, AppConfig, LogEntry, TaskResult, AgentType, LiveNotice, AgentEvent, AttachedFile } from "../types";
+import { executeTask, isTauriEnvironment, removeAttachment, warmupAgent } from "../utils/tauri";
 import { ChatSidebar, ChatSession } from "./ChatSidebar";
 import { UserInputDialog, InputRequest } from "./UserInputDialog";
 import { 
@@ -94,6 +94,7 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
   const [lastTaskContext, setLastTaskContext] = useState<any>(null); // 保存上次任务上下文
   const [isDragging, setIsDragging] = useState(false);
   const [attachedPath, setAttachedPath] = useState<string | null>(null);
+  const [attachedFiles, setAttachedFiles] = useState<AttachedFile[]>([]); // 拖入窗口、等待带入下一个任务的文件
   
   // 用户输入请求（登录、验证码等）
   const [userInputRequest, setUserInputRequest] = useState<InputRequest | null>(null);
@@ -106,10 +107,12 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
   const messagesEndRef = useRef<HTMLDivElement>(null);
   const fileInputRef = useRef<HTMLInputElement>(null);
   const textareaRef = useRef<HTMLTextAreaElement>(null);
+  const warmedUpRef = useRef(false); // 首次输入时预热 Agent
   const currentAssistantMessageIdRef = useRef<string | null>(null); // 跟踪当前正在更新的AI消息ID
   const prevMessagesLengthRef = useRef<number>(0); // 用于优化滚动性能
   const isTaskCancelledRef = useRef<boolean>(false); // 任务是否被取消
   const unlistenProgressRef = useRef<(() => void) | null>(null); // 进度事件监听器的清理函数
+  const currentRequestIdRef = useRef<string | null>(null); // 当前任务 ID（从进度事件中取得），停止时指定
 
   // 组件加载时输出日志，确认控制台正常工作
   useEffect(() => {
@@ -121,16 +124,10 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
     
     if (isTauriEnvironment()) {
       import("@tauri-apps/api/event").then(({ listen }) => {
-        listen<{ paths: string[] }>("tauri://drag-drop", (event) => {
-          log.debug("[拖拽] 收到文件:", event.payload.paths);
-          if (event.payload.paths && event.payload.paths.length > 0) {
-            const path = event.payload.paths[0];
-            setAttachedPath(path);
-            setInput((prev) => {
-              const trimmed = prev.trim();
-              return trimmed ? `${trimmed}` : "";
-            });
-          }
+        // 后端已把拖入的文件复制进沙盒，执行下一个任务时自动带入 context.attached_files
+        listen<{ files: AttachedFile[]; pending: AttachedFile[] }>("files-attached", (event) => {
+          log.debug("[拖拽] 已附加文件:", event.payload.files);
+          setAttachedFiles(event.payload.pending);
         }).then((unlisten) => {
           unlistenDrop = unlisten;
         });
@@ -570,7 +567,7 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
     // 向后端发送停止命令
     try {
       const { stopTask } = await import("../utils/tauri");
-      await stopTask();
+      await stopTask(currentRequestIdRef.current ?? undefined);
       log.debug("✅ [handleStop] 停止命令已发送到后端");
     } catch (error) {
       log.error("❌ [handleStop] 发送停止命令失败:", error);
@@ -653,6 +650,7 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
     }
     // 重置取消标记
     isTaskCancelledRef.current = false;
+    currentRequestIdRef.current = null;
     
     updateStatus("planning");
     setCurrentSteps([]);
@@ -672,13 +670,16 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
     if (isTauriEnvironment()) {
       try {
         const { listen } = await import("@tauri-apps/api/event");
-        unlistenProgress = await listen("task-progress", (event: any) => {
+        unlistenProgress = await listen<AgentEvent>("task-progress", (event) => {
           // 如果任务已被取消，忽略进度事件
           if (isTaskCancelledRef.current) {
             return;
           }
-          const progressEvent = event.payload as any;
-          handleProgressEvent(progressEvent);
+          const payload = event.payload;
+          if (!currentRequestIdRef.current && "id" in payload && payload.id) {
+            currentRequestIdRef.current = payload.id;
+          }
+          handleProgressEvent(payload);
         });
         unlistenProgressRef.current = unlistenProgress;
       } catch (e) {
@@ -740,6 +741,8 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
       
       let result;
       try {
+        // 拖入的文件由后端带入本次任务
+        setAttachedFiles([]);
         result = await executeTask(instruction, Object.keys(context).length > 0 ? context : null);
         log.debug("✅ [handleSend] executeTask 调用成功，结果:", result);
       } catch (executeError: any) {
@@ -964,6 +967,8 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
       let messageContent = "";
       if (result.success) {
         messageContent = result.message || "任务执行完成";
+      } else if (result.termination_reason && totalCount > 0) {
+        messageContent = `${result.message}（已完成 ${totalCount} 个步骤）`;
       } else {
         messageContent = `执行失败: ${result.message || "未知错误"}`;
       }
@@ -1347,7 +1352,7 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
     switch (eventType) {
       // ========== 思考阶段 ==========
       case "thinking":
-        const thinkingContent = eventData.content || "让我想想...";
+        const thinkingContent = eventData.summary || eventData.content || "让我想想...";
         // 思考提示不进入聊天气泡，只在右侧“实时提示”短暂显示
         pushLiveNotice(thinkingContent, eventData.phase);
         if (eventData.phase === "reflecting" || eventData.phase === "preparing_reflection") {
@@ -2435,6 +2440,30 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
               </div>
             )}
             
+            {/* 拖入的文件 */}
+            {attachedFiles.length > 0 && (
+              <div className="mb-3 flex items-center gap-2 px-4 py-2.5 bg-blue-50 dark:bg-blue-900/20 rounded-2xl">
+                <svg className="w-4 h-4 text-blue-600 dark:text-blue-400 flex-shrink-0" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                  <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M15.172 7l-6.586 6.586a2 2 0 102.828 2.828l6.414-6.586a4 4 0 00-5.656-5.656l-6.415 6.585a6 6 0 108.486 8.486L20.5 13" />
+                </svg>
+                <span
+                  className="flex-1 text-sm text-blue-800 dark:text-blue-200 truncate"
+                  title={attachedFiles.map((f) => f.original_path).join("\n")}
+                >
+                  已附加 {attachedFiles.length} 个文件：{attachedFiles.map((f) => f.name).join("、")}
+                </span>
+                <button
+                  onClick={() => removeAttachment().then(setAttachedFiles)}
+                  className="flex-shrink-0 w-6 h-6 flex items-center justify-center rounded-lg hover:bg-blue-200 dark:hover:bg-blue-800/50 transition-colors"
+                  title="移除"
+                >
+                  <svg className="w-3.5 h-3.5 text-blue-600 dark:text-blue-400" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                    <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M6 18L18 6M6 6l12 12" />
+                  </svg>
+                </button>
+              </div>
+            )}
+
             {/* 输入框容器：嵌入式按钮 */}
             <div 
               className={`relative ${isDragging ? "opacity-50" : ""}`}
@@ -2447,6 +2476,10 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
                 value={input}
                 onChange={(e) => {
                   setInput(e.target.value);
+                  if (!warmedUpRef.current && e.target.value.trim()) {
+                    warmedUpRef.current = true;
+                    void warmupAgent();
+                  }
                   // 自动调整高度
                   const textarea = e.target as HTMLTextAreaElement;
                   textarea.style.height = "auto";
@@ -5,6 +5,7 @@
 import React, { useEffect, useRef, useState } from "react";
 import { motion, AnimatePresence } from "framer-motion";
 import { TaskStep, StepResult, TaskStatus, AgentType, ExecutionMode, LiveNotice } from "../types";
+import { revealInFolder } from "../utils/tauri";
 
 interface ProgressPanelProps {
   collapsed: boolean;
@@ -242,6 +243,25 @@ const StepItem: React.FC<{
                       </svg>
                       <span>查看</span>
                     </button>
+                    <button
+                      onClick={async (e) => {
+                        e.stopPropagation();
+                        e.preventDefault();
+                        try {
+                          await revealInFolder(filePath);
+                        } catch (error: any) {
+                          console.error('❌ 在文件夹中显示失败:', error);
+                        }
+                      }}
+                      className="px-2 py-1 rounded text-xs text-gray-600 dark:text-gray-400 hover:text-gray-900 dark:hover:text-gray-100 hover:bg-gray-100 dark:hover:bg-gray-700 transition-colors flex items-center gap-1"
+                      type="button"
+                      title="在文件夹中显示"
+                    >
+                      <svg className="w-3 h-3" fill="none" viewBox="0 0 24 24" stroke="currentColor">
+                        <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M3 7v10a2 2 0 002 2h14a2 2 0 002-2V9a2 2 0 00-2-2h-6l-2-2H5a2 2 0 00-2 2z" />
+                      </svg>
+                      <span>位置</span>
+                    </button>
                   </div>
                 );
               })}
@@ -0,0 +1,103 @@
+/**
+ * 任务小窗：主窗口隐藏时在托盘附近显示当前任务的最新进度
+ *
+ * 由 Rust 侧创建（index.html?view=widget），通过 task-progress / agent-status 事件实时刷新
+ */
+
+import React, { useEffect, useState } from "react";
+import { AgentEvent, AgentStatus } from "../types";
+import { getWidgetState, hideWidget, isTauriEnvironment } from "../utils/tauri";
+import { createLogger } from "../utils/logger";
+
+const log = createLogger("TaskWidget");
+
+/** 把进度事件概括为一行文字 */
+function describeProgress(event: AgentEvent | null): string | null {
+  if (!event) return null;
+  switch (event.type) {
+    case "thinking":
+      return event.data.summary;
+    case "executing":
+    case "success": {
+      const { description, step_index, total_steps } = event.data;
+      const prefix = step_index != null && total_steps ? `[${step_index + 1}/${total_steps}] ` : "";
+      return `${prefix}${description ?? (event.type === "success" ? "步骤完成" : "正在执行")}`;
+    }
+    case "error":
+      return event.message ?? event.data?.message ?? "步骤失败";
+    case "tool_call":
+      return `调用工具 ${event.name}`;
+    case "waiting_for_input":
+    case "request_input":
+      return "等待你的输入…";
+    default:
+      return null;
+  }
+}
+
+function describeStatus(status: AgentStatus | null): string {
+  switch (status?.state) {
+    case "running":
+      return status.instruction;
+    case "error":
+      return `任务失败：${status.message}`;
+    case "server_down":
+      return "Python 服务未运行";
+    default:
+      return "空闲";
+  }
+}
+
+export const TaskWidget: React.FC = () => {
+  const [status, setStatus] = useState<AgentStatus | null>(null);
+  const [progress, setProgress] = useState<AgentEvent | null>(null);
+
+  useEffect(() => {
+    if (!isTauriEnvironment()) return;
+    const unlisteners: Array<() => void> = [];
+    (async () => {
+      try {
+        const { listen } = await import("@tauri-apps/api/event");
+        unlisteners.push(await listen<AgentEvent>("task-progress", (e) => setProgress(e.payload)));
+        unlisteners.push(
+          await listen<AgentStatus>("agent-status", (e) => {
+            setStatus(e.payload);
+            if (e.payload.state === "running") setProgress(null);
+          })
+        );
+        const state = await getWidgetState();
+        if (state) {
+          setStatus(state.status);
+          setProgress(state.progress);
+        }
+      } catch (e) {
+        log.error("初始化任务小窗失败:", e);
+      }
+    })();
+    return () => unlisteners.forEach((unlisten) => unlisten());
+  }, []);
+
+  const running = status?.state === "running";
+  const detail = running ? describeProgress(progress) : null;
+
+  return (
+    <div className="h-screen w-screen flex items-center gap-3 px-4 bg-white/95 dark:bg-gray-900/95 text-gray-900 dark:text-gray-100 select-none">
+      <span
+        className={`h-2.5 w-2.5 shrink-0 rounded-full ${
+          running ? "bg-blue-500 animate-pulse" : status?.state === "error" ? "bg-red-500" : "bg-gray-400"
+        }`}
+      />
+      <div className="min-w-0 flex-1">
+        <div className="truncate text-sm font-medium">{describeStatus(status)}</div>
+        {detail && <div className="truncate text-xs text-gray-500 dark:text-gray-400">{detail}</div>}
+      </div>
+      <button
+        onClick={() => hideWidget()}
+        className="shrink-0 text-gray-400 hover:text-gray-700 dark:hover:text-gray-200"
+        title="隐藏"
+      >
+        ✕
+      </button>
+    </div>
+  );
+};
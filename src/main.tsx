@@ -1,13 +1,17 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import { TaskWidget } from "./components/TaskWidget";
 import "./index.css";
 
+// Rust 侧创建的附属窗口通过 view 参数区分
+const view = new URLSearchParams(window.location.search).get("view");
+
 const rootElement = document.getElementById("root");
 if (rootElement) {
   ReactDOM.createRoot(rootElement).render(
     <React.StrictMode>
-      <App />
+      {view === "widget" ? <TaskWidget /> : <App />}
     </React.StrictMode>
   );
 }
@@ -0,0 +1,29 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ApiCallEvent } from "./ApiCallEvent";
+import type { ConfirmationRequestEvent } from "./ConfirmationRequestEvent";
+import type { FileAccessRequestEvent } from "./FileAccessRequestEvent";
+import type { OcrBlock } from "./OcrBlock";
+import type { ProgressData } from "./ProgressData";
+import type { PromptEvent } from "./PromptEvent";
+import type { ScreenshotRequestEvent } from "./ScreenshotRequestEvent";
+import type { StepData } from "./StepData";
+import type { StepGateEvent } from "./StepGateEvent";
+import type { TaskResult } from "./TaskResult";
+import type { ToolCallEvent } from "./ToolCallEvent";
+import type { UsageEvent } from "./UsageEvent";
+import type { UserInputRequestData } from "./UserInputRequestData";
+import type { WaitingForInputData } from "./WaitingForInputData";
+import type { WarmupAckEvent } from "./WarmupAckEvent";
+
+/**
+ * Python 服务输出的一条事件，按 type 字段区分
+ */
+export type AgentEvent = { "type": "ready", timestamp: number | null, 
+/**
+ * 启动耗时（秒）
+ */
+startup_time: number | null, 
+/**
+ * 协商的 stdout 协议版本，旧版服务不带该字段
+ */
+protocol: number | null, } | { "type": "pong", id: string, timestamp: number | null, } | { "type": "stop_ack", id: string, timestamp: number | null, } | { "type": "shutdown_ack", id: string | null, timestamp: number | null, } | { "type": "validate_result", id: string, timestamp: number | null, ok: boolean, message: string, } | { "type": "reload_ack", id: string, timestamp: number | null, provider: string | null, model: string | null, } | { "type": "warmup_ack" } & WarmupAckEvent | { "type": "transcribe_result", id: string, timestamp: number | null, ok: boolean, text: string, message: string | null, } | { "type": "ocr_result", id: string, timestamp: number | null, ok: boolean, text: string, blocks: Array<OcrBlock>, message: string | null, } | { "type": "thinking", id: string | null, timestamp: number | null, data: ProgressData, } | { "type": "executing", id: string | null, timestamp: number | null, data: StepData, } | { "type": "success", id: string | null, timestamp: number | null, data: StepData, } | { "type": "stream", id: string | null, delta: string, } | { "type": "usage" } & UsageEvent | { "type": "api_call" } & ApiCallEvent | { "type": "prompt" } & PromptEvent | { "type": "confirmation_request" } & ConfirmationRequestEvent | { "type": "file_access_request" } & FileAccessRequestEvent | { "type": "screenshot_request" } & ScreenshotRequestEvent | { "type": "tool_call" } & ToolCallEvent | { "type": "request_input", id: string | null, timestamp: number | null, data: UserInputRequestData, } | { "type": "waiting_for_input", id: string | null, timestamp: number | null, data: WaitingForInputData, } | { "type": "paused", id: string | null, timestamp: number | null, } | { "type": "resumed", id: string | null, timestamp: number | null, } | { "type": "step_gate" } & StepGateEvent | { "type": "result", id: string | null, timestamp: number | null, data: TaskResult, } | { "type": "error", id: string | null, timestamp: number | null, message: string | null, data: StepData | null, };
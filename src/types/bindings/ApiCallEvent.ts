@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * api_call 事件
+ */
+export type ApiCallEvent = { id: string | null, timestamp: number | null, provider: string, model: string, latency_ms: bigint, ok: boolean, error: string | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * confirmation_request 事件
+ */
+export type ConfirmationRequestEvent = { id: string | null, timestamp: number | null, access_id: string, capability: string, step_type: string, 
+/**
+ * 脱敏后的步骤参数摘要
+ */
+summary: string, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * task-warning 事件负载：服务输出了无法识别的事件
+ */
+export type EventWarning = { request_id: string, 
+/**
+ * 原始事件的 type 字段，缺失时为空
+ */
+event_type: string, message: string, };
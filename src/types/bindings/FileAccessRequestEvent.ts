@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * file_access_request 事件
+ */
+export type FileAccessRequestEvent = { id: string | null, timestamp: number | null, access_id: string, path: string, 
+/**
+ * write 或 append
+ */
+operation: string, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * ocr_result 事件中的一行文字及其位置（图片像素坐标）
+ */
+export type OcrBlock = { text: string, x: number, y: number, width: number, height: number, 
+/**
+ * 平均置信度（0~100）
+ */
+confidence: number, };
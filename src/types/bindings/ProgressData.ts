@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * thinking 事件的数据
+ */
+export type ProgressData = { 
+/**
+ * 规划阶段，如 planning、reflecting、multi_agent
+ */
+phase: string, 
+/**
+ * 进度摘要（最多 50 字符）
+ */
+summary: string, };
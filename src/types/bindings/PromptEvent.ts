@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PromptMessage } from "./PromptMessage";
+
+/**
+ * prompt 事件，也是提示词日志中的一条记录
+ */
+export type PromptEvent = { id: string | null, timestamp: number | null, model: string, messages: Array<PromptMessage>, 
+/**
+ * 模型响应文本，无法提取时为空
+ */
+response: string | null, 
+/**
+ * 写入日志时是否截断过内容
+ */
+truncated: boolean, };
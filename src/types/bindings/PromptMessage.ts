@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * prompt 事件中的一条消息
+ */
+export type PromptMessage = { 
+/**
+ * system、user 或 assistant
+ */
+role: string, content: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 截图范围
+ */
+export type ScreenshotMode = "full" | "active_window" | "region";
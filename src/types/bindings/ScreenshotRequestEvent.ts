@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ScreenshotMode } from "./ScreenshotMode";
+
+/**
+ * screenshot_request 事件
+ */
+export type ScreenshotRequestEvent = { id: string | null, timestamp: number | null, access_id: string, mode: ScreenshotMode, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 步骤事件的数据（executing、success、error）
+ */
+export type StepData = { description: string | null, 
+/**
+ * 失败原因，仅 error 事件有
+ */
+message: string | null, step_index: number | null, total_steps: number | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * step_gate 事件
+ */
+export type StepGateEvent = { id: string | null, timestamp: number | null, 
+/**
+ * 刚完成的步骤序号（从 0 开始）
+ */
+step_index: number, total_steps: number, description: string | null, };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StepResult } from "./StepResult";
+import type { TerminationReason } from "./TerminationReason";
+
+/**
+ * 任务执行结果
+ */
+export type TaskResult = { success: boolean, message: string, steps: Array<StepResult>, user_instruction: string, 
+/**
+ * 因超出单任务费用上限而被中止
+ */
+truncated_by_budget: boolean, 
+/**
+ * 提前结束的原因；此时 steps 为已完成的步骤
+ */
+termination_reason: TerminationReason | null, 
+/**
+ * 任务工作目录中的文件（相对路径），任务结束后由 Tauri 填写
+ */
+artifacts: Array<string>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 任务提前结束的原因
+ */
+export type TerminationReason = "user_cancel" | "timeout" | "budget" | "crash";
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * tool_call 事件
+ */
+export type ToolCallEvent = { id: string | null, timestamp: number | null, call_id: string, 
+/**
+ * 工具名，如 shell
+ */
+name: string, args: Record<string, any>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * usage 事件
+ */
+export type UsageEvent = { id: string | null, timestamp: number | null, model: string, input_tokens: bigint, output_tokens: bigint, };
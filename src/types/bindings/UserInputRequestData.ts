@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * request_input 事件的数据
+ */
+export type UserInputRequestData = { id: string, 
+/**
+ * 输入类型，如 login、captcha、text
+ */
+type: string, title: string, message: string | null, fields: Array<any>, captchaImage: string | null, };
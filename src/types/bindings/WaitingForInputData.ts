@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * waiting_for_input 事件的数据
+ */
+export type WaitingForInputData = { request_id: string, 
+/**
+ * 已等待秒数
+ */
+elapsed: bigint, 
+/**
+ * 剩余秒数
+ */
+remaining: bigint, };
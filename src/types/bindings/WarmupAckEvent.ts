@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WarmupStep } from "./WarmupStep";
+
+/**
+ * warmup_ack 事件
+ */
+export type WarmupAckEvent = { id: string, timestamp: number | null, 
+/**
+ * 预热总耗时（毫秒）
+ */
+elapsed_ms: bigint, steps: Array<WarmupStep>, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * warmup_ack 事件中一项资源的预热结果
+ */
+export type WarmupStep = { 
+/**
+ * 资源名称，如 embedding_model、memory
+ */
+name: string, ok: boolean, elapsed_ms: bigint, error: string | null, };
@@ -4,6 +4,8 @@
  * 支持浏览器环境降级（使用localStorage）
  */
 
+import type { ActiveWindow, AttachedFile, AutostartStatus, BackupInfo, CreatedEvent, DiagnosticsReport, EmailFilter, EmailOAuthStatus, EmailTestResult, EventList, EventRange, FetchedEmails, ImportedSession, InstructionTemplate, Locale, LocalRuntime, McpServerConfig, McpTestResult, McpTool, NewCalendarEvent, OcrOutput, ProposedPlan, ProxyTestResult, ReportFormat, RestoreResult, ScreenshotMode, ServerLogLine, SessionTranscript, SettingsBundle, SettingsImportReport, TelemetryPreview, TemplateInput, TrashedFile, UndoReport, UpdateInfo, UsagePeriod, UsageStats, WidgetState } from "../types";
+
 // 检测是否在Tauri环境中
 export function isTauriEnvironment(): boolean {
   if (typeof window === "undefined") return false;
@@ -16,11 +18,15 @@ async function safeInvoke(command: string, args?: any): Promise<any> {
     throw new Error("Tauri环境不可用");
   }
   
+  const { invoke } = await import("@tauri-apps/api/core");
   try {
-    const { invoke } = await import("@tauri-apps/api/core");
     return await invoke(command, args);
   } catch (e) {
     console.error(`调用Tauri命令失败 [${command}]:`, e);
+    // Rust 返回的错误文本按界面语言翻译，翻译失败时保留原文（结构化错误的 message 已翻译）
+    if (typeof e === "string") {
+      throw await invoke<string>("localize_message", { message: e }).catch(() => e);
+    }
     throw e;
   }
 }
@@ -81,12 +87,596 @@ export async function executeTask(instruction: string, context?: any): Promise<a
 }
 
 /**
- * 停止当前正在执行的任务
+ * 逐步执行：每个步骤完成后发送 step-completed-awaiting-next 事件并等待，
+ * 调用 continueTask 继续下一步或 abortTask 中止
+ */
+export async function executeTaskInteractive(instruction: string, context?: any): Promise<any> {
+  if (!isTauriEnvironment()) {
+    throw new Error("执行任务需要在Tauri桌面应用中运行。请使用 'npm run tauri:dev' 启动完整应用。");
+  }
+  return await safeInvoke("execute_task_interactive", { instruction, context: context || null });
+}
+
+/**
+ * 逐步执行：继续下一步
+ */
+export async function continueTask(requestId: string): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  await safeInvoke("continue_task", { requestId });
+}
+
+/**
+ * 逐步执行：中止任务，已完成的步骤作为部分结果返回
+ */
+export async function abortTask(requestId: string): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  await safeInvoke("abort_task", { requestId });
+}
+
+/**
+ * 只规划不执行：返回规划的步骤供审阅，不会修改文件或发送邮件
+ */
+export async function planTask(instruction: string, context?: any): Promise<ProposedPlan> {
+  if (!isTauriEnvironment()) {
+    throw new Error("执行任务需要在Tauri桌面应用中运行。请使用 'npm run tauri:dev' 启动完整应用。");
+  }
+  return await safeInvoke("plan_task", { instruction, context: context || null });
+}
+
+/**
+ * 执行审阅过的规划，只执行批准的步骤（按 index，按原顺序执行）
+ */
+export async function executePlan(planId: string, approvedSteps: number[]): Promise<any> {
+  if (!isTauriEnvironment()) {
+    throw new Error("执行任务需要在Tauri桌面应用中运行。请使用 'npm run tauri:dev' 启动完整应用。");
+  }
+  return await safeInvoke("execute_plan", { planId, approvedSteps });
+}
+
+/**
+ * 以脱离模式执行长时间任务：退出应用后任务继续运行，下次启动时自动接上
+ *
+ * 立即返回任务 ID；进度照常以 task-* 事件发送，结束时发送 task-detached-finished。
+ */
+export async function executeTaskDetached(instruction: string, context?: any): Promise<string> {
+  if (!isTauriEnvironment()) {
+    throw new Error("执行任务需要在Tauri桌面应用中运行。请使用 'npm run tauri:dev' 启动完整应用。");
+  }
+  return await safeInvoke("execute_task_detached", { instruction, context: context || null });
+}
+
+/**
+ * 停止脱离任务（在下一个步骤前生效）
+ */
+export async function stopDetachedTask(requestId: string): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  await safeInvoke("stop_detached_task", { requestId });
+}
+
+/**
+ * 撤销任务的文件修改（fs 工具与获准的文件写入），按相反顺序恢复；原位置已被占用的项跳过
+ */
+export async function undoTask(taskId: string): Promise<UndoReport> {
+  if (!isTauriEnvironment()) {
+    throw new Error("撤销任务需要在Tauri桌面应用中运行。");
+  }
+  return await safeInvoke("undo_task", { taskId });
+}
+
+/**
+ * 回收区中 Agent 删除的文件（按删除时间从新到旧）
+ */
+export async function listTrashedFiles(): Promise<TrashedFile[]> {
+  if (!isTauriEnvironment()) return [];
+  return await safeInvoke("list_trashed_files");
+}
+
+/**
+ * 把回收区中的文件放回原处，返回恢复后的路径；原位置已有同名文件时失败
+ */
+export async function restoreTrashedFile(taskId: string, trashedPath: string): Promise<string> {
+  if (!isTauriEnvironment()) {
+    throw new Error("恢复文件需要在Tauri桌面应用中运行。");
+  }
+  return await safeInvoke("restore_trashed_file", { taskId, trashedPath });
+}
+
+/**
+ * 截图并保存到沙盒，返回图片路径
+ *
+ * @param mode full（默认）、active_window 或 region（框选区域，Windows 不支持）
+ */
+export async function captureScreenshot(mode?: ScreenshotMode): Promise<string> {
+  if (!isTauriEnvironment()) {
+    throw new Error("截图需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("capture_screenshot", { mode: mode || null });
+}
+
+/**
+ * 移除拖入的文件，path 为空时全部移除
+ *
+ * @returns 剩余的附件
+ */
+export async function removeAttachment(path?: string): Promise<AttachedFile[]> {
+  if (!isTauriEnvironment()) return [];
+  return await safeInvoke("remove_attachment", { path: path || null });
+}
+
+/**
+ * 在 Finder / 资源管理器 / 文件管理器中显示文件
+ *
+ * 路径须位于沙盒或已授权的目录内；失败时抛出 FileActionError（kind 为 not_found 表示文件已被移动或删除）。
+ */
+export async function revealInFolder(path: string): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  await safeInvoke("reveal_in_folder", { path });
+}
+
+/**
+ * 导出任务报告（指令、步骤、输出和产物），返回报告路径
+ *
+ * @param dest 沙盒内的保存路径，默认 sandbox/reports/ 下；PDF 需要本机安装 Chrome、Chromium 或 Edge
+ */
+export async function exportTaskReport(taskId: string, format: ReportFormat, dest?: string): Promise<string> {
+  if (!isTauriEnvironment()) {
+    throw new Error("导出报告需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("export_task_report", { taskId, format, dest: dest || null });
+}
+
+/**
+ * 探测本机的 Ollama 和 LM Studio，列出已安装的模型
+ *
+ * 选中模型后以 provider（ollama / lmstudio）、model 和 base_url 调用 save_config 即可切换
+ */
+export async function detectLocalModels(): Promise<LocalRuntime[]> {
+  if (!isTauriEnvironment()) {
+    return [];
+  }
+  return await safeInvoke("detect_local_models");
+}
+
+/**
+ * 获取模型用量统计（token 数与估算费用，按天和按模型汇总）
+ */
+export async function getUsageStats(period: UsagePeriod = "today"): Promise<UsageStats | null> {
+  if (!isTauriEnvironment()) {
+    return null;
+  }
+  return await safeInvoke("get_usage_stats", { period });
+}
+
+/**
+ * 放行下一个任务：跳过一次每小时任务数和每日费用上限检查
+ */
+export async function overrideBudgetOnce(): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  await safeInvoke("override_budget_once");
+}
+
+/**
+ * 测试能否经代理连通当前提供商的 API（只建立连接，不发起 API 请求）
+ *
+ * 不传参数时使用已保存的代理设置，便于保存前先测试
+ */
+export async function testProxy(proxyUrl?: string, noProxy?: string): Promise<ProxyTestResult> {
+  if (!isTauriEnvironment()) {
+    throw new Error("测试代理需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("test_proxy", { proxyUrl: proxyUrl ?? null, noProxy: noProxy ?? null });
+}
+
+/**
+ * 导出配置、定时任务、脱敏规则、工作流模板和快捷指令到指定文件（不含 API Key 等敏感信息）
+ */
+export async function exportSettings(path: string): Promise<SettingsBundle> {
+  if (!isTauriEnvironment()) {
+    throw new Error("导出设置需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("export_settings", { path });
+}
+
+/**
+ * 导入设置包：敏感字段沿用本机已有的值，missing_secrets 中的字段需要重新填写
+ */
+export async function importSettings(path: string): Promise<SettingsImportReport> {
+  if (!isTauriEnvironment()) {
+    throw new Error("导入设置需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("import_settings", { path });
+}
+
+/**
+ * 导出会话记录（指令、回复、步骤和产物，已脱敏），path 以 .md 结尾时写 Markdown，否则写 JSON
+ */
+export async function exportSession(sessionId: string, path: string): Promise<SessionTranscript> {
+  if (!isTauriEnvironment()) {
+    throw new Error("导出会话需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("export_session", { sessionId, path });
+}
+
+/**
+ * 导入 JSON 会话记录：新建会话并恢复对话记忆，可在新会话中继续追问
+ */
+export async function importSession(path: string): Promise<ImportedSession> {
+  if (!isTauriEnvironment()) {
+    throw new Error("导入会话需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("import_session", { path });
+}
+
+/**
+ * 查看匿名使用统计下次将发送的完整内容（未开启时 batch 为 null）
+ */
+export async function getTelemetryPreview(): Promise<TelemetryPreview | null> {
+  if (!isTauriEnvironment()) return null;
+  return await safeInvoke("get_telemetry_preview");
+}
+
+/**
+ * 开启或关闭匿名使用统计（默认关闭），关闭时丢弃未发送的事件
+ */
+export async function setTelemetryEnabled(enabled: boolean): Promise<void> {
+  if (!isTauriEnvironment()) {
+    throw new Error("使用统计设置需要在Tauri桌面应用中运行");
+  }
+  await safeInvoke("set_telemetry_enabled", { enabled });
+}
+
+/**
+ * 检查运行环境（Python、Agent 脚本、沙盒、网络、钥匙串、磁盘空间），首次启动和托盘 "诊断" 菜单（open-diagnostics 事件）时调用
+ */
+export async function runDiagnostics(): Promise<DiagnosticsReport> {
+  if (!isTauriEnvironment()) {
+    throw new Error("环境诊断需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("run_diagnostics");
+}
+
+/**
+ * 保存并立即切换界面语言（托盘、通知和错误信息），切换后触发 locale-changed 事件
+ */
+export async function setLocale(locale: Locale): Promise<void> {
+  if (!isTauriEnvironment()) {
+    throw new Error("切换界面语言需要在Tauri桌面应用中运行");
+  }
+  await safeInvoke("set_locale", { locale });
+}
+
+/**
+ * 开启或关闭调试控制台：开启期间 Python 服务的 stderr 日志以 server-log 事件实时推送，
+ * 开启时返回最近 tail 行（默认 200 行）；关闭控制台时应传 active=false
+ */
+export async function subscribeServerLogs(active: boolean, tail?: number): Promise<ServerLogLine[]> {
+  if (!isTauriEnvironment()) {
+    throw new Error("调试控制台需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("subscribe_server_logs", { active, tail });
+}
+
+/**
+ * 立即备份数据目录（~/.deskjarvis），includeSandbox 为 true 时包含沙盒中的任务产物
+ */
+export async function createBackup(includeSandbox = false): Promise<BackupInfo> {
+  if (!isTauriEnvironment()) {
+    throw new Error("备份需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("create_backup", { includeSandbox });
+}
+
+/**
+ * 列出所有备份（从新到旧）
+ */
+export async function listBackups(): Promise<BackupInfo[]> {
+  if (!isTauriEnvironment()) {
+    return [];
+  }
+  return await safeInvoke("list_backups");
+}
+
+/**
+ * 从备份恢复数据目录（恢复前会自动备份当前数据），完成后应提示用户重启应用
  */
-export async function stopTask(): Promise<void> {
+export async function restoreBackup(id: string): Promise<RestoreResult> {
+  if (!isTauriEnvironment()) {
+    throw new Error("恢复备份需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("restore_backup", { id });
+}
+
+/**
+ * 重启应用（恢复备份后使用）
+ */
+export async function restartApp(): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  await safeInvoke("restart_app");
+}
+
+/**
+ * 获取开机自启状态
+ */
+export async function getAutostartStatus(): Promise<AutostartStatus | null> {
+  if (!isTauriEnvironment()) {
+    return null;
+  }
+  return await safeInvoke("get_autostart_status");
+}
+
+/**
+ * 开启或关闭开机自启（启动后是否只驻留托盘由配置 launch_minimized 决定）
+ */
+export async function setAutostart(enabled: boolean): Promise<AutostartStatus> {
+  if (!isTauriEnvironment()) {
+    throw new Error("开机自启需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("set_autostart", { enabled });
+}
+
+/**
+ * 检查当前发布渠道（配置 release_channel）是否有新版本
+ */
+export async function checkForUpdates(): Promise<UpdateInfo | null> {
+  if (!isTauriEnvironment()) {
+    return null;
+  }
+  return await safeInvoke("check_for_updates");
+}
+
+/**
+ * 下载检查到的新版本，进度通过 update-download-progress 事件推送，返回版本号
+ */
+export async function downloadUpdate(): Promise<string> {
+  if (!isTauriEnvironment()) {
+    throw new Error("更新需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("download_update");
+}
+
+/**
+ * 安装已下载的新版本并重启应用（有任务执行时会被拒绝）
+ */
+export async function installUpdateAndRestart(): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  await safeInvoke("install_update_and_restart");
+}
+
+/**
+ * 在托盘附近显示任务小窗
+ */
+export async function showWidget(): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  await safeInvoke("show_widget");
+}
+
+/**
+ * 隐藏任务小窗
+ */
+export async function hideWidget(): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  await safeInvoke("hide_widget");
+}
+
+/**
+ * 获取当前 Agent 状态和最近一条进度事件（任务小窗打开时调用）
+ */
+export async function getWidgetState(): Promise<WidgetState | null> {
+  if (!isTauriEnvironment()) return null;
+  return await safeInvoke("get_widget_state");
+}
+
+/**
+ * 重置主窗口布局：清除保存的位置和大小，恢复默认尺寸并居中
+ */
+export async function resetWindowLayout(): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  await safeInvoke("reset_window_layout");
+}
+
+/**
+ * 开始用默认麦克风录音
+ */
+export async function startRecording(): Promise<void> {
+  if (!isTauriEnvironment()) {
+    throw new Error("语音输入需要在Tauri桌面应用中运行");
+  }
+  await safeInvoke("start_recording");
+}
+
+/**
+ * 结束录音并转写，返回识别出的文字（用于预填指令输入框）
+ */
+export async function stopRecording(): Promise<string> {
+  if (!isTauriEnvironment()) {
+    throw new Error("语音输入需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("stop_recording");
+}
+
+/**
+ * 用系统语音朗读文字（打断正在进行的朗读）
+ */
+export async function speak(text: string): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  await safeInvoke("speak", { text });
+}
+
+/**
+ * 停止朗读
+ */
+export async function stopSpeaking(): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  await safeInvoke("stop_speaking");
+}
+
+/**
+ * 识别图片中的文字，返回全文和逐行位置
+ *
+ * @param path 图片路径（支持 ~）
+ * @param lang 语言代码，如 "zh"、"en"、"zh+en"，不传时中英文混合识别
+ */
+export async function ocrImage(path: string, lang?: string): Promise<OcrOutput> {
+  if (!isTauriEnvironment()) {
+    throw new Error("文字识别需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("ocr_image", { path, lang: lang || null });
+}
+
+/**
+ * 最近记录的前台窗口，执行任务时在 context 中传 capture_active_window: true 即可带入
+ */
+export async function getActiveWindow(): Promise<ActiveWindow | null> {
+  if (!isTauriEnvironment()) return null;
+  return await safeInvoke("get_active_window");
+}
+
+/**
+ * 用 SMTP 服务器完成一次真实的握手和登录（不发送邮件），参数为表单中尚未保存的值，不传时使用已保存的配置
+ */
+export async function testEmailConfig(options?: {
+  smtpServer?: string;
+  smtpPort?: number;
+  sender?: string;
+  password?: string;
+}): Promise<EmailTestResult> {
+  if (!isTauriEnvironment()) {
+    throw new Error("测试邮件配置需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("test_email_config", {
+    smtpServer: options?.smtpServer ?? null,
+    smtpPort: options?.smtpPort ?? null,
+    sender: options?.sender ?? null,
+    password: options?.password ?? null,
+  });
+}
+
+/**
+ * 打开浏览器完成邮箱 OAuth2 授权（需先保存 email_auth_method、email_oauth_provider 和客户端 ID），
+ * 用户在浏览器中完成授权后返回
+ */
+export async function startEmailOAuth(): Promise<EmailOAuthStatus> {
+  if (!isTauriEnvironment()) {
+    throw new Error("邮箱授权需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("start_email_oauth");
+}
+
+/**
+ * 邮箱 OAuth2 授权状态
+ */
+export async function getEmailOAuthStatus(): Promise<EmailOAuthStatus | null> {
+  if (!isTauriEnvironment()) return null;
+  return await safeInvoke("get_email_oauth_status");
+}
+
+/**
+ * 删除已保存的邮箱 OAuth2 令牌
+ */
+export async function signOutEmailOAuth(): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  await safeInvoke("sign_out_email_oauth");
+}
+
+/**
+ * 按条件读取最近的邮件（只读，不会标为已读），不传条件时读取收件箱最近 20 封的邮件头
+ */
+export async function fetchRecentEmails(filter?: EmailFilter): Promise<FetchedEmails> {
+  if (!isTauriEnvironment()) {
+    throw new Error("读取邮件需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("fetch_recent_emails", { filter: filter ?? null });
+}
+
+/**
+ * 列出时间范围内（默认今后 7 天）各日历的日程，重复日程展开为每一次
+ */
+export async function listUpcomingEvents(range?: EventRange): Promise<EventList> {
+  if (!isTauriEnvironment()) {
+    return { events: [], errors: [] };
+  }
+  return await safeInvoke("list_upcoming_events", { range: range ?? null });
+}
+
+/**
+ * 新建日程，返回时间上冲突的已有日程
+ */
+export async function createCalendarEvent(event: NewCalendarEvent): Promise<CreatedEvent> {
+  if (!isTauriEnvironment()) {
+    throw new Error("新建日程需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("create_calendar_event", { event });
+}
+
+/**
+ * 列出指令模板
+ */
+export async function listTemplates(): Promise<InstructionTemplate[]> {
+  if (!isTauriEnvironment()) {
+    return [];
+  }
+  return await safeInvoke("list_templates");
+}
+
+/**
+ * 新建（不带 id）或更新模板，变化后发送 templates-changed 事件
+ */
+export async function saveTemplate(template: TemplateInput): Promise<InstructionTemplate> {
+  if (!isTauriEnvironment()) {
+    throw new Error("保存模板需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("save_template", { template });
+}
+
+/**
+ * 删除模板
+ */
+export async function deleteTemplate(id: string): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  await safeInvoke("delete_template", { id });
+}
+
+/**
+ * 填入参数执行模板，未填的参数使用模板的默认值，仍缺少时报错
+ */
+export async function runTemplate(id: string, params?: Record<string, string>): Promise<any> {
+  if (!isTauriEnvironment()) {
+    throw new Error("执行任务需要在Tauri桌面应用中运行。请使用 'npm run tauri:dev' 启动完整应用。");
+  }
+  return await safeInvoke("run_template", { id, params: params ?? null });
+}
+
+/**
+ * 列出 MCP 工具
+ *
+ * @param server 服务器名称，不传时列出全部已启用服务器（跳过不可用的）
+ */
+export async function listMcpTools(server?: string): Promise<McpTool[]> {
+  if (!isTauriEnvironment()) {
+    return [];
+  }
+  return await safeInvoke("list_mcp_tools", { server: server || null });
+}
+
+/**
+ * 测试 MCP 服务器配置（握手并列出工具，不保存配置）
+ */
+export async function testMcpServer(server: McpServerConfig): Promise<McpTestResult> {
+  if (!isTauriEnvironment()) {
+    throw new Error("测试 MCP 服务器需要在Tauri桌面应用中运行");
+  }
+  return await safeInvoke("test_mcp_server", { server });
+}
+
+/**
+ * 停止正在执行的任务
+ *
+ * @param requestId 要停止的任务 ID；省略时停止唯一正在执行的任务（有多个任务时报错）
+ */
+export async function stopTask(requestId?: string): Promise<void> {
   if (isTauriEnvironment()) {
     try {
-      await safeInvoke("stop_task");
+      await safeInvoke("stop_task", { requestId: requestId ?? null });
     } catch (error) {
       console.error("停止任务失败:", error);
       throw error;
@@ -96,6 +686,20 @@ export async function stopTask(): Promise<void> {
   }
 }
 
+/**
+ * 预热 Agent（加载嵌入模型、记忆库等），用户开始输入时调用
+ *
+ * 正在执行任务时后端直接跳过；失败不影响后续任务，只记录日志。
+ */
+export async function warmupAgent(): Promise<void> {
+  if (!isTauriEnvironment()) return;
+  try {
+    await safeInvoke("warmup_agent");
+  } catch (error) {
+    console.warn("预热 Agent 失败:", error);
+  }
+}
+
 /**
  * 获取配置
  * 
@@ -0,0 +1,75 @@
+"""
+能力审批模块单元测试
+"""
+
+import pytest
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.capabilities import capability_of, check_step, set_requester
+
+
+class TestCapabilityOf:
+    """capability_of 测试"""
+
+    def test_mutating_steps(self):
+        """测试修改类步骤的能力归类"""
+        assert capability_of("file_write") == "file_write"
+        assert capability_of("python_script") == "shell"
+        assert capability_of("send_email") == "email"
+        assert capability_of("keyboard_type") == "input"
+        assert capability_of("set_volume") == "system"
+
+    def test_read_only_steps(self):
+        """测试只读步骤不需要能力"""
+        assert capability_of("file_read") is None
+        assert capability_of("search_emails") is None
+        assert capability_of("screenshot_desktop") is None
+
+
+class TestCheckStep:
+    """check_step 测试"""
+
+    @pytest.fixture(autouse=True)
+    def clear_requester(self):
+        yield
+        set_requester(None)
+
+    def test_no_requester_allows(self):
+        """测试未注册回调时不做限制"""
+        assert check_step("send_email") is None
+
+    def test_read_only_skips_requester(self):
+        """测试只读步骤不询问回调"""
+        calls = []
+        set_requester(lambda capability, step_type, summary: calls.append(step_type) or False)
+
+        assert check_step("file_read") is None
+        assert calls == []
+
+    def test_denied(self):
+        """测试回调拒绝时返回原因"""
+        set_requester(lambda capability, step_type, summary: False)
+
+        reason = check_step("send_email")
+
+        assert reason is not None
+        assert "send_email" in reason
+
+    def test_summary_passed(self):
+        """测试参数摘要传给回调"""
+        summaries = []
+        set_requester(lambda capability, step_type, summary: summaries.append(summary) or True)
+
+        assert check_step("file_delete", {"file_path": "/tmp/a.txt"}) is None
+        assert "/tmp/a.txt" in summaries[0]
+
+    def test_requester_error_denies(self):
+        """测试回调异常视为拒绝"""
+        def broken(capability, step_type, summary):
+            raise RuntimeError("boom")
+        set_requester(broken)
+
+        assert check_step("mouse_click") is not None
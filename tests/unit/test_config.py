@@ -221,6 +221,62 @@ class TestConfigIOAndProperties:
         cfg6 = Config(config_path=str(cfg_path))
         assert cfg6.email_imap_server == "imap.custom.com"
 
+    def test_local_provider_without_api_key(self, tmp_path: Path):
+        """测试本地模型不需要 API 密钥，未设置地址时使用默认地址"""
+        cfg = Config(config_path=str(tmp_path / "config.json"))
+        cfg.set("api_key", "")
+        cfg.set("provider", "ollama")
+        assert cfg.is_local_provider is True
+        assert cfg.validate() is True
+        assert cfg.base_url == "http://localhost:11434/v1"
+
+        cfg.set("base_url", "http://192.168.1.5:11434/v1")
+        assert cfg.base_url == "http://192.168.1.5:11434/v1"
+
+    def test_remote_provider_base_url(self, tmp_path: Path):
+        """测试远程提供商未设置地址时 base_url 为空"""
+        cfg = Config(config_path=str(tmp_path / "config.json"))
+        cfg.set("provider", "openai")
+        cfg.set("base_url", "")
+        assert cfg.is_local_provider is False
+        assert cfg.base_url is None
+
+
+class TestEnvOverrides:
+    """环境变量覆盖测试"""
+
+    def test_env_overrides(self):
+        """测试读取 DESKJARVIS_* 覆盖"""
+        overrides = Config.env_overrides({
+            "DESKJARVIS_SANDBOX": "/tmp/test-sandbox",
+            "DESKJARVIS_PROVIDER": " ollama ",
+            "DESKJARVIS_MODEL": "",
+            "DESKJARVIS_AUTO_CONFIRM": "yes",
+        })
+        assert overrides == {"sandbox_path": "/tmp/test-sandbox", "provider": "ollama", "auto_confirm": True}
+
+    def test_invalid_bool_ignored(self):
+        """测试无效的布尔值被忽略"""
+        assert Config.env_overrides({"DESKJARVIS_AUTO_CONFIRM": "maybe"}) == {}
+
+    def test_override_not_saved(self, tmp_path: Path):
+        """测试覆盖值只影响读取，不写回配置文件，显式设置优先"""
+        import os
+
+        cfg_path = tmp_path / "config.json"
+        os.environ["DESKJARVIS_SANDBOX"] = str(tmp_path / "sandbox")
+        try:
+            cfg = Config(config_path=str(cfg_path))
+            assert cfg.sandbox_path == tmp_path / "sandbox"
+            cfg.save()
+            saved = json.loads(cfg_path.read_text(encoding="utf-8"))
+            assert saved["sandbox_path"] != str(tmp_path / "sandbox")
+
+            cfg.set("sandbox_path", "/tmp/explicit")
+            assert cfg.sandbox_path == Path("/tmp/explicit")
+        finally:
+            del os.environ["DESKJARVIS_SANDBOX"]
+
 
 if __name__ == "__main__":
     pytest.main([__file__, "-v"])
@@ -0,0 +1,63 @@
+"""
+只规划不执行（dry-run）模块单元测试
+"""
+
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.dry_run import approved_plan, describe_plan
+from agent.tools.resume import apply_resume
+
+
+class TestDescribePlan:
+    """describe_plan 测试"""
+
+    def test_adds_capability(self):
+        """测试为每个步骤附加能力，只读步骤为 None，不修改原步骤"""
+        steps = [
+            {"type": "file_move", "params": {"source": "a", "target": "b"}},
+            {"type": "file_read", "params": {"path": "a"}},
+        ]
+
+        described = describe_plan(steps)
+
+        assert described[0]["capability"] == "file_write"
+        assert described[0]["params"] == {"source": "a", "target": "b"}
+        assert described[1]["capability"] is None
+        assert "capability" not in steps[0]
+
+    def test_skips_invalid(self):
+        """测试忽略不是字典的项"""
+        assert describe_plan(None) == []
+        assert describe_plan(["bad", {"type": "send_email"}]) == [
+            {"type": "send_email", "capability": "email"}
+        ]
+
+
+class TestApprovedPlan:
+    """approved_plan 测试"""
+
+    def test_not_approved(self):
+        """测试没有批准的计划时返回 None"""
+        assert approved_plan(None) is None
+        assert approved_plan({"a": 1}) is None
+        assert approved_plan({"approved_plan": "bad"}) is None
+
+    def test_strips_review_fields(self):
+        """测试去掉审阅时附加的字段"""
+        context = {"approved_plan": describe_plan([{"type": "file_delete", "params": {"path": "a"}}])}
+
+        assert approved_plan(context) == [{"type": "file_delete", "params": {"path": "a"}}]
+
+    def test_empty_plan(self):
+        """测试批准空计划时返回空列表而不是 None"""
+        assert approved_plan({"approved_plan": []}) == []
+
+    def test_resume_skips_completed(self):
+        """测试重试时跳过已完成的步骤"""
+        steps = [{"type": "file_write"}, {"type": "send_email"}, {"type": "file_delete"}]
+        context = apply_resume({"approved_plan": steps}, 1, steps[:1])
+
+        assert approved_plan(context) == steps[1:]
@@ -0,0 +1,61 @@
+"""
+邮箱 OAuth2 凭据模块单元测试
+"""
+
+import time
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools import email_auth
+
+
+def make_credential(**fields):
+    return {
+        "user": "me@gmail.com",
+        "access_token": "ya29.token",
+        "expires_at": int(time.time() * 1000) + 3600 * 1000,
+        **fields,
+    }
+
+
+class TestCredential:
+    """set_credential / get_credential 测试"""
+
+    def teardown_method(self):
+        email_auth.set_credential(None)
+
+    def test_without_credential(self):
+        """测试未启用 OAuth2 时没有凭据"""
+        assert email_auth.get_credential("me@gmail.com") is None
+
+    def test_matches_user(self):
+        """测试只对授权的邮箱使用凭据（不区分大小写）"""
+        email_auth.set_credential(make_credential())
+
+        assert email_auth.get_credential("Me@Gmail.com")["access_token"] == "ya29.token"
+        assert email_auth.get_credential() is not None
+        assert email_auth.get_credential("other@gmail.com") is None
+
+    def test_expired(self):
+        """测试过期的令牌不再使用"""
+        email_auth.set_credential(make_credential(expires_at=int(time.time() * 1000) - 1000))
+
+        assert email_auth.get_credential("me@gmail.com") is None
+
+    def test_ignores_empty_token(self):
+        """测试缺少访问令牌的凭据视为未启用"""
+        email_auth.set_credential(make_credential(access_token=""))
+
+        assert email_auth.get_credential() is None
+
+
+class TestXoauth2String:
+    """xoauth2_string 测试"""
+
+    def test_format(self):
+        """测试 SASL XOAUTH2 初始响应格式"""
+        assert email_auth.xoauth2_string(make_credential()) == (
+            "user=me@gmail.com\x01auth=Bearer ya29.token\x01\x01"
+        )
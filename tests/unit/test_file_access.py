@@ -0,0 +1,71 @@
+"""
+文件访问审批模块单元测试
+"""
+
+import json
+import pytest
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.exceptions import FileManagerError
+from agent.tools.file_access import require_access, set_requester, wait_for_decision
+
+
+class TestRequireAccess:
+    """require_access 测试"""
+
+    @pytest.fixture(autouse=True)
+    def clear_requester(self):
+        yield
+        set_requester(None)
+
+    def test_no_requester_allows(self):
+        """测试未注册回调时不做限制"""
+        require_access(Path("/tmp/a.txt"), "write")
+
+    def test_approved(self):
+        """测试回调批准"""
+        calls = []
+        set_requester(lambda path, op: calls.append((path, op)) or True)
+
+        require_access(Path("/tmp/a.txt"), "write")
+
+        assert calls == [("/tmp/a.txt", "write")]
+
+    def test_denied(self):
+        """测试回调拒绝"""
+        set_requester(lambda path, op: False)
+
+        with pytest.raises(FileManagerError):
+            require_access(Path("/tmp/a.txt"), "delete")
+
+    def test_requester_error_denies(self):
+        """测试回调异常视为拒绝"""
+        def broken(path, op):
+            raise RuntimeError("boom")
+        set_requester(broken)
+
+        with pytest.raises(FileManagerError):
+            require_access(Path("/tmp/a.txt"), "write")
+
+
+class TestWaitForDecision:
+    """wait_for_decision 测试"""
+
+    def test_reads_and_removes_response(self, tmp_path):
+        """测试读取审批结果后删除文件"""
+        response = tmp_path / "access_1.json"
+        response.write_text(json.dumps({"access_id": "access_1", "approved": True}))
+
+        assert wait_for_decision(tmp_path, "access_1", timeout=1) is True
+        assert not response.exists()
+
+    def test_timeout_denies(self, tmp_path):
+        """测试超时视为拒绝"""
+        assert wait_for_decision(tmp_path, "access_2", timeout=0.1) is False
+
+    def test_cancelled_denies(self, tmp_path):
+        """测试任务取消视为拒绝"""
+        assert wait_for_decision(tmp_path, "access_3", timeout=5, is_cancelled=lambda: True) is False
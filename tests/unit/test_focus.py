@@ -0,0 +1,45 @@
+"""
+专注时段模块单元测试
+"""
+
+import json
+import pytest
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.focus import focus_ends_at, is_focus_active
+
+
+def write_session(path: Path, ends_at_ms: int) -> Path:
+    path.write_text(json.dumps({"started_at": 0, "ends_at": ends_at_ms, "allow_interactive": True}))
+    return path
+
+
+class TestFocus:
+    """focus_ends_at / is_focus_active 测试"""
+
+    def test_missing_file(self, tmp_path):
+        """测试没有会话文件"""
+        assert not is_focus_active(tmp_path / "focus.json")
+
+    def test_active_session(self, tmp_path):
+        """测试未到期的会话"""
+        path = write_session(tmp_path / "focus.json", 2_000_000)
+
+        assert is_focus_active(path, now=1_000)
+        assert focus_ends_at(path, now=1_000) == 2_000
+
+    def test_expired_session(self, tmp_path):
+        """测试已到期的会话"""
+        path = write_session(tmp_path / "focus.json", 2_000_000)
+
+        assert not is_focus_active(path, now=3_000)
+
+    def test_corrupt_file(self, tmp_path):
+        """测试损坏的会话文件"""
+        path = tmp_path / "focus.json"
+        path.write_text("not json")
+
+        assert not is_focus_active(path)
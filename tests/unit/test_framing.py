@@ -0,0 +1,49 @@
+"""
+stdout 协议分帧单元测试
+"""
+
+import pytest
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.framing import FRAME_PREFIX, decode_line, encode_event, negotiate_version
+
+
+class TestNegotiateVersion:
+    """negotiate_version 测试"""
+
+    def test_missing_falls_back_to_v1(self):
+        """测试旧版 Tauri 未声明版本"""
+        assert negotiate_version(None) == 1
+
+    def test_invalid_falls_back_to_v1(self):
+        """测试无效的版本号"""
+        assert negotiate_version("abc") == 1
+
+    def test_caps_at_supported_version(self):
+        """测试声明的版本高于本服务支持的版本"""
+        assert negotiate_version("99") == 2
+
+
+class TestEncodeEvent:
+    """encode_event / decode_line 测试"""
+
+    def test_v1_is_plain_json(self):
+        """测试版本 1 输出原始 JSON"""
+        assert encode_event({"type": "pong", "id": "p1"}, 1) == '{"type": "pong", "id": "p1"}'
+
+    def test_v2_round_trip(self):
+        """测试版本 2 编码后可还原，且内容中的换行不会拆行"""
+        event = {"type": "result", "data": {"message": "第一行\n第二行"}}
+        line = encode_event(event, 2)
+
+        assert line.startswith(FRAME_PREFIX)
+        assert "\n" not in line
+        assert decode_line(line) == event
+
+    def test_decode_rejects_stray_output(self):
+        """测试杂散输出无法解码"""
+        assert decode_line("Downloading model...") is None
+        assert decode_line(FRAME_PREFIX + "not base64!") is None
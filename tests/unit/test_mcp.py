@@ -0,0 +1,83 @@
+"""
+MCP 工具模块单元测试
+"""
+
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.mcp import MAX_TOOLS, format_result, mcp_hint
+
+
+def _tool(name, **kwargs):
+    tool = {"server": "github", "name": name, "description": "", "input_schema": None}
+    tool.update(kwargs)
+    return tool
+
+
+class TestMcpHint:
+    """mcp_hint 测试"""
+
+    def test_no_tools(self):
+        """测试没有 MCP 工具时没有提示"""
+        assert mcp_hint(None) == ""
+        assert mcp_hint({"mcp_tools": []}) == ""
+
+    def test_lists_tools_with_params(self):
+        """测试提示中列出工具和参数"""
+        context = {"mcp_tools": [_tool(
+            "search_issues",
+            description="搜索 issue\n更多说明",
+            input_schema={
+                "type": "object",
+                "properties": {"query": {"type": "string", "description": "关键词"}, "limit": {"type": "integer"}},
+                "required": ["query"],
+            },
+        )]}
+
+        hint = mcp_hint(context)
+
+        assert "mcp_call" in hint
+        assert "- github / search_issues: 搜索 issue 参数:" in hint
+        assert "更多说明" not in hint
+        assert "string，关键词" in hint
+        assert "integer（可选）" in hint
+
+    def test_too_many_tools(self):
+        """测试工具过多时只列出前 MAX_TOOLS 个"""
+        context = {"mcp_tools": [_tool(f"t{i}") for i in range(MAX_TOOLS + 3)]}
+
+        hint = mcp_hint(context)
+
+        assert f"t{MAX_TOOLS - 1}:" in hint
+        assert f"t{MAX_TOOLS}:" not in hint
+        assert "另有 3 个工具" in hint
+
+
+class TestFormatResult:
+    """format_result 测试"""
+
+    def test_joins_text_content(self):
+        """测试合并文本内容"""
+        result = {"content": [
+            {"type": "text", "text": "第一段"},
+            {"type": "image", "data": "..."},
+            {"type": "resource", "resource": {"uri": "file:///a.txt", "text": "文件内容"}},
+        ]}
+
+        text, is_error = format_result(result)
+
+        assert text == "第一段\n[image 内容]\n文件内容"
+        assert is_error is False
+
+    def test_is_error(self):
+        """测试工具报告失败"""
+        text, is_error = format_result({"content": [{"type": "text", "text": "权限不足"}], "isError": True})
+
+        assert text == "权限不足"
+        assert is_error is True
+
+    def test_empty_result(self):
+        """测试空结果"""
+        assert format_result({}) == ("", False)
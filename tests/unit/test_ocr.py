@@ -0,0 +1,103 @@
+"""
+图片文字识别模块单元测试
+"""
+
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.ocr import group_lines, ocr_image, resolve_lang
+
+
+def make_data(words):
+    """按 (文字, 行号, left, top, width, height, conf) 构造 image_to_data 结果"""
+    data = {key: [] for key in ("text", "block_num", "par_num", "line_num", "left", "top", "width", "height", "conf")}
+    for text, line, left, top, width, height, conf in words:
+        data["text"].append(text)
+        data["block_num"].append(1)
+        data["par_num"].append(1)
+        data["line_num"].append(line)
+        data["left"].append(left)
+        data["top"].append(top)
+        data["width"].append(width)
+        data["height"].append(height)
+        data["conf"].append(conf)
+    return data
+
+
+class TestResolveLang:
+    """resolve_lang 测试"""
+
+    def test_aliases(self):
+        """测试语言代码映射为语言包名"""
+        assert resolve_lang("zh") == "chi_sim"
+        assert resolve_lang("zh+en") == "chi_sim+eng"
+        assert resolve_lang("jpn") == "jpn"
+
+    def test_default_without_chinese_pack(self):
+        """测试未安装中文语言包时默认只用英文"""
+        assert resolve_lang(None) == "chi_sim+eng"
+        assert resolve_lang(None, installed=["eng"]) == "eng"
+
+
+class TestGroupLines:
+    """group_lines 测试"""
+
+    def test_merges_words_by_line(self):
+        """测试同一行的词合并并取外接矩形"""
+        data = make_data([
+            ("名称", 1, 10, 20, 40, 12, 90),
+            ("金额", 1, 200, 18, 40, 14, 80),
+            ("Total", 2, 10, 50, 50, 12, 95),
+            ("100", 2, 70, 50, 30, 12, 85),
+        ])
+
+        blocks = group_lines(data)
+
+        assert [b["text"] for b in blocks] == ["名称金额", "Total 100"]
+        assert blocks[0]["x"] == 10 and blocks[0]["y"] == 18
+        assert blocks[0]["width"] == 230 and blocks[0]["height"] == 14
+        assert blocks[0]["confidence"] == 85.0
+
+    def test_skips_empty_and_structural_entries(self):
+        """测试跳过空白词和 conf 为 -1 的版面条目"""
+        data = make_data([("", 0, 0, 0, 500, 300, -1), ("  ", 1, 0, 0, 5, 5, 90), ("ok", 1, 5, 5, 10, 10, 90)])
+
+        assert [b["text"] for b in group_lines(data)] == ["ok"]
+
+
+class TestOcrImage:
+    """ocr_image 测试"""
+
+    def test_returns_text_and_blocks(self, tmp_path):
+        """测试返回全文和文本块"""
+        image = tmp_path / "shot.png"
+        image.write_bytes(b"PNG")
+        data = make_data([("第一行", 1, 0, 0, 10, 10, 90), ("第二行", 2, 0, 20, 10, 10, 90)])
+
+        outcome = ocr_image(str(image), lang="zh", recognizer=lambda p, lang: data)
+
+        assert outcome["ok"] is True
+        assert outcome["text"] == "第一行\n第二行"
+        assert len(outcome["blocks"]) == 2
+
+    def test_missing_image(self, tmp_path):
+        """测试图片不存在"""
+        outcome = ocr_image(str(tmp_path / "missing.png"), recognizer=lambda p, lang: {})
+
+        assert outcome["ok"] is False
+        assert "图片不存在" in outcome["message"]
+
+    def test_missing_dependency(self, tmp_path):
+        """测试未安装 OCR 依赖时提示安装"""
+        image = tmp_path / "shot.png"
+        image.write_bytes(b"PNG")
+
+        def missing(path, lang):
+            raise ImportError("pytesseract")
+
+        outcome = ocr_image(str(image), recognizer=missing)
+
+        assert outcome["ok"] is False
+        assert "pytesseract" in outcome["message"]
@@ -0,0 +1,65 @@
+"""
+断点续跑模块单元测试
+"""
+
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.resume import MAX_STEP_CHARS, apply_resume, resume_hint
+
+
+class TestApplyResume:
+    """apply_resume 测试"""
+
+    def test_first_attempt_unchanged(self):
+        """测试首次执行不修改 context"""
+        assert apply_resume(None, None, None) is None
+        assert apply_resume({"a": 1}, None, []) == {"a": 1}
+
+    def test_merge_resume(self):
+        """测试重试时并入 resume，不修改原 context"""
+        original = {"a": 1}
+        steps = [{"type": "file_write", "params": {"path": "a.txt"}}, "bad"]
+
+        context = apply_resume(original, 1, steps)
+
+        assert context["a"] == 1
+        assert context["resume"] == {
+            "from_step": 1,
+            "completed_steps": [{"type": "file_write", "params": {"path": "a.txt"}}],
+        }
+        assert "resume" not in original
+
+
+class TestResumeHint:
+    """resume_hint 测试"""
+
+    def test_no_resume(self):
+        """测试不是续跑时没有提示"""
+        assert resume_hint(None) == ""
+        assert resume_hint({"a": 1}) == ""
+
+    def test_lists_completed_steps(self):
+        """测试提示中列出已完成的步骤"""
+        context = apply_resume(None, 2, [
+            {"type": "download_file", "description": "下载报表"},
+            {"type": "file_write", "params": {"path": "a.txt"}},
+        ])
+
+        hint = resume_hint(context)
+
+        assert "前 2 个步骤" in hint
+        assert "1. download_file: 下载报表" in hint
+        assert "2. file_write:" in hint and "a.txt" in hint
+
+    def test_nothing_completed(self):
+        """测试没有完成任何步骤时要求重新规划"""
+        assert "完整的任务" in resume_hint(apply_resume(None, 0, []))
+
+    def test_long_step_truncated(self):
+        """测试过长的步骤定义被截断"""
+        context = apply_resume(None, 1, [{"type": "python_script", "description": "x" * 1000}])
+
+        assert "x" * (MAX_STEP_CHARS + 1) not in resume_hint(context)
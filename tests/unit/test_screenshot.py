@@ -0,0 +1,85 @@
+"""
+系统截图请求模块单元测试
+"""
+
+import json
+import threading
+import pytest
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.screenshot import (
+    ScreenshotError,
+    is_available,
+    request_screenshot,
+    set_requester,
+    wait_for_response,
+)
+
+
+def write_response(base_dir: Path, access_id: str, **fields) -> None:
+    (base_dir / f"{access_id}.json").write_text(json.dumps({"access_id": access_id, **fields}))
+
+
+class TestRequestScreenshot:
+    """request_screenshot 测试"""
+
+    def teardown_method(self):
+        set_requester(None)
+
+    def test_without_requester(self):
+        """测试未注册回调时不可用"""
+        assert not is_available()
+        with pytest.raises(ScreenshotError):
+            request_screenshot("full")
+
+    def test_invalid_mode(self):
+        """测试拒绝不支持的截图范围"""
+        set_requester(lambda mode: "/tmp/x.png")
+
+        with pytest.raises(ScreenshotError):
+            request_screenshot("webcam")
+
+    def test_forwards_mode(self):
+        """测试把截图范围交给回调并返回路径"""
+        modes = []
+        set_requester(lambda mode: modes.append(mode) or "/tmp/shot.png")
+
+        assert request_screenshot("region") == "/tmp/shot.png"
+        assert modes == ["region"]
+
+
+class TestWaitForResponse:
+    """wait_for_response 测试"""
+
+    def test_path(self, tmp_path):
+        """测试读取截图路径并删除结果文件"""
+        write_response(tmp_path, "shot_1", path="/tmp/shot.png")
+
+        assert wait_for_response(tmp_path, "shot_1") == "/tmp/shot.png"
+        assert not (tmp_path / "shot_1.json").exists()
+
+    def test_error(self, tmp_path):
+        """测试 Tauri 返回错误时抛出异常"""
+        write_response(tmp_path, "shot_1", error="已取消截图")
+
+        with pytest.raises(ScreenshotError, match="已取消截图"):
+            wait_for_response(tmp_path, "shot_1")
+
+    def test_waits_for_file(self, tmp_path):
+        """测试结果文件稍后写入"""
+        threading.Timer(0.05, write_response, args=(tmp_path, "shot_1"), kwargs={"path": "/a.png"}).start()
+
+        assert wait_for_response(tmp_path, "shot_1", timeout=2) == "/a.png"
+
+    def test_timeout(self, tmp_path):
+        """测试超时"""
+        with pytest.raises(ScreenshotError):
+            wait_for_response(tmp_path, "shot_1", timeout=0.1)
+
+    def test_cancelled(self, tmp_path):
+        """测试任务取消时停止等待"""
+        with pytest.raises(ScreenshotError, match="取消"):
+            wait_for_response(tmp_path, "shot_1", timeout=2, is_cancelled=lambda: True)
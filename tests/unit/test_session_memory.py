@@ -0,0 +1,82 @@
+"""
+会话记忆模块单元测试
+"""
+
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.session_memory import (
+    MAX_TURNS,
+    apply_session_context,
+    collect_files,
+    load_turns,
+    record_turn,
+)
+
+
+def make_result(message: str, path: str = None) -> dict:
+    steps = []
+    if path:
+        steps.append({"step": {"type": "file_write"}, "result": {"success": True, "data": {"path": path}}})
+    return {"success": True, "message": message, "steps": steps}
+
+
+class TestSessionMemory:
+    """record_turn / apply_session_context 测试"""
+
+    def test_empty_session(self, tmp_path):
+        """测试没有记忆的会话不修改 context"""
+        assert apply_session_context("session_1", None, tmp_path) == {}
+        assert apply_session_context("session_1", {"a": 1}, tmp_path) == {"a": 1}
+
+    def test_follow_up_context(self, tmp_path):
+        """测试追问时带上之前的对话和文件"""
+        record_turn("session_1", "生成报告", make_result("已生成 report.docx", "/tmp/report.docx"), tmp_path)
+
+        context = apply_session_context("session_1", {}, tmp_path)
+
+        assert context["chat_history"] == [
+            {"role": "user", "content": "生成报告"},
+            {"role": "assistant", "content": "已生成 report.docx"},
+        ]
+        assert context["last_created_file"] == "/tmp/report.docx"
+
+    def test_existing_fields_kept(self, tmp_path):
+        """测试前端已提供的字段不被覆盖"""
+        record_turn("session_1", "生成报告", make_result("完成", "/tmp/report.docx"), tmp_path)
+
+        context = apply_session_context("session_1", {"last_created_file": "/tmp/other.txt"}, tmp_path)
+
+        assert context["last_created_file"] == "/tmp/other.txt"
+        assert "chat_history" in context
+
+    def test_turns_trimmed(self, tmp_path):
+        """测试超出上限时丢弃最早的轮次"""
+        for i in range(MAX_TURNS + 3):
+            record_turn("session_1", f"指令 {i}", make_result("完成"), tmp_path)
+
+        turns = load_turns("session_1", tmp_path)
+
+        assert len(turns) == MAX_TURNS
+        assert turns[0]["instruction"] == "指令 3"
+
+    def test_invalid_session_id(self, tmp_path):
+        """测试拒绝包含路径分隔符的会话 ID"""
+        record_turn("../escape", "指令", make_result("完成"), tmp_path)
+
+        assert not (tmp_path.parent / "escape.json").exists()
+        assert load_turns("../escape", tmp_path) == []
+
+    def test_collect_files(self):
+        """测试收集步骤中的文件路径"""
+        result = {
+            "steps": [
+                {"result": {"data": {"path": "/a.txt"}}},
+                {"result": {"data": {"created_files": ["/b.txt", "/a.txt"]}}},
+                {"result": None},
+            ]
+        }
+
+        assert collect_files(result) == ["/a.txt", "/b.txt"]
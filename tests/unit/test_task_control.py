@@ -0,0 +1,137 @@
+"""
+任务控制模块单元测试
+"""
+
+import json
+import threading
+import pytest
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.task_control import clear, read_command, wait_for_next_step, wait_while_paused
+
+
+def write_command(base_dir: Path, request_id: str, cmd: str) -> None:
+    (base_dir / f"{request_id}.json").write_text(json.dumps({"cmd": cmd, "id": request_id}))
+
+
+class TestTaskControl:
+    """read_command / wait_while_paused 测试"""
+
+    def test_no_command(self, tmp_path):
+        """测试没有控制文件时不阻塞"""
+        events = []
+
+        stopped = wait_while_paused("task_1", lambda: events.append("paused"), lambda: events.append("resumed"), tmp_path)
+
+        assert not stopped
+        assert events == []
+
+    def test_stop_command(self, tmp_path):
+        """测试停止命令"""
+        write_command(tmp_path, "task_1", "stop")
+
+        assert wait_while_paused("task_1", lambda: None, lambda: None, tmp_path)
+
+    def test_pause_then_resume(self, tmp_path):
+        """测试暂停后恢复"""
+        write_command(tmp_path, "task_1", "pause")
+        events = []
+
+        def on_paused():
+            events.append("paused")
+            threading.Timer(0.05, write_command, args=(tmp_path, "task_1", "resume")).start()
+
+        stopped = wait_while_paused(
+            "task_1", on_paused, lambda: events.append("resumed"), tmp_path, poll_interval=0.01
+        )
+
+        assert not stopped
+        assert events == ["paused", "resumed"]
+
+    def test_stop_while_paused(self, tmp_path):
+        """测试暂停期间停止"""
+        write_command(tmp_path, "task_1", "pause")
+        events = []
+
+        def on_paused():
+            events.append("paused")
+            threading.Timer(0.05, write_command, args=(tmp_path, "task_1", "stop")).start()
+
+        stopped = wait_while_paused(
+            "task_1", on_paused, lambda: events.append("resumed"), tmp_path, poll_interval=0.01
+        )
+
+        assert stopped
+        assert events == ["paused"]
+
+    def test_invalid_request_id(self, tmp_path):
+        """测试拒绝包含路径分隔符的任务 ID"""
+        assert read_command("../task", tmp_path) is None
+
+    def test_clear(self, tmp_path):
+        """测试任务结束后删除控制文件"""
+        write_command(tmp_path, "task_1", "pause")
+
+        clear("task_1", tmp_path)
+
+        assert read_command("task_1", tmp_path) is None
+
+
+class TestStepGate:
+    """wait_for_next_step 测试"""
+
+    def test_continue(self, tmp_path):
+        """测试等待后继续"""
+        events = []
+
+        def on_waiting():
+            events.append("waiting")
+            threading.Timer(0.05, write_command, args=(tmp_path, "task_1", "resume")).start()
+
+        aborted = wait_for_next_step(
+            "task_1", on_waiting, lambda: events.append("continued"), tmp_path, poll_interval=0.01
+        )
+
+        assert not aborted
+        assert events == ["waiting", "continued"]
+
+    def test_abort(self, tmp_path):
+        """测试等待期间中止"""
+        events = []
+
+        def on_waiting():
+            events.append("waiting")
+            threading.Timer(0.05, write_command, args=(tmp_path, "task_1", "stop")).start()
+
+        aborted = wait_for_next_step(
+            "task_1", on_waiting, lambda: events.append("continued"), tmp_path, poll_interval=0.01
+        )
+
+        assert aborted
+        assert events == ["waiting"]
+
+    def test_stop_before_gate(self, tmp_path):
+        """测试已写入停止命令时不再等待"""
+        write_command(tmp_path, "task_1", "stop")
+        events = []
+
+        assert wait_for_next_step("task_1", lambda: events.append("waiting"), lambda: None, tmp_path)
+        assert events == []
+
+    def test_stale_resume_ignored(self, tmp_path):
+        """测试之前留下的 resume 命令不会直接放行"""
+        write_command(tmp_path, "task_1", "resume")
+        events = []
+
+        def on_waiting():
+            events.append("waiting")
+            assert read_command("task_1", tmp_path) is None
+            threading.Timer(0.05, write_command, args=(tmp_path, "task_1", "resume")).start()
+
+        aborted = wait_for_next_step("task_1", on_waiting, lambda: None, tmp_path, poll_interval=0.01)
+
+        assert not aborted
+        assert events == ["waiting"]
@@ -0,0 +1,129 @@
+"""
+内置工具调用模块单元测试
+"""
+
+import json
+import threading
+import pytest
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.tool_server import (
+    ToolCallError,
+    call_tool,
+    is_available,
+    set_caller,
+    wait_for_result,
+)
+
+
+def write_result(base_dir: Path, call_id: str, **fields) -> None:
+    (base_dir / f"{call_id}.json").write_text(json.dumps({"type": "tool_result", "call_id": call_id, **fields}))
+
+
+class TestCallTool:
+    """call_tool 测试"""
+
+    def teardown_method(self):
+        set_caller(None)
+
+    def test_without_caller(self):
+        """测试未注册回调时不可用"""
+        assert not is_available()
+        with pytest.raises(ToolCallError):
+            call_tool("shell", {"cmd": "ls"})
+
+    def test_unknown_tool(self):
+        """测试拒绝未知的工具"""
+        set_caller(lambda name, args: {})
+
+        with pytest.raises(ToolCallError):
+            call_tool("rm", {})
+
+    def test_forwards_call(self):
+        """测试把工具名和参数交给回调并返回输出"""
+        calls = []
+        set_caller(lambda name, args: calls.append((name, args)) or {"exit_code": 0, "stdout": "a\n"})
+
+        assert call_tool("shell", {"cmd": "ls"})["stdout"] == "a\n"
+        assert calls == [("shell", {"cmd": "ls"})]
+
+    def test_fs_tools(self):
+        """测试文件工具与 shell 走同一通道"""
+        calls = []
+        set_caller(lambda name, args: calls.append(name) or {"encoding": "utf-8", "content": "hi"})
+
+        assert call_tool("fs.read", {"path": "a.txt"})["content"] == "hi"
+        call_tool("fs.delete", {"path": "a.txt"})
+        assert calls == ["fs.read", "fs.delete"]
+        with pytest.raises(ToolCallError):
+            call_tool("fs.chmod", {"path": "a.txt"})
+
+    def test_system_info(self):
+        """测试系统信息工具与 shell 走同一通道"""
+        calls = []
+        set_caller(lambda name, args: calls.append((name, args)) or {"battery": None})
+
+        assert call_tool("system.info", {"sections": ["battery"]}) == {"battery": None}
+        assert calls == [("system.info", {"sections": ["battery"]})]
+
+    def test_email_fetch(self):
+        """测试读取邮件工具已注册，筛选条件原样转发"""
+        calls = []
+        set_caller(lambda name, args: calls.append((name, args)) or {"folder": "INBOX", "total": 0, "emails": []})
+
+        args = {"unseen_only": True, "since_days": 0, "include_body": True}
+        assert call_tool("email.fetch", args)["emails"] == []
+        assert calls == [("email.fetch", args)]
+
+    def test_calendar(self):
+        """测试日程工具已注册"""
+        calls = []
+        set_caller(lambda name, args: calls.append(name) or {"events": [], "errors": []})
+
+        call_tool("calendar.list", {"days": 1})
+        call_tool("calendar.create", {"title": "会议", "start": "2026-03-01 15:00"})
+        assert calls == ["calendar.list", "calendar.create"]
+
+
+class TestWaitForResult:
+    """wait_for_result 测试"""
+
+    def test_output(self, tmp_path):
+        """测试读取输出并删除结果文件"""
+        write_result(tmp_path, "tool_1", ok=True, output={"exit_code": 0})
+
+        assert wait_for_result(tmp_path, "tool_1") == {"exit_code": 0}
+        assert not (tmp_path / "tool_1.json").exists()
+
+    def test_error(self, tmp_path):
+        """测试被拒绝时抛出异常"""
+        write_result(tmp_path, "tool_1", ok=False, error="程序不在允许列表中: rm")
+
+        with pytest.raises(ToolCallError, match="rm"):
+            wait_for_result(tmp_path, "tool_1")
+
+    def test_waits_for_file(self, tmp_path):
+        """测试结果文件稍后写入"""
+        threading.Timer(0.05, write_result, args=(tmp_path, "tool_1"), kwargs={"ok": True, "output": {"stdout": "x"}}).start()
+
+        assert wait_for_result(tmp_path, "tool_1", timeout=2) == {"stdout": "x"}
+
+    def test_timeout(self, tmp_path):
+        """测试超时"""
+        with pytest.raises(ToolCallError):
+            wait_for_result(tmp_path, "tool_1", timeout=0.1)
+
+    def test_cancelled(self, tmp_path):
+        """测试任务取消后等待片刻仍无结果时停止等待"""
+        with pytest.raises(ToolCallError, match="取消"):
+            wait_for_result(tmp_path, "tool_1", timeout=2, is_cancelled=lambda: True, cancel_grace=0.1)
+
+    def test_result_after_cancel(self, tmp_path):
+        """测试取消后 Rust 写回的结果仍被读取"""
+        threading.Timer(0.05, write_result, args=(tmp_path, "tool_1"), kwargs={"ok": True, "output": {"exit_code": None}}).start()
+
+        assert wait_for_result(tmp_path, "tool_1", timeout=2, is_cancelled=lambda: True) == {"exit_code": None}
+
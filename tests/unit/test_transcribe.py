@@ -0,0 +1,58 @@
+"""
+语音转写模块单元测试
+"""
+
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.transcribe import transcribe_file
+
+
+class TestTranscribeFile:
+    """transcribe_file 测试"""
+
+    def test_returns_trimmed_text(self, tmp_path):
+        """测试返回去掉首尾空白的文字"""
+        audio = tmp_path / "voice.wav"
+        audio.write_bytes(b"RIFF")
+        calls = []
+
+        def fake(path, language):
+            calls.append((path, language))
+            return "  打开下载文件夹 \n"
+
+        outcome = transcribe_file(str(audio), language="zh", transcriber=fake)
+
+        assert outcome == {"ok": True, "text": "打开下载文件夹"}
+        assert calls == [(str(audio), "zh")]
+
+    def test_auto_language(self, tmp_path):
+        """测试 auto 语言按自动识别处理"""
+        audio = tmp_path / "voice.wav"
+        audio.write_bytes(b"RIFF")
+        languages = []
+
+        transcribe_file(str(audio), language="auto", transcriber=lambda p, lang: languages.append(lang) or "")
+
+        assert languages == [None]
+
+    def test_missing_file(self, tmp_path):
+        """测试音频文件不存在"""
+        outcome = transcribe_file(str(tmp_path / "missing.wav"), transcriber=lambda p, lang: "x")
+
+        assert outcome["ok"] is False
+        assert "音频文件不存在" in outcome["message"]
+
+    def test_transcriber_error(self, tmp_path):
+        """测试转写失败时返回错误信息"""
+        audio = tmp_path / "voice.wav"
+        audio.write_bytes(b"RIFF")
+
+        def broken(path, language):
+            raise RuntimeError("未安装语音识别模型")
+
+        outcome = transcribe_file(str(audio), transcriber=broken)
+
+        assert outcome == {"ok": False, "text": "", "message": "未安装语音识别模型"}
@@ -0,0 +1,157 @@
+"""
+用量上报模块单元测试
+"""
+
+import pytest
+from pathlib import Path
+from types import SimpleNamespace
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.usage import (
+    extract_text,
+    extract_usage,
+    report_prompt,
+    report_usage,
+    set_call_reporter,
+    set_prompt_reporter,
+    set_reporter,
+    track_call,
+)
+
+
+class TestExtractUsage:
+    """extract_usage 测试"""
+
+    def test_anthropic_usage(self):
+        """测试 Anthropic 响应"""
+        response = SimpleNamespace(usage=SimpleNamespace(input_tokens=120, output_tokens=30))
+
+        assert extract_usage(response) == {"input_tokens": 120, "output_tokens": 30}
+
+    def test_openai_usage(self):
+        """测试 OpenAI 兼容响应"""
+        response = SimpleNamespace(usage=SimpleNamespace(prompt_tokens=80, completion_tokens=20))
+
+        assert extract_usage(response) == {"input_tokens": 80, "output_tokens": 20}
+
+    def test_missing_usage(self):
+        """测试响应不含用量"""
+        assert extract_usage(SimpleNamespace()) is None
+        assert extract_usage(SimpleNamespace(usage=None)) is None
+
+
+class TestReportUsage:
+    """report_usage 测试"""
+
+    @pytest.fixture(autouse=True)
+    def clear_reporter(self):
+        yield
+        set_reporter(None)
+
+    def test_report_to_reporter(self):
+        """测试注册回调后上报"""
+        events = []
+        set_reporter(events.append)
+        response = SimpleNamespace(usage=SimpleNamespace(input_tokens=10, output_tokens=5))
+
+        report_usage("claude-3-5-sonnet", response)
+
+        assert events == [{"model": "claude-3-5-sonnet", "input_tokens": 10, "output_tokens": 5}]
+
+    def test_report_without_reporter(self):
+        """测试未注册回调时为空操作"""
+        response = SimpleNamespace(usage=SimpleNamespace(input_tokens=10, output_tokens=5))
+
+        report_usage("gpt-4o", response)
+
+    def test_reporter_error_is_swallowed(self):
+        """测试回调异常不影响调用方"""
+        def broken(_event):
+            raise RuntimeError("boom")
+
+        set_reporter(broken)
+        response = SimpleNamespace(usage=SimpleNamespace(input_tokens=1, output_tokens=1))
+
+        report_usage("gpt-4o", response)
+
+
+class TestReportPrompt:
+    """extract_text / report_prompt 测试"""
+
+    @pytest.fixture(autouse=True)
+    def clear_reporter(self):
+        yield
+        set_prompt_reporter(None)
+
+    def test_extract_anthropic_text(self):
+        """测试 Anthropic 响应文本"""
+        response = SimpleNamespace(content=[SimpleNamespace(text="[{"), SimpleNamespace(text="}]")])
+
+        assert extract_text(response) == "[{}]"
+
+    def test_extract_openai_text(self):
+        """测试 OpenAI 兼容响应文本"""
+        response = SimpleNamespace(choices=[SimpleNamespace(message=SimpleNamespace(content="ok"))])
+
+        assert extract_text(response) == "ok"
+
+    def test_extract_missing_text(self):
+        """测试响应不含文本"""
+        assert extract_text(SimpleNamespace()) is None
+
+    def test_report_to_reporter(self):
+        """测试注册回调后上报提示词与响应"""
+        events = []
+        set_prompt_reporter(events.append)
+        messages = [{"role": "user", "content": "规划任务"}]
+        response = SimpleNamespace(content=[SimpleNamespace(text="[]")])
+
+        report_prompt("claude-3-5-sonnet", messages, response)
+
+        assert events == [{"model": "claude-3-5-sonnet", "messages": messages, "response": "[]"}]
+
+    def test_report_without_reporter(self):
+        """测试任务关闭提示词日志（未注册回调）时为空操作"""
+        report_prompt("gpt-4o", [{"role": "user", "content": "x"}], SimpleNamespace())
+
+
+class TestTrackCall:
+    """track_call 测试"""
+
+    @pytest.fixture(autouse=True)
+    def clear_reporter(self):
+        yield
+        set_call_reporter(None)
+
+    def test_report_success(self):
+        """测试成功调用上报耗时"""
+        events = []
+        set_call_reporter(events.append)
+
+        with track_call("claude-3-5-sonnet"):
+            pass
+
+        assert len(events) == 1
+        assert events[0]["model"] == "claude-3-5-sonnet"
+        assert events[0]["ok"] is True
+        assert events[0]["latency_ms"] >= 0
+        assert "error" not in events[0]
+
+    def test_report_failure_and_reraise(self):
+        """测试调用失败时上报原因并原样抛出"""
+        events = []
+        set_call_reporter(events.append)
+
+        with pytest.raises(RuntimeError):
+            with track_call("gpt-4o"):
+                raise RuntimeError("overloaded")
+
+        assert events[0]["ok"] is False
+        assert events[0]["error"] == "RuntimeError: overloaded"
+
+    def test_without_reporter(self):
+        """测试未注册回调时为空操作"""
+        with track_call("gpt-4o"):
+            pass
@@ -0,0 +1,44 @@
+"""
+预热模块单元测试
+"""
+
+from pathlib import Path
+import sys
+
+sys.path.insert(0, str(Path(__file__).parent.parent.parent))
+
+from agent.tools.warmup import run_warmup
+
+
+class TestRunWarmup:
+    """run_warmup 测试"""
+
+    def test_all_steps_run(self):
+        """测试依次执行全部步骤"""
+        loaded = []
+
+        report = run_warmup([
+            ("embedding_model", lambda: loaded.append("embedding_model")),
+            ("memory", lambda: loaded.append("memory")),
+        ])
+
+        assert loaded == ["embedding_model", "memory"]
+        assert [s["name"] for s in report["steps"]] == ["embedding_model", "memory"]
+        assert all(s["ok"] for s in report["steps"])
+        assert report["elapsed_ms"] >= 0
+
+    def test_failure_does_not_stop_others(self):
+        """测试单项失败不影响其他项"""
+        def broken():
+            raise TimeoutError("加载超时")
+
+        report = run_warmup([("embedding_model", broken), ("memory", lambda: None)])
+
+        assert report["steps"][0]["ok"] is False
+        assert report["steps"][0]["error"] == "TimeoutError: 加载超时"
+        assert report["steps"][1]["ok"] is True
+        assert "error" not in report["steps"][1]
+
+    def test_empty_steps(self):
+        """测试没有预热步骤"""
+        assert run_warmup([])["steps"] == []